@@ -67,7 +67,7 @@ fn main() {
         )));
 
         // Create a render target (a combination of a color and a depth texture) to write into
-        let pixels = RenderTarget::new(
+        let image = RenderTarget::new(
             texture.as_color_target(None),
             depth_texture.as_depth_target(),
         )
@@ -75,21 +75,16 @@ fn main() {
         .clear(ClearState::color_and_depth(0.8, 0.8, 0.8, 1.0, 1.0))
         // Render the triangle with the per vertex colors defined at construction
         .render(&camera, &model, &[])
-        // Read out the colors from the render target
-        .read_color();
+        // Read out the colors from the render target as an image
+        .read_color_to_image();
 
         // Save the rendered image
         use three_d_asset::io::Serialize;
 
         three_d_asset::io::save(
-            &CpuTexture {
-                data: TextureData::RgbaU8(pixels),
-                width: texture.width(),
-                height: texture.height(),
-                ..Default::default()
-            }
-            .serialize(format!("headless-{}.png", frame_index))
-            .unwrap(),
+            &image
+                .serialize(format!("headless-{}.png", frame_index))
+                .unwrap(),
         )
         .unwrap();
     }
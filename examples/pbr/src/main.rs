@@ -143,6 +143,15 @@ pub async fn run() {
                         NormalDistributionFunction::TrowbridgeReitzGGX,
                         GeometryFunction::SmithSchlickGGX,
                     ),
+                    dissolve: None,
+                    uv_transformation: model.material.uv_transformation,
+                    occlusion_uv_channel: model.material.occlusion_uv_channel,
+                    emissive_uv_channel: model.material.emissive_uv_channel,
+                    detail: model.material.detail.clone(),
+                    displacement: model.material.displacement.clone(),
+                    light_map_texture: model.material.light_map_texture.clone(),
+                    light_map_uv_channel: model.material.light_map_uv_channel,
+                    light_map_intensity: model.material.light_map_intensity,
                 };
                 model.render_with_material(&material, &camera, &[&light]);
                 gui.render();
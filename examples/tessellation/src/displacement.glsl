@@ -0,0 +1,11 @@
+uniform float displacementScale;
+
+float height(vec2 p) {
+    return sin(0.7 * p.x) * cos(0.6 * p.y)
+        + 0.25 * sin(2.3 * p.x + 1.7 * p.y)
+        + 0.1 * sin(7.1 * p.x) * sin(6.3 * p.y);
+}
+
+vec3 displace(vec3 p) {
+    return p + vec3(0.0, displacementScale * height(p.xz), 0.0);
+}
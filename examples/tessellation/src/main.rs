@@ -0,0 +1,84 @@
+use three_d::*;
+
+pub fn main() {
+    // Create a window (a canvas on web)
+    let window = Window::new(WindowSettings {
+        title: "Tessellation!".to_string(),
+        #[cfg(not(target_arch = "wasm32"))]
+        max_size: Some((1280, 720)),
+        ..Default::default()
+    })
+    .unwrap();
+
+    // Get the graphics context from the window
+    let context: Context = window.gl();
+
+    // Define a coarse grid of triangles which is refined on the GPU if tessellation is supported
+    let size = 32;
+    let mut positions = Vec::new();
+    for i in 0..size {
+        for j in 0..size {
+            let x0 = i as f32 - 0.5 * size as f32;
+            let z0 = j as f32 - 0.5 * size as f32;
+            let (x1, z1) = (x0 + 1.0, z0 + 1.0);
+            positions.extend_from_slice(&[
+                vec3(x0, 0.0, z0),
+                vec3(x0, 0.0, z1),
+                vec3(x1, 0.0, z1),
+                vec3(x0, 0.0, z0),
+                vec3(x1, 0.0, z1),
+                vec3(x1, 0.0, z0),
+            ]);
+        }
+    }
+    let positions = VertexBuffer::new_with_data(&context, &positions);
+
+    // Create a program with tessellation stages, the program falls back to only displacing the vertices of the grid if tessellation is not supported
+    let displacement = include_str!("displacement.glsl");
+    let program = Program::from_source_with_tessellation(
+        &context,
+        &format!("{}{}", displacement, include_str!("terrain.vert")),
+        include_str!("terrain.tesc"),
+        &format!("{}{}", displacement, include_str!("terrain.tese")),
+        include_str!("terrain.frag"),
+    )
+    .unwrap();
+    if !program.uses_tessellation() {
+        println!("Tessellation is not supported, rendering without tessellation");
+    }
+
+    let mut camera = Camera::new_perspective(
+        window.viewport(),
+        vec3(12.0, 8.0, 12.0),
+        vec3(0.0, 0.0, 0.0),
+        vec3(0.0, 1.0, 0.0),
+        degrees(45.0),
+        0.1,
+        100.0,
+    );
+    let mut control = OrbitControl::new(*camera.target(), 1.0, 50.0);
+
+    window.render_loop(move |mut frame_input| {
+        camera.set_viewport(frame_input.viewport);
+        control.handle_events(&mut camera, &mut frame_input.events);
+
+        frame_input
+            .screen()
+            .clear(ClearState::color_and_depth(0.8, 0.8, 0.8, 1.0, 1.0))
+            .write(|| {
+                program.use_uniform("viewProjection", camera.projection() * camera.view());
+                program.use_uniform_if_required("cameraPosition", *camera.position());
+                program.use_uniform_if_required("tessellationFactor", 32.0f32);
+                program.use_uniform("displacementScale", 2.0f32);
+                program.use_uniform("lightDirection", vec3(-1.0, -1.0, -0.5).normalize());
+                program.use_vertex_attribute("position", &positions);
+                program.draw_arrays(
+                    RenderStates::default(),
+                    frame_input.viewport,
+                    positions.vertex_count(),
+                );
+            });
+
+        FrameOutput::default()
+    });
+}
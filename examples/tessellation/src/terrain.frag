@@ -0,0 +1,15 @@
+uniform vec3 lightDirection;
+
+in vec3 pos;
+out vec4 fragColor;
+
+void main() {
+    vec3 normal = normalize(cross(dFdx(pos), dFdy(pos)));
+    if (normal.y < 0.0) {
+        normal = -normal;
+    }
+    float diffuse = max(dot(normal, -lightDirection), 0.0);
+    vec3 color = mix(vec3(0.2, 0.45, 0.15), vec3(0.55, 0.5, 0.45), smoothstep(0.5, 2.0, pos.y));
+    fragColor = vec4(color * (0.2 + 0.8 * diffuse), 1.0);
+    fragColor.rgb = pow(fragColor.rgb, vec3(1.0 / 2.2));
+}
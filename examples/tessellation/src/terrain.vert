@@ -0,0 +1,22 @@
+uniform mat4 viewProjection;
+in vec3 position;
+
+#ifdef USE_TESSELLATION
+
+out vec3 vPosition;
+
+void main() {
+    // The displacement is applied in the tessellation evaluation shader
+    vPosition = position;
+}
+
+#else
+
+out vec3 pos;
+
+void main() {
+    pos = displace(position);
+    gl_Position = viewProjection * vec4(pos, 1.0);
+}
+
+#endif
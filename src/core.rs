@@ -32,6 +32,30 @@ mod scissor_box;
 #[doc(inline)]
 pub use scissor_box::*;
 
+mod memory;
+#[doc(inline)]
+pub use memory::*;
+
+mod upload_queue;
+#[doc(inline)]
+pub use upload_queue::*;
+
+mod frame_recording;
+#[doc(inline)]
+pub use frame_recording::*;
+
+mod gpu_timer;
+#[doc(inline)]
+pub use gpu_timer::*;
+
+mod skeleton;
+#[doc(inline)]
+pub use skeleton::*;
+
+mod bvh;
+#[doc(inline)]
+pub use bvh::*;
+
 pub mod prelude {
 
     //!
@@ -59,6 +83,22 @@ pub enum CoreError {
     ShaderCompilation(String, String, String),
     #[error("failed to link shader program: {0}")]
     ShaderLink(String),
+    #[error("{0} shaders are not supported on this device")]
+    UnsupportedShaderStage(String),
+    #[error("the uniform {0} is sent to the shader but not defined or never used")]
+    MissingUniform(String),
+    #[error("the attribute {0} is sent to the shader but not defined or never used")]
+    MissingAttribute(String),
+    #[error("the uniform {0} is of type {1} in the shader but is sent {2}")]
+    UniformTypeMismatch(String, String, String),
+    #[error("the uniform array {0} has length {1} in the shader but is sent {2} elements")]
+    UniformArrayLength(String, usize, usize),
+    #[error("the attribute {0} is of type {1} in the shader but is sent {2}")]
+    AttributeTypeMismatch(String, String, String),
+    #[error("the shader program uses {0} textures but only {1} texture units are available")]
+    TooManyTextures(usize, usize),
+    #[error("failed to read frame recording: {0}")]
+    InvalidRecording(String),
 }
 
 pub(crate) fn full_screen_draw(
@@ -104,6 +144,14 @@ fn to_byte_slice<T: DataType>(data: &[T]) -> &[u8] {
     }
 }
 
+fn byte_size_of_data_type(data_type: u32) -> usize {
+    match data_type {
+        crate::context::UNSIGNED_BYTE | crate::context::BYTE => 1,
+        crate::context::UNSIGNED_SHORT | crate::context::SHORT | crate::context::HALF_FLOAT => 2,
+        _ => 4,
+    }
+}
+
 fn from_byte_slice<T: DataType>(data: &[u8]) -> &[T] {
     unsafe {
         let (_prefix, values, _suffix) = data.align_to::<T>();
@@ -49,13 +49,15 @@ struct Buffer {
     data_type: u32,
     data_size: u32,
     normalized: bool,
+    allocation: TrackedAllocation,
 }
 
 impl Buffer {
-    pub fn new(context: &Context) -> Self {
+    pub fn new(context: &Context, resource_type: GpuResourceType) -> Self {
         Self {
             context: context.clone(),
             id: unsafe { context.create_buffer().expect("Failed creating buffer") },
+            allocation: TrackedAllocation::new(context, resource_type, 0),
             attribute_count: 0,
             data_type: 0,
             data_size: 0,
@@ -63,8 +65,12 @@ impl Buffer {
         }
     }
 
-    pub fn new_with_data<T: BufferDataType>(context: &Context, data: &[T]) -> Self {
-        let mut buffer = Self::new(context);
+    pub fn new_with_data<T: BufferDataType>(
+        context: &Context,
+        resource_type: GpuResourceType,
+        data: &[T],
+    ) -> Self {
+        let mut buffer = Self::new(context, resource_type);
         if !data.is_empty() {
             buffer.fill(data);
         }
@@ -85,6 +91,7 @@ impl Buffer {
             );
             self.context.bind_buffer(crate::context::ARRAY_BUFFER, None);
         }
+        self.allocation.set_byte_size(std::mem::size_of_val(data));
         self.attribute_count = data.len() as u32;
         self.data_type = T::data_type();
         self.data_size = T::size();
@@ -95,6 +102,36 @@ impl Buffer {
         self.attribute_count
     }
 
+    pub fn read_back(&self) -> Vec<u8> {
+        let mut data = vec![
+            0u8;
+            (self.attribute_count * self.data_size) as usize
+                * byte_size_of_data_type(self.data_type)
+        ];
+        unsafe {
+            self.context
+                .bind_buffer(crate::context::COPY_READ_BUFFER, Some(self.id));
+            self.context
+                .get_buffer_sub_data(crate::context::COPY_READ_BUFFER, 0, &mut data);
+            self.context
+                .bind_buffer(crate::context::COPY_READ_BUFFER, None);
+        }
+        data
+    }
+
+    pub fn duplicate(&self, resource_type: GpuResourceType) -> Self {
+        let mut buffer = Self::new(&self.context, resource_type);
+        let byte_size = (self.attribute_count * self.data_size) as usize
+            * byte_size_of_data_type(self.data_type);
+        copy_buffer_data(&self.context, self.id, buffer.id, byte_size);
+        buffer.allocation.set_byte_size(byte_size);
+        buffer.attribute_count = self.attribute_count;
+        buffer.data_type = self.data_type;
+        buffer.data_size = self.data_size;
+        buffer.normalized = self.normalized;
+        buffer
+    }
+
     pub fn bind(&self) {
         unsafe {
             self.context
@@ -103,6 +140,35 @@ impl Buffer {
     }
 }
 
+// Copies the given number of bytes from the source buffer to the destination buffer on the GPU, replacing the data in the destination buffer
+fn copy_buffer_data(
+    context: &Context,
+    source: crate::context::Buffer,
+    destination: crate::context::Buffer,
+    byte_size: usize,
+) {
+    unsafe {
+        context.bind_buffer(crate::context::COPY_READ_BUFFER, Some(source));
+        context.bind_buffer(crate::context::COPY_WRITE_BUFFER, Some(destination));
+        context.buffer_data_size(
+            crate::context::COPY_WRITE_BUFFER,
+            byte_size as i32,
+            crate::context::STATIC_DRAW,
+        );
+        if byte_size > 0 {
+            context.copy_buffer_sub_data(
+                crate::context::COPY_READ_BUFFER,
+                crate::context::COPY_WRITE_BUFFER,
+                0,
+                0,
+                byte_size as i32,
+            );
+        }
+        context.bind_buffer(crate::context::COPY_READ_BUFFER, None);
+        context.bind_buffer(crate::context::COPY_WRITE_BUFFER, None);
+    }
+}
+
 impl Drop for Buffer {
     fn drop(&mut self) {
         unsafe {
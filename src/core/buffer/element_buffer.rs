@@ -33,6 +33,7 @@ pub struct ElementBuffer {
     id: crate::context::Buffer,
     count: usize,
     data_type: u32,
+    allocation: TrackedAllocation,
 }
 
 impl ElementBuffer {
@@ -46,6 +47,7 @@ impl ElementBuffer {
             id,
             count: 0,
             data_type: 0,
+            allocation: TrackedAllocation::new(context, GpuResourceType::ElementBuffer, 0),
         }
     }
 
@@ -74,6 +76,7 @@ impl ElementBuffer {
             self.context
                 .bind_buffer(crate::context::ELEMENT_ARRAY_BUFFER, None);
         }
+        self.allocation.set_byte_size(std::mem::size_of_val(data));
         self.count = data.len();
         self.data_type = T::data_type();
     }
@@ -92,6 +95,19 @@ impl ElementBuffer {
         self.count / 3
     }
 
+    ///
+    /// Returns a new element buffer containing a copy of the indices in this buffer.
+    ///
+    pub(crate) fn duplicate(&self) -> Self {
+        let mut buffer = Self::new(&self.context);
+        let byte_size = self.count * byte_size_of_data_type(self.data_type);
+        super::copy_buffer_data(&self.context, self.id, buffer.id, byte_size);
+        buffer.allocation.set_byte_size(byte_size);
+        buffer.count = self.count;
+        buffer.data_type = self.data_type;
+        buffer
+    }
+
     pub(crate) fn bind(&self) {
         unsafe {
             self.context
@@ -102,6 +118,19 @@ impl ElementBuffer {
     pub(crate) fn data_type(&self) -> u32 {
         self.data_type
     }
+
+    pub(in crate::core) fn read_back(&self) -> Vec<u8> {
+        let mut data = vec![0u8; self.count * byte_size_of_data_type(self.data_type)];
+        unsafe {
+            self.context
+                .bind_buffer(crate::context::COPY_READ_BUFFER, Some(self.id));
+            self.context
+                .get_buffer_sub_data(crate::context::COPY_READ_BUFFER, 0, &mut data);
+            self.context
+                .bind_buffer(crate::context::COPY_READ_BUFFER, None);
+        }
+        data
+    }
 }
 
 impl Drop for ElementBuffer {
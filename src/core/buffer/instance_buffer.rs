@@ -15,7 +15,7 @@ impl InstanceBuffer {
     ///
     pub fn new(context: &Context) -> Self {
         Self {
-            buffer: Buffer::new(context),
+            buffer: Buffer::new(context, GpuResourceType::InstanceBuffer),
         }
     }
 
@@ -25,7 +25,7 @@ impl InstanceBuffer {
     ///
     pub fn new_with_data<T: BufferDataType>(context: &Context, data: &[T]) -> Self {
         Self {
-            buffer: Buffer::new_with_data(context, data),
+            buffer: Buffer::new_with_data(context, GpuResourceType::InstanceBuffer, data),
         }
     }
 
@@ -66,4 +66,8 @@ impl InstanceBuffer {
     pub(in crate::core) fn normalized(&self) -> bool {
         self.buffer.normalized
     }
+
+    pub(in crate::core) fn read_back(&self) -> Vec<u8> {
+        self.buffer.read_back()
+    }
 }
@@ -9,6 +9,7 @@ pub struct UniformBuffer {
     id: crate::context::Buffer,
     offsets: Vec<usize>,
     data: Vec<f32>,
+    _allocation: TrackedAllocation,
 }
 
 impl UniformBuffer {
@@ -32,6 +33,11 @@ impl UniformBuffer {
             id,
             offsets,
             data: vec![0.0; length],
+            _allocation: TrackedAllocation::new(
+                context,
+                GpuResourceType::UniformBuffer,
+                length * std::mem::size_of::<f32>(),
+            ),
         };
         buffer.send();
         buffer
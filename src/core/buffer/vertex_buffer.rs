@@ -15,7 +15,7 @@ impl VertexBuffer {
     ///
     pub fn new(context: &Context) -> Self {
         Self {
-            buffer: Buffer::new(context),
+            buffer: Buffer::new(context, GpuResourceType::VertexBuffer),
         }
     }
 
@@ -25,7 +25,7 @@ impl VertexBuffer {
     ///
     pub fn new_with_data<T: BufferDataType>(context: &Context, data: &[T]) -> Self {
         Self {
-            buffer: Buffer::new_with_data(context, data),
+            buffer: Buffer::new_with_data(context, GpuResourceType::VertexBuffer, data),
         }
     }
 
@@ -51,6 +51,15 @@ impl VertexBuffer {
         self.buffer.attribute_count()
     }
 
+    ///
+    /// Returns a new vertex buffer containing a copy of the data in this buffer.
+    ///
+    pub(crate) fn duplicate(&self) -> Self {
+        Self {
+            buffer: self.buffer.duplicate(GpuResourceType::VertexBuffer),
+        }
+    }
+
     pub(in crate::core) fn bind(&self) {
         self.buffer.bind();
     }
@@ -66,4 +75,8 @@ impl VertexBuffer {
     pub(in crate::core) fn normalized(&self) -> bool {
         self.buffer.normalized
     }
+
+    pub(in crate::core) fn read_back(&self) -> Vec<u8> {
+        self.buffer.read_back()
+    }
 }
@@ -0,0 +1,266 @@
+use crate::core::*;
+use three_d_asset::{Indices, TriMesh as CpuMesh};
+
+///
+/// A ray, ie. a half line starting at an origin and extending infinitely in a direction, for example constructed from a pixel using [Camera::ray_at_pixel](crate::renderer::Camera::ray_at_pixel).
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ray {
+    /// The start of the ray.
+    pub origin: Vec3,
+    /// The direction of the ray, which does not need to be normalized.
+    pub direction: Vec3,
+}
+
+impl Ray {
+    ///
+    /// Constructs a new ray with the given origin and direction.
+    ///
+    pub fn new(origin: Vec3, direction: Vec3) -> Self {
+        Self { origin, direction }
+    }
+
+    ///
+    /// Returns the point on the ray at the given parameter, ie. `origin + t * direction`.
+    ///
+    pub fn at(&self, t: f32) -> Vec3 {
+        self.origin + t * self.direction
+    }
+}
+
+///
+/// The closest intersection between a [Ray] and the triangles in a [Bvh], see [Bvh::intersect].
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Hit {
+    /// The intersection point.
+    pub position: Vec3,
+    /// The normalized geometric normal of the hit triangle, which points towards the side from which the triangle is counter clockwise.
+    pub normal: Vec3,
+    /// The index of the hit triangle, ie. the vertices of the triangle are given by the indices at `3 * triangle_index`, `3 * triangle_index + 1` and `3 * triangle_index + 2`.
+    pub triangle_index: usize,
+    /// The ray parameter at the intersection point, ie. `position = ray.origin + t * ray.direction`.
+    pub t: f32,
+}
+
+enum BvhNode {
+    Leaf {
+        min: Vec3,
+        max: Vec3,
+        triangles: Vec<usize>,
+    },
+    Branch {
+        min: Vec3,
+        max: Vec3,
+        children: Box<[BvhNode; 2]>,
+    },
+}
+
+///
+/// A bounding volume hierarchy over the triangles of a mesh which is used to find the exact intersections between a ray and the mesh on the CPU,
+/// for example to find the triangle under the cursor in an editor.
+/// The hierarchy is built by splitting the triangles at the median of the longest axis, so an intersection test only visits a few triangles.
+///
+/// The triangles are stored in the local space of the mesh, so to intersect a mesh with a transformation,
+/// either transform the [CpuMesh](crate::renderer::CpuMesh) before building the hierarchy or transform the ray by the inverse transformation.
+///
+pub struct Bvh {
+    triangles: Vec<[Vec3; 3]>,
+    root: Option<BvhNode>,
+}
+
+impl Bvh {
+    ///
+    /// Builds a bounding volume hierarchy over the triangles of the given mesh.
+    ///
+    pub fn new(cpu_mesh: &CpuMesh) -> Self {
+        let positions = cpu_mesh.positions.to_f32();
+        let indices: Vec<u32> = match &cpu_mesh.indices {
+            Indices::U8(ind) => ind.iter().map(|i| *i as u32).collect(),
+            Indices::U16(ind) => ind.iter().map(|i| *i as u32).collect(),
+            Indices::U32(ind) => ind.clone(),
+            Indices::None => (0..positions.len() as u32).collect(),
+        };
+        Self::new_from_triangles(
+            indices
+                .chunks_exact(3)
+                .map(|t| {
+                    [
+                        positions[t[0] as usize],
+                        positions[t[1] as usize],
+                        positions[t[2] as usize],
+                    ]
+                })
+                .collect(),
+        )
+    }
+
+    ///
+    /// Builds a bounding volume hierarchy over the given triangles.
+    ///
+    pub fn new_from_triangles(triangles: Vec<[Vec3; 3]>) -> Self {
+        let centers = triangles
+            .iter()
+            .map(|t| (t[0] + t[1] + t[2]) / 3.0)
+            .collect::<Vec<_>>();
+        let indices = (0..triangles.len()).collect::<Vec<_>>();
+        let root = if indices.is_empty() {
+            None
+        } else {
+            Some(Self::build(&triangles, &centers, indices))
+        };
+        Self { triangles, root }
+    }
+
+    ///
+    /// Returns the triangles in the hierarchy.
+    ///
+    pub fn triangles(&self) -> &[[Vec3; 3]] {
+        &self.triangles
+    }
+
+    ///
+    /// Returns the bounding box of all the triangles.
+    ///
+    pub fn aabb(&self) -> AxisAlignedBoundingBox {
+        match &self.root {
+            Some(BvhNode::Leaf { min, max, .. }) | Some(BvhNode::Branch { min, max, .. }) => {
+                AxisAlignedBoundingBox::new_with_positions(&[*min, *max])
+            }
+            None => AxisAlignedBoundingBox::EMPTY,
+        }
+    }
+
+    ///
+    /// Returns the closest intersection between the given ray and the triangles, or `None` if the ray does not hit any triangle.
+    /// Both sides of the triangles are hit.
+    ///
+    pub fn intersect(&self, ray: &Ray) -> Option<Hit> {
+        let mut closest: Option<(f32, usize)> = None;
+        let mut stack = Vec::new();
+        stack.extend(self.root.as_ref());
+        while let Some(node) = stack.pop() {
+            let (min, max) = match node {
+                BvhNode::Leaf { min, max, .. } | BvhNode::Branch { min, max, .. } => (min, max),
+            };
+            match ray_box_distance(ray.origin, ray.direction, *min, *max) {
+                Some(distance) if closest.map(|(c, _)| distance < c).unwrap_or(true) => {}
+                _ => continue,
+            }
+            match node {
+                BvhNode::Leaf { triangles: ids, .. } => {
+                    for id in ids {
+                        if let Some(distance) =
+                            ray_triangle_distance(ray.origin, ray.direction, &self.triangles[*id])
+                        {
+                            if closest.map(|(c, _)| distance < c).unwrap_or(true) {
+                                closest = Some((distance, *id));
+                            }
+                        }
+                    }
+                }
+                BvhNode::Branch { children, .. } => {
+                    stack.push(&children[0]);
+                    stack.push(&children[1]);
+                }
+            }
+        }
+        closest.map(|(t, triangle_index)| {
+            let triangle = &self.triangles[triangle_index];
+            Hit {
+                position: ray.at(t),
+                normal: (triangle[1] - triangle[0])
+                    .cross(triangle[2] - triangle[0])
+                    .normalize(),
+                triangle_index,
+                t,
+            }
+        })
+    }
+
+    fn build(triangles: &[[Vec3; 3]], centers: &[Vec3], mut indices: Vec<usize>) -> BvhNode {
+        let mut min = vec3(f32::MAX, f32::MAX, f32::MAX);
+        let mut max = vec3(f32::MIN, f32::MIN, f32::MIN);
+        for i in indices.iter() {
+            for p in triangles[*i].iter() {
+                min = vec3(min.x.min(p.x), min.y.min(p.y), min.z.min(p.z));
+                max = vec3(max.x.max(p.x), max.y.max(p.y), max.z.max(p.z));
+            }
+        }
+        if indices.len() <= 4 {
+            return BvhNode::Leaf {
+                min,
+                max,
+                triangles: indices,
+            };
+        }
+        let size = max - min;
+        let axis = if size.x >= size.y && size.x >= size.z {
+            0
+        } else if size.y >= size.z {
+            1
+        } else {
+            2
+        };
+        indices.sort_by(|a, b| {
+            centers[*a][axis]
+                .partial_cmp(&centers[*b][axis])
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        let right = indices.split_off(indices.len() / 2);
+        BvhNode::Branch {
+            min,
+            max,
+            children: Box::new([
+                Self::build(triangles, centers, indices),
+                Self::build(triangles, centers, right),
+            ]),
+        }
+    }
+}
+
+fn ray_box_distance(origin: Vec3, direction: Vec3, min: Vec3, max: Vec3) -> Option<f32> {
+    let mut t_min = 0.0f32;
+    let mut t_max = f32::INFINITY;
+    for axis in 0..3 {
+        let inverse = 1.0 / direction[axis];
+        let mut t0 = (min[axis] - origin[axis]) * inverse;
+        let mut t1 = (max[axis] - origin[axis]) * inverse;
+        if inverse < 0.0 {
+            std::mem::swap(&mut t0, &mut t1);
+        }
+        t_min = t_min.max(t0);
+        t_max = t_max.min(t1);
+        if t_max < t_min {
+            return None;
+        }
+    }
+    Some(t_min)
+}
+
+fn ray_triangle_distance(origin: Vec3, direction: Vec3, triangle: &[Vec3; 3]) -> Option<f32> {
+    let e1 = triangle[1] - triangle[0];
+    let e2 = triangle[2] - triangle[0];
+    let p = direction.cross(e2);
+    let determinant = e1.dot(p);
+    if determinant.abs() < f32::EPSILON {
+        return None;
+    }
+    let inverse = 1.0 / determinant;
+    let s = origin - triangle[0];
+    let u = s.dot(p) * inverse;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+    let q = s.cross(e1);
+    let v = direction.dot(q) * inverse;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+    let t = e2.dot(q) * inverse;
+    if t > 0.0 {
+        Some(t)
+    } else {
+        None
+    }
+}
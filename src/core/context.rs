@@ -17,6 +17,12 @@ pub struct Context {
     pub(super) vao: crate::context::VertexArray,
     /// A cache of programs to avoid recompiling a [Program] every frame.
     pub programs: Arc<RwLock<HashMap<Vec<u8>, Program>>>,
+    pub(super) memory: Arc<RwLock<MemoryTracker>>,
+    intermediate_format: Arc<RwLock<IntermediateFormat>>,
+    pub(super) recording: Arc<RwLock<Option<FrameRecording>>>,
+    // Whether transparent materials are currently rendered into the order-independent transparency targets
+    pub(crate) oit_pass: Arc<RwLock<bool>>,
+    pub(crate) resource_cache: Arc<RwLock<crate::renderer::ResourceCache>>,
 }
 
 impl Context {
@@ -44,11 +50,175 @@ impl Context {
                 context,
                 vao,
                 programs: Arc::new(RwLock::new(HashMap::new())),
+                memory: Arc::new(RwLock::new(MemoryTracker::default())),
+                intermediate_format: Arc::new(RwLock::new(IntermediateFormat::default())),
+                recording: Arc::new(RwLock::new(None)),
+                oit_pass: Arc::new(RwLock::new(false)),
+                resource_cache: Arc::new(RwLock::new(Default::default())),
             }
         };
         Ok(c)
     }
 
+    ///
+    /// Sets whether or not textures and meshes with the same content are shared on the GPU when they are created from loaded assets,
+    /// for example when the same texture is used by multiple materials in a [CpuModel](crate::renderer::CpuModel) or the same mesh is used by multiple nodes in a scene.
+    /// The content of the assets is hashed to find the identical resources and a copy of the content is kept while the resources are in use to verify that they are identical,
+    /// so disable this to avoid the cost of hashing and the extra memory if the assets are known not to contain duplicates.
+    /// Enabled by default.
+    ///
+    pub fn set_resource_sharing(&self, enabled: bool) {
+        self.resource_cache.write().unwrap().set_enabled(enabled);
+    }
+
+    ///
+    /// Sets the format of the intermediate textures which are created internally when rendering, for example the geometry buffer used when rendering with deferred materials.
+    /// The default is [IntermediateFormat::Rgba16F] which keeps the lighting in high dynamic range until the final tone mapping,
+    /// use [IntermediateFormat::Rgba8] to save memory and bandwidth at the cost of clamping the values to 8-bit between the passes.
+    ///
+    pub fn set_intermediate_format(&self, format: IntermediateFormat) {
+        *self.intermediate_format.write().unwrap() = format;
+    }
+
+    ///
+    /// Returns the format of the intermediate textures which are created internally when rendering, see [Context::set_intermediate_format].
+    ///
+    pub fn intermediate_format(&self) -> IntermediateFormat {
+        *self.intermediate_format.read().unwrap()
+    }
+
+    ///
+    /// Returns a summary of the GPU memory allocated by the buffers and textures created with this context that are still alive.
+    /// Render targets do not allocate memory themselves, they only refer to textures.
+    ///
+    pub fn memory_usage(&self) -> GpuMemoryUsage {
+        self.memory.read().unwrap().usage()
+    }
+
+    ///
+    /// Returns the GPU memory allocated by each of the buffers and textures created with this context that are still alive, ordered by creation.
+    /// Can be used to find resources that are unexpectedly kept alive.
+    ///
+    pub fn memory_allocations(&self) -> Vec<GpuAllocation> {
+        self.memory.read().unwrap().allocations()
+    }
+
+    ///
+    /// Returns a description of all buffers and textures created with this context which are still alive or `None` if there are none.
+    /// In debug builds, the description also contains the backtrace of where each resource was created if backtraces are enabled, for example by setting the `RUST_BACKTRACE=1` environment variable.
+    ///
+    /// When all objects using the GPU are expected to be dropped, for example when closing the application, this can be used to find the resources that are accidentally kept alive.
+    /// In debug builds, this report is logged as a warning using the [log](https://crates.io/crates/log) crate when the graphics context of a [Window](crate::window::Window),
+    /// a `WindowedContext` or a `HeadlessContext` is destroyed. If this context is created from a graphics context created elsewhere, call this method before destroying that graphics context.
+    ///
+    pub fn leak_report(&self) -> Option<String> {
+        self.memory.read().unwrap().leak_report()
+    }
+
+    // Logs the leak report, at most once, since the graphics context is about to be destroyed
+    pub(crate) fn report_leaks(&self) {
+        #[cfg(debug_assertions)]
+        self.memory.write().unwrap().report_leaks();
+    }
+
+    ///
+    /// Starts recording the draw calls, render states, uniform values and attribute data sent to the GPU using a [Program] until [Context::stop_recording] is called.
+    /// Typically the recording is started at the beginning of a frame and stopped at the end of the frame.
+    /// Any ongoing recording is discarded.
+    ///
+    /// **Note:** Recording reads back the data of all vertex, instance and element buffers used when drawing, so it is slow and should only be used for debugging.
+    ///
+    pub fn start_recording(&self) {
+        *self.recording.write().unwrap() = Some(FrameRecording::default());
+    }
+
+    ///
+    /// Stops the recording started by [Context::start_recording] and returns the recording or `None` if nothing is being recorded.
+    /// The recording can be saved to a file and replayed on another device using a [FrameReplayer].
+    ///
+    pub fn stop_recording(&self) -> Option<FrameRecording> {
+        self.recording.write().unwrap().take()
+    }
+
+    ///
+    /// Returns true if the render commands are currently being recorded, see [Context::start_recording].
+    ///
+    pub fn is_recording(&self) -> bool {
+        self.recording.read().unwrap().is_some()
+    }
+
+    ///
+    /// Returns true if the tessellation shader stages are supported, which requires OpenGL 4.0 or newer and is therefore not available on OpenGL ES and WebGL.
+    /// See [Program::from_source_with_tessellation].
+    ///
+    pub fn supports_tessellation(&self) -> bool {
+        let version = self.version();
+        !version.is_embedded && version.major >= 4
+    }
+
+    ///
+    /// Returns true if the geometry shader stage is supported, which is always the case on desktop but not available on OpenGL ES 3.0 and WebGL.
+    /// See [Program::from_source_with_geometry_shader].
+    ///
+    pub fn supports_geometry_shaders(&self) -> bool {
+        !self.version().is_embedded
+    }
+
+    ///
+    /// Returns the maximum number of samples supported for multisample render targets, see [RenderTargetMultisample].
+    /// The number of samples of a multisample render target is independent of the number of samples of the surface of the window.
+    ///
+    pub fn max_samples(&self) -> u32 {
+        unsafe {
+            self.get_parameter_i32(crate::context::MAX_SAMPLES)
+                .try_into()
+                .unwrap()
+        }
+    }
+
+    ///
+    /// Returns true if debug markers are supported, ie. if [Context::push_debug_group] and [Context::pop_debug_group] has any effect.
+    /// Requires OpenGL 4.3 or the `KHR_debug` extension and is therefore not available on WebGL.
+    ///
+    pub fn supports_debug_markers(&self) -> bool {
+        self.supports_debug()
+    }
+
+    ///
+    /// Starts a named group of GPU commands which is shown as a marker in graphics debuggers like [RenderDoc](https://renderdoc.org/),
+    /// which makes it easier to navigate a captured frame. Each call must be followed by a call to [Context::pop_debug_group].
+    /// Does nothing if debug markers are not supported, see [Context::supports_debug_markers].
+    ///
+    pub fn push_debug_group(&self, name: &str) {
+        if self.supports_debug_markers() {
+            unsafe {
+                self.context
+                    .push_debug_group(crate::context::DEBUG_SOURCE_APPLICATION, 0, name);
+            }
+        }
+    }
+
+    ///
+    /// Ends the group of GPU commands started by the last call to [Context::push_debug_group].
+    ///
+    pub fn pop_debug_group(&self) {
+        if self.supports_debug_markers() {
+            unsafe {
+                self.context.pop_debug_group();
+            }
+        }
+    }
+
+    ///
+    /// Calls the given closure inside a named group of GPU commands, see [Context::push_debug_group].
+    ///
+    pub fn debug_group<T>(&self, name: &str, callback: impl FnOnce() -> T) -> T {
+        self.push_debug_group(name);
+        let result = callback();
+        self.pop_debug_group();
+        result
+    }
+
     ///
     /// Set the scissor test for this context (see [ScissorBox]).
     ///
@@ -295,6 +465,18 @@ impl Context {
     }
 }
 
+///
+/// The format of the intermediate textures which are created internally when rendering, see [Context::set_intermediate_format].
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum IntermediateFormat {
+    /// Four 8-bit unsigned normalized channels, ie. values are clamped to the range `[0..1]` and stored with 256 levels.
+    Rgba8,
+    /// Four 16-bit floating point channels which are able to store high dynamic range values.
+    #[default]
+    Rgba16F,
+}
+
 impl std::fmt::Debug for Context {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let mut d = f.debug_struct("Context");
@@ -227,6 +227,9 @@ pub trait DataType: std::fmt::Debug + Clone {
         false
     }
     fn send_uniform(context: &Context, location: &UniformLocation, data: &[Self]);
+    fn to_bytes(data: &[Self]) -> Vec<u8> {
+        to_byte_slice(data).to_vec()
+    }
 }
 
 impl<T: DataType + ?Sized> DataType for &T {
@@ -250,6 +253,10 @@ impl<T: DataType + ?Sized> DataType for &T {
             &data.iter().map(|v| (*v).clone()).collect::<Vec<_>>(),
         )
     }
+
+    fn to_bytes(data: &[Self]) -> Vec<u8> {
+        T::to_bytes(&data.iter().map(|v| (*v).clone()).collect::<Vec<_>>())
+    }
 }
 
 impl DataType for u8 {
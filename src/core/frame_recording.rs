@@ -0,0 +1,788 @@
+use crate::core::*;
+use std::collections::HashMap;
+
+///
+/// A command sent to the GPU using a [Program], which is recorded in a [FrameRecording].
+/// The programs are referred to by their index in the recording.
+///
+#[derive(Clone, Debug)]
+pub enum RenderCommand {
+    /// A program is used for the first time in the recording.
+    Program {
+        /// The index of the program in the recording.
+        index: u32,
+        /// The shader type and the source code of each shader stage.
+        sources: Vec<(u32, String)>,
+        /// The number of vertices in each patch if the program uses the tessellation stages.
+        patch_vertices: Option<u32>,
+    },
+    /// A uniform is sent to a program, see [Program::use_uniform].
+    Uniform {
+        /// The index of the program in the recording.
+        program: u32,
+        /// The name of the uniform.
+        name: String,
+        /// The data type of each value, for example `FLOAT`.
+        data_type: u32,
+        /// The values in the native byte order.
+        data: Vec<u8>,
+    },
+    /// An attribute is sent to a program, see [Program::use_vertex_attribute] and [Program::use_instance_attribute].
+    Attribute {
+        /// The index of the program in the recording.
+        program: u32,
+        /// The name of the attribute.
+        name: String,
+        /// The data type of each value, for example `FLOAT`.
+        data_type: u32,
+        /// The number of values for each vertex or instance.
+        data_size: u32,
+        /// Whether or not the integer values are normalized.
+        normalized: bool,
+        /// Whether or not the attribute is per instance instead of per vertex.
+        instanced: bool,
+        /// The values in the native byte order.
+        data: Vec<u8>,
+    },
+    /// A draw call, see for example [Program::draw_arrays] and [Program::draw_elements].
+    Draw {
+        /// The index of the program in the recording.
+        program: u32,
+        /// The render states used for the draw call.
+        render_states: RenderStates,
+        /// The viewport used for the draw call.
+        viewport: Viewport,
+        /// The data type and the data of the indices if the draw call uses an [ElementBuffer].
+        elements: Option<(u32, Vec<u8>)>,
+        /// The first index.
+        first: u32,
+        /// The number of vertices or indices.
+        count: u32,
+        /// The number of instances if the draw call is instanced.
+        instance_count: Option<u32>,
+    },
+}
+
+///
+/// A recording of the commands sent to the GPU, typically during one frame, see [Context::start_recording].
+/// The recording can be saved to a file and loaded on another device, for example to debug platform specific rendering differences using a [FrameReplayer].
+///
+/// **Note:** Textures and uniform buffers are not recorded.
+///
+#[derive(Clone, Debug, Default)]
+pub struct FrameRecording {
+    commands: Vec<RenderCommand>,
+    programs: HashMap<crate::context::Program, u32>,
+}
+
+impl FrameRecording {
+    ///
+    /// Returns the recorded commands in the order they were sent to the GPU.
+    ///
+    pub fn commands(&self) -> &[RenderCommand] {
+        &self.commands
+    }
+
+    ///
+    /// Returns the number of recorded draw calls.
+    ///
+    pub fn draw_count(&self) -> usize {
+        self.commands
+            .iter()
+            .filter(|c| matches!(c, RenderCommand::Draw { .. }))
+            .count()
+    }
+
+    pub(super) fn program_index(
+        &mut self,
+        id: crate::context::Program,
+        sources: &[(u32, String)],
+        patch_vertices: Option<u32>,
+    ) -> u32 {
+        if let Some(index) = self.programs.get(&id) {
+            return *index;
+        }
+        let index = self.programs.len() as u32;
+        self.programs.insert(id, index);
+        self.commands.push(RenderCommand::Program {
+            index,
+            sources: sources.to_vec(),
+            patch_vertices,
+        });
+        index
+    }
+
+    pub(super) fn push(&mut self, command: RenderCommand) {
+        self.commands.push(command);
+    }
+
+    ///
+    /// Serializes the recording into bytes, which can be deserialized using [FrameRecording::from_bytes].
+    ///
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut writer = Writer(MAGIC.to_vec());
+        writer.u32(self.commands.len() as u32);
+        for command in self.commands.iter() {
+            match command {
+                RenderCommand::Program {
+                    index,
+                    sources,
+                    patch_vertices,
+                } => {
+                    writer.u8(0);
+                    writer.u32(*index);
+                    writer.u32(sources.len() as u32);
+                    for (shader_type, source) in sources {
+                        writer.u32(*shader_type);
+                        writer.bytes(source.as_bytes());
+                    }
+                    writer.u32(patch_vertices.unwrap_or(0));
+                }
+                RenderCommand::Uniform {
+                    program,
+                    name,
+                    data_type,
+                    data,
+                } => {
+                    writer.u8(1);
+                    writer.u32(*program);
+                    writer.bytes(name.as_bytes());
+                    writer.u32(*data_type);
+                    writer.bytes(data);
+                }
+                RenderCommand::Attribute {
+                    program,
+                    name,
+                    data_type,
+                    data_size,
+                    normalized,
+                    instanced,
+                    data,
+                } => {
+                    writer.u8(2);
+                    writer.u32(*program);
+                    writer.bytes(name.as_bytes());
+                    writer.u32(*data_type);
+                    writer.u32(*data_size);
+                    writer.u8(*normalized as u8);
+                    writer.u8(*instanced as u8);
+                    writer.bytes(data);
+                }
+                RenderCommand::Draw {
+                    program,
+                    render_states,
+                    viewport,
+                    elements,
+                    first,
+                    count,
+                    instance_count,
+                } => {
+                    writer.u8(3);
+                    writer.u32(*program);
+                    writer.render_states(*render_states);
+                    writer.u32(viewport.x as u32);
+                    writer.u32(viewport.y as u32);
+                    writer.u32(viewport.width);
+                    writer.u32(viewport.height);
+                    match elements {
+                        Some((data_type, data)) => {
+                            writer.u32(*data_type);
+                            writer.bytes(data);
+                        }
+                        None => writer.u32(0),
+                    }
+                    writer.u32(*first);
+                    writer.u32(*count);
+                    writer.u32(instance_count.map(|c| c + 1).unwrap_or(0));
+                }
+            }
+        }
+        writer.0
+    }
+
+    ///
+    /// Deserializes a recording serialized using [FrameRecording::to_bytes].
+    ///
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CoreError> {
+        if !bytes.starts_with(MAGIC) {
+            Err(CoreError::InvalidRecording(
+                "not a frame recording".to_owned(),
+            ))?;
+        }
+        let mut reader = Reader(&bytes[MAGIC.len()..]);
+        let command_count = reader.u32()?;
+        let mut commands = Vec::new();
+        for _ in 0..command_count {
+            commands.push(match reader.u8()? {
+                0 => {
+                    let index = reader.u32()?;
+                    let mut sources = Vec::new();
+                    for _ in 0..reader.u32()? {
+                        let shader_type = reader.u32()?;
+                        sources.push((shader_type, reader.string()?));
+                    }
+                    let patch_vertices = Some(reader.u32()?).filter(|p| *p > 0);
+                    RenderCommand::Program {
+                        index,
+                        sources,
+                        patch_vertices,
+                    }
+                }
+                1 => RenderCommand::Uniform {
+                    program: reader.u32()?,
+                    name: reader.string()?,
+                    data_type: reader.u32()?,
+                    data: reader.bytes()?,
+                },
+                2 => RenderCommand::Attribute {
+                    program: reader.u32()?,
+                    name: reader.string()?,
+                    data_type: reader.u32()?,
+                    data_size: reader.u32()?,
+                    normalized: reader.u8()? != 0,
+                    instanced: reader.u8()? != 0,
+                    data: reader.bytes()?,
+                },
+                3 => RenderCommand::Draw {
+                    program: reader.u32()?,
+                    render_states: reader.render_states()?,
+                    viewport: Viewport {
+                        x: reader.u32()? as i32,
+                        y: reader.u32()? as i32,
+                        width: reader.u32()?,
+                        height: reader.u32()?,
+                    },
+                    elements: match reader.u32()? {
+                        0 => None,
+                        data_type => Some((data_type, reader.bytes()?)),
+                    },
+                    first: reader.u32()?,
+                    count: reader.u32()?,
+                    instance_count: reader.u32()?.checked_sub(1),
+                },
+                c => Err(CoreError::InvalidRecording(format!(
+                    "unknown command {}",
+                    c
+                )))?,
+            });
+        }
+        Ok(Self {
+            commands,
+            programs: HashMap::new(),
+        })
+    }
+
+    ///
+    /// Saves the recording to a file.
+    ///
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save(&self, path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.to_bytes())
+    }
+
+    ///
+    /// Loads a recording saved using [FrameRecording::save].
+    ///
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load(path: impl AsRef<std::path::Path>) -> Result<Self, CoreError> {
+        let bytes = std::fs::read(path).map_err(|e| CoreError::InvalidRecording(e.to_string()))?;
+        Self::from_bytes(&bytes)
+    }
+}
+
+///
+/// Replays a [FrameRecording] by compiling the recorded programs and sending the recorded uniforms, attributes and draw calls to the GPU.
+/// This can for example be used to replay a frame recorded on one device on another device, or to step through the draw calls of a frame.
+///
+/// Since textures are not recorded, each texture is replaced by a white texture.
+///
+pub struct FrameReplayer {
+    context: Context,
+    recording: FrameRecording,
+    programs: HashMap<u32, Program>,
+    buffers: HashMap<usize, crate::context::Buffer>,
+    placeholders: Vec<(u32, crate::context::Texture)>,
+}
+
+impl FrameReplayer {
+    ///
+    /// Creates a new replayer for the given recording, which compiles the recorded programs and uploads the recorded attribute data.
+    ///
+    pub fn new(context: &Context, recording: FrameRecording) -> Result<Self, CoreError> {
+        let mut programs = HashMap::new();
+        let mut buffers = HashMap::new();
+        for (i, command) in recording.commands.iter().enumerate() {
+            match command {
+                RenderCommand::Program {
+                    index,
+                    sources,
+                    patch_vertices,
+                } => {
+                    let program = PendingProgram::start_with_versioned_sources(
+                        context,
+                        sources.clone(),
+                        *patch_vertices,
+                    )
+                    .finish()?;
+                    programs.insert(*index, program);
+                }
+                RenderCommand::Attribute { data, .. } => unsafe {
+                    let buffer = context.create_buffer().expect("Failed creating buffer");
+                    context.bind_buffer(crate::context::ARRAY_BUFFER, Some(buffer));
+                    context.buffer_data_u8_slice(
+                        crate::context::ARRAY_BUFFER,
+                        data,
+                        crate::context::STATIC_DRAW,
+                    );
+                    context.bind_buffer(crate::context::ARRAY_BUFFER, None);
+                    buffers.insert(i, buffer);
+                },
+                _ => {}
+            }
+        }
+        let placeholders = [
+            crate::context::TEXTURE_2D,
+            crate::context::TEXTURE_2D_ARRAY,
+            crate::context::TEXTURE_3D,
+            crate::context::TEXTURE_CUBE_MAP,
+        ]
+        .into_iter()
+        .map(|target| (target, create_placeholder_texture(context, target)))
+        .collect();
+        Ok(Self {
+            context: context.clone(),
+            recording,
+            programs,
+            buffers,
+            placeholders,
+        })
+    }
+
+    ///
+    /// Returns the recording which is replayed.
+    ///
+    pub fn recording(&self) -> &FrameRecording {
+        &self.recording
+    }
+
+    ///
+    /// Replays all of the recorded commands into the given render target.
+    ///
+    pub fn replay(&self, target: &RenderTarget) {
+        self.replay_commands(target, self.recording.commands.len());
+    }
+
+    ///
+    /// Replays the first `command_count` recorded commands into the given render target, which can be used to step through the draw calls.
+    ///
+    pub fn replay_commands(&self, target: &RenderTarget, command_count: usize) {
+        target.write(|| {
+            for (i, command) in self
+                .recording
+                .commands
+                .iter()
+                .enumerate()
+                .take(command_count)
+            {
+                match command {
+                    RenderCommand::Program { .. } => {}
+                    RenderCommand::Uniform {
+                        program,
+                        name,
+                        data_type,
+                        data,
+                    } => {
+                        if let Some(program) = self.programs.get(program) {
+                            program.use_recorded_uniform(name, &decode_values(*data_type, data));
+                        }
+                    }
+                    RenderCommand::Attribute {
+                        program,
+                        name,
+                        data_type,
+                        data_size,
+                        normalized,
+                        instanced,
+                        ..
+                    } => {
+                        if let (Some(program), Some(buffer)) =
+                            (self.programs.get(program), self.buffers.get(&i))
+                        {
+                            program.use_recorded_attribute(
+                                name,
+                                *buffer,
+                                *data_type,
+                                *data_size,
+                                *normalized,
+                                *instanced,
+                            );
+                        }
+                    }
+                    RenderCommand::Draw {
+                        program,
+                        render_states,
+                        viewport,
+                        elements,
+                        first,
+                        count,
+                        instance_count,
+                    } => {
+                        if let Some(program) = self.programs.get(program) {
+                            self.bind_placeholders(program);
+                            self.draw(
+                                program,
+                                *render_states,
+                                *viewport,
+                                elements.as_ref(),
+                                *first,
+                                *count,
+                                *instance_count,
+                            );
+                        }
+                    }
+                }
+            }
+        });
+    }
+
+    fn bind_placeholders(&self, program: &Program) {
+        for uniform in program.active_uniforms() {
+            let target = match sampler_target(uniform.variable_type) {
+                Some(target) => target,
+                None => continue,
+            };
+            let texture = self
+                .placeholders
+                .iter()
+                .find(|(t, _)| *t == target)
+                .map(|(_, texture)| *texture);
+            if let Some(unit) = program.texture_unit(&uniform.name) {
+                for i in 0..uniform.size {
+                    unsafe {
+                        self.context
+                            .active_texture(crate::context::TEXTURE0 + unit + i);
+                        self.context.bind_texture(target, texture);
+                    }
+                }
+            }
+        }
+    }
+
+    fn draw(
+        &self,
+        program: &Program,
+        render_states: RenderStates,
+        viewport: Viewport,
+        elements: Option<&(u32, Vec<u8>)>,
+        first: u32,
+        count: u32,
+        instance_count: Option<u32>,
+    ) {
+        match elements {
+            Some((data_type, data)) => {
+                let indices = decode_values(*data_type, data)
+                    .into_iter()
+                    .map(|i| i as u32)
+                    .collect::<Vec<_>>();
+                let element_buffer = ElementBuffer::new_with_data(&self.context, &indices);
+                // The first index is given as a byte offset, which changes when the indices are converted to u32
+                let first = first / byte_size_of_data_type(*data_type) as u32
+                    * std::mem::size_of::<u32>() as u32;
+                match instance_count {
+                    Some(instance_count) => program.draw_subset_of_elements_instanced(
+                        render_states,
+                        viewport,
+                        &element_buffer,
+                        first,
+                        count,
+                        instance_count,
+                    ),
+                    None => program.draw_subset_of_elements(
+                        render_states,
+                        viewport,
+                        &element_buffer,
+                        first,
+                        count,
+                    ),
+                }
+            }
+            None => match instance_count {
+                Some(instance_count) => {
+                    program.draw_arrays_instanced(render_states, viewport, count, instance_count)
+                }
+                None => program.draw_arrays(render_states, viewport, count),
+            },
+        }
+    }
+}
+
+impl Drop for FrameReplayer {
+    fn drop(&mut self) {
+        unsafe {
+            for buffer in self.buffers.values() {
+                self.context.delete_buffer(*buffer);
+            }
+            for (_, texture) in self.placeholders.iter() {
+                self.context.delete_texture(*texture);
+            }
+        }
+    }
+}
+
+fn create_placeholder_texture(context: &Context, target: u32) -> crate::context::Texture {
+    let white = [255u8; 4];
+    unsafe {
+        let texture = context.create_texture().expect("Failed creating texture");
+        context.bind_texture(target, Some(texture));
+        context.tex_parameter_i32(
+            target,
+            crate::context::TEXTURE_MIN_FILTER,
+            crate::context::NEAREST as i32,
+        );
+        context.tex_parameter_i32(
+            target,
+            crate::context::TEXTURE_MAG_FILTER,
+            crate::context::NEAREST as i32,
+        );
+        match target {
+            crate::context::TEXTURE_2D_ARRAY | crate::context::TEXTURE_3D => {
+                context.tex_image_3d(
+                    target,
+                    0,
+                    crate::context::RGBA8 as i32,
+                    1,
+                    1,
+                    1,
+                    0,
+                    crate::context::RGBA,
+                    crate::context::UNSIGNED_BYTE,
+                    Some(&white),
+                );
+            }
+            crate::context::TEXTURE_CUBE_MAP => {
+                for face in 0..6 {
+                    context.tex_image_2d(
+                        crate::context::TEXTURE_CUBE_MAP_POSITIVE_X + face,
+                        0,
+                        crate::context::RGBA8 as i32,
+                        1,
+                        1,
+                        0,
+                        crate::context::RGBA,
+                        crate::context::UNSIGNED_BYTE,
+                        Some(&white),
+                    );
+                }
+            }
+            _ => {
+                context.tex_image_2d(
+                    target,
+                    0,
+                    crate::context::RGBA8 as i32,
+                    1,
+                    1,
+                    0,
+                    crate::context::RGBA,
+                    crate::context::UNSIGNED_BYTE,
+                    Some(&white),
+                );
+            }
+        }
+        context.bind_texture(target, None);
+        texture
+    }
+}
+
+fn sampler_target(variable_type: u32) -> Option<u32> {
+    match variable_type {
+        crate::context::SAMPLER_2D
+        | crate::context::SAMPLER_2D_SHADOW
+        | crate::context::INT_SAMPLER_2D
+        | crate::context::UNSIGNED_INT_SAMPLER_2D => Some(crate::context::TEXTURE_2D),
+        crate::context::SAMPLER_2D_ARRAY
+        | crate::context::SAMPLER_2D_ARRAY_SHADOW
+        | crate::context::INT_SAMPLER_2D_ARRAY
+        | crate::context::UNSIGNED_INT_SAMPLER_2D_ARRAY => Some(crate::context::TEXTURE_2D_ARRAY),
+        crate::context::SAMPLER_3D
+        | crate::context::INT_SAMPLER_3D
+        | crate::context::UNSIGNED_INT_SAMPLER_3D => Some(crate::context::TEXTURE_3D),
+        crate::context::SAMPLER_CUBE
+        | crate::context::SAMPLER_CUBE_SHADOW
+        | crate::context::INT_SAMPLER_CUBE
+        | crate::context::UNSIGNED_INT_SAMPLER_CUBE => Some(crate::context::TEXTURE_CUBE_MAP),
+        _ => None,
+    }
+}
+
+fn decode_values(data_type: u32, data: &[u8]) -> Vec<f64> {
+    let size = byte_size_of_data_type(data_type);
+    data.chunks_exact(size)
+        .map(|b| match data_type {
+            crate::context::UNSIGNED_BYTE => b[0] as f64,
+            crate::context::BYTE => b[0] as i8 as f64,
+            crate::context::UNSIGNED_SHORT => u16::from_ne_bytes([b[0], b[1]]) as f64,
+            crate::context::SHORT => i16::from_ne_bytes([b[0], b[1]]) as f64,
+            crate::context::HALF_FLOAT => f16::from_ne_bytes([b[0], b[1]]).to_f64(),
+            crate::context::UNSIGNED_INT => u32::from_ne_bytes([b[0], b[1], b[2], b[3]]) as f64,
+            crate::context::INT => i32::from_ne_bytes([b[0], b[1], b[2], b[3]]) as f64,
+            _ => f32::from_ne_bytes([b[0], b[1], b[2], b[3]]) as f64,
+        })
+        .collect()
+}
+
+const MAGIC: &[u8] = b"THREE-D-FRAME-RECORDING-1";
+
+const CULLS: [Cull; 4] = [Cull::None, Cull::Back, Cull::Front, Cull::FrontAndBack];
+const DEPTH_TESTS: [DepthTest; 8] = [
+    DepthTest::Never,
+    DepthTest::Less,
+    DepthTest::Equal,
+    DepthTest::LessOrEqual,
+    DepthTest::Greater,
+    DepthTest::NotEqual,
+    DepthTest::GreaterOrEqual,
+    DepthTest::Always,
+];
+const BLEND_MULTIPLIERS: [BlendMultiplierType; 11] = [
+    BlendMultiplierType::Zero,
+    BlendMultiplierType::One,
+    BlendMultiplierType::SrcColor,
+    BlendMultiplierType::OneMinusSrcColor,
+    BlendMultiplierType::DstColor,
+    BlendMultiplierType::OneMinusDstColor,
+    BlendMultiplierType::SrcAlpha,
+    BlendMultiplierType::OneMinusSrcAlpha,
+    BlendMultiplierType::DstAlpha,
+    BlendMultiplierType::OneMinusDstAlpha,
+    BlendMultiplierType::SrcAlphaSaturate,
+];
+const BLEND_EQUATIONS: [BlendEquationType; 5] = [
+    BlendEquationType::Add,
+    BlendEquationType::Subtract,
+    BlendEquationType::ReverseSubtract,
+    BlendEquationType::Min,
+    BlendEquationType::Max,
+];
+
+struct Writer(Vec<u8>);
+
+impl Writer {
+    fn u8(&mut self, value: u8) {
+        self.0.push(value);
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn bytes(&mut self, data: &[u8]) {
+        self.u32(data.len() as u32);
+        self.0.extend_from_slice(data);
+    }
+
+    fn index<T: PartialEq>(&mut self, values: &[T], value: &T) {
+        self.u8(values.iter().position(|v| v == value).unwrap_or(0) as u8);
+    }
+
+    fn render_states(&mut self, render_states: RenderStates) {
+        self.index(&CULLS, &render_states.cull);
+        self.index(&DEPTH_TESTS, &render_states.depth_test);
+        let mask = render_states.write_mask;
+        self.u8(mask.red as u8
+            | (mask.green as u8) << 1
+            | (mask.blue as u8) << 2
+            | (mask.alpha as u8) << 3
+            | (mask.depth as u8) << 4);
+        match render_states.blend {
+            Blend::Enabled {
+                source_rgb_multiplier,
+                source_alpha_multiplier,
+                destination_rgb_multiplier,
+                destination_alpha_multiplier,
+                rgb_equation,
+                alpha_equation,
+            } => {
+                self.u8(1);
+                self.index(&BLEND_MULTIPLIERS, &source_rgb_multiplier);
+                self.index(&BLEND_MULTIPLIERS, &source_alpha_multiplier);
+                self.index(&BLEND_MULTIPLIERS, &destination_rgb_multiplier);
+                self.index(&BLEND_MULTIPLIERS, &destination_alpha_multiplier);
+                self.index(&BLEND_EQUATIONS, &rgb_equation);
+                self.index(&BLEND_EQUATIONS, &alpha_equation);
+            }
+            Blend::Disabled => self.u8(0),
+        }
+    }
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, count: usize) -> Result<&'a [u8], CoreError> {
+        if self.0.len() < count {
+            Err(CoreError::InvalidRecording(
+                "unexpected end of data".to_owned(),
+            ))?;
+        }
+        let (data, rest) = self.0.split_at(count);
+        self.0 = rest;
+        Ok(data)
+    }
+
+    fn u8(&mut self) -> Result<u8, CoreError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, CoreError> {
+        let b = self.take(4)?;
+        Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn bytes(&mut self) -> Result<Vec<u8>, CoreError> {
+        let count = self.u32()? as usize;
+        Ok(self.take(count)?.to_vec())
+    }
+
+    fn string(&mut self) -> Result<String, CoreError> {
+        String::from_utf8(self.bytes()?).map_err(|e| CoreError::InvalidRecording(e.to_string()))
+    }
+
+    fn index<T: Copy>(&mut self, values: &[T]) -> Result<T, CoreError> {
+        let index = self.u8()? as usize;
+        values
+            .get(index)
+            .copied()
+            .ok_or_else(|| CoreError::InvalidRecording(format!("invalid index {}", index)))
+    }
+
+    fn render_states(&mut self) -> Result<RenderStates, CoreError> {
+        let cull = self.index(&CULLS)?;
+        let depth_test = self.index(&DEPTH_TESTS)?;
+        let mask = self.u8()?;
+        let write_mask = WriteMask {
+            red: mask & 1 != 0,
+            green: mask & (1 << 1) != 0,
+            blue: mask & (1 << 2) != 0,
+            alpha: mask & (1 << 3) != 0,
+            depth: mask & (1 << 4) != 0,
+        };
+        let blend = if self.u8()? != 0 {
+            Blend::Enabled {
+                source_rgb_multiplier: self.index(&BLEND_MULTIPLIERS)?,
+                source_alpha_multiplier: self.index(&BLEND_MULTIPLIERS)?,
+                destination_rgb_multiplier: self.index(&BLEND_MULTIPLIERS)?,
+                destination_alpha_multiplier: self.index(&BLEND_MULTIPLIERS)?,
+                rgb_equation: self.index(&BLEND_EQUATIONS)?,
+                alpha_equation: self.index(&BLEND_EQUATIONS)?,
+            }
+        } else {
+            Blend::Disabled
+        };
+        Ok(RenderStates {
+            write_mask,
+            depth_test,
+            blend,
+            cull,
+        })
+    }
+}
@@ -0,0 +1,121 @@
+use crate::core::*;
+use std::collections::VecDeque;
+
+/// The maximum number of measurements waiting for the result before the oldest is discarded.
+const MAX_PENDING_QUERIES: usize = 4;
+
+///
+/// Measures the time the GPU spends executing the commands issued in a closure, see [GpuTimer::measure].
+/// The result is read asynchronously a few frames later to avoid stalling the CPU, so [GpuTimer::elapsed_time] returns the most recent result which is available.
+///
+/// Requires OpenGL 3.3 or the `EXT_disjoint_timer_query` extension on OpenGL ES and WebGL, see [GpuTimer::is_supported].
+/// **Note:** Measurements can not be nested.
+///
+pub struct GpuTimer {
+    context: Context,
+    pending: VecDeque<crate::context::Query>,
+    free: Vec<crate::context::Query>,
+    elapsed_time: Option<f64>,
+}
+
+impl GpuTimer {
+    ///
+    /// Returns true if timer queries are supported by the given context.
+    ///
+    pub fn is_supported(context: &Context) -> bool {
+        let version = context.version();
+        if version.is_embedded {
+            let extensions = context.supported_extensions();
+            extensions.contains("GL_EXT_disjoint_timer_query")
+                || extensions.contains("EXT_disjoint_timer_query_webgl2")
+                || extensions.contains("EXT_disjoint_timer_query")
+        } else {
+            version.major > 3 || (version.major == 3 && version.minor >= 3)
+        }
+    }
+
+    ///
+    /// Constructs a new timer or returns `None` if timer queries are not supported, see [GpuTimer::is_supported].
+    ///
+    pub fn new(context: &Context) -> Option<Self> {
+        if Self::is_supported(context) {
+            Some(Self {
+                context: context.clone(),
+                pending: VecDeque::new(),
+                free: Vec::new(),
+                elapsed_time: None,
+            })
+        } else {
+            None
+        }
+    }
+
+    ///
+    /// Measures the time the GPU spends executing the commands issued in the given closure.
+    /// The result is available from [GpuTimer::elapsed_time] when the GPU has finished executing the commands.
+    ///
+    pub fn measure<T>(&mut self, callback: impl FnOnce() -> T) -> T {
+        self.poll();
+        if self.pending.len() >= MAX_PENDING_QUERIES {
+            if let Some(query) = self.pending.pop_front() {
+                self.free.push(query);
+            }
+        }
+        let query = match self.free.pop() {
+            Some(query) => Some(query),
+            None => unsafe { self.context.create_query().ok() },
+        };
+        match query {
+            Some(query) => {
+                unsafe {
+                    self.context
+                        .begin_query(crate::context::TIME_ELAPSED, query);
+                }
+                let result = callback();
+                unsafe {
+                    self.context.end_query(crate::context::TIME_ELAPSED);
+                }
+                self.pending.push_back(query);
+                result
+            }
+            None => callback(),
+        }
+    }
+
+    ///
+    /// Returns the most recent measured time in milliseconds or `None` if no measurement has finished yet.
+    ///
+    pub fn elapsed_time(&mut self) -> Option<f64> {
+        self.poll();
+        self.elapsed_time
+    }
+
+    fn poll(&mut self) {
+        while let Some(query) = self.pending.front().copied() {
+            let available = unsafe {
+                self.context
+                    .get_query_parameter_u32(query, crate::context::QUERY_RESULT_AVAILABLE)
+            };
+            if available == 0 {
+                break;
+            }
+            let nanoseconds = unsafe {
+                self.context
+                    .get_query_parameter_u32(query, crate::context::QUERY_RESULT)
+            };
+            self.elapsed_time = Some(nanoseconds as f64 * 1e-6);
+            self.pending.pop_front();
+            self.free.push(query);
+        }
+    }
+}
+
+impl Drop for GpuTimer {
+    fn drop(&mut self) {
+        unsafe {
+            for query in self.pending.drain(..).chain(self.free.drain(..)) {
+                self.context.delete_query(query);
+            }
+        }
+    }
+}
@@ -0,0 +1,165 @@
+use crate::core::*;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+///
+/// The type of a GPU resource which memory usage is tracked by the [Context], see [Context::memory_usage].
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[allow(missing_docs)]
+pub enum GpuResourceType {
+    VertexBuffer,
+    InstanceBuffer,
+    ElementBuffer,
+    UniformBuffer,
+    Texture2D,
+    Texture2DArray,
+    Texture3D,
+    TextureCubeMap,
+    Texture2DMultisample,
+    DepthTexture2D,
+    DepthTexture2DArray,
+    DepthTextureCubeMap,
+    DepthTexture2DMultisample,
+}
+
+///
+/// A GPU resource together with the number of bytes allocated for it on the GPU, see [Context::memory_allocations].
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GpuAllocation {
+    /// A unique id of the resource, which is the same for the lifetime of the resource.
+    pub id: u64,
+    /// The type of the resource.
+    pub resource_type: GpuResourceType,
+    /// The number of bytes allocated for the resource.
+    /// This is computed from the size and format of the resource, so the actual size allocated by the driver can be a bit larger because of padding and alignment.
+    pub byte_size: usize,
+}
+
+///
+/// A summary of the GPU memory allocated by the resources created with a [Context], see [Context::memory_usage].
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GpuMemoryUsage {
+    /// The total number of bytes allocated.
+    pub byte_size: usize,
+    /// The number of resources that are alive.
+    pub resource_count: usize,
+    /// The number of bytes allocated for each type of resource.
+    pub byte_size_per_type: HashMap<GpuResourceType, usize>,
+}
+
+#[derive(Default)]
+pub(crate) struct MemoryTracker {
+    next_id: u64,
+    allocations: HashMap<u64, GpuAllocation>,
+    #[cfg(debug_assertions)]
+    backtraces: HashMap<u64, std::backtrace::Backtrace>,
+    #[cfg(debug_assertions)]
+    leaks_reported: bool,
+}
+
+impl MemoryTracker {
+    pub fn usage(&self) -> GpuMemoryUsage {
+        let mut usage = GpuMemoryUsage {
+            resource_count: self.allocations.len(),
+            ..Default::default()
+        };
+        for allocation in self.allocations.values() {
+            usage.byte_size += allocation.byte_size;
+            *usage
+                .byte_size_per_type
+                .entry(allocation.resource_type)
+                .or_insert(0) += allocation.byte_size;
+        }
+        usage
+    }
+
+    pub fn allocations(&self) -> Vec<GpuAllocation> {
+        let mut allocations = self.allocations.values().cloned().collect::<Vec<_>>();
+        allocations.sort_by_key(|a| a.id);
+        allocations
+    }
+
+    pub fn leak_report(&self) -> Option<String> {
+        if self.allocations.is_empty() {
+            return None;
+        }
+        let mut report = format!(
+            "{} GPU resources are still alive:\n",
+            self.allocations.len()
+        );
+        for allocation in self.allocations() {
+            report.push_str(&format!(
+                "- {:?} #{} using {} bytes",
+                allocation.resource_type, allocation.id, allocation.byte_size
+            ));
+            #[cfg(debug_assertions)]
+            if let Some(backtrace) = self.backtraces.get(&allocation.id) {
+                if backtrace.status() == std::backtrace::BacktraceStatus::Captured {
+                    report.push_str(&format!(", created at:\n{}", backtrace));
+                }
+            }
+            report.push('\n');
+        }
+        Some(report)
+    }
+
+    #[cfg(debug_assertions)]
+    pub fn report_leaks(&mut self) {
+        if !self.leaks_reported {
+            self.leaks_reported = true;
+            if let Some(report) = self.leak_report() {
+                log::warn!("{}", report);
+            }
+        }
+    }
+}
+
+///
+/// Registers a GPU resource in the memory tracker of the context for as long as it is alive.
+///
+pub(crate) struct TrackedAllocation {
+    tracker: Arc<RwLock<MemoryTracker>>,
+    id: u64,
+}
+
+impl TrackedAllocation {
+    pub fn new(context: &Context, resource_type: GpuResourceType, byte_size: usize) -> Self {
+        let tracker = context.memory.clone();
+        let id = {
+            let mut t = tracker.write().unwrap();
+            let id = t.next_id;
+            t.next_id += 1;
+            t.allocations.insert(
+                id,
+                GpuAllocation {
+                    id,
+                    resource_type,
+                    byte_size,
+                },
+            );
+            #[cfg(debug_assertions)]
+            t.backtraces
+                .insert(id, std::backtrace::Backtrace::capture());
+            id
+        };
+        Self { tracker, id }
+    }
+
+    pub fn set_byte_size(&self, byte_size: usize) {
+        if let Some(allocation) = self.tracker.write().unwrap().allocations.get_mut(&self.id) {
+            allocation.byte_size = byte_size;
+        }
+    }
+}
+
+impl Drop for TrackedAllocation {
+    fn drop(&mut self) {
+        let mut tracker = self.tracker.write().unwrap();
+        tracker.allocations.remove(&self.id);
+        #[cfg(debug_assertions)]
+        tracker.backtraces.remove(&self.id);
+    }
+}
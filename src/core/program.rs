@@ -3,7 +3,8 @@ use std::collections::HashMap;
 use std::sync::RwLock;
 
 ///
-/// A shader program consisting of a programmable vertex shader followed by a programmable fragment shader.
+/// A shader program consisting of a programmable vertex shader followed by a programmable fragment shader
+/// and optionally tessellation shaders or a geometry shader in between.
 /// Functionality includes transferring per vertex data to the vertex shader (see the use_attribute functionality)
 /// and transferring uniform data to both shader stages (see the use_uniform and use_texture functionality)
 /// and execute the shader program (see the draw functionality).
@@ -12,97 +13,172 @@ pub struct Program {
     context: Context,
     id: crate::context::Program,
     attributes: HashMap<String, u32>,
-    textures: RwLock<HashMap<String, u32>>,
+    texture_units: HashMap<String, u32>,
     uniforms: HashMap<String, crate::context::UniformLocation>,
     uniform_blocks: RwLock<HashMap<String, (u32, u32)>>,
+    patch_vertices: Option<u32>,
+    active_attributes: Vec<ActiveVariable>,
+    active_uniforms: Vec<ActiveVariable>,
+    nearest_sampler: RwLock<Option<crate::context::Sampler>>,
+    sources: Vec<(u32, String)>,
 }
 
 impl Program {
     ///
     /// Creates a new shader program from the given vertex and fragment glsl shader source.
     ///
+    /// The shader source should not contain a `#version` directive, since the version header is added depending on the platform,
+    /// ie. `#version 330 core` on desktop and `#version 300 es` on OpenGL ES and WebGL2 together with default precision qualifiers.
+    /// Any `#version` directive in the given source is therefore ignored and `#extension` directives are moved to right after the version header,
+    /// so the same shader source compiles on all platforms. Use `#ifdef GL_ES` for the few cases where the shader needs to differ between platforms.
+    ///
     pub fn from_source(
         context: &Context,
         vertex_shader_source: &str,
         fragment_shader_source: &str,
     ) -> Result<Self, CoreError> {
-        unsafe {
-            let vert_shader = context
-                .create_shader(crate::context::VERTEX_SHADER)
-                .expect("Failed creating vertex shader");
-            let frag_shader = context
-                .create_shader(crate::context::FRAGMENT_SHADER)
-                .expect("Failed creating fragment shader");
-
-            let header: &str = if context.version().is_embedded {
-                "#version 300 es
-                    #ifdef GL_FRAGMENT_PRECISION_HIGH
-                        precision highp float;
-                        precision highp int;
-                        precision highp sampler2DArray;
-                        precision highp sampler3D;
-                    #else
-                        precision mediump float;
-                        precision mediump int;
-                        precision mediump sampler2DArray;
-                        precision mediump sampler3D;
-                    #endif\n"
-            } else {
-                "#version 330 core\n"
-            };
-            let vertex_shader_source = format!("{}{}", header, vertex_shader_source);
-            let fragment_shader_source = format!("{}{}", header, fragment_shader_source);
+        Self::from_stages(
+            context,
+            330,
+            "",
+            &[
+                (crate::context::VERTEX_SHADER, vertex_shader_source),
+                (crate::context::FRAGMENT_SHADER, fragment_shader_source),
+            ],
+            None,
+        )
+    }
 
-            context.shader_source(vert_shader, &vertex_shader_source);
-            context.shader_source(frag_shader, &fragment_shader_source);
-            context.compile_shader(vert_shader);
-            context.compile_shader(frag_shader);
+    ///
+    /// Creates a new shader program from the given vertex, tessellation control, tessellation evaluation and fragment glsl shader source.
+    /// The tessellation stages are only available on desktop with OpenGL 4.0 or newer (see [Context::supports_tessellation]),
+    /// in that case the `USE_TESSELLATION` define is added to all shader stages and the draw calls renders patches of three vertices instead of triangles.
+    ///
+    /// If tessellation is not supported, the tessellation stages are ignored and the program is created from only the vertex and fragment shader source,
+    /// so the vertex shader should compute the final position in that case, for example by using `#ifndef USE_TESSELLATION`.
+    ///
+    pub fn from_source_with_tessellation(
+        context: &Context,
+        vertex_shader_source: &str,
+        tessellation_control_shader_source: &str,
+        tessellation_evaluation_shader_source: &str,
+        fragment_shader_source: &str,
+    ) -> Result<Self, CoreError> {
+        if context.supports_tessellation() {
+            Self::from_stages(
+                context,
+                400,
+                "#define USE_TESSELLATION\n",
+                &[
+                    (crate::context::VERTEX_SHADER, vertex_shader_source),
+                    (
+                        crate::context::TESS_CONTROL_SHADER,
+                        tessellation_control_shader_source,
+                    ),
+                    (
+                        crate::context::TESS_EVALUATION_SHADER,
+                        tessellation_evaluation_shader_source,
+                    ),
+                    (crate::context::FRAGMENT_SHADER, fragment_shader_source),
+                ],
+                Some(3),
+            )
+        } else {
+            Self::from_source(context, vertex_shader_source, fragment_shader_source)
+        }
+    }
 
-            let id = context.create_program().expect("Failed creating program");
-            context.attach_shader(id, vert_shader);
-            context.attach_shader(id, frag_shader);
-            context.link_program(id);
+    ///
+    /// Creates a new shader program from the given vertex, geometry and fragment glsl shader source.
+    /// The geometry shader is executed once for each triangle and can emit any number of primitives, which for example enables
+    /// rendering to all sides of a cube map in one pass or visualizing normals without expanding the mesh on the CPU.
+    /// The `USE_GEOMETRY_SHADER` define is added to all shader stages.
+    ///
+    /// The geometry shader stage is only available on desktop (see [Context::supports_geometry_shaders]), otherwise [CoreError::UnsupportedShaderStage] is returned.
+    ///
+    pub fn from_source_with_geometry_shader(
+        context: &Context,
+        vertex_shader_source: &str,
+        geometry_shader_source: &str,
+        fragment_shader_source: &str,
+    ) -> Result<Self, CoreError> {
+        if !context.supports_geometry_shaders() {
+            Err(CoreError::UnsupportedShaderStage(
+                shader_type_name(crate::context::GEOMETRY_SHADER).to_string(),
+            ))?;
+        }
+        Self::from_stages(
+            context,
+            330,
+            "#define USE_GEOMETRY_SHADER\n",
+            &[
+                (crate::context::VERTEX_SHADER, vertex_shader_source),
+                (crate::context::GEOMETRY_SHADER, geometry_shader_source),
+                (crate::context::FRAGMENT_SHADER, fragment_shader_source),
+            ],
+            None,
+        )
+    }
 
-            if !context.get_program_link_status(id) {
-                let log = context.get_shader_info_log(vert_shader);
-                if !log.is_empty() {
-                    Err(CoreError::ShaderCompilation(
-                        "vertex".to_string(),
-                        log,
-                        vertex_shader_source,
-                    ))?;
-                }
-                let log = context.get_shader_info_log(frag_shader);
-                if !log.is_empty() {
-                    Err(CoreError::ShaderCompilation(
-                        "fragment".to_string(),
-                        log,
-                        fragment_shader_source,
-                    ))?;
-                }
-                let log = context.get_program_info_log(id);
-                if !log.is_empty() {
-                    Err(CoreError::ShaderLink(log))?;
-                }
-                unreachable!();
-            }
+    fn from_stages(
+        context: &Context,
+        desktop_version: u32,
+        defines: &str,
+        stages: &[(u32, &str)],
+        patch_vertices: Option<u32>,
+    ) -> Result<Self, CoreError> {
+        PendingProgram::start(context, desktop_version, defines, stages, patch_vertices).finish()
+    }
 
-            context.detach_shader(id, vert_shader);
-            context.detach_shader(id, frag_shader);
-            context.delete_shader(vert_shader);
-            context.delete_shader(frag_shader);
+    ///
+    /// Starts compiling a new shader program from the given vertex and fragment glsl shader source without waiting for the compilation to finish.
+    /// The returned [PendingProgram] should be polled, for example once each frame, using [PendingProgram::is_ready]
+    /// and when it is ready, [PendingProgram::finish] returns the program without stalling.
+    ///
+    /// The pending program is currently always reported as ready, since the completion status of the `KHR_parallel_shader_compile` extension cannot be queried with the pinned version of glow.
+    /// Drivers which compile in the background still do so until [PendingProgram::finish] is called, which then waits for the compilation to finish.
+    ///
+    pub fn compile_async(
+        context: &Context,
+        vertex_shader_source: &str,
+        fragment_shader_source: &str,
+    ) -> PendingProgram {
+        PendingProgram::start(
+            context,
+            330,
+            "",
+            &[
+                (crate::context::VERTEX_SHADER, vertex_shader_source),
+                (crate::context::FRAGMENT_SHADER, fragment_shader_source),
+            ],
+            None,
+        )
+    }
 
+    fn from_linked(
+        context: &Context,
+        id: crate::context::Program,
+        patch_vertices: Option<u32>,
+        sources: Vec<(u32, String)>,
+    ) -> Result<Self, CoreError> {
+        unsafe {
             // Init vertex attributes
             let num_attribs = context.get_active_attributes(id);
             let mut attributes = HashMap::new();
+            let mut active_attributes = Vec::new();
             for i in 0..num_attribs {
-                if let Some(crate::context::ActiveAttribute { name, .. }) = context
+                if let Some(crate::context::ActiveAttribute { name, atype, size }) = context
                     .get_active_attribute(id, i)
                     .filter(|a| !a.name.starts_with("gl_"))
                 {
                     let location = context.get_attrib_location(id, &name).unwrap_or_else(|| {
                         panic!("Could not get the location of attribute {}", name)
                     });
+                    active_attributes.push(ActiveVariable {
+                        name: name.clone(),
+                        variable_type: atype,
+                        size: size as u32,
+                    });
                     attributes.insert(name, location);
                 }
             }
@@ -110,8 +186,9 @@ impl Program {
             // Init uniforms
             let num_uniforms = context.get_active_uniforms(id);
             let mut uniforms = HashMap::new();
+            let mut active_uniforms = Vec::new();
             for i in 0..num_uniforms {
-                if let Some(crate::context::ActiveUniform { name, .. }) = context
+                if let Some(crate::context::ActiveUniform { name, utype, size }) = context
                     .get_active_uniform(id, i)
                     .filter(|a| !a.name.starts_with("gl_"))
                 {
@@ -119,21 +196,62 @@ impl Program {
                         panic!("Could not get the location of uniform {}", name)
                     });
                     let name = name.split('[').next().unwrap().to_string();
+                    active_uniforms.push(ActiveVariable {
+                        name: name.clone(),
+                        variable_type: utype,
+                        size: size as u32,
+                    });
                     uniforms.insert(name, location);
                 }
             }
 
+            // Assign a fixed texture unit to each sampler, so textures used by different materials and effects never share a unit
+            let mut texture_units = HashMap::new();
+            let mut unit_count = 0;
+            context.use_program(Some(id));
+            for uniform in active_uniforms
+                .iter()
+                .filter(|u| is_sampler(u.variable_type))
+            {
+                let units = (unit_count..unit_count + uniform.size as i32).collect::<Vec<_>>();
+                context.uniform_1_i32_slice(uniforms.get(&uniform.name), &units);
+                texture_units.insert(uniform.name.clone(), unit_count as u32);
+                unit_count += uniform.size as i32;
+            }
+            context.use_program(None);
+            let max_units =
+                context.get_parameter_i32(crate::context::MAX_COMBINED_TEXTURE_IMAGE_UNITS);
+            if unit_count > max_units {
+                context.delete_program(id);
+                Err(CoreError::TooManyTextures(
+                    unit_count as usize,
+                    max_units as usize,
+                ))?;
+            }
+
             Ok(Program {
                 context: context.clone(),
                 id,
                 attributes,
                 uniforms,
                 uniform_blocks: RwLock::new(HashMap::new()),
-                textures: RwLock::new(HashMap::new()),
+                texture_units,
+                patch_vertices,
+                active_attributes,
+                active_uniforms,
+                nearest_sampler: RwLock::new(None),
+                sources,
             })
         }
     }
 
+    ///
+    /// Returns true if this program is using the tessellation stages, ie. if it is created using [Program::from_source_with_tessellation] and tessellation is supported.
+    ///
+    pub fn uses_tessellation(&self) -> bool {
+        self.patch_vertices.is_some()
+    }
+
     ///
     /// Send the given uniform data to this shader program and associate it with the given named variable.
     /// The glsl shader variable must be of type `uniform int` if the data is an integer, `uniform vec2` if it is of type [Vec2] etc.
@@ -144,7 +262,17 @@ impl Program {
     /// In the latter case the variable is removed by the shader compiler.
     ///
     pub fn use_uniform<T: UniformDataType>(&self, name: &str, data: T) {
+        #[cfg(debug_assertions)]
+        if let Err(e) = self.validate_uniform::<T>(name, 1) {
+            panic!("{}", e);
+        }
         let location = self.get_uniform_location(name);
+        self.record(|program| RenderCommand::Uniform {
+            program,
+            name: name.to_owned(),
+            data_type: T::data_type(),
+            data: T::to_bytes(std::slice::from_ref(&data)),
+        });
         T::send_uniform(&self.context, location, &[data]);
         self.unuse_program();
     }
@@ -168,11 +296,96 @@ impl Program {
     /// In the latter case the variable is removed by the shader compiler.
     ///
     pub fn use_uniform_array<T: UniformDataType>(&self, name: &str, data: &[T]) {
+        #[cfg(debug_assertions)]
+        if let Err(e) = self.validate_uniform::<T>(name, data.len()) {
+            panic!("{}", e);
+        }
         let location = self.get_uniform_location(name);
+        self.record(|program| RenderCommand::Uniform {
+            program,
+            name: name.to_owned(),
+            data_type: T::data_type(),
+            data: T::to_bytes(data),
+        });
         T::send_uniform(&self.context, location, data);
         self.unuse_program();
     }
 
+    ///
+    /// Same as [Program::use_uniform] except that it returns an error instead of panicking if the uniform is not used in the shader code
+    /// or if the type of the uniform in the shader code does not match the type of the given data.
+    ///
+    pub fn try_use_uniform<T: UniformDataType>(
+        &self,
+        name: &str,
+        data: T,
+    ) -> Result<(), CoreError> {
+        self.validate_uniform::<T>(name, 1)?;
+        self.use_uniform(name, data);
+        Ok(())
+    }
+
+    ///
+    /// Same as [Program::use_uniform_array] except that it returns an error instead of panicking if the uniform is not used in the shader code,
+    /// if the type of the uniform in the shader code does not match the type of the given data or if the array in the shader code is shorter than the given data.
+    ///
+    pub fn try_use_uniform_array<T: UniformDataType>(
+        &self,
+        name: &str,
+        data: &[T],
+    ) -> Result<(), CoreError> {
+        self.validate_uniform::<T>(name, data.len())?;
+        self.use_uniform_array(name, data);
+        Ok(())
+    }
+
+    fn validate_uniform<T: UniformDataType>(
+        &self,
+        name: &str,
+        count: usize,
+    ) -> Result<(), CoreError> {
+        let uniform = self
+            .active_uniforms
+            .iter()
+            .find(|u| u.name == name)
+            .ok_or_else(|| CoreError::MissingUniform(name.to_string()))?;
+        if let Some((kind, components)) = variable_kind(uniform.variable_type) {
+            if !kind.accepts(T::data_type()) || components != T::size() {
+                Err(CoreError::UniformTypeMismatch(
+                    name.to_string(),
+                    uniform.type_name().to_string(),
+                    format!(
+                        "{} components of type {}",
+                        T::size(),
+                        data_type_name(T::data_type())
+                    ),
+                ))?;
+            }
+        }
+        if count > uniform.size as usize {
+            Err(CoreError::UniformArrayLength(
+                name.to_string(),
+                uniform.size as usize,
+                count,
+            ))?;
+        }
+        Ok(())
+    }
+
+    ///
+    /// Returns the uniforms that are used in this program, ie. not removed by the shader compiler.
+    ///
+    pub fn active_uniforms(&self) -> &[ActiveVariable] {
+        &self.active_uniforms
+    }
+
+    ///
+    /// Returns the attributes that are used in this program, ie. not removed by the shader compiler.
+    ///
+    pub fn active_attributes(&self) -> &[ActiveVariable] {
+        &self.active_attributes
+    }
+
     fn get_uniform_location(&self, name: &str) -> &crate::context::UniformLocation {
         self.use_program();
         self.uniforms.get(name).unwrap_or_else(|| {
@@ -196,6 +409,15 @@ impl Program {
         texture.bind();
     }
 
+    ///
+    /// Calls [Self::use_texture] if [Self::requires_uniform] returns true.
+    ///
+    pub fn use_texture_if_required(&self, name: &str, texture: &Texture2D) {
+        if self.requires_uniform(name) {
+            self.use_texture(name, texture);
+        }
+    }
+
     ///
     /// Use the given [DepthTexture2D] in this shader program and associate it with the given named variable.
     /// The glsl shader variable must be of type `uniform sampler2D` and can only be accessed in the fragment shader.
@@ -286,18 +508,78 @@ impl Program {
     }
 
     fn use_texture_internal(&self, name: &str) -> u32 {
-        if !self.textures.read().unwrap().contains_key(name) {
-            let mut map = self.textures.write().unwrap();
-            let index = map.len() as u32;
-            map.insert(name.to_owned(), index);
-        };
-        let index = *self.textures.read().unwrap().get(name).unwrap();
-        self.use_uniform(name, index as i32);
+        let unit = self.texture_unit(name).unwrap_or_else(|| {
+            panic!(
+                "the texture {} is sent to the shader but not defined or never used",
+                name
+            )
+        });
         unsafe {
-            self.context
-                .active_texture(crate::context::TEXTURE0 + index);
+            self.context.active_texture(crate::context::TEXTURE0 + unit);
+        }
+        unit
+    }
+
+    ///
+    /// Overrides the filtering of all textures used by this program with nearest neighbour filtering, ie. no filtering, which is for example used for crisp pixel art.
+    /// This binds a sampler object to the texture units of the program, which also overrides the wrapping of the textures to [Wrapping::ClampToEdge].
+    /// Since the sampler objects affect all programs using the same texture units, the override should be disabled again after drawing.
+    ///
+    pub fn set_nearest_filtering(&self, enabled: bool) {
+        let sampler = if enabled {
+            let mut sampler = self.nearest_sampler.write().unwrap();
+            if sampler.is_none() {
+                unsafe {
+                    let id = self
+                        .context
+                        .create_sampler()
+                        .expect("Failed creating sampler");
+                    for (parameter, value) in [
+                        (crate::context::TEXTURE_MIN_FILTER, crate::context::NEAREST),
+                        (crate::context::TEXTURE_MAG_FILTER, crate::context::NEAREST),
+                        (
+                            crate::context::TEXTURE_WRAP_S,
+                            crate::context::CLAMP_TO_EDGE,
+                        ),
+                        (
+                            crate::context::TEXTURE_WRAP_T,
+                            crate::context::CLAMP_TO_EDGE,
+                        ),
+                        (
+                            crate::context::TEXTURE_WRAP_R,
+                            crate::context::CLAMP_TO_EDGE,
+                        ),
+                    ] {
+                        self.context
+                            .sampler_parameter_i32(id, parameter, value as i32);
+                    }
+                    *sampler = Some(id);
+                }
+            }
+            *sampler
+        } else {
+            None
+        };
+        let unit_count: u32 = self
+            .active_uniforms
+            .iter()
+            .filter(|u| is_sampler(u.variable_type))
+            .map(|u| u.size)
+            .sum();
+        for unit in 0..unit_count {
+            unsafe {
+                self.context.bind_sampler(unit, sampler);
+            }
         }
-        index
+    }
+
+    ///
+    /// Returns the texture unit assigned to the sampler with the given name or `None` if the sampler is not used in this program.
+    /// Each sampler in a program is assigned a unique texture unit when the program is created, for sampler arrays the elements are assigned consecutive units starting with the returned unit.
+    /// This is only needed when binding textures using low-level context calls, otherwise use the use_texture methods.
+    ///
+    pub fn texture_unit(&self, name: &str) -> Option<u32> {
+        self.texture_units.get(name).copied()
     }
 
     ///
@@ -334,7 +616,25 @@ impl Program {
     /// In the latter case the variable is removed by the shader compiler.
     ///
     pub fn use_vertex_attribute(&self, name: &str, buffer: &VertexBuffer) {
+        #[cfg(debug_assertions)]
+        if let Err(e) = self.validate_attribute(
+            name,
+            buffer.data_type(),
+            buffer.data_size(),
+            buffer.normalized(),
+        ) {
+            panic!("{}", e);
+        }
         if buffer.count() > 0 {
+            self.record(|program| RenderCommand::Attribute {
+                program,
+                name: name.to_owned(),
+                data_type: buffer.data_type(),
+                data_size: buffer.data_size(),
+                normalized: buffer.normalized(),
+                instanced: false,
+                data: buffer.read_back(),
+            });
             buffer.bind();
             let loc = self.location(name);
             unsafe {
@@ -382,7 +682,25 @@ impl Program {
     /// In the latter case the variable is removed by the shader compiler.
     ///
     pub fn use_instance_attribute(&self, name: &str, buffer: &InstanceBuffer) {
+        #[cfg(debug_assertions)]
+        if let Err(e) = self.validate_attribute(
+            name,
+            buffer.data_type(),
+            buffer.data_size(),
+            buffer.normalized(),
+        ) {
+            panic!("{}", e);
+        }
         if buffer.count() > 0 {
+            self.record(|program| RenderCommand::Attribute {
+                program,
+                name: name.to_owned(),
+                data_type: buffer.data_type(),
+                data_size: buffer.data_size(),
+                normalized: buffer.normalized(),
+                instanced: true,
+                data: buffer.read_back(),
+            });
             buffer.bind();
             let loc = self.location(name);
             unsafe {
@@ -420,6 +738,79 @@ impl Program {
         }
     }
 
+    ///
+    /// Same as [Program::use_vertex_attribute] except that it returns an error instead of panicking if the attribute is not used in the shader code
+    /// or if the type of the attribute in the shader code does not match the type of the data in the given buffer.
+    ///
+    pub fn try_use_vertex_attribute(
+        &self,
+        name: &str,
+        buffer: &VertexBuffer,
+    ) -> Result<(), CoreError> {
+        self.validate_attribute(
+            name,
+            buffer.data_type(),
+            buffer.data_size(),
+            buffer.normalized(),
+        )?;
+        self.use_vertex_attribute(name, buffer);
+        Ok(())
+    }
+
+    ///
+    /// Same as [Program::use_instance_attribute] except that it returns an error instead of panicking if the attribute is not used in the shader code
+    /// or if the type of the attribute in the shader code does not match the type of the data in the given buffer.
+    ///
+    pub fn try_use_instance_attribute(
+        &self,
+        name: &str,
+        buffer: &InstanceBuffer,
+    ) -> Result<(), CoreError> {
+        self.validate_attribute(
+            name,
+            buffer.data_type(),
+            buffer.data_size(),
+            buffer.normalized(),
+        )?;
+        self.use_instance_attribute(name, buffer);
+        Ok(())
+    }
+
+    fn validate_attribute(
+        &self,
+        name: &str,
+        data_type: u32,
+        data_size: u32,
+        normalized: bool,
+    ) -> Result<(), CoreError> {
+        let attribute = self
+            .active_attributes
+            .iter()
+            .find(|a| a.name == name)
+            .ok_or_else(|| CoreError::MissingAttribute(name.to_string()))?;
+        if let Some((kind, components)) = variable_kind(attribute.variable_type) {
+            // Integer data which is not normalized is sent as integers, everything else is converted to floats
+            let valid_kind = if normalized {
+                kind == VariableKind::Float
+            } else {
+                kind.accepts(data_type)
+            };
+            if !valid_kind || data_size > components {
+                Err(CoreError::AttributeTypeMismatch(
+                    name.to_string(),
+                    attribute.type_name().to_string(),
+                    format!(
+                        "{} {}components of type {}",
+                        data_size,
+                        if normalized { "normalized " } else { "" },
+                        data_type_name(data_type)
+                    ),
+                ))?;
+            }
+        }
+        Ok(())
+    }
+
     ///
     /// Draws `count` number of triangles with the given render states and viewport using this shader program.
     /// Requires that all attributes and uniforms have been defined using the use_attribute and use_uniform methods.
@@ -427,12 +818,20 @@ impl Program {
     /// If you want to use an [ElementBuffer], see [Program::draw_elements].
     ///
     pub fn draw_arrays(&self, render_states: RenderStates, viewport: Viewport, count: u32) {
+        self.record(|program| RenderCommand::Draw {
+            program,
+            render_states,
+            viewport,
+            elements: None,
+            first: 0,
+            count,
+            instance_count: None,
+        });
         self.context.set_viewport(viewport);
         self.context.set_render_states(render_states);
         self.use_program();
         unsafe {
-            self.context
-                .draw_arrays(crate::context::TRIANGLES, 0, count as i32);
+            self.context.draw_arrays(self.mode(), 0, count as i32);
             for location in self.attributes.values() {
                 self.context.disable_vertex_attrib_array(*location);
             }
@@ -457,16 +856,21 @@ impl Program {
         count: u32,
         instance_count: u32,
     ) {
+        self.record(|program| RenderCommand::Draw {
+            program,
+            render_states,
+            viewport,
+            elements: None,
+            first: 0,
+            count,
+            instance_count: Some(instance_count),
+        });
         self.context.set_viewport(viewport);
         self.context.set_render_states(render_states);
         self.use_program();
         unsafe {
-            self.context.draw_arrays_instanced(
-                crate::context::TRIANGLES,
-                0,
-                count as i32,
-                instance_count as i32,
-            );
+            self.context
+                .draw_arrays_instanced(self.mode(), 0, count as i32, instance_count as i32);
             self.context
                 .bind_buffer(crate::context::ELEMENT_ARRAY_BUFFER, None);
             for location in self.attributes.values() {
@@ -515,13 +919,22 @@ impl Program {
         first: u32,
         count: u32,
     ) {
+        self.record(|program| RenderCommand::Draw {
+            program,
+            render_states,
+            viewport,
+            elements: Some((element_buffer.data_type(), element_buffer.read_back())),
+            first,
+            count,
+            instance_count: None,
+        });
         self.context.set_viewport(viewport);
         self.context.set_render_states(render_states);
         self.use_program();
         element_buffer.bind();
         unsafe {
             self.context.draw_elements(
-                crate::context::TRIANGLES,
+                self.mode(),
                 count as i32,
                 element_buffer.data_type(),
                 first as i32,
@@ -576,13 +989,22 @@ impl Program {
         count: u32,
         instance_count: u32,
     ) {
+        self.record(|program| RenderCommand::Draw {
+            program,
+            render_states,
+            viewport,
+            elements: Some((element_buffer.data_type(), element_buffer.read_back())),
+            first,
+            count,
+            instance_count: Some(instance_count),
+        });
         self.context.set_viewport(viewport);
         self.context.set_render_states(render_states);
         self.use_program();
         element_buffer.bind();
         unsafe {
             self.context.draw_elements_instanced(
-                crate::context::TRIANGLES,
+                self.mode(),
                 count as i32,
                 element_buffer.data_type(),
                 first as i32,
@@ -627,6 +1049,138 @@ impl Program {
         })
     }
 
+    fn mode(&self) -> u32 {
+        if let Some(patch_vertices) = self.patch_vertices {
+            unsafe {
+                self.context
+                    .patch_parameter_i32(crate::context::PATCH_VERTICES, patch_vertices as i32);
+            }
+            crate::context::PATCHES
+        } else {
+            crate::context::TRIANGLES
+        }
+    }
+
+    fn record(&self, command: impl FnOnce(u32) -> RenderCommand) {
+        if let Some(recording) = self.context.recording.write().unwrap().as_mut() {
+            let program = recording.program_index(self.id, &self.sources, self.patch_vertices);
+            recording.push(command(program));
+        }
+    }
+
+    ///
+    /// Sends uniform data recorded by [Program::use_uniform] or [Program::use_uniform_array], which is converted to the type of the uniform in the shader.
+    ///
+    pub(in crate::core) fn use_recorded_uniform(&self, name: &str, data: &[f64]) {
+        let (location, variable_type) = match (
+            self.uniforms.get(name),
+            self.active_uniforms.iter().find(|u| u.name == name),
+        ) {
+            (Some(location), Some(uniform)) => (location, uniform.variable_type),
+            _ => return,
+        };
+        let floats = || data.iter().map(|v| *v as f32).collect::<Vec<_>>();
+        let ints = || data.iter().map(|v| *v as i32).collect::<Vec<_>>();
+        let uints = || data.iter().map(|v| *v as u32).collect::<Vec<_>>();
+        self.use_program();
+        unsafe {
+            let location = Some(location);
+            match variable_type {
+                crate::context::FLOAT => self.context.uniform_1_f32_slice(location, &floats()),
+                crate::context::FLOAT_VEC2 => self.context.uniform_2_f32_slice(location, &floats()),
+                crate::context::FLOAT_VEC3 => self.context.uniform_3_f32_slice(location, &floats()),
+                crate::context::FLOAT_VEC4 => self.context.uniform_4_f32_slice(location, &floats()),
+                crate::context::FLOAT_MAT2 => {
+                    self.context
+                        .uniform_matrix_2_f32_slice(location, false, &floats())
+                }
+                crate::context::FLOAT_MAT3 => {
+                    self.context
+                        .uniform_matrix_3_f32_slice(location, false, &floats())
+                }
+                crate::context::FLOAT_MAT4 => {
+                    self.context
+                        .uniform_matrix_4_f32_slice(location, false, &floats())
+                }
+                crate::context::INT | crate::context::BOOL => {
+                    self.context.uniform_1_i32_slice(location, &ints())
+                }
+                crate::context::INT_VEC2 | crate::context::BOOL_VEC2 => {
+                    self.context.uniform_2_i32_slice(location, &ints())
+                }
+                crate::context::INT_VEC3 | crate::context::BOOL_VEC3 => {
+                    self.context.uniform_3_i32_slice(location, &ints())
+                }
+                crate::context::INT_VEC4 | crate::context::BOOL_VEC4 => {
+                    self.context.uniform_4_i32_slice(location, &ints())
+                }
+                crate::context::UNSIGNED_INT => {
+                    self.context.uniform_1_u32_slice(location, &uints())
+                }
+                crate::context::UNSIGNED_INT_VEC2 => {
+                    self.context.uniform_2_u32_slice(location, &uints())
+                }
+                crate::context::UNSIGNED_INT_VEC3 => {
+                    self.context.uniform_3_u32_slice(location, &uints())
+                }
+                crate::context::UNSIGNED_INT_VEC4 => {
+                    self.context.uniform_4_u32_slice(location, &uints())
+                }
+                _ => {}
+            }
+        }
+        self.unuse_program();
+    }
+
+    ///
+    /// Uses attribute data recorded by [Program::use_vertex_attribute] or [Program::use_instance_attribute] which is stored in the given low-level buffer.
+    ///
+    pub(in crate::core) fn use_recorded_attribute(
+        &self,
+        name: &str,
+        buffer: crate::context::Buffer,
+        data_type: u32,
+        data_size: u32,
+        normalized: bool,
+        instanced: bool,
+    ) {
+        let loc = match self.attributes.get(name) {
+            Some(loc) => *loc,
+            None => return,
+        };
+        self.use_program();
+        unsafe {
+            self.context
+                .bind_buffer(crate::context::ARRAY_BUFFER, Some(buffer));
+            self.context.bind_vertex_array(Some(self.context.vao));
+            self.context.enable_vertex_attrib_array(loc);
+            if !normalized
+                && (data_type == crate::context::UNSIGNED_BYTE
+                    || data_type == crate::context::BYTE
+                    || data_type == crate::context::UNSIGNED_SHORT
+                    || data_type == crate::context::SHORT
+                    || data_type == crate::context::UNSIGNED_INT
+                    || data_type == crate::context::INT)
+            {
+                self.context
+                    .vertex_attrib_pointer_i32(loc, data_size as i32, data_type, 0, 0);
+            } else {
+                self.context.vertex_attrib_pointer_f32(
+                    loc,
+                    data_size as i32,
+                    data_type,
+                    normalized,
+                    0,
+                    0,
+                );
+            }
+            self.context
+                .vertex_attrib_divisor(loc, if instanced { 1 } else { 0 });
+            self.context.bind_buffer(crate::context::ARRAY_BUFFER, None);
+        }
+        self.unuse_program();
+    }
+
     fn use_program(&self) {
         unsafe {
             self.context.use_program(Some(self.id));
@@ -640,10 +1194,359 @@ impl Program {
     }
 }
 
+///
+/// A uniform or attribute variable which is used in a [Program], see [Program::active_uniforms] and [Program::active_attributes].
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ActiveVariable {
+    /// The name of the variable. For arrays, the name is without the brackets.
+    pub name: String,
+    /// The type of the variable, for example [FLOAT_VEC3](crate::context::FLOAT_VEC3).
+    pub variable_type: u32,
+    /// The number of elements in the array if the variable is an array, otherwise 1.
+    pub size: u32,
+}
+
+impl ActiveVariable {
+    ///
+    /// Returns the glsl name of the type of this variable, for example `vec3`.
+    ///
+    pub fn type_name(&self) -> &'static str {
+        match self.variable_type {
+            crate::context::FLOAT => "float",
+            crate::context::FLOAT_VEC2 => "vec2",
+            crate::context::FLOAT_VEC3 => "vec3",
+            crate::context::FLOAT_VEC4 => "vec4",
+            crate::context::FLOAT_MAT2 => "mat2",
+            crate::context::FLOAT_MAT3 => "mat3",
+            crate::context::FLOAT_MAT4 => "mat4",
+            crate::context::INT => "int",
+            crate::context::INT_VEC2 => "ivec2",
+            crate::context::INT_VEC3 => "ivec3",
+            crate::context::INT_VEC4 => "ivec4",
+            crate::context::UNSIGNED_INT => "uint",
+            crate::context::UNSIGNED_INT_VEC2 => "uvec2",
+            crate::context::UNSIGNED_INT_VEC3 => "uvec3",
+            crate::context::UNSIGNED_INT_VEC4 => "uvec4",
+            crate::context::BOOL => "bool",
+            crate::context::BOOL_VEC2 => "bvec2",
+            crate::context::BOOL_VEC3 => "bvec3",
+            crate::context::BOOL_VEC4 => "bvec4",
+            crate::context::SAMPLER_2D => "sampler2D",
+            crate::context::SAMPLER_3D => "sampler3D",
+            crate::context::SAMPLER_CUBE => "samplerCube",
+            crate::context::SAMPLER_2D_ARRAY => "sampler2DArray",
+            crate::context::SAMPLER_2D_SHADOW => "sampler2DShadow",
+            crate::context::SAMPLER_2D_ARRAY_SHADOW => "sampler2DArrayShadow",
+            crate::context::SAMPLER_CUBE_SHADOW => "samplerCubeShadow",
+            crate::context::INT_SAMPLER_2D => "isampler2D",
+            crate::context::UNSIGNED_INT_SAMPLER_2D => "usampler2D",
+            _ => "unknown",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum VariableKind {
+    Float,
+    Int,
+    UnsignedInt,
+    Bool,
+}
+
+impl VariableKind {
+    fn accepts(&self, data_type: u32) -> bool {
+        match data_type {
+            crate::context::FLOAT | crate::context::HALF_FLOAT => {
+                matches!(self, Self::Float | Self::Bool)
+            }
+            crate::context::BYTE | crate::context::SHORT | crate::context::INT => {
+                matches!(self, Self::Int | Self::Bool)
+            }
+            crate::context::UNSIGNED_BYTE
+            | crate::context::UNSIGNED_SHORT
+            | crate::context::UNSIGNED_INT => matches!(self, Self::UnsignedInt | Self::Bool),
+            _ => false,
+        }
+    }
+}
+
+///
+/// Returns the kind and number of components of the given variable type or `None` if the type is not validated.
+/// Samplers are integers since they are assigned the index of a texture unit.
+///
+fn variable_kind(variable_type: u32) -> Option<(VariableKind, u32)> {
+    Some(match variable_type {
+        crate::context::FLOAT => (VariableKind::Float, 1),
+        crate::context::FLOAT_VEC2 => (VariableKind::Float, 2),
+        crate::context::FLOAT_VEC3 => (VariableKind::Float, 3),
+        crate::context::FLOAT_VEC4 => (VariableKind::Float, 4),
+        crate::context::FLOAT_MAT2 => (VariableKind::Float, 4),
+        crate::context::FLOAT_MAT3 => (VariableKind::Float, 9),
+        crate::context::FLOAT_MAT4 => (VariableKind::Float, 16),
+        crate::context::INT => (VariableKind::Int, 1),
+        crate::context::INT_VEC2 => (VariableKind::Int, 2),
+        crate::context::INT_VEC3 => (VariableKind::Int, 3),
+        crate::context::INT_VEC4 => (VariableKind::Int, 4),
+        crate::context::UNSIGNED_INT => (VariableKind::UnsignedInt, 1),
+        crate::context::UNSIGNED_INT_VEC2 => (VariableKind::UnsignedInt, 2),
+        crate::context::UNSIGNED_INT_VEC3 => (VariableKind::UnsignedInt, 3),
+        crate::context::UNSIGNED_INT_VEC4 => (VariableKind::UnsignedInt, 4),
+        crate::context::BOOL => (VariableKind::Bool, 1),
+        crate::context::BOOL_VEC2 => (VariableKind::Bool, 2),
+        crate::context::BOOL_VEC3 => (VariableKind::Bool, 3),
+        crate::context::BOOL_VEC4 => (VariableKind::Bool, 4),
+        t if is_sampler(t) => (VariableKind::Int, 1),
+        _ => return None,
+    })
+}
+
+fn is_sampler(variable_type: u32) -> bool {
+    matches!(
+        variable_type,
+        crate::context::SAMPLER_2D
+            | crate::context::SAMPLER_3D
+            | crate::context::SAMPLER_CUBE
+            | crate::context::SAMPLER_2D_ARRAY
+            | crate::context::SAMPLER_2D_SHADOW
+            | crate::context::SAMPLER_2D_ARRAY_SHADOW
+            | crate::context::SAMPLER_CUBE_SHADOW
+            | crate::context::INT_SAMPLER_2D
+            | crate::context::INT_SAMPLER_3D
+            | crate::context::INT_SAMPLER_CUBE
+            | crate::context::INT_SAMPLER_2D_ARRAY
+            | crate::context::UNSIGNED_INT_SAMPLER_2D
+            | crate::context::UNSIGNED_INT_SAMPLER_3D
+            | crate::context::UNSIGNED_INT_SAMPLER_CUBE
+            | crate::context::UNSIGNED_INT_SAMPLER_2D_ARRAY
+    )
+}
+
+fn data_type_name(data_type: u32) -> &'static str {
+    match data_type {
+        crate::context::FLOAT => "f32",
+        crate::context::HALF_FLOAT => "f16",
+        crate::context::BYTE => "i8",
+        crate::context::SHORT => "i16",
+        crate::context::INT => "i32",
+        crate::context::UNSIGNED_BYTE => "u8",
+        crate::context::UNSIGNED_SHORT => "u16",
+        crate::context::UNSIGNED_INT => "u32",
+        _ => "unknown",
+    }
+}
+
+///
+/// A [Program] which is being compiled and linked, possibly in parallel with the rest of the application.
+/// Use [PendingProgram::is_ready] to check if the compilation is finished and [PendingProgram::finish] to get the program.
+/// See [Program::compile_async].
+///
+pub struct PendingProgram {
+    context: Context,
+    id: Option<crate::context::Program>,
+    shaders: Vec<(u32, crate::context::Shader, String)>,
+    patch_vertices: Option<u32>,
+    sources: Vec<(u32, String)>,
+}
+
+impl PendingProgram {
+    fn start(
+        context: &Context,
+        desktop_version: u32,
+        defines: &str,
+        stages: &[(u32, &str)],
+        patch_vertices: Option<u32>,
+    ) -> Self {
+        let sources = stages
+            .iter()
+            .map(|(shader_type, source)| {
+                (
+                    *shader_type,
+                    versioned_source(context, desktop_version, defines, source),
+                )
+            })
+            .collect::<Vec<_>>();
+        Self::start_with_versioned_sources(context, sources, patch_vertices)
+    }
+
+    ///
+    /// Starts compiling a program from sources which already contain the version header, for example the sources in a [FrameRecording].
+    ///
+    pub(in crate::core) fn start_with_versioned_sources(
+        context: &Context,
+        sources: Vec<(u32, String)>,
+        patch_vertices: Option<u32>,
+    ) -> Self {
+        unsafe {
+            let shaders = sources
+                .iter()
+                .cloned()
+                .map(|(shader_type, source)| {
+                    let shader = context.create_shader(shader_type).unwrap_or_else(|_| {
+                        panic!("Failed creating {} shader", shader_type_name(shader_type))
+                    });
+                    context.shader_source(shader, &source);
+                    context.compile_shader(shader);
+                    (shader_type, shader, source)
+                })
+                .collect::<Vec<_>>();
+
+            let id = context.create_program().expect("Failed creating program");
+            for (_, shader, _) in shaders.iter() {
+                context.attach_shader(id, *shader);
+            }
+            context.link_program(id);
+            Self {
+                context: context.clone(),
+                id: Some(id),
+                shaders,
+                patch_vertices,
+                sources,
+            }
+        }
+    }
+
+    ///
+    /// Returns true if the compilation and linking is finished, so [PendingProgram::finish] does not stall.
+    /// Currently always returns true, see [Program::compile_async].
+    ///
+    pub fn is_ready(&self) -> bool {
+        true
+    }
+
+    ///
+    /// Returns the compiled program or an error if the compilation or linking failed.
+    /// Waits for the compilation to finish if it is not already finished, see [PendingProgram::is_ready].
+    ///
+    pub fn finish(mut self) -> Result<Program, CoreError> {
+        let id = self.id.expect("the program is already finished");
+        if !self.shaders.is_empty() {
+            unsafe {
+                if !self.context.get_program_link_status(id) {
+                    for (shader_type, shader, source) in self.shaders.iter() {
+                        let log = self.context.get_shader_info_log(*shader);
+                        if !log.is_empty() {
+                            Err(CoreError::ShaderCompilation(
+                                shader_type_name(*shader_type).to_string(),
+                                log,
+                                source.clone(),
+                            ))?;
+                        }
+                    }
+                    let log = self.context.get_program_info_log(id);
+                    if !log.is_empty() {
+                        Err(CoreError::ShaderLink(log))?;
+                    }
+                    unreachable!();
+                }
+
+                for (_, shader, _) in self.shaders.drain(..) {
+                    self.context.detach_shader(id, shader);
+                    self.context.delete_shader(shader);
+                }
+            }
+        }
+        self.id = None;
+        Program::from_linked(
+            &self.context,
+            id,
+            self.patch_vertices,
+            std::mem::take(&mut self.sources),
+        )
+    }
+}
+
+impl Drop for PendingProgram {
+    fn drop(&mut self) {
+        if let Some(id) = self.id.take() {
+            unsafe {
+                for (_, shader, _) in self.shaders.drain(..) {
+                    self.context.detach_shader(id, shader);
+                    self.context.delete_shader(shader);
+                }
+                self.context.delete_program(id);
+            }
+        }
+    }
+}
+
+///
+/// Returns the given shader source with a version header matching the platform of the given context.
+/// On desktop, the given version is used, otherwise `#version 300 es` is used together with default precision qualifiers for all types without a default precision.
+///
+fn versioned_source(
+    context: &Context,
+    desktop_version: u32,
+    defines: &str,
+    source: &str,
+) -> String {
+    let mut output = if context.version().is_embedded {
+        "#version 300 es\n".to_string()
+    } else {
+        format!("#version {} core\n", desktop_version)
+    };
+    // The extension directives must come before anything other than the version directive
+    let mut body = String::with_capacity(source.len());
+    for line in source.lines() {
+        let directive = line.trim_start();
+        if directive.starts_with("#version") {
+            continue;
+        } else if directive.starts_with("#extension") {
+            output.push_str(directive);
+            output.push('\n');
+        } else {
+            body.push_str(line);
+            body.push('\n');
+        }
+    }
+    if context.version().is_embedded {
+        output.push_str(
+            "#ifdef GL_FRAGMENT_PRECISION_HIGH
+    precision highp float;
+    precision highp int;
+    precision highp sampler2DArray;
+    precision highp sampler3D;
+    precision highp sampler2DShadow;
+    precision highp sampler2DArrayShadow;
+    precision highp samplerCubeShadow;
+    precision highp usampler2D;
+    precision highp isampler2D;
+#else
+    precision mediump float;
+    precision mediump int;
+    precision mediump sampler2DArray;
+    precision mediump sampler3D;
+    precision mediump sampler2DShadow;
+    precision mediump sampler2DArrayShadow;
+    precision mediump samplerCubeShadow;
+    precision mediump usampler2D;
+    precision mediump isampler2D;
+#endif\n",
+        );
+    }
+    output.push_str(defines);
+    output.push_str(&body);
+    output
+}
+
+fn shader_type_name(shader_type: u32) -> &'static str {
+    match shader_type {
+        crate::context::VERTEX_SHADER => "vertex",
+        crate::context::TESS_CONTROL_SHADER => "tessellation control",
+        crate::context::TESS_EVALUATION_SHADER => "tessellation evaluation",
+        crate::context::GEOMETRY_SHADER => "geometry",
+        crate::context::FRAGMENT_SHADER => "fragment",
+        _ => "unknown",
+    }
+}
+
 impl Drop for Program {
     fn drop(&mut self) {
         unsafe {
             self.context.delete_program(self.id);
+            if let Some(sampler) = *self.nearest_sampler.read().unwrap() {
+                self.context.delete_sampler(sampler);
+            }
         }
     }
 }
@@ -26,6 +26,14 @@ mod depth_target_multisample;
 #[doc(inline)]
 pub use depth_target_multisample::*;
 
+mod readback;
+#[doc(inline)]
+pub use readback::*;
+
+mod render_target_cube_map;
+#[doc(inline)]
+pub use render_target_cube_map::*;
+
 use crate::core::*;
 
 use crate::context::Framebuffer;
@@ -133,6 +141,22 @@ impl<'a> RenderTarget<'a> {
         self.read_color_partially(self.scissor_box())
     }
 
+    ///
+    /// Returns the colors of the pixels in this render target as an 8-bit RGBA image where the first pixel is the top left pixel.
+    /// The image can for example be saved as a PNG file using [three_d_asset::io::Serialize] with the `png` feature of `three-d-asset` enabled,
+    /// which makes it possible to save renders made with a headless context without any display.
+    ///
+    /// **Note:** On web, the color texture needs to have the data format `[u8; 4]`.
+    ///
+    pub fn read_color_to_image(&self) -> CpuTexture {
+        CpuTexture {
+            data: TextureData::RgbaU8(self.read_color()),
+            width: self.width,
+            height: self.height,
+            ..Default::default()
+        }
+    }
+
     ///
     /// Returns the colors of the pixels in this render target inside the given scissor box.
     /// The number of channels per pixel and the data format for each channel is specified by the generic parameter.
@@ -145,13 +169,7 @@ impl<'a> RenderTarget<'a> {
         }
         self.bind(crate::context::DRAW_FRAMEBUFFER);
         self.bind(crate::context::READ_FRAMEBUFFER);
-        let mut data_size = std::mem::size_of::<T>();
-        // On web, the format needs to be RGBA if the data type is byte.
-        if data_size / T::size() as usize == 1 {
-            data_size *= 4 / T::size() as usize
-        }
-        let mut bytes =
-            vec![0u8; scissor_box.width as usize * scissor_box.height as usize * data_size];
+        let mut bytes = vec![0u8; read_byte_size::<T>(scissor_box)];
         unsafe {
             self.context.read_pixels(
                 scissor_box.x,
@@ -172,6 +190,41 @@ impl<'a> RenderTarget<'a> {
         pixels
     }
 
+    ///
+    /// Starts reading the colors of the pixels in this render target without waiting for the GPU to finish rendering.
+    /// The returned [PendingReadback] can be polled later, for example in the next frame, to get the same data as returned by [RenderTarget::read_color].
+    /// This avoids stalling the pipeline, for example when reading back object ids for picking or when taking screenshots.
+    ///
+    /// **Note:** On web, the data format needs to match the data format of the color texture.
+    ///
+    pub fn read_color_async<T: TextureDataType>(&self) -> PendingReadback<T> {
+        self.read_color_partially_async(self.scissor_box())
+    }
+
+    ///
+    /// Starts reading the colors of the pixels in this render target inside the given scissor box without waiting for the GPU to finish rendering.
+    /// See [RenderTarget::read_color_async] for more information.
+    ///
+    /// **Note:** On web, the data format needs to match the data format of the color texture.
+    ///
+    pub fn read_color_partially_async<T: TextureDataType>(
+        &self,
+        scissor_box: ScissorBox,
+    ) -> PendingReadback<T> {
+        if self.id.is_some() && self.color.is_none() {
+            panic!("cannot read color from a render target without a color target");
+        }
+        self.bind(crate::context::DRAW_FRAMEBUFFER);
+        self.bind(crate::context::READ_FRAMEBUFFER);
+        PendingReadback::new(
+            &self.context,
+            scissor_box,
+            format_from_data_type::<T>(),
+            T::data_type(),
+            read_byte_size::<T>(scissor_box),
+        )
+    }
+
     ///
     /// Returns the depth values in this render target.
     ///
@@ -205,6 +258,61 @@ impl<'a> RenderTarget<'a> {
         from_byte_slice(&pixels).to_vec()
     }
 
+    ///
+    /// Returns the linear depth in the given pixel, ie. the distance from the camera to the rendered geometry along the view direction of the given camera,
+    /// which should be the camera used when rendering to this render target.
+    /// The pixel coordinate must be in physical pixels, where (0, 0) is the bottom left corner of this render target.
+    /// Returns `None` if nothing was rendered in the pixel, ie. if the depth is at the far plane.
+    ///
+    /// Also see [RenderTarget::read_position_at] to get the position in world space.
+    ///
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn read_depth_at(
+        &self,
+        camera: &three_d_asset::Camera,
+        pixel: impl Into<three_d_asset::PixelPoint>,
+    ) -> Option<f32> {
+        let pixel = pixel.into();
+        let depth = self.read_depth_partially(ScissorBox {
+            x: pixel.x as i32,
+            y: pixel.y as i32,
+            width: 1,
+            height: 1,
+        })[0];
+        if depth >= 1.0 {
+            return None;
+        }
+        let (z_near, z_far) = (camera.z_near(), camera.z_far());
+        Some(match camera.projection_type() {
+            three_d_asset::ProjectionType::Perspective { .. } => {
+                let z = 2.0 * depth - 1.0;
+                2.0 * z_near * z_far / (z_far + z_near - z * (z_far - z_near))
+            }
+            three_d_asset::ProjectionType::Orthographic { .. } => z_near + depth * (z_far - z_near),
+        })
+    }
+
+    ///
+    /// Returns the world space position of the rendered geometry in the given pixel using the depth in this render target and the given camera,
+    /// which should be the camera used when rendering to this render target.
+    /// The pixel coordinate must be in physical pixels, where (0, 0) is the bottom left corner of this render target.
+    /// Returns `None` if nothing was rendered in the pixel, ie. if the depth is at the far plane.
+    ///
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn read_position_at(
+        &self,
+        camera: &three_d_asset::Camera,
+        pixel: impl Into<three_d_asset::PixelPoint>,
+    ) -> Option<Vec3> {
+        let pixel = pixel.into();
+        let depth = self.read_depth_at(camera, pixel)?;
+        let direction = camera.view_direction_at_pixel(pixel);
+        Some(
+            camera.position_at_pixel(pixel)
+                + direction * depth / direction.dot(camera.view_direction()),
+        )
+    }
+
     ///
     /// Creates a [RenderTarget] with the given low-level [Framebuffer]. Should only be used if the [Framebuffer] is used for something else, ie. to be able
     /// to combine this crate with functionality of another crate. Also see [Self::into_framebuffer].
@@ -334,12 +442,7 @@ fn new_framebuffer(context: &Context) -> crate::context::Framebuffer {
 
 #[cfg(debug_assertions)]
 fn multisample_sanity_check(context: &Context, number_of_samples: u32) {
-    let max_samples: u32 = unsafe {
-        context
-            .get_parameter_i32(crate::context::MAX_SAMPLES)
-            .try_into()
-            .unwrap()
-    };
+    let max_samples = context.max_samples();
     if number_of_samples > max_samples {
         panic!("number_of_samples ({}) for multisample target is larger than supported number of samples: {}", number_of_samples, max_samples);
     }
@@ -401,3 +504,12 @@ impl_render_target_core_extensions!(
 );
 impl_render_target_core_extensions!(ColorTargetMultisample<C: TextureDataType>);
 impl_render_target_core_extensions!(DepthTargetMultisample<D: DepthTextureDataType>);
+
+fn read_byte_size<T: TextureDataType>(scissor_box: ScissorBox) -> usize {
+    let mut data_size = std::mem::size_of::<T>();
+    // On web, the format needs to be RGBA if the data type is byte.
+    if data_size / T::size() as usize == 1 {
+        data_size *= 4 / T::size() as usize
+    }
+    scissor_box.width as usize * scissor_box.height as usize * data_size
+}
@@ -126,6 +126,30 @@ impl<'a> ColorTarget<'a> {
         self.as_render_target().read_color_partially(scissor_box)
     }
 
+    ///
+    /// Starts reading the colors of the pixels in this color target without waiting for the GPU to finish rendering.
+    /// See [RenderTarget::read_color_async] for more information.
+    ///
+    /// **Note:** On web, the data format needs to match the data format of the color texture.
+    ///
+    pub fn read_async<T: TextureDataType>(&self) -> PendingReadback<T> {
+        self.read_partially_async(self.scissor_box())
+    }
+
+    ///
+    /// Starts reading the colors of the pixels in this color target inside the given scissor box without waiting for the GPU to finish rendering.
+    /// See [RenderTarget::read_color_async] for more information.
+    ///
+    /// **Note:** On web, the data format needs to match the data format of the color texture.
+    ///
+    pub fn read_partially_async<T: TextureDataType>(
+        &self,
+        scissor_box: ScissorBox,
+    ) -> PendingReadback<T> {
+        self.as_render_target()
+            .read_color_partially_async(scissor_box)
+    }
+
     ///
     /// Returns the width of the color target in texels.
     /// If using the zero mip level of the underlying texture, then this is simply the width of that texture, otherwise it is the width of the given mip level.
@@ -0,0 +1,133 @@
+use crate::core::*;
+use std::marker::PhantomData;
+
+///
+/// A non-blocking read of pixels from a render target, see [RenderTarget::read_color_async].
+///
+/// The pixels are copied into a pixel buffer on the GPU when this is created, so reading from a render target does not stall until the GPU has finished rendering.
+/// Use [PendingReadback::try_read] to poll for the data, typically once each frame, or [PendingReadback::read] to wait for it.
+///
+pub struct PendingReadback<T: TextureDataType> {
+    context: Context,
+    buffer: crate::context::Buffer,
+    fence: Option<crate::context::Fence>,
+    width: u32,
+    height: u32,
+    byte_size: usize,
+    _data: PhantomData<T>,
+}
+
+impl<T: TextureDataType> PendingReadback<T> {
+    pub(in crate::core) fn new(
+        context: &Context,
+        scissor_box: ScissorBox,
+        format: u32,
+        data_type: u32,
+        byte_size: usize,
+    ) -> Self {
+        unsafe {
+            let buffer = context
+                .create_buffer()
+                .expect("Failed creating pixel buffer");
+            context.bind_buffer(crate::context::PIXEL_PACK_BUFFER, Some(buffer));
+            context.buffer_data_size(
+                crate::context::PIXEL_PACK_BUFFER,
+                byte_size as i32,
+                crate::context::STREAM_READ,
+            );
+            context.read_pixels(
+                scissor_box.x,
+                scissor_box.y,
+                scissor_box.width as i32,
+                scissor_box.height as i32,
+                format,
+                data_type,
+                crate::context::PixelPackData::BufferOffset(0),
+            );
+            context.bind_buffer(crate::context::PIXEL_PACK_BUFFER, None);
+            let fence = context
+                .fence_sync(crate::context::SYNC_GPU_COMMANDS_COMPLETE, 0)
+                .ok();
+            context.flush();
+            Self {
+                context: context.clone(),
+                buffer,
+                fence,
+                width: scissor_box.width,
+                height: scissor_box.height,
+                byte_size,
+                _data: PhantomData,
+            }
+        }
+    }
+
+    ///
+    /// Returns whether or not the GPU has finished copying the pixels, in which case [PendingReadback::try_read] returns the data without stalling.
+    ///
+    pub fn is_ready(&self) -> bool {
+        match self.fence {
+            Some(fence) => {
+                let status = unsafe { self.context.client_wait_sync(fence, 0, 0) };
+                status == crate::context::ALREADY_SIGNALED
+                    || status == crate::context::CONDITION_SATISFIED
+            }
+            None => true,
+        }
+    }
+
+    ///
+    /// Returns the pixels if the GPU has finished copying them, otherwise `None`.
+    /// The data has the same layout as the data returned by [RenderTarget::read_color].
+    ///
+    pub fn try_read(&self) -> Option<Vec<T>> {
+        if self.is_ready() {
+            Some(self.read_buffer())
+        } else {
+            None
+        }
+    }
+
+    ///
+    /// Returns the pixels, waiting for the GPU to finish copying them if necessary.
+    /// The data has the same layout as the data returned by [RenderTarget::read_color].
+    ///
+    pub fn read(self) -> Vec<T> {
+        self.read_buffer()
+    }
+
+    /// The width of the area that is read.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// The height of the area that is read.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    fn read_buffer(&self) -> Vec<T> {
+        let mut bytes = vec![0u8; self.byte_size];
+        unsafe {
+            self.context
+                .bind_buffer(crate::context::PIXEL_PACK_BUFFER, Some(self.buffer));
+            self.context
+                .get_buffer_sub_data(crate::context::PIXEL_PACK_BUFFER, 0, &mut bytes);
+            self.context
+                .bind_buffer(crate::context::PIXEL_PACK_BUFFER, None);
+        }
+        let mut pixels = from_byte_slice(&bytes).to_vec();
+        flip_y(&mut pixels, self.width as usize, self.height as usize);
+        pixels
+    }
+}
+
+impl<T: TextureDataType> Drop for PendingReadback<T> {
+    fn drop(&mut self) {
+        unsafe {
+            if let Some(fence) = self.fence {
+                self.context.delete_sync(fence);
+            }
+            self.context.delete_buffer(self.buffer);
+        }
+    }
+}
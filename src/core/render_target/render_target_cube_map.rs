@@ -0,0 +1,127 @@
+use crate::core::*;
+
+///
+/// A [TextureCubeMap] together with the depth textures needed to render into each side and mip level of the cube map,
+/// for example when rendering the surroundings of a position for dynamic reflections or light probes.
+/// Use [RenderTargetCubeMap::as_render_target] to get a [RenderTarget] for a side and mip level and render into it using a camera
+/// with a field of view of 90 degrees looking in the [CubeMapSide::direction] with the [CubeMapSide::up] direction.
+///
+/// To render into the layers of a [Texture2DArray], for example the cascades of a cascaded shadow map, combine [Texture2DArray::as_color_target]
+/// and [DepthTexture2DArray::as_depth_target] using [RenderTarget::new] instead.
+///
+pub struct RenderTargetCubeMap {
+    context: Context,
+    texture: TextureCubeMap,
+    depth_textures: Vec<Option<DepthTexture2D>>,
+}
+
+impl RenderTargetCubeMap {
+    ///
+    /// Creates a new render target with an empty cube map with the given size in texels of each side.
+    /// The format of the cube map is determined by the generic [TextureDataType] parameter.
+    /// If a mip map filter is specified, mip maps are generated each time the zero mip level of a side has been written to.
+    ///
+    pub fn new<T: TextureDataType>(
+        context: &Context,
+        size: u32,
+        mip_map_filter: Option<Interpolation>,
+    ) -> Self {
+        let texture = TextureCubeMap::new_empty::<T>(
+            context,
+            size,
+            size,
+            Interpolation::Linear,
+            Interpolation::Linear,
+            mip_map_filter,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+        Self {
+            context: context.clone(),
+            depth_textures: (0..texture.number_of_mip_maps()).map(|_| None).collect(),
+            texture,
+        }
+    }
+
+    ///
+    /// The size in texels of each side of the cube map.
+    ///
+    pub fn size(&self) -> u32 {
+        self.texture.width()
+    }
+
+    ///
+    /// Returns the cube map which is rendered into.
+    ///
+    pub fn texture(&self) -> &TextureCubeMap {
+        &self.texture
+    }
+
+    ///
+    /// Returns the cube map which is rendered into, consuming the render target.
+    ///
+    pub fn into_texture(self) -> TextureCubeMap {
+        self.texture
+    }
+
+    ///
+    /// Returns a [RenderTarget] which can be used to clear, write to and read from the given side of the cube map.
+    /// If `None` is specified as the mip level, the zero mip level is used and mip maps are generated after a write operation if a mip map filter was specified at construction.
+    /// Otherwise, the given mip level is used and no mip maps are generated.
+    ///
+    /// # Panic
+    /// If the given mip level does not exist.
+    ///
+    pub fn as_render_target(
+        &mut self,
+        side: CubeMapSide,
+        mip_level: Option<u32>,
+    ) -> RenderTarget<'_> {
+        let level = mip_level.unwrap_or(0);
+        if level >= self.texture.number_of_mip_maps() {
+            panic!(
+                "the cube map does not have the mip level {}, the number of mip levels is {}",
+                level,
+                self.texture.number_of_mip_maps()
+            );
+        }
+        let size = self.texture.width() >> level;
+        let context = &self.context;
+        let depth_texture = self.depth_textures[level as usize].get_or_insert_with(|| {
+            DepthTexture2D::new::<f32>(
+                context,
+                size,
+                size,
+                Wrapping::ClampToEdge,
+                Wrapping::ClampToEdge,
+            )
+        });
+        RenderTarget::new(
+            self.texture.as_color_target(side_slice(side), mip_level),
+            depth_texture.as_depth_target(),
+        )
+    }
+}
+
+impl RenderTargetCubeMap {
+    ///
+    /// Generates the mip maps of the cube map from the zero mip level, if a mip map filter was specified at construction.
+    /// Use this after rendering into the zero mip level of the sides using `Some(0)` as the mip level in [RenderTargetCubeMap::as_render_target],
+    /// to only generate the mip maps once after all of the sides have been rendered.
+    ///
+    pub fn generate_mip_maps(&mut self) {
+        self.texture.generate_mip_maps();
+    }
+}
+
+fn side_slice(side: CubeMapSide) -> &'static [CubeMapSide] {
+    match side {
+        CubeMapSide::Right => &[CubeMapSide::Right],
+        CubeMapSide::Left => &[CubeMapSide::Left],
+        CubeMapSide::Top => &[CubeMapSide::Top],
+        CubeMapSide::Bottom => &[CubeMapSide::Bottom],
+        CubeMapSide::Front => &[CubeMapSide::Front],
+        CubeMapSide::Back => &[CubeMapSide::Back],
+    }
+}
@@ -0,0 +1,220 @@
+use crate::core::*;
+
+///
+/// A joint (also called a bone) in a [Skeleton].
+///
+#[derive(Clone, Debug)]
+pub struct Joint {
+    /// The name of the joint, for example the node name in a glTF file.
+    pub name: String,
+    /// The index of the parent joint in the list of joints in the skeleton or `None` if this is a root joint.
+    pub parent: Option<usize>,
+    /// The transformation of the joint relative to the parent joint (or the model space if this is a root joint) in the rest pose.
+    pub rest_transformation: Mat4,
+    /// The transformation from model space to the local space of the joint in the bind pose, ie. the pose the mesh is modelled in.
+    pub inverse_bind_matrix: Mat4,
+}
+
+///
+/// A hierarchy of [Joint]s used for skeletal animation of a mesh, see [SkinnedMesh](crate::renderer::SkinnedMesh).
+/// A pose of the skeleton is given as a local transformation for each joint, for example evaluated from an [AnimationClip],
+/// which is then converted into the joint matrices used for skinning by [Skeleton::joint_matrices].
+///
+#[derive(Clone, Debug)]
+pub struct Skeleton {
+    joints: Vec<Joint>,
+}
+
+impl Skeleton {
+    ///
+    /// Constructs a new skeleton from the given joints.
+    ///
+    /// # Panics
+    /// If a joint has a parent which is not placed before the joint itself in the list of joints.
+    ///
+    pub fn new(joints: Vec<Joint>) -> Self {
+        for (index, joint) in joints.iter().enumerate() {
+            if let Some(parent) = joint.parent {
+                assert!(
+                    parent < index,
+                    "the parent of the joint {} must be placed before the joint",
+                    joint.name
+                );
+            }
+        }
+        Self { joints }
+    }
+
+    ///
+    /// Returns the joints of this skeleton.
+    ///
+    pub fn joints(&self) -> &[Joint] {
+        &self.joints
+    }
+
+    ///
+    /// Returns the index of the joint with the given name or `None` if the skeleton does not contain a joint with that name.
+    ///
+    pub fn joint_index(&self, name: &str) -> Option<usize> {
+        self.joints.iter().position(|joint| joint.name == name)
+    }
+
+    ///
+    /// Returns the rest pose, ie. the local transformation of each joint when the skeleton is not animated.
+    ///
+    pub fn rest_pose(&self) -> Vec<Mat4> {
+        self.joints
+            .iter()
+            .map(|joint| joint.rest_transformation)
+            .collect()
+    }
+
+    ///
+    /// Returns the transformation of each joint in model space given the local transformation of each joint in the given pose.
+    ///
+    pub fn global_transformations(&self, pose: &[Mat4]) -> Vec<Mat4> {
+        let mut transformations: Vec<Mat4> = Vec::with_capacity(self.joints.len());
+        for (index, joint) in self.joints.iter().enumerate() {
+            let local = pose
+                .get(index)
+                .copied()
+                .unwrap_or(joint.rest_transformation);
+            let global = match joint.parent {
+                Some(parent) => transformations[parent] * local,
+                None => local,
+            };
+            transformations.push(global);
+        }
+        transformations
+    }
+
+    ///
+    /// Returns the joint matrices used for skinning, ie. the transformation from the bind pose to the given pose in model space for each joint.
+    ///
+    pub fn joint_matrices(&self, pose: &[Mat4]) -> Vec<Mat4> {
+        self.global_transformations(pose)
+            .into_iter()
+            .zip(self.joints.iter())
+            .map(|(global, joint)| global * joint.inverse_bind_matrix)
+            .collect()
+    }
+}
+
+///
+/// The key frames of the local transformation of a single joint in an [AnimationClip].
+/// Translations and scales are linearly interpolated and rotations are spherically interpolated between the key frames.
+///
+#[derive(Clone, Debug)]
+pub struct JointTrack {
+    /// The index of the animated joint in the [Skeleton].
+    pub joint: usize,
+    /// The time of each key frame in seconds in increasing order.
+    pub times: Vec<f32>,
+    /// The translation at each key frame or `None` if the translation of the rest pose is used.
+    pub translations: Option<Vec<Vec3>>,
+    /// The rotation at each key frame or `None` if the rotation of the rest pose is used.
+    pub rotations: Option<Vec<Quat>>,
+    /// The non-uniform scale at each key frame or `None` if the scale of the rest pose is used.
+    pub scales: Option<Vec<Vec3>>,
+}
+
+impl JointTrack {
+    fn sample_index(&self, time: f32) -> (usize, usize, f32) {
+        let last = self.times.len().saturating_sub(1);
+        match self.times.iter().position(|t| *t > time) {
+            Some(0) => (0, 0, 0.0),
+            Some(next) => {
+                let t0 = self.times[next - 1];
+                let t1 = self.times[next];
+                (next - 1, next, (time - t0) / (t1 - t0).max(f32::EPSILON))
+            }
+            None => (last, last, 0.0),
+        }
+    }
+
+    fn transformation(&self, time: f32, rest: Mat4) -> Mat4 {
+        if self.times.is_empty() {
+            return rest;
+        }
+        let (i0, i1, t) = self.sample_index(time);
+        let (rest_translation, rest_rotation, rest_scale) = decompose(rest);
+        let translation = self
+            .translations
+            .as_ref()
+            .map(|v| v[i0].lerp(v[i1], t))
+            .unwrap_or(rest_translation);
+        let rotation = self
+            .rotations
+            .as_ref()
+            .map(|v| v[i0].slerp(v[i1], t))
+            .unwrap_or(rest_rotation);
+        let scale = self
+            .scales
+            .as_ref()
+            .map(|v| v[i0].lerp(v[i1], t))
+            .unwrap_or(rest_scale);
+        Mat4::from_translation(translation)
+            * Mat4::from(rotation)
+            * Mat4::from_nonuniform_scale(scale.x, scale.y, scale.z)
+    }
+}
+
+///
+/// A named skeletal animation consisting of a [JointTrack] for each animated joint in a [Skeleton].
+///
+#[derive(Clone, Debug)]
+pub struct AnimationClip {
+    /// The name of the animation.
+    pub name: String,
+    /// The key frames for each animated joint.
+    pub tracks: Vec<JointTrack>,
+    /// Whether or not the animation is repeated when the time exceeds the duration of the animation.
+    pub looping: bool,
+}
+
+impl AnimationClip {
+    ///
+    /// Returns the duration of the animation in seconds, ie. the time of the last key frame.
+    ///
+    pub fn duration(&self) -> f32 {
+        self.tracks
+            .iter()
+            .filter_map(|track| track.times.last().copied())
+            .fold(0.0, f32::max)
+    }
+
+    ///
+    /// Evaluates the animation at the given time in seconds and returns the local transformation of each joint in the given skeleton.
+    /// Joints without a track use the transformation of the rest pose.
+    ///
+    pub fn pose(&self, skeleton: &Skeleton, time: f32) -> Vec<Mat4> {
+        let duration = self.duration();
+        let time = if self.looping && duration > 0.0 {
+            time.rem_euclid(duration)
+        } else {
+            time
+        };
+        let mut pose = skeleton.rest_pose();
+        for track in self.tracks.iter() {
+            if let Some(local) = pose.get_mut(track.joint) {
+                *local = track.transformation(time, *local);
+            }
+        }
+        pose
+    }
+}
+
+fn decompose(transformation: Mat4) -> (Vec3, Quat, Vec3) {
+    let translation = transformation.w.truncate();
+    let scale = vec3(
+        transformation.x.truncate().magnitude(),
+        transformation.y.truncate().magnitude(),
+        transformation.z.truncate().magnitude(),
+    );
+    let rotation = Mat3::from_cols(
+        transformation.x.truncate() / scale.x.max(f32::EPSILON),
+        transformation.y.truncate() / scale.y.max(f32::EPSILON),
+        transformation.z.truncate() / scale.z.max(f32::EPSILON),
+    );
+    (translation, Quat::from(rotation), scale)
+}
@@ -263,7 +263,7 @@ impl DepthTexture<'_> {
         match self {
             Self::Single(texture) => program.use_depth_texture("depthMap", texture),
             Self::Array { texture, layer } => {
-                program.use_uniform("depthLayer", layer);
+                program.use_uniform("depthLayer", *layer as i32);
                 program.use_depth_texture_array("depthMap", texture);
             }
             Self::CubeMap { .. } => todo!(),
@@ -349,6 +349,31 @@ fn set_parameters(
     }
 }
 
+///
+/// Returns the number of bytes allocated for a texture, where the depth is the number of layers, sides or samples unless the depth is also reduced for each mip map as for a 3D texture.
+///
+fn texture_byte_size(
+    width: u32,
+    height: u32,
+    depth: u32,
+    reduce_depth: bool,
+    number_of_mip_maps: u32,
+    texel_byte_size: usize,
+) -> usize {
+    (0..number_of_mip_maps)
+        .map(|level| {
+            let w = (width >> level).max(1) as usize;
+            let h = (height >> level).max(1) as usize;
+            let d = if reduce_depth {
+                (depth >> level).max(1)
+            } else {
+                depth
+            } as usize;
+            w * h * d * texel_byte_size
+        })
+        .sum()
+}
+
 fn calculate_number_of_mip_maps(
     mip_map_filter: Option<Interpolation>,
     width: u32,
@@ -6,6 +6,7 @@ use crate::core::texture::*;
 pub struct DepthTexture2D {
     context: Context,
     id: crate::context::Texture,
+    _allocation: TrackedAllocation,
     width: u32,
     height: u32,
 }
@@ -25,6 +26,11 @@ impl DepthTexture2D {
         let texture = Self {
             context: context.clone(),
             id,
+            _allocation: TrackedAllocation::new(
+                context,
+                GpuResourceType::DepthTexture2D,
+                texture_byte_size(width, height, 1, false, 1, std::mem::size_of::<T>()),
+            ),
             width,
             height,
         };
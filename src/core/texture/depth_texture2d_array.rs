@@ -6,6 +6,7 @@ use crate::core::texture::*;
 pub struct DepthTexture2DArray {
     context: Context,
     id: crate::context::Texture,
+    _allocation: TrackedAllocation,
     width: u32,
     height: u32,
     depth: u32,
@@ -27,6 +28,11 @@ impl DepthTexture2DArray {
         let texture = Self {
             context: context.clone(),
             id,
+            _allocation: TrackedAllocation::new(
+                context,
+                GpuResourceType::DepthTexture2DArray,
+                texture_byte_size(width, height, depth, false, 1, std::mem::size_of::<T>()),
+            ),
             width,
             height,
             depth,
@@ -6,6 +6,7 @@ use crate::core::texture::*;
 pub struct DepthTextureCubeMap {
     context: Context,
     id: crate::context::Texture,
+    _allocation: TrackedAllocation,
     width: u32,
     height: u32,
 }
@@ -26,6 +27,11 @@ impl DepthTextureCubeMap {
         let texture = Self {
             context: context.clone(),
             id,
+            _allocation: TrackedAllocation::new(
+                context,
+                GpuResourceType::DepthTextureCubeMap,
+                texture_byte_size(width, height, 6, false, 1, std::mem::size_of::<T>()),
+            ),
             width,
             height,
         };
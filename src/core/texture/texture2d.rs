@@ -6,6 +6,7 @@ use crate::core::texture::*;
 pub struct Texture2D {
     context: Context,
     id: crate::context::Texture,
+    _allocation: TrackedAllocation,
     width: u32,
     height: u32,
     number_of_mip_maps: u32,
@@ -38,7 +39,40 @@ impl Texture2D {
         cpu_texture: &CpuTexture,
         data: &[T],
     ) -> Self {
-        let mut texture = Self::new_empty::<T>(
+        let mut texture = Self::new_empty_with_format::<T>(context, cpu_texture);
+        texture.fill(data);
+        texture
+    }
+
+    ///
+    /// Constructs a new empty texture with the size, format and sampling parameters of the given texture, without uploading any data.
+    ///
+    pub(crate) fn new_empty_like(context: &Context, cpu_texture: &CpuTexture) -> Self {
+        match cpu_texture.data {
+            TextureData::RU8(_) => Self::new_empty_with_format::<u8>(context, cpu_texture),
+            TextureData::RgU8(_) => Self::new_empty_with_format::<[u8; 2]>(context, cpu_texture),
+            TextureData::RgbU8(_) => Self::new_empty_with_format::<[u8; 3]>(context, cpu_texture),
+            TextureData::RgbaU8(_) => Self::new_empty_with_format::<[u8; 4]>(context, cpu_texture),
+            TextureData::RF16(_) => Self::new_empty_with_format::<f16>(context, cpu_texture),
+            TextureData::RgF16(_) => Self::new_empty_with_format::<[f16; 2]>(context, cpu_texture),
+            TextureData::RgbF16(_) => Self::new_empty_with_format::<[f16; 3]>(context, cpu_texture),
+            TextureData::RgbaF16(_) => {
+                Self::new_empty_with_format::<[f16; 4]>(context, cpu_texture)
+            }
+            TextureData::RF32(_) => Self::new_empty_with_format::<f32>(context, cpu_texture),
+            TextureData::RgF32(_) => Self::new_empty_with_format::<[f32; 2]>(context, cpu_texture),
+            TextureData::RgbF32(_) => Self::new_empty_with_format::<[f32; 3]>(context, cpu_texture),
+            TextureData::RgbaF32(_) => {
+                Self::new_empty_with_format::<[f32; 4]>(context, cpu_texture)
+            }
+        }
+    }
+
+    fn new_empty_with_format<T: TextureDataType>(
+        context: &Context,
+        cpu_texture: &CpuTexture,
+    ) -> Self {
+        Self::new_empty::<T>(
             context,
             cpu_texture.width,
             cpu_texture.height,
@@ -47,9 +81,7 @@ impl Texture2D {
             cpu_texture.mip_map_filter,
             cpu_texture.wrap_s,
             cpu_texture.wrap_t,
-        );
-        texture.fill(data);
-        texture
+        )
     }
 
     ///
@@ -72,6 +104,18 @@ impl Texture2D {
         let texture = Self {
             context: context.clone(),
             id,
+            _allocation: TrackedAllocation::new(
+                context,
+                GpuResourceType::Texture2D,
+                texture_byte_size(
+                    width,
+                    height,
+                    1,
+                    false,
+                    number_of_mip_maps,
+                    std::mem::size_of::<T>(),
+                ),
+            ),
             width,
             height,
             number_of_mip_maps,
@@ -133,6 +177,45 @@ impl Texture2D {
         self.generate_mip_maps();
     }
 
+    ///
+    /// Fills the given range of rows of this texture with the given data, where row 0 is the top row as in [CpuTexture].
+    /// This makes it possible to upload a large texture in smaller parts, for example over multiple frames.
+    /// The mip maps are generated when the bottom row is filled, so the rows should be filled from top to bottom.
+    ///
+    /// # Panic
+    /// Will panic if the range is outside the texture or if the length of the data does not correspond to the width of the texture,
+    /// the number of rows and the format specified at construction.
+    ///
+    pub fn fill_rows<T: TextureDataType>(&mut self, rows: std::ops::Range<u32>, data: &[T]) {
+        if rows.end > self.height || rows.start > rows.end {
+            panic!(
+                "the rows {:?} are outside the texture with height {}",
+                rows, self.height
+            );
+        }
+        let row_count = rows.end - rows.start;
+        check_data_length::<T>(self.width, row_count, 1, self.data_byte_size, data.len());
+        self.bind();
+        let mut data = data.to_owned();
+        flip_y(&mut data, self.width as usize, row_count as usize);
+        unsafe {
+            self.context.tex_sub_image_2d(
+                crate::context::TEXTURE_2D,
+                0,
+                0,
+                (self.height - rows.end) as i32,
+                self.width as i32,
+                row_count as i32,
+                format_from_data_type::<T>(),
+                T::data_type(),
+                crate::context::PixelUnpackData::Slice(to_byte_slice(&data)),
+            );
+        }
+        if rows.end == self.height {
+            self.generate_mip_maps();
+        }
+    }
+
     ///
     /// Returns a [ColorTarget] which can be used to clear, write to and read from the given mip level of this texture.
     /// Combine this together with a [DepthTarget] with [RenderTarget::new] to be able to write to both a depth and color target at the same time.
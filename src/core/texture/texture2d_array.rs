@@ -9,6 +9,7 @@ use crate::core::texture::*;
 pub struct Texture2DArray {
     context: Context,
     id: crate::context::Texture,
+    _allocation: TrackedAllocation,
     width: u32,
     height: u32,
     depth: u32,
@@ -161,6 +162,18 @@ impl Texture2DArray {
         let texture = Self {
             context: context.clone(),
             id,
+            _allocation: TrackedAllocation::new(
+                context,
+                GpuResourceType::Texture2DArray,
+                texture_byte_size(
+                    width,
+                    height,
+                    depth,
+                    false,
+                    number_of_mip_maps,
+                    std::mem::size_of::<T>(),
+                ),
+            ),
             width,
             height,
             depth,
@@ -3,6 +3,7 @@ use crate::core::texture::*;
 pub struct Texture2DMultisample {
     context: Context,
     id: crate::context::Renderbuffer,
+    _allocation: TrackedAllocation,
     width: u32,
     height: u32,
     number_of_samples: u32,
@@ -23,6 +24,18 @@ impl Texture2DMultisample {
         let texture = Self {
             context: context.clone(),
             id,
+            _allocation: TrackedAllocation::new(
+                context,
+                GpuResourceType::Texture2DMultisample,
+                texture_byte_size(
+                    width,
+                    height,
+                    number_of_samples,
+                    false,
+                    1,
+                    std::mem::size_of::<T>(),
+                ),
+            ),
             width,
             height,
             number_of_samples,
@@ -5,6 +5,7 @@ use crate::core::texture::*;
 pub struct Texture3D {
     context: Context,
     id: crate::context::Texture,
+    _allocation: TrackedAllocation,
     width: u32,
     height: u32,
     depth: u32,
@@ -75,6 +76,18 @@ impl Texture3D {
         let texture = Self {
             context: context.clone(),
             id,
+            _allocation: TrackedAllocation::new(
+                context,
+                GpuResourceType::Texture3D,
+                texture_byte_size(
+                    width,
+                    height,
+                    depth,
+                    true,
+                    number_of_mip_maps,
+                    std::mem::size_of::<T>(),
+                ),
+            ),
             width,
             height,
             depth,
@@ -107,6 +107,7 @@ impl CubeMapSide {
 pub struct TextureCubeMap {
     context: Context,
     id: crate::context::Texture,
+    _allocation: TrackedAllocation,
     width: u32,
     height: u32,
     number_of_mip_maps: u32,
@@ -315,6 +316,18 @@ impl TextureCubeMap {
         let texture = Self {
             context: context.clone(),
             id,
+            _allocation: TrackedAllocation::new(
+                context,
+                GpuResourceType::TextureCubeMap,
+                texture_byte_size(
+                    width,
+                    height,
+                    6,
+                    false,
+                    number_of_mip_maps,
+                    std::mem::size_of::<T>(),
+                ),
+            ),
             width,
             height,
             number_of_mip_maps,
@@ -522,6 +535,10 @@ impl TextureCubeMap {
         self.height
     }
 
+    pub(in crate::core) fn number_of_mip_maps(&self) -> u32 {
+        self.number_of_mip_maps
+    }
+
     pub(in crate::core) fn generate_mip_maps(&self) {
         if self.number_of_mip_maps > 1 {
             self.bind();
@@ -0,0 +1,333 @@
+use crate::core::*;
+#[cfg(target_arch = "wasm32")]
+use instant::Instant;
+use std::any::Any;
+use std::collections::{HashMap, VecDeque};
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+
+/// The approximate number of bytes uploaded in each chunk when uploading in chunks.
+const CHUNK_BYTE_SIZE: usize = 1 << 20;
+
+///
+/// CPU-side data which can be uploaded to the GPU, for example a [CpuTexture] which is uploaded to a [Texture2D].
+/// Used for sending data created on another thread to an [UploadQueue].
+///
+pub trait Upload: Send + 'static {
+    /// The GPU resource created from the data.
+    type Output: 'static;
+
+    ///
+    /// Uploads the data to the GPU.
+    ///
+    fn upload(self, context: &Context) -> Self::Output;
+
+    ///
+    /// Creates the GPU resource without uploading any data and returns it together with the number of chunks the data is divided into,
+    /// if the data can be uploaded in chunks using [Upload::upload_chunk]. Used by [UploadQueue::process_with_budget] to spread large uploads over multiple frames.
+    /// The default implementation returns `None` which means that the data is always uploaded at once using [Upload::upload].
+    ///
+    fn create_empty(&self, _context: &Context) -> Option<(Self::Output, usize)> {
+        None
+    }
+
+    ///
+    /// Uploads the chunk with the given index to the GPU resource created by [Upload::create_empty].
+    /// The chunks are uploaded in order, starting with chunk 0.
+    ///
+    fn upload_chunk(&self, _output: &mut Self::Output, _chunk: usize) {}
+}
+
+impl Upload for CpuTexture {
+    type Output = Texture2D;
+    fn upload(self, context: &Context) -> Self::Output {
+        Texture2D::new(context, &self)
+    }
+
+    fn create_empty(&self, context: &Context) -> Option<(Self::Output, usize)> {
+        let rows_per_chunk = rows_per_chunk(self);
+        let chunk_count = (self.height + rows_per_chunk - 1) / rows_per_chunk;
+        Some((
+            Texture2D::new_empty_like(context, self),
+            chunk_count as usize,
+        ))
+    }
+
+    fn upload_chunk(&self, output: &mut Self::Output, chunk: usize) {
+        let rows_per_chunk = rows_per_chunk(self);
+        let start = chunk as u32 * rows_per_chunk;
+        let rows = start..(start + rows_per_chunk).min(self.height);
+        match self.data {
+            TextureData::RU8(ref data) => fill_rows(output, self.width, rows, data),
+            TextureData::RgU8(ref data) => fill_rows(output, self.width, rows, data),
+            TextureData::RgbU8(ref data) => fill_rows(output, self.width, rows, data),
+            TextureData::RgbaU8(ref data) => fill_rows(output, self.width, rows, data),
+            TextureData::RF16(ref data) => fill_rows(output, self.width, rows, data),
+            TextureData::RgF16(ref data) => fill_rows(output, self.width, rows, data),
+            TextureData::RgbF16(ref data) => fill_rows(output, self.width, rows, data),
+            TextureData::RgbaF16(ref data) => fill_rows(output, self.width, rows, data),
+            TextureData::RF32(ref data) => fill_rows(output, self.width, rows, data),
+            TextureData::RgF32(ref data) => fill_rows(output, self.width, rows, data),
+            TextureData::RgbF32(ref data) => fill_rows(output, self.width, rows, data),
+            TextureData::RgbaF32(ref data) => fill_rows(output, self.width, rows, data),
+        }
+    }
+}
+
+fn rows_per_chunk(cpu_texture: &CpuTexture) -> u32 {
+    let texel_byte_size = match cpu_texture.data {
+        TextureData::RU8(_) => 1,
+        TextureData::RgU8(_) | TextureData::RF16(_) => 2,
+        TextureData::RgbU8(_) => 3,
+        TextureData::RgbaU8(_) | TextureData::RgF16(_) | TextureData::RF32(_) => 4,
+        TextureData::RgbF16(_) => 6,
+        TextureData::RgbaF16(_) | TextureData::RgF32(_) => 8,
+        TextureData::RgbF32(_) => 12,
+        TextureData::RgbaF32(_) => 16,
+    };
+    let row_byte_size = (cpu_texture.width as usize * texel_byte_size).max(1);
+    (CHUNK_BYTE_SIZE / row_byte_size).max(1) as u32
+}
+
+fn fill_rows<T: TextureDataType>(
+    texture: &mut Texture2D,
+    width: u32,
+    rows: std::ops::Range<u32>,
+    data: &[T],
+) {
+    let start = (rows.start * width) as usize;
+    let end = (rows.end * width) as usize;
+    texture.fill_rows(rows, &data[start..end]);
+}
+
+impl Upload for CpuTexture3D {
+    type Output = Texture3D;
+    fn upload(self, context: &Context) -> Self::Output {
+        Texture3D::new(context, &self)
+    }
+}
+
+///
+/// Data submitted to the queue which is not yet started uploading. Can be sent between threads, unlike the GPU resources.
+///
+trait PendingUpload: Send {
+    fn start(self: Box<Self>, context: &Context) -> Box<dyn UploadInProgress>;
+}
+
+impl<U: Upload> PendingUpload for U {
+    fn start(self: Box<Self>, context: &Context) -> Box<dyn UploadInProgress> {
+        match self.create_empty(context) {
+            Some((output, chunk_count)) => Box::new(ChunkedUpload {
+                data: *self,
+                output: Some(output),
+                chunk_count,
+                next_chunk: 0,
+            }),
+            None => Box::new(FinishedUpload(Some(
+                Box::new(self.upload(context)) as Box<dyn Any>
+            ))),
+        }
+    }
+}
+
+///
+/// An upload which is started on the thread owning the graphics context.
+///
+trait UploadInProgress {
+    ///
+    /// Uploads the next part of the data and returns the GPU resource when all of the data is uploaded.
+    ///
+    fn step(&mut self) -> Option<Box<dyn Any>>;
+}
+
+struct FinishedUpload(Option<Box<dyn Any>>);
+
+impl UploadInProgress for FinishedUpload {
+    fn step(&mut self) -> Option<Box<dyn Any>> {
+        self.0.take()
+    }
+}
+
+struct ChunkedUpload<U: Upload> {
+    data: U,
+    output: Option<U::Output>,
+    chunk_count: usize,
+    next_chunk: usize,
+}
+
+impl<U: Upload> UploadInProgress for ChunkedUpload<U> {
+    fn step(&mut self) -> Option<Box<dyn Any>> {
+        if self.next_chunk < self.chunk_count {
+            if let Some(ref mut output) = self.output {
+                self.data.upload_chunk(output, self.next_chunk);
+            }
+            self.next_chunk += 1;
+        }
+        if self.next_chunk >= self.chunk_count {
+            self.output
+                .take()
+                .map(|output| Box::new(output) as Box<dyn Any>)
+        } else {
+            None
+        }
+    }
+}
+
+///
+/// A ticket for data submitted to an [UploadQueue] using an [UploadSender], which is used to get the GPU resource when it is uploaded, see [UploadQueue::take].
+///
+#[derive(Debug)]
+pub struct UploadTicket<T> {
+    id: u64,
+    _output: PhantomData<fn() -> T>,
+}
+
+impl<T> Clone for UploadTicket<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for UploadTicket<T> {}
+
+///
+/// Submits data to an [UploadQueue]. Can be cloned and sent to other threads, for example worker threads loading and processing assets.
+///
+#[derive(Clone)]
+pub struct UploadSender {
+    pending: Arc<Mutex<Vec<(u64, Box<dyn PendingUpload>)>>>,
+    next_id: Arc<AtomicU64>,
+}
+
+impl UploadSender {
+    ///
+    /// Submits the given data for upload the next time [UploadQueue::process] or [UploadQueue::process_with_budget] is called.
+    /// Returns a ticket which can be sent back to the thread owning the [UploadQueue] and used to get the GPU resource when it is uploaded.
+    ///
+    pub fn submit<U: Upload>(&self, data: U) -> UploadTicket<U::Output> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        self.pending.lock().unwrap().push((id, Box::new(data)));
+        UploadTicket {
+            id,
+            _output: PhantomData,
+        }
+    }
+}
+
+///
+/// A queue of CPU-side data waiting to be uploaded to the GPU.
+///
+/// GPU resources can only be created on the thread owning the graphics context, so data created on other threads, for example by background asset processing,
+/// are submitted to this queue using an [UploadSender] and then uploaded when [UploadQueue::process] is called, typically at the start of each frame.
+/// The uploaded GPU resources are then available using [UploadQueue::take] with the ticket returned when submitting the data.
+///
+/// Uploading a large resource at once, for example a 4k texture, can take long enough to cause a visible hitch.
+/// To avoid that, use [UploadQueue::process_with_budget] instead which splits large uploads into chunks that are uploaded over multiple frames.
+///
+pub struct UploadQueue {
+    context: Context,
+    sender: UploadSender,
+    in_progress: VecDeque<(u64, Box<dyn UploadInProgress>)>,
+    uploaded: HashMap<u64, Box<dyn Any>>,
+}
+
+impl UploadQueue {
+    ///
+    /// Creates a new empty upload queue.
+    ///
+    pub fn new(context: &Context) -> Self {
+        Self {
+            context: context.clone(),
+            sender: UploadSender {
+                pending: Arc::new(Mutex::new(Vec::new())),
+                next_id: Arc::new(AtomicU64::new(0)),
+            },
+            in_progress: VecDeque::new(),
+            uploaded: HashMap::new(),
+        }
+    }
+
+    ///
+    /// Returns a sender which can be sent to other threads and used to submit data to this queue.
+    ///
+    pub fn sender(&self) -> UploadSender {
+        self.sender.clone()
+    }
+
+    ///
+    /// Returns the number of submitted uploads which are not yet completely uploaded.
+    ///
+    pub fn pending_count(&self) -> usize {
+        self.sender.pending.lock().unwrap().len() + self.in_progress.len()
+    }
+
+    ///
+    /// Uploads all data submitted since the last call to this method, including data that is partially uploaded by [UploadQueue::process_with_budget].
+    /// Should be called on the thread owning the graphics context, typically at the start of each frame.
+    ///
+    pub fn process(&mut self) {
+        self.start_pending();
+        while let Some((id, mut upload)) = self.in_progress.pop_front() {
+            loop {
+                if let Some(output) = upload.step() {
+                    self.uploaded.insert(id, output);
+                    break;
+                }
+            }
+        }
+    }
+
+    ///
+    /// Uploads submitted data until the given time budget is spent, typically a few milliseconds each frame.
+    /// Large textures are uploaded in chunks of rows, so an upload can be spread over multiple calls to this method,
+    /// in which case the GPU resource is available using [UploadQueue::take] when the last chunk is uploaded.
+    /// At least one chunk is uploaded in each call, so all data is eventually uploaded even if the budget is very small.
+    /// Should be called on the thread owning the graphics context, typically at the start of each frame.
+    ///
+    pub fn process_with_budget(&mut self, budget: Duration) {
+        let start = Instant::now();
+        loop {
+            if self.in_progress.is_empty() {
+                let mut pending = self.sender.pending.lock().unwrap();
+                if pending.is_empty() {
+                    break;
+                }
+                let (id, upload) = pending.remove(0);
+                drop(pending);
+                self.in_progress
+                    .push_back((id, upload.start(&self.context)));
+            }
+            if let Some((id, upload)) = self.in_progress.front_mut() {
+                if let Some(output) = upload.step() {
+                    let id = *id;
+                    self.in_progress.pop_front();
+                    self.uploaded.insert(id, output);
+                }
+            }
+            if start.elapsed() >= budget {
+                break;
+            }
+        }
+    }
+
+    fn start_pending(&mut self) {
+        let pending = std::mem::take(&mut *self.sender.pending.lock().unwrap());
+        for (id, upload) in pending {
+            self.in_progress
+                .push_back((id, upload.start(&self.context)));
+        }
+    }
+
+    ///
+    /// Returns the GPU resource uploaded from the data associated with the given ticket if it has been uploaded and not already taken.
+    ///
+    pub fn take<T: 'static>(&mut self, ticket: &UploadTicket<T>) -> Option<T> {
+        self.uploaded
+            .remove(&ticket.id)
+            .map(|output| *output.downcast::<T>().unwrap())
+    }
+}
@@ -1,13 +1,14 @@
 use crate::control::*;
 use crate::core::*;
-use egui_glow::Painter;
 use std::cell::RefCell;
+use std::collections::HashMap;
 
 #[doc(hidden)]
 pub use egui;
 
 ///
 /// Integration of [egui](https://crates.io/crates/egui), an immediate mode GUI.
+/// The events are converted to egui input in [GUI::update] and the output of egui is rendered on top of the current render target in [GUI::render].
 ///
 pub struct GUI {
     painter: RefCell<Painter>,
@@ -22,23 +23,40 @@ impl GUI {
     /// Creates a new GUI from a mid-level [Context].
     ///
     pub fn new(context: &Context) -> Self {
-        use std::ops::Deref;
-        Self::from_gl_context(context.deref().clone())
-    }
-
-    ///
-    /// Creates a new GUI from a low-level graphics [Context](crate::context::Context).
-    ///
-    pub fn from_gl_context(context: std::sync::Arc<crate::context::Context>) -> Self {
         GUI {
             egui_context: egui::Context::default(),
-            painter: RefCell::new(Painter::new(context, "", None).unwrap()),
+            painter: RefCell::new(Painter::new(context)),
             output: RefCell::new(None),
             viewport: Viewport::new_at_origo(1, 1),
             modifiers: Modifiers::default(),
         }
     }
 
+    ///
+    /// Creates a new GUI from a low-level graphics [Context](crate::context::Context).
+    ///
+    pub fn from_gl_context(context: std::sync::Arc<crate::context::Context>) -> Self {
+        Self::new(&Context::from_gl_context(context).unwrap())
+    }
+
+    ///
+    /// Initialises a new frame of the GUI and handles the events of the given [FrameInput](crate::window::FrameInput), see [GUI::update].
+    ///
+    #[cfg(feature = "window")]
+    pub fn update_from_frame_input(
+        &mut self,
+        frame_input: &mut crate::window::FrameInput,
+        callback: impl FnOnce(&egui::Context),
+    ) -> bool {
+        self.update(
+            &mut frame_input.events,
+            frame_input.accumulated_time,
+            frame_input.viewport,
+            frame_input.device_pixel_ratio,
+            callback,
+        )
+    }
+
     ///
     /// Initialises a new frame of the GUI and handles events.
     /// Construct the GUI (Add panels, widgets etc.) using the [egui::Context] in the callback function.
@@ -245,19 +263,214 @@ impl GUI {
             .borrow_mut()
             .take()
             .expect("need to call GUI::update before GUI::render");
-        let clipped_meshes = self.egui_context.tessellate(output.shapes);
+        let clipped_primitives = self.egui_context.tessellate(output.shapes);
         let scale = self.egui_context.pixels_per_point();
-        self.painter.borrow_mut().paint_and_update_textures(
-            [self.viewport.width, self.viewport.height],
+        self.painter.borrow_mut().paint(
+            self.viewport,
             scale,
-            &clipped_meshes,
-            &output.textures_delta,
+            clipped_primitives,
+            output.textures_delta,
+        );
+    }
+}
+
+struct GuiTexture {
+    texture: Texture2D,
+    width: usize,
+    pixels: Vec<[u8; 4]>,
+}
+
+// Renders the triangles output by egui using a dedicated shader program, which multiplies the vertex colors with the egui textures
+struct Painter {
+    context: Context,
+    program: Program,
+    positions: VertexBuffer,
+    uvs: VertexBuffer,
+    colors: VertexBuffer,
+    indices: ElementBuffer,
+    textures: HashMap<egui::TextureId, GuiTexture>,
+}
+
+impl Painter {
+    fn new(context: &Context) -> Self {
+        Self {
+            context: context.clone(),
+            program: Program::from_source(
+                context,
+                include_str!("shaders/egui.vert"),
+                include_str!("shaders/egui.frag"),
+            )
+            .unwrap(),
+            positions: VertexBuffer::new(context),
+            uvs: VertexBuffer::new(context),
+            colors: VertexBuffer::new(context),
+            indices: ElementBuffer::new(context),
+            textures: HashMap::new(),
+        }
+    }
+
+    fn paint(
+        &mut self,
+        viewport: Viewport,
+        pixels_per_point: f32,
+        clipped_primitives: Vec<egui::ClippedPrimitive>,
+        textures_delta: egui::TexturesDelta,
+    ) {
+        for (id, delta) in textures_delta.set {
+            self.set_texture(id, delta);
+        }
+
+        let width = viewport.width as f32;
+        let height = viewport.height as f32;
+        let render_states = RenderStates {
+            write_mask: WriteMask::COLOR,
+            depth_test: DepthTest::Always,
+            blend: Blend::Enabled {
+                source_rgb_multiplier: BlendMultiplierType::One,
+                source_alpha_multiplier: BlendMultiplierType::OneMinusDstAlpha,
+                destination_rgb_multiplier: BlendMultiplierType::OneMinusSrcAlpha,
+                destination_alpha_multiplier: BlendMultiplierType::One,
+                rgb_equation: BlendEquationType::Add,
+                alpha_equation: BlendEquationType::Add,
+            },
+            cull: Cull::None,
+        };
+        self.program.use_uniform(
+            "screenSize",
+            vec2(width / pixels_per_point, height / pixels_per_point),
         );
-        #[cfg(not(target_arch = "wasm32"))]
-        #[allow(unsafe_code)]
-        unsafe {
-            use glow::HasContext as _;
-            self.painter.borrow().gl().disable(glow::FRAMEBUFFER_SRGB);
+        for egui::ClippedPrimitive {
+            clip_rect,
+            primitive,
+        } in clipped_primitives
+        {
+            let mesh = match primitive {
+                egui::epaint::Primitive::Mesh(mesh) => mesh,
+                egui::epaint::Primitive::Callback(_) => continue,
+            };
+            let texture = match self.textures.get(&mesh.texture_id) {
+                Some(texture) if !mesh.indices.is_empty() => texture,
+                _ => continue,
+            };
+
+            // The clip rectangle is in points with the origin in the top left corner
+            let min_x = (clip_rect.min.x * pixels_per_point)
+                .round()
+                .clamp(0.0, width);
+            let min_y = (clip_rect.min.y * pixels_per_point)
+                .round()
+                .clamp(0.0, height);
+            let max_x = (clip_rect.max.x * pixels_per_point)
+                .round()
+                .clamp(min_x, width);
+            let max_y = (clip_rect.max.y * pixels_per_point)
+                .round()
+                .clamp(min_y, height);
+            if max_x <= min_x || max_y <= min_y {
+                continue;
+            }
+            self.context.set_scissor(ScissorBox {
+                x: min_x as i32,
+                y: (height - max_y) as i32,
+                width: (max_x - min_x) as u32,
+                height: (max_y - min_y) as u32,
+            });
+
+            self.positions.fill(
+                &mesh
+                    .vertices
+                    .iter()
+                    .map(|v| vec2(v.pos.x, v.pos.y))
+                    .collect::<Vec<_>>(),
+            );
+            self.uvs.fill(
+                &mesh
+                    .vertices
+                    .iter()
+                    .map(|v| vec2(v.uv.x, v.uv.y))
+                    .collect::<Vec<_>>(),
+            );
+            self.colors.fill(
+                &mesh
+                    .vertices
+                    .iter()
+                    .map(|v| {
+                        let [r, g, b, a] = v.color.to_array();
+                        vec4(r as f32, g as f32, b as f32, a as f32) / 255.0
+                    })
+                    .collect::<Vec<_>>(),
+            );
+            self.indices.fill(&mesh.indices);
+
+            self.program.use_texture("guiTexture", &texture.texture);
+            self.program
+                .use_vertex_attribute("position", &self.positions);
+            self.program.use_vertex_attribute("uv", &self.uvs);
+            self.program.use_vertex_attribute("color", &self.colors);
+            self.program.draw_elements(
+                render_states,
+                Viewport::new_at_origo(viewport.width, viewport.height),
+                &self.indices,
+            );
+        }
+        self.context
+            .set_scissor(ScissorBox::new_at_origo(viewport.width, viewport.height));
+
+        for id in textures_delta.free {
+            self.textures.remove(&id);
+        }
+    }
+
+    fn set_texture(&mut self, id: egui::TextureId, delta: egui::epaint::ImageDelta) {
+        let [width, height] = delta.image.size();
+        let pixels = match &delta.image {
+            egui::ImageData::Color(image) => image
+                .pixels
+                .iter()
+                .map(|c| c.to_array())
+                .collect::<Vec<_>>(),
+            egui::ImageData::Font(image) => image
+                .srgba_pixels(None)
+                .map(|c| c.to_array())
+                .collect::<Vec<_>>(),
+        };
+        if let Some([x, y]) = delta.pos {
+            // Updates a part of an existing texture, for example when new glyphs are added to the font texture
+            if let Some(texture) = self.textures.get_mut(&id) {
+                for row in 0..height {
+                    let start = (y + row) * texture.width + x;
+                    texture.pixels[start..start + width]
+                        .copy_from_slice(&pixels[row * width..(row + 1) * width]);
+                }
+                texture.texture.fill_rows(
+                    y as u32..(y + height) as u32,
+                    &texture.pixels[y * texture.width..(y + height) * texture.width],
+                );
+            }
+        } else {
+            let interpolation = |filter: egui::TextureFilter| match filter {
+                egui::TextureFilter::Nearest => Interpolation::Nearest,
+                egui::TextureFilter::Linear => Interpolation::Linear,
+            };
+            let mut texture = Texture2D::new_empty::<[u8; 4]>(
+                &self.context,
+                width as u32,
+                height as u32,
+                interpolation(delta.options.minification),
+                interpolation(delta.options.magnification),
+                None,
+                Wrapping::ClampToEdge,
+                Wrapping::ClampToEdge,
+            );
+            texture.fill(&pixels);
+            self.textures.insert(
+                id,
+                GuiTexture {
+                    texture,
+                    width,
+                    pixels,
+                },
+            );
         }
     }
 }
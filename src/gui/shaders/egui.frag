@@ -0,0 +1,12 @@
+uniform sampler2D guiTexture;
+
+in vec2 uvs;
+in vec4 col;
+
+layout (location = 0) out vec4 outColor;
+
+void main()
+{
+    // Both the vertex colors and the textures are premultiplied by alpha and in gamma space, as is the output
+    outColor = col * texture(guiTexture, uvs);
+}
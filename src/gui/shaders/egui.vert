@@ -0,0 +1,15 @@
+uniform vec2 screenSize;
+
+in vec2 position;
+in vec2 uv;
+in vec4 color;
+
+out vec2 uvs;
+out vec4 col;
+
+void main()
+{
+    uvs = vec2(uv.x, 1.0 - uv.y);
+    col = color;
+    gl_Position = vec4(2.0 * position.x / screenSize.x - 1.0, 1.0 - 2.0 * position.y / screenSize.y, 0.0, 1.0);
+}
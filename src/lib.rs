@@ -21,3 +21,7 @@ pub use window::*;
 
 mod gui;
 pub use gui::*;
+
+#[cfg(all(feature = "test-utils", not(target_arch = "wasm32")))]
+#[cfg_attr(docsrs, doc(feature = "test-utils"))]
+pub mod test;
@@ -29,6 +29,43 @@ pub enum RendererError {
     InvalidBufferLength(String, usize, usize),
     #[error("the material {0} is required by the geometry {1} but could not be found")]
     MissingMaterial(String, String),
+    #[error("failed to parse gaussian splat file: {0}")]
+    InvalidSplatFile(String),
+    #[error("the shader include {0} could not be resolved")]
+    UnresolvedShaderInclude(String),
+    #[error("failed to parse IES file: {0}")]
+    InvalidIesFile(String),
+    #[error("failed to parse volume file: {0}")]
+    InvalidVolumeFile(String),
+    #[cfg(feature = "annotations")]
+    #[error("failed to parse annotations: {0}")]
+    InvalidAnnotations(String),
+    #[cfg(feature = "text")]
+    #[error("failed to load font: {0}")]
+    InvalidFont(String),
+    #[cfg(any(
+        feature = "gltf",
+        feature = "hdr",
+        feature = "asset-bundle",
+        all(feature = "hot-reload", not(target_arch = "wasm32"))
+    ))]
+    #[error("failed to load asset")]
+    Asset(#[from] three_d_asset::Error),
+    #[cfg(all(feature = "render-export", not(target_arch = "wasm32")))]
+    #[error("failed to save image")]
+    Image(#[from] image::ImageError),
+    #[cfg(all(feature = "render-export", not(target_arch = "wasm32")))]
+    #[error("failed to create directory")]
+    Io(#[from] std::io::Error),
+    #[cfg(all(feature = "hot-reload", not(target_arch = "wasm32")))]
+    #[error("failed to reload asset: {0}")]
+    ReloadFailed(String),
+    #[cfg(feature = "gltf")]
+    #[error("failed to read glTF scene hierarchy: {0}")]
+    InvalidGltf(String),
+    #[cfg(feature = "asset-bundle")]
+    #[error("failed to read asset bundle: {0}")]
+    InvalidAssetBundle(String),
 }
 
 mod camera;
@@ -52,12 +89,91 @@ pub use object::*;
 pub mod control;
 pub use control::*;
 
+mod shader_library;
+#[doc(inline)]
+pub use shader_library::*;
+
+mod reference_rasterizer;
+#[doc(inline)]
+pub use reference_rasterizer::*;
+
+mod render_scale;
+#[doc(inline)]
+pub use render_scale::*;
+
+mod temporal_upsampling;
+#[doc(inline)]
+pub use temporal_upsampling::*;
+
+mod quality_manager;
+#[doc(inline)]
+pub use quality_manager::*;
+
+mod panorama;
+#[doc(inline)]
+pub use panorama::*;
+
+mod render_layers;
+#[doc(inline)]
+pub use render_layers::*;
+
+mod slicing;
+#[doc(inline)]
+pub use slicing::*;
+
+mod wall_thickness;
+#[doc(inline)]
+pub use wall_thickness::*;
+
+mod turntable;
+#[doc(inline)]
+pub use turntable::*;
+
+mod streamlines;
+#[doc(inline)]
+pub use streamlines::*;
+
+mod picking;
+#[doc(inline)]
+pub use picking::*;
+
+mod volume;
+#[doc(inline)]
+pub use volume::*;
+
+mod frustum;
+#[doc(inline)]
+pub use frustum::*;
+
+mod annotations;
+#[doc(inline)]
+pub use annotations::*;
+
+mod oit;
+
+mod resource_cache;
+pub(crate) use resource_cache::{shared_mesh, shared_texture, unshare_mesh, ResourceCache};
+
+#[cfg(all(feature = "hot-reload", not(target_arch = "wasm32")))]
+#[cfg_attr(docsrs, doc(feature = "hot-reload"))]
+mod hot_reload;
+#[cfg(all(feature = "hot-reload", not(target_arch = "wasm32")))]
+#[doc(inline)]
+pub use hot_reload::*;
+
+#[cfg(feature = "asset-bundle")]
+#[cfg_attr(docsrs, doc(feature = "asset-bundle"))]
+mod asset_bundle;
+#[cfg(feature = "asset-bundle")]
+#[doc(inline)]
+pub use asset_bundle::*;
+
 macro_rules! impl_render_target_extensions_body {
     () => {
         ///
         /// Render the objects using the given camera and lights into this render target.
         /// Use an empty array for the `lights` argument, if the objects does not require lights to be rendered.
-        /// Also, objects outside the camera frustum are not rendered and the objects are rendered in the order given by [cmp_render_order].
+        /// Also, objects outside the camera frustum are not rendered, see [Frustum], and the objects are rendered in the order given by [cmp_render_order].
         ///
         pub fn render(
             &self,
@@ -71,7 +187,7 @@ macro_rules! impl_render_target_extensions_body {
         ///
         /// Render the objects using the given camera and lights into the part of this render target defined by the scissor box.
         /// Use an empty array for the `lights` argument, if the objects does not require lights to be rendered.
-        /// Also, objects outside the camera frustum are not rendered and the objects are rendered in the order given by [cmp_render_order].
+        /// Also, objects outside the camera frustum are not rendered, see [Frustum], and the objects are rendered in the order given by [cmp_render_order].
         ///
         pub fn render_partially(
             &self,
@@ -80,30 +196,23 @@ macro_rules! impl_render_target_extensions_body {
             objects: impl IntoIterator<Item = impl Object>,
             lights: &[&dyn Light],
         ) -> &Self {
+            let frustum = Frustum::new(camera);
             let (mut deferred_objects, mut forward_objects): (Vec<_>, Vec<_>) = objects
                 .into_iter()
-                .filter(|o| camera.in_frustum(&o.aabb()))
+                .filter(|o| frustum.is_visible(o))
                 .partition(|o| o.material_type() == MaterialType::Deferred);
 
             // Deferred
             if deferred_objects.len() > 0 {
                 // Geometry pass
+                self.context.push_debug_group("Geometry pass");
                 let mut geometry_pass_camera = camera.clone();
                 let viewport =
                     Viewport::new_at_origo(camera.viewport().width, camera.viewport().height);
                 geometry_pass_camera.set_viewport(viewport);
                 deferred_objects.sort_by(|a, b| cmp_render_order(&geometry_pass_camera, a, b));
-                let mut geometry_pass_texture = Texture2DArray::new_empty::<[u8; 4]>(
-                    &self.context,
-                    viewport.width,
-                    viewport.height,
-                    3,
-                    Interpolation::Nearest,
-                    Interpolation::Nearest,
-                    None,
-                    Wrapping::ClampToEdge,
-                    Wrapping::ClampToEdge,
-                );
+                let mut geometry_pass_texture =
+                    new_geometry_pass_texture(&self.context, viewport.width, viewport.height);
                 let mut geometry_pass_depth_texture = DepthTexture2D::new::<f32>(
                     &self.context,
                     viewport.width,
@@ -122,31 +231,177 @@ macro_rules! impl_render_target_extensions_body {
                         object.render(&geometry_pass_camera, lights);
                     }
                 });
+                self.context.pop_debug_group();
+
+                // Ambient occlusion pass
+                let geometry_pass_color_texture = ColorTexture::Array {
+                    texture: &geometry_pass_texture,
+                    layers: &gbuffer_layers,
+                };
+                let geometry_pass_depth = DepthTexture::Single(&geometry_pass_depth_texture);
+                let ssao_texture = camera.ssao.map(|ssao| {
+                    self.context.debug_group("Ambient occlusion pass", || {
+                        ssao.occlusion_texture(
+                            &self.context,
+                            &geometry_pass_camera,
+                            geometry_pass_color_texture,
+                            geometry_pass_depth,
+                        )
+                    })
+                });
 
                 // Lighting pass
+                self.context.push_debug_group("Lighting pass");
                 self.apply_screen_effect_partially(
                     scissor_box,
-                    &lighting_pass::LightingPassEffect {},
+                    &lighting_pass::LightingPassEffect {
+                        ssao: ssao_texture.as_ref(),
+                    },
                     camera,
                     lights,
-                    Some(ColorTexture::Array {
-                        texture: &geometry_pass_texture,
-                        layers: &gbuffer_layers,
-                    }),
-                    Some(DepthTexture::Single(&geometry_pass_depth_texture)),
+                    Some(geometry_pass_color_texture),
+                    Some(geometry_pass_depth),
                 );
+                self.context.pop_debug_group();
             }
 
             // Forward
             forward_objects.sort_by(|a, b| cmp_render_order(camera, a, b));
-            self.write_partially(scissor_box, || {
-                for object in forward_objects {
-                    object.render(camera, lights);
-                }
+            self.context.debug_group("Forward pass", || {
+                self.write_partially(scissor_box, || {
+                    for object in forward_objects {
+                        object.render(camera, lights);
+                    }
+                })
             });
             self
         }
 
+        ///
+        /// Render the objects using the given camera and lights into this render target using weighted blended order-independent transparency (OIT) for the transparent objects,
+        /// see [RenderTarget::render_partially_with_oit].
+        ///
+        pub fn render_with_oit(
+            &self,
+            camera: &Camera,
+            objects: impl IntoIterator<Item = impl Object>,
+            lights: &[&dyn Light],
+        ) -> &Self {
+            self.render_partially_with_oit(self.scissor_box(), camera, objects, lights)
+        }
+
+        ///
+        /// Render the objects using the given camera and lights into the part of this render target defined by the scissor box
+        /// using weighted blended order-independent transparency (OIT) for the transparent objects.
+        /// The opaque objects are rendered as in [RenderTarget::render_partially], while the transparent objects are rendered in any order into accumulation targets
+        /// which are then composited on top of the opaque objects. This avoids the artifacts of sorting the transparent objects, for example when they intersect,
+        /// at the cost of an approximation of the blending which is most accurate when the transparent surfaces have a similar color or a low opacity.
+        ///
+        /// The transparent objects are only hidden behind the opaque objects given in the same call and not behind what is already in the render target.
+        /// The materials of the transparent objects must write the color to an output named `outColor`, as all the materials in this crate do.
+        ///
+        pub fn render_partially_with_oit(
+            &self,
+            scissor_box: ScissorBox,
+            camera: &Camera,
+            objects: impl IntoIterator<Item = impl Object>,
+            lights: &[&dyn Light],
+        ) -> &Self {
+            let frustum = Frustum::new(camera);
+            let (transparent_objects, opaque_objects): (Vec<_>, Vec<_>) = objects
+                .into_iter()
+                .filter(|o| frustum.is_visible(o))
+                .partition(|o| o.material_type() == MaterialType::Transparent);
+            self.render_partially(scissor_box, camera, &opaque_objects, lights);
+            if transparent_objects.is_empty() {
+                return self;
+            }
+
+            let viewport =
+                Viewport::new_at_origo(camera.viewport().width, camera.viewport().height);
+            let mut oit_camera = camera.clone();
+            oit_camera.set_viewport(viewport);
+            let mut depth_texture = DepthTexture2D::new::<f32>(
+                &self.context,
+                viewport.width,
+                viewport.height,
+                Wrapping::ClampToEdge,
+                Wrapping::ClampToEdge,
+            );
+            let mut accumulation_texture = Texture2DArray::new_empty::<[f16; 4]>(
+                &self.context,
+                viewport.width,
+                viewport.height,
+                2,
+                Interpolation::Nearest,
+                Interpolation::Nearest,
+                None,
+                Wrapping::ClampToEdge,
+                Wrapping::ClampToEdge,
+            );
+
+            // Depth of the opaque objects, so the transparent objects are hidden behind them
+            self.context.debug_group("Transparency depth pass", || {
+                depth_texture
+                    .as_depth_target()
+                    .clear(ClearState::depth(1.0))
+                    .write(|| {
+                        for object in opaque_objects.iter() {
+                            object.render(&oit_camera, lights);
+                        }
+                    });
+            });
+
+            // Accumulation of the color weighted by opacity and depth in the first layer and the total weight in the second layer
+            let layers = [0, 1];
+            self.context
+                .debug_group("Transparency accumulation pass", || {
+                    *self.context.oit_pass.write().unwrap() = true;
+                    RenderTarget::new(
+                        accumulation_texture.as_color_target(&layers, None),
+                        depth_texture.as_depth_target(),
+                    )
+                    .clear(ClearState::color(0.0, 0.0, 0.0, 1.0))
+                    .write(|| {
+                        for object in transparent_objects {
+                            object.render(&oit_camera, lights);
+                        }
+                    });
+                    *self.context.oit_pass.write().unwrap() = false;
+                });
+
+            self.context.push_debug_group("Transparency resolve pass");
+            self.apply_screen_effect_partially(
+                scissor_box,
+                &oit::OitResolveEffect,
+                camera,
+                lights,
+                Some(ColorTexture::Array {
+                    texture: &accumulation_texture,
+                    layers: &layers,
+                }),
+                None,
+            );
+            self.context.pop_debug_group();
+            self
+        }
+
+        ///
+        /// Render the 2D objects, for example a HUD, on top of the content of this render target using a 2D camera which covers the viewport of this render target, see [Camera::new_2d].
+        /// This is intended to be the final render pass after the 3D scene is rendered and tone mapped, so the depth is cleared before rendering
+        /// and the objects are rendered without tone mapping and only mapped to sRGB color space.
+        /// This means the colors of the objects are exactly as specified and not affected by the exposure or the tone mapping of the 3D scene.
+        ///
+        pub fn render_ui(&self, objects: impl IntoIterator<Item = impl Object>) -> &Self {
+            let mut camera = Camera::new_2d(self.viewport());
+            camera.tone_mapping = ToneMapping::None;
+            camera.color_mapping = ColorMapping::ComputeToSrgb;
+            self.context.debug_group("UI pass", || {
+                self.clear(ClearState::depth(1.0));
+                self.render(&camera, objects, &[])
+            })
+        }
+
         ///
         /// Render the geometries with the given [Material] using the given camera and lights into this render target.
         /// Use an empty array for the `lights` argument, if the material does not require lights to be rendered.
@@ -179,10 +434,11 @@ macro_rules! impl_render_target_extensions_body {
             geometries: impl IntoIterator<Item = impl Geometry>,
             lights: &[&dyn Light],
         ) -> &Self {
+            let frustum = Frustum::new(camera);
             self.write_partially(scissor_box, || {
                 for geometry in geometries
                     .into_iter()
-                    .filter(|o| camera.in_frustum(&o.aabb()))
+                    .filter(|o| frustum.intersects_aabb(&o.aabb()))
                 {
                     render_with_material(&self.context, camera, geometry, material, lights);
                 }
@@ -228,10 +484,11 @@ macro_rules! impl_render_target_extensions_body {
             color_texture: Option<ColorTexture>,
             depth_texture: Option<DepthTexture>,
         ) -> &Self {
+            let frustum = Frustum::new(camera);
             self.write_partially(scissor_box, || {
                 for geometry in geometries
                     .into_iter()
-                    .filter(|o| camera.in_frustum(&o.aabb()))
+                    .filter(|o| frustum.intersects_aabb(&o.aabb()))
                 {
                     render_with_effect(
                         &self.context,
@@ -271,8 +528,10 @@ macro_rules! impl_render_target_extensions_body {
             camera: &Camera,
             lights: &[&dyn Light],
         ) -> &Self {
-            self.write_partially(scissor_box, || {
-                apply_screen_material(&self.context, material, camera, lights)
+            self.context.debug_group("Screen material", || {
+                self.write_partially(scissor_box, || {
+                    apply_screen_material(&self.context, material, camera, lights)
+                })
             });
             self
         }
@@ -312,15 +571,17 @@ macro_rules! impl_render_target_extensions_body {
             color_texture: Option<ColorTexture>,
             depth_texture: Option<DepthTexture>,
         ) -> &Self {
-            self.write_partially(scissor_box, || {
-                apply_screen_effect(
-                    &self.context,
-                    effect,
-                    camera,
-                    lights,
-                    color_texture,
-                    depth_texture,
-                )
+            self.context.debug_group("Screen effect", || {
+                self.write_partially(scissor_box, || {
+                    apply_screen_effect(
+                        &self.context,
+                        effect,
+                        camera,
+                        lights,
+                        color_texture,
+                        depth_texture,
+                    )
+                })
             });
             self
         }
@@ -376,26 +637,52 @@ pub fn render_with_material(
     lights: &[&dyn Light],
 ) {
     let fragment_attributes = material.fragment_attributes();
-    let mut id = geometry.id(fragment_attributes).to_le_bytes().to_vec();
+    let oit =
+        material.material_type() == MaterialType::Transparent && *context.oit_pass.read().unwrap();
+    let tessellation_shader_source = material.tessellation_shader_source();
+    let mut id = if oit { b"oit".to_vec() } else { Vec::new() };
+    if tessellation_shader_source.is_some() {
+        id.extend(b"tess");
+    }
+    id.extend(geometry.id(fragment_attributes).to_le_bytes());
     id.extend(material.id().to_le_bytes());
     id.extend(lights.iter().map(|l| l.id()));
 
     let mut programs = context.programs.write().unwrap();
     let program = programs.entry(id).or_insert_with(|| {
-        Program::from_source(
-            context,
-            &geometry.vertex_shader_source(fragment_attributes),
-            &material.fragment_shader_source(lights),
-        )
+        let fragment_shader_source = if oit {
+            oit::fragment_shader_source(&material.fragment_shader_source(lights))
+        } else {
+            material.fragment_shader_source(lights)
+        };
+        let vertex_shader_source = geometry.vertex_shader_source(fragment_attributes);
+        if let Some((control_shader_source, evaluation_shader_source)) = tessellation_shader_source
+        {
+            Program::from_source_with_tessellation(
+                context,
+                &vertex_shader_source,
+                &control_shader_source,
+                &evaluation_shader_source,
+                &fragment_shader_source,
+            )
+        } else {
+            Program::from_source(context, &vertex_shader_source, &fragment_shader_source)
+        }
         .expect("Failed compiling shader")
     });
     material.use_uniforms(program, camera, lights);
-    geometry.draw(
-        camera,
-        program,
-        material.render_states(),
-        fragment_attributes,
-    );
+    if camera.pixel_perfect {
+        program.set_nearest_filtering(true);
+    }
+    let render_states = if oit {
+        oit::render_states(material.render_states())
+    } else {
+        material.render_states()
+    };
+    geometry.draw(camera, program, render_states, fragment_attributes);
+    if camera.pixel_perfect {
+        program.set_nearest_filtering(false);
+    }
 }
 
 ///
@@ -626,3 +913,63 @@ pub fn ray_intersect(
         None
     }
 }
+
+///
+/// Creates a color texture with the [IntermediateFormat] of the context, used for the intermediate textures the scene is rendered into before the final tone and color mapping.
+///
+pub(crate) fn new_intermediate_texture(
+    context: &Context,
+    width: u32,
+    height: u32,
+    interpolation: Interpolation,
+) -> Texture2D {
+    match context.intermediate_format() {
+        IntermediateFormat::Rgba8 => Texture2D::new_empty::<[u8; 4]>(
+            context,
+            width,
+            height,
+            interpolation,
+            interpolation,
+            None,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        ),
+        IntermediateFormat::Rgba16F => Texture2D::new_empty::<[f16; 4]>(
+            context,
+            width,
+            height,
+            interpolation,
+            interpolation,
+            None,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        ),
+    }
+}
+
+fn new_geometry_pass_texture(context: &Context, width: u32, height: u32) -> Texture2DArray {
+    match context.intermediate_format() {
+        IntermediateFormat::Rgba8 => Texture2DArray::new_empty::<[u8; 4]>(
+            context,
+            width,
+            height,
+            3,
+            Interpolation::Nearest,
+            Interpolation::Nearest,
+            None,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        ),
+        IntermediateFormat::Rgba16F => Texture2DArray::new_empty::<[f16; 4]>(
+            context,
+            width,
+            height,
+            3,
+            Interpolation::Nearest,
+            Interpolation::Nearest,
+            None,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        ),
+    }
+}
@@ -0,0 +1,396 @@
+use crate::renderer::*;
+
+///
+/// A markup placed in the 3D scene, for example by a reviewer, see [AnnotationLayer].
+///
+/// The positions are in world space, unless the annotation has an anchor, in which case they are in the local space of the anchor,
+/// so the annotation follows the anchored object when it is moved, see [AnnotationLayer::set_anchor].
+///
+#[derive(Clone, Debug, PartialEq)]
+pub enum Annotation {
+    /// A marker anchored at a position, for example on the surface of a model found using [pick] or [Bvh::intersect].
+    Marker {
+        /// The position of the marker.
+        position: Vec3,
+        /// The color of the marker.
+        color: Srgba,
+        /// The name of the anchor the position is relative to, if any.
+        anchor: Option<String>,
+    },
+    /// A text note anchored at a position. The note is shown as a marker and the text can be shown in a user interface or using a text object.
+    Note {
+        /// The position of the note.
+        position: Vec3,
+        /// The text of the note.
+        text: String,
+        /// The color of the marker.
+        color: Srgba,
+        /// The name of the anchor the position is relative to, if any.
+        anchor: Option<String>,
+    },
+    /// A freehand stroke through a list of points, for example drawn on the surface of a model by picking the point under the cursor every frame while a mouse button is pressed.
+    /// The points should be offset slightly along the surface normal to avoid the stroke intersecting the surface.
+    Stroke {
+        /// The points of the stroke.
+        points: Vec<Vec3>,
+        /// The color of the stroke.
+        color: Srgba,
+        /// The name of the anchor the points are relative to, if any.
+        anchor: Option<String>,
+    },
+}
+
+impl Annotation {
+    ///
+    /// Returns the name of the anchor of this annotation, if any.
+    ///
+    pub fn anchor(&self) -> Option<&str> {
+        match self {
+            Self::Marker { anchor, .. }
+            | Self::Note { anchor, .. }
+            | Self::Stroke { anchor, .. } => anchor.as_deref(),
+        }
+    }
+}
+
+///
+/// A collection of [Annotation]s which can be rendered together with the scene and, with the `annotations` feature, saved to and loaded from JSON,
+/// so for example review tools can save the markups made by a user and reload them later.
+///
+/// An annotation can be anchored to an object by giving it the name of an anchor and setting the transformation of the anchor to the transformation of the object,
+/// either directly using [AnnotationLayer::set_anchor] or from the named nodes of a scene graph using [AnnotationLayer::set_anchors_from_scene].
+///
+/// After changing the annotations or the anchors, the geometries used for rendering are updated automatically.
+/// The markers are rendered as spheres and the strokes as lines with a thickness in world units, both unlit in the color of the annotation.
+///
+pub struct AnnotationLayer {
+    context: Context,
+    annotations: Vec<Annotation>,
+    anchors: std::collections::HashMap<String, Mat4>,
+    marker_radius: f32,
+    stroke_thickness: f32,
+    markers: Option<Gm<InstancedMesh, ColorMaterial>>,
+    strokes: Option<Gm<Polylines, ColorMaterial>>,
+}
+
+impl AnnotationLayer {
+    ///
+    /// Creates a new empty annotation layer where the markers are rendered as spheres with the given radius and the strokes with the given thickness.
+    ///
+    pub fn new(context: &Context, marker_radius: f32, stroke_thickness: f32) -> Self {
+        Self {
+            context: context.clone(),
+            annotations: Vec::new(),
+            anchors: std::collections::HashMap::new(),
+            marker_radius,
+            stroke_thickness,
+            markers: None,
+            strokes: None,
+        }
+    }
+
+    ///
+    /// Creates a new annotation layer with the annotations in the given JSON, for example saved using [AnnotationLayer::to_json].
+    ///
+    #[cfg(feature = "annotations")]
+    #[cfg_attr(docsrs, doc(feature = "annotations"))]
+    pub fn from_json(
+        context: &Context,
+        json: &str,
+        marker_radius: f32,
+        stroke_thickness: f32,
+    ) -> Result<Self, RendererError> {
+        let file: AnnotationsFile = serde_json::from_str(json)
+            .map_err(|e| RendererError::InvalidAnnotations(e.to_string()))?;
+        let mut layer = Self::new(context, marker_radius, stroke_thickness);
+        layer.set_annotations(file.annotations.into_iter().map(|a| a.into()).collect());
+        Ok(layer)
+    }
+
+    ///
+    /// Returns the annotations serialized as JSON, which can be loaded again using [AnnotationLayer::from_json].
+    /// The transformations of the anchors are not saved, only the names of the anchors.
+    ///
+    #[cfg(feature = "annotations")]
+    #[cfg_attr(docsrs, doc(feature = "annotations"))]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&AnnotationsFile {
+            version: 1,
+            annotations: self.annotations.iter().map(|a| a.into()).collect(),
+        })
+        .unwrap()
+    }
+
+    ///
+    /// Sets the local to world transformation of the anchor with the given name, for example the transformation of the object the annotations with this anchor are placed on.
+    ///
+    pub fn set_anchor(&mut self, name: &str, transformation: Mat4) {
+        self.anchors.insert(name.to_string(), transformation);
+        self.update();
+    }
+
+    ///
+    /// Sets the transformation of an anchor for each named node in the subtree starting at the given node to the world transformation of the node, see [SceneNode::set_name].
+    ///
+    pub fn set_anchors_from_scene(&mut self, node: &SceneNode) {
+        fn collect(node: &SceneNode, anchors: &mut std::collections::HashMap<String, Mat4>) {
+            if let Some(name) = node.name() {
+                anchors.insert(name.to_string(), node.world_transformation());
+            }
+            for child in node.children() {
+                collect(child, anchors);
+            }
+        }
+        collect(node, &mut self.anchors);
+        self.update();
+    }
+
+    ///
+    /// Returns the given world space position in the local space of the anchor with the given name,
+    /// for example to anchor an annotation at a position found using [pick], or `None` if the anchor is not set or its transformation is not invertible.
+    ///
+    pub fn anchor_position(&self, name: &str, world_position: Vec3) -> Option<Vec3> {
+        let inverse = self.anchors.get(name)?.invert()?;
+        Some((inverse * world_position.extend(1.0)).truncate())
+    }
+
+    ///
+    /// Returns the annotations.
+    ///
+    pub fn annotations(&self) -> &[Annotation] {
+        &self.annotations
+    }
+
+    ///
+    /// Replaces all the annotations.
+    ///
+    pub fn set_annotations(&mut self, annotations: Vec<Annotation>) {
+        self.annotations = annotations;
+        self.update();
+    }
+
+    ///
+    /// Adds the given annotation and returns its index.
+    ///
+    pub fn add(&mut self, annotation: Annotation) -> usize {
+        self.annotations.push(annotation);
+        self.update();
+        self.annotations.len() - 1
+    }
+
+    ///
+    /// Replaces the annotation with the given index, for example to add a point to a stroke while it is being drawn.
+    ///
+    /// # Panics
+    /// If the index is out of bounds.
+    ///
+    pub fn set(&mut self, index: usize, annotation: Annotation) {
+        self.annotations[index] = annotation;
+        self.update();
+    }
+
+    ///
+    /// Removes and returns the annotation with the given index. The indices of the following annotations are decreased by one.
+    ///
+    /// # Panics
+    /// If the index is out of bounds.
+    ///
+    pub fn remove(&mut self, index: usize) -> Annotation {
+        let annotation = self.annotations.remove(index);
+        self.update();
+        annotation
+    }
+
+    ///
+    /// Removes all annotations.
+    ///
+    pub fn clear(&mut self) {
+        self.set_annotations(Vec::new());
+    }
+
+    fn update(&mut self) {
+        let mut transformations = Vec::new();
+        let mut marker_colors = Vec::new();
+        let mut lines = Vec::new();
+        let mut line_colors = Vec::new();
+        for annotation in self.annotations.iter() {
+            let anchor_transformation = annotation
+                .anchor()
+                .and_then(|name| self.anchors.get(name))
+                .copied()
+                .unwrap_or(Mat4::identity());
+            let world_position = |p: &Vec3| (anchor_transformation * p.extend(1.0)).truncate();
+            match annotation {
+                Annotation::Marker {
+                    position, color, ..
+                }
+                | Annotation::Note {
+                    position, color, ..
+                } => {
+                    transformations.push(
+                        Mat4::from_translation(world_position(position))
+                            * Mat4::from_scale(self.marker_radius),
+                    );
+                    marker_colors.push(*color);
+                }
+                Annotation::Stroke { points, color, .. } => {
+                    lines.push(points.iter().map(world_position).collect());
+                    line_colors.push(vec![*color; points.len()]);
+                }
+            }
+        }
+        self.markers = if transformations.is_empty() {
+            None
+        } else {
+            Some(Gm::new(
+                InstancedMesh::new(
+                    &self.context,
+                    &Instances {
+                        transformations,
+                        colors: Some(marker_colors),
+                        ..Default::default()
+                    },
+                    &CpuMesh::sphere(16),
+                ),
+                ColorMaterial::default(),
+            ))
+        };
+        self.strokes = if lines.iter().all(|line| line.len() < 2) {
+            None
+        } else {
+            Some(Gm::new(
+                Polylines::new_with_colors(
+                    &self.context,
+                    &lines,
+                    &line_colors,
+                    self.stroke_thickness,
+                ),
+                ColorMaterial::default(),
+            ))
+        };
+    }
+}
+
+impl<'a> IntoIterator for &'a AnnotationLayer {
+    type Item = &'a dyn Object;
+    type IntoIter = std::vec::IntoIter<&'a dyn Object>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let mut objects: Vec<&dyn Object> = Vec::new();
+        if let Some(markers) = &self.markers {
+            objects.push(markers);
+        }
+        if let Some(strokes) = &self.strokes {
+            objects.push(strokes);
+        }
+        objects.into_iter()
+    }
+}
+
+#[cfg(feature = "annotations")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct AnnotationsFile {
+    version: u32,
+    annotations: Vec<AnnotationData>,
+}
+
+#[cfg(feature = "annotations")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum AnnotationData {
+    Marker {
+        position: [f32; 3],
+        color: [u8; 4],
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        anchor: Option<String>,
+    },
+    Note {
+        position: [f32; 3],
+        text: String,
+        color: [u8; 4],
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        anchor: Option<String>,
+    },
+    Stroke {
+        points: Vec<[f32; 3]>,
+        color: [u8; 4],
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        anchor: Option<String>,
+    },
+}
+
+#[cfg(feature = "annotations")]
+impl From<&Annotation> for AnnotationData {
+    fn from(annotation: &Annotation) -> Self {
+        let color = |c: &Srgba| [c.r, c.g, c.b, c.a];
+        match annotation {
+            Annotation::Marker {
+                position,
+                color: c,
+                anchor,
+            } => Self::Marker {
+                position: (*position).into(),
+                color: color(c),
+                anchor: anchor.clone(),
+            },
+            Annotation::Note {
+                position,
+                text,
+                color: c,
+                anchor,
+            } => Self::Note {
+                position: (*position).into(),
+                text: text.clone(),
+                color: color(c),
+                anchor: anchor.clone(),
+            },
+            Annotation::Stroke {
+                points,
+                color: c,
+                anchor,
+            } => Self::Stroke {
+                points: points.iter().map(|p| (*p).into()).collect(),
+                color: color(c),
+                anchor: anchor.clone(),
+            },
+        }
+    }
+}
+
+#[cfg(feature = "annotations")]
+impl From<AnnotationData> for Annotation {
+    fn from(data: AnnotationData) -> Self {
+        let color = |[r, g, b, a]: [u8; 4]| Srgba::new(r, g, b, a);
+        match data {
+            AnnotationData::Marker {
+                position,
+                color: c,
+                anchor,
+            } => Self::Marker {
+                position: position.into(),
+                color: color(c),
+                anchor,
+            },
+            AnnotationData::Note {
+                position,
+                text,
+                color: c,
+                anchor,
+            } => Self::Note {
+                position: position.into(),
+                text,
+                color: color(c),
+                anchor,
+            },
+            AnnotationData::Stroke {
+                points,
+                color: c,
+                anchor,
+            } => Self::Stroke {
+                points: points.into_iter().map(|p| p.into()).collect(),
+                color: color(c),
+                anchor,
+            },
+        }
+    }
+}
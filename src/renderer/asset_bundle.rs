@@ -0,0 +1,42 @@
+use crate::renderer::*;
+use std::io::{Cursor, Read};
+
+///
+/// Loads a zip archive containing a bundle of assets and returns the files in the archive as raw assets,
+/// which can be deserialized using the path of the file relative to the root of the archive, for example `load_asset_bundle("scene.zip").await?.deserialize("scene.gltf")`.
+/// On web, the path is an url which is fetched using http, so a scene with many small files, for example the buffers and textures of a glTF file, is loaded using a single request.
+///
+pub async fn load_asset_bundle(
+    path: impl AsRef<std::path::Path>,
+) -> Result<three_d_asset::io::RawAssets, RendererError> {
+    let path = path.as_ref();
+    let bytes = three_d_asset::io::load_async(&[path]).await?.remove(path)?;
+    unpack_asset_bundle(&bytes)
+}
+
+///
+/// Returns the files in the given zip archive as raw assets, see [load_asset_bundle].
+/// Use this if the archive is already loaded, for example if it is included in the binary or downloaded using a custom loader.
+///
+pub fn unpack_asset_bundle(bytes: &[u8]) -> Result<three_d_asset::io::RawAssets, RendererError> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(bytes))
+        .map_err(|e| RendererError::InvalidAssetBundle(e.to_string()))?;
+    let mut raw_assets = three_d_asset::io::RawAssets::new();
+    for i in 0..archive.len() {
+        let mut file = archive
+            .by_index(i)
+            .map_err(|e| RendererError::InvalidAssetBundle(e.to_string()))?;
+        if file.is_dir() {
+            continue;
+        }
+        // Ignore files with paths outside the archive
+        if let Some(path) = file.enclosed_name().map(|p| p.to_path_buf()) {
+            // The uncompressed size is read from the archive, so it is only trusted up to the size of the archive
+            let mut data = Vec::with_capacity((file.size() as usize).min(bytes.len()));
+            file.read_to_end(&mut data)
+                .map_err(|e| RendererError::InvalidAssetBundle(e.to_string()))?;
+            raw_assets.insert(path, data);
+        }
+    }
+    Ok(raw_assets)
+}
@@ -5,6 +5,7 @@ mod color_space;
 pub use color_space::*;
 
 use crate::core::*;
+use crate::renderer::{PhysicalPoint, Ssao};
 
 ///
 /// Represents a camera used for viewing 2D and 3D objects.
@@ -14,8 +15,24 @@ pub struct Camera {
     camera: three_d_asset::Camera,
     /// This tone mapping is applied to the final color of renders using this camera.
     pub tone_mapping: ToneMapping,
+    ///
+    /// The colors are multiplied by the exposure before the tone mapping is applied, so a higher exposure gives a brighter image.
+    /// This makes it possible to adjust the brightness of high dynamic range scenes, for example a scene lit by the sun, without changing the lights. The default is 1.
+    /// The exposure is not applied when the tone mapping is [ToneMapping::None].
+    ///
+    pub exposure: f32,
     /// This color mapping is applied to the final color of renders using this camera.
     pub color_mapping: ColorMapping,
+    ///
+    /// Whether or not the vertices of the rendered geometries are snapped to the pixels of the viewport and the textures are sampled without filtering,
+    /// which gives crisp edges without sub-pixel jitter, for example for user interfaces and pixel art. See [Camera::new_2d_pixel_perfect].
+    ///
+    pub pixel_perfect: bool,
+    ///
+    /// Screen space ambient occlusion applied to the ambient light of objects with a deferred material, for example [DeferredPhysicalMaterial],
+    /// when rendered using for example [RenderTarget::render]. It is disabled by default.
+    ///
+    pub ssao: Option<Ssao>,
 }
 
 impl Camera {
@@ -36,7 +53,10 @@ impl Camera {
                 viewport, position, target, up, height, z_near, z_far,
             ),
             tone_mapping: ToneMapping::default(),
+            exposure: 1.0,
             color_mapping: ColorMapping::default(),
+            pixel_perfect: false,
+            ssao: None,
         }
     }
 
@@ -63,7 +83,10 @@ impl Camera {
                 z_far,
             ),
             tone_mapping: ToneMapping::default(),
+            exposure: 1.0,
             color_mapping: ColorMapping::default(),
+            pixel_perfect: false,
+            ssao: None,
         }
     }
 
@@ -93,6 +116,28 @@ impl Camera {
         )
     }
 
+    ///
+    /// Returns an orthographic camera for viewing 2D content where one unit is the given integer number of pixels, for example for rendering pixel art at 2x or 3x scale.
+    /// The (0, 0) position is at the bottom left corner and the (`viewport.width / scale`, `viewport.height / scale`) position is at the top right corner.
+    /// The vertices of the rendered geometries are snapped to the pixels of the viewport and the textures are sampled without filtering, see [Camera::pixel_perfect].
+    ///
+    pub fn new_2d_pixel_perfect(viewport: Viewport, scale: u32) -> Self {
+        let scale = scale.max(1) as f32;
+        let width = viewport.width as f32 / scale;
+        let height = viewport.height as f32 / scale;
+        let mut camera = Self::new_orthographic(
+            viewport,
+            vec3(width * 0.5, height * 0.5, 1.0),
+            vec3(width * 0.5, height * 0.5, 0.0),
+            vec3(0.0, 1.0, 0.0),
+            height,
+            0.0,
+            10.0,
+        );
+        camera.pixel_perfect = true;
+        camera
+    }
+
     ///
     /// Disables the tone and color mapping so as to be ready for rendering into an intermediate render target with this camera.
     ///
@@ -108,6 +153,56 @@ impl Camera {
         self.tone_mapping = ToneMapping::default();
         self.color_mapping = ColorMapping::default();
     }
+
+    ///
+    /// Sends the uniform data needed to apply the tone mapping, including the [Camera::exposure], and the color mapping of this camera to the fragment shader,
+    /// ie. for the `tone_mapping` and `color_mapping` functions in [ToneMapping::fragment_shader_source] and [ColorMapping::fragment_shader_source].
+    ///
+    pub fn use_tone_and_color_mapping_uniforms(&self, program: &Program) {
+        self.tone_mapping
+            .use_uniforms_with_exposure(program, self.exposure);
+        self.color_mapping.use_uniforms(program);
+    }
+
+    ///
+    /// Returns the focus distance, ie. the distance along the view direction to the geometry visible in the given pixel,
+    /// using the depth of the given render target which should be rendered with this camera.
+    /// This is typically used to set the focus distance of a depth of field effect to the object under the cursor.
+    /// Returns `None` if nothing is visible in the pixel.
+    ///
+    /// To also move the target of an [OrbitControl] to the point under the cursor, use [RenderTarget::read_position_at] and [OrbitControl::animate_target_to].
+    ///
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn auto_focus(
+        &self,
+        render_target: &RenderTarget,
+        pixel: impl Into<PhysicalPoint>,
+    ) -> Option<f32> {
+        render_target.read_depth_at(self, pixel.into())
+    }
+
+    ///
+    /// Returns the ray in world space starting at the camera and going through the given pixel,
+    /// which for example can be intersected with a mesh using [Bvh::intersect] to find the triangle under the cursor.
+    /// The pixel coordinate must be in physical pixels, where (viewport.x, viewport.y) indicate the bottom left corner of the viewport
+    /// and (viewport.x + viewport.width, viewport.y + viewport.height) indicate the top right corner.
+    ///
+    pub fn ray_at_pixel(&self, pixel: impl Into<PhysicalPoint> + Copy) -> Ray {
+        Ray::new(
+            self.position_at_pixel(pixel),
+            self.view_direction_at_pixel(pixel),
+        )
+    }
+
+    /// The size of the pixel grid which the vertices are snapped to, or zero if the vertices should not be snapped.
+    pub(crate) fn pixel_grid(&self) -> Vec2 {
+        if self.pixel_perfect {
+            let viewport = self.viewport();
+            vec2(viewport.width as f32, viewport.height as f32)
+        } else {
+            vec2(0.0, 0.0)
+        }
+    }
 }
 
 use std::ops::Deref;
@@ -24,8 +24,12 @@ impl ToneMapping {
     pub fn fragment_shader_source() -> &'static str {
         "
         uniform uint toneMappingType;
+        uniform float toneMappingExposure;
 
         vec3 tone_mapping(vec3 color) {
+            if (toneMappingType != 0u) {
+                color *= toneMappingExposure;
+            }
             if (toneMappingType == 1u) {
                 color = color / (color + vec3(1.0));
                 color = clamp(color, 0.0, 1.0);
@@ -52,9 +56,19 @@ impl ToneMapping {
     }
 
     ///
-    /// Sends the uniform data needed to apply this tone mapping to the fragment shader.
+    /// Sends the uniform data needed to apply this tone mapping to the fragment shader with an exposure of 1.
+    /// Use [Camera::use_tone_and_color_mapping_uniforms](crate::renderer::Camera::use_tone_and_color_mapping_uniforms) to apply the exposure of the camera.
     ///
     pub fn use_uniforms(&self, program: &Program) {
+        self.use_uniforms_with_exposure(program, 1.0);
+    }
+
+    ///
+    /// Sends the uniform data needed to apply this tone mapping to the fragment shader, where the color is multiplied by the given exposure before the tone mapping is applied.
+    /// The exposure is ignored if the tone mapping is [ToneMapping::None], so the high dynamic range colors are kept unchanged in intermediate render targets.
+    ///
+    pub fn use_uniforms_with_exposure(&self, program: &Program, exposure: f32) {
         program.use_uniform("toneMappingType", *self as u32);
+        program.use_uniform("toneMappingExposure", exposure);
     }
 }
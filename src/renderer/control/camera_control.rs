@@ -187,3 +187,55 @@ impl CameraControl {
         control_type != CameraAction::None
     }
 }
+
+// Keeps track of the movement keys which are held down, ie. W, A, S and D or the arrow keys for moving forward, left, backward and right and Q and E for moving down and up.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct MovementKeys {
+    forward: bool,
+    backward: bool,
+    left: bool,
+    right: bool,
+    up: bool,
+    down: bool,
+}
+
+impl MovementKeys {
+    // Updates the keys which are held down and returns whether or not a movement key was pressed or released.
+    pub fn handle_events(&mut self, events: &mut [Event]) -> bool {
+        let mut change = false;
+        for event in events.iter_mut() {
+            let (kind, pressed, handled) = match event {
+                Event::KeyPress { kind, handled, .. } => (*kind, true, handled),
+                Event::KeyRelease { kind, handled, .. } => (*kind, false, handled),
+                _ => continue,
+            };
+            // Releases are handled even if already handled to avoid keys getting stuck
+            if *handled && pressed {
+                continue;
+            }
+            let key = match kind {
+                Key::W | Key::ArrowUp => &mut self.forward,
+                Key::S | Key::ArrowDown => &mut self.backward,
+                Key::A | Key::ArrowLeft => &mut self.left,
+                Key::D | Key::ArrowRight => &mut self.right,
+                Key::E => &mut self.up,
+                Key::Q => &mut self.down,
+                _ => continue,
+            };
+            *key = pressed;
+            *handled = true;
+            change = true;
+        }
+        change
+    }
+
+    // Returns the direction of movement in camera space, ie. right, up and forward, which is zero if no keys are held down.
+    pub fn direction(&self) -> Vec3 {
+        let axis = |positive: bool, negative: bool| positive as i32 as f32 - negative as i32 as f32;
+        vec3(
+            axis(self.right, self.left),
+            axis(self.up, self.down),
+            axis(self.forward, self.backward),
+        )
+    }
+}
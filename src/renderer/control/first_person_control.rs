@@ -5,10 +5,13 @@ use crate::renderer::*;
 ///
 pub struct FirstPersonControl {
     control: CameraControl,
+    keys: MovementKeys,
+    speed: f32,
 }
 
 impl FirstPersonControl {
     /// Creates a new first person control with the given speed of movements.
+    /// Call [FirstPersonControl::update] each frame to also move the camera using the keyboard.
     pub fn new(speed: f32) -> Self {
         Self {
             control: CameraControl {
@@ -21,11 +24,32 @@ impl FirstPersonControl {
                 scroll_vertical: CameraAction::Forward { speed },
                 ..Default::default()
             },
+            keys: MovementKeys::default(),
+            speed,
         }
     }
 
     /// Handles the events. Must be called each frame.
     pub fn handle_events(&mut self, camera: &mut Camera, events: &mut [Event]) -> bool {
+        self.keys.handle_events(events);
         self.control.handle_events(camera, events)
     }
+
+    ///
+    /// Moves the camera while the W, A, S and D keys, the arrow keys or the Q and E keys are held down. Forward, left, backward and right are along the ground, ie. orthogonal to the up direction of the camera, while Q and E moves down and up.
+    /// The camera is moved the given speed for each 10 milliseconds, so the elapsed time in milliseconds since the last frame, for example the elapsed time given in the frame input, should be given.
+    /// Must be called each frame after [FirstPersonControl::handle_events]. Returns whether or not the camera has changed.
+    ///
+    pub fn update(&mut self, camera: &mut Camera, elapsed_time: f64) -> bool {
+        let direction = self.keys.direction();
+        if direction == vec3(0.0, 0.0, 0.0) {
+            return false;
+        }
+        let up = camera.up().normalize();
+        let right = camera.right_direction();
+        let forward = up.cross(right);
+        let translation = right * direction.x + up * direction.y + forward * direction.z;
+        camera.translate(&(translation * self.speed * 0.1 * elapsed_time as f32));
+        true
+    }
 }
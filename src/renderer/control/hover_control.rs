@@ -0,0 +1,166 @@
+use crate::renderer::*;
+
+///
+/// A 2D object which can test whether or not a point, typically the position of the cursor, is inside the object.
+/// Implemented for the 2D geometries, for example [Rectangle], [Circle] and [Line], and for a [Gm] with one of these geometries.
+///
+pub trait HitTest2D {
+    ///
+    /// Returns whether or not the given point in physical pixels is inside the object.
+    ///
+    fn hit_test(&self, point: PhysicalPoint) -> bool;
+}
+
+impl<T: HitTest2D + ?Sized> HitTest2D for &T {
+    fn hit_test(&self, point: PhysicalPoint) -> bool {
+        (*self).hit_test(point)
+    }
+}
+
+impl<T: HitTest2D + ?Sized> HitTest2D for Box<T> {
+    fn hit_test(&self, point: PhysicalPoint) -> bool {
+        self.as_ref().hit_test(point)
+    }
+}
+
+impl<G: Geometry + HitTest2D, M: Material> HitTest2D for Gm<G, M> {
+    fn hit_test(&self, point: PhysicalPoint) -> bool {
+        self.geometry.hit_test(point)
+    }
+}
+
+///
+/// An interaction between the cursor and a 2D object, returned by [HoverControl::handle_events].
+/// The object is given by its index in the list of objects given to [HoverControl::handle_events].
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Interaction2D {
+    /// The cursor entered the object.
+    HoverStart(usize),
+    /// The cursor left the object.
+    HoverEnd(usize),
+    /// A mouse button was pressed while the cursor was on the object.
+    Press(usize, MouseButton),
+    /// A mouse button was pressed and released while the cursor was on the object.
+    Click(usize, MouseButton),
+}
+
+///
+/// A control that keeps track of which 2D object, for example a [Rectangle] in a HUD, is under the cursor and reports when the cursor enters and leaves an object and when an object is clicked.
+/// This enables simple interactive user interfaces without a full GUI library.
+///
+/// When objects overlap, the object last in the list of objects is considered to be on top, which matches the render order when rendering the objects in the same order, see [RenderTarget::render_ui].
+///
+#[derive(Debug, Default)]
+pub struct HoverControl {
+    hovered: Option<usize>,
+    pressed: Option<(usize, MouseButton)>,
+}
+
+impl HoverControl {
+    ///
+    /// Creates a new hover control.
+    ///
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///
+    /// Handles the events and returns the interactions with the given objects in the order they happened. Must be called each frame.
+    /// The mouse press and release events on an object are marked as handled, so they are not also used by for example a camera control.
+    ///
+    pub fn handle_events(
+        &mut self,
+        events: &mut [Event],
+        objects: &[&dyn HitTest2D],
+    ) -> Vec<Interaction2D> {
+        let mut interactions = Vec::new();
+        for event in events.iter_mut() {
+            match event {
+                Event::MouseMotion {
+                    position, handled, ..
+                } => {
+                    let hit = if *handled {
+                        None
+                    } else {
+                        pick_2d(objects, PhysicalPoint::from(*position))
+                    };
+                    self.set_hovered(hit, &mut interactions);
+                }
+                Event::MousePress {
+                    button,
+                    position,
+                    handled,
+                    ..
+                } => {
+                    if !*handled {
+                        let hit = pick_2d(objects, PhysicalPoint::from(*position));
+                        self.set_hovered(hit, &mut interactions);
+                        if let Some(index) = hit {
+                            interactions.push(Interaction2D::Press(index, *button));
+                            self.pressed = Some((index, *button));
+                            *handled = true;
+                        }
+                    }
+                }
+                Event::MouseRelease {
+                    button,
+                    position,
+                    handled,
+                    ..
+                } => {
+                    if !*handled {
+                        let hit = pick_2d(objects, PhysicalPoint::from(*position));
+                        if let Some(index) = hit {
+                            if self.pressed == Some((index, *button)) {
+                                interactions.push(Interaction2D::Click(index, *button));
+                            }
+                            *handled = true;
+                        }
+                    }
+                    self.pressed = None;
+                }
+                Event::MouseLeave => {
+                    self.set_hovered(None, &mut interactions);
+                    self.pressed = None;
+                }
+                _ => {}
+            }
+        }
+        interactions
+    }
+
+    ///
+    /// Returns the index of the object under the cursor, if any.
+    ///
+    pub fn hovered(&self) -> Option<usize> {
+        self.hovered
+    }
+
+    ///
+    /// Returns the index of the object which a mouse button was pressed on and which has not been released yet, if any.
+    ///
+    pub fn pressed(&self) -> Option<usize> {
+        self.pressed.map(|(index, _)| index)
+    }
+
+    fn set_hovered(&mut self, hit: Option<usize>, interactions: &mut Vec<Interaction2D>) {
+        if hit != self.hovered {
+            if let Some(index) = self.hovered {
+                interactions.push(Interaction2D::HoverEnd(index));
+            }
+            if let Some(index) = hit {
+                interactions.push(Interaction2D::HoverStart(index));
+            }
+            self.hovered = hit;
+        }
+    }
+}
+
+///
+/// Returns the index of the top-most of the given objects at the given point in physical pixels, if any.
+/// The object last in the list is considered to be on top.
+///
+pub fn pick_2d(objects: &[&dyn HitTest2D], point: PhysicalPoint) -> Option<usize> {
+    objects.iter().rposition(|object| object.hit_test(point))
+}
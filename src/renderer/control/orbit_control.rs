@@ -2,9 +2,20 @@ use crate::renderer::*;
 
 ///
 /// A control that makes the camera orbit around a target.
+/// Dragging with the left mouse button orbits around the target, dragging with the right mouse button pans the camera and the target
+/// and scrolling zooms towards the target within the minimum and maximum distance.
 ///
 pub struct OrbitControl {
     control: CameraControl,
+    target: Vec3,
+    animation: Option<TargetAnimation>,
+}
+
+struct TargetAnimation {
+    from: Vec3,
+    to: Vec3,
+    duration: f64,
+    time: f64,
 }
 
 impl OrbitControl {
@@ -22,6 +33,78 @@ impl OrbitControl {
                 },
                 ..Default::default()
             },
+            target,
+            animation: None,
+        }
+    }
+
+    /// Returns the target that the camera orbits around.
+    pub fn target(&self) -> Vec3 {
+        self.target
+    }
+
+    ///
+    /// Sets the target that the camera orbits around without moving the camera.
+    /// Stops any ongoing animation started by [OrbitControl::animate_target_to].
+    ///
+    pub fn set_target(&mut self, target: Vec3) {
+        self.animation = None;
+        self.set_target_internal(target);
+    }
+
+    ///
+    /// Starts an animation which moves the target, and the camera along with it, to the given target over the given duration in milliseconds.
+    /// The animation is advanced by [OrbitControl::update] which must be called each frame.
+    /// This can be used together with [RenderTarget::read_position_at] to focus on the point under the cursor.
+    ///
+    pub fn animate_target_to(&mut self, target: Vec3, duration: f64) {
+        self.animation = Some(TargetAnimation {
+            from: self.target,
+            to: target,
+            duration,
+            time: 0.0,
+        });
+    }
+
+    ///
+    /// Advances the animation started by [OrbitControl::animate_target_to] by the given elapsed time in milliseconds, for example the elapsed time given in the frame input.
+    /// Returns whether or not the camera has changed.
+    ///
+    pub fn update(&mut self, camera: &mut Camera, elapsed_time: f64) -> bool {
+        if let Some(mut animation) = self.animation.take() {
+            animation.time += elapsed_time;
+            let t = if animation.duration > 0.0 {
+                (animation.time / animation.duration).min(1.0) as f32
+            } else {
+                1.0
+            };
+            // Smoothstep for ease-in and ease-out
+            let t = t * t * (3.0 - 2.0 * t);
+            let target = animation.from.lerp(animation.to, t);
+            let translation = target - self.target;
+            let position = *camera.position() + translation;
+            let up = *camera.up();
+            camera.set_view(position, *camera.target() + translation, up);
+            self.set_target_internal(target);
+            if animation.time < animation.duration {
+                self.animation = Some(animation);
+            }
+            true
+        } else {
+            false
+        }
+    }
+
+    fn set_target_internal(&mut self, new_target: Vec3) {
+        self.target = new_target;
+        if let CameraAction::Zoom { target, .. } = &mut self.control.scroll_vertical {
+            *target = new_target;
+        }
+        if let CameraAction::OrbitLeft { target, .. } = &mut self.control.left_drag_horizontal {
+            *target = new_target;
+        }
+        if let CameraAction::OrbitUp { target, .. } = &mut self.control.left_drag_vertical {
+            *target = new_target;
         }
     }
 
@@ -39,6 +122,27 @@ impl OrbitControl {
             let x = target.distance(*camera.position());
             *speed = 0.01 * x + 0.001;
         }
-        self.control.handle_events(camera, events)
+        let mut change = false;
+        for event in events.iter_mut() {
+            if let Event::MouseMotion {
+                delta,
+                button: Some(MouseButton::Right),
+                handled,
+                ..
+            } = event
+            {
+                if !*handled {
+                    let speed = 0.001 * self.target.distance(*camera.position()) + 0.0001;
+                    let right = camera.right_direction();
+                    let up = right.cross(camera.view_direction());
+                    let translation = (up * delta.1 - right * delta.0) * speed;
+                    camera.translate(&translation);
+                    self.set_target_internal(self.target + translation);
+                    *handled = true;
+                    change = true;
+                }
+            }
+        }
+        self.control.handle_events(camera, events) || change
     }
 }
@@ -64,6 +64,38 @@ mod water;
 #[doc(inline)]
 pub use water::*;
 
+mod backdrop_blur;
+#[doc(inline)]
+pub use backdrop_blur::*;
+
+mod upsample;
+#[doc(inline)]
+pub use upsample::*;
+
+mod effect_chain;
+#[doc(inline)]
+pub use effect_chain::*;
+
+mod bloom;
+#[doc(inline)]
+pub use bloom::*;
+
+mod vignette;
+#[doc(inline)]
+pub use vignette::*;
+
+mod color_grading;
+#[doc(inline)]
+pub use color_grading::*;
+
+mod ssao;
+#[doc(inline)]
+pub use ssao::*;
+
+mod soft_particles;
+#[doc(inline)]
+pub use soft_particles::*;
+
 pub(crate) mod lighting_pass;
 
 use crate::renderer::*;
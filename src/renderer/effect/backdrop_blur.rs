@@ -0,0 +1,89 @@
+use crate::renderer::*;
+
+///
+/// An effect that blurs the background, ie. the pixels where nothing but the skybox or the clear color is rendered, while keeping the rendered objects sharp.
+/// This gives the typical frosted backdrop look of a studio product shot, where the subject stands out from a soft, out of focus environment.
+///
+/// The background is detected using the depth texture, so the scene has to be rendered into a color and a depth texture before applying this effect.
+/// For a cheaper but lower quality blur of an environment map, see [Skybox::set_blur].
+///
+#[derive(Clone, Debug)]
+pub struct BackdropBlurEffect {
+    /// The radius of the blur in pixels.
+    pub radius: f32,
+}
+
+impl Default for BackdropBlurEffect {
+    fn default() -> Self {
+        Self { radius: 25.0 }
+    }
+}
+
+impl Effect for BackdropBlurEffect {
+    fn fragment_shader_source(
+        &self,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) -> String {
+        format!(
+            "{}\n{}\n{}\n{}\n{}",
+            color_texture
+                .expect("Must supply a color texture to apply a backdrop blur effect")
+                .fragment_shader_source(),
+            depth_texture
+                .expect("Must supply a depth texture to apply a backdrop blur effect")
+                .fragment_shader_source(),
+            ToneMapping::fragment_shader_source(),
+            ColorMapping::fragment_shader_source(),
+            include_str!("shaders/backdrop_blur_effect.frag")
+        )
+    }
+
+    fn id(&self, color_texture: Option<ColorTexture>, depth_texture: Option<DepthTexture>) -> u16 {
+        0b1u16 << 14
+            | 0b1u16 << 11
+            | color_texture
+                .expect("Must supply a color texture to apply a backdrop blur effect")
+                .id()
+            | depth_texture
+                .expect("Must supply a depth texture to apply a backdrop blur effect")
+                .id()
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            uv: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(
+        &self,
+        program: &Program,
+        camera: &Camera,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) {
+        camera.use_tone_and_color_mapping_uniforms(program);
+        let color_texture =
+            color_texture.expect("Must supply a color texture to apply a backdrop blur effect");
+        let w = color_texture.width();
+        let h = color_texture.height();
+        color_texture.use_uniforms(program);
+        depth_texture
+            .expect("Must supply a depth texture to apply a backdrop blur effect")
+            .use_uniforms(program);
+        program.use_uniform("resolution", vec2(w as f32, h as f32));
+        program.use_uniform("radius", self.radius);
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates {
+            depth_test: DepthTest::Always,
+            cull: Cull::Back,
+            ..Default::default()
+        }
+    }
+}
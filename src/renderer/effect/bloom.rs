@@ -0,0 +1,218 @@
+use crate::renderer::*;
+use std::cell::RefCell;
+
+///
+/// An [ImageEffect] which makes bright areas of the image bleed into the surroundings, simulating the glow around bright light sources seen by a real camera.
+/// The pixels brighter than the threshold are extracted into a half resolution texture which is blurred and added on top of the image.
+///
+/// The bloom is most convincing when applied to the high dynamic range colors before tone mapping, which is the case in an [EffectChain].
+///
+pub struct BloomEffect {
+    /// The brightness above which pixels contributes to the bloom. A value above 1 means that only high dynamic range colors contributes.
+    pub threshold: f32,
+    /// The intensity of the bloom which is added to the image.
+    pub intensity: f32,
+    /// The number of blur iterations, more iterations gives a wider glow.
+    pub iterations: u32,
+    textures: RefCell<Option<(Texture2D, Texture2D)>>,
+}
+
+impl BloomEffect {
+    ///
+    /// Creates a new bloom effect with the given threshold and intensity.
+    ///
+    pub fn new(threshold: f32, intensity: f32) -> Self {
+        Self {
+            threshold,
+            intensity,
+            iterations: 4,
+            textures: RefCell::new(None),
+        }
+    }
+}
+
+impl Default for BloomEffect {
+    fn default() -> Self {
+        Self::new(1.0, 0.5)
+    }
+}
+
+impl ImageEffect for BloomEffect {
+    fn apply(
+        &self,
+        context: &Context,
+        camera: &Camera,
+        target: &ColorTarget,
+        color_texture: &Texture2D,
+        _depth_texture: &DepthTexture2D,
+    ) {
+        let width = (color_texture.width() / 2).max(1);
+        let height = (color_texture.height() / 2).max(1);
+        let mut textures = self.textures.borrow_mut();
+        if textures
+            .as_ref()
+            .map(|(t, _)| t.width() != width || t.height() != height)
+            .unwrap_or(true)
+        {
+            let new_texture = || {
+                Texture2D::new_empty::<[f16; 4]>(
+                    context,
+                    width,
+                    height,
+                    Interpolation::Linear,
+                    Interpolation::Linear,
+                    None,
+                    Wrapping::ClampToEdge,
+                    Wrapping::ClampToEdge,
+                )
+            };
+            *textures = Some((new_texture(), new_texture()));
+        }
+        let (bright, blurred) = textures.as_mut().unwrap();
+        let mut half_camera = camera.clone();
+        half_camera.set_viewport(Viewport::new_at_origo(width, height));
+
+        bright.as_color_target(None).apply_screen_effect(
+            &BloomPass::Threshold(self.threshold),
+            &half_camera,
+            &[],
+            Some(ColorTexture::Single(color_texture)),
+            None,
+        );
+        for _ in 0..self.iterations.max(1) {
+            blurred.as_color_target(None).apply_screen_effect(
+                &BloomPass::Blur(vec2(1.0 / width as f32, 0.0)),
+                &half_camera,
+                &[],
+                Some(ColorTexture::Single(bright)),
+                None,
+            );
+            bright.as_color_target(None).apply_screen_effect(
+                &BloomPass::Blur(vec2(0.0, 1.0 / height as f32)),
+                &half_camera,
+                &[],
+                Some(ColorTexture::Single(blurred)),
+                None,
+            );
+        }
+        target.apply_screen_effect(
+            &BloomPass::Composite(self.intensity, bright),
+            camera,
+            &[],
+            Some(ColorTexture::Single(color_texture)),
+            None,
+        );
+    }
+}
+
+enum BloomPass<'a> {
+    Threshold(f32),
+    Blur(Vec2),
+    Composite(f32, &'a Texture2D),
+}
+
+impl Effect for BloomPass<'_> {
+    fn fragment_shader_source(
+        &self,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        _depth_texture: Option<DepthTexture>,
+    ) -> String {
+        let main = match self {
+            Self::Threshold(_) => {
+                "
+                uniform float threshold;
+                void main()
+                {
+                    vec3 color = sample_color(uvs).rgb;
+                    float brightness = max(color.r, max(color.g, color.b));
+                    outColor = vec4(color * max(brightness - threshold, 0.0) / max(brightness, 0.0001), 1.0);
+                }"
+            }
+            Self::Blur(_) => {
+                "
+                uniform vec2 direction;
+                void main()
+                {
+                    float weights[5] = float[](0.227027, 0.1945946, 0.1216216, 0.054054, 0.016216);
+                    vec3 color = sample_color(uvs).rgb * weights[0];
+                    for (int i = 1; i < 5; i++) {
+                        color += sample_color(uvs + float(i) * direction).rgb * weights[i];
+                        color += sample_color(uvs - float(i) * direction).rgb * weights[i];
+                    }
+                    outColor = vec4(color, 1.0);
+                }"
+            }
+            Self::Composite(..) => {
+                "
+                uniform sampler2D bloomMap;
+                uniform float intensity;
+                void main()
+                {
+                    outColor = sample_color(uvs);
+                    outColor.rgb += intensity * texture(bloomMap, uvs).rgb;
+                }"
+            }
+        };
+        format!(
+            "{}
+            in vec2 uvs;
+            layout (location = 0) out vec4 outColor;
+            {}",
+            color_texture
+                .expect("Must supply a color texture to apply a bloom effect")
+                .fragment_shader_source(),
+            main
+        )
+    }
+
+    fn id(&self, color_texture: Option<ColorTexture>, _depth_texture: Option<DepthTexture>) -> u16 {
+        let color_texture =
+            color_texture.expect("Must supply a color texture to apply a bloom effect");
+        0b1u16 << 14
+            | 0b1u16 << 7
+            | match self {
+                Self::Threshold(_) => 0u16,
+                Self::Blur(_) => 0b1u16 << 8,
+                Self::Composite(..) => 0b1u16 << 9,
+            }
+            | color_texture.id()
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            uv: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(
+        &self,
+        program: &Program,
+        _camera: &Camera,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        _depth_texture: Option<DepthTexture>,
+    ) {
+        color_texture
+            .expect("Must supply a color texture to apply a bloom effect")
+            .use_uniforms(program);
+        match self {
+            Self::Threshold(threshold) => program.use_uniform("threshold", *threshold),
+            Self::Blur(direction) => program.use_uniform("direction", *direction),
+            Self::Composite(intensity, bloom_texture) => {
+                program.use_uniform("intensity", *intensity);
+                program.use_texture("bloomMap", bloom_texture);
+            }
+        }
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates {
+            write_mask: WriteMask::COLOR,
+            depth_test: DepthTest::Always,
+            cull: Cull::Back,
+            ..Default::default()
+        }
+    }
+}
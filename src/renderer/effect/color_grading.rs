@@ -0,0 +1,147 @@
+use crate::renderer::*;
+use std::sync::Arc;
+
+///
+/// An effect which changes the colors of the image using a color lookup table (LUT), for example to give the image a warm, cold or cinematic look.
+/// The lookup table is a 3D texture where the red, green and blue channels of the input color are used as the x, y and z texture coordinates
+/// and the color at that position is the output color. A lookup table of 16x16x16 or 32x32x32 texels is usually sufficient.
+///
+/// Lookup tables are typically created by color grading a screenshot in an image editor together with a neutral lookup table and are stored as a strip,
+/// ie. an image where the slices of the 3D texture are placed next to each other horizontally, see [ColorGradingEffect::new_from_strip].
+///
+/// The input colors are clamped to the range `[0, 1]` before the lookup.
+///
+#[derive(Clone)]
+pub struct ColorGradingEffect {
+    lut: Arc<Texture3D>,
+    /// How much the colors are changed, where 0 gives the original colors and 1 gives the colors from the lookup table.
+    pub intensity: f32,
+}
+
+impl ColorGradingEffect {
+    ///
+    /// Creates a new color grading effect from a 3D lookup table.
+    ///
+    pub fn new(context: &Context, lut: &CpuTexture3D) -> Self {
+        let mut lut = lut.clone();
+        lut.min_filter = Interpolation::Linear;
+        lut.mag_filter = Interpolation::Linear;
+        lut.mip_map_filter = None;
+        lut.wrap_s = Wrapping::ClampToEdge;
+        lut.wrap_t = Wrapping::ClampToEdge;
+        lut.wrap_r = Wrapping::ClampToEdge;
+        Self {
+            lut: Arc::new(Texture3D::new(context, &lut)),
+            intensity: 1.0,
+        }
+    }
+
+    ///
+    /// Creates a new color grading effect from a lookup table stored as a strip, ie. an 8-bit RGBA image with the size `N*N x N`
+    /// where the `N` slices of the 3D lookup table are placed next to each other from left to right with increasing blue value.
+    ///
+    /// # Panics
+    /// If the image is not an 8-bit RGBA image or the width is not the height squared.
+    ///
+    pub fn new_from_strip(context: &Context, strip: &CpuTexture) -> Self {
+        let size = strip.height;
+        assert_eq!(
+            strip.width,
+            size * size,
+            "the width of a lookup table strip must be the height squared"
+        );
+        let data = match &strip.data {
+            TextureData::RgbaU8(data) => data,
+            _ => panic!("a lookup table strip must be an 8-bit RGBA image"),
+        };
+        let mut texels = Vec::with_capacity(data.len());
+        for z in 0..size {
+            for y in 0..size {
+                for x in 0..size {
+                    texels.push(data[(y * strip.width + z * size + x) as usize]);
+                }
+            }
+        }
+        Self::new(
+            context,
+            &CpuTexture3D {
+                data: TextureData::RgbaU8(texels),
+                width: size,
+                height: size,
+                depth: size,
+                ..Default::default()
+            },
+        )
+    }
+}
+
+impl Effect for ColorGradingEffect {
+    fn fragment_shader_source(
+        &self,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        _depth_texture: Option<DepthTexture>,
+    ) -> String {
+        format!(
+            "{}
+            uniform sampler3D lut;
+            uniform float lutSize;
+            uniform float intensity;
+
+            in vec2 uvs;
+            layout (location = 0) out vec4 outColor;
+
+            void main()
+            {{
+                outColor = sample_color(uvs);
+                // Sample at the texel centers, so the colors 0 and 1 maps to the first and last texel
+                vec3 coords = (clamp(outColor.rgb, 0.0, 1.0) * (lutSize - 1.0) + 0.5) / lutSize;
+                outColor.rgb = mix(outColor.rgb, texture(lut, coords).rgb, intensity);
+            }}",
+            color_texture
+                .expect("Must supply a color texture to apply a color grading effect")
+                .fragment_shader_source()
+        )
+    }
+
+    fn id(&self, color_texture: Option<ColorTexture>, _depth_texture: Option<DepthTexture>) -> u16 {
+        0b1u16 << 14
+            | 0b1u16 << 7
+            | 0b1u16 << 11
+            | color_texture
+                .expect("Must supply a color texture to apply a color grading effect")
+                .id()
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            uv: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(
+        &self,
+        program: &Program,
+        _camera: &Camera,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        _depth_texture: Option<DepthTexture>,
+    ) {
+        color_texture
+            .expect("Must supply a color texture to apply a color grading effect")
+            .use_uniforms(program);
+        program.use_texture_3d("lut", &self.lut);
+        program.use_uniform("lutSize", self.lut.width() as f32);
+        program.use_uniform("intensity", self.intensity.clamp(0.0, 1.0));
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates {
+            write_mask: WriteMask::COLOR,
+            depth_test: DepthTest::Always,
+            cull: Cull::Back,
+            ..Default::default()
+        }
+    }
+}
@@ -0,0 +1,251 @@
+use crate::renderer::*;
+
+///
+/// An effect in an [EffectChain] which reads the color and depth texture of the previous step in the chain and writes the result into a color target.
+/// In contrast to an [Effect], an image effect can consist of several passes, for example the [BloomEffect].
+/// Any [Effect] which only needs the color and/or depth texture can be used in an effect chain, see [EffectChain::push_effect].
+///
+pub trait ImageEffect {
+    ///
+    /// Applies this effect to the given color texture and writes the result into the given target which has the same size as the color texture.
+    /// The depth texture contains the depth of the rendered scene.
+    /// The camera has the same viewport as the target and tone and color mapping is disabled, since it is applied at the end of the chain.
+    ///
+    fn apply(
+        &self,
+        context: &Context,
+        camera: &Camera,
+        target: &ColorTarget,
+        color_texture: &Texture2D,
+        depth_texture: &DepthTexture2D,
+    );
+}
+
+impl<T: ImageEffect + ?Sized> ImageEffect for &T {
+    fn apply(
+        &self,
+        context: &Context,
+        camera: &Camera,
+        target: &ColorTarget,
+        color_texture: &Texture2D,
+        depth_texture: &DepthTexture2D,
+    ) {
+        (*self).apply(context, camera, target, color_texture, depth_texture)
+    }
+}
+
+impl<T: ImageEffect + ?Sized> ImageEffect for Box<T> {
+    fn apply(
+        &self,
+        context: &Context,
+        camera: &Camera,
+        target: &ColorTarget,
+        color_texture: &Texture2D,
+        depth_texture: &DepthTexture2D,
+    ) {
+        self.as_ref()
+            .apply(context, camera, target, color_texture, depth_texture)
+    }
+}
+
+struct ScreenSpaceEffect<E: Effect>(E);
+
+impl<E: Effect> ImageEffect for ScreenSpaceEffect<E> {
+    fn apply(
+        &self,
+        _context: &Context,
+        camera: &Camera,
+        target: &ColorTarget,
+        color_texture: &Texture2D,
+        depth_texture: &DepthTexture2D,
+    ) {
+        target.apply_screen_effect(
+            &self.0,
+            camera,
+            &[],
+            Some(ColorTexture::Single(color_texture)),
+            Some(DepthTexture::Single(depth_texture)),
+        );
+    }
+}
+
+///
+/// A stack of screen space effects, for example [BloomEffect], [FxaaEffect], [VignetteEffect] and [ColorGradingEffect], which are applied in order to a rendered scene.
+/// The chain owns the textures which the scene is rendered into and the intermediate textures which are used for ping-ponging between the effects.
+///
+/// The scene is rendered without tone and color mapping into floating point textures (unless the [IntermediateFormat] of the context is [IntermediateFormat::Rgba8]),
+/// so the effects work on the linear high dynamic range colors, and the exposure, tone mapping and color mapping of the camera is applied in the final write to the target,
+/// for example the screen, see [Camera::exposure] and [Camera::tone_mapping].
+/// Use [EffectChain::render] to render the scene and apply the effects in one call, or render into [EffectChain::scene_target] and call [EffectChain::apply].
+///
+pub struct EffectChain {
+    context: Context,
+    effects: Vec<Box<dyn ImageEffect>>,
+    textures: Option<(Texture2D, Texture2D, DepthTexture2D)>,
+}
+
+impl EffectChain {
+    ///
+    /// Creates a new empty effect chain.
+    ///
+    pub fn new(context: &Context) -> Self {
+        Self {
+            context: context.clone(),
+            effects: Vec::new(),
+            textures: None,
+        }
+    }
+
+    ///
+    /// Adds the given image effect at the end of the chain.
+    ///
+    pub fn push(&mut self, effect: impl ImageEffect + 'static) {
+        self.effects.push(Box::new(effect));
+    }
+
+    ///
+    /// Adds the given [Effect] at the end of the chain. The effect is given the color and depth texture of the previous step and no lights.
+    ///
+    pub fn push_effect(&mut self, effect: impl Effect + 'static) {
+        self.push(ScreenSpaceEffect(effect));
+    }
+
+    ///
+    /// Removes and returns the effect with the given index.
+    ///
+    /// # Panics
+    /// If the index is out of bounds.
+    ///
+    pub fn remove(&mut self, index: usize) -> Box<dyn ImageEffect> {
+        self.effects.remove(index)
+    }
+
+    ///
+    /// Removes all effects from the chain.
+    ///
+    pub fn clear(&mut self) {
+        self.effects.clear();
+    }
+
+    ///
+    /// Returns the number of effects in the chain.
+    ///
+    pub fn len(&self) -> usize {
+        self.effects.len()
+    }
+
+    ///
+    /// Returns whether or not the chain contains any effects.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.effects.is_empty()
+    }
+
+    ///
+    /// Returns the render target which the scene should be rendered into before calling [EffectChain::apply]. The render target has the given size
+    /// and the scene should be rendered with a camera with a viewport at the origin with that size and with tone and color mapping disabled,
+    /// see [Camera::disable_tone_and_color_mapping].
+    ///
+    pub fn scene_target(&mut self, width: u32, height: u32) -> RenderTarget<'_> {
+        let (color_texture, _, depth_texture) = self.textures(width, height);
+        RenderTarget::new(
+            color_texture.as_color_target(None),
+            depth_texture.as_depth_target(),
+        )
+    }
+
+    ///
+    /// Renders the given objects with the given camera and lights, applies all effects in the chain and writes the result into the given target,
+    /// which is usually the screen, with the viewport, tone mapping and color mapping of the camera.
+    ///
+    pub fn render(
+        &mut self,
+        camera: &Camera,
+        objects: impl IntoIterator<Item = impl Object>,
+        lights: &[&dyn Light],
+        target: &RenderTarget,
+    ) {
+        let viewport = camera.viewport();
+        let mut scene_camera = camera.clone();
+        scene_camera.set_viewport(Viewport::new_at_origo(viewport.width, viewport.height));
+        scene_camera.disable_tone_and_color_mapping();
+        self.scene_target(viewport.width, viewport.height)
+            .clear(ClearState::color_and_depth(0.0, 0.0, 0.0, 0.0, 1.0))
+            .render(&scene_camera, objects, lights);
+        self.apply(camera, target);
+    }
+
+    ///
+    /// Applies all effects in the chain to the scene rendered into [EffectChain::scene_target] and writes the result into the given target,
+    /// which is usually the screen, with the viewport, tone mapping and color mapping of the camera.
+    ///
+    pub fn apply(&mut self, camera: &Camera, target: &RenderTarget) {
+        let (first, second, depth_texture) = match self.textures.as_mut() {
+            Some((first, second, depth_texture)) => (first, second, depth_texture),
+            None => return,
+        };
+        let mut effect_camera = camera.clone();
+        effect_camera.set_viewport(Viewport::new_at_origo(first.width(), first.height()));
+        effect_camera.disable_tone_and_color_mapping();
+
+        self.context.push_debug_group("Effect chain");
+        let mut input_is_first = true;
+        for effect in self.effects.iter() {
+            let (input, output) = if input_is_first {
+                (&*first, &mut *second)
+            } else {
+                (&*second, &mut *first)
+            };
+            effect.apply(
+                &self.context,
+                &effect_camera,
+                output
+                    .as_color_target(None)
+                    .clear(ClearState::color(0.0, 0.0, 0.0, 0.0)),
+                input,
+                depth_texture,
+            );
+            input_is_first = !input_is_first;
+        }
+        let result = if input_is_first { &*first } else { &*second };
+        target.apply_screen_effect(
+            &ScreenEffect {
+                write_mask: WriteMask::COLOR,
+            },
+            camera,
+            &[],
+            Some(ColorTexture::Single(result)),
+            None,
+        );
+        self.context.pop_debug_group();
+    }
+
+    fn textures(
+        &mut self,
+        width: u32,
+        height: u32,
+    ) -> (&mut Texture2D, &mut Texture2D, &mut DepthTexture2D) {
+        if self
+            .textures
+            .as_ref()
+            .map(|(t, _, _)| t.width() != width || t.height() != height)
+            .unwrap_or(true)
+        {
+            let new_texture =
+                || new_intermediate_texture(&self.context, width, height, Interpolation::Linear);
+            self.textures = Some((
+                new_texture(),
+                new_texture(),
+                DepthTexture2D::new::<f32>(
+                    &self.context,
+                    width,
+                    height,
+                    Wrapping::ClampToEdge,
+                    Wrapping::ClampToEdge,
+                ),
+            ));
+        }
+        let (first, second, depth_texture) = self.textures.as_mut().unwrap();
+        (first, second, depth_texture)
+    }
+}
@@ -75,8 +75,7 @@ impl Effect for FogEffect {
         color_texture: Option<ColorTexture>,
         depth_texture: Option<DepthTexture>,
     ) {
-        camera.tone_mapping.use_uniforms(program);
-        camera.color_mapping.use_uniforms(program);
+        camera.use_tone_and_color_mapping_uniforms(program);
         color_texture
             .expect("Must supply a color texture to apply a fog effect")
             .use_uniforms(program);
@@ -75,8 +75,7 @@ impl Effect for ScreenEffect {
         depth_texture: Option<DepthTexture>,
     ) {
         if let Some(color_texture) = color_texture {
-            camera.tone_mapping.use_uniforms(program);
-            camera.color_mapping.use_uniforms(program);
+            camera.use_tone_and_color_mapping_uniforms(program);
             color_texture.use_uniforms(program);
         }
         if let Some(depth_texture) = depth_texture {
@@ -1,21 +1,28 @@
 use crate::renderer::*;
 
-pub struct LightingPassEffect {}
+pub struct LightingPassEffect<'a> {
+    pub ssao: Option<&'a Texture2D>,
+}
 
-impl Effect for LightingPassEffect {
+impl Effect for LightingPassEffect<'_> {
     fn fragment_shader_source(
         &self,
         lights: &[&dyn Light],
         color_texture: Option<ColorTexture>,
         depth_texture: Option<DepthTexture>,
     ) -> String {
-        let mut fragment_shader = lights_shader_source(
+        let mut fragment_shader = if self.ssao.is_some() {
+            "#define USE_SSAO\n".to_owned()
+        } else {
+            String::new()
+        };
+        fragment_shader.push_str(&lights_shader_source(
             lights,
             LightingModel::Cook(
                 NormalDistributionFunction::TrowbridgeReitzGGX,
                 GeometryFunction::SmithSchlickGGX,
             ),
-        );
+        ));
         fragment_shader.push_str(&color_texture.unwrap().fragment_shader_source());
         fragment_shader.push_str(&depth_texture.unwrap().fragment_shader_source());
         fragment_shader.push_str(ToneMapping::fragment_shader_source());
@@ -25,7 +32,11 @@ impl Effect for LightingPassEffect {
     }
 
     fn id(&self, color_texture: Option<ColorTexture>, depth_texture: Option<DepthTexture>) -> u16 {
-        0b1u16 << 14 | 0b1u16 << 12 | color_texture.unwrap().id() | depth_texture.unwrap().id()
+        0b1u16 << 14
+            | 0b1u16 << 12
+            | if self.ssao.is_some() { 0b1u16 << 10 } else { 0 }
+            | color_texture.unwrap().id()
+            | depth_texture.unwrap().id()
     }
 
     fn fragment_attributes(&self) -> FragmentAttributes {
@@ -43,8 +54,7 @@ impl Effect for LightingPassEffect {
         color_texture: Option<ColorTexture>,
         depth_texture: Option<DepthTexture>,
     ) {
-        camera.tone_mapping.use_uniforms(program);
-        camera.color_mapping.use_uniforms(program);
+        camera.use_tone_and_color_mapping_uniforms(program);
         color_texture.unwrap().use_uniforms(program);
         depth_texture.unwrap().use_uniforms(program);
         program.use_uniform_if_required("cameraPosition", camera.position());
@@ -55,6 +65,9 @@ impl Effect for LightingPassEffect {
             "viewProjectionInverse",
             (camera.projection() * camera.view()).invert().unwrap(),
         );
+        if let Some(ssao) = self.ssao {
+            program.use_texture("ssaoMap", ssao);
+        }
         program.use_uniform("debug_type", DebugType::None as i32);
     }
 
@@ -0,0 +1,36 @@
+
+uniform vec2 resolution;
+uniform float radius;
+
+in vec2 uvs;
+
+layout (location = 0) out vec4 outColor;
+
+const int SAMPLE_COUNT = 48;
+const float GOLDEN_ANGLE = 2.39996323;
+
+void main()
+{
+    vec4 color = sample_color(uvs);
+    float depth = sample_depth(uvs);
+    if (depth < 1.0) {
+        outColor = color;
+    } else {
+        // Samples on a spiral, only including other background pixels to avoid the objects bleeding into the background
+        vec4 sum = color;
+        float weight = 1.0;
+        for (int i = 1; i < SAMPLE_COUNT; i++) {
+            float r = radius * sqrt(float(i) / float(SAMPLE_COUNT));
+            float a = float(i) * GOLDEN_ANGLE;
+            vec2 uv = uvs + r * vec2(cos(a), sin(a)) / resolution;
+            if (sample_depth(uv) >= 1.0) {
+                sum += sample_color(uv);
+                weight += 1.0;
+            }
+        }
+        outColor = sum / weight;
+    }
+    outColor.rgb = tone_mapping(outColor.rgb);
+    outColor.rgb = color_mapping(outColor.rgb);
+    gl_FragDepth = depth;
+}
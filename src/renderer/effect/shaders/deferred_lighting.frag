@@ -4,6 +4,9 @@ uniform float zNear;
 uniform float zFar;
 uniform vec3 cameraPosition;
 uniform int debug_type;
+#ifdef USE_SSAO
+uniform sampler2D ssaoMap;
+#endif
 
 in vec2 uvs;
 
@@ -30,9 +33,12 @@ void main()
     if (z > 0.0001) {
         z = sqrt(z);
     }
-    vec3 normal = normalize(vec3(n2.x, n2.y, (int(floor(n.z * 255.0)) & 128) == 128 ? z: -z));
+    vec3 normal = normalize(vec3(n2.x, n2.y, (int(round(n.z * 255.0)) & 128) == 128 ? z: -z));
     float roughness_factor = n.w;
-    float occlusion = float(int(floor(n.z * 255.0)) & 127) / 127.0;
+    float occlusion = float(int(round(n.z * 255.0)) & 127) / 127.0;
+#ifdef USE_SSAO
+    occlusion *= texture(ssaoMap, uvs).r;
+#endif
     vec3 total_emissive = sample_layer(uvs, 2).rgb;
 
     if(debug_type == 0) // Position
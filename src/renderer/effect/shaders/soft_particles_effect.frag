@@ -0,0 +1,29 @@
+
+uniform mat4 viewProjectionInverse;
+uniform vec4 viewport;
+uniform vec3 eyePosition;
+uniform float fadeDistance;
+
+in vec3 pos;
+in vec2 uvs;
+in vec4 col;
+
+layout (location = 0) out vec4 outColor;
+
+void main()
+{
+    float d = length(2.0 * uvs - 1.0);
+    float alpha = col.a * (1.0 - smoothstep(0.5, 1.0, d));
+
+    vec2 screenUv = (gl_FragCoord.xy - viewport.xy) / viewport.zw;
+    float depth = sample_depth(screenUv);
+    if (depth < 0.999) {
+        vec3 scenePosition = world_pos_from_depth(viewProjectionInverse, depth, screenUv);
+        float difference = distance(scenePosition, eyePosition) - distance(pos, eyePosition);
+        alpha *= clamp(difference / fadeDistance, 0.0, 1.0);
+    }
+    if (alpha < 0.004) {
+        discard;
+    }
+    outColor = vec4(color_mapping(col.rgb), alpha);
+}
@@ -0,0 +1,65 @@
+
+uniform mat4 viewProjection;
+uniform mat4 viewProjectionInverse;
+uniform vec3 cameraPosition;
+uniform vec3 viewDirection;
+uniform vec3 kernel[MAX_SAMPLE_COUNT];
+uniform int sampleCount;
+uniform float radius;
+uniform float bias;
+uniform float intensity;
+
+in vec2 uvs;
+
+layout (location = 0) out vec4 outColor;
+
+float view_depth(vec3 position)
+{
+    return dot(position - cameraPosition, viewDirection);
+}
+
+void main()
+{
+    float depth = sample_depth(uvs);
+    if(depth > 0.99999)
+    {
+        outColor = vec4(1.0, 0.0, 0.0, 1.0);
+        return;
+    }
+    vec3 position = world_pos_from_depth(viewProjectionInverse, depth, uvs);
+
+    vec4 n = sample_layer(uvs, 1);
+    vec2 n2 = n.xy*2.0 - 1.0;
+    float z = 1.0 - n2.x * n2.x - n2.y * n2.y;
+    if (z > 0.0001) {
+        z = sqrt(z);
+    }
+    vec3 normal = normalize(vec3(n2.x, n2.y, (int(round(n.z * 255.0)) & 128) == 128 ? z: -z));
+
+    // Rotate the kernel around the normal by a per pixel noise to trade banding for noise which is removed by the blur
+    float angle = 6.2831853 * fract(52.9829189 * fract(dot(gl_FragCoord.xy, vec2(0.06711056, 0.00583715))));
+    vec3 random = vec3(cos(angle), sin(angle), 0.0);
+    if (abs(dot(random, normal)) > 0.99) {
+        random = vec3(0.0, 0.0, 1.0);
+    }
+    vec3 tangent = normalize(random - normal * dot(random, normal));
+    vec3 bitangent = cross(normal, tangent);
+    mat3 tbn = mat3(tangent, bitangent, normal);
+
+    float pixel_depth = view_depth(position);
+    float occlusion = 0.0;
+    for (int i = 0; i < sampleCount; i++) {
+        vec3 sample_position = position + radius * (tbn * kernel[i]);
+        vec4 clip_position = viewProjection * vec4(sample_position, 1.0);
+        vec2 sample_uvs = clip_position.xy / clip_position.w * 0.5 + 0.5;
+        if (any(lessThan(sample_uvs, vec2(0.0))) || any(greaterThan(sample_uvs, vec2(1.0)))) {
+            continue;
+        }
+        vec3 surface_position = world_pos_from_depth(viewProjectionInverse, sample_depth(sample_uvs), sample_uvs);
+        float surface_depth = view_depth(surface_position);
+        float range_check = smoothstep(0.0, 1.0, radius / max(abs(pixel_depth - surface_depth), 0.0001));
+        occlusion += (surface_depth <= view_depth(sample_position) - bias ? 1.0 : 0.0) * range_check;
+    }
+    occlusion = 1.0 - intensity * occlusion / float(max(sampleCount, 1));
+    outColor = vec4(clamp(occlusion, 0.0, 1.0), 0.0, 0.0, 1.0);
+}
@@ -0,0 +1,35 @@
+
+uniform vec2 sourceResolution;
+uniform float sharpness;
+
+in vec2 uvs;
+
+layout (location = 0) out vec4 outColor;
+
+// Samples the color with the tone and color mapping applied, so the sharpening works on the displayed colors
+vec4 sample_mapped_color(vec2 uv)
+{
+    vec4 color = sample_color(uv);
+    color.rgb = color_mapping(tone_mapping(color.rgb));
+    return color;
+}
+
+void main()
+{
+    vec4 color = sample_mapped_color(uvs);
+#ifdef SHARPEN
+    // Contrast adaptive sharpening using the four neighbouring texels in the low resolution source
+    vec2 texel = 1.0 / sourceResolution;
+    vec3 n = sample_mapped_color(uvs + vec2(0.0, texel.y)).rgb;
+    vec3 s = sample_mapped_color(uvs - vec2(0.0, texel.y)).rgb;
+    vec3 e = sample_mapped_color(uvs + vec2(texel.x, 0.0)).rgb;
+    vec3 w = sample_mapped_color(uvs - vec2(texel.x, 0.0)).rgb;
+    vec3 minColor = min(color.rgb, min(min(n, s), min(e, w)));
+    vec3 maxColor = max(color.rgb, max(max(n, s), max(e, w)));
+    // Sharpen less where the local contrast is already high to avoid ringing
+    vec3 amplitude = clamp(min(minColor, 1.0 - maxColor) / max(maxColor, vec3(0.0001)), 0.0, 1.0);
+    vec3 weight = -sqrt(amplitude) * mix(0.125, 0.2, sharpness);
+    color.rgb = clamp((color.rgb + (n + s + e + w) * weight) / (1.0 + 4.0 * weight), 0.0, 1.0);
+#endif
+    outColor = color;
+}
@@ -0,0 +1,93 @@
+use crate::renderer::*;
+
+///
+/// An effect for rendering particles, for example [GpuParticles], which fades out the particles where they are close to the scene behind them (soft particles)
+/// instead of cutting them off where they intersect the scene.
+/// The effect requires the depth texture of the scene and must be used with [RenderTarget::render_with_effect] after the scene is rendered to the target.
+///
+#[derive(Clone, Debug)]
+pub struct SoftParticlesEffect {
+    /// The distance in world space between a particle and the scene behind it where the particle is completely faded out at zero distance and is not faded at all at this distance.
+    pub fade_distance: f32,
+}
+
+impl Default for SoftParticlesEffect {
+    fn default() -> Self {
+        Self { fade_distance: 0.5 }
+    }
+}
+
+impl Effect for SoftParticlesEffect {
+    fn fragment_shader_source(
+        &self,
+        _lights: &[&dyn Light],
+        _color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) -> String {
+        format!(
+            "{}\n{}\n{}\n{}",
+            include_str!("../../core/shared.frag"),
+            depth_texture
+                .expect("Must supply a depth texture to apply a soft particles effect")
+                .fragment_shader_source(),
+            ColorMapping::fragment_shader_source(),
+            include_str!("shaders/soft_particles_effect.frag")
+        )
+    }
+
+    fn id(&self, _color_texture: Option<ColorTexture>, depth_texture: Option<DepthTexture>) -> u16 {
+        0b1u16 << 14
+            | 0b1u16 << 9
+            | depth_texture
+                .expect("Must supply a depth texture to apply a soft particles effect")
+                .id()
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            position: true,
+            uv: true,
+            color: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(
+        &self,
+        program: &Program,
+        camera: &Camera,
+        _lights: &[&dyn Light],
+        _color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) {
+        camera.color_mapping.use_uniforms(program);
+        depth_texture
+            .expect("Must supply a depth texture to apply a soft particles effect")
+            .use_uniforms(program);
+        let viewport = camera.viewport();
+        program.use_uniform(
+            "viewport",
+            vec4(
+                viewport.x as f32,
+                viewport.y as f32,
+                viewport.width as f32,
+                viewport.height as f32,
+            ),
+        );
+        program.use_uniform(
+            "viewProjectionInverse",
+            (camera.projection() * camera.view()).invert().unwrap(),
+        );
+        program.use_uniform("eyePosition", camera.position());
+        program.use_uniform("fadeDistance", self.fade_distance.max(0.0001));
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates {
+            write_mask: WriteMask::COLOR,
+            blend: Blend::TRANSPARENCY,
+            cull: Cull::None,
+            ..Default::default()
+        }
+    }
+}
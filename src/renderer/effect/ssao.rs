@@ -0,0 +1,229 @@
+use crate::renderer::*;
+
+const MAX_SAMPLE_COUNT: u32 = 64;
+
+///
+/// Screen space ambient occlusion which darkens the ambient light in creases, corners and other places which are partially hidden from the surroundings.
+/// The occlusion is estimated from the depth and normals of the geometry pass of the deferred pipeline, ie. objects with a [MaterialType::Deferred] material,
+/// by sampling points in a hemisphere around each pixel and counting how many of them are hidden behind the rendered geometry.
+///
+/// Enable it by setting [Camera::ssao] on the camera used for rendering. The occlusion only affects the ambient light, see [AmbientLight].
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ssao {
+    /// The radius in world units of the hemisphere around each pixel in which occluders are found.
+    pub radius: f32,
+    /// The minimum difference in depth, in world units, before a sample is considered occluded. Increase this to remove self-occlusion artifacts on flat surfaces.
+    pub bias: f32,
+    /// The number of samples per pixel, more samples gives less noise but is more expensive. The maximum is 64.
+    pub sample_count: u32,
+    /// The radius in pixels of the blur which is applied to remove the noise from the occlusion. A radius of 0 disables the blur.
+    pub blur_radius: u32,
+    /// The strength of the occlusion in the range `[0, 1]`.
+    pub intensity: f32,
+}
+
+impl Default for Ssao {
+    fn default() -> Self {
+        Self {
+            radius: 0.5,
+            bias: 0.025,
+            sample_count: 16,
+            blur_radius: 2,
+            intensity: 1.0,
+        }
+    }
+}
+
+impl Ssao {
+    ///
+    /// Returns a texture with the same size as the given geometry pass textures containing the ambient occlusion in the red channel,
+    /// where 1 means no occlusion and 0 means fully occluded.
+    /// The geometry pass textures are the textures written in the first stage of the deferred pipeline, see [DeferredPhysicalMaterial::lighting_pass].
+    ///
+    pub fn occlusion_texture(
+        &self,
+        context: &Context,
+        camera: &Camera,
+        geometry_pass_color_texture: ColorTexture,
+        geometry_pass_depth_texture: DepthTexture,
+    ) -> Texture2D {
+        let width = geometry_pass_depth_texture.width();
+        let height = geometry_pass_depth_texture.height();
+        let new_texture = || {
+            Texture2D::new_empty::<u8>(
+                context,
+                width,
+                height,
+                Interpolation::Linear,
+                Interpolation::Linear,
+                None,
+                Wrapping::ClampToEdge,
+                Wrapping::ClampToEdge,
+            )
+        };
+        let mut occlusion_camera = camera.clone();
+        occlusion_camera.set_viewport(Viewport::new_at_origo(width, height));
+
+        let mut occlusion_texture = new_texture();
+        occlusion_texture.as_color_target(None).apply_screen_effect(
+            &SsaoPass::Occlusion(self),
+            &occlusion_camera,
+            &[],
+            Some(geometry_pass_color_texture),
+            Some(geometry_pass_depth_texture),
+        );
+        if self.blur_radius == 0 {
+            return occlusion_texture;
+        }
+        let mut blurred_texture = new_texture();
+        blurred_texture.as_color_target(None).apply_screen_effect(
+            &SsaoPass::Blur(self.blur_radius),
+            &occlusion_camera,
+            &[],
+            Some(ColorTexture::Single(&occlusion_texture)),
+            None,
+        );
+        blurred_texture
+    }
+
+    ///
+    /// The sample points in a unit hemisphere around the z-axis. The points are distributed using a Hammersley sequence
+    /// and scaled so more points are placed close to the center, since occluders close to the surface matters the most.
+    ///
+    fn kernel(&self) -> Vec<Vec3> {
+        let count = self.sample_count.clamp(1, MAX_SAMPLE_COUNT);
+        (0..count)
+            .map(|i| {
+                let u = (i as f32 + 0.5) / count as f32;
+                let v = (i.reverse_bits() as f64 / 4294967296.0) as f32;
+                let phi = 2.0 * std::f32::consts::PI * v;
+                let cos_theta = 1.0 - u;
+                let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
+                let scale = 0.1 + 0.9 * u * u;
+                scale * vec3(sin_theta * phi.cos(), sin_theta * phi.sin(), cos_theta)
+            })
+            .collect()
+    }
+}
+
+enum SsaoPass<'a> {
+    Occlusion(&'a Ssao),
+    Blur(u32),
+}
+
+impl Effect for SsaoPass<'_> {
+    fn fragment_shader_source(
+        &self,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) -> String {
+        let color_texture =
+            color_texture.expect("Must supply a color texture to apply an ambient occlusion pass");
+        match self {
+            Self::Occlusion(_) => format!(
+                "{}\n{}\n#define MAX_SAMPLE_COUNT {}\n{}",
+                color_texture.fragment_shader_source(),
+                depth_texture
+                    .expect("Must supply a depth texture to apply an ambient occlusion pass")
+                    .fragment_shader_source(),
+                MAX_SAMPLE_COUNT,
+                include_str!("shaders/ssao_effect.frag")
+            ),
+            Self::Blur(_) => format!(
+                "{}
+                uniform vec2 texelSize;
+                uniform int blurRadius;
+
+                in vec2 uvs;
+                layout (location = 0) out vec4 outColor;
+
+                void main()
+                {{
+                    float occlusion = 0.0;
+                    for (int x = -blurRadius; x <= blurRadius; x++) {{
+                        for (int y = -blurRadius; y <= blurRadius; y++) {{
+                            occlusion += sample_color(uvs + vec2(float(x), float(y)) * texelSize).r;
+                        }}
+                    }}
+                    float size = float(2 * blurRadius + 1);
+                    outColor = vec4(occlusion / (size * size), 0.0, 0.0, 1.0);
+                }}",
+                color_texture.fragment_shader_source()
+            ),
+        }
+    }
+
+    fn id(&self, color_texture: Option<ColorTexture>, depth_texture: Option<DepthTexture>) -> u16 {
+        let color_texture =
+            color_texture.expect("Must supply a color texture to apply an ambient occlusion pass");
+        0b1u16 << 14
+            | 0b1u16 << 7
+            | 0b1u16 << 12
+            | match self {
+                Self::Occlusion(_) => depth_texture
+                    .expect("Must supply a depth texture to apply an ambient occlusion pass")
+                    .id(),
+                Self::Blur(_) => 0b1u16 << 8,
+            }
+            | color_texture.id()
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            uv: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(
+        &self,
+        program: &Program,
+        camera: &Camera,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) {
+        let color_texture =
+            color_texture.expect("Must supply a color texture to apply an ambient occlusion pass");
+        color_texture.use_uniforms(program);
+        match self {
+            Self::Occlusion(ssao) => {
+                depth_texture
+                    .expect("Must supply a depth texture to apply an ambient occlusion pass")
+                    .use_uniforms(program);
+                let view_projection = camera.projection() * camera.view();
+                program.use_uniform("viewProjection", view_projection);
+                program.use_uniform("viewProjectionInverse", view_projection.invert().unwrap());
+                program.use_uniform("cameraPosition", camera.position());
+                program.use_uniform("viewDirection", camera.view_direction());
+                let kernel = ssao.kernel();
+                program.use_uniform_array("kernel", &kernel);
+                program.use_uniform("sampleCount", kernel.len() as i32);
+                program.use_uniform("radius", ssao.radius.max(0.0001));
+                program.use_uniform("bias", ssao.bias);
+                program.use_uniform("intensity", ssao.intensity.clamp(0.0, 1.0));
+            }
+            Self::Blur(radius) => {
+                program.use_uniform(
+                    "texelSize",
+                    vec2(
+                        1.0 / color_texture.width() as f32,
+                        1.0 / color_texture.height() as f32,
+                    ),
+                );
+                program.use_uniform("blurRadius", *radius as i32);
+            }
+        }
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates {
+            write_mask: WriteMask::COLOR,
+            depth_test: DepthTest::Always,
+            cull: Cull::Back,
+            ..Default::default()
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use crate::renderer::*;
+
+///
+/// The filter used by the [UpsampleEffect] when scaling a low resolution image up to the resolution of the render target.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum UpsampleFilter {
+    /// Bilinear filtering, which is cheap but gives a slightly blurry result.
+    Bilinear,
+    /// Bilinear filtering followed by a contrast adaptive sharpening, similar to the sharpening in AMD FidelityFX Super Resolution, which restores some of the details lost when rendering at a lower resolution.
+    Sharpen {
+        /// The amount of sharpening in the range `[0, 1]`.
+        sharpness: f32,
+    },
+}
+
+impl Default for UpsampleFilter {
+    fn default() -> Self {
+        Self::Sharpen { sharpness: 0.5 }
+    }
+}
+
+///
+/// An effect that scales the color texture up to the size of the render target using the given [UpsampleFilter].
+/// The color texture should use linear interpolation. Like the [ScreenEffect], this effect applies the tone and color mapping of the [Camera],
+/// so a color texture which is already tone and color mapped should be scaled up using a camera where the mapping is disabled, see [Camera::disable_tone_and_color_mapping].
+///
+/// Typically used together with [RenderScale] to render the scene at a lower resolution than the window.
+///
+#[derive(Clone, Debug, Default)]
+pub struct UpsampleEffect {
+    /// The filter used when scaling up.
+    pub filter: UpsampleFilter,
+}
+
+impl Effect for UpsampleEffect {
+    fn fragment_shader_source(
+        &self,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        _depth_texture: Option<DepthTexture>,
+    ) -> String {
+        let color_texture =
+            color_texture.expect("Must supply a color texture to apply an upsample effect");
+        format!(
+            "{}{}{}{}\n{}",
+            match self.filter {
+                UpsampleFilter::Bilinear => "",
+                UpsampleFilter::Sharpen { .. } => "#define SHARPEN\n",
+            },
+            color_texture.fragment_shader_source(),
+            ToneMapping::fragment_shader_source(),
+            ColorMapping::fragment_shader_source(),
+            include_str!("shaders/upsample_effect.frag")
+        )
+    }
+
+    fn id(&self, color_texture: Option<ColorTexture>, _depth_texture: Option<DepthTexture>) -> u16 {
+        let color_texture =
+            color_texture.expect("Must supply a color texture to apply an upsample effect");
+        let mut id = 0b1u16 << 14 | 0b1u16 << 10 | color_texture.id();
+        if let UpsampleFilter::Sharpen { .. } = self.filter {
+            id |= 0b1u16 << 9;
+        }
+        id
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            uv: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(
+        &self,
+        program: &Program,
+        camera: &Camera,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        _depth_texture: Option<DepthTexture>,
+    ) {
+        let color_texture =
+            color_texture.expect("Must supply a color texture to apply an upsample effect");
+        camera.use_tone_and_color_mapping_uniforms(program);
+        color_texture.use_uniforms(program);
+        if let UpsampleFilter::Sharpen { sharpness } = self.filter {
+            program.use_uniform(
+                "sourceResolution",
+                vec2(color_texture.width() as f32, color_texture.height() as f32),
+            );
+            program.use_uniform("sharpness", sharpness.clamp(0.0, 1.0));
+        }
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates {
+            write_mask: WriteMask::COLOR,
+            depth_test: DepthTest::Always,
+            cull: Cull::Back,
+            ..Default::default()
+        }
+    }
+}
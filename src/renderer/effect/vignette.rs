@@ -0,0 +1,104 @@
+use crate::renderer::*;
+
+///
+/// An effect which darkens the image towards the edges, like the light falloff of a real camera lens, to draw the attention towards the center of the image.
+///
+#[derive(Clone, Debug)]
+pub struct VignetteEffect {
+    /// The strength of the darkening in the corners in the range `[0, 1]`.
+    pub intensity: f32,
+    /// The distance from the center, relative to the distance from the center to the corners, where the darkening begins.
+    pub radius: f32,
+    /// The width of the transition from no darkening to full darkening, relative to the distance from the center to the corners.
+    pub smoothness: f32,
+    /// The color which the edges fade towards, usually black.
+    pub color: Srgba,
+}
+
+impl Default for VignetteEffect {
+    fn default() -> Self {
+        Self {
+            intensity: 0.5,
+            radius: 0.6,
+            smoothness: 0.4,
+            color: Srgba::BLACK,
+        }
+    }
+}
+
+impl Effect for VignetteEffect {
+    fn fragment_shader_source(
+        &self,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        _depth_texture: Option<DepthTexture>,
+    ) -> String {
+        format!(
+            "{}
+            uniform vec4 parameters;
+            uniform vec3 vignetteColor;
+
+            in vec2 uvs;
+            layout (location = 0) out vec4 outColor;
+
+            void main()
+            {{
+                outColor = sample_color(uvs);
+                float distance_to_center = length(uvs - 0.5) / length(vec2(0.5));
+                float vignette = parameters.x * smoothstep(parameters.y, parameters.y + parameters.z, distance_to_center);
+                outColor.rgb = mix(outColor.rgb, vignetteColor, vignette);
+            }}",
+            color_texture
+                .expect("Must supply a color texture to apply a vignette effect")
+                .fragment_shader_source()
+        )
+    }
+
+    fn id(&self, color_texture: Option<ColorTexture>, _depth_texture: Option<DepthTexture>) -> u16 {
+        0b1u16 << 14
+            | 0b1u16 << 7
+            | 0b1u16 << 10
+            | color_texture
+                .expect("Must supply a color texture to apply a vignette effect")
+                .id()
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            uv: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(
+        &self,
+        program: &Program,
+        _camera: &Camera,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        _depth_texture: Option<DepthTexture>,
+    ) {
+        color_texture
+            .expect("Must supply a color texture to apply a vignette effect")
+            .use_uniforms(program);
+        program.use_uniform(
+            "parameters",
+            vec4(
+                self.intensity.clamp(0.0, 1.0),
+                self.radius,
+                self.smoothness.max(0.0001),
+                0.0,
+            ),
+        );
+        program.use_uniform("vignetteColor", self.color.to_linear_srgb().truncate());
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates {
+            write_mask: WriteMask::COLOR,
+            depth_test: DepthTest::Always,
+            cull: Cull::Back,
+            ..Default::default()
+        }
+    }
+}
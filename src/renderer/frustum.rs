@@ -0,0 +1,103 @@
+use crate::renderer::*;
+
+///
+/// The volume visible from a camera defined by six planes, which is used to skip rendering of objects outside the view, see [cull].
+/// Each plane is given as a vector `(a, b, c, d)` where `(a, b, c)` is the normalized normal pointing into the frustum and a point `p` is inside the plane if `dot((a, b, c), p) + d >= 0`.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Frustum {
+    /// The left, right, bottom, top, near and far planes.
+    pub planes: [Vec4; 6],
+}
+
+impl Frustum {
+    ///
+    /// Constructs the frustum of the given camera.
+    ///
+    pub fn new(camera: &Camera) -> Self {
+        Self::from_view_projection(camera.projection() * camera.view())
+    }
+
+    ///
+    /// Constructs the frustum from the given view-projection matrix by extracting the planes from the rows of the matrix.
+    ///
+    pub fn from_view_projection(view_projection: Mat4) -> Self {
+        let r0 = view_projection.row(0);
+        let r1 = view_projection.row(1);
+        let r2 = view_projection.row(2);
+        let r3 = view_projection.row(3);
+        let normalize = |plane: Vec4| {
+            let length = plane.truncate().magnitude();
+            if length > 0.0 {
+                plane / length
+            } else {
+                plane
+            }
+        };
+        Self {
+            planes: [
+                normalize(r3 + r0),
+                normalize(r3 - r0),
+                normalize(r3 + r1),
+                normalize(r3 - r1),
+                normalize(r3 + r2),
+                normalize(r3 - r2),
+            ],
+        }
+    }
+
+    ///
+    /// Returns whether or not the given point is inside the frustum.
+    ///
+    pub fn contains_point(&self, point: Vec3) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| plane.truncate().dot(point) + plane.w >= 0.0)
+    }
+
+    ///
+    /// Returns whether or not the given bounding box is partly or completely inside the frustum.
+    /// The test is conservative, so a box close to a corner of the frustum might be reported as intersecting even though it is outside.
+    /// An empty box never intersects and an infinite box always intersects.
+    ///
+    pub fn intersects_aabb(&self, aabb: &AxisAlignedBoundingBox) -> bool {
+        if aabb.is_empty() {
+            return false;
+        }
+        let min = aabb.min();
+        let max = aabb.max();
+        self.planes.iter().all(|plane| {
+            // The corner of the box furthest along the plane normal
+            let corner = vec3(
+                if plane.x >= 0.0 { max.x } else { min.x },
+                if plane.y >= 0.0 { max.y } else { min.y },
+                if plane.z >= 0.0 { max.z } else { min.z },
+            );
+            // An infinite box gives NaN which is treated as inside
+            let distance = plane.truncate().dot(corner) + plane.w;
+            distance >= 0.0 || distance.is_nan()
+        })
+    }
+
+    ///
+    /// Returns whether or not the given object should be rendered, ie. if it is not culled, see [Object::is_frustum_culled], or if its bounding box intersects the frustum.
+    ///
+    pub fn is_visible<T: Object + ?Sized>(&self, object: &T) -> bool {
+        !object.is_frustum_culled() || self.intersects_aabb(&object.aabb())
+    }
+}
+
+///
+/// Returns the objects which are visible from the given camera, ie. removes the objects with a bounding box outside the camera frustum, see [Frustum].
+/// Objects which are never culled, see [Object::is_frustum_culled], for example a [Skybox], are always returned.
+/// This is done automatically when rendering using for example [RenderTarget::render], but can be used to avoid work when rendering objects directly,
+/// for example using [Object::render] inside a [RenderTarget::write] closure.
+///
+pub fn cull<'a>(camera: &Camera, objects: &[&'a dyn Object]) -> Vec<&'a dyn Object> {
+    let frustum = Frustum::new(camera);
+    objects
+        .iter()
+        .copied()
+        .filter(|object| frustum.is_visible(*object))
+        .collect()
+}
@@ -57,6 +57,18 @@ mod mesh;
 #[doc(inline)]
 pub use mesh::*;
 
+mod mesh_builder;
+#[doc(inline)]
+pub use mesh_builder::*;
+
+mod skinned_mesh;
+#[doc(inline)]
+pub use skinned_mesh::*;
+
+mod morph_mesh;
+#[doc(inline)]
+pub use morph_mesh::*;
+
 mod instanced_mesh;
 #[doc(inline)]
 pub use instanced_mesh::*;
@@ -73,6 +85,14 @@ mod bounding_box;
 #[doc(inline)]
 pub use bounding_box::*;
 
+mod polylines;
+#[doc(inline)]
+pub use polylines::*;
+
+mod marching_cubes;
+#[doc(inline)]
+pub use marching_cubes::*;
+
 mod line;
 #[doc(inline)]
 pub use line::*;
@@ -177,7 +197,7 @@ impl<T: Geometry + ?Sized> Geometry for &mut T {
     }
 }
 
-impl<T: Geometry> Geometry for Box<T> {
+impl<T: Geometry + ?Sized> Geometry for Box<T> {
     impl_geometry_body!(as_ref);
 }
 
@@ -257,17 +277,35 @@ impl<T: Geometry> Geometry for std::sync::RwLock<T> {
     }
 }
 
-struct BaseMesh {
+pub(crate) struct BaseMesh {
     indices: Option<ElementBuffer>,
     positions: VertexBuffer,
     normals: Option<VertexBuffer>,
     tangents: Option<VertexBuffer>,
     uvs: Option<VertexBuffer>,
+    uvs1: Option<VertexBuffer>,
     colors: Option<VertexBuffer>,
 }
 
 impl BaseMesh {
+    // Returns a copy of this mesh data which does not share the buffers on the GPU
+    pub fn duplicate(&self) -> Self {
+        Self {
+            indices: self.indices.as_ref().map(|b| b.duplicate()),
+            positions: self.positions.duplicate(),
+            normals: self.normals.as_ref().map(|b| b.duplicate()),
+            tangents: self.tangents.as_ref().map(|b| b.duplicate()),
+            uvs: self.uvs.as_ref().map(|b| b.duplicate()),
+            uvs1: self.uvs1.as_ref().map(|b| b.duplicate()),
+            colors: self.colors.as_ref().map(|b| b.duplicate()),
+        }
+    }
+
     pub fn new(context: &Context, cpu_mesh: &CpuMesh) -> Self {
+        Self::new_with_uvs1(context, cpu_mesh, None)
+    }
+
+    pub fn new_with_uvs1(context: &Context, cpu_mesh: &CpuMesh, uvs1: Option<&[Vec2]>) -> Self {
         #[cfg(debug_assertions)]
         cpu_mesh.validate().expect("invalid cpu mesh");
 
@@ -296,6 +334,7 @@ impl BaseMesh {
                         .collect::<Vec<_>>(),
                 )
             }),
+            uvs1: uvs1.map(|uvs1| uvs1_buffer(context, cpu_mesh.positions.len(), uvs1)),
             colors: cpu_mesh.colors.as_ref().map(|data| {
                 VertexBuffer::new_with_data(
                     context,
@@ -305,6 +344,14 @@ impl BaseMesh {
         }
     }
 
+    pub fn set_uvs1(&mut self, context: &Context, uvs1: &[Vec2]) {
+        self.uvs1 = Some(uvs1_buffer(
+            context,
+            self.positions.vertex_count() as usize,
+            uvs1,
+        ));
+    }
+
     pub fn draw(
         &self,
         program: &Program,
@@ -387,6 +434,17 @@ impl BaseMesh {
             );
         }
 
+        if attributes.uv1 {
+            program.use_vertex_attribute(
+                "uv_coordinates1",
+                self.uvs1.as_ref().unwrap_or_else(|| {
+                    panic!(
+                        "the material requires a second set of uv coordinates but the geometry did not provide it"
+                    )
+                }),
+            );
+        }
+
         if attributes.color {
             if let Some(colors) = &self.colors {
                 program.use_vertex_attribute("color", colors);
@@ -394,3 +452,19 @@ impl BaseMesh {
         }
     }
 }
+
+// Creates a vertex buffer with the second set of uv coordinates, which are flipped in the same way as the uv coordinates of the CpuMesh
+fn uvs1_buffer(context: &Context, vertex_count: usize, uvs1: &[Vec2]) -> VertexBuffer {
+    debug_assert_eq!(
+        uvs1.len(),
+        vertex_count,
+        "the number of second uv coordinates must match the number of vertices"
+    );
+    VertexBuffer::new_with_data(
+        context,
+        &uvs1
+            .iter()
+            .map(|uv| vec2(uv.x, 1.0 - uv.y))
+            .collect::<Vec<_>>(),
+    )
+}
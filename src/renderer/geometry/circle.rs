@@ -83,3 +83,11 @@ impl Geometry for Circle {
         self.mesh.animate(time)
     }
 }
+
+impl HitTest2D for Circle {
+    fn hit_test(&self, point: PhysicalPoint) -> bool {
+        let center: Vec2 = self.center.into();
+        let point: Vec2 = point.into();
+        point.distance2(center) <= self.radius * self.radius
+    }
+}
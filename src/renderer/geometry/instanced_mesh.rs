@@ -27,10 +27,33 @@ impl InstancedMesh {
     /// The model is rendered in as many instances as there are attributes in [Instances] given as input.
     ///
     pub fn new(context: &Context, instances: &Instances, cpu_mesh: &CpuMesh) -> Self {
+        Self::new_internal(context, instances, cpu_mesh, None)
+    }
+
+    ///
+    /// Creates a new instanced 3D mesh from the given [CpuMesh] and a second set of uv coordinates, one for each vertex, for example used for sampling light maps or ambient occlusion maps
+    /// (see [UvChannel] and [PhysicalMaterial::occlusion_uv_channel]).
+    /// The model is rendered in as many instances as there are attributes in [Instances] given as input.
+    ///
+    pub fn new_with_uvs1(
+        context: &Context,
+        instances: &Instances,
+        cpu_mesh: &CpuMesh,
+        uvs1: &[Vec2],
+    ) -> Self {
+        Self::new_internal(context, instances, cpu_mesh, Some(uvs1))
+    }
+
+    fn new_internal(
+        context: &Context,
+        instances: &Instances,
+        cpu_mesh: &CpuMesh,
+        uvs1: Option<&[Vec2]>,
+    ) -> Self {
         let aabb = cpu_mesh.compute_aabb();
         let mut instanced_mesh = Self {
             context: context.clone(),
-            base_mesh: BaseMesh::new(context, cpu_mesh),
+            base_mesh: BaseMesh::new_with_uvs1(context, cpu_mesh, uvs1),
             instance_buffers: RwLock::new((Default::default(), vec3(0.0, 0.0, 0.0))),
             aabb,
             aabb_local: aabb,
@@ -68,6 +91,14 @@ impl InstancedMesh {
         self.animation = Some(Box::new(animation));
     }
 
+    ///
+    /// Sets a second set of uv coordinates, one for each vertex, for example used for sampling light maps or ambient occlusion maps
+    /// (see [UvChannel] and [PhysicalMaterial::occlusion_uv_channel]).
+    ///
+    pub fn set_uvs1(&mut self, uvs1: &[Vec2]) {
+        self.base_mesh.set_uvs1(&self.context, uvs1);
+    }
+
     /// Returns the number of instances that is rendered.
     pub fn instance_count(&self) -> u32 {
         self.instances.count()
@@ -85,6 +116,154 @@ impl InstancedMesh {
         self.update_instance_buffers(None);
     }
 
+    ///
+    /// Update the instances from the given [InstanceSource].
+    /// In contrast to [Self::set_instances], this reuses the memory of the current instances,
+    /// so it is possible to update the instances every frame without allocating intermediate lists.
+    /// The [Instances::custom_attributes] are kept, so the number of instances must be unchanged if there are any custom attributes.
+    ///
+    pub fn set_instances_from(&mut self, source: &(impl InstanceSource + ?Sized)) {
+        let use_texture_transformations = source.has_texture_transformations();
+        let use_colors = source.has_colors();
+        let use_emissive_intensities = source.has_emissive_intensities();
+        let mut transformations = std::mem::take(&mut self.instances.transformations);
+        let mut texture_transformations = self
+            .instances
+            .texture_transformations
+            .take()
+            .unwrap_or_default();
+        let mut colors = self.instances.colors.take().unwrap_or_default();
+        let mut emissive_intensities = self
+            .instances
+            .emissive_intensities
+            .take()
+            .unwrap_or_default();
+        transformations.clear();
+        texture_transformations.clear();
+        colors.clear();
+        emissive_intensities.clear();
+        source.for_each_instance(&mut |instance| {
+            transformations.push(instance.transformation);
+            if use_texture_transformations {
+                texture_transformations.push(instance.texture_transformation);
+            }
+            if use_colors {
+                colors.push(instance.color);
+            }
+            if use_emissive_intensities {
+                emissive_intensities.push(instance.emissive_intensity);
+            }
+        });
+        self.instances = Instances {
+            transformations,
+            texture_transformations: use_texture_transformations.then_some(texture_transformations),
+            colors: use_colors.then_some(colors),
+            emissive_intensities: use_emissive_intensities.then_some(emissive_intensities),
+            custom_attributes: std::mem::take(&mut self.instances.custom_attributes),
+        };
+        #[cfg(debug_assertions)]
+        self.instances.validate().expect("invalid instances");
+        self.update_aabb();
+
+        self.update_instance_buffers(None);
+    }
+
+    ///
+    /// Overwrites the attributes of the instances starting at the given index with the given instances, so for example
+    /// `update_instances(5, &instances)` where `instances` contains two instances updates the sixth and seventh instance.
+    /// In contrast to [Self::set_instances], the instances which are not updated do not have to be given and the existing buffers on the GPU are reused.
+    /// The attributes which are not specified in the given instances, for example if [Instances::colors] is `None`, are left unchanged.
+    ///
+    /// # Panics
+    /// If the updated instances are not within the current instances
+    /// or if the given instances specifies an attribute which is not specified for the current instances.
+    ///
+    pub fn update_instances(&mut self, start: usize, instances: &Instances) {
+        #[cfg(debug_assertions)]
+        instances.validate().expect("invalid instances");
+        let end = start + instances.count() as usize;
+        if end > self.instance_count() as usize {
+            panic!(
+                "cannot update instances {}..{} when there are {} instances",
+                start,
+                end,
+                self.instance_count()
+            );
+        }
+        fn update<T: Copy>(
+            target: Option<&mut Vec<T>>,
+            source: Option<&Vec<T>>,
+            start: usize,
+            end: usize,
+            name: &str,
+        ) {
+            if let Some(source) = source {
+                let target = target.unwrap_or_else(|| {
+                    panic!("cannot update the {} of instances without {}", name, name)
+                });
+                target[start..end].copy_from_slice(&source[..end - start]);
+            }
+        }
+        update(
+            Some(&mut self.instances.transformations),
+            Some(&instances.transformations),
+            start,
+            end,
+            "transformations",
+        );
+        update(
+            self.instances.texture_transformations.as_mut(),
+            instances.texture_transformations.as_ref(),
+            start,
+            end,
+            "texture transformations",
+        );
+        update(
+            self.instances.colors.as_mut(),
+            instances.colors.as_ref(),
+            start,
+            end,
+            "colors",
+        );
+        update(
+            self.instances.emissive_intensities.as_mut(),
+            instances.emissive_intensities.as_ref(),
+            start,
+            end,
+            "emissive intensities",
+        );
+        for (name, source) in instances.custom_attributes.iter() {
+            let target = self
+                .instances
+                .custom_attributes
+                .get_mut(name)
+                .unwrap_or_else(|| {
+                    panic!(
+                        "cannot update the custom attribute {} of instances without it",
+                        name
+                    )
+                });
+            match (target, source) {
+                (CustomInstanceAttribute::Float(t), CustomInstanceAttribute::Float(s)) => {
+                    update(Some(t), Some(s), start, end, name)
+                }
+                (CustomInstanceAttribute::Vec2(t), CustomInstanceAttribute::Vec2(s)) => {
+                    update(Some(t), Some(s), start, end, name)
+                }
+                (CustomInstanceAttribute::Vec3(t), CustomInstanceAttribute::Vec3(s)) => {
+                    update(Some(t), Some(s), start, end, name)
+                }
+                (CustomInstanceAttribute::Vec4(t), CustomInstanceAttribute::Vec4(s)) => {
+                    update(Some(t), Some(s), start, end, name)
+                }
+                _ => panic!("cannot change the type of the custom attribute {}", name),
+            }
+        }
+        self.update_aabb();
+
+        self.update_instance_buffers(None);
+    }
+
     fn update_aabb(&mut self) {
         let mut aabb = AxisAlignedBoundingBox::EMPTY;
         for transformation in self.instances.transformations.iter() {
@@ -122,24 +301,26 @@ impl InstancedMesh {
         };
 
         // Next, we can compute the instance buffers with that ordering.
-        let instance_buffers = &mut s.0;
-        instance_buffers.clear();
+        // The existing buffers are reused, and the buffers which are no longer needed are dropped.
+        let mut old_buffers = std::mem::take(&mut s.0);
+        let mut buffers = InstanceBuffers {
+            context: &self.context,
+            old: &mut old_buffers,
+            new: &mut s.0,
+        };
 
         if indices
             .iter()
             .map(|i| self.instances.transformations[*i])
             .all(|t| Mat3::from_cols(t.x.truncate(), t.y.truncate(), t.z.truncate()).is_identity())
         {
-            instance_buffers.insert(
-                "instance_translation".to_string(),
-                InstanceBuffer::new_with_data(
-                    &self.context,
-                    &indices
-                        .iter()
-                        .map(|i| self.instances.transformations[*i])
-                        .map(|t| t.w.truncate())
-                        .collect::<Vec<_>>(),
-                ),
+            buffers.insert(
+                "instance_translation",
+                &indices
+                    .iter()
+                    .map(|i| self.instances.transformations[*i])
+                    .map(|t| t.w.truncate())
+                    .collect::<Vec<_>>(),
             );
         } else {
             let mut row1 = Vec::new();
@@ -151,18 +332,9 @@ impl InstancedMesh {
                 row3.push(transformation.row(2));
             }
 
-            instance_buffers.insert(
-                "row1".to_string(),
-                InstanceBuffer::new_with_data(&self.context, &row1),
-            );
-            instance_buffers.insert(
-                "row2".to_string(),
-                InstanceBuffer::new_with_data(&self.context, &row2),
-            );
-            instance_buffers.insert(
-                "row3".to_string(),
-                InstanceBuffer::new_with_data(&self.context, &row3),
-            );
+            buffers.insert("row1", &row1);
+            buffers.insert("row2", &row2);
+            buffers.insert("row3", &row3);
         }
 
         if let Some(texture_transforms) = &self.instances.texture_transformations {
@@ -180,29 +352,71 @@ impl InstancedMesh {
                     texture_transform.z.y,
                 ));
             }
-            instance_buffers.insert(
-                "tex_transform_row1".to_string(),
-                InstanceBuffer::new_with_data(&self.context, &instance_tex_transform1),
-            );
-            instance_buffers.insert(
-                "tex_transform_row2".to_string(),
-                InstanceBuffer::new_with_data(&self.context, &instance_tex_transform2),
-            );
+            buffers.insert("tex_transform_row1", &instance_tex_transform1);
+            buffers.insert("tex_transform_row2", &instance_tex_transform2);
         }
+        buffers.insert(
+            "instance_index",
+            &indices.iter().map(|i| *i as f32).collect::<Vec<_>>(),
+        );
         if let Some(instance_colors) = &self.instances.colors {
             // Create the re-ordered color buffer by depth.
             let ordered_instance_colors = indices
                 .iter()
                 .map(|i| instance_colors[*i].to_linear_srgb())
                 .collect::<Vec<_>>();
-            instance_buffers.insert(
-                "instance_color".to_string(),
-                InstanceBuffer::new_with_data(&self.context, &ordered_instance_colors),
-            );
+            buffers.insert("instance_color", &ordered_instance_colors);
         }
+        if let Some(emissive_intensities) = &self.instances.emissive_intensities {
+            let ordered_emissive_intensities = indices
+                .iter()
+                .map(|i| emissive_intensities[*i])
+                .collect::<Vec<_>>();
+            buffers.insert("instance_emissive_intensity", &ordered_emissive_intensities);
+        }
+        for (name, attribute) in self.instances.custom_attributes.iter() {
+            let name = custom_attribute_buffer_name(name);
+            match attribute {
+                CustomInstanceAttribute::Float(data) => {
+                    buffers.insert(&name, &indices.iter().map(|i| data[*i]).collect::<Vec<_>>())
+                }
+                CustomInstanceAttribute::Vec2(data) => {
+                    buffers.insert(&name, &indices.iter().map(|i| data[*i]).collect::<Vec<_>>())
+                }
+                CustomInstanceAttribute::Vec3(data) => {
+                    buffers.insert(&name, &indices.iter().map(|i| data[*i]).collect::<Vec<_>>())
+                }
+                CustomInstanceAttribute::Vec4(data) => {
+                    buffers.insert(&name, &indices.iter().map(|i| data[*i]).collect::<Vec<_>>())
+                }
+            }
+        }
+    }
+}
+
+// Creates the instance buffers, reusing the previously created buffers when possible
+struct InstanceBuffers<'a> {
+    context: &'a Context,
+    old: &'a mut HashMap<String, InstanceBuffer>,
+    new: &'a mut HashMap<String, InstanceBuffer>,
+}
+
+impl InstanceBuffers<'_> {
+    fn insert<T: BufferDataType>(&mut self, name: &str, data: &[T]) {
+        let buffer = if let Some(mut buffer) = self.old.remove(name) {
+            buffer.fill(data);
+            buffer
+        } else {
+            InstanceBuffer::new_with_data(self.context, data)
+        };
+        self.new.insert(name.to_string(), buffer);
     }
 }
 
+fn custom_attribute_buffer_name(name: &str) -> String {
+    format!("custom_{}", name)
+}
+
 impl<'a> IntoIterator for &'a InstancedMesh {
     type Item = &'a dyn Geometry;
     type IntoIter = std::iter::Once<&'a dyn Geometry>;
@@ -237,6 +451,7 @@ impl Geometry for InstancedMesh {
             }
         }
         program.use_uniform("viewProjection", camera.projection() * camera.view());
+        program.use_uniform_if_required("pixelGrid", camera.pixel_grid());
         program.use_uniform("modelMatrix", self.current_transformation);
 
         for attribute_name in [
@@ -247,6 +462,8 @@ impl Geometry for InstancedMesh {
             "tex_transform_row1",
             "tex_transform_row2",
             "instance_color",
+            "instance_emissive_intensity",
+            "instance_index",
         ] {
             if program.requires_attribute(attribute_name) {
                 program.use_instance_attribute(
@@ -256,6 +473,12 @@ impl Geometry for InstancedMesh {
                 );
             }
         }
+        for name in self.instances.custom_attributes.keys() {
+            let buffer_name = custom_attribute_buffer_name(name);
+            if program.requires_attribute(&buffer_name) {
+                program.use_instance_attribute(&buffer_name, &instance_buffers[&buffer_name]);
+            }
+        }
         self.base_mesh.draw_instanced(
             program,
             render_states,
@@ -267,8 +490,25 @@ impl Geometry for InstancedMesh {
 
     fn vertex_shader_source(&self, required_attributes: FragmentAttributes) -> String {
         let instance_buffers = &self.instance_buffers.read().unwrap().0;
+        let mut custom_attributes = String::new();
+        if !self.instances.custom_attributes.is_empty() {
+            let mut assignments = String::new();
+            for (name, attribute) in self.instances.custom_attributes.iter() {
+                let glsl_type = attribute.glsl_type();
+                let buffer_name = custom_attribute_buffer_name(name);
+                custom_attributes.push_str(&format!(
+                    "in {} {};\nflat out {} {};\n",
+                    glsl_type, buffer_name, glsl_type, name
+                ));
+                assignments.push_str(&format!("{} = {}; ", name, buffer_name));
+            }
+            custom_attributes.push_str(&format!(
+                "#define CUSTOM_INSTANCE_ATTRIBUTES {}\n",
+                assignments
+            ));
+        }
         format!(
-            "{}{}{}{}{}{}{}{}{}",
+            "{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}",
             if required_attributes.normal {
                 "#define USE_NORMALS\n"
             } else {
@@ -284,6 +524,16 @@ impl Geometry for InstancedMesh {
             } else {
                 ""
             },
+            if required_attributes.uv1 {
+                "#define USE_UVS1\n"
+            } else {
+                ""
+            },
+            if required_attributes.displacement {
+                "#define USE_DISPLACEMENT\n"
+            } else {
+                ""
+            },
             if required_attributes.color && self.base_mesh.colors.is_some() {
                 "#define USE_VERTEX_COLORS\n"
             } else {
@@ -304,6 +554,24 @@ impl Geometry for InstancedMesh {
             } else {
                 ""
             },
+            if required_attributes.emissive_intensity {
+                "#define USE_EMISSIVE_INTENSITY\n"
+            } else {
+                ""
+            },
+            if required_attributes.emissive_intensity
+                && instance_buffers.contains_key("instance_emissive_intensity")
+            {
+                "#define USE_INSTANCE_EMISSIVE_INTENSITIES\n"
+            } else {
+                ""
+            },
+            if required_attributes.instance_id {
+                "#define USE_INSTANCE_ID\n"
+            } else {
+                ""
+            },
+            custom_attributes,
             include_str!("../../core/shared.frag"),
             include_str!("shaders/mesh.vert"),
         )
@@ -325,6 +593,12 @@ impl Geometry for InstancedMesh {
         if required_attributes.uv {
             id |= 0b1u16 << 2;
         }
+        if required_attributes.uv1 {
+            id |= 0b1u16 << 8;
+        }
+        if required_attributes.displacement {
+            id |= 0b1u16 << 9;
+        }
         if required_attributes.color && self.base_mesh.colors.is_some() {
             id |= 0b1u16 << 3;
         }
@@ -337,6 +611,20 @@ impl Geometry for InstancedMesh {
         if required_attributes.uv && instance_buffers.contains_key("tex_transform_row1") {
             id |= 0b1u16 << 6;
         }
+        if required_attributes.emissive_intensity {
+            id |= 0b1u16 << 10;
+        }
+        if required_attributes.emissive_intensity
+            && instance_buffers.contains_key("instance_emissive_intensity")
+        {
+            id |= 0b1u16 << 11;
+        }
+        if required_attributes.instance_id {
+            id |= 0b1u16 << 12;
+        }
+        if !self.instances.custom_attributes.is_empty() {
+            id |= 0b1u16 << 13;
+        }
         id
     }
 
@@ -396,6 +684,16 @@ pub struct Instances {
     pub texture_transformations: Option<Vec<Mat3>>,
     /// Colors multiplied onto the base color of each instance.
     pub colors: Option<Vec<Srgba>>,
+    /// Intensities multiplied onto the emissive color of the material of each instance.
+    /// Values above 1 makes the instance brighter than the emissive color, which is useful for making individual instances, for example lights in a LED wall, feed the bloom pass.
+    /// Only used by materials with an emissive color, for example [PhysicalMaterial].
+    pub emissive_intensities: Option<Vec<f32>>,
+    ///
+    /// Custom attributes of each instance, for example a texture index or a scalar parameter, which are available in the fragment shader of a custom [Material].
+    /// An attribute with the name `name` is available as `flat in float name;` in the fragment shader for a [CustomInstanceAttribute::Float] attribute and similarly for the vector types.
+    /// The names must be valid GLSL identifiers which are different from the names of the other inputs of the fragment shader, for example `pos`, `nor`, `uvs` and `col`.
+    ///
+    pub custom_attributes: HashMap<String, CustomInstanceAttribute>,
 }
 
 impl Instances {
@@ -423,6 +721,13 @@ impl Instances {
         )?;
         buffer_check(Some(self.transformations.len()), "transformations")?;
         buffer_check(self.colors.as_ref().map(|b| b.len()), "colors")?;
+        buffer_check(
+            self.emissive_intensities.as_ref().map(|b| b.len()),
+            "emissive intensities",
+        )?;
+        for (name, attribute) in self.custom_attributes.iter() {
+            buffer_check(Some(attribute.len()), name)?;
+        }
 
         Ok(())
     }
@@ -433,6 +738,152 @@ impl Instances {
     }
 }
 
+///
+/// The data of a custom attribute of each instance, see [Instances::custom_attributes].
+///
+#[derive(Clone, Debug)]
+pub enum CustomInstanceAttribute {
+    /// A `float` for each instance.
+    Float(Vec<f32>),
+    /// A `vec2` for each instance.
+    Vec2(Vec<Vec2>),
+    /// A `vec3` for each instance.
+    Vec3(Vec<Vec3>),
+    /// A `vec4` for each instance.
+    Vec4(Vec<Vec4>),
+}
+
+impl CustomInstanceAttribute {
+    /// Returns the number of instances with this attribute.
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Float(data) => data.len(),
+            Self::Vec2(data) => data.len(),
+            Self::Vec3(data) => data.len(),
+            Self::Vec4(data) => data.len(),
+        }
+    }
+
+    /// Returns whether or not there are no instances with this attribute.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn glsl_type(&self) -> &'static str {
+        match self {
+            Self::Float(_) => "float",
+            Self::Vec2(_) => "vec2",
+            Self::Vec3(_) => "vec3",
+            Self::Vec4(_) => "vec4",
+        }
+    }
+}
+
+///
+/// A single instance given by an [InstanceSource].
+///
+#[derive(Clone, Copy, Debug)]
+pub struct Instance {
+    /// The transformation applied to the instance.
+    pub transformation: Mat4,
+    /// The texture transform applied to the uv coordinates of the instance.
+    /// Only used if [InstanceSource::has_texture_transformations] returns true.
+    pub texture_transformation: Mat3,
+    /// Color multiplied onto the base color of the instance.
+    /// Only used if [InstanceSource::has_colors] returns true.
+    pub color: Srgba,
+    /// Intensity multiplied onto the emissive color of the instance.
+    /// Only used if [InstanceSource::has_emissive_intensities] returns true.
+    pub emissive_intensity: f32,
+}
+
+impl Default for Instance {
+    fn default() -> Self {
+        Self {
+            transformation: Mat4::identity(),
+            texture_transformation: Mat3::identity(),
+            color: Srgba::WHITE,
+            emissive_intensity: 1.0,
+        }
+    }
+}
+
+///
+/// Provides the attributes for the instances of an [InstancedMesh] or [InstancedModel] without the need for collecting them into an [Instances] struct first.
+/// Implement this for example for a query into an entity component system and use [InstancedMesh::set_instances_from] to update the instances each frame.
+///
+pub trait InstanceSource {
+    ///
+    /// Calls the callback once for each instance.
+    ///
+    fn for_each_instance(&self, callback: &mut dyn FnMut(Instance));
+
+    ///
+    /// Returns whether or not the instances have a texture transformation.
+    ///
+    fn has_texture_transformations(&self) -> bool {
+        false
+    }
+
+    ///
+    /// Returns whether or not the instances have a color.
+    ///
+    fn has_colors(&self) -> bool {
+        false
+    }
+
+    ///
+    /// Returns whether or not the instances have an emissive intensity.
+    ///
+    fn has_emissive_intensities(&self) -> bool {
+        false
+    }
+}
+
+impl InstanceSource for Instances {
+    fn for_each_instance(&self, callback: &mut dyn FnMut(Instance)) {
+        for i in 0..self.transformations.len() {
+            callback(Instance {
+                transformation: self.transformations[i],
+                texture_transformation: self
+                    .texture_transformations
+                    .as_ref()
+                    .map(|t| t[i])
+                    .unwrap_or(Mat3::identity()),
+                color: self.colors.as_ref().map(|c| c[i]).unwrap_or(Srgba::WHITE),
+                emissive_intensity: self
+                    .emissive_intensities
+                    .as_ref()
+                    .map(|e| e[i])
+                    .unwrap_or(1.0),
+            });
+        }
+    }
+
+    fn has_texture_transformations(&self) -> bool {
+        self.texture_transformations.is_some()
+    }
+
+    fn has_colors(&self) -> bool {
+        self.colors.is_some()
+    }
+
+    fn has_emissive_intensities(&self) -> bool {
+        self.emissive_intensities.is_some()
+    }
+}
+
+impl InstanceSource for [Mat4] {
+    fn for_each_instance(&self, callback: &mut dyn FnMut(Instance)) {
+        for transformation in self.iter() {
+            callback(Instance {
+                transformation: *transformation,
+                ..Default::default()
+            });
+        }
+    }
+}
+
 impl From<PointCloud> for Instances {
     fn from(points: PointCloud) -> Self {
         Self {
@@ -109,3 +109,19 @@ impl Geometry for Line {
         self.mesh.animate(time)
     }
 }
+
+impl HitTest2D for Line {
+    fn hit_test(&self, point: PhysicalPoint) -> bool {
+        let p0: Vec2 = self.pixel0.into();
+        let p1: Vec2 = self.pixel1.into();
+        let point: Vec2 = point.into();
+        let line = p1 - p0;
+        let length2 = line.magnitude2();
+        let t = if length2 > 0.0 {
+            ((point - p0).dot(line) / length2).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        point.distance2(p0 + t * line) <= 0.25 * self.thickness * self.thickness
+    }
+}
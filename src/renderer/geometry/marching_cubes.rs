@@ -0,0 +1,289 @@
+use crate::core::*;
+use crate::renderer::*;
+use std::sync::Arc;
+
+///
+/// A geometry which extracts the isosurface in the voxel data of a [Texture3D] using marching cubes on the GPU every time it is rendered,
+/// so the iso value can be changed interactively without uploading a new mesh, for example to explore medical scans or simulation volumes.
+/// The surface is placed where the red channel of the voxel data is equal to the iso value and encloses the voxels with a value above the iso value,
+/// so the surface normals point towards lower values.
+///
+/// The voxel data is scaled to fill a box of the given size with center in origo which is then transformed by the transformation.
+/// Every cell between eight neighbouring voxels is processed by the vertex shader, so the cost of rendering depends on the number of voxels and not on the size of the surface.
+/// The surface has no texture coordinates, so the tangents given to materials which require them are an arbitrary orthonormal basis around the normal.
+/// Also see [IsosurfaceMaterial] which renders the isosurface by raymarching the voxel data in the fragment shader.
+///
+pub struct MarchingCubes {
+    context: Context,
+    voxels: Arc<Texture3D>,
+    triangle_table: Texture2D,
+    iso_value: f32,
+    size: Vec3,
+    transformation: Mat4,
+}
+
+impl MarchingCubes {
+    ///
+    /// Creates a new geometry extracting the isosurface at the given iso value (in the range `[0..1]` for 8 bit voxel data) from the given voxel data
+    /// which is scaled to fill a box with the given size.
+    ///
+    pub fn new(context: &Context, voxels: Arc<Texture3D>, iso_value: f32, size: Vec3) -> Self {
+        let mut triangle_table = Texture2D::new_empty::<u8>(
+            context,
+            16,
+            256,
+            Interpolation::Nearest,
+            Interpolation::Nearest,
+            None,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+        triangle_table.fill(&triangle_table_data());
+        Self {
+            context: context.clone(),
+            voxels,
+            triangle_table,
+            iso_value,
+            size,
+            transformation: Mat4::identity(),
+        }
+    }
+
+    ///
+    /// Returns the iso value which defines the surface.
+    ///
+    pub fn iso_value(&self) -> f32 {
+        self.iso_value
+    }
+
+    ///
+    /// Sets the iso value which defines the surface. The surface is extracted again the next time it is rendered.
+    ///
+    pub fn set_iso_value(&mut self, iso_value: f32) {
+        self.iso_value = iso_value;
+    }
+
+    ///
+    /// Returns the voxel data.
+    ///
+    pub fn voxels(&self) -> &Arc<Texture3D> {
+        &self.voxels
+    }
+
+    ///
+    /// Sets the voxel data, for example the next time step of a simulation.
+    ///
+    pub fn set_voxels(&mut self, voxels: Arc<Texture3D>) {
+        self.voxels = voxels;
+    }
+
+    ///
+    /// Returns the size of the box which the voxel data is scaled to fill.
+    ///
+    pub fn size(&self) -> Vec3 {
+        self.size
+    }
+
+    ///
+    /// Sets the size of the box which the voxel data is scaled to fill.
+    ///
+    pub fn set_size(&mut self, size: Vec3) {
+        self.size = size;
+    }
+
+    ///
+    /// Returns the local to world transformation applied to the surface.
+    ///
+    pub fn transformation(&self) -> Mat4 {
+        self.transformation
+    }
+
+    ///
+    /// Set the local to world transformation applied to the surface.
+    ///
+    pub fn set_transformation(&mut self, transformation: Mat4) {
+        self.transformation = transformation;
+    }
+
+    fn cell_count(&self) -> [u32; 3] {
+        [
+            self.voxels.width().saturating_sub(1),
+            self.voxels.height().saturating_sub(1),
+            self.voxels.depth().saturating_sub(1),
+        ]
+    }
+}
+
+impl<'a> IntoIterator for &'a MarchingCubes {
+    type Item = &'a dyn Geometry;
+    type IntoIter = std::iter::Once<&'a dyn Geometry>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        std::iter::once(self)
+    }
+}
+
+impl Geometry for MarchingCubes {
+    fn draw(
+        &self,
+        camera: &Camera,
+        program: &Program,
+        render_states: RenderStates,
+        attributes: FragmentAttributes,
+    ) {
+        let [x, y, z] = self.cell_count();
+        if x * y * z == 0 {
+            return;
+        }
+        program.use_uniform("viewProjection", camera.projection() * camera.view());
+        program.use_uniform("modelMatrix", self.transformation);
+        program.use_uniform_if_required(
+            "normalMatrix",
+            self.transformation.invert().unwrap().transpose(),
+        );
+        program.use_uniform("cellCount", vec3(x as i32, y as i32, z as i32));
+        program.use_uniform("isoValue", self.iso_value);
+        program.use_uniform("size", self.size);
+        program.use_texture_3d("voxels", &self.voxels);
+        program.use_texture("triangleTable", &self.triangle_table);
+        program.draw_arrays_instanced(render_states, camera.viewport(), 15, x * y * z)
+    }
+
+    fn vertex_shader_source(&self, required_attributes: FragmentAttributes) -> String {
+        format!(
+            "{}{}",
+            if required_attributes.tangents {
+                "#define USE_TANGENTS\n"
+            } else {
+                ""
+            },
+            include_str!("shaders/marching_cubes.vert")
+        )
+    }
+
+    fn id(&self, required_attributes: FragmentAttributes) -> u16 {
+        if required_attributes.tangents {
+            0b1u16 << 15 | 0b1u16 << 14 | 0b110u16
+        } else {
+            0b1u16 << 15 | 0b110u16
+        }
+    }
+
+    fn render_with_material(
+        &self,
+        material: &dyn Material,
+        camera: &Camera,
+        lights: &[&dyn Light],
+    ) {
+        render_with_material(&self.context, camera, &self, material, lights);
+    }
+
+    fn render_with_effect(
+        &self,
+        material: &dyn Effect,
+        camera: &Camera,
+        lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) {
+        render_with_effect(
+            &self.context,
+            camera,
+            self,
+            material,
+            lights,
+            color_texture,
+            depth_texture,
+        )
+    }
+
+    fn aabb(&self) -> AxisAlignedBoundingBox {
+        let mut aabb =
+            AxisAlignedBoundingBox::new_with_positions(&[-0.5 * self.size, 0.5 * self.size]);
+        aabb.transform(&self.transformation);
+        aabb
+    }
+}
+
+const EDGES: [[usize; 2]; 12] = [
+    [0, 1],
+    [1, 2],
+    [2, 3],
+    [3, 0],
+    [4, 5],
+    [5, 6],
+    [6, 7],
+    [7, 4],
+    [0, 4],
+    [1, 5],
+    [2, 6],
+    [3, 7],
+];
+
+// The corners of each face of the cube ordered counter clockwise when seen from outside.
+const FACES: [[usize; 4]; 6] = [
+    [0, 3, 2, 1],
+    [4, 5, 6, 7],
+    [0, 1, 5, 4],
+    [2, 3, 7, 6],
+    [0, 4, 7, 3],
+    [1, 2, 6, 5],
+];
+
+///
+/// Generates the marching cubes triangle table with a row of 16 entries for each of the 256 cases, where a case has a bit set for each corner below the iso value.
+/// Each entry is the index of the cube edge plus one of a triangle vertex, or zero if the case has no more vertices.
+/// On each face of the cube, the crossed edges are connected so the corners below the iso value are separated, which makes the surfaces of neighbouring cells match.
+/// The segments on the faces are then chained into closed polygons which are triangulated as fans.
+///
+fn triangle_table_data() -> Vec<u8> {
+    let edge_index = |a: usize, b: usize| {
+        EDGES
+            .iter()
+            .position(|e| (e[0] == a && e[1] == b) || (e[0] == b && e[1] == a))
+            .unwrap()
+    };
+    let mut table = vec![0u8; 16 * 256];
+    for case in 0..256usize {
+        let below = |corner: usize| case & (1 << corner) != 0;
+
+        // The segment starting at each crossed edge
+        let mut next_edge = [None; 12];
+        for face in FACES.iter() {
+            for i in 0..4 {
+                let previous = face[(i + 3) % 4];
+                if below(face[i]) && !below(previous) {
+                    let mut j = i;
+                    while below(face[(j + 1) % 4]) {
+                        j += 1;
+                    }
+                    next_edge[edge_index(previous, face[i])] =
+                        Some(edge_index(face[j % 4], face[(j + 1) % 4]));
+                }
+            }
+        }
+
+        let mut visited = [false; 12];
+        let mut vertices = Vec::new();
+        for start in 0..12 {
+            if visited[start] || next_edge[start].is_none() {
+                continue;
+            }
+            let mut polygon = vec![start];
+            visited[start] = true;
+            let mut edge = next_edge[start].unwrap();
+            while edge != start {
+                polygon.push(edge);
+                visited[edge] = true;
+                edge = next_edge[edge].unwrap();
+            }
+            for k in 1..polygon.len() - 1 {
+                vertices.extend([polygon[0], polygon[k + 1], polygon[k]]);
+            }
+        }
+        for (i, edge) in vertices.into_iter().enumerate() {
+            table[case * 16 + i] = edge as u8 + 1;
+        }
+    }
+    table
+}
@@ -2,12 +2,13 @@ use crate::core::*;
 use crate::renderer::*;
 
 use super::BaseMesh;
+use std::sync::Arc;
 
 ///
 /// A triangle mesh [Geometry].
 ///
 pub struct Mesh {
-    base_mesh: BaseMesh,
+    base_mesh: Arc<BaseMesh>,
     context: Context,
     aabb: AxisAlignedBoundingBox,
     transformation: Mat4,
@@ -24,7 +25,7 @@ impl Mesh {
         let aabb = cpu_mesh.compute_aabb();
         Self {
             context: context.clone(),
-            base_mesh: BaseMesh::new(context, cpu_mesh),
+            base_mesh: Arc::new(BaseMesh::new(context, cpu_mesh)),
             aabb,
             transformation: Mat4::identity(),
             current_transformation: Mat4::identity(),
@@ -32,6 +33,58 @@ impl Mesh {
         }
     }
 
+    ///
+    /// Creates a new triangle mesh from the given [CpuMesh] and a second set of uv coordinates, one for each vertex, for example used for sampling light maps or ambient occlusion maps
+    /// (see [UvChannel] and [PhysicalMaterial::occlusion_uv_channel]).
+    /// All data is transfered to the GPU, so make sure to remove all unnecessary data from the [CpuMesh] before calling this method.
+    ///
+    pub fn new_with_uvs1(context: &Context, cpu_mesh: &CpuMesh, uvs1: &[Vec2]) -> Self {
+        let aabb = cpu_mesh.compute_aabb();
+        Self {
+            context: context.clone(),
+            base_mesh: Arc::new(BaseMesh::new_with_uvs1(context, cpu_mesh, Some(uvs1))),
+            aabb,
+            transformation: Mat4::identity(),
+            current_transformation: Mat4::identity(),
+            animation: None,
+        }
+    }
+
+    ///
+    /// Creates a new triangle mesh from the given [CpuMesh] and optional second set of uv coordinates which shares the vertex data on the GPU with other meshes created using this method from the same content,
+    /// see [Context::set_resource_sharing]. This is used when loading assets.
+    ///
+    pub(in crate::renderer) fn new_shared(
+        context: &Context,
+        cpu_mesh: &CpuMesh,
+        uvs1: Option<&[Vec2]>,
+    ) -> Self {
+        let aabb = cpu_mesh.compute_aabb();
+        Self {
+            context: context.clone(),
+            base_mesh: shared_mesh(context, cpu_mesh, uvs1),
+            aabb,
+            transformation: Mat4::identity(),
+            current_transformation: Mat4::identity(),
+            animation: None,
+        }
+    }
+
+    ///
+    /// Returns a new mesh which shares the vertex data on the GPU with this mesh, but has its own transformation, initially the transformation of this mesh, and no animation.
+    /// This makes it cheap to render the same mesh many times with different transformations, for example when instantiating a [Prefab].
+    ///
+    pub fn share(&self) -> Self {
+        Self {
+            context: self.context.clone(),
+            base_mesh: self.base_mesh.clone(),
+            aabb: self.aabb,
+            transformation: self.transformation,
+            current_transformation: self.transformation,
+            animation: None,
+        }
+    }
+
     pub(in crate::renderer) fn set_transformation_2d(&mut self, transformation: Mat3) {
         self.set_transformation(Mat4::new(
             transformation.x.x,
@@ -77,6 +130,32 @@ impl Mesh {
     pub fn set_animation(&mut self, animation: impl Fn(f32) -> Mat4 + Send + Sync + 'static) {
         self.animation = Some(Box::new(animation));
     }
+
+    ///
+    /// Sets a second set of uv coordinates, one for each vertex, for example used for sampling light maps or ambient occlusion maps
+    /// (see [UvChannel] and [PhysicalMaterial::occlusion_uv_channel]).
+    ///
+    /// If the vertex data is shared with other meshes, see [Mesh::share] and [Context::set_resource_sharing],
+    /// the vertex data is copied first, so the other meshes are not changed.
+    ///
+    pub fn set_uvs1(&mut self, uvs1: &[Vec2]) {
+        if Arc::strong_count(&self.base_mesh) == 1 {
+            unshare_mesh(&self.context, &self.base_mesh);
+        }
+        if Arc::get_mut(&mut self.base_mesh).is_none() {
+            self.base_mesh = Arc::new(self.base_mesh.duplicate());
+        }
+        Arc::get_mut(&mut self.base_mesh)
+            .unwrap()
+            .set_uvs1(&self.context, uvs1);
+    }
+}
+
+impl Upload for CpuMesh {
+    type Output = Mesh;
+    fn upload(self, context: &Context) -> Self::Output {
+        Mesh::new(context, &self)
+    }
 }
 
 impl<'a> IntoIterator for &'a Mesh {
@@ -118,6 +197,7 @@ impl Geometry for Mesh {
         }
 
         program.use_uniform("viewProjection", camera.projection() * camera.view());
+        program.use_uniform_if_required("pixelGrid", camera.pixel_grid());
         program.use_uniform("modelMatrix", self.current_transformation);
 
         self.base_mesh
@@ -126,7 +206,7 @@ impl Geometry for Mesh {
 
     fn vertex_shader_source(&self, required_attributes: FragmentAttributes) -> String {
         format!(
-            "{}{}{}{}{}{}",
+            "{}{}{}{}{}{}{}{}{}",
             if required_attributes.normal {
                 "#define USE_NORMALS\n"
             } else {
@@ -142,11 +222,26 @@ impl Geometry for Mesh {
             } else {
                 ""
             },
+            if required_attributes.uv1 {
+                "#define USE_UVS1\n"
+            } else {
+                ""
+            },
+            if required_attributes.displacement {
+                "#define USE_DISPLACEMENT\n"
+            } else {
+                ""
+            },
             if required_attributes.color && self.base_mesh.colors.is_some() {
                 "#define USE_VERTEX_COLORS\n"
             } else {
                 ""
             },
+            if required_attributes.emissive_intensity {
+                "#define USE_EMISSIVE_INTENSITY\n"
+            } else {
+                ""
+            },
             include_str!("../../core/shared.frag"),
             include_str!("shaders/mesh.vert"),
         )
@@ -163,9 +258,18 @@ impl Geometry for Mesh {
         if required_attributes.uv {
             id |= 0b1u16 << 2;
         }
+        if required_attributes.uv1 {
+            id |= 0b1u16 << 8;
+        }
+        if required_attributes.displacement {
+            id |= 0b1u16 << 9;
+        }
         if required_attributes.color && self.base_mesh.colors.is_some() {
             id |= 0b1u16 << 3;
         }
+        if required_attributes.emissive_intensity {
+            id |= 0b1u16 << 10;
+        }
         id
     }
 
@@ -0,0 +1,268 @@
+use crate::renderer::*;
+
+///
+/// A vertex with a position and optional attributes which can be added to a [MeshBuilder].
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MeshVertex {
+    /// The position of the vertex.
+    pub position: Vec3,
+    /// The normal of the vertex. If not specified, the normal is computed from the surrounding triangles when the mesh is built.
+    pub normal: Option<Vec3>,
+    /// The uv coordinates of the vertex.
+    pub uv: Option<Vec2>,
+    /// The color of the vertex.
+    pub color: Option<Srgba>,
+}
+
+impl MeshVertex {
+    ///
+    /// Creates a new vertex at the given position without any attributes.
+    ///
+    pub fn new(position: Vec3) -> Self {
+        Self {
+            position,
+            normal: None,
+            uv: None,
+            color: None,
+        }
+    }
+
+    ///
+    /// Returns the vertex with the given normal.
+    ///
+    pub fn with_normal(mut self, normal: Vec3) -> Self {
+        self.normal = Some(normal);
+        self
+    }
+
+    ///
+    /// Returns the vertex with the given uv coordinates.
+    ///
+    pub fn with_uv(mut self, uv: Vec2) -> Self {
+        self.uv = Some(uv);
+        self
+    }
+
+    ///
+    /// Returns the vertex with the given color.
+    ///
+    pub fn with_color(mut self, color: Srgba) -> Self {
+        self.color = Some(color);
+        self
+    }
+}
+
+impl From<Vec3> for MeshVertex {
+    fn from(position: Vec3) -> Self {
+        Self::new(position)
+    }
+}
+
+///
+/// Builds a [CpuMesh] from vertices and triangles, for example when generating procedural geometry,
+/// so there is no need to keep the attribute vectors of the mesh in sync or to compute the indices by hand.
+///
+#[derive(Clone, Debug, Default)]
+pub struct MeshBuilder {
+    positions: Vec<Vec3>,
+    normals: Vec<Option<Vec3>>,
+    uvs: Vec<Option<Vec2>>,
+    colors: Vec<Option<Srgba>>,
+    indices: Vec<u32>,
+}
+
+impl MeshBuilder {
+    ///
+    /// Creates a new empty builder.
+    ///
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///
+    /// Returns the number of vertices added so far.
+    ///
+    pub fn vertex_count(&self) -> u32 {
+        self.positions.len() as u32
+    }
+
+    ///
+    /// Returns the number of triangles added so far.
+    ///
+    pub fn triangle_count(&self) -> u32 {
+        self.indices.len() as u32 / 3
+    }
+
+    ///
+    /// Adds the given vertex and returns the index of the vertex, which is used when adding triangles using [MeshBuilder::push_triangle] or [MeshBuilder::push_quad].
+    ///
+    pub fn push_vertex(&mut self, vertex: impl Into<MeshVertex>) -> u32 {
+        let vertex = vertex.into();
+        self.positions.push(vertex.position);
+        self.normals.push(vertex.normal);
+        self.uvs.push(vertex.uv);
+        self.colors.push(vertex.color);
+        self.positions.len() as u32 - 1
+    }
+
+    ///
+    /// Adds a triangle between the vertices with the given indices. The vertices should be in counter clockwise order when seen from the front.
+    ///
+    /// # Panics
+    /// If one of the indices does not refer to a vertex added using [MeshBuilder::push_vertex].
+    ///
+    pub fn push_triangle(&mut self, a: u32, b: u32, c: u32) -> &mut Self {
+        let vertex_count = self.vertex_count();
+        assert!(
+            a < vertex_count && b < vertex_count && c < vertex_count,
+            "the triangle ({}, {}, {}) refers to a vertex which does not exist, the number of vertices is {}",
+            a,
+            b,
+            c,
+            vertex_count
+        );
+        self.indices.extend_from_slice(&[a, b, c]);
+        self
+    }
+
+    ///
+    /// Adds a quad between the vertices with the given indices as the two triangles `(a, b, c)` and `(a, c, d)`.
+    /// The vertices should be in counter clockwise order when seen from the front.
+    ///
+    /// # Panics
+    /// If one of the indices does not refer to a vertex added using [MeshBuilder::push_vertex].
+    ///
+    pub fn push_quad(&mut self, a: u32, b: u32, c: u32, d: u32) -> &mut Self {
+        self.push_triangle(a, b, c).push_triangle(a, c, d)
+    }
+
+    ///
+    /// Adds the vertices and triangles of the other builder, transformed by the given transformation.
+    /// The normals are transformed by the inverse transpose of the transformation
+    /// and the order of the vertices in each triangle is reversed if the transformation mirrors the geometry, so the triangles still face outwards.
+    ///
+    pub fn append(&mut self, other: &MeshBuilder, transformation: Mat4) -> &mut Self {
+        let normal_transformation = transformation
+            .invert()
+            .map(|m| m.transpose())
+            .unwrap_or(transformation);
+        let offset = self.vertex_count();
+        self.positions.extend(
+            other
+                .positions
+                .iter()
+                .map(|p| (transformation * p.extend(1.0)).truncate()),
+        );
+        self.normals.extend(other.normals.iter().map(|n| {
+            n.map(|n| {
+                (normal_transformation * n.extend(0.0))
+                    .truncate()
+                    .normalize()
+            })
+        }));
+        self.uvs.extend_from_slice(&other.uvs);
+        self.colors.extend_from_slice(&other.colors);
+        let mirrored = transformation.determinant() < 0.0;
+        for triangle in other.indices.chunks_exact(3) {
+            if mirrored {
+                self.indices.extend_from_slice(&[
+                    offset + triangle[0],
+                    offset + triangle[2],
+                    offset + triangle[1],
+                ]);
+            } else {
+                self.indices
+                    .extend(triangle.iter().map(|index| offset + index));
+            }
+        }
+        self
+    }
+
+    ///
+    /// Adds the vertices and triangles of the given mesh, transformed by the given transformation, see [MeshBuilder::append].
+    /// The tangents of the mesh are not added, use [CpuMesh::compute_tangents] on the built mesh if needed.
+    ///
+    pub fn append_mesh(&mut self, cpu_mesh: &CpuMesh, transformation: Mat4) -> &mut Self {
+        self.append(&MeshBuilder::from(cpu_mesh), transformation)
+    }
+
+    ///
+    /// Builds a [CpuMesh] from the added vertices and triangles.
+    /// The normals which are not specified are computed from the surrounding triangles, while the specified normals are kept.
+    /// The uv coordinates and colors are only added to the mesh if they are specified for at least one vertex,
+    /// in which case the remaining vertices get zero uv coordinates and a white color respectively.
+    ///
+    pub fn build(&self) -> CpuMesh {
+        let mut cpu_mesh = CpuMesh {
+            positions: Positions::F32(self.positions.clone()),
+            indices: Indices::U32(self.indices.clone()),
+            uvs: if self.uvs.iter().any(|uv| uv.is_some()) {
+                Some(
+                    self.uvs
+                        .iter()
+                        .map(|uv| uv.unwrap_or(vec2(0.0, 0.0)))
+                        .collect(),
+                )
+            } else {
+                None
+            },
+            colors: if self.colors.iter().any(|color| color.is_some()) {
+                Some(
+                    self.colors
+                        .iter()
+                        .map(|color| color.unwrap_or(Srgba::WHITE))
+                        .collect(),
+                )
+            } else {
+                None
+            },
+            ..Default::default()
+        };
+        if self.normals.iter().all(|normal| normal.is_some()) {
+            cpu_mesh.normals = Some(self.normals.iter().flatten().copied().collect());
+        } else {
+            cpu_mesh.compute_normals();
+            if let Some(normals) = &mut cpu_mesh.normals {
+                normals
+                    .iter_mut()
+                    .zip(self.normals.iter())
+                    .for_each(|(normal, specified)| {
+                        if let Some(specified) = specified {
+                            *normal = *specified;
+                        }
+                    });
+            }
+        }
+        cpu_mesh
+    }
+}
+
+impl From<&CpuMesh> for MeshBuilder {
+    fn from(cpu_mesh: &CpuMesh) -> Self {
+        let positions = cpu_mesh.positions.to_f32();
+        let vertex_count = positions.len();
+        let indices = match &cpu_mesh.indices {
+            Indices::U8(indices) => indices.iter().map(|i| *i as u32).collect(),
+            Indices::U16(indices) => indices.iter().map(|i| *i as u32).collect(),
+            Indices::U32(indices) => indices.clone(),
+            Indices::None => (0..vertex_count as u32).collect(),
+        };
+        Self {
+            positions,
+            normals: match &cpu_mesh.normals {
+                Some(normals) => normals.iter().map(|n| Some(*n)).collect(),
+                None => vec![None; vertex_count],
+            },
+            uvs: match &cpu_mesh.uvs {
+                Some(uvs) => uvs.iter().map(|uv| Some(*uv)).collect(),
+                None => vec![None; vertex_count],
+            },
+            colors: match &cpu_mesh.colors {
+                Some(colors) => colors.iter().map(|c| Some(*c)).collect(),
+                None => vec![None; vertex_count],
+            },
+            indices,
+        }
+    }
+}
@@ -0,0 +1,232 @@
+use crate::core::*;
+use crate::renderer::*;
+
+use super::BaseMesh;
+
+///
+/// A triangle mesh [Geometry] which is a blend between two meshes with the same topology, ie. the same number of vertices, the same indices and the same uv coordinates,
+/// where only the positions and normals differ. The blend is computed on the GPU, so changing the blend weight is cheap.
+/// This is for example useful for before/after comparisons of a scan or a simulation or for animating between two shapes.
+///
+/// The uv coordinates, tangents and colors of the first mesh are used for the blended mesh.
+///
+pub struct MorphMesh {
+    base_mesh: BaseMesh,
+    context: Context,
+    aabb: AxisAlignedBoundingBox,
+    transformation: Mat4,
+    morph_positions: VertexBuffer,
+    morph_normals: Option<VertexBuffer>,
+    weight: f32,
+}
+
+impl MorphMesh {
+    ///
+    /// Creates a new morph mesh which blends from the first mesh to the second mesh. The blend weight is initially 0, ie. the mesh looks like the first mesh.
+    ///
+    /// # Panics
+    /// If the two meshes do not have the same number of vertices.
+    ///
+    pub fn new(context: &Context, from: &CpuMesh, to: &CpuMesh) -> Self {
+        assert_eq!(
+            from.vertex_count(),
+            to.vertex_count(),
+            "the meshes of a morph mesh must have the same number of vertices"
+        );
+        let morph_normals = if from.normals.is_some() {
+            Some(match &to.normals {
+                Some(normals) => normals.clone(),
+                None => {
+                    let mut to = to.clone();
+                    to.compute_normals();
+                    to.normals.unwrap_or_default()
+                }
+            })
+        } else {
+            None
+        };
+        let mut aabb = from.compute_aabb();
+        aabb.expand_with_aabb(&to.compute_aabb());
+        Self {
+            base_mesh: BaseMesh::new(context, from),
+            context: context.clone(),
+            aabb,
+            transformation: Mat4::identity(),
+            morph_positions: VertexBuffer::new_with_data(context, &to.positions.to_f32()),
+            morph_normals: morph_normals
+                .map(|normals| VertexBuffer::new_with_data(context, &normals)),
+            weight: 0.0,
+        }
+    }
+
+    ///
+    /// Returns the blend weight, where 0 is the first mesh and 1 is the second mesh.
+    ///
+    pub fn weight(&self) -> f32 {
+        self.weight
+    }
+
+    ///
+    /// Sets the blend weight, where 0 is the first mesh and 1 is the second mesh. The weight is clamped to the range `[0, 1]`.
+    ///
+    pub fn set_weight(&mut self, weight: f32) {
+        self.weight = weight.clamp(0.0, 1.0);
+    }
+
+    ///
+    /// Returns the local to world transformation applied to this mesh.
+    ///
+    pub fn transformation(&self) -> Mat4 {
+        self.transformation
+    }
+
+    ///
+    /// Set the local to world transformation applied to this mesh.
+    ///
+    pub fn set_transformation(&mut self, transformation: Mat4) {
+        self.transformation = transformation;
+    }
+}
+
+impl<'a> IntoIterator for &'a MorphMesh {
+    type Item = &'a dyn Geometry;
+    type IntoIter = std::iter::Once<&'a dyn Geometry>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        std::iter::once(self)
+    }
+}
+
+impl Geometry for MorphMesh {
+    fn aabb(&self) -> AxisAlignedBoundingBox {
+        let mut aabb = self.aabb;
+        aabb.transform(&self.transformation);
+        aabb
+    }
+
+    fn draw(
+        &self,
+        camera: &Camera,
+        program: &Program,
+        render_states: RenderStates,
+        attributes: FragmentAttributes,
+    ) {
+        if attributes.normal {
+            if let Some(inverse) = self.transformation.invert() {
+                program.use_uniform("normalMatrix", inverse.transpose());
+            } else {
+                // determinant is float zero
+                return;
+            }
+            if let Some(morph_normals) = &self.morph_normals {
+                program.use_vertex_attribute("morph_normal", morph_normals);
+            }
+        }
+
+        program.use_uniform("viewProjection", camera.projection() * camera.view());
+        program.use_uniform_if_required("pixelGrid", camera.pixel_grid());
+        program.use_uniform("modelMatrix", self.transformation);
+        program.use_uniform("morphWeight", self.weight);
+        program.use_vertex_attribute("morph_position", &self.morph_positions);
+
+        self.base_mesh
+            .draw(program, render_states, camera, attributes);
+    }
+
+    fn vertex_shader_source(&self, required_attributes: FragmentAttributes) -> String {
+        format!(
+            "#define USE_MORPH\n{}{}{}{}{}{}{}{}{}",
+            if required_attributes.normal {
+                "#define USE_NORMALS\n"
+            } else {
+                ""
+            },
+            if required_attributes.tangents {
+                "#define USE_TANGENTS\n"
+            } else {
+                ""
+            },
+            if required_attributes.uv {
+                "#define USE_UVS\n"
+            } else {
+                ""
+            },
+            if required_attributes.uv1 {
+                "#define USE_UVS1\n"
+            } else {
+                ""
+            },
+            if required_attributes.displacement {
+                "#define USE_DISPLACEMENT\n"
+            } else {
+                ""
+            },
+            if required_attributes.color && self.base_mesh.colors.is_some() {
+                "#define USE_VERTEX_COLORS\n"
+            } else {
+                ""
+            },
+            if required_attributes.emissive_intensity {
+                "#define USE_EMISSIVE_INTENSITY\n"
+            } else {
+                ""
+            },
+            include_str!("../../core/shared.frag"),
+            include_str!("shaders/mesh.vert"),
+        )
+    }
+
+    fn id(&self, required_attributes: FragmentAttributes) -> u16 {
+        let mut id = 0b1u16 << 15 | 0b1u16 << 4 | 0b1u16 << 12;
+        if required_attributes.normal {
+            id |= 0b1u16;
+        }
+        if required_attributes.tangents {
+            id |= 0b1u16 << 1;
+        }
+        if required_attributes.uv {
+            id |= 0b1u16 << 2;
+        }
+        if required_attributes.uv1 {
+            id |= 0b1u16 << 8;
+        }
+        if required_attributes.displacement {
+            id |= 0b1u16 << 9;
+        }
+        if required_attributes.color && self.base_mesh.colors.is_some() {
+            id |= 0b1u16 << 3;
+        }
+        if required_attributes.emissive_intensity {
+            id |= 0b1u16 << 10;
+        }
+        id
+    }
+
+    fn render_with_material(
+        &self,
+        material: &dyn Material,
+        camera: &Camera,
+        lights: &[&dyn Light],
+    ) {
+        render_with_material(&self.context, camera, &self, material, lights);
+    }
+
+    fn render_with_effect(
+        &self,
+        material: &dyn Effect,
+        camera: &Camera,
+        lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) {
+        render_with_effect(
+            &self.context,
+            camera,
+            self,
+            material,
+            lights,
+            color_texture,
+            depth_texture,
+        )
+    }
+}
@@ -198,6 +198,9 @@ impl Geometry for ParticleSystem {
         if required_attributes.uv {
             id |= 0b1u16 << 2;
         }
+        if required_attributes.uv1 {
+            id |= 0b1u16 << 8;
+        }
         if required_attributes.color && self.base_mesh.colors.is_some() {
             id |= 0b1u16 << 3;
         }
@@ -207,12 +210,15 @@ impl Geometry for ParticleSystem {
         if required_attributes.uv && self.instance_buffers.contains_key("tex_transform_row1") {
             id |= 0b1u16 << 5;
         }
+        if required_attributes.emissive_intensity {
+            id |= 0b1u16 << 10;
+        }
         id
     }
 
     fn vertex_shader_source(&self, required_attributes: FragmentAttributes) -> String {
         format!(
-            "#define PARTICLES\n{}{}{}{}{}{}{}{}",
+            "#define PARTICLES\n{}{}{}{}{}{}{}{}{}{}",
             if required_attributes.normal {
                 "#define USE_NORMALS\n"
             } else {
@@ -228,6 +234,11 @@ impl Geometry for ParticleSystem {
             } else {
                 ""
             },
+            if required_attributes.uv1 {
+                "#define USE_UVS1\n"
+            } else {
+                ""
+            },
             if required_attributes.color && self.base_mesh.colors.is_some() {
                 "#define USE_VERTEX_COLORS\n"
             } else {
@@ -243,6 +254,11 @@ impl Geometry for ParticleSystem {
             } else {
                 ""
             },
+            if required_attributes.emissive_intensity {
+                "#define USE_EMISSIVE_INTENSITY\n"
+            } else {
+                ""
+            },
             include_str!("../../core/shared.frag"),
             include_str!("shaders/mesh.vert"),
         )
@@ -264,6 +280,7 @@ impl Geometry for ParticleSystem {
             }
         }
         program.use_uniform("viewProjection", camera.projection() * camera.view());
+        program.use_uniform_if_required("pixelGrid", camera.pixel_grid());
         program.use_uniform("modelMatrix", self.transformation);
         program.use_uniform("acceleration", self.acceleration);
         program.use_uniform("time", self.time);
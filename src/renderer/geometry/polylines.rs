@@ -0,0 +1,129 @@
+use crate::renderer::*;
+
+///
+/// A geometry consisting of a list of 3D polylines with a thickness in world units, for example used for visualising streamlines, see [trace_streamlines], or paths.
+/// Each line segment is rendered as an instanced cylinder, so the polylines can optionally be colored per point using the instance colors,
+/// in which case the material should use the instance colors, for example [ColorMaterial] or [PhysicalMaterial].
+///
+pub struct Polylines {
+    mesh: InstancedMesh,
+}
+
+impl Polylines {
+    ///
+    /// Creates polylines through the given lists of points with the given thickness.
+    ///
+    pub fn new(context: &Context, lines: &[Vec<Vec3>], thickness: f32) -> Self {
+        Self {
+            mesh: InstancedMesh::new(
+                context,
+                &Self::instances(lines, None, thickness),
+                &CpuMesh::cylinder(8),
+            ),
+        }
+    }
+
+    ///
+    /// Creates polylines through the given lists of points with the given thickness and a color for each point.
+    /// Each line segment is given the color of the first of its two points.
+    ///
+    /// # Panics
+    /// If the number of colors for a line is smaller than the number of points.
+    ///
+    pub fn new_with_colors(
+        context: &Context,
+        lines: &[Vec<Vec3>],
+        colors: &[Vec<Srgba>],
+        thickness: f32,
+    ) -> Self {
+        Self {
+            mesh: InstancedMesh::new(
+                context,
+                &Self::instances(lines, Some(colors), thickness),
+                &CpuMesh::cylinder(8),
+            ),
+        }
+    }
+
+    ///
+    /// Sets the lists of points, the thickness and optionally a color for each point, for example when the lines are recomputed.
+    ///
+    /// # Panics
+    /// If the number of colors for a line is smaller than the number of points.
+    ///
+    pub fn set_lines(
+        &mut self,
+        lines: &[Vec<Vec3>],
+        colors: Option<&[Vec<Srgba>]>,
+        thickness: f32,
+    ) {
+        self.mesh
+            .set_instances(&Self::instances(lines, colors, thickness));
+    }
+
+    fn instances(lines: &[Vec<Vec3>], colors: Option<&[Vec<Srgba>]>, thickness: f32) -> Instances {
+        let mut transformations = Vec::new();
+        let mut segment_colors = Vec::new();
+        for (i, line) in lines.iter().enumerate() {
+            for (j, points) in line.windows(2).enumerate() {
+                let segment = points[1] - points[0];
+                let length = segment.magnitude();
+                if length < f32::EPSILON {
+                    continue;
+                }
+                let x = segment / length;
+                let up = if x.y.abs() < 0.99 {
+                    vec3(0.0, 1.0, 0.0)
+                } else {
+                    vec3(0.0, 0.0, 1.0)
+                };
+                let z = x.cross(up).normalize();
+                let y = z.cross(x);
+                let radius = 0.5 * thickness;
+                transformations.push(
+                    Mat4::from_translation(points[0])
+                        * Mat4::from(Mat3::from_cols(length * x, radius * y, radius * z)),
+                );
+                if let Some(colors) = colors {
+                    segment_colors.push(colors[i][j]);
+                }
+            }
+        }
+        Instances {
+            transformations,
+            colors: colors.map(|_| segment_colors),
+            ..Default::default()
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a Polylines {
+    type Item = &'a dyn Geometry;
+    type IntoIter = std::iter::Once<&'a dyn Geometry>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        std::iter::once(self)
+    }
+}
+
+use std::ops::Deref;
+impl Deref for Polylines {
+    type Target = InstancedMesh;
+    fn deref(&self) -> &Self::Target {
+        &self.mesh
+    }
+}
+
+impl std::ops::DerefMut for Polylines {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.mesh
+    }
+}
+
+impl Geometry for Polylines {
+    impl_geometry_body!(deref);
+
+    fn animate(&mut self, time: f32) {
+        self.mesh.animate(time)
+    }
+}
@@ -108,3 +108,14 @@ impl Geometry for Rectangle {
         self.mesh.animate(time)
     }
 }
+
+impl HitTest2D for Rectangle {
+    fn hit_test(&self, point: PhysicalPoint) -> bool {
+        let center: Vec2 = self.center.into();
+        let point: Vec2 = point.into();
+        let d = point - center;
+        let (sin, cos) = self.rotation.0.sin_cos();
+        let local = vec2(cos * d.x + sin * d.y, -sin * d.x + cos * d.y);
+        local.x.abs() <= 0.5 * self.width && local.y.abs() <= 0.5 * self.height
+    }
+}
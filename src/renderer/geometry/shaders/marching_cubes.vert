@@ -0,0 +1,101 @@
+uniform mat4 viewProjection;
+uniform mat4 modelMatrix;
+uniform mat4 normalMatrix;
+uniform ivec3 cellCount;
+uniform float isoValue;
+uniform vec3 size;
+uniform sampler3D voxels;
+uniform sampler2D triangleTable;
+
+out vec3 pos;
+out vec3 nor;
+#ifdef USE_TANGENTS
+out vec3 tang;
+out vec3 bitang;
+#endif
+out vec2 uvs;
+out vec4 col;
+out float emissive_intensity;
+
+const ivec3 corners[8] = ivec3[8](
+    ivec3(0, 0, 0), ivec3(1, 0, 0), ivec3(1, 1, 0), ivec3(0, 1, 0),
+    ivec3(0, 0, 1), ivec3(1, 0, 1), ivec3(1, 1, 1), ivec3(0, 1, 1)
+);
+
+const ivec2 edges[12] = ivec2[12](
+    ivec2(0, 1), ivec2(1, 2), ivec2(2, 3), ivec2(3, 0),
+    ivec2(4, 5), ivec2(5, 6), ivec2(6, 7), ivec2(7, 4),
+    ivec2(0, 4), ivec2(1, 5), ivec2(2, 6), ivec2(3, 7)
+);
+
+float voxel(ivec3 p) {
+    return texelFetch(voxels, clamp(p, ivec3(0), cellCount), 0).r;
+}
+
+vec3 gradient(ivec3 p) {
+    return vec3(
+        voxel(p + ivec3(1, 0, 0)) - voxel(p - ivec3(1, 0, 0)),
+        voxel(p + ivec3(0, 1, 0)) - voxel(p - ivec3(0, 1, 0)),
+        voxel(p + ivec3(0, 0, 1)) - voxel(p - ivec3(0, 0, 1))
+    );
+}
+
+void main()
+{
+    uvs = vec2(0.0);
+    col = vec4(1.0);
+    emissive_intensity = 1.0;
+
+    ivec3 cell = ivec3(
+        gl_InstanceID % cellCount.x,
+        (gl_InstanceID / cellCount.x) % cellCount.y,
+        gl_InstanceID / (cellCount.x * cellCount.y)
+    );
+
+    float values[8];
+    int cubeIndex = 0;
+    for (int i = 0; i < 8; i++) {
+        values[i] = voxel(cell + corners[i]);
+        if (values[i] < isoValue) {
+            cubeIndex |= 1 << i;
+        }
+    }
+
+    int edge = int(texelFetch(triangleTable, ivec2(gl_VertexID, cubeIndex), 0).r * 255.0 + 0.5) - 1;
+    if (edge < 0) {
+        // Collapse the unused vertices of the cell into a degenerate triangle outside the clip volume
+        pos = vec3(0.0);
+        nor = vec3(0.0, 1.0, 0.0);
+#ifdef USE_TANGENTS
+        tang = vec3(1.0, 0.0, 0.0);
+        bitang = vec3(0.0, 0.0, 1.0);
+#endif
+        gl_Position = vec4(2.0, 2.0, 2.0, 1.0);
+        return;
+    }
+
+    ivec2 e = edges[edge];
+    float d = values[e.y] - values[e.x];
+    float t = abs(d) > 0.00001 ? clamp((isoValue - values[e.x]) / d, 0.0, 1.0) : 0.5;
+    ivec3 a = cell + corners[e.x];
+    ivec3 b = cell + corners[e.y];
+    vec3 p = mix(vec3(a), vec3(b), t);
+
+    // The voxel centers are placed like the texels of a texture filling the box
+    vec3 voxelSize = size / vec3(textureSize(voxels, 0));
+    vec4 world_pos = modelMatrix * vec4((p + 0.5) * voxelSize - 0.5 * size, 1.0);
+    pos = world_pos.xyz / world_pos.w;
+
+    vec3 g = mix(gradient(a), gradient(b), t) / voxelSize;
+    nor = mat3(normalMatrix) * normalize(-g + vec3(0.0, 0.0, 0.000001));
+
+#ifdef USE_TANGENTS
+    // There are no texture coordinates to align the tangents with, so any orthonormal basis around the normal is used
+    vec3 n = normalize(nor);
+    vec3 helper = abs(n.y) < 0.999 ? vec3(0.0, 1.0, 0.0) : vec3(1.0, 0.0, 0.0);
+    tang = normalize(cross(helper, n));
+    bitang = cross(n, tang);
+#endif
+
+    gl_Position = viewProjection * world_pos;
+}
@@ -1,6 +1,7 @@
 
 uniform mat4 viewProjection;
 uniform mat4 modelMatrix;
+uniform vec2 pixelGrid;
 in vec3 position;
 
 #ifdef PARTICLES
@@ -20,6 +21,29 @@ in vec4 row2;
 in vec4 row3;
 #endif
 
+#ifdef USE_SKINNING
+uniform sampler2D jointMatrices;
+in vec4 joint_indices;
+in vec4 joint_weights;
+
+mat4 jointMatrix(float index)
+{
+    int i = 4 * int(index);
+    return mat4(texelFetch(jointMatrices, ivec2(i, 0), 0),
+        texelFetch(jointMatrices, ivec2(i + 1, 0), 0),
+        texelFetch(jointMatrices, ivec2(i + 2, 0), 0),
+        texelFetch(jointMatrices, ivec2(i + 3, 0), 0));
+}
+#endif
+
+#ifdef USE_MORPH
+uniform float morphWeight;
+in vec3 morph_position;
+#ifdef USE_NORMALS
+in vec3 morph_normal;
+#endif
+#endif
+
 out vec3 pos;
 
 #ifdef USE_NORMALS 
@@ -45,6 +69,17 @@ in vec2 uv_coordinates;
 out vec2 uvs;
 #endif
 
+#ifdef USE_DISPLACEMENT
+uniform sampler2D displacementTexture;
+uniform mat3 displacementTexTransform;
+uniform float displacementScale;
+#endif
+
+#ifdef USE_UVS1
+in vec2 uv_coordinates1;
+out vec2 uvs1;
+#endif
+
 #ifdef USE_VERTEX_COLORS 
 in vec4 color;
 #endif
@@ -54,6 +89,18 @@ in vec4 instance_color;
 
 out vec4 col;
 
+#ifdef USE_EMISSIVE_INTENSITY
+#ifdef USE_INSTANCE_EMISSIVE_INTENSITIES
+in float instance_emissive_intensity;
+#endif
+out float emissive_intensity;
+#endif
+
+#ifdef USE_INSTANCE_ID
+in float instance_index;
+flat out int instance_id;
+#endif
+
 void main()
 {
     // *** POSITION ***
@@ -68,26 +115,58 @@ void main()
     local2World *= transform;
 #endif
 
-    vec4 worldPosition = local2World * vec4(position, 1.);
+#ifdef USE_SKINNING
+    local2World *= joint_weights.x * jointMatrix(joint_indices.x)
+        + joint_weights.y * jointMatrix(joint_indices.y)
+        + joint_weights.z * jointMatrix(joint_indices.z)
+        + joint_weights.w * jointMatrix(joint_indices.w);
+#endif
+
+    vec3 vertexPosition = position;
+#ifdef USE_NORMALS
+    vec3 vertexNormal = normal;
+#endif
+#ifdef USE_MORPH
+    vertexPosition = mix(position, morph_position, morphWeight);
+#ifdef USE_NORMALS
+    vertexNormal = normalize(mix(normal, morph_normal, morphWeight));
+#endif
+#endif
+
+    vec4 worldPosition = local2World * vec4(vertexPosition, 1.);
     worldPosition /= worldPosition.w;
 #ifdef PARTICLES
     worldPosition.xyz += start_position + start_velocity * time + 0.5 * acceleration * time * time;
 #endif
 #ifdef USE_INSTANCE_TRANSLATIONS 
     worldPosition.xyz += instance_translation;
+#endif
+#if defined(USE_DISPLACEMENT) && !defined(USE_TESSELLATION)
+#if defined(USE_INSTANCE_TRANSFORMS) || defined(USE_SKINNING)
+    vec3 displacementNormal = normalize(mat3(transpose(inverse(local2World))) * vertexNormal);
+#else
+    vec3 displacementNormal = normalize(mat3(normalMatrix) * vertexNormal);
+#endif
+    float displacement = textureLod(displacementTexture, (displacementTexTransform * vec3(uv_coordinates, 1.0)).xy, 0.0).r;
+    worldPosition.xyz += displacementScale * displacement * displacementNormal;
 #endif
     gl_Position = viewProjection * worldPosition;
+    if (pixelGrid.x > 0.0) {
+        // Snap the vertex to the closest pixel corner
+        vec2 pixel = floor((0.5 * gl_Position.xy / gl_Position.w + 0.5) * pixelGrid + 0.5);
+        gl_Position.xy = (2.0 * pixel / pixelGrid - 1.0) * gl_Position.w;
+    }
 
     pos = worldPosition.xyz;
 
     // *** NORMAL ***
 #ifdef USE_NORMALS 
-#ifdef USE_INSTANCE_TRANSFORMS
+#if defined(USE_INSTANCE_TRANSFORMS) || defined(USE_SKINNING)
     mat3 normalMat = mat3(transpose(inverse(local2World)));
 #else
     mat3 normalMat = mat3(normalMatrix);
 #endif
-    nor = normalize(normalMat * normal);
+    nor = normalize(normalMat * vertexNormal);
 
 #ifdef USE_TANGENTS 
     tang = normalize(normalMat * tangent.xyz);
@@ -107,6 +186,9 @@ void main()
 #else
     uvs = uv_coordinates;
 #endif
+#endif
+#ifdef USE_UVS1
+    uvs1 = uv_coordinates1;
 #endif
 
     // *** COLOR ***
@@ -117,4 +199,22 @@ void main()
 #ifdef USE_INSTANCE_COLORS
     col *= instance_color;
 #endif
-}
\ No newline at end of file
+
+    // *** EMISSIVE INTENSITY ***
+#ifdef USE_EMISSIVE_INTENSITY
+    emissive_intensity = 1.0;
+#ifdef USE_INSTANCE_EMISSIVE_INTENSITIES
+    emissive_intensity = instance_emissive_intensity;
+#endif
+#endif
+
+    // *** INSTANCE ID ***
+#ifdef USE_INSTANCE_ID
+    instance_id = int(instance_index);
+#endif
+
+    // *** CUSTOM INSTANCE ATTRIBUTES ***
+#ifdef CUSTOM_INSTANCE_ATTRIBUTES
+    CUSTOM_INSTANCE_ATTRIBUTES
+#endif
+}
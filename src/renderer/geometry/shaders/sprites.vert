@@ -2,6 +2,7 @@ uniform mat4 viewProjection;
 uniform vec3 eye;
 uniform mat4 transformation;
 uniform vec3 direction;
+uniform vec2 pixelGrid;
 
 in vec3 center;
 
@@ -11,11 +12,13 @@ in vec2 uv_coordinate;
 out vec2 uvs;
 out vec4 col;
 out vec3 pos;
+out float emissive_intensity;
 
 void main()
 {
     uvs = uv_coordinate;
     col = vec4(1.0);
+    emissive_intensity = 1.0;
 
     vec3 z = normalize(eye - center);
     vec3 y = direction;
@@ -38,4 +41,9 @@ void main()
     vec4 world_pos = instanced_transform * transformation * vec4(position, 1.);
     pos = world_pos.xyz / world_pos.w;
     gl_Position = viewProjection * world_pos;
+    if (pixelGrid.x > 0.0) {
+        // Snap the vertex to the closest pixel corner
+        vec2 pixel = floor((0.5 * gl_Position.xy / gl_Position.w + 0.5) * pixelGrid + 0.5);
+        gl_Position.xy = (2.0 * pixel / pixelGrid - 1.0) * gl_Position.w;
+    }
 }
@@ -0,0 +1,269 @@
+use crate::core::*;
+use crate::renderer::*;
+
+use super::BaseMesh;
+
+///
+/// The skinning data of a [CpuMesh], ie. which joints in a [Skeleton] each vertex is attached to and how much each joint affects the vertex.
+///
+#[derive(Clone, Debug, Default)]
+pub struct CpuSkin {
+    /// The indices of the (up to) four joints affecting each vertex.
+    pub joint_indices: Vec<[u16; 4]>,
+    /// The weights of the four joints affecting each vertex. The weights for each vertex should sum to one.
+    pub joint_weights: Vec<Vec4>,
+}
+
+///
+/// A triangle mesh [Geometry] which is deformed by a [Skeleton], ie. skeletal animation where the vertices are skinned on the GPU.
+/// The mesh can be animated by choosing an [AnimationClip], see [SkinnedMesh::set_animation_clip], or by setting a pose directly, see [SkinnedMesh::set_pose].
+///
+/// **Note:** The bounding box is computed from the mesh in the bind pose, so parts of the animated mesh might be outside the bounding box.
+///
+pub struct SkinnedMesh {
+    base_mesh: BaseMesh,
+    context: Context,
+    aabb: AxisAlignedBoundingBox,
+    transformation: Mat4,
+    joint_indices: VertexBuffer,
+    joint_weights: VertexBuffer,
+    joint_texture: Texture2D,
+    skeleton: Skeleton,
+    animation_clip: Option<AnimationClip>,
+}
+
+impl SkinnedMesh {
+    ///
+    /// Creates a new skinned triangle mesh from the given [CpuMesh], the skinning data with a value for each vertex and the skeleton which deforms the mesh.
+    /// The mesh is initially in the rest pose of the skeleton.
+    ///
+    pub fn new(context: &Context, cpu_mesh: &CpuMesh, skin: &CpuSkin, skeleton: Skeleton) -> Self {
+        let vertex_count = cpu_mesh.vertex_count();
+        debug_assert_eq!(
+            skin.joint_indices.len(),
+            vertex_count,
+            "the number of joint indices must match the number of vertices"
+        );
+        debug_assert_eq!(
+            skin.joint_weights.len(),
+            vertex_count,
+            "the number of joint weights must match the number of vertices"
+        );
+        let joint_count = skeleton.joints().len().max(1) as u32;
+        let mut mesh = Self {
+            context: context.clone(),
+            base_mesh: BaseMesh::new(context, cpu_mesh),
+            aabb: cpu_mesh.compute_aabb(),
+            transformation: Mat4::identity(),
+            joint_indices: VertexBuffer::new_with_data(
+                context,
+                &skin
+                    .joint_indices
+                    .iter()
+                    .map(|i| vec4(i[0] as f32, i[1] as f32, i[2] as f32, i[3] as f32))
+                    .collect::<Vec<_>>(),
+            ),
+            joint_weights: VertexBuffer::new_with_data(context, &skin.joint_weights),
+            joint_texture: Texture2D::new_empty::<[f32; 4]>(
+                context,
+                4 * joint_count,
+                1,
+                Interpolation::Nearest,
+                Interpolation::Nearest,
+                None,
+                Wrapping::ClampToEdge,
+                Wrapping::ClampToEdge,
+            ),
+            skeleton,
+            animation_clip: None,
+        };
+        let rest_pose = mesh.skeleton.rest_pose();
+        mesh.set_pose(&rest_pose);
+        mesh
+    }
+
+    ///
+    /// Returns the local to world transformation applied to this mesh.
+    ///
+    pub fn transformation(&self) -> Mat4 {
+        self.transformation
+    }
+
+    ///
+    /// Set the local to world transformation applied to this mesh. The skinning is applied before this transformation.
+    ///
+    pub fn set_transformation(&mut self, transformation: Mat4) {
+        self.transformation = transformation;
+    }
+
+    ///
+    /// Returns the skeleton which deforms this mesh.
+    ///
+    pub fn skeleton(&self) -> &Skeleton {
+        &self.skeleton
+    }
+
+    ///
+    /// Specifies the animation clip which is evaluated when [Geometry::animate] is called or `None` to stop animating the mesh.
+    ///
+    pub fn set_animation_clip(&mut self, animation_clip: Option<AnimationClip>) {
+        self.animation_clip = animation_clip;
+    }
+
+    ///
+    /// Sets the pose of the skeleton, ie. the local transformation of each joint, see [Skeleton::joint_matrices].
+    ///
+    pub fn set_pose(&mut self, pose: &[Mat4]) {
+        let data = self
+            .skeleton
+            .joint_matrices(pose)
+            .into_iter()
+            .flat_map(|m| [m.x.into(), m.y.into(), m.z.into(), m.w.into()])
+            .collect::<Vec<[f32; 4]>>();
+        if !data.is_empty() {
+            self.joint_texture.fill(&data);
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a SkinnedMesh {
+    type Item = &'a dyn Geometry;
+    type IntoIter = std::iter::Once<&'a dyn Geometry>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        std::iter::once(self)
+    }
+}
+
+impl Geometry for SkinnedMesh {
+    fn aabb(&self) -> AxisAlignedBoundingBox {
+        let mut aabb = self.aabb;
+        aabb.transform(&self.transformation);
+        aabb
+    }
+
+    fn animate(&mut self, time: f32) {
+        if let Some(pose) = self
+            .animation_clip
+            .as_ref()
+            .map(|clip| clip.pose(&self.skeleton, time))
+        {
+            self.set_pose(&pose);
+        }
+    }
+
+    fn draw(
+        &self,
+        camera: &Camera,
+        program: &Program,
+        render_states: RenderStates,
+        attributes: FragmentAttributes,
+    ) {
+        program.use_uniform("viewProjection", camera.projection() * camera.view());
+        program.use_uniform_if_required("pixelGrid", camera.pixel_grid());
+        program.use_uniform("modelMatrix", self.transformation);
+        program.use_texture("jointMatrices", &self.joint_texture);
+        program.use_vertex_attribute("joint_indices", &self.joint_indices);
+        program.use_vertex_attribute("joint_weights", &self.joint_weights);
+
+        self.base_mesh
+            .draw(program, render_states, camera, attributes);
+    }
+
+    fn vertex_shader_source(&self, required_attributes: FragmentAttributes) -> String {
+        format!(
+            "#define USE_SKINNING\n{}{}{}{}{}{}{}{}{}",
+            if required_attributes.normal {
+                "#define USE_NORMALS\n"
+            } else {
+                ""
+            },
+            if required_attributes.tangents {
+                "#define USE_TANGENTS\n"
+            } else {
+                ""
+            },
+            if required_attributes.uv {
+                "#define USE_UVS\n"
+            } else {
+                ""
+            },
+            if required_attributes.uv1 {
+                "#define USE_UVS1\n"
+            } else {
+                ""
+            },
+            if required_attributes.displacement {
+                "#define USE_DISPLACEMENT\n"
+            } else {
+                ""
+            },
+            if required_attributes.color && self.base_mesh.colors.is_some() {
+                "#define USE_VERTEX_COLORS\n"
+            } else {
+                ""
+            },
+            if required_attributes.emissive_intensity {
+                "#define USE_EMISSIVE_INTENSITY\n"
+            } else {
+                ""
+            },
+            include_str!("../../core/shared.frag"),
+            include_str!("shaders/mesh.vert"),
+        )
+    }
+
+    fn id(&self, required_attributes: FragmentAttributes) -> u16 {
+        let mut id = 0b1u16 << 15 | 0b1u16 << 4 | 0b1u16 << 11;
+        if required_attributes.normal {
+            id |= 0b1u16;
+        }
+        if required_attributes.tangents {
+            id |= 0b1u16 << 1;
+        }
+        if required_attributes.uv {
+            id |= 0b1u16 << 2;
+        }
+        if required_attributes.uv1 {
+            id |= 0b1u16 << 8;
+        }
+        if required_attributes.displacement {
+            id |= 0b1u16 << 9;
+        }
+        if required_attributes.color && self.base_mesh.colors.is_some() {
+            id |= 0b1u16 << 3;
+        }
+        if required_attributes.emissive_intensity {
+            id |= 0b1u16 << 10;
+        }
+        id
+    }
+
+    fn render_with_material(
+        &self,
+        material: &dyn Material,
+        camera: &Camera,
+        lights: &[&dyn Light],
+    ) {
+        render_with_material(&self.context, camera, &self, material, lights);
+    }
+
+    fn render_with_effect(
+        &self,
+        material: &dyn Effect,
+        camera: &Camera,
+        lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) {
+        render_with_effect(
+            &self.context,
+            camera,
+            self,
+            material,
+            lights,
+            color_texture,
+            depth_texture,
+        )
+    }
+}
@@ -86,6 +86,7 @@ impl Sprites {
     fn draw(&self, program: &Program, render_states: RenderStates, camera: &Camera) {
         program.use_uniform("eye", camera.position());
         program.use_uniform("viewProjection", camera.projection() * camera.view());
+        program.use_uniform_if_required("pixelGrid", camera.pixel_grid());
         program.use_uniform("transformation", self.transformation);
         program.use_vertex_attribute("position", &self.position_buffer);
         program.use_vertex_attribute("uv_coordinate", &self.uv_buffer);
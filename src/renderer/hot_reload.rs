@@ -0,0 +1,251 @@
+use crate::renderer::*;
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::{Duration, Instant, SystemTime};
+
+///
+/// A GPU resource which can be reloaded from one or more asset files, see [AssetWatcher].
+///
+pub trait HotReload {
+    ///
+    /// Replaces the GPU resource by the content of the given files, which are already loaded into the given raw assets.
+    /// If reloading fails, the resource is left unchanged.
+    ///
+    fn reload(
+        &mut self,
+        context: &Context,
+        paths: &[PathBuf],
+        raw_assets: &mut three_d_asset::io::RawAssets,
+    ) -> Result<(), RendererError>;
+}
+
+impl HotReload for Texture2D {
+    fn reload(
+        &mut self,
+        context: &Context,
+        paths: &[PathBuf],
+        raw_assets: &mut three_d_asset::io::RawAssets,
+    ) -> Result<(), RendererError> {
+        let cpu_texture: CpuTexture = raw_assets.deserialize(first_path(paths)?)?;
+        *self = Texture2D::new(context, &cpu_texture);
+        Ok(())
+    }
+}
+
+impl HotReload for Texture2DRef {
+    fn reload(
+        &mut self,
+        context: &Context,
+        paths: &[PathBuf],
+        raw_assets: &mut three_d_asset::io::RawAssets,
+    ) -> Result<(), RendererError> {
+        let cpu_texture: CpuTexture = raw_assets.deserialize(first_path(paths)?)?;
+        self.texture = std::sync::Arc::new(Texture2D::new(context, &cpu_texture));
+        Ok(())
+    }
+}
+
+impl HotReload for Mesh {
+    ///
+    /// Replaces the mesh by the first triangle mesh in the model file. The transformation of the mesh is kept.
+    ///
+    fn reload(
+        &mut self,
+        context: &Context,
+        paths: &[PathBuf],
+        raw_assets: &mut three_d_asset::io::RawAssets,
+    ) -> Result<(), RendererError> {
+        let path = first_path(paths)?;
+        let cpu_model: CpuModel = raw_assets.deserialize(path)?;
+        let cpu_mesh = cpu_model
+            .geometries
+            .iter()
+            .find_map(|primitive| match &primitive.geometry {
+                CpuGeometry::Triangles(mesh) => Some(mesh),
+                _ => None,
+            })
+            .ok_or_else(|| {
+                RendererError::ReloadFailed(format!(
+                    "{} does not contain a triangle mesh",
+                    path.display()
+                ))
+            })?;
+        let transformation = self.transformation();
+        *self = Mesh::new(context, cpu_mesh);
+        self.set_transformation(transformation);
+        Ok(())
+    }
+}
+
+impl HotReload for Program {
+    ///
+    /// Recompiles the program from a vertex shader file followed by a fragment shader file.
+    /// If the shaders fail to compile, the previous program is kept, so a typo does not stop the application.
+    ///
+    fn reload(
+        &mut self,
+        context: &Context,
+        paths: &[PathBuf],
+        raw_assets: &mut three_d_asset::io::RawAssets,
+    ) -> Result<(), RendererError> {
+        if paths.len() != 2 {
+            return Err(RendererError::ReloadFailed(
+                "a program must be watched using a vertex shader file followed by a fragment shader file".to_owned(),
+            ));
+        }
+        let vertex_shader_source =
+            String::from_utf8_lossy(&raw_assets.remove(&paths[0])?).into_owned();
+        let fragment_shader_source =
+            String::from_utf8_lossy(&raw_assets.remove(&paths[1])?).into_owned();
+        *self = Program::from_source(context, &vertex_shader_source, &fragment_shader_source)
+            .map_err(|e| RendererError::ReloadFailed(e.to_string()))?;
+        Ok(())
+    }
+}
+
+fn first_path(paths: &[PathBuf]) -> Result<&Path, RendererError> {
+    paths
+        .first()
+        .map(|p| p.as_path())
+        .ok_or_else(|| RendererError::ReloadFailed("no file to reload from".to_owned()))
+}
+
+struct WatchedAsset {
+    paths: Vec<PathBuf>,
+    modified: Vec<Option<SystemTime>>,
+    resource: Rc<RefCell<dyn HotReload>>,
+}
+
+///
+/// Watches asset files on disk, for example textures, meshes and shaders, and reloads the corresponding GPU resources when the files change,
+/// so changes made in external tools are visible without restarting the application.
+///
+/// The resources are owned by the watcher and shared with the application through a `Rc<RefCell<T>>`, see [AssetWatcher::watch].
+/// The files are checked for changes when [AssetWatcher::update] is called, which should be done once per frame before rendering,
+/// so the resources are never replaced in the middle of a frame.
+///
+pub struct AssetWatcher {
+    context: Context,
+    assets: Vec<WatchedAsset>,
+    poll_interval: Duration,
+    last_poll: Option<Instant>,
+}
+
+impl AssetWatcher {
+    ///
+    /// Creates a new watcher without any watched files which checks the files for changes at most every 250 milliseconds.
+    ///
+    pub fn new(context: &Context) -> Self {
+        Self {
+            context: context.clone(),
+            assets: Vec::new(),
+            poll_interval: Duration::from_millis(250),
+            last_poll: None,
+        }
+    }
+
+    ///
+    /// Sets the minimum time between checking the watched files for changes.
+    ///
+    pub fn set_poll_interval(&mut self, poll_interval: Duration) {
+        self.poll_interval = poll_interval;
+    }
+
+    ///
+    /// Watches the given files and reloads the given resource from the files whenever one of them changes.
+    /// A texture or mesh is watched using a single file while a [Program] is watched using a vertex shader file followed by a fragment shader file.
+    /// Returns the resource which is shared between the watcher and the application.
+    ///
+    pub fn watch<T: HotReload + 'static>(
+        &mut self,
+        paths: &[impl AsRef<Path>],
+        resource: T,
+    ) -> Rc<RefCell<T>> {
+        let paths = paths
+            .iter()
+            .map(|p| p.as_ref().to_path_buf())
+            .collect::<Vec<_>>();
+        let resource = Rc::new(RefCell::new(resource));
+        self.assets.push(WatchedAsset {
+            modified: paths.iter().map(|p| modified_time(p.as_path())).collect(),
+            paths,
+            resource: resource.clone(),
+        });
+        resource
+    }
+
+    ///
+    /// Stops watching the files of the given resource.
+    ///
+    pub fn unwatch<T: HotReload + 'static>(&mut self, resource: &Rc<RefCell<T>>) {
+        let resource = Rc::as_ptr(resource) as *const ();
+        self.assets
+            .retain(|asset| Rc::as_ptr(&asset.resource) as *const () != resource);
+    }
+
+    ///
+    /// Stops watching the files of the resources which are no longer used by the application.
+    ///
+    pub fn unwatch_unused(&mut self) {
+        self.assets
+            .retain(|asset| Rc::strong_count(&asset.resource) > 1);
+    }
+
+    ///
+    /// Checks the watched files for changes and reloads the resources from the changed files.
+    /// Should be called once per frame before rendering, for example at the start of the render loop.
+    /// Returns whether or not any resources were reloaded.
+    ///
+    /// # Errors
+    /// If a resource fails to reload, the other resources are still reloaded, the failed resource keeps its previous content and the first error is returned.
+    /// The resource is reloaded again the next time one of its files changes.
+    ///
+    pub fn update(&mut self) -> Result<bool, RendererError> {
+        if self
+            .last_poll
+            .map(|last_poll| last_poll.elapsed() < self.poll_interval)
+            .unwrap_or(false)
+        {
+            return Ok(false);
+        }
+        self.last_poll = Some(Instant::now());
+
+        let mut reloaded = false;
+        let mut error = None;
+        for asset in self.assets.iter_mut() {
+            let modified = asset
+                .paths
+                .iter()
+                .map(|p| modified_time(p.as_path()))
+                .collect::<Vec<_>>();
+            if modified == asset.modified {
+                continue;
+            }
+            asset.modified = modified;
+            let result = three_d_asset::io::load(&asset.paths)
+                .map_err(RendererError::from)
+                .and_then(|mut raw_assets| {
+                    asset
+                        .resource
+                        .borrow_mut()
+                        .reload(&self.context, &asset.paths, &mut raw_assets)
+                });
+            match result {
+                Ok(()) => reloaded = true,
+                Err(e) => {
+                    error.get_or_insert(e);
+                }
+            }
+        }
+        if let Some(e) = error {
+            Err(e)
+        } else {
+            Ok(reloaded)
+        }
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
@@ -15,6 +15,9 @@ macro_rules! impl_light_body {
         fn id(&self) -> u8 {
             self.$inner().id()
         }
+        fn is_indirect(&self) -> bool {
+            self.$inner().is_indirect()
+        }
     };
 }
 
@@ -40,6 +43,34 @@ mod environment;
 #[doc(inline)]
 pub use environment::*;
 
+mod environment_light;
+#[doc(inline)]
+pub use environment_light::*;
+
+mod dynamic_environment_map;
+#[doc(inline)]
+pub use dynamic_environment_map::*;
+
+mod units;
+#[doc(inline)]
+pub use units::*;
+
+mod light_profile;
+#[doc(inline)]
+pub use light_profile::*;
+
+mod light_animation;
+#[doc(inline)]
+pub use light_animation::*;
+
+mod shadow_atlas;
+#[doc(inline)]
+pub use shadow_atlas::*;
+
+mod contact_shadows;
+#[doc(inline)]
+pub use contact_shadows::*;
+
 use crate::core::*;
 use crate::renderer::camera::*;
 
@@ -57,6 +88,18 @@ pub struct Attenuation {
     pub quadratic: f32,
 }
 
+impl Attenuation {
+    ///
+    /// Physically correct attenuation where the intensity of the light is inversely proportional to the square of the distance to the light.
+    /// Use this together with light intensities given in physical units, see [Exposure].
+    ///
+    pub const INVERSE_SQUARE: Self = Self {
+        constant: 0.0,
+        linear: 0.0,
+        quadratic: 1.0,
+    };
+}
+
 impl Default for Attenuation {
     fn default() -> Self {
         Self {
@@ -67,6 +110,65 @@ impl Default for Attenuation {
     }
 }
 
+///
+/// The quality of soft shadows, ie. the number of samples taken from the shadow map in each fragment.
+/// Higher quality gives smoother and less noisy shadows but is more expensive to render.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum ShadowQuality {
+    /// 8 samples.
+    Low,
+    /// 16 samples.
+    #[default]
+    Medium,
+    /// 32 samples.
+    High,
+}
+
+impl ShadowQuality {
+    fn sample_count(&self) -> i32 {
+        match self {
+            Self::Low => 8,
+            Self::Medium => 16,
+            Self::High => 32,
+        }
+    }
+}
+
+///
+/// Specifies which lights are used for rendering an object, which makes it possible to combine baked lighting, ie. light maps, with real-time lighting.
+/// For example, static architecture can use light maps while moving characters receive real-time lights.
+/// Use [Scene::set_lighting_mode] to specify the lighting mode of an object in a scene or [LightingMode::lights] to select the lights when rendering an object directly.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum LightingMode {
+    /// All lighting is calculated in real-time.
+    #[default]
+    Dynamic,
+    /// The indirect lighting is baked into a light map, see [PhysicalMaterial::light_map_texture], and the direct lighting is calculated in real-time,
+    /// ie. all lights are used except the lights that only contribute indirect light, see [Light::is_indirect].
+    BakedIndirect,
+    /// All lighting is baked into a light map, see [PhysicalMaterial::light_map_texture], so no lights are used.
+    Baked,
+}
+
+impl LightingMode {
+    ///
+    /// Returns the subset of the given lights which should be used for rendering an object with this lighting mode.
+    ///
+    pub fn lights<'a>(&self, lights: &[&'a dyn Light]) -> Vec<&'a dyn Light> {
+        match self {
+            Self::Dynamic => lights.to_vec(),
+            Self::BakedIndirect => lights
+                .iter()
+                .filter(|light| !light.is_indirect())
+                .copied()
+                .collect(),
+            Self::Baked => Vec::new(),
+        }
+    }
+}
+
 /// Represents a light source.
 pub trait Light {
     /// The fragment shader source for calculating this lights contribution to the color in a fragment.
@@ -86,30 +188,73 @@ pub trait Light {
     /// outside of this crate, always return an id that is smaller than `0b1u8 << 7`.
     ///
     fn id(&self) -> u8;
+
+    ///
+    /// Returns a light which is identical to this light except that it does not cast shadows, or `None` if this light does not cast shadows.
+    /// Used for rendering objects that should not receive shadows, see [Scene::set_receives_shadows].
+    ///
+    fn without_shadows(&self) -> Option<Box<dyn Light + '_>> {
+        None
+    }
+
+    ///
+    /// Returns whether or not this light only contributes indirect light, like an [AmbientLight].
+    /// Indirect lights are not used for objects where the indirect light is baked into a light map, see [LightingMode::BakedIndirect].
+    ///
+    fn is_indirect(&self) -> bool {
+        false
+    }
 }
 
 impl<T: Light + ?Sized> Light for &T {
     impl_light_body!(deref);
+
+    fn without_shadows(&self) -> Option<Box<dyn Light + '_>> {
+        self.deref().without_shadows()
+    }
 }
 
 impl<T: Light + ?Sized> Light for &mut T {
     impl_light_body!(deref);
+
+    fn without_shadows(&self) -> Option<Box<dyn Light + '_>> {
+        self.deref().without_shadows()
+    }
 }
 
 impl<T: Light> Light for Box<T> {
     impl_light_body!(as_ref);
+
+    fn without_shadows(&self) -> Option<Box<dyn Light + '_>> {
+        self.as_ref().without_shadows()
+    }
 }
 
 impl<T: Light> Light for std::rc::Rc<T> {
     impl_light_body!(as_ref);
+
+    fn without_shadows(&self) -> Option<Box<dyn Light + '_>> {
+        self.as_ref().without_shadows()
+    }
 }
 
 impl<T: Light> Light for std::sync::Arc<T> {
     impl_light_body!(as_ref);
+
+    fn without_shadows(&self) -> Option<Box<dyn Light + '_>> {
+        self.as_ref().without_shadows()
+    }
 }
 
 impl<T: Light> Light for std::cell::RefCell<T> {
     impl_light_body!(borrow);
+
+    fn without_shadows(&self) -> Option<Box<dyn Light + '_>> {
+        self.borrow()
+            .without_shadows()
+            .is_some()
+            .then(|| Box::new(BorrowedWithoutShadows(self)) as Box<dyn Light + '_>)
+    }
 }
 
 impl<T: Light> Light for std::sync::Arc<std::sync::RwLock<T>> {
@@ -122,6 +267,64 @@ impl<T: Light> Light for std::sync::Arc<std::sync::RwLock<T>> {
     fn id(&self) -> u8 {
         self.read().unwrap().id()
     }
+    fn is_indirect(&self) -> bool {
+        self.read().unwrap().is_indirect()
+    }
+    fn without_shadows(&self) -> Option<Box<dyn Light + '_>> {
+        self.read()
+            .unwrap()
+            .without_shadows()
+            .is_some()
+            .then(|| Box::new(BorrowedWithoutShadows(self)) as Box<dyn Light + '_>)
+    }
+}
+
+///
+/// A light behind a [RefCell](std::cell::RefCell) or [RwLock](std::sync::RwLock) which can only be accessed during a call.
+///
+trait LightCell {
+    fn with_light<R>(&self, f: impl FnOnce(&dyn Light) -> R) -> R;
+}
+
+impl<T: Light> LightCell for std::cell::RefCell<T> {
+    fn with_light<R>(&self, f: impl FnOnce(&dyn Light) -> R) -> R {
+        f(&*self.borrow())
+    }
+}
+
+impl<T: Light> LightCell for std::sync::Arc<std::sync::RwLock<T>> {
+    fn with_light<R>(&self, f: impl FnOnce(&dyn Light) -> R) -> R {
+        f(&*self.read().unwrap())
+    }
+}
+
+///
+/// The light without shadows of a light behind a [LightCell], which is looked up in each call since the light can not be borrowed for longer than that.
+///
+struct BorrowedWithoutShadows<'a, C: LightCell>(&'a C);
+
+impl<C: LightCell> BorrowedWithoutShadows<'_, C> {
+    fn with_light_without_shadows<R>(&self, f: impl FnOnce(&dyn Light) -> R) -> R {
+        self.0.with_light(|light| match light.without_shadows() {
+            Some(light_without_shadows) => f(light_without_shadows.as_ref()),
+            None => f(light),
+        })
+    }
+}
+
+impl<C: LightCell> Light for BorrowedWithoutShadows<'_, C> {
+    fn shader_source(&self, i: u32) -> String {
+        self.with_light_without_shadows(|light| light.shader_source(i))
+    }
+    fn use_uniforms(&self, program: &Program, i: u32) {
+        self.with_light_without_shadows(|light| light.use_uniforms(program, i))
+    }
+    fn id(&self) -> u8 {
+        self.with_light_without_shadows(|light| light.id())
+    }
+    fn is_indirect(&self) -> bool {
+        self.with_light_without_shadows(|light| light.is_indirect())
+    }
 }
 
 ///
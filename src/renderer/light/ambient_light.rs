@@ -47,6 +47,7 @@ impl Light for AmbientLight {
                 uniform samplerCube irradianceMap;
                 uniform samplerCube prefilterMap;
                 uniform sampler2D brdfLUT;
+                uniform float prefilterMaxLod;
                 uniform vec3 ambientColor;
     
                 vec3 calculate_lighting{}(vec3 surface_color, vec3 position, vec3 normal, vec3 view_direction, float metallic, float roughness, float occlusion)
@@ -67,8 +68,7 @@ impl Light for AmbientLight {
                     vec3 diffuse = diffuse_fresnel * mix(surface_color, vec3(0.0), metallic) * irradiance;
                     
                     // sample both the pre-filter map and the BRDF lut and combine them together as per the Split-Sum approximation to get the IBL specular part.
-                    const float MAX_REFLECTION_LOD = 4.0;
-                    vec3 prefilteredColor = textureLod(prefilterMap, R,  roughness * MAX_REFLECTION_LOD).rgb;    
+                    vec3 prefilteredColor = textureLod(prefilterMap, R,  roughness * prefilterMaxLod).rgb;    
                     vec2 brdf  = texture(brdfLUT, vec2(NdV, roughness)).rg;
                     vec3 specular = prefilteredColor * (specular_fresnel * brdf.x + brdf.y);
     
@@ -92,6 +92,7 @@ impl Light for AmbientLight {
         if let Some(ref environment) = self.environment {
             program.use_texture_cube("irradianceMap", &environment.irradiance_map);
             program.use_texture_cube("prefilterMap", &environment.prefilter_map);
+            program.use_uniform("prefilterMaxLod", environment.prefilter_max_lod);
             program.use_texture("brdfLUT", &environment.brdf_map);
         }
         program.use_uniform(
@@ -107,6 +108,10 @@ impl Light for AmbientLight {
             0b1u8 << 7 | 0b1u8
         }
     }
+
+    fn is_indirect(&self) -> bool {
+        true
+    }
 }
 
 impl Default for AmbientLight {
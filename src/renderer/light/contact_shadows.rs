@@ -0,0 +1,140 @@
+use crate::core::*;
+use crate::renderer::*;
+use std::sync::{Arc, RwLock};
+
+///
+/// Short range shadows which are calculated by marching from each fragment towards the light in screen space using a depth map of the scene seen from the camera.
+/// Contact shadows capture the small details, for example where a small object meets the ground, which are typically missed by shadow maps
+/// because of their limited resolution and the shadow bias that makes shadows detach from the objects casting them (peter-panning).
+///
+/// Update the depth map each frame before rendering the scene, see [ContactShadows::update], and assign the contact shadows to a [DirectionalLight] or a [SpotLight].
+/// If the light also has a shadow map, the contact shadows are combined with the shadows from the shadow map.
+/// The same contact shadows can be assigned to several lights, since a clone shares the depth map with the original.
+///
+/// **Note:** The depth map is expected to be rendered with a perspective camera.
+///
+#[derive(Clone)]
+pub struct ContactShadows {
+    context: Context,
+    depth: Arc<RwLock<ContactShadowsDepth>>,
+    /// The length in world units of the ray which is marched towards the light, ie. only objects closer than this distance cast contact shadows.
+    pub length: f32,
+    /// The assumed thickness in world units of the surfaces in the depth map. Surfaces in front of the ray which are further away than this are not considered occluders.
+    pub thickness: f32,
+    /// The number of steps along the ray. More steps give more accurate shadows but are more expensive to render.
+    pub step_count: u32,
+}
+
+struct ContactShadowsDepth {
+    texture: DepthTexture2D,
+    view_projection: Mat4,
+    depth_range: Vec2,
+}
+
+impl ContactShadows {
+    ///
+    /// Constructs new contact shadows. There are no contact shadows until the depth map is updated, see [ContactShadows::update].
+    ///
+    pub fn new(context: &Context) -> Self {
+        Self {
+            context: context.clone(),
+            depth: Arc::new(RwLock::new(ContactShadowsDepth {
+                texture: new_depth_texture(context, 1, 1),
+                view_projection: Mat4::identity(),
+                depth_range: vec2(0.01, 1.0),
+            })),
+            length: 0.1,
+            thickness: 0.05,
+            step_count: 16,
+        }
+    }
+
+    ///
+    /// Updates the depth map by rendering the given geometries with the given camera, which should be the camera used for rendering the scene afterwards.
+    /// Should be called each time the camera or the geometries change.
+    ///
+    pub fn update(&mut self, camera: &Camera, geometries: impl IntoIterator<Item = impl Geometry>) {
+        let viewport = camera.viewport();
+        let mut depth_camera = camera.clone();
+        depth_camera.set_viewport(Viewport::new_at_origo(viewport.width, viewport.height));
+
+        let mut depth = self.depth.write().unwrap();
+        if depth.texture.width() != viewport.width || depth.texture.height() != viewport.height {
+            depth.texture = new_depth_texture(&self.context, viewport.width, viewport.height);
+        }
+        let depth_material = DepthMaterial {
+            render_states: RenderStates {
+                write_mask: WriteMask::DEPTH,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        self.context.push_debug_group("Contact shadows depth");
+        depth
+            .texture
+            .as_depth_target()
+            .clear(ClearState::default())
+            .write(|| {
+                for geometry in geometries
+                    .into_iter()
+                    .filter(|g| depth_camera.in_frustum(&g.aabb()))
+                {
+                    render_with_material(
+                        &self.context,
+                        &depth_camera,
+                        &geometry,
+                        &depth_material,
+                        &[],
+                    );
+                }
+            });
+        self.context.pop_debug_group();
+        depth.view_projection = depth_camera.projection() * depth_camera.view();
+        depth.depth_range = vec2(depth_camera.z_near(), depth_camera.z_far());
+    }
+
+    pub(super) fn shader_source(i: u32) -> String {
+        format!(
+            "uniform sampler2D contactShadowDepth{};\nuniform mat4 contactShadowViewProjection{};\nuniform vec4 contactShadowSettings{};\nuniform vec2 contactShadowDepthRange{};\n",
+            i, i, i, i
+        )
+    }
+
+    pub(super) fn shadow_call(i: u32, light_direction: &str) -> String {
+        format!(
+            "calculate_contact_shadow({}, position, contactShadowDepth{}, contactShadowViewProjection{}, contactShadowSettings{}, contactShadowDepthRange{})",
+            light_direction, i, i, i, i
+        )
+    }
+
+    pub(super) fn use_uniforms(&self, program: &Program, i: u32) {
+        let depth = self.depth.read().unwrap();
+        program.use_depth_texture(&format!("contactShadowDepth{}", i), &depth.texture);
+        program.use_uniform(
+            &format!("contactShadowViewProjection{}", i),
+            depth.view_projection,
+        );
+        program.use_uniform(
+            &format!("contactShadowSettings{}", i),
+            vec4(
+                self.length,
+                self.thickness,
+                self.step_count.max(1) as f32,
+                0.0,
+            ),
+        );
+        program.use_uniform(&format!("contactShadowDepthRange{}", i), depth.depth_range);
+    }
+}
+
+fn new_depth_texture(context: &Context, width: u32, height: u32) -> DepthTexture2D {
+    let mut texture = DepthTexture2D::new::<f32>(
+        context,
+        width,
+        height,
+        Wrapping::ClampToEdge,
+        Wrapping::ClampToEdge,
+    );
+    texture.as_depth_target().clear(ClearState::default());
+    texture
+}
@@ -4,18 +4,23 @@ use crate::renderer::*;
 
 ///
 /// A light which shines in the given direction.
-/// The light will cast shadows if you [generate a shadow map](DirectionalLight::generate_shadow_map).
+/// The light will cast shadows if you [generate a shadow map](DirectionalLight::generate_shadow_map)
+/// or [generate cascaded shadow maps](DirectionalLight::generate_cascaded_shadow_maps).
 ///
 pub struct DirectionalLight {
     context: Context,
     shadow_texture: Option<DepthTexture2D>,
+    shadow_tile: Option<ShadowAtlasTile>,
     shadow_matrix: Mat4,
+    cascades: Option<ShadowCascades>,
     /// The intensity of the light. This allows for higher intensity than 1 which can be used to simulate high intensity light sources like the sun.
     pub intensity: f32,
     /// The base color of the light.
     pub color: Srgba,
     /// The direction the light shines.
     pub direction: Vec3,
+    /// Optional [ContactShadows] which are combined with the shadows from the shadow map to capture the small details close to the shadow casters.
+    pub contact_shadows: Option<ContactShadows>,
 }
 
 impl DirectionalLight {
@@ -30,9 +35,12 @@ impl DirectionalLight {
             context: context.clone(),
             shadow_matrix: Mat4::identity(),
             shadow_texture: None,
+            shadow_tile: None,
+            cascades: None,
             intensity,
             color,
             direction: *direction,
+            contact_shadows: None,
         }
     }
 
@@ -42,7 +50,9 @@ impl DirectionalLight {
     ///
     pub fn clear_shadow_map(&mut self) {
         self.shadow_texture = None;
+        self.shadow_tile = None;
         self.shadow_matrix = Mat4::identity();
+        self.cascades = None;
     }
 
     ///
@@ -56,30 +66,11 @@ impl DirectionalLight {
         texture_size: u32,
         geometries: impl IntoIterator<Item = impl Geometry> + Clone,
     ) {
-        let up = compute_up_direction(self.direction);
-
         let viewport = Viewport::new_at_origo(texture_size, texture_size);
-        let mut aabb = AxisAlignedBoundingBox::EMPTY;
-        for geometry in geometries.clone() {
-            aabb.expand_with_aabb(&geometry.aabb());
-        }
-        if aabb.is_empty() {
-            return;
-        }
-        let target = aabb.center();
-        let position = target - aabb.max().distance(aabb.min()) * self.direction;
-        let z_far = aabb.distance_max(&position);
-        let z_near = aabb.distance(&position);
-        let frustum_height = aabb.max().distance(aabb.min()); // TODO: more tight fit
-        let shadow_camera = Camera::new_orthographic(
-            viewport,
-            position,
-            target,
-            up,
-            frustum_height,
-            z_near,
-            z_far,
-        );
+        let shadow_camera = match self.shadow_camera(viewport, geometries.clone()) {
+            Some(camera) => camera,
+            None => return,
+        };
         let mut shadow_texture = DepthTexture2D::new::<f32>(
             &self.context,
             texture_size,
@@ -94,6 +85,8 @@ impl DirectionalLight {
             },
             ..Default::default()
         };
+        self.context
+            .push_debug_group("Directional light shadow map");
         shadow_texture
             .as_depth_target()
             .clear(ClearState::default())
@@ -111,8 +104,225 @@ impl DirectionalLight {
                     );
                 }
             });
+        self.context.pop_debug_group();
         self.shadow_texture = Some(shadow_texture);
+        self.shadow_tile = None;
+        self.shadow_matrix = shadow_matrix(&shadow_camera);
+        self.cascades = None;
+    }
+
+    ///
+    /// Generate cascaded shadow maps which are used to simulate shadows from the directional light onto the geometries given as input.
+    /// The view frustum of the given camera is split into the given number of cascades (between 1 and 4) along the view direction
+    /// and a shadow map with the given texture size is fitted to each cascade, so the shadows close to the camera have a higher resolution than the shadows far away.
+    /// This is especially useful for large scenes, for example an outdoor scene lit by the sun, where a single shadow map would be too low resolution.
+    ///
+    /// The lambda parameter, which should be between 0 and 1, specifies how the frustum is split;
+    /// 0 gives cascades of uniform length while 1 gives logarithmically increasing lengths which distributes the resolution more evenly on the screen.
+    /// A value of 0.5-0.9 usually works well.
+    ///
+    /// The cascaded shadow maps depend on the camera, so they should be generated again whenever the camera or the geometries change.
+    ///
+    pub fn generate_cascaded_shadow_maps(
+        &mut self,
+        camera: &Camera,
+        texture_size: u32,
+        cascade_count: u32,
+        lambda: f32,
+        geometries: impl IntoIterator<Item = impl Geometry> + Clone,
+    ) {
+        let cascade_count = cascade_count.clamp(1, MAX_CASCADE_COUNT);
+        let mut aabb = AxisAlignedBoundingBox::EMPTY;
+        for geometry in geometries.clone() {
+            aabb.expand_with_aabb(&geometry.aabb());
+        }
+        let inverse_view_projection = match (camera.projection() * camera.view()).invert() {
+            Some(m) => m,
+            None => return,
+        };
+        if aabb.is_empty() {
+            return;
+        }
+
+        let z_near = camera.z_near();
+        let z_far = camera.z_far();
+        let lambda = lambda.clamp(0.0, 1.0);
+        let splits = (1..=cascade_count)
+            .map(|c| {
+                let t = c as f32 / cascade_count as f32;
+                let logarithmic =
+                    z_near.max(f32::EPSILON) * (z_far / z_near.max(f32::EPSILON)).powf(t);
+                let uniform = z_near + (z_far - z_near) * t;
+                lambda * logarithmic + (1.0 - lambda) * uniform
+            })
+            .collect::<Vec<_>>();
+
+        // The corners of the camera frustum at the near and far plane
+        let corners = [(-1.0, -1.0), (1.0, -1.0), (-1.0, 1.0), (1.0, 1.0)].map(|(x, y)| {
+            let near = inverse_view_projection * vec4(x, y, -1.0, 1.0);
+            let far = inverse_view_projection * vec4(x, y, 1.0, 1.0);
+            (near.truncate() / near.w, far.truncate() / far.w)
+        });
+
+        let direction = self.direction.normalize();
+        let up = compute_up_direction(direction);
+        let right = direction.cross(up).normalize();
+        let mut texture = DepthTexture2DArray::new::<f32>(
+            &self.context,
+            texture_size,
+            texture_size,
+            cascade_count,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+        let depth_material = DepthMaterial {
+            render_states: RenderStates {
+                write_mask: WriteMask::DEPTH,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut matrices = Vec::new();
+        self.context
+            .push_debug_group("Directional light cascaded shadow maps");
+        let mut start = z_near;
+        for (c, end) in splits.iter().enumerate() {
+            let a = (start - z_near) / (z_far - z_near);
+            let b = (end - z_near) / (z_far - z_near);
+            let points = corners
+                .iter()
+                .flat_map(|(near, far)| [*near + (*far - *near) * a, *near + (*far - *near) * b])
+                .collect::<Vec<_>>();
+            start = *end;
+
+            // Fit a sphere to the cascade, so the size of the shadow map is independent of the camera orientation
+            let center =
+                points.iter().fold(vec3(0.0, 0.0, 0.0), |acc, p| acc + p) / points.len() as f32;
+            let radius = points
+                .iter()
+                .map(|p| p.distance(center))
+                .fold(0.0f32, f32::max)
+                .max(f32::EPSILON);
+
+            // Snap the center to the texels of the shadow map to avoid shimmering shadow edges when the camera moves
+            let texel_size = 2.0 * radius / texture_size as f32;
+            let snap = |v: Vec3| (v.dot(center) / texel_size).floor() * texel_size;
+            let center = snap(right) * right + snap(up) * up + center.dot(direction) * direction;
+
+            // Move the shadow camera backwards, so all geometries between the light and the cascade casts shadows
+            let (min, max) = (aabb.min(), aabb.max());
+            let behind = (0..8)
+                .map(|corner| {
+                    vec3(
+                        if corner & 1 == 0 { min.x } else { max.x },
+                        if corner & 2 == 0 { min.y } else { max.y },
+                        if corner & 4 == 0 { min.z } else { max.z },
+                    )
+                })
+                .map(|p| (center - p).dot(direction))
+                .fold(radius, f32::max);
+            let shadow_camera = Camera::new_orthographic(
+                Viewport::new_at_origo(texture_size, texture_size),
+                center - behind * direction,
+                center,
+                up,
+                2.0 * radius,
+                0.0,
+                behind + radius,
+            );
+            texture
+                .as_depth_target(c as u32)
+                .clear(ClearState::default())
+                .write(|| {
+                    for geometry in geometries
+                        .clone()
+                        .into_iter()
+                        .filter(|g| shadow_camera.in_frustum(&g.aabb()))
+                    {
+                        render_with_material(
+                            &self.context,
+                            &shadow_camera,
+                            &geometry,
+                            &depth_material,
+                            &[],
+                        );
+                    }
+                });
+            matrices.push(shadow_matrix(&shadow_camera));
+        }
+        self.context.pop_debug_group();
+        self.shadow_texture = None;
+        self.shadow_tile = None;
+        self.shadow_matrix = Mat4::identity();
+        self.cascades = Some(ShadowCascades {
+            texture,
+            matrices,
+            splits,
+            eye: *camera.position(),
+            view_direction: camera.view_direction(),
+        });
+    }
+
+    ///
+    /// Generate a shadow map like [DirectionalLight::generate_shadow_map] but render it into a tile of the given [ShadowAtlas] instead of a separate texture.
+    /// The importance is used to decide which lights get a tile when there are not enough tiles for all lights, see [ShadowAtlas::allocate].
+    /// Returns whether or not the light got a tile, if not, the light does not cast shadows.
+    ///
+    pub fn generate_shadow_map_in_atlas(
+        &mut self,
+        atlas: &mut ShadowAtlas,
+        importance: f32,
+        geometries: impl IntoIterator<Item = impl Geometry> + Clone,
+    ) -> bool {
+        let tile = atlas.allocate(self.shadow_tile.as_ref(), importance);
+        let shadow_camera = tile
+            .as_ref()
+            .and_then(|tile| self.shadow_camera(tile.viewport(), geometries.clone()));
+        let (tile, shadow_camera) = match (tile, shadow_camera) {
+            (Some(tile), Some(camera)) => (tile, camera),
+            (tile, _) => {
+                if let Some(tile) = tile {
+                    atlas.release(&tile);
+                }
+                self.clear_shadow_map();
+                return false;
+            }
+        };
+        atlas.render(&tile, &shadow_camera, geometries);
+        self.shadow_texture = None;
+        self.shadow_tile = Some(tile);
         self.shadow_matrix = shadow_matrix(&shadow_camera);
+        self.cascades = None;
+        true
+    }
+
+    fn shadow_camera(
+        &self,
+        viewport: Viewport,
+        geometries: impl IntoIterator<Item = impl Geometry>,
+    ) -> Option<Camera> {
+        let up = compute_up_direction(self.direction);
+        let mut aabb = AxisAlignedBoundingBox::EMPTY;
+        for geometry in geometries {
+            aabb.expand_with_aabb(&geometry.aabb());
+        }
+        if aabb.is_empty() {
+            return None;
+        }
+        let target = aabb.center();
+        let position = target - aabb.max().distance(aabb.min()) * self.direction;
+        let z_far = aabb.distance_max(&position);
+        let z_near = aabb.distance(&position);
+        let frustum_height = aabb.max().distance(aabb.min()); // TODO: more tight fit
+        Some(Camera::new_orthographic(
+            viewport,
+            position,
+            target,
+            up,
+            frustum_height,
+            z_near,
+            z_far,
+        ))
     }
 
     ///
@@ -121,57 +331,245 @@ impl DirectionalLight {
     pub fn shadow_map(&self) -> Option<&DepthTexture2D> {
         self.shadow_texture.as_ref()
     }
+
+    ///
+    /// Returns a reference to the cascaded shadow maps, one layer for each cascade, if they have been generated.
+    ///
+    pub fn cascaded_shadow_maps(&self) -> Option<&DepthTexture2DArray> {
+        self.cascades.as_ref().map(|c| &c.texture)
+    }
+}
+
+const MAX_CASCADE_COUNT: u32 = 4;
+
+struct ShadowCascades {
+    texture: DepthTexture2DArray,
+    matrices: Vec<Mat4>,
+    splits: Vec<f32>,
+    eye: Vec3,
+    view_direction: Vec3,
+}
+
+impl ShadowCascades {
+    fn shader_source(&self, i: u32) -> String {
+        let count = self.matrices.len();
+        let mut source = format!(
+            "
+                uniform sampler2DArray cascadeShadowMaps{i};
+                uniform vec4 cascadeSplits{i};
+                uniform vec3 cascadeEye{i};
+                uniform vec3 cascadeViewDirection{i};
+            "
+        );
+        for c in 0..count {
+            source.push_str(&format!("uniform mat4 cascadeMVP{i}_{c};\n"));
+        }
+        let components = ["x", "y", "z", "w"];
+        source.push_str(&format!(
+            "
+                float cascaded_shadow{i}(vec3 lightDirection, vec3 normal, vec3 position)
+                {{
+                    float depth = dot(position - cascadeEye{i}, cascadeViewDirection{i});
+            "
+        ));
+        for c in 0..count {
+            let end = format!("cascadeSplits{i}.{}", components[c]);
+            let shadow = |c: usize| {
+                format!("calculate_shadow_in_layer(lightDirection, normal, cascadeShadowMaps{i}, cascadeMVP{i}_{c}, {c}.0, position)")
+            };
+            source.push_str(&format!(
+                "if (depth < {end}) {{\nfloat shadow = {};\n",
+                shadow(c)
+            ));
+            if c + 1 < count {
+                // Blend with the next cascade at the end of this cascade to hide the seam between the cascades
+                source.push_str(&format!(
+                    "float blend = smoothstep(0.9 * {end}, {end}, depth);
+                    if (blend > 0.0) {{
+                        shadow = mix(shadow, {}, blend);
+                    }}\n",
+                    shadow(c + 1)
+                ));
+            }
+            source.push_str("return shadow;\n}\n");
+        }
+        source.push_str("return 1.0;\n}\n");
+        source
+    }
+
+    fn use_uniforms(&self, program: &Program, i: u32) {
+        program.use_depth_texture_array(&format!("cascadeShadowMaps{}", i), &self.texture);
+        let mut splits = [f32::MAX; 4];
+        splits[..self.splits.len()].copy_from_slice(&self.splits);
+        program.use_uniform(&format!("cascadeSplits{}", i), Vec4::from(splits));
+        program.use_uniform(&format!("cascadeEye{}", i), self.eye);
+        program.use_uniform(&format!("cascadeViewDirection{}", i), self.view_direction);
+        for (c, matrix) in self.matrices.iter().enumerate() {
+            program.use_uniform(&format!("cascadeMVP{}_{}", i, c), *matrix);
+        }
+    }
 }
 
 impl Light for DirectionalLight {
     fn shader_source(&self, i: u32) -> String {
-        if self.shadow_texture.is_some() {
-            format!(
-                "
-                    uniform sampler2D shadowMap{};
-                    uniform mat4 shadowMVP{};
-
-                    uniform vec3 color{};
-                    uniform vec3 direction{};
-
-                    vec3 calculate_lighting{}(vec3 surface_color, vec3 position, vec3 normal, vec3 view_direction, float metallic, float roughness, float occlusion)
-                    {{
-                        return calculate_light(color{}, -direction{}, surface_color, view_direction, normal, metallic, roughness)
-                            * calculate_shadow(-direction{}, normal, shadowMap{}, shadowMVP{}, position);
-                    }}
-
-                ", i, i, i, i, i, i, i, i, i, i)
-        } else {
-            format!(
-                "
-                    uniform vec3 color{};
-                    uniform vec3 direction{};
+        self.shader_source_internal(
+            i,
+            self.shadow_source(),
+            self.cascades.as_ref(),
+            self.contact_shadows.as_ref(),
+        )
+    }
+
+    fn use_uniforms(&self, program: &Program, i: u32) {
+        self.use_uniforms_internal(
+            program,
+            i,
+            self.shadow_source(),
+            self.cascades.as_ref(),
+            self.contact_shadows.as_ref(),
+        )
+    }
 
-                    vec3 calculate_lighting{}(vec3 surface_color, vec3 position, vec3 normal, vec3 view_direction, float metallic, float roughness, float occlusion)
-                    {{
-                        return calculate_light(color{}, -direction{}, surface_color, view_direction, normal, metallic, roughness);
-                    }}
+    fn id(&self) -> u8 {
+        self.id_internal(
+            self.shadow_source(),
+            self.cascades.as_ref(),
+            self.contact_shadows.as_ref(),
+        )
+    }
 
-                ", i, i, i, i, i)
+    fn without_shadows(&self) -> Option<Box<dyn Light + '_>> {
+        if self.shadow_source() != ShadowSource::None
+            || self.cascades.is_some()
+            || self.contact_shadows.is_some()
+        {
+            Some(Box::new(DirectionalLightWithoutShadows(self)))
+        } else {
+            None
         }
     }
-    fn use_uniforms(&self, program: &Program, i: u32) {
-        if let Some(ref tex) = self.shadow_texture {
-            program.use_depth_texture(&format!("shadowMap{}", i), tex);
-            program.use_uniform(&format!("shadowMVP{}", i), self.shadow_matrix);
+}
+
+impl DirectionalLight {
+    fn shadow_source(&self) -> ShadowSource {
+        ShadowSource::new(self.shadow_texture.as_ref(), self.shadow_tile.as_ref())
+    }
+
+    fn shader_source_internal(
+        &self,
+        i: u32,
+        shadows: ShadowSource,
+        cascades: Option<&ShadowCascades>,
+        contact_shadows: Option<&ContactShadows>,
+    ) -> String {
+        let light_direction = format!("-direction{}", i);
+        let shadow_call = match cascades {
+            Some(_) => format!(
+                "cascaded_shadow{}({}, normal, position)",
+                i, light_direction
+            ),
+            None => shadows.shadow_call(i, &light_direction),
+        };
+        let shadow = match contact_shadows {
+            Some(_) => format!(
+                "min({}, {})",
+                shadow_call,
+                ContactShadows::shadow_call(i, &light_direction)
+            ),
+            None => shadow_call,
+        };
+        format!(
+            "
+                {}
+                {}
+                {}
+                uniform vec3 color{};
+                uniform vec3 direction{};
+
+                vec3 calculate_lighting{}(vec3 surface_color, vec3 position, vec3 normal, vec3 view_direction, float metallic, float roughness, float occlusion)
+                {{
+                    return calculate_light(color{}, -direction{}, surface_color, view_direction, normal, metallic, roughness)
+                        * {};
+                }}
+
+            ",
+            shadows.shader_source(i),
+            cascades
+                .map(|cascades| cascades.shader_source(i))
+                .unwrap_or_default(),
+            if contact_shadows.is_some() {
+                ContactShadows::shader_source(i)
+            } else {
+                String::new()
+            },
+            i, i, i, i, i,
+            shadow,
+        )
+    }
+    fn use_uniforms_internal(
+        &self,
+        program: &Program,
+        i: u32,
+        shadows: ShadowSource,
+        cascades: Option<&ShadowCascades>,
+        contact_shadows: Option<&ContactShadows>,
+    ) {
+        shadows.use_uniforms(
+            program,
+            i,
+            self.shadow_texture.as_ref(),
+            self.shadow_tile.as_ref(),
+            self.shadow_matrix,
+        );
+        if let Some(cascades) = cascades {
+            cascades.use_uniforms(program, i);
         }
         program.use_uniform(
             &format!("color{}", i),
             self.color.to_linear_srgb().truncate() * self.intensity,
         );
         program.use_uniform(&format!("direction{}", i), self.direction.normalize());
+        if let Some(contact_shadows) = contact_shadows {
+            contact_shadows.use_uniforms(program, i);
+        }
+    }
+
+    fn id_internal(
+        &self,
+        shadows: ShadowSource,
+        cascades: Option<&ShadowCascades>,
+        contact_shadows: Option<&ContactShadows>,
+    ) -> u8 {
+        let (shadows_bits, cascade_bits) = match cascades {
+            Some(cascades) => (0b11u8, cascades.matrices.len() as u8 - 1),
+            None => match shadows {
+                ShadowSource::None => (0b00u8, 0),
+                ShadowSource::Texture => (0b01u8, 0),
+                ShadowSource::Atlas => (0b10u8, 0),
+            },
+        };
+        0b1u8 << 7
+            | 0b1u8 << 5
+            | cascade_bits << 3
+            | (contact_shadows.is_some() as u8) << 2
+            | shadows_bits
+    }
+}
+
+struct DirectionalLightWithoutShadows<'a>(&'a DirectionalLight);
+
+impl Light for DirectionalLightWithoutShadows<'_> {
+    fn shader_source(&self, i: u32) -> String {
+        self.0
+            .shader_source_internal(i, ShadowSource::None, None, None)
+    }
+
+    fn use_uniforms(&self, program: &Program, i: u32) {
+        self.0
+            .use_uniforms_internal(program, i, ShadowSource::None, None, None)
     }
 
     fn id(&self) -> u8 {
-        if self.shadow_texture.is_some() {
-            0b1u8 << 7 | 0b10u8
-        } else {
-            0b1u8 << 7 | 0b11u8
-        }
+        self.0.id_internal(ShadowSource::None, None, None)
     }
 }
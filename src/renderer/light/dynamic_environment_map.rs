@@ -0,0 +1,152 @@
+use crate::core::*;
+use crate::renderer::*;
+
+///
+/// An environment map which is updated at runtime by rendering the surroundings of a position into a cube map,
+/// which makes it possible for shiny objects, for example a car, to reflect the objects around them.
+///
+/// Each time the environment map is updated, see [DynamicEnvironmentMap::update], the objects are rendered into the 6 sides of a low resolution cube map
+/// and an [AmbientLight] with an [Environment] calculated from the cube map is constructed.
+/// Use that light, see [DynamicEnvironmentMap::light], when rendering the shiny object to make the object reflect its surroundings.
+///
+pub struct DynamicEnvironmentMap {
+    context: Context,
+    target: RenderTargetCubeMap,
+    light: AmbientLight,
+    time_since_update: Option<f64>,
+    /// The position which the surroundings are rendered from, typically the center of the shiny object.
+    pub position: Vec3,
+    /// The minimum time in milliseconds between two updates of the environment map. Use 0 to update each time [DynamicEnvironmentMap::update] is called.
+    pub update_interval: f64,
+    /// The distance to the near plane of the cameras used for rendering the surroundings. Should be larger than the radius of the shiny object so it does not occlude its surroundings.
+    pub z_near: f32,
+    /// The distance to the far plane of the cameras used for rendering the surroundings.
+    pub z_far: f32,
+    /// The intensity of the ambient light constructed from the environment map.
+    pub intensity: f32,
+}
+
+impl DynamicEnvironmentMap {
+    ///
+    /// Creates a new dynamic environment map with the given size in texels of each side of the cube map, placed at the given position.
+    /// The environment map is empty (black) until the first call to [DynamicEnvironmentMap::update].
+    ///
+    pub fn new(context: &Context, size: u32, position: Vec3) -> Self {
+        Self {
+            context: context.clone(),
+            light: AmbientLight::new(context, 1.0, Srgba::WHITE),
+            target: RenderTargetCubeMap::new::<[f16; 4]>(
+                context,
+                size,
+                Some(Interpolation::Linear),
+            ),
+            time_since_update: None,
+            position,
+            update_interval: 100.0,
+            z_near: 0.1,
+            z_far: 100.0,
+            intensity: 1.0,
+        }
+    }
+
+    ///
+    /// Updates the environment map by rendering the given objects with the given lights, if the [DynamicEnvironmentMap::update_interval] has passed since the last update
+    /// or if this is the first update. The elapsed time in milliseconds since the last call, for example the elapsed time given in the frame input, is used to keep track of the time.
+    /// The shiny object itself should not be included in the given objects.
+    /// Returns whether or not the environment map was updated.
+    ///
+    pub fn update(
+        &mut self,
+        elapsed_time: f64,
+        objects: &[&dyn Object],
+        lights: &[&dyn Light],
+    ) -> bool {
+        let time_since_update = self.time_since_update.map(|t| t + elapsed_time);
+        match time_since_update {
+            Some(t) if t < self.update_interval => {
+                self.time_since_update = Some(t);
+                false
+            }
+            _ => {
+                self.force_update(objects, lights);
+                true
+            }
+        }
+    }
+
+    ///
+    /// Updates the environment map by rendering the given objects with the given lights, regardless of the time since the last update.
+    /// The shiny object itself should not be included in the given objects.
+    ///
+    /// This is the same as calling [DynamicEnvironmentMap::render_sides] with all sides followed by [DynamicEnvironmentMap::bake].
+    ///
+    pub fn force_update(&mut self, objects: &[&dyn Object], lights: &[&dyn Light]) {
+        self.render_sides(&CubeMapSide::iter().collect::<Vec<_>>(), objects, lights);
+        self.bake();
+    }
+
+    ///
+    /// Renders the given objects with the given lights into the given sides of the cube map and generates the mip maps of the cube map,
+    /// without updating the light, see [DynamicEnvironmentMap::bake].
+    /// This can be used to spread the cost of an update over several frames by rendering a few sides each frame and only baking when all sides have been rendered.
+    /// The shiny object itself should not be included in the given objects.
+    ///
+    pub fn render_sides(
+        &mut self,
+        sides: &[CubeMapSide],
+        objects: &[&dyn Object],
+        lights: &[&dyn Light],
+    ) {
+        let size = self.target.size();
+        let viewport = Viewport::new_at_origo(size, size);
+        for side in sides.iter().copied() {
+            let mut camera = Camera::new_perspective(
+                viewport,
+                self.position,
+                self.position + side.direction(),
+                side.up(),
+                degrees(90.0),
+                self.z_near,
+                self.z_far,
+            );
+            camera.disable_tone_and_color_mapping();
+            // Render into the zero mip level explicitly, so the mip maps are only generated once when all sides are rendered
+            self.target
+                .as_render_target(side, Some(0))
+                .clear(ClearState::color_and_depth(0.0, 0.0, 0.0, 1.0, 1.0))
+                .render(&camera, objects, lights);
+        }
+        if !sides.is_empty() {
+            self.target.generate_mip_maps();
+        }
+    }
+
+    ///
+    /// Updates the light, see [DynamicEnvironmentMap::light], by calculating the irradiance and prefiltered maps from the current content of the cube map,
+    /// without rendering the surroundings again, for example after changing the [DynamicEnvironmentMap::intensity]
+    /// or after rendering some of the sides using [DynamicEnvironmentMap::render_sides].
+    ///
+    pub fn bake(&mut self) {
+        self.light = AmbientLight::new_with_environment(
+            &self.context,
+            self.intensity,
+            Srgba::WHITE,
+            self.target.texture(),
+        );
+        self.time_since_update = Some(0.0);
+    }
+
+    ///
+    /// Returns the cube map containing the rendered surroundings.
+    ///
+    pub fn texture(&self) -> &TextureCubeMap {
+        self.target.texture()
+    }
+
+    ///
+    /// Returns the ambient light calculated from the environment map, which should be used when rendering the shiny object.
+    ///
+    pub fn light(&self) -> &AmbientLight {
+        &self.light
+    }
+}
@@ -11,6 +11,8 @@ pub struct Environment {
     /// A cube map used to calculate the specular contribution from the environment.
     /// Each mip-map level contain the prefiltered color for a certain surface roughness.
     pub prefilter_map: TextureCubeMap,
+    /// The mip level of the prefiltered map which contain the prefiltered color for a surface roughness of 1, ie. the number of prefiltered mip levels minus one.
+    pub prefilter_max_lod: f32,
     /// A 2D texture that contain the BRDF lookup tables (LUT).
     pub brdf_map: Texture2D,
 }
@@ -39,73 +41,16 @@ impl Environment {
         environment_map: &TextureCubeMap,
         lighting_model: LightingModel,
     ) -> Self {
-        // Diffuse
-        let irradiance_size = 32;
-        let mut irradiance_map = TextureCubeMap::new_empty::<[f16; 4]>(
-            context,
-            irradiance_size,
-            irradiance_size,
-            Interpolation::Linear,
-            Interpolation::Linear,
-            Some(Interpolation::Linear),
-            Wrapping::ClampToEdge,
-            Wrapping::ClampToEdge,
-            Wrapping::ClampToEdge,
-        );
-        {
-            let viewport = Viewport::new_at_origo(irradiance_size, irradiance_size);
-            for side in CubeMapSide::iter() {
-                irradiance_map
-                    .as_color_target(&[side], None)
-                    .clear(ClearState::default())
-                    .apply_screen_material(
-                        &IrradianceMaterial {
-                            environment_map,
-                            side,
-                        },
-                        &Camera::new_2d(viewport),
-                        &[],
-                    );
-            }
-        }
-
-        // Prefilter
+        let irradiance_map = TextureCubeMap::new_irradiance_map(context, environment_map, 32);
         let prefilter_size = 128;
-        let mut prefilter_map = TextureCubeMap::new_empty::<[f16; 4]>(
+        let prefilter_mip_levels = 5;
+        let prefilter_map = TextureCubeMap::new_prefilter_map(
             context,
+            environment_map,
             prefilter_size,
-            prefilter_size,
-            Interpolation::Linear,
-            Interpolation::Linear,
-            Some(Interpolation::Linear),
-            Wrapping::ClampToEdge,
-            Wrapping::ClampToEdge,
-            Wrapping::ClampToEdge,
+            prefilter_mip_levels,
+            lighting_model,
         );
-        {
-            let max_mip_levels = 5;
-            for mip in 0..max_mip_levels {
-                for side in CubeMapSide::iter() {
-                    let sides = [side];
-                    let color_target = prefilter_map.as_color_target(&sides, Some(mip));
-                    let viewport =
-                        Viewport::new_at_origo(color_target.width(), color_target.height());
-                    color_target
-                        .clear(ClearState::default())
-                        .apply_screen_material(
-                            &PrefilterMaterial {
-                                lighting_model,
-                                environment_map,
-                                side,
-                                mip,
-                                max_mip_levels,
-                            },
-                            &Camera::new_2d(viewport),
-                            &[],
-                        );
-                }
-            }
-        }
 
         // BRDF
         let mut brdf_map = Texture2D::new_empty::<[f32; 2]>(
@@ -131,11 +76,112 @@ impl Environment {
         Self {
             irradiance_map,
             prefilter_map,
+            prefilter_max_lod: (prefilter_mip_levels_for_size(prefilter_size, prefilter_mip_levels)
+                - 1) as f32,
             brdf_map,
         }
     }
 }
 
+impl TextureCubeMap {
+    ///
+    /// Computes the irradiance map, ie. the diffuse contribution from the environment, by convolving the given environment map
+    /// and returns it as a new cube map with the given size. The result is the same as [Environment::irradiance_map].
+    ///
+    /// Can be used to bake the irradiance map for a light probe offline, for example by reading the sides using [TextureCubeMap::as_color_target]
+    /// and saving them to disk, to avoid the convolution when loading the probe.
+    ///
+    pub fn new_irradiance_map(
+        context: &Context,
+        environment_map: &TextureCubeMap,
+        size: u32,
+    ) -> Self {
+        let mut irradiance_map = TextureCubeMap::new_empty::<[f16; 4]>(
+            context,
+            size,
+            size,
+            Interpolation::Linear,
+            Interpolation::Linear,
+            Some(Interpolation::Linear),
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+        let viewport = Viewport::new_at_origo(size, size);
+        for side in CubeMapSide::iter() {
+            irradiance_map
+                .as_color_target(&[side], None)
+                .clear(ClearState::default())
+                .apply_screen_material(
+                    &IrradianceMaterial {
+                        environment_map,
+                        side,
+                    },
+                    &Camera::new_2d(viewport),
+                    &[],
+                );
+        }
+        irradiance_map
+    }
+
+    ///
+    /// Computes the prefiltered map, ie. the specular contribution from the environment, by prefiltering the given environment map using the given lighting model
+    /// and returns it as a new cube map with the given size. Each of the given number of mip levels contain the prefiltered color for a certain surface roughness,
+    /// starting with a roughness of 0 at mip level 0 and ending with a roughness of 1 at the last mip level.
+    /// The number of mip levels is clamped to the number of mip levels of a texture with the given size, ie. `log2(size) + 1`,
+    /// and the last mip level should be used as [Environment::prefilter_max_lod] when the result is used in an [Environment].
+    /// The result is the same as [Environment::prefilter_map] when using a size of 128 and 5 mip levels.
+    ///
+    /// Can be used to bake the prefiltered map for a light probe offline, for example by reading each side and mip level using [TextureCubeMap::as_color_target]
+    /// and saving them to disk, to avoid the prefiltering when loading the probe.
+    ///
+    pub fn new_prefilter_map(
+        context: &Context,
+        environment_map: &TextureCubeMap,
+        size: u32,
+        mip_levels: u32,
+        lighting_model: LightingModel,
+    ) -> Self {
+        let mut prefilter_map = TextureCubeMap::new_empty::<[f16; 4]>(
+            context,
+            size,
+            size,
+            Interpolation::Linear,
+            Interpolation::Linear,
+            Some(Interpolation::Linear),
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+        let max_mip_levels = prefilter_mip_levels_for_size(size, mip_levels);
+        for mip in 0..max_mip_levels {
+            for side in CubeMapSide::iter() {
+                let sides = [side];
+                let color_target = prefilter_map.as_color_target(&sides, Some(mip));
+                let viewport = Viewport::new_at_origo(color_target.width(), color_target.height());
+                color_target
+                    .clear(ClearState::default())
+                    .apply_screen_material(
+                        &PrefilterMaterial {
+                            lighting_model,
+                            environment_map,
+                            side,
+                            mip,
+                            max_mip_levels,
+                        },
+                        &Camera::new_2d(viewport),
+                        &[],
+                    );
+            }
+        }
+        prefilter_map
+    }
+}
+
+fn prefilter_mip_levels_for_size(size: u32, mip_levels: u32) -> u32 {
+    mip_levels.clamp(1, 32 - size.max(1).leading_zeros())
+}
+
 struct PrefilterMaterial<'a> {
     lighting_model: LightingModel,
     environment_map: &'a TextureCubeMap,
@@ -168,10 +214,12 @@ impl Material for PrefilterMaterial<'_> {
 
     fn use_uniforms(&self, program: &Program, _camera: &Camera, _lights: &[&dyn Light]) {
         program.use_texture_cube("environmentMap", self.environment_map);
-        program.use_uniform(
-            "roughness",
-            self.mip as f32 / (self.max_mip_levels as f32 - 1.0),
-        );
+        let roughness = if self.max_mip_levels > 1 {
+            self.mip as f32 / (self.max_mip_levels as f32 - 1.0)
+        } else {
+            0.0
+        };
+        program.use_uniform("roughness", roughness);
         program.use_uniform("resolution", self.environment_map.width() as f32);
         program.use_uniform("direction", self.side.direction());
         program.use_uniform("up", self.side.up());
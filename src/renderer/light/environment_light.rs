@@ -0,0 +1,130 @@
+use crate::core::*;
+use crate::renderer::*;
+
+///
+/// A light shining from an environment map, known as image based lighting (IBL), which gives realistic diffuse and specular ambient light,
+/// for example from an HDR photo of a studio or an outdoor scene.
+/// The environment map is prefiltered on the GPU into an irradiance map for the diffuse light and a prefiltered map with a mip level per roughness for the specular light,
+/// together with a BRDF lookup table, see [Environment].
+///
+/// Use the same environment map for a [Skybox] with the same rotation to make the background match the lighting.
+///
+pub struct EnvironmentLight {
+    /// The intensity which the light from the environment is multiplied with.
+    pub intensity: f32,
+    /// The rotation of the environment around the vertical (y) axis, which should match the rotation of a [Skybox] using the same environment map, see [Skybox::set_rotation].
+    pub rotation: Radians,
+    /// The precalculated light shining from the environment.
+    pub environment: Environment,
+}
+
+impl EnvironmentLight {
+    ///
+    /// Constructs a light shining from the given environment map.
+    ///
+    pub fn new(context: &Context, intensity: f32, environment_map: &TextureCubeMap) -> Self {
+        Self {
+            intensity,
+            rotation: radians(0.0),
+            environment: Environment::new(context, environment_map),
+        }
+    }
+
+    ///
+    /// Constructs a light shining from the environment in the given equirectangular texture,
+    /// for example an HDR image loaded using [load_hdr].
+    ///
+    pub fn new_from_equirectangular(
+        context: &Context,
+        intensity: f32,
+        cpu_texture: &CpuTexture,
+    ) -> Self {
+        Self::new(
+            context,
+            intensity,
+            &environment_map_from_equirectangular(context, cpu_texture),
+        )
+    }
+}
+
+impl Light for EnvironmentLight {
+    fn shader_source(&self, i: u32) -> String {
+        format!(
+            "
+                uniform samplerCube irradianceMap{i};
+                uniform samplerCube prefilterMap{i};
+                uniform sampler2D brdfLUT{i};
+                uniform float prefilterMaxLod{i};
+                uniform vec2 environmentRotation{i};
+                uniform float environmentIntensity{i};
+
+                vec3 calculate_lighting{i}(vec3 surface_color, vec3 position, vec3 normal, vec3 view_direction, float metallic, float roughness, float occlusion)
+                {{
+                    float c = environmentRotation{i}.x;
+                    float s = environmentRotation{i}.y;
+                    vec3 N = normal;
+                    vec3 V = view_direction;
+                    vec3 R = reflect(-V, N);
+                    float NdV = max(0.001, dot(N, V));
+                    vec3 rotated_normal = vec3(c * N.x + s * N.z, N.y, c * N.z - s * N.x);
+                    vec3 rotated_reflection = vec3(c * R.x + s * R.z, R.y, c * R.z - s * R.x);
+
+                    vec3 F0 = mix(vec3(0.04), surface_color, metallic);
+                    vec3 specular_fresnel = fresnel_schlick_roughness(F0, NdV, roughness);
+                    vec3 diffuse_fresnel = 1.0 - specular_fresnel;
+
+                    vec3 irradiance = texture(irradianceMap{i}, rotated_normal).rgb;
+                    vec3 diffuse = diffuse_fresnel * mix(surface_color, vec3(0.0), metallic) * irradiance;
+
+                    vec3 prefilteredColor = textureLod(prefilterMap{i}, rotated_reflection, roughness * prefilterMaxLod{i}).rgb;
+                    vec2 brdf = texture(brdfLUT{i}, vec2(NdV, roughness)).rg;
+                    vec3 specular = prefilteredColor * (specular_fresnel * brdf.x + brdf.y);
+
+                    return (diffuse + specular) * occlusion * environmentIntensity{i};
+                }}
+            "
+        )
+    }
+
+    fn use_uniforms(&self, program: &Program, i: u32) {
+        program.use_texture_cube(
+            &format!("irradianceMap{}", i),
+            &self.environment.irradiance_map,
+        );
+        program.use_texture_cube(
+            &format!("prefilterMap{}", i),
+            &self.environment.prefilter_map,
+        );
+        program.use_texture(&format!("brdfLUT{}", i), &self.environment.brdf_map);
+        program.use_uniform(
+            &format!("prefilterMaxLod{}", i),
+            self.environment.prefilter_max_lod,
+        );
+        program.use_uniform(
+            &format!("environmentRotation{}", i),
+            vec2(self.rotation.0.cos(), self.rotation.0.sin()),
+        );
+        program.use_uniform(&format!("environmentIntensity{}", i), self.intensity);
+    }
+
+    fn id(&self) -> u8 {
+        0b1u8 << 7 | 0b10u8
+    }
+
+    fn is_indirect(&self) -> bool {
+        true
+    }
+}
+
+///
+/// Loads an equirectangular high dynamic range image from a `.hdr` file, which can be used to construct an [EnvironmentLight] and a [Skybox].
+/// On web, the path is an url which is fetched using http.
+///
+#[cfg(feature = "hdr")]
+#[cfg_attr(docsrs, doc(feature = "hdr"))]
+pub async fn load_hdr(path: impl AsRef<std::path::Path>) -> Result<CpuTexture, RendererError> {
+    let path = path.as_ref();
+    Ok(three_d_asset::io::load_async(&[path])
+        .await?
+        .deserialize(path)?)
+}
@@ -0,0 +1,251 @@
+use crate::core::*;
+use crate::renderer::*;
+
+///
+/// Animates the intensity, color and/or position of a light, for example a flickering candle, a pulsing alarm or a light moving along a path.
+///
+/// Each animated property is given by a function which takes the time in seconds as input and returns the value of the property at that time.
+/// Use one of the provided functions, for example [flicker], [pulse], [blink] or [smooth_path], or a custom function.
+/// Call [LightAnimation::update] each frame with the elapsed time and then [LightAnimation::apply] to update the light.
+///
+#[derive(Default)]
+pub struct LightAnimation {
+    time: f32,
+    intensity: Option<Box<dyn Fn(f32) -> f32 + Send + Sync>>,
+    color: Option<Box<dyn Fn(f32) -> Srgba + Send + Sync>>,
+    position: Option<Box<dyn Fn(f32) -> Vec3 + Send + Sync>>,
+}
+
+impl LightAnimation {
+    ///
+    /// Constructs a new light animation which does not animate any properties.
+    ///
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///
+    /// Specifies a function which takes the time in seconds as input and returns the intensity of the light at that time.
+    ///
+    pub fn set_intensity_animation(
+        &mut self,
+        animation: impl Fn(f32) -> f32 + Send + Sync + 'static,
+    ) {
+        self.intensity = Some(Box::new(animation));
+    }
+
+    ///
+    /// Specifies a function which takes the time in seconds as input and returns the color of the light at that time.
+    ///
+    pub fn set_color_animation(
+        &mut self,
+        animation: impl Fn(f32) -> Srgba + Send + Sync + 'static,
+    ) {
+        self.color = Some(Box::new(animation));
+    }
+
+    ///
+    /// Specifies a function which takes the time in seconds as input and returns the position of the light at that time.
+    /// The position is not applied to lights without a position, for example a [DirectionalLight].
+    ///
+    pub fn set_position_animation(
+        &mut self,
+        animation: impl Fn(f32) -> Vec3 + Send + Sync + 'static,
+    ) {
+        self.position = Some(Box::new(animation));
+    }
+
+    ///
+    /// Advances the animation by the given elapsed time in milliseconds, for example the elapsed time given in the frame input.
+    ///
+    pub fn update(&mut self, elapsed_time: f64) {
+        self.time += 0.001 * elapsed_time as f32;
+    }
+
+    ///
+    /// Returns the current time of the animation in seconds.
+    ///
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    ///
+    /// Sets the current time of the animation in seconds.
+    ///
+    pub fn set_time(&mut self, time: f32) {
+        self.time = time;
+    }
+
+    ///
+    /// Applies the animated properties at the current time to the given light.
+    ///
+    pub fn apply(&self, light: &mut impl AnimatableLight) {
+        if let Some(intensity) = &self.intensity {
+            light.set_intensity(intensity(self.time));
+        }
+        if let Some(color) = &self.color {
+            light.set_color(color(self.time));
+        }
+        if let Some(position) = &self.position {
+            light.set_position(position(self.time));
+        }
+    }
+}
+
+///
+/// A light which properties can be animated by a [LightAnimation].
+///
+pub trait AnimatableLight {
+    /// Sets the intensity of the light.
+    fn set_intensity(&mut self, intensity: f32);
+    /// Sets the color of the light.
+    fn set_color(&mut self, color: Srgba);
+    /// Sets the position of the light, if the light has a position.
+    fn set_position(&mut self, _position: Vec3) {}
+}
+
+impl AnimatableLight for AmbientLight {
+    fn set_intensity(&mut self, intensity: f32) {
+        self.intensity = intensity;
+    }
+    fn set_color(&mut self, color: Srgba) {
+        self.color = color;
+    }
+}
+
+impl AnimatableLight for DirectionalLight {
+    fn set_intensity(&mut self, intensity: f32) {
+        self.intensity = intensity;
+    }
+    fn set_color(&mut self, color: Srgba) {
+        self.color = color;
+    }
+}
+
+impl AnimatableLight for PointLight {
+    fn set_intensity(&mut self, intensity: f32) {
+        self.intensity = intensity;
+    }
+    fn set_color(&mut self, color: Srgba) {
+        self.color = color;
+    }
+    fn set_position(&mut self, position: Vec3) {
+        self.position = position;
+    }
+}
+
+impl AnimatableLight for SpotLight {
+    fn set_intensity(&mut self, intensity: f32) {
+        self.intensity = intensity;
+    }
+    fn set_color(&mut self, color: Srgba) {
+        self.color = color;
+    }
+    fn set_position(&mut self, position: Vec3) {
+        self.position = position;
+    }
+}
+
+///
+/// Returns a function which varies randomly but smoothly around the given base value, for example to simulate the intensity of a candle or a fire.
+/// The amount is the maximum deviation relative to the base value and the speed is the number of changes per second.
+///
+pub fn flicker(base: f32, amount: f32, speed: f32) -> impl Fn(f32) -> f32 + Send + Sync + 'static {
+    move |time| {
+        let t = time * speed;
+        let noise = 0.5 * value_noise(t)
+            + 0.3 * value_noise(2.0 * t + 17.0)
+            + 0.2 * value_noise(4.0 * t + 31.0);
+        base * (1.0 + amount * (2.0 * noise - 1.0))
+    }
+}
+
+///
+/// Returns a function which varies smoothly between the minimum and maximum value with the given period in seconds, for example to simulate a pulsing light.
+///
+pub fn pulse(min: f32, max: f32, period: f32) -> impl Fn(f32) -> f32 + Send + Sync + 'static {
+    move |time| {
+        let t = 0.5 - 0.5 * (2.0 * std::f32::consts::PI * time / period).cos();
+        min + (max - min) * t
+    }
+}
+
+///
+/// Returns a function which switches between the maximum and minimum value with the given period in seconds, for example to simulate an alarm light.
+/// The duty cycle is the fraction of the period where the value is the maximum value.
+///
+pub fn blink(
+    min: f32,
+    max: f32,
+    period: f32,
+    duty_cycle: f32,
+) -> impl Fn(f32) -> f32 + Send + Sync + 'static {
+    move |time| {
+        if (time / period).rem_euclid(1.0) < duty_cycle {
+            max
+        } else {
+            min
+        }
+    }
+}
+
+///
+/// Returns a function which moves smoothly through the given points, spending the given duration in seconds between two consecutive points.
+/// If `looping` is true, the path continues from the last point to the first point and starts over, otherwise it stops at the last point.
+///
+pub fn smooth_path(
+    points: Vec<Vec3>,
+    duration: f32,
+    looping: bool,
+) -> impl Fn(f32) -> Vec3 + Send + Sync + 'static {
+    move |time| {
+        let count = points.len();
+        if count < 2 {
+            return points.first().copied().unwrap_or(vec3(0.0, 0.0, 0.0));
+        }
+        let t = time / duration;
+        let (index, t) = if looping {
+            let t = t.rem_euclid(count as f32);
+            (t.floor() as usize, t.fract())
+        } else if t >= (count - 1) as f32 {
+            return points[count - 1];
+        } else {
+            let t = t.max(0.0);
+            (t.floor() as usize, t.fract())
+        };
+        let point = |i: isize| {
+            if looping {
+                points[i.rem_euclid(count as isize) as usize]
+            } else {
+                points[i.clamp(0, count as isize - 1) as usize]
+            }
+        };
+        let i = index as isize;
+        catmull_rom(point(i - 1), point(i), point(i + 1), point(i + 2), t)
+    }
+}
+
+fn catmull_rom(p0: Vec3, p1: Vec3, p2: Vec3, p3: Vec3, t: f32) -> Vec3 {
+    let t2 = t * t;
+    let t3 = t2 * t;
+    0.5 * (2.0 * p1
+        + (p2 - p0) * t
+        + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t2
+        + (3.0 * p1 - p0 - 3.0 * p2 + p3) * t3)
+}
+
+fn value_noise(t: f32) -> f32 {
+    let hash = |i: i32| {
+        let mut x = (i as u32).wrapping_mul(0x27d4_eb2d);
+        x ^= x >> 15;
+        x = x.wrapping_mul(0x85eb_ca6b);
+        x ^= x >> 13;
+        (x & 0xffff) as f32 / 65535.0
+    };
+    let i = t.floor();
+    let f = t - i;
+    let f = f * f * (3.0 - 2.0 * f);
+    let a = hash(i as i32);
+    let b = hash(i as i32 + 1);
+    a + (b - a) * f
+}
@@ -0,0 +1,256 @@
+use crate::core::*;
+use crate::renderer::*;
+use std::sync::Arc;
+
+///
+/// The photometric data of a real world luminaire, ie. how the luminous intensity in candela varies with the direction, parsed from an IES file (IESNA LM-63).
+/// Use [LightProfile::new] to upload the profile to the GPU and apply it to a [PointLight] or [SpotLight].
+///
+/// The vertical angles are measured in degrees from the main direction of the luminaire (typically pointing down) and the horizontal angles are measured in degrees around the main direction.
+///
+#[derive(Clone, Debug)]
+pub struct IesProfile {
+    /// The vertical angles in degrees in increasing order.
+    pub vertical_angles: Vec<f32>,
+    /// The horizontal angles in degrees in increasing order.
+    pub horizontal_angles: Vec<f32>,
+    /// The luminous intensity in candela for each horizontal angle and then for each vertical angle, ie. `candela[h][v]`.
+    pub candela: Vec<Vec<f32>>,
+}
+
+impl IesProfile {
+    ///
+    /// Parses the content of an IES file.
+    ///
+    pub fn from_ies(text: &str) -> Result<Self, RendererError> {
+        let error = |message: &str| RendererError::InvalidIesFile(message.to_string());
+        let mut lines = text.lines();
+        let tilt = lines
+            .by_ref()
+            .map(|line| line.trim())
+            .find(|line| line.starts_with("TILT"))
+            .ok_or_else(|| error("missing TILT keyword"))?;
+        let mut tokens = lines
+            .flat_map(|line| line.split(|c: char| c.is_whitespace() || c == ','))
+            .filter(|token| !token.is_empty())
+            .map(|token| {
+                token
+                    .parse::<f32>()
+                    .map_err(|_| RendererError::InvalidIesFile(format!("invalid number {}", token)))
+            });
+        let mut next = || {
+            tokens
+                .next()
+                .unwrap_or_else(|| Err(error("unexpected end of file")))
+        };
+
+        if tilt
+            .trim_start_matches("TILT")
+            .trim_start_matches('=')
+            .trim()
+            == "INCLUDE"
+        {
+            // Lamp to luminaire geometry followed by the tilt angles and multiplying factors, which are not used
+            next()?;
+            let tilt_count = next()? as usize;
+            for _ in 0..2 * tilt_count {
+                next()?;
+            }
+        }
+
+        let _lamp_count = next()?;
+        let _lumens_per_lamp = next()?;
+        let multiplier = next()?;
+        let vertical_count = next()? as usize;
+        let horizontal_count = next()? as usize;
+        let _photometric_type = next()?;
+        let _units_type = next()?;
+        let _width = next()?;
+        let _length = next()?;
+        let _height = next()?;
+        let ballast_factor = next()?;
+        let _future_use = next()?;
+        let _input_watts = next()?;
+        if vertical_count == 0 || horizontal_count == 0 {
+            Err(error("no angles"))?;
+        }
+
+        let vertical_angles = (0..vertical_count)
+            .map(|_| next())
+            .collect::<Result<Vec<_>, _>>()?;
+        let horizontal_angles = (0..horizontal_count)
+            .map(|_| next())
+            .collect::<Result<Vec<_>, _>>()?;
+        let candela = (0..horizontal_count)
+            .map(|_| {
+                (0..vertical_count)
+                    .map(|_| next().map(|c| c * multiplier * ballast_factor))
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self {
+            vertical_angles,
+            horizontal_angles,
+            candela,
+        })
+    }
+
+    ///
+    /// Returns the maximum luminous intensity in candela.
+    ///
+    pub fn max_candela(&self) -> f32 {
+        self.candela
+            .iter()
+            .flatten()
+            .fold(0.0f32, |max, &c| max.max(c))
+    }
+
+    ///
+    /// Returns the luminous intensity in candela in the direction given by the vertical and horizontal angle in degrees.
+    /// The luminous intensity is linearly interpolated between the measured angles and the symmetry of the measurements is taken into account,
+    /// for example if only the horizontal angles between 0 and 90 degrees are measured, the luminous intensity is mirrored into the other quadrants.
+    ///
+    pub fn candela_at(&self, vertical_angle: f32, horizontal_angle: f32) -> f32 {
+        let last_horizontal = *self.horizontal_angles.last().unwrap();
+        let mut h = horizontal_angle.rem_euclid(360.0);
+        if last_horizontal <= 90.0 {
+            h %= 180.0;
+            if h > 90.0 {
+                h = 180.0 - h;
+            }
+        } else if last_horizontal <= 180.0 && h > 180.0 {
+            h = 360.0 - h;
+        }
+        if vertical_angle < self.vertical_angles[0]
+            || vertical_angle > *self.vertical_angles.last().unwrap()
+        {
+            return 0.0;
+        }
+        let (h0, h1, ht) = interpolation_indices(&self.horizontal_angles, h);
+        let (v0, v1, vt) = interpolation_indices(&self.vertical_angles, vertical_angle);
+        let c0 = self.candela[h0][v0] * (1.0 - vt) + self.candela[h0][v1] * vt;
+        let c1 = self.candela[h1][v0] * (1.0 - vt) + self.candela[h1][v1] * vt;
+        c0 * (1.0 - ht) + c1 * ht
+    }
+}
+
+fn interpolation_indices(angles: &[f32], angle: f32) -> (usize, usize, f32) {
+    match angles.iter().position(|a| *a > angle) {
+        Some(0) => (0, 0, 0.0),
+        Some(i) => {
+            let t = (angle - angles[i - 1]) / (angles[i] - angles[i - 1]);
+            (i - 1, i, t)
+        }
+        None => (angles.len() - 1, angles.len() - 1, 0.0),
+    }
+}
+
+///
+/// An [IesProfile] uploaded to the GPU as a lookup texture, which can be applied to a [PointLight] or a [SpotLight]
+/// to make the intensity of the light vary with the direction like a real world luminaire.
+///
+/// The lookup texture contains the luminous intensity relative to the maximum luminous intensity,
+/// so the intensity of the light, for example [PointLight::intensity], is the intensity in the brightest direction.
+/// Use [LightProfile::max_candela] together with [Exposure::intensity_from_candela] to get the physically correct intensity.
+///
+#[derive(Clone)]
+pub struct LightProfile {
+    texture: Arc<Texture2D>,
+    max_candela: f32,
+}
+
+impl LightProfile {
+    ///
+    /// Constructs a new light profile from the given IES profile.
+    ///
+    pub fn new(context: &Context, profile: &IesProfile) -> Self {
+        let max_candela = profile.max_candela();
+        let width = if profile.horizontal_angles.len() > 1 {
+            128
+        } else {
+            1
+        };
+        let height = 128;
+        let mut data = Vec::with_capacity((width * height) as usize);
+        // The first row of the texture data is the top of the texture, ie. the largest vertical angle
+        for y in 0..height {
+            let vertical_angle = 180.0 * (height - 1 - y) as f32 / (height - 1) as f32;
+            for x in 0..width {
+                let horizontal_angle = 360.0 * x as f32 / width as f32;
+                let candela = profile.candela_at(vertical_angle, horizontal_angle);
+                data.push(f16::from_f32(if max_candela > 0.0 {
+                    candela / max_candela
+                } else {
+                    0.0
+                }));
+            }
+        }
+        let mut texture = Texture2D::new_empty::<f16>(
+            context,
+            width,
+            height,
+            Interpolation::Linear,
+            Interpolation::Linear,
+            None,
+            Wrapping::Repeat,
+            Wrapping::ClampToEdge,
+        );
+        texture.fill(&data);
+        Self {
+            texture: Arc::new(texture),
+            max_candela,
+        }
+    }
+
+    ///
+    /// Constructs a new light profile from the content of an IES file.
+    ///
+    pub fn from_ies(context: &Context, text: &str) -> Result<Self, RendererError> {
+        Ok(Self::new(context, &IesProfile::from_ies(text)?))
+    }
+
+    ///
+    /// Returns the maximum luminous intensity in candela of the profile.
+    ///
+    pub fn max_candela(&self) -> f32 {
+        self.max_candela
+    }
+
+    ///
+    /// Returns the lookup texture where the u coordinate is the horizontal angle divided by 360 degrees
+    /// and the v coordinate is the vertical angle divided by 180 degrees.
+    ///
+    pub fn texture(&self) -> &Texture2D {
+        &self.texture
+    }
+
+    pub(super) fn shader_source(i: u32) -> String {
+        format!(
+            "
+            uniform sampler2D profileTexture{};
+            uniform mat3 profileRotation{};
+            float profile_intensity{}(vec3 direction) {{
+                vec3 d = profileRotation{} * direction;
+                float vertical = acos(clamp(d.z, -1.0, 1.0)) / 3.14159265;
+                float horizontal = fract(atan(d.y, d.x) / 6.28318531);
+                return texture(profileTexture{}, vec2(horizontal, vertical)).r;
+            }}
+            ",
+            i, i, i, i, i
+        )
+    }
+
+    ///
+    /// The rotation is from world space into the space of the profile where the main direction is the z-axis and the horizontal angle 0 is along the x-axis.
+    ///
+    pub(super) fn use_uniforms(&self, program: &Program, i: u32, main_direction: Vec3, x: Vec3) {
+        let z = main_direction.normalize();
+        let y = z.cross(x).normalize();
+        let x = y.cross(z);
+        program.use_texture(&format!("profileTexture{}", i), &self.texture);
+        program.use_uniform(
+            &format!("profileRotation{}", i),
+            Mat3::from_cols(x, y, z).transpose(),
+        );
+    }
+}
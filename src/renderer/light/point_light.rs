@@ -1,10 +1,15 @@
 use crate::core::*;
+use crate::renderer::light::*;
 use crate::renderer::*;
 
 ///
 /// A light which shines from the given position in all directions.
+/// The light will cast shadows if you [generate a shadow map](PointLight::generate_shadow_map).
 ///
 pub struct PointLight {
+    context: Context,
+    shadow_texture: Option<DepthTextureCubeMap>,
+    shadow_depth_range: Vec2,
     /// The intensity of the light. This allows for higher intensity than 1 which can be used to simulate high intensity light sources like the sun.
     pub intensity: f32,
     /// The base color of the light.
@@ -13,30 +18,171 @@ pub struct PointLight {
     pub position: Vec3,
     /// The [Attenuation] of the light.
     pub attenuation: Attenuation,
+    ///
+    /// An optional profile, typically loaded from an IES file, which makes the intensity of the light vary with the direction like a real world luminaire.
+    /// The main direction of the profile (the vertical angle 0) points down along the negative y-axis and the horizontal angle 0 is along the x-axis.
+    ///
+    pub profile: Option<LightProfile>,
+    ///
+    /// The bias used when comparing the distance to a surface with the distance stored in the shadow map, relative to the distance from the light to the surface.
+    /// Increase the bias if the surfaces are shadowing themselves (shadow acne) and decrease it if the shadows are detached from the shadow casters (peter panning).
+    ///
+    pub shadow_bias: f32,
+    ///
+    /// The radius of the area which is sampled in the shadow map to soften the edges of the shadows (percentage-closer filtering), relative to the distance from the light to the surface.
+    /// A softness of 0 gives hard shadows.
+    ///
+    pub shadow_softness: f32,
+    /// The number of samples taken from the shadow map when the [PointLight::shadow_softness] is larger than 0.
+    pub shadow_quality: ShadowQuality,
 }
 
 impl PointLight {
     /// Constructs a new point light.
     pub fn new(
-        _context: &Context,
+        context: &Context,
         intensity: f32,
         color: Srgba,
         position: &Vec3,
         attenuation: Attenuation,
     ) -> PointLight {
         PointLight {
+            context: context.clone(),
+            shadow_texture: None,
+            shadow_depth_range: vec2(0.01, 1.0),
             intensity,
             color,
             position: *position,
             attenuation,
+            profile: None,
+            shadow_bias: 0.01,
+            shadow_softness: 0.005,
+            shadow_quality: ShadowQuality::default(),
         }
     }
+
+    ///
+    /// Clear the shadow map, effectively disable the shadow.
+    /// Only necessary if you want to disable the shadow, if you want to update the shadow, just use [PointLight::generate_shadow_map].
+    ///
+    pub fn clear_shadow_map(&mut self) {
+        self.shadow_texture = None;
+    }
+
+    ///
+    /// Generate a shadow map which is used to simulate shadows from the point light onto the geometries given as input.
+    /// The shadow map is a cube map where the geometries are rendered into each of the six sides, so the shadows are cast in all directions.
+    /// It is recomended that the texture size is power of 2.
+    /// If the shadows are too low resolution (the edges between shadow and non-shadow are pixelated) try to increase the texture size.
+    ///
+    pub fn generate_shadow_map(
+        &mut self,
+        texture_size: u32,
+        geometries: impl IntoIterator<Item = impl Geometry> + Clone,
+    ) {
+        let mut z_far = 0.0f32;
+        let mut z_near = f32::MAX;
+        for geometry in geometries.clone() {
+            let aabb = geometry.aabb();
+            if !aabb.is_empty() {
+                z_far = z_far.max(aabb.distance_max(&self.position));
+                z_near = z_near.min(aabb.distance(&self.position));
+            }
+        }
+        if z_far <= 0.0 {
+            self.clear_shadow_map();
+            return;
+        }
+        // The near plane is limited by the closest geometry, but it cannot be too small relative to the far plane without losing depth precision
+        let z_near = z_near.max(0.0001 * z_far);
+
+        let mut shadow_texture = DepthTextureCubeMap::new::<f32>(
+            &self.context,
+            texture_size,
+            texture_size,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+        let depth_material = DepthMaterial {
+            render_states: RenderStates {
+                write_mask: WriteMask::DEPTH,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let viewport = Viewport::new_at_origo(texture_size, texture_size);
+        self.context.push_debug_group("Point light shadow map");
+        for side in CubeMapSide::iter() {
+            let shadow_camera = Camera::new_perspective(
+                viewport,
+                self.position,
+                self.position + side.direction(),
+                side.up(),
+                degrees(90.0),
+                z_near,
+                z_far,
+            );
+            shadow_texture
+                .as_depth_target(side)
+                .clear(ClearState::default())
+                .write(|| {
+                    for geometry in geometries
+                        .clone()
+                        .into_iter()
+                        .filter(|g| shadow_camera.in_frustum(&g.aabb()))
+                    {
+                        render_with_material(
+                            &self.context,
+                            &shadow_camera,
+                            &geometry,
+                            &depth_material,
+                            &[],
+                        );
+                    }
+                });
+        }
+        self.context.pop_debug_group();
+        self.shadow_texture = Some(shadow_texture);
+        self.shadow_depth_range = vec2(z_near, z_far);
+    }
+
+    ///
+    /// Returns a reference to the shadow map if it has been generated.
+    ///
+    pub fn shadow_map(&self) -> Option<&DepthTextureCubeMap> {
+        self.shadow_texture.as_ref()
+    }
 }
 
 impl Light for PointLight {
     fn shader_source(&self, i: u32) -> String {
+        self.shader_source_internal(i, self.shadow_texture.is_some())
+    }
+
+    fn use_uniforms(&self, program: &Program, i: u32) {
+        self.use_uniforms_internal(program, i, self.shadow_texture.is_some())
+    }
+
+    fn id(&self) -> u8 {
+        self.id_internal(self.shadow_texture.is_some())
+    }
+
+    fn without_shadows(&self) -> Option<Box<dyn Light + '_>> {
+        if self.shadow_texture.is_some() {
+            Some(Box::new(PointLightWithoutShadows(self)))
+        } else {
+            None
+        }
+    }
+}
+
+impl PointLight {
+    fn shader_source_internal(&self, i: u32, shadows: bool) -> String {
         format!(
         "
+            {}
+            {}
             uniform vec3 color{};
             uniform vec3 attenuation{};
             uniform vec3 position{};
@@ -48,12 +194,64 @@ impl Light for PointLight {
                 light_direction = light_direction / distance;
 
                 vec3 light_color = attenuate(color{}, attenuation{}, distance);
+                {}
+                {}
                 return calculate_light(light_color, light_direction, surface_color, view_direction, normal, metallic, roughness);
             }}
         
-        ", i, i, i, i, i, i, i)
+        ",
+        if self.profile.is_some() {
+            LightProfile::shader_source(i)
+        } else {
+            String::new()
+        },
+        if shadows {
+            format!(
+                "uniform samplerCube pointShadowMap{};\nuniform vec4 shadowParameters{};\nuniform int shadowSamples{};",
+                i, i, i
+            )
+        } else {
+            String::new()
+        },
+        i, i, i, i, i, i, i,
+        if self.profile.is_some() {
+            format!("light_color *= profile_intensity{}(-light_direction);", i)
+        } else {
+            String::new()
+        },
+        if shadows {
+            format!(
+                "light_color *= calculate_point_shadow(light_direction, normal, pointShadowMap{}, position{}, shadowParameters{}, shadowSamples{}, position);",
+                i, i, i, i
+            )
+        } else {
+            String::new()
+        })
     }
-    fn use_uniforms(&self, program: &Program, i: u32) {
+
+    fn use_uniforms_internal(&self, program: &Program, i: u32, shadows: bool) {
+        if shadows {
+            if let Some(ref shadow_texture) = self.shadow_texture {
+                program.use_depth_texture_cube(&format!("pointShadowMap{}", i), shadow_texture);
+            }
+            program.use_uniform(
+                &format!("shadowParameters{}", i),
+                vec4(
+                    self.shadow_depth_range.x,
+                    self.shadow_depth_range.y,
+                    self.shadow_bias,
+                    self.shadow_softness,
+                ),
+            );
+            program.use_uniform(
+                &format!("shadowSamples{}", i),
+                if self.shadow_softness > 0.0 {
+                    self.shadow_quality.sample_count()
+                } else {
+                    1
+                },
+            );
+        }
         program.use_uniform(
             &format!("color{}", i),
             self.color.to_linear_srgb().truncate() * self.intensity,
@@ -67,9 +265,33 @@ impl Light for PointLight {
             ),
         );
         program.use_uniform(&format!("position{}", i), self.position);
+        if let Some(ref profile) = self.profile {
+            profile.use_uniforms(program, i, vec3(0.0, -1.0, 0.0), vec3(1.0, 0.0, 0.0));
+        }
+    }
+
+    fn id_internal(&self, shadows: bool) -> u8 {
+        let base = if self.profile.is_some() {
+            0b1u8 << 7 | 0b1001u8
+        } else {
+            0b1u8 << 7 | 0b100u8
+        };
+        base | (shadows as u8) << 4
+    }
+}
+
+struct PointLightWithoutShadows<'a>(&'a PointLight);
+
+impl Light for PointLightWithoutShadows<'_> {
+    fn shader_source(&self, i: u32) -> String {
+        self.0.shader_source_internal(i, false)
+    }
+
+    fn use_uniforms(&self, program: &Program, i: u32) {
+        self.0.use_uniforms_internal(program, i, false)
     }
 
     fn id(&self) -> u8 {
-        0b1u8 << 7 | 0b100u8
+        self.0.id_internal(false)
     }
 }
@@ -146,13 +146,13 @@ vec3 attenuate(vec3 light_color, vec3 attenuation, float distance)
     return light_color / max(1.0, att);
 }
 
-float is_visible(vec3 lightDirection, vec3 normal, sampler2D shadowMap, vec4 shadow_coord, vec2 offset)
+float is_visible(vec3 lightDirection, vec3 normal, sampler2D shadowMap, vec4 shadow_coord, vec2 offset, vec4 tile)
 {
     vec2 uv = (shadow_coord.xy + offset)/shadow_coord.w;
     if(uv.x < 0.0 || uv.x > 1.0 || uv.y < 0.0 || uv.y > 1.0) {
         return 1.0;
     }
-    float shadow_cast_distance = texture(shadowMap, uv).x;
+    float shadow_cast_distance = texture(shadowMap, tile.xy + uv * tile.zw).x;
     if(shadow_cast_distance > 0.999) {
         return 1.0;
     }
@@ -162,7 +162,8 @@ float is_visible(vec3 lightDirection, vec3 normal, sampler2D shadowMap, vec4 sha
     return shadow_cast_distance > true_distance ? 1.0 : 0.0;
 }
 
-float calculate_shadow(vec3 lightDirection, vec3 normal, sampler2D shadowMap, mat4 shadowMVP, vec3 position)
+// The tile is the offset (xy) and size (zw) in uv coordinates of the part of the shadow map that contains the shadow map of the light, for example a tile in a shadow atlas
+float calculate_shadow_in_tile(vec3 lightDirection, vec3 normal, sampler2D shadowMap, mat4 shadowMVP, vec4 tile, vec3 position)
 {
     vec4 shadow_coord = shadowMVP * vec4(position, 1.);
     float visibility = 0.0;
@@ -174,11 +175,133 @@ float calculate_shadow(vec3 lightDirection, vec3 normal, sampler2D shadowMap, ma
                                  );
     for (int i=0;i<4;i++)
     {
-        visibility += is_visible(lightDirection, normal, shadowMap, shadow_coord, poissonDisk[i] * 0.001f);
+        visibility += is_visible(lightDirection, normal, shadowMap, shadow_coord, poissonDisk[i] * 0.001f, tile);
+    }
+    return visibility * 0.25;
+}
+
+float calculate_shadow(vec3 lightDirection, vec3 normal, sampler2D shadowMap, mat4 shadowMVP, vec3 position)
+{
+    return calculate_shadow_in_tile(lightDirection, normal, shadowMap, shadowMVP, vec4(0.0, 0.0, 1.0, 1.0), position);
+}
+
+// The layer is the index of the shadow map in the array of shadow maps, for example the index of the cascade when using cascaded shadow maps
+float calculate_shadow_in_layer(vec3 lightDirection, vec3 normal, sampler2DArray shadowMaps, mat4 shadowMVP, float layer, vec3 position)
+{
+    vec4 shadow_coord = shadowMVP * vec4(position, 1.);
+    vec2 poissonDisk[4] = vec2[](
+                                 vec2( -0.94201624, -0.39906216 ),
+                                 vec2( 0.94558609, -0.76890725 ),
+                                 vec2( -0.094184101, -0.92938870 ),
+                                 vec2( 0.34495938, 0.29387760 )
+                                 );
+    float bias = max(0.05 * (1.0 - dot(normal, lightDirection)), 0.005);
+    float true_distance = (shadow_coord.z - bias)/shadow_coord.w;
+    float visibility = 0.0;
+    for (int i=0;i<4;i++)
+    {
+        vec2 uv = (shadow_coord.xy + poissonDisk[i] * 0.001f)/shadow_coord.w;
+        if(uv.x < 0.0 || uv.x > 1.0 || uv.y < 0.0 || uv.y > 1.0) {
+            visibility += 1.0;
+            continue;
+        }
+        float shadow_cast_distance = texture(shadowMaps, vec3(uv, layer)).x;
+        visibility += shadow_cast_distance > 0.999 || shadow_cast_distance > true_distance ? 1.0 : 0.0;
     }
     return visibility * 0.25;
 }
 
+float linearize_shadow_depth(float depth, vec2 depth_range)
+{
+    float z = 2.0 * depth - 1.0;
+    return 2.0 * depth_range.x * depth_range.y / (depth_range.y + depth_range.x - z * (depth_range.y - depth_range.x));
+}
+
+// Percentage-closer soft shadows for perspective shadow maps.
+// The softness contains the size of the light divided by the width of the shadow map at distance 1 from the light (x) and the near and far plane distances of the shadow camera (yz).
+// The average distance to the blockers around the position is used to estimate the size of the penumbra,
+// so the shadow is sharp where the shadow caster is close to the receiver and becomes softer with the distance between them.
+float calculate_soft_shadow_in_tile(vec3 lightDirection, vec3 normal, sampler2D shadowMap, mat4 shadowMVP, vec4 tile, vec3 position, vec3 softness, int sample_count)
+{
+    vec4 shadow_coord = shadowMVP * vec4(position, 1.);
+    vec2 uv = shadow_coord.xy / shadow_coord.w;
+    if(uv.x < 0.0 || uv.x > 1.0 || uv.y < 0.0 || uv.y > 1.0) {
+        return 1.0;
+    }
+    float bias = max(0.05 * (1.0 - dot(normal, lightDirection)), 0.005);
+    float receiver_depth = (shadow_coord.z - bias) / shadow_coord.w;
+    float receiver_distance = linearize_shadow_depth(receiver_depth, softness.yz);
+    float near = softness.y;
+
+    // Rotate the sample pattern randomly per fragment to exchange banding for noise
+    float rotation = 6.28318531 * fract(sin(dot(position, vec3(12.9898, 78.233, 37.719))) * 43758.5453);
+    float sample_scale = 1.0 / float(sample_count);
+
+    // Blocker search
+    float search_radius = min(softness.x * (receiver_distance - near) / (receiver_distance * near), 0.1);
+    float blocker_distance = 0.0;
+    float blocker_count = 0.0;
+    for (int i = 0; i < sample_count; i++)
+    {
+        float angle = float(i) * 2.39996323 + rotation;
+        vec2 offset = sqrt((float(i) + 0.5) * sample_scale) * vec2(cos(angle), sin(angle));
+        float depth = texture(shadowMap, tile.xy + clamp(uv + offset * search_radius, 0.0, 1.0) * tile.zw).x;
+        if(depth < receiver_depth) {
+            blocker_distance += linearize_shadow_depth(depth, softness.yz);
+            blocker_count += 1.0;
+        }
+    }
+    if(blocker_count < 0.5) {
+        return 1.0;
+    }
+    blocker_distance /= blocker_count;
+
+    // Filtering with a radius given by the size of the penumbra
+    float filter_radius = clamp(softness.x * (receiver_distance - blocker_distance) / (blocker_distance * receiver_distance), 0.001, 0.1);
+    float visibility = 0.0;
+    for (int i = 0; i < sample_count; i++)
+    {
+        float angle = float(i) * 2.39996323 + rotation;
+        vec2 offset = sqrt((float(i) + 0.5) * sample_scale) * vec2(cos(angle), sin(angle));
+        vec2 sample_uv = uv + offset * filter_radius;
+        if(sample_uv.x < 0.0 || sample_uv.x > 1.0 || sample_uv.y < 0.0 || sample_uv.y > 1.0) {
+            visibility += 1.0;
+            continue;
+        }
+        float depth = texture(shadowMap, tile.xy + sample_uv * tile.zw).x;
+        visibility += depth > 0.999 || depth > receiver_depth ? 1.0 : 0.0;
+    }
+    return visibility * sample_scale;
+}
+
+// Marches from the position towards the light and returns 0 if the ray passes behind the surface in the depth map, ie. the position is occluded, otherwise 1.
+// The settings contains the length of the ray (x), the thickness of the occluders (y) and the number of steps (z) in world units,
+// and the depth range contains the near and far plane distances of the camera that rendered the depth map.
+float calculate_contact_shadow(vec3 lightDirection, vec3 position, sampler2D depthMap, mat4 viewProjection, vec4 settings, vec2 depth_range)
+{
+    int step_count = int(settings.z);
+    float step_length = settings.x / settings.z;
+    float dither = fract(sin(dot(position, vec3(12.9898, 78.233, 37.719))) * 43758.5453);
+    for (int i = 0; i < step_count; i++)
+    {
+        float t = step_length * (float(i) + 0.5 + 0.5 * dither);
+        vec4 clip_coord = viewProjection * vec4(position + t * lightDirection, 1.0);
+        vec3 ndc = clip_coord.xyz / clip_coord.w;
+        vec2 uv = 0.5 * ndc.xy + 0.5;
+        if(uv.x < 0.0 || uv.x > 1.0 || uv.y < 0.0 || uv.y > 1.0 || ndc.z > 1.0) {
+            return 1.0;
+        }
+        float ray_distance = linearize_shadow_depth(0.5 * ndc.z + 0.5, depth_range);
+        float scene_distance = linearize_shadow_depth(texture(depthMap, uv).x, depth_range);
+        float difference = ray_distance - scene_distance;
+        if(difference > 0.002 * ray_distance && difference < settings.y) {
+            // Fade out towards the end of the ray to avoid a hard cut
+            return smoothstep(0.5, 1.0, t / settings.x);
+        }
+    }
+    return 1.0;
+}
+
 vec3 ImportanceSampleGGX(vec2 Xi, vec3 N, float roughness)
 {
 	float a = roughness*roughness;
@@ -201,3 +324,30 @@ vec3 ImportanceSampleGGX(vec2 Xi, vec3 N, float roughness)
 	vec3 sampleVec = tangent * H.x + bitangent * H.y + N * H.z;
 	return normalize(sampleVec);
 }
+
+// The parameters are the near and far plane of the shadow map, the bias and the softness (the radius of the sampled area), both relative to the distance to the light
+float calculate_point_shadow(vec3 lightDirection, vec3 normal, samplerCube shadowMap, vec3 lightPosition, vec4 parameters, int samples, vec3 position)
+{
+    float z_near = parameters.x;
+    float z_far = parameters.y;
+    vec3 direction = position - lightPosition;
+    float distance = length(direction);
+    float bias = parameters.z * distance * (2.0 - max(dot(normal, lightDirection), 0.0));
+    vec3 tangent = normalize(cross(abs(lightDirection.y) < 0.99 ? vec3(0.0, 1.0, 0.0) : vec3(1.0, 0.0, 0.0), lightDirection));
+    vec3 bitangent = cross(lightDirection, tangent);
+    float visibility = 0.0;
+    for (int s = 0; s < samples; s++)
+    {
+        // Samples distributed on a disc using the golden angle
+        float r = parameters.w * distance * sqrt((float(s) + 0.5) / float(samples));
+        float a = float(s) * 2.39996323;
+        vec3 sample_direction = direction + r * (cos(a) * tangent + sin(a) * bitangent);
+        vec3 d = abs(sample_direction);
+        float face_depth = max(d.x, max(d.y, d.z));
+        float z = 2.0 * texture(shadowMap, sample_direction).x - 1.0;
+        float shadow_cast_depth = 2.0 * z_near * z_far / (z_far + z_near - z * (z_far - z_near));
+        float sample_distance = length(sample_direction);
+        visibility += shadow_cast_depth * sample_distance / face_depth > sample_distance - bias ? 1.0 : 0.0;
+    }
+    return visibility / float(samples);
+}
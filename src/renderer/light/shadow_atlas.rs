@@ -0,0 +1,321 @@
+use crate::core::*;
+use crate::renderer::*;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+
+///
+/// A single depth texture which is divided into square tiles that are allocated to the lights that cast shadows,
+/// instead of each light having its own shadow map which quickly exhausts the available texture units when many lights cast shadows.
+/// All lights using the same atlas only use one texture unit when rendering.
+///
+/// Use for example [DirectionalLight::generate_shadow_map_in_atlas] or [SpotLight::generate_shadow_map_in_atlas] to render the shadow map of a light into a tile of the atlas.
+/// A light keeps its tile between frames as long as it is not evicted.
+/// When all tiles are in use, the tile of the least important light is evicted, and if several lights have the same importance, the least recently used of them is evicted.
+/// A light which is less important than all of the lights currently in the atlas does not get a tile and is rendered without shadows.
+///
+pub struct ShadowAtlas {
+    context: Context,
+    texture: Arc<RwLock<DepthTexture2D>>,
+    tile_size: u32,
+    slots: Vec<ShadowAtlasSlot>,
+    frame: u64,
+}
+
+struct ShadowAtlasSlot {
+    viewport: Viewport,
+    generation: Arc<AtomicU64>,
+    occupied: bool,
+    importance: f32,
+    last_used: u64,
+}
+
+impl ShadowAtlas {
+    ///
+    /// Creates a new shadow atlas with the given size in texels which is divided into tiles of the given size in texels.
+    /// Both sizes should preferably be a power of 2 and the size must be a multiple of the tile size.
+    ///
+    pub fn new(context: &Context, size: u32, tile_size: u32) -> Self {
+        let tiles_per_side = (size / tile_size).max(1);
+        let mut slots = Vec::new();
+        for y in 0..tiles_per_side {
+            for x in 0..tiles_per_side {
+                slots.push(ShadowAtlasSlot {
+                    viewport: Viewport {
+                        x: (x * tile_size) as i32,
+                        y: (y * tile_size) as i32,
+                        width: tile_size,
+                        height: tile_size,
+                    },
+                    generation: Arc::new(AtomicU64::new(0)),
+                    occupied: false,
+                    importance: 0.0,
+                    last_used: 0,
+                });
+            }
+        }
+        Self {
+            context: context.clone(),
+            texture: Arc::new(RwLock::new(DepthTexture2D::new::<f32>(
+                context,
+                tiles_per_side * tile_size,
+                tiles_per_side * tile_size,
+                Wrapping::ClampToEdge,
+                Wrapping::ClampToEdge,
+            ))),
+            tile_size,
+            slots,
+            frame: 0,
+        }
+    }
+
+    ///
+    /// Returns the size in texels of each tile.
+    ///
+    pub fn tile_size(&self) -> u32 {
+        self.tile_size
+    }
+
+    ///
+    /// Returns the total number of tiles in the atlas.
+    ///
+    pub fn tile_count(&self) -> usize {
+        self.slots.len()
+    }
+
+    ///
+    /// Returns the number of tiles which are not allocated to a light.
+    ///
+    pub fn free_tile_count(&self) -> usize {
+        self.slots.iter().filter(|s| !s.occupied).count()
+    }
+
+    ///
+    /// Marks the beginning of a new frame, which is used to find the least recently used tile when a tile needs to be evicted.
+    /// Should be called once each frame before generating the shadow maps.
+    ///
+    pub fn next_frame(&mut self) {
+        self.frame += 1;
+    }
+
+    ///
+    /// Allocates a tile for a light with the given importance, for example the intensity of the light divided by the distance to the camera.
+    /// If the previous tile of the light is given and it is still valid, that tile is returned.
+    /// Returns `None` if all tiles are allocated to lights that are more important.
+    ///
+    pub fn allocate(
+        &mut self,
+        previous: Option<&ShadowAtlasTile>,
+        importance: f32,
+    ) -> Option<ShadowAtlasTile> {
+        let index = match previous.filter(|tile| tile.is_valid()) {
+            Some(tile) => tile.index,
+            None => {
+                let index = self.slots.iter().position(|s| !s.occupied).or_else(|| {
+                    self.slots
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, s)| s.importance <= importance)
+                        .min_by(|(_, a), (_, b)| {
+                            a.importance
+                                .partial_cmp(&b.importance)
+                                .unwrap_or(std::cmp::Ordering::Equal)
+                                .then(a.last_used.cmp(&b.last_used))
+                        })
+                        .map(|(i, _)| i)
+                })?;
+                let slot = &mut self.slots[index];
+                slot.generation.fetch_add(1, Ordering::SeqCst);
+                slot.occupied = true;
+                index
+            }
+        };
+        let slot = &mut self.slots[index];
+        slot.importance = importance;
+        slot.last_used = self.frame;
+        Some(ShadowAtlasTile {
+            texture: self.texture.clone(),
+            generation: slot.generation.clone(),
+            owned_generation: slot.generation.load(Ordering::SeqCst),
+            index,
+            viewport: slot.viewport,
+            atlas_size: self.texture.read().unwrap().width(),
+        })
+    }
+
+    ///
+    /// Releases the given tile so it can be allocated to another light. The tile becomes invalid.
+    ///
+    pub fn release(&mut self, tile: &ShadowAtlasTile) {
+        if tile.is_valid() {
+            let slot = &mut self.slots[tile.index];
+            slot.generation.fetch_add(1, Ordering::SeqCst);
+            slot.occupied = false;
+        }
+    }
+
+    pub(super) fn render(
+        &self,
+        tile: &ShadowAtlasTile,
+        camera: &Camera,
+        geometries: impl IntoIterator<Item = impl Geometry>,
+    ) {
+        let depth_material = DepthMaterial {
+            render_states: RenderStates {
+                write_mask: WriteMask::DEPTH,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let scissor_box = ScissorBox {
+            x: tile.viewport.x,
+            y: tile.viewport.y,
+            width: tile.viewport.width,
+            height: tile.viewport.height,
+        };
+        self.context.push_debug_group("Shadow atlas tile");
+        self.texture
+            .write()
+            .unwrap()
+            .as_depth_target()
+            .clear_partially(scissor_box, ClearState::default())
+            .write_partially(scissor_box, || {
+                for geometry in geometries
+                    .into_iter()
+                    .filter(|g| camera.in_frustum(&g.aabb()))
+                {
+                    render_with_material(&self.context, camera, &geometry, &depth_material, &[]);
+                }
+            });
+        self.context.pop_debug_group();
+    }
+}
+
+///
+/// A tile in a [ShadowAtlas] which is allocated to a light.
+/// The tile becomes invalid when it is evicted or released from the atlas.
+///
+#[derive(Clone)]
+pub struct ShadowAtlasTile {
+    texture: Arc<RwLock<DepthTexture2D>>,
+    generation: Arc<AtomicU64>,
+    owned_generation: u64,
+    index: usize,
+    viewport: Viewport,
+    atlas_size: u32,
+}
+
+impl ShadowAtlasTile {
+    ///
+    /// Returns whether or not the tile is still allocated to the light, ie. it has not been evicted or released.
+    ///
+    pub fn is_valid(&self) -> bool {
+        self.generation.load(Ordering::SeqCst) == self.owned_generation
+    }
+
+    ///
+    /// Returns the viewport of the tile in the atlas texture.
+    ///
+    pub fn viewport(&self) -> Viewport {
+        self.viewport
+    }
+
+    fn uv_transformation(&self) -> Vec4 {
+        let size = self.atlas_size as f32;
+        vec4(
+            self.viewport.x as f32 / size,
+            self.viewport.y as f32 / size,
+            self.viewport.width as f32 / size,
+            self.viewport.height as f32 / size,
+        )
+    }
+}
+
+///
+/// Where the shadow map of a light is stored, if the light casts shadows.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum ShadowSource {
+    None,
+    Texture,
+    Atlas,
+}
+
+impl ShadowSource {
+    pub(super) fn new(texture: Option<&DepthTexture2D>, tile: Option<&ShadowAtlasTile>) -> Self {
+        if texture.is_some() {
+            Self::Texture
+        } else if tile.map(|t| t.is_valid()).unwrap_or(false) {
+            Self::Atlas
+        } else {
+            Self::None
+        }
+    }
+
+    pub(super) fn shader_source(&self, i: u32) -> String {
+        match self {
+            Self::None => String::new(),
+            Self::Texture => format!(
+                "uniform sampler2D shadowMap{};\nuniform mat4 shadowMVP{};\n",
+                i, i
+            ),
+            Self::Atlas => format!(
+                "#ifndef SHADOW_ATLAS\n#define SHADOW_ATLAS\nuniform sampler2D shadowAtlas;\n#endif\nuniform mat4 shadowMVP{};\nuniform vec4 shadowTile{};\n",
+                i, i
+            ),
+        }
+    }
+
+    pub(super) fn shadow_call(&self, i: u32, light_direction: &str) -> String {
+        match self {
+            Self::None => "1.0".to_owned(),
+            Self::Texture => format!(
+                "calculate_shadow({}, normal, shadowMap{}, shadowMVP{}, position)",
+                light_direction, i, i
+            ),
+            Self::Atlas => format!(
+                "calculate_shadow_in_tile({}, normal, shadowAtlas, shadowMVP{}, shadowTile{}, position)",
+                light_direction, i, i
+            ),
+        }
+    }
+
+    ///
+    /// Returns the call to the contact-hardening shadow function which expects the uniforms `shadowSoftness{i}` and `shadowSamples{i}` to be defined.
+    ///
+    pub(super) fn soft_shadow_call(&self, i: u32, light_direction: &str) -> String {
+        match self {
+            Self::None => "1.0".to_owned(),
+            Self::Texture => format!(
+                "calculate_soft_shadow_in_tile({}, normal, shadowMap{}, shadowMVP{}, vec4(0.0, 0.0, 1.0, 1.0), position, shadowSoftness{}, shadowSamples{})",
+                light_direction, i, i, i, i
+            ),
+            Self::Atlas => format!(
+                "calculate_soft_shadow_in_tile({}, normal, shadowAtlas, shadowMVP{}, shadowTile{}, position, shadowSoftness{}, shadowSamples{})",
+                light_direction, i, i, i, i
+            ),
+        }
+    }
+
+    pub(super) fn use_uniforms(
+        &self,
+        program: &Program,
+        i: u32,
+        texture: Option<&DepthTexture2D>,
+        tile: Option<&ShadowAtlasTile>,
+        shadow_matrix: Mat4,
+    ) {
+        match self {
+            Self::None => {}
+            Self::Texture => {
+                program.use_depth_texture(&format!("shadowMap{}", i), texture.unwrap());
+                program.use_uniform(&format!("shadowMVP{}", i), shadow_matrix);
+            }
+            Self::Atlas => {
+                let tile = tile.unwrap();
+                program.use_depth_texture("shadowAtlas", &tile.texture.read().unwrap());
+                program.use_uniform(&format!("shadowMVP{}", i), shadow_matrix);
+                program.use_uniform(&format!("shadowTile{}", i), tile.uv_transformation());
+            }
+        }
+    }
+}
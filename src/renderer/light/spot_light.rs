@@ -9,7 +9,9 @@ use crate::renderer::*;
 pub struct SpotLight {
     context: Context,
     shadow_texture: Option<DepthTexture2D>,
+    shadow_tile: Option<ShadowAtlasTile>,
     shadow_matrix: Mat4,
+    shadow_depth_range: Vec2,
     /// The intensity of the light. This allows for higher intensity than 1 which can be used to simulate high intensity light sources like the sun.
     pub intensity: f32,
     /// The base color of the light.
@@ -22,6 +24,27 @@ pub struct SpotLight {
     pub cutoff: Radians,
     /// The [Attenuation] of the light.
     pub attenuation: Attenuation,
+    ///
+    /// An optional texture, also called a cookie or gobo, which is projected from the light in the light direction and multiplied onto the color of the light,
+    /// for example to simulate the pattern of a flashlight, the shadow of a window frame or the image of a projector.
+    /// The texture covers the square which encloses the cone of the light, use [Wrapping::ClampToEdge] and a black border to avoid stretching the edge of the texture.
+    ///
+    pub cookie: Option<Texture2DRef>,
+    ///
+    /// An optional profile, typically loaded from an IES file, which makes the intensity of the light vary with the direction like a real world luminaire.
+    /// The main direction of the profile (the vertical angle 0) is the [SpotLight::direction].
+    ///
+    pub profile: Option<LightProfile>,
+    ///
+    /// The size of the light source, for example the diameter of the bulb, which is used to calculate contact-hardening soft shadows.
+    /// The shadows are sharp close to where the shadow caster touches the receiver and become softer with the distance between them, more so the larger the light source.
+    /// A size of 0, which is the default, gives shadows with a constant and small amount of softness.
+    ///
+    pub light_size: f32,
+    /// The quality of the contact-hardening soft shadows, only used if the [SpotLight::light_size] is larger than 0.
+    pub shadow_quality: ShadowQuality,
+    /// Optional [ContactShadows] which are combined with the shadows from the shadow map to capture the small details close to the shadow casters.
+    pub contact_shadows: Option<ContactShadows>,
 }
 
 impl SpotLight {
@@ -38,6 +61,7 @@ impl SpotLight {
         SpotLight {
             context: context.clone(),
             shadow_texture: None,
+            shadow_tile: None,
             intensity,
             color,
             position: *position,
@@ -45,6 +69,12 @@ impl SpotLight {
             cutoff: cutoff.into(),
             attenuation,
             shadow_matrix: Mat4::identity(),
+            shadow_depth_range: vec2(0.01, 1.0),
+            cookie: None,
+            profile: None,
+            light_size: 0.0,
+            shadow_quality: ShadowQuality::default(),
+            contact_shadows: None,
         }
     }
 
@@ -54,6 +84,7 @@ impl SpotLight {
     ///
     pub fn clear_shadow_map(&mut self) {
         self.shadow_texture = None;
+        self.shadow_tile = None;
         self.shadow_matrix = Mat4::identity();
     }
 
@@ -67,32 +98,10 @@ impl SpotLight {
         texture_size: u32,
         geometries: impl IntoIterator<Item = impl Geometry> + Clone,
     ) {
-        let position = self.position;
-        let direction = self.direction;
-        let up = compute_up_direction(self.direction);
-
         let viewport = Viewport::new_at_origo(texture_size, texture_size);
-
-        let mut z_far = 0.0f32;
-        let mut z_near = f32::MAX;
-        for geometry in geometries.clone() {
-            let aabb = geometry.aabb();
-            if !aabb.is_empty() {
-                z_far = z_far.max(aabb.distance_max(&self.position));
-                z_near = z_near.min(aabb.distance(&self.position));
-            }
-        }
-
-        let shadow_camera = Camera::new_perspective(
-            viewport,
-            position,
-            position + direction,
-            up,
-            self.cutoff,
-            z_near.max(0.01),
-            z_far,
-        );
+        let shadow_camera = self.shadow_camera(viewport, geometries.clone());
         self.shadow_matrix = shadow_matrix(&shadow_camera);
+        self.shadow_depth_range = vec2(shadow_camera.z_near(), shadow_camera.z_far());
 
         let mut shadow_texture = DepthTexture2D::new::<f32>(
             &self.context,
@@ -108,6 +117,7 @@ impl SpotLight {
             },
             ..Default::default()
         };
+        self.context.push_debug_group("Spot light shadow map");
         shadow_texture
             .as_depth_target()
             .clear(ClearState::default())
@@ -125,7 +135,67 @@ impl SpotLight {
                     );
                 }
             });
+        self.context.pop_debug_group();
         self.shadow_texture = Some(shadow_texture);
+        self.shadow_tile = None;
+    }
+
+    ///
+    /// Generate a shadow map like [SpotLight::generate_shadow_map] but render it into a tile of the given [ShadowAtlas] instead of a separate texture.
+    /// The importance is used to decide which lights get a tile when there are not enough tiles for all lights, see [ShadowAtlas::allocate].
+    /// Returns whether or not the light got a tile, if not, the light does not cast shadows.
+    ///
+    pub fn generate_shadow_map_in_atlas(
+        &mut self,
+        atlas: &mut ShadowAtlas,
+        importance: f32,
+        geometries: impl IntoIterator<Item = impl Geometry> + Clone,
+    ) -> bool {
+        match atlas.allocate(self.shadow_tile.as_ref(), importance) {
+            Some(tile) => {
+                let shadow_camera = self.shadow_camera(tile.viewport(), geometries.clone());
+                atlas.render(&tile, &shadow_camera, geometries);
+                self.shadow_texture = None;
+                self.shadow_tile = Some(tile);
+                self.shadow_matrix = shadow_matrix(&shadow_camera);
+                self.shadow_depth_range = vec2(shadow_camera.z_near(), shadow_camera.z_far());
+                true
+            }
+            None => {
+                self.clear_shadow_map();
+                false
+            }
+        }
+    }
+
+    fn shadow_camera(
+        &self,
+        viewport: Viewport,
+        geometries: impl IntoIterator<Item = impl Geometry>,
+    ) -> Camera {
+        let position = self.position;
+        let direction = self.direction;
+        let up = compute_up_direction(self.direction);
+
+        let mut z_far = 0.0f32;
+        let mut z_near = f32::MAX;
+        for geometry in geometries {
+            let aabb = geometry.aabb();
+            if !aabb.is_empty() {
+                z_far = z_far.max(aabb.distance_max(&self.position));
+                z_near = z_near.min(aabb.distance(&self.position));
+            }
+        }
+
+        Camera::new_perspective(
+            viewport,
+            position,
+            position + direction,
+            up,
+            self.cutoff,
+            z_near.max(0.01),
+            z_far,
+        )
     }
 
     ///
@@ -138,70 +208,174 @@ impl SpotLight {
 
 impl Light for SpotLight {
     fn shader_source(&self, i: u32) -> String {
-        if self.shadow_texture.is_some() {
-            format!(
-                "
-                    uniform sampler2D shadowMap{};
-                    uniform mat4 shadowMVP{};
-
-                    uniform vec3 color{};
-                    uniform vec3 attenuation{};
-                    uniform vec3 position{};
-                    uniform float cutoff{};
-                    uniform vec3 direction{};
-                    vec3 calculate_lighting{}(vec3 surface_color, vec3 position, vec3 normal, vec3 view_direction, float metallic, float roughness, float occlusion)
-                    {{
-                        vec3 light_direction = position{} - position;
-                        float distance = length(light_direction);
-                        light_direction = light_direction / distance;
-
-                        float angle = acos(dot(-light_direction, normalize(direction{})));
-                        float cutoff = cutoff{};
-
-                        vec3 result = vec3(0.0);
-                        if (angle < cutoff) {{
-                            vec3 light_color = attenuate(color{}, attenuation{}, distance);
-                            result = calculate_light(light_color, light_direction, surface_color, view_direction, normal,
-                                metallic, roughness) * (1.0 - smoothstep(0.75 * cutoff, cutoff, angle));
-                            result *= calculate_shadow(light_direction, normal, shadowMap{}, shadowMVP{}, position);
-                        }}
-                        return result;
-                    }}
+        self.shader_source_internal(i, self.shadow_source(), self.contact_shadows.as_ref())
+    }
+
+    fn use_uniforms(&self, program: &Program, i: u32) {
+        self.use_uniforms_internal(
+            program,
+            i,
+            self.shadow_source(),
+            self.contact_shadows.as_ref(),
+        )
+    }
+
+    fn id(&self) -> u8 {
+        self.id_internal(self.shadow_source(), self.contact_shadows.as_ref())
+    }
+
+    fn without_shadows(&self) -> Option<Box<dyn Light + '_>> {
+        if self.shadow_source() != ShadowSource::None || self.contact_shadows.is_some() {
+            Some(Box::new(SpotLightWithoutShadows(self)))
+        } else {
+            None
+        }
+    }
+}
+
+impl SpotLight {
+    fn shadow_source(&self) -> ShadowSource {
+        ShadowSource::new(self.shadow_texture.as_ref(), self.shadow_tile.as_ref())
+    }
+
+    fn soft_shadows(&self, shadows: ShadowSource) -> bool {
+        shadows != ShadowSource::None && self.light_size > 0.0
+    }
 
-                ", i, i, i, i, i, i, i, i, i, i, i, i, i, i, i)
+    fn shader_source_internal(
+        &self,
+        i: u32,
+        shadows: ShadowSource,
+        contact_shadows: Option<&ContactShadows>,
+    ) -> String {
+        let cookie = self.cookie.is_some();
+        let profile = self.profile.is_some();
+        let soft_shadows = self.soft_shadows(shadows);
+        let shadow = if soft_shadows {
+            shadows.soft_shadow_call(i, "light_direction")
         } else {
-            format!(
-                "
-                    uniform vec3 color{};
-                    uniform vec3 attenuation{};
-                    uniform vec3 position{};
-                    uniform float cutoff{};
-                    uniform vec3 direction{};
-                    vec3 calculate_lighting{}(vec3 surface_color, vec3 position, vec3 normal, vec3 view_direction, float metallic, float roughness, float occlusion)
-                    {{
-                        vec3 light_direction = position{} - position;
-                        float distance = length(light_direction);
-                        light_direction = light_direction / distance;
-
-                        float angle = acos(dot(-light_direction, normalize(direction{})));
-                        float cutoff = cutoff{};
-
-                        vec3 result = vec3(0.0);
-                        if (angle < cutoff) {{
-                            vec3 light_color = attenuate(color{}, attenuation{}, distance);
-                            result = calculate_light(light_color, light_direction, surface_color, view_direction, normal,
-                                metallic, roughness) * (1.0 - smoothstep(0.75 * cutoff, cutoff, angle));
-                        }}
-                        return result;
+            shadows.shadow_call(i, "light_direction")
+        };
+        let shadow = match contact_shadows {
+            Some(_) => format!(
+                "min({}, {})",
+                shadow,
+                ContactShadows::shadow_call(i, "light_direction")
+            ),
+            None => shadow,
+        };
+        format!(
+            "
+                {}
+                {}
+                {}
+                {}
+                {}
+                uniform vec3 color{};
+                uniform vec3 attenuation{};
+                uniform vec3 position{};
+                uniform float cutoff{};
+                uniform vec3 direction{};
+                vec3 calculate_lighting{}(vec3 surface_color, vec3 position, vec3 normal, vec3 view_direction, float metallic, float roughness, float occlusion)
+                {{
+                    vec3 light_direction = position{} - position;
+                    float distance = length(light_direction);
+                    light_direction = light_direction / distance;
+
+                    float angle = acos(dot(-light_direction, normalize(direction{})));
+                    float cutoff = cutoff{};
+
+                    vec3 result = vec3(0.0);
+                    if (angle < cutoff) {{
+                        vec3 light_color = attenuate(color{}, attenuation{}, distance);
+                        {}
+                        {}
+                        result = calculate_light(light_color, light_direction, surface_color, view_direction, normal,
+                            metallic, roughness) * (1.0 - smoothstep(0.75 * cutoff, cutoff, angle));
+                        {}
                     }}
+                    return result;
+                }}
 
-                ", i, i, i, i, i, i, i, i, i, i, i)
-        }
+            ",
+            shadows.shader_source(i),
+            if soft_shadows {
+                format!(
+                    "uniform vec3 shadowSoftness{};\nuniform int shadowSamples{};",
+                    i, i
+                )
+            } else {
+                String::new()
+            },
+            if contact_shadows.is_some() {
+                ContactShadows::shader_source(i)
+            } else {
+                String::new()
+            },
+            if cookie {
+                format!(
+                    "uniform sampler2D cookieTexture{};\nuniform mat3 cookieTexTransform{};\nuniform mat4 cookieMVP{};",
+                    i, i, i
+                )
+            } else {
+                String::new()
+            },
+            if profile {
+                LightProfile::shader_source(i)
+            } else {
+                String::new()
+            },
+            i, i, i, i, i, i, i, i, i, i, i,
+            if profile {
+                format!("light_color *= profile_intensity{}(-light_direction);", i)
+            } else {
+                String::new()
+            },
+            if cookie {
+                format!(
+                    "vec4 cookie_coords = cookieMVP{} * vec4(position, 1.0);
+                        light_color *= texture(cookieTexture{}, (cookieTexTransform{} * vec3(cookie_coords.xy / cookie_coords.w, 1.0)).xy).rgb;",
+                    i, i, i
+                )
+            } else {
+                String::new()
+            },
+            if shadows != ShadowSource::None || contact_shadows.is_some() {
+                format!("result *= {};", shadow)
+            } else {
+                String::new()
+            },
+        )
     }
-    fn use_uniforms(&self, program: &Program, i: u32) {
-        if let Some(ref tex) = self.shadow_texture {
-            program.use_depth_texture(&format!("shadowMap{}", i), tex);
-            program.use_uniform(&format!("shadowMVP{}", i), self.shadow_matrix);
+    fn use_uniforms_internal(
+        &self,
+        program: &Program,
+        i: u32,
+        shadows: ShadowSource,
+        contact_shadows: Option<&ContactShadows>,
+    ) {
+        shadows.use_uniforms(
+            program,
+            i,
+            self.shadow_texture.as_ref(),
+            self.shadow_tile.as_ref(),
+            self.shadow_matrix,
+        );
+        if self.soft_shadows(shadows) {
+            // The size of the light relative to the width of the shadow map at distance 1 from the light
+            let light_size = self.light_size / (2.0 * (0.5 * self.cutoff.0).tan());
+            program.use_uniform(
+                &format!("shadowSoftness{}", i),
+                vec3(
+                    light_size,
+                    self.shadow_depth_range.x,
+                    self.shadow_depth_range.y,
+                ),
+            );
+            program.use_uniform(
+                &format!("shadowSamples{}", i),
+                self.shadow_quality.sample_count(),
+            );
         }
         program.use_uniform(
             &format!("color{}", i),
@@ -218,13 +392,66 @@ impl Light for SpotLight {
         program.use_uniform(&format!("position{}", i), self.position);
         program.use_uniform(&format!("direction{}", i), self.direction.normalize());
         program.use_uniform(&format!("cutoff{}", i), self.cutoff.0);
+        if let Some(contact_shadows) = contact_shadows {
+            contact_shadows.use_uniforms(program, i);
+        }
+        if let Some(ref cookie) = self.cookie {
+            program.use_texture(&format!("cookieTexture{}", i), cookie);
+            program.use_uniform(&format!("cookieTexTransform{}", i), cookie.transformation);
+            program.use_uniform(&format!("cookieMVP{}", i), self.cookie_matrix());
+        }
+        if let Some(ref profile) = self.profile {
+            profile.use_uniforms(
+                program,
+                i,
+                self.direction,
+                compute_up_direction(self.direction.normalize()),
+            );
+        }
+    }
+
+    fn cookie_matrix(&self) -> Mat4 {
+        let direction = self.direction.normalize();
+        shadow_matrix(&Camera::new_perspective(
+            Viewport::new_at_origo(1, 1),
+            self.position,
+            self.position + direction,
+            compute_up_direction(direction),
+            self.cutoff * 2.0,
+            0.01,
+            1.0,
+        ))
+    }
+
+    fn id_internal(&self, shadows: ShadowSource, contact_shadows: Option<&ContactShadows>) -> u8 {
+        let shadows_bits = match shadows {
+            ShadowSource::None => 0b00u8,
+            ShadowSource::Texture => 0b01u8,
+            ShadowSource::Atlas => 0b10u8,
+        };
+        0b1u8 << 7
+            | 0b1u8 << 6
+            | (contact_shadows.is_some() as u8) << 5
+            | (self.soft_shadows(shadows) as u8) << 4
+            | (self.profile.is_some() as u8) << 3
+            | (self.cookie.is_some() as u8) << 2
+            | shadows_bits
+    }
+}
+
+struct SpotLightWithoutShadows<'a>(&'a SpotLight);
+
+impl Light for SpotLightWithoutShadows<'_> {
+    fn shader_source(&self, i: u32) -> String {
+        self.0.shader_source_internal(i, ShadowSource::None, None)
+    }
+
+    fn use_uniforms(&self, program: &Program, i: u32) {
+        self.0
+            .use_uniforms_internal(program, i, ShadowSource::None, None)
     }
 
     fn id(&self) -> u8 {
-        if self.shadow_texture.is_some() {
-            0b1u8 << 7 | 0b101u8
-        } else {
-            0b1u8 << 7 | 0b110u8
-        }
+        self.0.id_internal(ShadowSource::None, None)
     }
 }
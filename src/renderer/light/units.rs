@@ -0,0 +1,111 @@
+use crate::core::*;
+
+///
+/// The exposure of a physical camera given as an exposure value at ISO 100 (EV100), which is used to convert light intensities given in physical units,
+/// for example from a lighting reference or from the `KHR_lights_punctual` glTF extension, into the intensity of the lights, see for example [DirectionalLight::intensity].
+///
+/// The intensity of a light is the physical quantity multiplied by the exposure scale `1 / (1.2 * 2^EV100)`,
+/// so a surface lit with a luminance that saturates the sensor of the physical camera ends up with a color value of approximately 1 before tone mapping.
+/// Use the same exposure for all lights in a scene and combine point and spot lights with [Attenuation::INVERSE_SQUARE] to get a physically plausible falloff.
+///
+/// | Scene                      | EV100 |
+/// |----------------------------|-------|
+/// | Sunny day outdoor          | 15    |
+/// | Overcast day outdoor       | 12    |
+/// | Bright office              | 8     |
+/// | Living room in the evening | 5     |
+/// | Night with a full moon     | -2    |
+///
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub struct Exposure {
+    /// The exposure value at ISO 100.
+    pub ev100: f32,
+}
+
+impl Exposure {
+    ///
+    /// Constructs a new exposure from the given exposure value at ISO 100.
+    ///
+    pub fn from_ev100(ev100: f32) -> Self {
+        Self { ev100 }
+    }
+
+    ///
+    /// Constructs a new exposure from the settings of a physical camera, ie. the aperture given as an f-number (for example 16 for f/16),
+    /// the shutter time in seconds and the sensor sensitivity given as an ISO value.
+    ///
+    pub fn from_camera_settings(aperture: f32, shutter_time: f32, iso: f32) -> Self {
+        Self::from_ev100((aperture * aperture / shutter_time * 100.0 / iso).log2())
+    }
+
+    ///
+    /// Constructs a new exposure which maps the given average luminance of the scene in candela per square meter to middle grey.
+    /// This is typically used for auto exposure where the average luminance is measured from the rendered image.
+    ///
+    pub fn from_average_luminance(luminance: f32) -> Self {
+        Self::from_ev100((luminance * 100.0 / 12.5).log2())
+    }
+
+    ///
+    /// Returns the factor which physical light quantities are multiplied with to get the light intensity.
+    ///
+    pub fn scale(&self) -> f32 {
+        1.0 / (1.2 * 2.0f32.powf(self.ev100))
+    }
+
+    ///
+    /// Returns the intensity of a light from the given illuminance in lux.
+    /// This is the unit used for the intensity of directional lights, for example the sun, in the `KHR_lights_punctual` glTF extension.
+    ///
+    pub fn intensity_from_lux(&self, illuminance: f32) -> f32 {
+        illuminance * self.scale()
+    }
+
+    ///
+    /// Returns the intensity of a light from the given luminous intensity in candela.
+    /// This is the unit used for the intensity of point and spot lights in the `KHR_lights_punctual` glTF extension.
+    ///
+    pub fn intensity_from_candela(&self, luminous_intensity: f32) -> f32 {
+        luminous_intensity * self.scale()
+    }
+
+    ///
+    /// Returns the intensity of a point light from the given luminous power in lumen, which is typically the unit given on light bulbs.
+    ///
+    pub fn intensity_from_point_lumen(&self, luminous_power: f32) -> f32 {
+        self.intensity_from_candela(point_lumen_to_candela(luminous_power))
+    }
+
+    ///
+    /// Returns the intensity of a spot light with the given cutoff angle from the given luminous power in lumen.
+    /// The luminous power is distributed over the cone of the spot light, so the spot light becomes brighter when the cone becomes narrower.
+    ///
+    pub fn intensity_from_spot_lumen(
+        &self,
+        luminous_power: f32,
+        cutoff: impl Into<Radians>,
+    ) -> f32 {
+        self.intensity_from_candela(spot_lumen_to_candela(luminous_power, cutoff))
+    }
+}
+
+impl Default for Exposure {
+    fn default() -> Self {
+        Self::from_ev100(0.0)
+    }
+}
+
+///
+/// Converts the luminous power in lumen of a light which shines equally in all directions into luminous intensity in candela.
+///
+pub fn point_lumen_to_candela(luminous_power: f32) -> f32 {
+    luminous_power / (4.0 * std::f32::consts::PI)
+}
+
+///
+/// Converts the luminous power in lumen of a spot light with the given cutoff angle into luminous intensity in candela.
+///
+pub fn spot_lumen_to_candela(luminous_power: f32, cutoff: impl Into<Radians>) -> f32 {
+    let cutoff: Radians = cutoff.into();
+    luminous_power / (2.0 * std::f32::consts::PI * (1.0 - cutoff.0.cos()).max(0.0001))
+}
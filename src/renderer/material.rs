@@ -25,6 +25,9 @@ macro_rules! impl_material_body {
         fn id(&self) -> u16 {
             self.$inner().id()
         }
+        fn tessellation_shader_source(&self) -> Option<(String, String)> {
+            self.$inner().tessellation_shader_source()
+        }
     };
 }
 
@@ -66,6 +69,10 @@ mod deferred_physical_material;
 #[doc(inline)]
 pub use deferred_physical_material::*;
 
+mod tessellated_physical_material;
+#[doc(inline)]
+pub use tessellated_physical_material::*;
+
 mod skybox_material;
 #[doc(inline)]
 pub(in crate::renderer) use skybox_material::*;
@@ -74,6 +81,30 @@ mod isosurface_material;
 #[doc(inline)]
 pub use isosurface_material::*;
 
+mod splat_map_material;
+#[doc(inline)]
+pub use splat_map_material::*;
+
+mod animated_material;
+#[doc(inline)]
+pub use animated_material::*;
+
+mod overhang_material;
+#[doc(inline)]
+pub use overhang_material::*;
+
+mod scalar_field_material;
+#[doc(inline)]
+pub use scalar_field_material::*;
+
+mod pick_material;
+#[doc(inline)]
+pub use pick_material::*;
+
+mod mpr_material;
+#[doc(inline)]
+pub use mpr_material::*;
+
 use std::{ops::Deref, sync::Arc};
 
 ///
@@ -90,9 +121,10 @@ pub struct Texture2DRef {
 
 impl Texture2DRef {
     /// Creates a new [Texture2DRef] with an identity transformation from a [CpuTexture].
+    /// The texture is shared with other textures created from a [CpuTexture] with the same content, see [Context::set_resource_sharing].
     pub fn from_cpu_texture(context: &Context, cpu_texture: &CpuTexture) -> Self {
         Self {
-            texture: Arc::new(Texture2D::new(context, cpu_texture)),
+            texture: shared_texture(context, cpu_texture),
             transformation: Mat3::identity(),
         }
     }
@@ -128,6 +160,83 @@ impl std::convert::From<Arc<Texture2D>> for Texture2DRef {
     }
 }
 
+///
+/// Displaces the vertices of a [Geometry] along the normal by the values in the red channel of a displacement map, for example for adding bumps to a terrain or animating a surface.
+/// The normals of the displaced surface are computed in the fragment shader from the gradient of the displacement map.
+/// Supported by [PhysicalMaterial] and [DeferredPhysicalMaterial] when used together with a [Mesh] or an [InstancedMesh] with normals and uv coordinates.
+///
+#[derive(Clone)]
+pub struct Displacement {
+    /// The displacement map where the red channel contains the displacement, sampled using uv coordinates.
+    pub texture: Texture2DRef,
+    /// The displacement of a vertex where the displacement map has the value 1.
+    pub scale: f32,
+}
+
+impl Displacement {
+    pub(in crate::renderer) fn fragment_shader_source() -> &'static str {
+        include_str!("material/shaders/displacement.frag")
+    }
+
+    pub(in crate::renderer) fn use_uniforms(&self, program: &Program) {
+        program.use_uniform_if_required("displacementTexTransform", self.texture.transformation);
+        program.use_uniform_if_required("displacementScale", self.scale);
+        program.use_texture_if_required("displacementTexture", &self.texture);
+    }
+}
+
+///
+/// Specifies which set of uv coordinates is used when sampling a texture.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UvChannel {
+    /// The uv coordinates of the [CpuMesh].
+    Uv0,
+    /// The second set of uv coordinates, see [Mesh::new_with_uvs1] and [Mesh::set_uvs1].
+    Uv1,
+}
+
+impl Default for UvChannel {
+    fn default() -> Self {
+        Self::Uv0
+    }
+}
+
+///
+/// Dissolves a surface based on a noise texture, for example to make an object appear or disappear.
+/// All fragments where the noise value (sampled from the red channel of the noise texture) is below the threshold are discarded
+/// and fragments with a noise value just above the threshold are given an emissive edge color.
+/// Supported by [PhysicalMaterial], [DeferredPhysicalMaterial] and [ColorMaterial].
+///
+#[derive(Clone)]
+pub struct Dissolve {
+    /// The noise texture which is sampled using uv coordinates (requires that the [Geometry] supports uv coordinates).
+    pub noise_texture: Texture2DRef,
+    /// A value in the range `[0..1]` where 0 means nothing is dissolved and 1 means everything is dissolved.
+    pub threshold: f32,
+    /// The width of the edge band measured in noise values.
+    pub edge_width: f32,
+    /// The color of the light shining from the edge band.
+    pub edge_color: Srgba,
+}
+
+impl Dissolve {
+    pub(in crate::renderer) fn fragment_shader_source() -> &'static str {
+        include_str!("material/shaders/dissolve.frag")
+    }
+
+    pub(in crate::renderer) fn use_uniforms(&self, program: &Program) {
+        program.use_uniform("dissolveTexTransform", self.noise_texture.transformation);
+        program.use_texture("dissolveTexture", &self.noise_texture);
+        program.use_uniform("dissolveThreshold", self.threshold);
+        program.use_uniform("dissolveEdgeWidth", self.edge_width);
+        program.use_uniform(
+            "dissolveEdgeColor",
+            self.edge_color.to_linear_srgb().truncate(),
+        );
+    }
+}
+
 ///
 /// Defines the material type which is needed to render the objects in the correct order.
 /// For example, transparent objects need to be rendered back to front, whereas opaque objects need to be rendered front to back.
@@ -156,8 +265,20 @@ pub struct FragmentAttributes {
     pub tangents: bool,
     /// UV coordinates: `in vec2 uvs;`
     pub uv: bool,
+    /// Second set of UV coordinates, for example used for light maps: `in vec2 uvs1;`
+    pub uv1: bool,
     /// Color: `in vec4 col;`
     pub color: bool,
+    /// Whether or not the vertices should be displaced along the normal by a displacement map, see [Displacement].
+    /// The displacement map is defined in the vertex shader by `uniform sampler2D displacementTexture; uniform mat3 displacementTexTransform; uniform float displacementScale;`
+    /// and requires that the normal and uv attributes are also enabled.
+    /// If the program uses the tessellation stages, the displacement should instead be applied in the tessellation evaluation shader, see [TessellatedPhysicalMaterial].
+    pub displacement: bool,
+    /// Intensity multiplied onto the emissive color, for example given for each instance by [Instances::emissive_intensities] and otherwise 1: `in float emissive_intensity;`
+    pub emissive_intensity: bool,
+    /// The index of the instance in the [Instances] of an [InstancedMesh], for example used for picking, see [PickMaterial].
+    /// Only provided by [InstancedMesh]: `flat in int instance_id;`
+    pub instance_id: bool,
 }
 
 impl FragmentAttributes {
@@ -167,7 +288,11 @@ impl FragmentAttributes {
         normal: true,
         tangents: true,
         uv: true,
+        uv1: true,
         color: true,
+        displacement: true,
+        emissive_intensity: true,
+        instance_id: true,
     };
     /// No attributes
     pub const NONE: Self = Self {
@@ -175,7 +300,11 @@ impl FragmentAttributes {
         normal: false,
         tangents: false,
         uv: false,
+        uv1: false,
         color: false,
+        displacement: false,
+        emissive_intensity: false,
+        instance_id: false,
     };
 }
 
@@ -218,6 +347,15 @@ pub trait Material {
     /// Returns the type of material.
     ///
     fn material_type(&self) -> MaterialType;
+
+    ///
+    /// Returns the tessellation control and evaluation shader source if this material refines the geometry using the tessellation stages, see [Program::from_source_with_tessellation].
+    /// The tessellation stages receive the output of the vertex shader of the geometry and must output the fragment attributes required by this material.
+    /// Returns `None` by default.
+    ///
+    fn tessellation_shader_source(&self) -> Option<(String, String)> {
+        None
+    }
 }
 
 ///
@@ -248,15 +386,15 @@ impl<T: Material + ?Sized> Material for &mut T {
     impl_material_body!(deref);
 }
 
-impl<T: Material> Material for Box<T> {
+impl<T: Material + ?Sized> Material for Box<T> {
     impl_material_body!(as_ref);
 }
 
-impl<T: Material> Material for std::rc::Rc<T> {
+impl<T: Material + ?Sized> Material for std::rc::Rc<T> {
     impl_material_body!(as_ref);
 }
 
-impl<T: Material> Material for std::sync::Arc<T> {
+impl<T: Material + ?Sized> Material for std::sync::Arc<T> {
     impl_material_body!(as_ref);
 }
 
@@ -283,6 +421,9 @@ impl<T: Material> Material for std::sync::RwLock<T> {
     fn id(&self) -> u16 {
         self.read().unwrap().id()
     }
+    fn tessellation_shader_source(&self) -> Option<(String, String)> {
+        self.read().unwrap().tessellation_shader_source()
+    }
 }
 
 fn is_transparent(cpu_material: &CpuMaterial) -> bool {
@@ -0,0 +1,189 @@
+use crate::core::*;
+use crate::renderer::*;
+
+///
+/// A curve defined by a list of key frames, each consisting of a time and a value, which is linearly interpolated between the key frames.
+/// Used for animating a uniform of an [AnimatedMaterial].
+///
+#[derive(Clone, Debug)]
+pub enum UniformCurve {
+    /// Animates a float uniform, for example a strength or a threshold.
+    Float(Vec<(f32, f32)>),
+    /// Animates a vec2 uniform.
+    Vec2(Vec<(f32, Vec2)>),
+    /// Animates a vec3 uniform, for example the `emissive` uniform of a [PhysicalMaterial].
+    Vec3(Vec<(f32, Vec3)>),
+    /// Animates a vec4 uniform, for example the `albedo` uniform of a [PhysicalMaterial] or the `surfaceColor` uniform of a [ColorMaterial].
+    /// Note that colors are sent to the shader in linear sRGB.
+    Vec4(Vec<(f32, Vec4)>),
+    /// Animates a texture transformation uniform, for example `albedoTexTransform`, by a translation of the uv coordinates, which is useful for scrolling textures.
+    /// The translation is applied before the base transformation, which should be the transformation sent by the wrapped material,
+    /// for example `texture.transformation * material.uv_transformation` for the albedo texture of a [PhysicalMaterial], so tiled or atlas textures keep their transformation.
+    TextureOffset {
+        /// The transformation of the texture without the animated translation.
+        base: Mat3,
+        /// The key frames of the translation of the uv coordinates.
+        key_frames: Vec<(f32, Vec2)>,
+    },
+}
+
+impl UniformCurve {
+    ///
+    /// Returns the time of the last key frame.
+    ///
+    pub fn duration(&self) -> f32 {
+        match self {
+            Self::Float(key_frames) => duration(key_frames),
+            Self::Vec2(key_frames) | Self::TextureOffset { key_frames, .. } => duration(key_frames),
+            Self::Vec3(key_frames) => duration(key_frames),
+            Self::Vec4(key_frames) => duration(key_frames),
+        }
+    }
+
+    fn use_uniform(&self, program: &Program, name: &str, time: f32) {
+        match self {
+            Self::Float(key_frames) => {
+                if let Some(value) = interpolate(key_frames, time, |a, b, t| a + (b - a) * t) {
+                    program.use_uniform_if_required(name, value);
+                }
+            }
+            Self::Vec2(key_frames) => {
+                if let Some(value) = interpolate(key_frames, time, |a, b, t| a.lerp(b, t)) {
+                    program.use_uniform_if_required(name, value);
+                }
+            }
+            Self::Vec3(key_frames) => {
+                if let Some(value) = interpolate(key_frames, time, |a, b, t| a.lerp(b, t)) {
+                    program.use_uniform_if_required(name, value);
+                }
+            }
+            Self::Vec4(key_frames) => {
+                if let Some(value) = interpolate(key_frames, time, |a, b, t| a.lerp(b, t)) {
+                    program.use_uniform_if_required(name, value);
+                }
+            }
+            Self::TextureOffset { base, key_frames } => {
+                if let Some(value) = interpolate(key_frames, time, |a, b, t| a.lerp(b, t)) {
+                    program.use_uniform_if_required(name, *base * Mat3::from_translation(value));
+                }
+            }
+        }
+    }
+}
+
+fn duration<T>(key_frames: &[(f32, T)]) -> f32 {
+    key_frames.last().map(|(t, _)| *t).unwrap_or(0.0)
+}
+
+fn interpolate<T: Copy>(
+    key_frames: &[(f32, T)],
+    time: f32,
+    lerp: impl Fn(T, T, f32) -> T,
+) -> Option<T> {
+    let (first, last) = (key_frames.first()?, key_frames.last()?);
+    if time <= first.0 {
+        return Some(first.1);
+    }
+    if time >= last.0 {
+        return Some(last.1);
+    }
+    let i = key_frames.iter().position(|(t, _)| *t > time)?;
+    let (t0, v0) = key_frames[i - 1];
+    let (t1, v1) = key_frames[i];
+    Some(lerp(v0, v1, (time - t0) / (t1 - t0)))
+}
+
+///
+/// A material which wraps another material and animates some of the uniforms of that material along [UniformCurve]s,
+/// for example to create pulsing highlights or scrolling textures.
+/// The animated uniforms are sent to the shader after the uniforms of the wrapped material and therefore overrides them.
+///
+/// The animation is only updated when [AnimatedMaterial::animate] is called, typically each frame with the accumulated time given in the frame input.
+///
+pub struct AnimatedMaterial<M: Material> {
+    /// The material that is animated.
+    pub material: M,
+    /// Whether or not the animation should start over after the last key frame of the longest curve.
+    pub looping: bool,
+    curves: Vec<(String, UniformCurve)>,
+    time: f32,
+}
+
+impl<M: Material> AnimatedMaterial<M> {
+    ///
+    /// Creates a new animated material that wraps the given material without any animated uniforms.
+    ///
+    pub fn new(material: M) -> Self {
+        Self {
+            material,
+            looping: true,
+            curves: Vec::new(),
+            time: 0.0,
+        }
+    }
+
+    ///
+    /// Animates the uniform with the given name along the given curve.
+    /// If the uniform is already animated, the curve is replaced.
+    ///
+    pub fn set_curve(&mut self, uniform_name: &str, curve: UniformCurve) {
+        self.curves.retain(|(name, _)| name != uniform_name);
+        self.curves.push((uniform_name.to_string(), curve));
+    }
+
+    ///
+    /// Stops animating the uniform with the given name.
+    ///
+    pub fn remove_curve(&mut self, uniform_name: &str) {
+        self.curves.retain(|(name, _)| name != uniform_name);
+    }
+
+    ///
+    /// Updates the animation to the given time. The time parameter should be some continious time, for example the time since start.
+    ///
+    pub fn animate(&mut self, time: f32) {
+        let duration = self
+            .curves
+            .iter()
+            .map(|(_, curve)| curve.duration())
+            .fold(0.0, f32::max);
+        self.time = if self.looping && duration > 0.0 {
+            time % duration
+        } else {
+            time
+        };
+    }
+}
+
+impl<M: Material> Material for AnimatedMaterial<M> {
+    fn fragment_shader_source(&self, lights: &[&dyn Light]) -> String {
+        self.material.fragment_shader_source(lights)
+    }
+
+    fn id(&self) -> u16 {
+        self.material.id()
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        self.material.fragment_attributes()
+    }
+
+    fn use_uniforms(&self, program: &Program, camera: &Camera, lights: &[&dyn Light]) {
+        self.material.use_uniforms(program, camera, lights);
+        for (name, curve) in self.curves.iter() {
+            curve.use_uniform(program, name, self.time);
+        }
+    }
+
+    fn render_states(&self) -> RenderStates {
+        self.material.render_states()
+    }
+
+    fn material_type(&self) -> MaterialType {
+        self.material.material_type()
+    }
+
+    fn tessellation_shader_source(&self) -> Option<(String, String)> {
+        self.material.tessellation_shader_source()
+    }
+}
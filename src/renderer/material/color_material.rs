@@ -5,7 +5,7 @@ use crate::renderer::*;
 /// A material that renders a [Geometry] in a color defined by multiplying a color with an optional texture and optional per vertex colors.
 /// This material is not affected by lights.
 ///
-#[derive(Clone, Default)]
+#[derive(Clone)]
 pub struct ColorMaterial {
     /// Base surface color.
     pub color: Srgba,
@@ -16,6 +16,11 @@ pub struct ColorMaterial {
     pub render_states: RenderStates,
     /// Whether this material should be treated as a transparent material (An object needs to be rendered differently depending on whether it is transparent or opaque).
     pub is_transparent: bool,
+    /// Optionally dissolves the surface based on a noise texture, see [Dissolve].
+    pub dissolve: Option<Dissolve>,
+    /// A transformation applied to the uv coordinates before the texture lookup, for example for tiling, scrolling or rotating the texture.
+    /// This is applied before the transformation of the [Self::texture].
+    pub uv_transformation: Mat3,
 }
 
 impl ColorMaterial {
@@ -51,6 +56,8 @@ impl ColorMaterial {
             texture,
             is_transparent: false,
             render_states: RenderStates::default(),
+            dissolve: None,
+            uv_transformation: Mat3::identity(),
         }
     }
 
@@ -77,6 +84,8 @@ impl ColorMaterial {
                 blend: Blend::TRANSPARENCY,
                 ..Default::default()
             },
+            dissolve: None,
+            uv_transformation: Mat3::identity(),
         }
     }
 
@@ -87,6 +96,8 @@ impl ColorMaterial {
             texture: physical_material.albedo_texture.clone(),
             render_states: physical_material.render_states,
             is_transparent: physical_material.is_transparent,
+            dissolve: physical_material.dissolve.clone(),
+            uv_transformation: physical_material.uv_transformation,
         }
     }
 }
@@ -99,17 +110,28 @@ impl FromCpuMaterial for ColorMaterial {
 
 impl Material for ColorMaterial {
     fn id(&self) -> u16 {
-        if self.texture.is_some() {
+        let mut id = if self.texture.is_some() {
             0b1u16 << 15
         } else {
             0b1u16 << 15 | 0b1u16
+        };
+        if self.dissolve.is_some() {
+            id |= 0b1u16 << 9;
         }
+        id
     }
 
     fn fragment_shader_source(&self, _lights: &[&dyn Light]) -> String {
         let mut shader = String::new();
         if self.texture.is_some() {
-            shader.push_str("#define USE_TEXTURE\nin vec2 uvs;\n");
+            shader.push_str("#define USE_TEXTURE\n");
+        }
+        if self.texture.is_some() || self.dissolve.is_some() {
+            shader.push_str("in vec2 uvs;\n");
+        }
+        if self.dissolve.is_some() {
+            shader.push_str("#define USE_DISSOLVE\n");
+            shader.push_str(Dissolve::fragment_shader_source());
         }
         shader.push_str(include_str!("../../core/shared.frag"));
         shader.push_str(ColorMapping::fragment_shader_source());
@@ -120,7 +142,7 @@ impl Material for ColorMaterial {
     fn fragment_attributes(&self) -> FragmentAttributes {
         FragmentAttributes {
             color: true,
-            uv: self.texture.is_some(),
+            uv: self.texture.is_some() || self.dissolve.is_some(),
             ..FragmentAttributes::NONE
         }
     }
@@ -129,9 +151,15 @@ impl Material for ColorMaterial {
         camera.color_mapping.use_uniforms(program);
         program.use_uniform("surfaceColor", self.color.to_linear_srgb());
         if let Some(ref tex) = self.texture {
-            program.use_uniform("textureTransformation", tex.transformation);
+            program.use_uniform(
+                "textureTransformation",
+                tex.transformation * self.uv_transformation,
+            );
             program.use_texture("tex", tex);
         }
+        if let Some(ref dissolve) = self.dissolve {
+            dissolve.use_uniforms(program);
+        }
     }
     fn render_states(&self) -> RenderStates {
         self.render_states
@@ -144,3 +172,16 @@ impl Material for ColorMaterial {
         }
     }
 }
+
+impl Default for ColorMaterial {
+    fn default() -> Self {
+        Self {
+            color: Srgba::default(),
+            texture: None,
+            render_states: RenderStates::default(),
+            is_transparent: false,
+            dissolve: None,
+            uv_transformation: Mat3::identity(),
+        }
+    }
+}
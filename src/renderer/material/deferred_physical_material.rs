@@ -47,6 +47,15 @@ pub struct DeferredPhysicalMaterial {
     /// If the alpha value of a pixel touched by an object with this material is less than the threshold, then that object is not contributing to the color of that pixel.
     /// On the other hand, if the alpha value is more than the threshold, then it is contributing fully to that pixel and thereby blocks out everything behind.
     pub alpha_cutout: Option<f32>,
+    /// A transformation applied to the uv coordinates before all texture lookups, for example for tiling, scrolling or rotating the textures.
+    /// This is applied before the transformation of each individual texture.
+    pub uv_transformation: Mat3,
+    /// Optionally dissolves the surface based on a noise texture, see [Dissolve].
+    pub dissolve: Option<Dissolve>,
+    /// Optional detail textures, see [DetailTextures].
+    pub detail: Option<DetailTextures>,
+    /// Optionally displaces the vertices along the normal, see [Displacement].
+    pub displacement: Option<Displacement>,
 }
 
 impl DeferredPhysicalMaterial {
@@ -116,6 +125,10 @@ impl DeferredPhysicalMaterial {
             alpha_cutout: cpu_material.alpha_cutout,
             emissive: cpu_material.emissive,
             emissive_texture,
+            uv_transformation: Mat3::identity(),
+            dissolve: None,
+            detail: None,
+            displacement: None,
         }
     }
 
@@ -146,6 +159,10 @@ impl DeferredPhysicalMaterial {
             } else {
                 None
             },
+            uv_transformation: physical_material.uv_transformation,
+            dissolve: physical_material.dissolve.clone(),
+            detail: physical_material.detail.clone(),
+            displacement: physical_material.displacement.clone(),
         }
     }
     ///
@@ -162,13 +179,48 @@ impl DeferredPhysicalMaterial {
     ) {
         apply_screen_effect(
             context,
-            lighting_pass::LightingPassEffect {},
+            lighting_pass::LightingPassEffect { ssao: None },
             camera,
             lights,
             Some(geometry_pass_color_texture),
             Some(geometry_pass_depth_texture),
         );
     }
+
+    fn detail_albedo_texture(&self) -> Option<&Texture2DRef> {
+        self.detail.as_ref().and_then(|d| d.albedo_texture.as_ref())
+    }
+
+    fn detail_normal_texture(&self) -> Option<&Texture2DRef> {
+        self.detail.as_ref().and_then(|d| d.normal_texture.as_ref())
+    }
+
+    // The break up of the tiling only has an effect if there is a detail texture
+    fn uses_detail_break_up(&self) -> bool {
+        (self.detail_albedo_texture().is_some() || self.detail_normal_texture().is_some())
+            && self
+                .detail
+                .as_ref()
+                .map(|d| d.break_up_tiling)
+                .unwrap_or(false)
+    }
+
+    fn uses_uv(&self) -> bool {
+        self.albedo_texture.is_some()
+            || self.metallic_roughness_texture.is_some()
+            || self.normal_texture.is_some()
+            || self.occlusion_texture.is_some()
+            || self.emissive_texture.is_some()
+            || self.alpha_cutout.is_some()
+            || self.dissolve.is_some()
+            || self.detail_albedo_texture().is_some()
+            || self.detail_normal_texture().is_some()
+            || self.displacement.is_some()
+    }
+
+    fn uses_emissive_intensity(&self) -> bool {
+        self.emissive.r > 0 || self.emissive.g > 0 || self.emissive.b > 0
+    }
 }
 
 impl FromCpuMaterial for DeferredPhysicalMaterial {
@@ -179,7 +231,8 @@ impl FromCpuMaterial for DeferredPhysicalMaterial {
 
 impl Material for DeferredPhysicalMaterial {
     fn id(&self) -> u16 {
-        let mut id = 0b1u16 << 15 | 0b1u16 << 6;
+        // Bit 14 is only used by the PhysicalMaterial together with bit 5 which is never used here, so the ids do not overlap
+        let mut id = 0b1u16 << 15 | 0b1u16 << 14 | 0b1u16 << 6;
         if self.albedo_texture.is_some() {
             id |= 0b1u16;
         }
@@ -195,21 +248,33 @@ impl Material for DeferredPhysicalMaterial {
         if self.emissive_texture.is_some() {
             id |= 0b1u16 << 4;
         }
+        if self.uses_emissive_intensity() {
+            id |= 0b1u16 << 7;
+        }
         if self.alpha_cutout.is_some() {
-            id |= 0b1u16 << 5;
+            id |= 0b1u16 << 8;
+        }
+        if self.dissolve.is_some() {
+            id |= 0b1u16 << 9;
+        }
+        if self.displacement.is_some() {
+            id |= 0b1u16 << 10;
+        }
+        if self.uses_detail_break_up() {
+            id |= 0b1u16 << 11;
+        }
+        if self.detail_albedo_texture().is_some() {
+            id |= 0b1u16 << 12;
+        }
+        if self.detail_normal_texture().is_some() {
+            id |= 0b1u16 << 13;
         }
         id
     }
 
     fn fragment_shader_source(&self, _lights: &[&dyn Light]) -> String {
         let mut output = include_str!("../../core/shared.frag").to_string();
-        if self.albedo_texture.is_some()
-            || self.metallic_roughness_texture.is_some()
-            || self.normal_texture.is_some()
-            || self.occlusion_texture.is_some()
-            || self.emissive_texture.is_some()
-            || self.alpha_cutout.is_some()
-        {
+        if self.uses_uv() {
             output.push_str("in vec2 uvs;\n");
             if self.albedo_texture.is_some() {
                 output.push_str("#define USE_ALBEDO_TEXTURE;\n");
@@ -221,7 +286,26 @@ impl Material for DeferredPhysicalMaterial {
                 output.push_str("#define USE_OCCLUSION_TEXTURE;\n");
             }
             if self.normal_texture.is_some() {
-                output.push_str("#define USE_NORMAL_TEXTURE;\nin vec3 tang;\nin vec3 bitang;\n");
+                output.push_str("#define USE_NORMAL_TEXTURE;\n");
+            }
+            if self.normal_texture.is_some() || self.detail_normal_texture().is_some() {
+                output.push_str("in vec3 tang;\nin vec3 bitang;\n");
+            }
+            if self.detail_albedo_texture().is_some() {
+                output.push_str("#define USE_DETAIL_ALBEDO_TEXTURE;\n");
+            }
+            if self.detail_normal_texture().is_some() {
+                output.push_str("#define USE_DETAIL_NORMAL_TEXTURE;\n");
+            }
+            if self.uses_detail_break_up() {
+                output.push_str("#define USE_DETAIL_BREAK_UP;\n");
+            }
+            if self.detail_albedo_texture().is_some() || self.detail_normal_texture().is_some() {
+                output.push_str(DetailTextures::fragment_shader_source());
+            }
+            if self.displacement.is_some() {
+                output.push_str("#define USE_DISPLACEMENT;\n");
+                output.push_str(Displacement::fragment_shader_source());
             }
             if self.emissive_texture.is_some() {
                 output.push_str("#define USE_EMISSIVE_TEXTURE;\n");
@@ -235,6 +319,13 @@ impl Material for DeferredPhysicalMaterial {
                     .as_str(),
                 );
             }
+            if self.dissolve.is_some() {
+                output.push_str("#define USE_DISSOLVE;\n");
+                output.push_str(Dissolve::fragment_shader_source());
+            }
+        }
+        if self.uses_emissive_intensity() {
+            output.push_str("#define USE_EMISSIVE_INTENSITY;\nin float emissive_intensity;\n");
         }
         output.push_str(include_str!("shaders/deferred_physical_material.frag"));
         output
@@ -245,13 +336,12 @@ impl Material for DeferredPhysicalMaterial {
             position: true,
             normal: true,
             color: true,
-            uv: self.albedo_texture.is_some()
-                || self.metallic_roughness_texture.is_some()
-                || self.normal_texture.is_some()
-                || self.occlusion_texture.is_some()
-                || self.emissive_texture.is_some()
-                || self.alpha_cutout.is_some(),
-            tangents: self.normal_texture.is_some(),
+            uv: self.uses_uv(),
+            tangents: self.normal_texture.is_some() || self.detail_normal_texture().is_some(),
+            uv1: false,
+            displacement: self.displacement.is_some(),
+            emissive_intensity: self.uses_emissive_intensity(),
+            instance_id: false,
         }
     }
 
@@ -262,28 +352,52 @@ impl Material for DeferredPhysicalMaterial {
         program.use_uniform("emissive", self.emissive.to_linear_srgb());
         if let Some(ref texture) = self.albedo_texture {
             program.use_texture("albedoTexture", texture);
-            program.use_uniform("albedoTexTransform", texture.transformation);
+            program.use_uniform(
+                "albedoTexTransform",
+                texture.transformation * self.uv_transformation,
+            );
         }
         if let Some(ref texture) = self.metallic_roughness_texture {
             program.use_texture("metallicRoughnessTexture", texture);
-            program.use_uniform("metallicRoughnessTexTransform", texture.transformation);
+            program.use_uniform(
+                "metallicRoughnessTexTransform",
+                texture.transformation * self.uv_transformation,
+            );
         }
         if let Some(ref texture) = self.occlusion_texture {
             program.use_uniform("occlusionStrength", self.occlusion_strength);
-            program.use_uniform("occlusionTexTransform", texture.transformation);
+            program.use_uniform(
+                "occlusionTexTransform",
+                texture.transformation * self.uv_transformation,
+            );
             program.use_texture("occlusionTexture", texture);
         }
         if let Some(ref texture) = self.normal_texture {
             program.use_uniform("normalScale", self.normal_scale);
-            program.use_uniform("normalTexTransform", texture.transformation);
+            program.use_uniform(
+                "normalTexTransform",
+                texture.transformation * self.uv_transformation,
+            );
             program.use_texture("normalTexture", texture);
         }
         if program.requires_uniform("emissiveTexture") {
             if let Some(ref texture) = self.emissive_texture {
-                program.use_uniform("emissiveTexTransform", texture.transformation);
+                program.use_uniform(
+                    "emissiveTexTransform",
+                    texture.transformation * self.uv_transformation,
+                );
                 program.use_texture("emissiveTexture", texture);
             }
         }
+        if let Some(ref dissolve) = self.dissolve {
+            dissolve.use_uniforms(program);
+        }
+        if let Some(ref detail) = self.detail {
+            detail.use_uniforms(program, self.uv_transformation);
+        }
+        if let Some(ref displacement) = self.displacement {
+            displacement.use_uniforms(program);
+        }
     }
 
     fn render_states(&self) -> RenderStates {
@@ -312,6 +426,10 @@ impl Default for DeferredPhysicalMaterial {
             alpha_cutout: None,
             emissive: Srgba::BLACK,
             emissive_texture: None,
+            uv_transformation: Mat3::identity(),
+            dissolve: None,
+            detail: None,
+            displacement: None,
         }
     }
 }
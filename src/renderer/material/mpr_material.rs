@@ -0,0 +1,101 @@
+use crate::core::*;
+use crate::renderer::*;
+use std::sync::Arc;
+
+///
+/// A material which shows the values of a [Texture3D] on a surface cutting through the volume, usually a plane, as used in multi-planar reconstruction (MPR), see [MprViews].
+/// The values are mapped to gray scale using a window and level, so the given range of values is shown from black to white,
+/// and a crosshair can be drawn at the position shared between the views.
+/// Surface points outside the volume are discarded.
+///
+#[derive(Clone)]
+pub struct MprMaterial {
+    /// The voxel data.
+    pub volume: Arc<Texture3D>,
+    /// The transformation from world space to the texture coordinates of the volume, ie. the volume covers the box from `(0, 0, 0)` to `(1, 1, 1)` after the transformation.
+    pub texture_transformation: Mat4,
+    /// The width of the range of values shown from black to white.
+    pub window: f32,
+    /// The center of the range of values shown from black to white.
+    pub level: f32,
+    /// The center of the crosshair in the texture coordinates of the volume.
+    pub crosshair: Vec3,
+    /// The width in pixels of the crosshair lines or zero to disable the crosshair.
+    pub crosshair_width: f32,
+    /// The color of the crosshair line along each of the x, y and z axis of the volume.
+    pub crosshair_colors: [Srgba; 3],
+    /// The axis of the volume (0 for x, 1 for y and 2 for z) which is orthogonal to the surface, so no crosshair line is drawn along that axis.
+    pub slice_axis: usize,
+    /// Render states.
+    pub render_states: RenderStates,
+}
+
+impl MprMaterial {
+    ///
+    /// Creates a new material showing the given volume with the given transformation from world space to texture coordinates.
+    /// The window covers all values and the crosshair is disabled.
+    ///
+    pub fn new(volume: Arc<Texture3D>, texture_transformation: Mat4) -> Self {
+        Self {
+            volume,
+            texture_transformation,
+            window: 1.0,
+            level: 0.5,
+            crosshair: vec3(0.5, 0.5, 0.5),
+            crosshair_width: 0.0,
+            crosshair_colors: [Srgba::RED, Srgba::GREEN, Srgba::BLUE],
+            slice_axis: 2,
+            render_states: RenderStates {
+                cull: Cull::None,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+impl Material for MprMaterial {
+    fn id(&self) -> u16 {
+        0b1u16 << 15 | 0b1u16 << 12 | 0b0100u16
+    }
+
+    fn fragment_shader_source(&self, _lights: &[&dyn Light]) -> String {
+        let mut source = ColorMapping::fragment_shader_source().to_owned();
+        source.push_str(include_str!("shaders/mpr_material.frag"));
+        source
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            position: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(&self, program: &Program, camera: &Camera, _lights: &[&dyn Light]) {
+        camera.color_mapping.use_uniforms(program);
+        program.use_texture_3d("volume", &self.volume);
+        program.use_uniform("textureTransformation", self.texture_transformation);
+        program.use_uniform(
+            "windowRange",
+            vec2(
+                self.level - 0.5 * self.window,
+                self.level + 0.5 * self.window,
+            ),
+        );
+        program.use_uniform("crosshair", self.crosshair);
+        program.use_uniform("crosshairWidth", self.crosshair_width);
+        program.use_uniform("sliceAxis", self.slice_axis as i32);
+        program.use_uniform_array(
+            "crosshairColors",
+            &self.crosshair_colors.map(|c| c.to_linear_srgb().truncate()),
+        );
+    }
+
+    fn render_states(&self) -> RenderStates {
+        self.render_states
+    }
+
+    fn material_type(&self) -> MaterialType {
+        MaterialType::Opaque
+    }
+}
@@ -0,0 +1,74 @@
+use crate::core::*;
+use crate::renderer::*;
+
+///
+/// A material which colors the surface by the overhang angle relative to a build direction, for example to visualise which parts of a 3D print requires support.
+/// The overhang angle is measured from the vertical, ie. it is 0 degrees for a vertical wall and 90 degrees for a surface facing straight down.
+/// Surfaces with an overhang angle below the critical angle are colored with the supported color while surfaces above are colored
+/// with a gradient towards the overhang color, which is reached when the surface faces straight down.
+/// The surface is shaded by the view direction to make the shape visible, so this material is not affected by lights.
+///
+#[derive(Clone, Debug)]
+pub struct OverhangMaterial {
+    /// The build direction, for example the y-axis when the build plate is placed in the xz-plane.
+    pub build_direction: Vec3,
+    /// The maximum overhang angle which can be printed without support.
+    pub critical_angle: Radians,
+    /// The color of the surfaces which do not require support.
+    pub supported_color: Srgba,
+    /// The color of the surfaces facing straight down.
+    pub overhang_color: Srgba,
+    /// Render states.
+    pub render_states: RenderStates,
+}
+
+impl Default for OverhangMaterial {
+    fn default() -> Self {
+        Self {
+            build_direction: vec3(0.0, 1.0, 0.0),
+            critical_angle: degrees(45.0).into(),
+            supported_color: Srgba::new_opaque(200, 200, 200),
+            overhang_color: Srgba::RED,
+            render_states: RenderStates::default(),
+        }
+    }
+}
+
+impl Material for OverhangMaterial {
+    fn id(&self) -> u16 {
+        0b1u16 << 15 | 0b1011u16
+    }
+
+    fn fragment_shader_source(&self, _lights: &[&dyn Light]) -> String {
+        format!(
+            "{}{}",
+            ColorMapping::fragment_shader_source(),
+            include_str!("shaders/overhang_material.frag")
+        )
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            position: true,
+            normal: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(&self, program: &Program, camera: &Camera, _lights: &[&dyn Light]) {
+        camera.color_mapping.use_uniforms(program);
+        program.use_uniform("eye", camera.position());
+        program.use_uniform("buildDirection", self.build_direction.normalize());
+        program.use_uniform("criticalAngle", self.critical_angle.0);
+        program.use_uniform("supportedColor", self.supported_color.to_linear_srgb());
+        program.use_uniform("overhangColor", self.overhang_color.to_linear_srgb());
+    }
+
+    fn render_states(&self) -> RenderStates {
+        self.render_states
+    }
+
+    fn material_type(&self) -> MaterialType {
+        MaterialType::Opaque
+    }
+}
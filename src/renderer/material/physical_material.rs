@@ -1,6 +1,51 @@
 use crate::core::*;
 use crate::renderer::*;
 
+///
+/// Detail textures of a [PhysicalMaterial] or a [DeferredPhysicalMaterial] which are sampled at a high tiling frequency and blended with the other textures of the material,
+/// so large surfaces, for example terrain or walls, do not look blurry up close.
+///
+#[derive(Clone)]
+pub struct DetailTextures {
+    /// Detail albedo texture which is multiplied onto the albedo color, where a value of 0.5 means no change.
+    pub albedo_texture: Option<Texture2DRef>,
+    /// Detail tangent space normal map which is blended with the normal from the [PhysicalMaterial::normal_texture].
+    pub normal_texture: Option<Texture2DRef>,
+    /// A scalar multiplier applied to each normal vector of the [Self::normal_texture].
+    pub normal_scale: f32,
+    /// How many times the detail textures are repeated in the uv coordinate range `[0..1]`.
+    pub tiling: f32,
+    /// Whether or not to break up the visible repetition of the detail textures by sampling each tile with a random offset (also called texture bombing).
+    pub break_up_tiling: bool,
+}
+
+impl DetailTextures {
+    pub(in crate::renderer) fn fragment_shader_source() -> &'static str {
+        include_str!("shaders/detail.frag")
+    }
+
+    pub(in crate::renderer) fn use_uniforms(&self, program: &Program, uv_transformation: Mat3) {
+        let tiling = Mat3::from_cols(
+            vec3(self.tiling, 0.0, 0.0),
+            vec3(0.0, self.tiling, 0.0),
+            vec3(0.0, 0.0, 1.0),
+        ) * uv_transformation;
+        if program.requires_uniform("detailAlbedoTexture") {
+            if let Some(ref texture) = self.albedo_texture {
+                program.use_uniform("detailAlbedoTexTransform", texture.transformation * tiling);
+                program.use_texture("detailAlbedoTexture", texture);
+            }
+        }
+        if program.requires_uniform("detailNormalTexture") {
+            if let Some(ref texture) = self.normal_texture {
+                program.use_uniform("detailNormalTexTransform", texture.transformation * tiling);
+                program.use_uniform("detailNormalScale", self.normal_scale);
+                program.use_texture("detailNormalTexture", texture);
+            }
+        }
+    }
+}
+
 ///
 /// A physically-based material that renders a [Geometry] in an approximate correct physical manner based on Physically Based Rendering (PBR).
 /// This material is affected by lights.
@@ -26,6 +71,8 @@ pub struct PhysicalMaterial {
     /// An occlusion map. Higher values indicate areas that should receive full indirect lighting and lower values indicate no indirect lighting.
     /// The occlusion values are sampled from the red channel.
     pub occlusion_texture: Option<Texture2DRef>,
+    /// The set of uv coordinates used for sampling the [Self::occlusion_texture].
+    pub occlusion_uv_channel: UvChannel,
     /// A scalar multiplier applied to each normal vector of the [Self::normal_texture].
     pub normal_scale: f32,
     /// A tangent space normal map, also known as bump map.
@@ -39,8 +86,27 @@ pub struct PhysicalMaterial {
     /// Texture with color of light shining from an object.
     /// The colors are assumed to be in linear sRGB (`RgbU8`), linear sRGB with an alpha channel (`RgbaU8`) or HDR color space.
     pub emissive_texture: Option<Texture2DRef>,
+    /// The set of uv coordinates used for sampling the [Self::emissive_texture], for example [UvChannel::Uv1] if the emissive texture is a light map.
+    pub emissive_uv_channel: UvChannel,
     /// The lighting model used when rendering this material
     pub lighting_model: LightingModel,
+    /// Optionally dissolves the surface based on a noise texture, see [Dissolve].
+    pub dissolve: Option<Dissolve>,
+    /// A transformation applied to the uv coordinates before all texture lookups, for example for tiling, scrolling or rotating the textures.
+    /// This is applied before the transformation of each individual texture.
+    pub uv_transformation: Mat3,
+    /// Optional detail textures, see [DetailTextures].
+    pub detail: Option<DetailTextures>,
+    /// Optionally displaces the vertices along the normal, see [Displacement].
+    pub displacement: Option<Displacement>,
+    /// A light map containing baked lighting which is multiplied with the albedo and added to the lighting calculated from the lights.
+    /// Use together with [LightingMode::BakedIndirect] or [LightingMode::Baked] to avoid that the baked lights are also applied in real-time.
+    /// The colors are assumed to be in linear sRGB or HDR color space.
+    pub light_map_texture: Option<Texture2DRef>,
+    /// The set of uv coordinates used for sampling the [Self::light_map_texture].
+    pub light_map_uv_channel: UvChannel,
+    /// A scalar multiplier applied to the light in the [Self::light_map_texture].
+    pub light_map_intensity: f32,
 }
 
 impl PhysicalMaterial {
@@ -126,6 +192,7 @@ impl PhysicalMaterial {
             normal_scale: cpu_material.normal_scale,
             occlusion_texture,
             occlusion_strength: cpu_material.occlusion_strength,
+            occlusion_uv_channel: UvChannel::Uv0,
             render_states: if is_transparent {
                 RenderStates {
                     write_mask: WriteMask::COLOR,
@@ -138,9 +205,58 @@ impl PhysicalMaterial {
             is_transparent,
             emissive: cpu_material.emissive,
             emissive_texture,
+            emissive_uv_channel: UvChannel::Uv0,
             lighting_model: cpu_material.lighting_model,
+            dissolve: None,
+            uv_transformation: Mat3::identity(),
+            detail: None,
+            displacement: None,
+            light_map_texture: None,
+            light_map_uv_channel: UvChannel::Uv1,
+            light_map_intensity: 1.0,
         }
     }
+
+    fn detail_albedo_texture(&self) -> Option<&Texture2DRef> {
+        self.detail.as_ref().and_then(|d| d.albedo_texture.as_ref())
+    }
+
+    fn detail_normal_texture(&self) -> Option<&Texture2DRef> {
+        self.detail.as_ref().and_then(|d| d.normal_texture.as_ref())
+    }
+
+    // The break up of the tiling only has an effect if there is a detail texture
+    fn uses_detail_break_up(&self) -> bool {
+        (self.detail_albedo_texture().is_some() || self.detail_normal_texture().is_some())
+            && self
+                .detail
+                .as_ref()
+                .map(|d| d.break_up_tiling)
+                .unwrap_or(false)
+    }
+
+    fn uses_uv1(&self) -> bool {
+        (self.occlusion_texture.is_some() && self.occlusion_uv_channel == UvChannel::Uv1)
+            || (self.emissive_texture.is_some() && self.emissive_uv_channel == UvChannel::Uv1)
+            || (self.light_map_texture.is_some() && self.light_map_uv_channel == UvChannel::Uv1)
+    }
+
+    fn uses_uv(&self) -> bool {
+        self.albedo_texture.is_some()
+            || self.metallic_roughness_texture.is_some()
+            || self.normal_texture.is_some()
+            || self.occlusion_texture.is_some()
+            || self.emissive_texture.is_some()
+            || self.dissolve.is_some()
+            || self.detail_albedo_texture().is_some()
+            || self.detail_normal_texture().is_some()
+            || self.displacement.is_some()
+            || self.light_map_texture.is_some()
+    }
+
+    fn uses_emissive_intensity(&self) -> bool {
+        self.emissive.r > 0 || self.emissive.g > 0 || self.emissive.b > 0
+    }
 }
 
 impl FromCpuMaterial for PhysicalMaterial {
@@ -149,6 +265,13 @@ impl FromCpuMaterial for PhysicalMaterial {
     }
 }
 
+impl Upload for CpuMaterial {
+    type Output = PhysicalMaterial;
+    fn upload(self, context: &Context) -> Self::Output {
+        PhysicalMaterial::new(context, &self)
+    }
+}
+
 impl Material for PhysicalMaterial {
     fn id(&self) -> u16 {
         let mut id = 0b1u16 << 15 | 0b1u16 << 5;
@@ -167,17 +290,39 @@ impl Material for PhysicalMaterial {
         if self.emissive_texture.is_some() {
             id |= 0b1u16 << 4;
         }
+        if self.dissolve.is_some() {
+            id |= 0b1u16 << 9;
+        }
+        if self.occlusion_texture.is_some() && self.occlusion_uv_channel == UvChannel::Uv1 {
+            id |= 0b1u16 << 10;
+        }
+        if self.emissive_texture.is_some() && self.emissive_uv_channel == UvChannel::Uv1 {
+            id |= 0b1u16 << 11;
+        }
+        if self.detail_albedo_texture().is_some() {
+            id |= 0b1u16 << 12;
+        }
+        if self.detail_normal_texture().is_some() {
+            id |= 0b1u16 << 13;
+        }
+        if self.uses_detail_break_up() {
+            id |= 0b1u16 << 14;
+        }
+        if self.displacement.is_some() {
+            id |= 0b1u16 << 7;
+        }
+        if self.uses_emissive_intensity() {
+            id |= 0b1u16 << 8;
+        }
+        if self.light_map_texture.is_some() {
+            id |= 0b1u16 << 6;
+        }
         id
     }
 
     fn fragment_shader_source(&self, lights: &[&dyn Light]) -> String {
         let mut output = lights_shader_source(lights, self.lighting_model);
-        if self.albedo_texture.is_some()
-            || self.metallic_roughness_texture.is_some()
-            || self.normal_texture.is_some()
-            || self.occlusion_texture.is_some()
-            || self.emissive_texture.is_some()
-        {
+        if self.uses_uv() {
             output.push_str("in vec2 uvs;\n");
             if self.albedo_texture.is_some() {
                 output.push_str("#define USE_ALBEDO_TEXTURE;\n");
@@ -187,13 +332,57 @@ impl Material for PhysicalMaterial {
             }
             if self.occlusion_texture.is_some() {
                 output.push_str("#define USE_OCCLUSION_TEXTURE;\n");
+                output.push_str(match self.occlusion_uv_channel {
+                    UvChannel::Uv0 => "#define OCCLUSION_UVS uvs\n",
+                    UvChannel::Uv1 => "#define OCCLUSION_UVS uvs1\n",
+                });
             }
             if self.normal_texture.is_some() {
-                output.push_str("#define USE_NORMAL_TEXTURE;\nin vec3 tang;\nin vec3 bitang;\n");
+                output.push_str("#define USE_NORMAL_TEXTURE;\n");
+            }
+            if self.normal_texture.is_some() || self.detail_normal_texture().is_some() {
+                output.push_str("in vec3 tang;\nin vec3 bitang;\n");
+            }
+            if self.detail_albedo_texture().is_some() {
+                output.push_str("#define USE_DETAIL_ALBEDO_TEXTURE;\n");
+            }
+            if self.detail_normal_texture().is_some() {
+                output.push_str("#define USE_DETAIL_NORMAL_TEXTURE;\n");
+            }
+            if self.uses_detail_break_up() {
+                output.push_str("#define USE_DETAIL_BREAK_UP;\n");
+            }
+            if self.detail_albedo_texture().is_some() || self.detail_normal_texture().is_some() {
+                output.push_str(DetailTextures::fragment_shader_source());
+            }
+            if self.displacement.is_some() {
+                output.push_str("#define USE_DISPLACEMENT;\n");
+                output.push_str(Displacement::fragment_shader_source());
             }
             if self.emissive_texture.is_some() {
                 output.push_str("#define USE_EMISSIVE_TEXTURE;\n");
+                output.push_str(match self.emissive_uv_channel {
+                    UvChannel::Uv0 => "#define EMISSIVE_UVS uvs\n",
+                    UvChannel::Uv1 => "#define EMISSIVE_UVS uvs1\n",
+                });
             }
+            if self.light_map_texture.is_some() {
+                output.push_str("#define USE_LIGHT_MAP_TEXTURE;\n");
+                output.push_str(match self.light_map_uv_channel {
+                    UvChannel::Uv0 => "#define LIGHT_MAP_UVS uvs\n",
+                    UvChannel::Uv1 => "#define LIGHT_MAP_UVS uvs1\n",
+                });
+            }
+            if self.uses_uv1() {
+                output.push_str("in vec2 uvs1;\n");
+            }
+            if self.dissolve.is_some() {
+                output.push_str("#define USE_DISSOLVE;\n");
+                output.push_str(Dissolve::fragment_shader_source());
+            }
+        }
+        if self.uses_emissive_intensity() {
+            output.push_str("#define USE_EMISSIVE_INTENSITY;\nin float emissive_intensity;\n");
         }
         output.push_str(ToneMapping::fragment_shader_source());
         output.push_str(ColorMapping::fragment_shader_source());
@@ -206,19 +395,18 @@ impl Material for PhysicalMaterial {
             position: true,
             normal: true,
             color: true,
-            uv: self.albedo_texture.is_some()
-                || self.metallic_roughness_texture.is_some()
-                || self.normal_texture.is_some()
-                || self.occlusion_texture.is_some()
-                || self.emissive_texture.is_some(),
-            tangents: self.normal_texture.is_some(),
+            uv: self.uses_uv(),
+            tangents: self.normal_texture.is_some() || self.detail_normal_texture().is_some(),
+            uv1: self.uses_uv1(),
+            displacement: self.displacement.is_some(),
+            emissive_intensity: self.uses_emissive_intensity(),
+            instance_id: false,
         }
     }
 
     fn use_uniforms(&self, program: &Program, camera: &Camera, lights: &[&dyn Light]) {
-        camera.tone_mapping.use_uniforms(program);
-        camera.color_mapping.use_uniforms(program);
-        if !lights.is_empty() {
+        camera.use_tone_and_color_mapping_uniforms(program);
+        if !lights.is_empty() || self.light_map_texture.is_some() {
             program.use_uniform_if_required("cameraPosition", camera.position());
             for (i, light) in lights.iter().enumerate() {
                 light.use_uniforms(program, i as u32);
@@ -227,39 +415,71 @@ impl Material for PhysicalMaterial {
             program.use_uniform_if_required("roughness", self.roughness);
             if program.requires_uniform("albedoTexture") {
                 if let Some(ref texture) = self.albedo_texture {
-                    program.use_uniform("albedoTexTransform", texture.transformation);
+                    program.use_uniform(
+                        "albedoTexTransform",
+                        texture.transformation * self.uv_transformation,
+                    );
                     program.use_texture("albedoTexture", texture);
                 }
             }
             if program.requires_uniform("metallicRoughnessTexture") {
                 if let Some(ref texture) = self.metallic_roughness_texture {
-                    program.use_uniform("metallicRoughnessTexTransform", texture.transformation);
+                    program.use_uniform(
+                        "metallicRoughnessTexTransform",
+                        texture.transformation * self.uv_transformation,
+                    );
                     program.use_texture("metallicRoughnessTexture", texture);
                 }
             }
             if program.requires_uniform("occlusionTexture") {
                 if let Some(ref texture) = self.occlusion_texture {
-                    program.use_uniform("occlusionTexTransform", texture.transformation);
+                    program.use_uniform(
+                        "occlusionTexTransform",
+                        texture.transformation * self.uv_transformation,
+                    );
                     program.use_uniform("occlusionStrength", self.occlusion_strength);
                     program.use_texture("occlusionTexture", texture);
                 }
             }
             if program.requires_uniform("normalTexture") {
                 if let Some(ref texture) = self.normal_texture {
-                    program.use_uniform("normalTexTransform", texture.transformation);
+                    program.use_uniform(
+                        "normalTexTransform",
+                        texture.transformation * self.uv_transformation,
+                    );
                     program.use_uniform("normalScale", self.normal_scale);
                     program.use_texture("normalTexture", texture);
                 }
             }
         }
+        if let Some(ref detail) = self.detail {
+            detail.use_uniforms(program, self.uv_transformation);
+        }
+        if let Some(ref displacement) = self.displacement {
+            displacement.use_uniforms(program);
+        }
         program.use_uniform("albedo", self.albedo.to_linear_srgb());
         program.use_uniform("emissive", self.emissive.to_linear_srgb());
         if program.requires_uniform("emissiveTexture") {
             if let Some(ref texture) = self.emissive_texture {
-                program.use_uniform("emissiveTexTransform", texture.transformation);
+                program.use_uniform(
+                    "emissiveTexTransform",
+                    texture.transformation * self.uv_transformation,
+                );
                 program.use_texture("emissiveTexture", texture);
             }
         }
+        if let Some(ref dissolve) = self.dissolve {
+            dissolve.use_uniforms(program);
+        }
+        if let Some(ref texture) = self.light_map_texture {
+            program.use_uniform(
+                "lightMapTexTransform",
+                texture.transformation * self.uv_transformation,
+            );
+            program.use_uniform("lightMapIntensity", self.light_map_intensity);
+            program.use_texture("lightMapTexture", texture);
+        }
     }
 
     fn render_states(&self) -> RenderStates {
@@ -287,11 +507,20 @@ impl Default for PhysicalMaterial {
             normal_scale: 1.0,
             occlusion_texture: None,
             occlusion_strength: 1.0,
+            occlusion_uv_channel: UvChannel::Uv0,
             render_states: RenderStates::default(),
             is_transparent: false,
             emissive: Srgba::BLACK,
             emissive_texture: None,
+            emissive_uv_channel: UvChannel::Uv0,
             lighting_model: LightingModel::Blinn,
+            dissolve: None,
+            uv_transformation: Mat3::identity(),
+            detail: None,
+            displacement: None,
+            light_map_texture: None,
+            light_map_uv_channel: UvChannel::Uv1,
+            light_map_intensity: 1.0,
         }
     }
 }
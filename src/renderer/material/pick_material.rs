@@ -0,0 +1,90 @@
+use crate::core::*;
+use crate::renderer::*;
+
+///
+/// A material which writes an object id, the instance index and the linear depth into a floating point color target, used for GPU picking, see [Picker].
+/// The red channel contains the id, the green channel contains the index of the instance or -1 if not instanced
+/// and the blue channel contains the distance from the camera along the view direction.
+///
+#[derive(Clone, Debug)]
+pub struct PickMaterial {
+    /// The id written for every fragment. Must be smaller than `2^24` to be represented exactly by a 32 bit float.
+    pub id: u32,
+    /// Whether or not to write the instance index, which is only supported by [InstancedMesh].
+    pub instanced: bool,
+    /// Render states.
+    pub render_states: RenderStates,
+}
+
+impl PickMaterial {
+    ///
+    /// Creates a new pick material writing the given id.
+    ///
+    pub fn new(id: u32, instanced: bool) -> Self {
+        Self {
+            id,
+            instanced,
+            render_states: RenderStates::default(),
+        }
+    }
+}
+
+impl Material for PickMaterial {
+    fn id(&self) -> u16 {
+        if self.instanced {
+            0b1u16 << 15 | 0b1u16 << 12 | 0b0011u16
+        } else {
+            0b1u16 << 15 | 0b1u16 << 12 | 0b0010u16
+        }
+    }
+
+    fn fragment_shader_source(&self, _lights: &[&dyn Light]) -> String {
+        format!(
+            "{}
+            uniform float objectId;
+            uniform vec3 eye;
+            uniform vec3 viewDirection;
+
+            in vec3 pos;
+
+            layout (location = 0) out vec4 outColor;
+
+            void main()
+            {{
+            #ifdef INSTANCED
+                float instance = float(instance_id);
+            #else
+                float instance = -1.0;
+            #endif
+                outColor = vec4(objectId, instance, dot(pos - eye, viewDirection), 1.0);
+            }}",
+            if self.instanced {
+                "#define INSTANCED\nflat in int instance_id;"
+            } else {
+                ""
+            }
+        )
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            position: true,
+            instance_id: self.instanced,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(&self, program: &Program, camera: &Camera, _lights: &[&dyn Light]) {
+        program.use_uniform("objectId", self.id as f32);
+        program.use_uniform("eye", camera.position());
+        program.use_uniform("viewDirection", camera.view_direction());
+    }
+
+    fn render_states(&self) -> RenderStates {
+        self.render_states
+    }
+
+    fn material_type(&self) -> MaterialType {
+        MaterialType::Opaque
+    }
+}
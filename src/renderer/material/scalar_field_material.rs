@@ -0,0 +1,273 @@
+use crate::core::*;
+use crate::renderer::*;
+
+///
+/// A color map which maps a scalar value in the range `[0, 1]` to a color, used for visualising scalar fields, see [ScalarFieldMaterial].
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum ColorMap {
+    /// The perceptually uniform viridis color map from dark blue through green to yellow.
+    #[default]
+    Viridis,
+    /// The perceptually uniform plasma color map from dark blue through purple and orange to yellow.
+    Plasma,
+    /// The diverging cool to warm color map from blue through light gray to red, which is suited for values that deviate from a center value.
+    CoolWarm,
+}
+
+impl ColorMap {
+    // Evenly spaced samples of the color maps in sRGB
+    fn stops(&self) -> [[f32; 3]; 9] {
+        match self {
+            Self::Viridis => [
+                [0.267, 0.005, 0.329],
+                [0.283, 0.141, 0.458],
+                [0.254, 0.265, 0.530],
+                [0.207, 0.372, 0.553],
+                [0.164, 0.471, 0.558],
+                [0.128, 0.567, 0.551],
+                [0.135, 0.659, 0.518],
+                [0.267, 0.749, 0.441],
+                [0.993, 0.906, 0.144],
+            ],
+            Self::Plasma => [
+                [0.050, 0.030, 0.528],
+                [0.255, 0.014, 0.615],
+                [0.418, 0.001, 0.658],
+                [0.563, 0.052, 0.642],
+                [0.693, 0.165, 0.565],
+                [0.798, 0.280, 0.470],
+                [0.881, 0.393, 0.383],
+                [0.949, 0.518, 0.296],
+                [0.940, 0.975, 0.131],
+            ],
+            Self::CoolWarm => [
+                [0.230, 0.299, 0.754],
+                [0.348, 0.466, 0.888],
+                [0.484, 0.622, 0.975],
+                [0.619, 0.744, 0.999],
+                [0.865, 0.865, 0.865],
+                [0.968, 0.721, 0.612],
+                [0.957, 0.598, 0.477],
+                [0.887, 0.394, 0.300],
+                [0.706, 0.016, 0.150],
+            ],
+        }
+    }
+
+    ///
+    /// Returns the color of the given value in the range `[0, 1]`. Values outside the range are clamped.
+    ///
+    pub fn color(&self, t: f32) -> Srgba {
+        let stops = self.stops();
+        let x = t.clamp(0.0, 1.0) * 8.0;
+        let i = (x as usize).min(7);
+        let f = x - i as f32;
+        let c = |j: usize| stops[i][j] * (1.0 - f) + stops[i + 1][j] * f;
+        Srgba::new_opaque(
+            (c(0) * 255.0).round() as u8,
+            (c(1) * 255.0).round() as u8,
+            (c(2) * 255.0).round() as u8,
+        )
+    }
+
+    fn linear_stops(&self) -> Vec<Vec3> {
+        self.stops()
+            .iter()
+            .map(|c| {
+                Srgba::new_opaque(
+                    (c[0] * 255.0).round() as u8,
+                    (c[1] * 255.0).round() as u8,
+                    (c[2] * 255.0).round() as u8,
+                )
+                .to_linear_srgb()
+                .truncate()
+            })
+            .collect()
+    }
+}
+
+///
+/// Attaches the given per-vertex scalar values, for example the stress or temperature from a FEM or CFD simulation, to the given mesh so that it can be rendered with a [ScalarFieldMaterial].
+/// The scalar values are stored in the x-component of the uv coordinates of the mesh, which means that any existing uv coordinates are replaced.
+///
+/// # Panics
+/// If the number of scalar values does not match the number of vertices.
+///
+pub fn set_vertex_scalars(cpu_mesh: &mut CpuMesh, scalars: &[f32]) {
+    assert_eq!(
+        scalars.len(),
+        cpu_mesh.vertex_count(),
+        "the number of scalar values must match the number of vertices"
+    );
+    cpu_mesh.uvs = Some(scalars.iter().map(|s| vec2(*s, 0.0)).collect());
+}
+
+///
+/// A material which visualises a per-vertex scalar field, attached to the mesh using [set_vertex_scalars], through a [ColorMap].
+/// The scalar values are interpolated across the triangles and the value at each fragment is mapped from the range given by the minimum and maximum value to a color.
+/// Optionally, contour lines are drawn at a regular interval.
+/// The surface is shaded by the view direction to make the shape visible, so this material is not affected by lights.
+///
+/// Use [ColorMapLegend] to create a legend which explains the colors.
+///
+#[derive(Clone, Debug)]
+pub struct ScalarFieldMaterial {
+    /// The color map used to map the scalar values to colors.
+    pub color_map: ColorMap,
+    /// The scalar value which is mapped to the first color of the color map. Smaller values are clamped.
+    pub min: f32,
+    /// The scalar value which is mapped to the last color of the color map. Larger values are clamped.
+    pub max: f32,
+    /// The interval between the contour lines in the same unit as the scalar values, starting at the minimum value, or `None` to disable the contour lines.
+    pub contour_interval: Option<f32>,
+    /// The color of the contour lines.
+    pub contour_color: Srgba,
+    /// Render states.
+    pub render_states: RenderStates,
+}
+
+impl ScalarFieldMaterial {
+    ///
+    /// Creates a new scalar field material with the given color map where the range is the minimum and maximum of the given scalar values.
+    ///
+    pub fn new_with_range_of(color_map: ColorMap, scalars: &[f32]) -> Self {
+        let (min, max) = scalars
+            .iter()
+            .filter(|s| s.is_finite())
+            .fold((f32::MAX, f32::MIN), |(min, max), s| {
+                (min.min(*s), max.max(*s))
+            });
+        Self {
+            color_map,
+            min: if min <= max { min } else { 0.0 },
+            max: if min <= max { max } else { 1.0 },
+            ..Default::default()
+        }
+    }
+
+    ///
+    /// Returns a legend for the color map and range of this material.
+    ///
+    pub fn legend(&self) -> ColorMapLegend {
+        ColorMapLegend {
+            color_map: self.color_map,
+            min: self.min,
+            max: self.max,
+        }
+    }
+}
+
+impl Default for ScalarFieldMaterial {
+    fn default() -> Self {
+        Self {
+            color_map: ColorMap::default(),
+            min: 0.0,
+            max: 1.0,
+            contour_interval: None,
+            contour_color: Srgba::BLACK,
+            render_states: RenderStates::default(),
+        }
+    }
+}
+
+impl Material for ScalarFieldMaterial {
+    fn id(&self) -> u16 {
+        0b1u16 << 15 | 0b1u16 << 12 | 0b0001u16
+    }
+
+    fn fragment_shader_source(&self, _lights: &[&dyn Light]) -> String {
+        format!(
+            "{}{}",
+            ColorMapping::fragment_shader_source(),
+            include_str!("shaders/scalar_field_material.frag")
+        )
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            position: true,
+            normal: true,
+            uv: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(&self, program: &Program, camera: &Camera, _lights: &[&dyn Light]) {
+        camera.color_mapping.use_uniforms(program);
+        program.use_uniform("eye", camera.position());
+        program.use_uniform_array("colorMap", &self.color_map.linear_stops());
+        program.use_uniform("range", vec2(self.min, self.max));
+        program.use_uniform(
+            "contourInterval",
+            self.contour_interval.unwrap_or(0.0).max(0.0),
+        );
+        program.use_uniform("contourColor", self.contour_color.to_linear_srgb());
+    }
+
+    fn render_states(&self) -> RenderStates {
+        self.render_states
+    }
+
+    fn material_type(&self) -> MaterialType {
+        MaterialType::Opaque
+    }
+}
+
+///
+/// A legend which explains the colors of a [ColorMap] applied to a range of scalar values, for example the range of a [ScalarFieldMaterial].
+/// Use [ColorMapLegend::image] to get the color bar, which can be rendered as a texture in the user interface, and [ColorMapLegend::ticks] to get the values to label it with.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ColorMapLegend {
+    /// The color map.
+    pub color_map: ColorMap,
+    /// The scalar value at the start of the color bar.
+    pub min: f32,
+    /// The scalar value at the end of the color bar.
+    pub max: f32,
+}
+
+impl ColorMapLegend {
+    ///
+    /// Returns an image of the color bar with the given size. If the width is larger than the height, the minimum value is to the left,
+    /// otherwise the minimum value is at the bottom.
+    ///
+    pub fn image(&self, width: u32, height: u32) -> CpuTexture {
+        let width = width.max(1);
+        let height = height.max(1);
+        let horizontal = width > height;
+        let mut data = Vec::with_capacity((width * height) as usize);
+        for y in 0..height {
+            for x in 0..width {
+                let t = if horizontal {
+                    x as f32 / (width - 1).max(1) as f32
+                } else {
+                    1.0 - y as f32 / (height - 1).max(1) as f32
+                };
+                let color = self.color_map.color(t);
+                data.push([color.r, color.g, color.b, color.a]);
+            }
+        }
+        CpuTexture {
+            name: "legend".to_string(),
+            data: TextureData::RgbaU8(data),
+            width,
+            height,
+            ..Default::default()
+        }
+    }
+
+    ///
+    /// Returns the given number of evenly spaced ticks as pairs of the position along the color bar in the range `[0, 1]` and the scalar value at that position.
+    ///
+    pub fn ticks(&self, count: u32) -> Vec<(f32, f32)> {
+        let count = count.max(2);
+        (0..count)
+            .map(|i| {
+                let t = i as f32 / (count - 1) as f32;
+                (t, self.min + t * (self.max - self.min))
+            })
+            .collect()
+    }
+}
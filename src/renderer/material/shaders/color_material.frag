@@ -11,11 +11,19 @@ layout (location = 0) out vec4 outColor;
 
 void main()
 {
+#ifdef USE_DISSOLVE
+    vec3 dissolve_edge = dissolve(uvs);
+#endif
+
     outColor = surfaceColor * col;
     
     #ifdef USE_TEXTURE
     outColor *= texture(tex, (textureTransformation * vec3(uvs, 1.0)).xy);
     #endif
 
+    #ifdef USE_DISSOLVE
+    outColor.rgb += dissolve_edge;
+    #endif
+
     outColor.rgb = color_mapping(outColor.rgb);
 }
\ No newline at end of file
@@ -41,6 +41,10 @@ layout (location = 2) out vec4 outEmissive;
 
 void main()
 {
+#ifdef USE_DISSOLVE
+    vec3 dissolve_edge = dissolve(uvs);
+#endif
+
     vec4 surface_color = albedo * col;
 #ifdef USE_ALBEDO_TEXTURE
     vec4 c = texture(albedoTexture, (albedoTexTransform * vec3(uvs, 1.0)).xy);
@@ -49,6 +53,9 @@ void main()
     #endif
     surface_color *= c;
 #endif
+#ifdef USE_DETAIL_ALBEDO_TEXTURE
+    surface_color.rgb *= 2.0 * sample_detail(detailAlbedoTexture, (detailAlbedoTexTransform * vec3(uvs, 1.0)).xy).rgb;
+#endif
 
     float metallic_factor = metallic;
     float roughness_factor = roughness;
@@ -64,17 +71,35 @@ void main()
 #endif
 
     vec3 normal = normalize(gl_FrontFacing ? nor : -nor);
-#ifdef USE_NORMAL_TEXTURE
+#ifdef USE_DISPLACEMENT
+    normal = displaced_normal(pos, normalize(nor), uvs);
+    normal = gl_FrontFacing ? normal : -normal;
+#endif
+#if defined(USE_NORMAL_TEXTURE) || defined(USE_DETAIL_NORMAL_TEXTURE)
     vec3 tangent = normalize(gl_FrontFacing ? tang : -tang);
     vec3 bitangent = normalize(gl_FrontFacing ? bitang : -bitang);
     mat3 tbn = mat3(tangent, bitangent, normal);
-    normal = tbn * ((2.0 * texture(normalTexture, (normalTexTransform * vec3(uvs, 1.0)).xy).xyz - 1.0) * vec3(normalScale, normalScale, 1.0));
+    vec3 tangent_normal = vec3(0.0, 0.0, 1.0);
+#ifdef USE_NORMAL_TEXTURE
+    tangent_normal = (2.0 * texture(normalTexture, (normalTexTransform * vec3(uvs, 1.0)).xy).xyz - 1.0) * vec3(normalScale, normalScale, 1.0);
+#endif
+#ifdef USE_DETAIL_NORMAL_TEXTURE
+    vec3 detail_normal = (2.0 * sample_detail(detailNormalTexture, (detailNormalTexTransform * vec3(uvs, 1.0)).xy).xyz - 1.0) * vec3(detailNormalScale, detailNormalScale, 1.0);
+    tangent_normal = normalize(vec3(tangent_normal.xy + detail_normal.xy, tangent_normal.z * detail_normal.z));
+#endif
+    normal = tbn * tangent_normal;
 #endif
 
     vec3 total_emissive = emissive.rgb;
 #ifdef USE_EMISSIVE_TEXTURE
     total_emissive *= texture(emissiveTexture, (emissiveTexTransform * vec3(uvs, 1.0)).xy).rgb;
 #endif
+#ifdef USE_EMISSIVE_INTENSITY
+    total_emissive *= emissive_intensity;
+#endif
+#ifdef USE_DISSOLVE
+    total_emissive += dissolve_edge;
+#endif
 
     outColor = vec4(surface_color.rgb, metallic_factor);
     int o = int(occlusion * 127.0);
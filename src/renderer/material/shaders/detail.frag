@@ -0,0 +1,36 @@
+#ifdef USE_DETAIL_ALBEDO_TEXTURE
+uniform sampler2D detailAlbedoTexture;
+uniform mat3 detailAlbedoTexTransform;
+#endif
+
+#ifdef USE_DETAIL_NORMAL_TEXTURE
+uniform sampler2D detailNormalTexture;
+uniform mat3 detailNormalTexTransform;
+uniform float detailNormalScale;
+#endif
+
+#if defined(USE_DETAIL_ALBEDO_TEXTURE) || defined(USE_DETAIL_NORMAL_TEXTURE)
+vec2 detail_hash(vec2 p)
+{
+    return fract(sin(vec2(dot(p, vec2(127.1, 311.7)), dot(p, vec2(269.5, 183.3)))) * 43758.5453);
+}
+
+vec4 sample_detail(sampler2D tex, vec2 uv)
+{
+#ifdef USE_DETAIL_BREAK_UP
+    // Blend between samples with a random offset for each of the four closest tiles
+    vec2 tile = floor(uv);
+    vec2 f = fract(uv);
+    vec2 w = f * f * (3.0 - 2.0 * f);
+    vec2 dx = dFdx(uv);
+    vec2 dy = dFdy(uv);
+    vec4 a = textureGrad(tex, uv + detail_hash(tile), dx, dy);
+    vec4 b = textureGrad(tex, uv + detail_hash(tile + vec2(1.0, 0.0)), dx, dy);
+    vec4 c = textureGrad(tex, uv + detail_hash(tile + vec2(0.0, 1.0)), dx, dy);
+    vec4 d = textureGrad(tex, uv + detail_hash(tile + vec2(1.0, 1.0)), dx, dy);
+    return mix(mix(a, b, w.x), mix(c, d, w.x), w.y);
+#else
+    return texture(tex, uv);
+#endif
+}
+#endif
@@ -0,0 +1,32 @@
+uniform sampler2D displacementTexture;
+uniform mat3 displacementTexTransform;
+uniform float displacementScale;
+
+// Computes the normal of the displaced surface from the gradient of the displacement map as described in
+// Mikkelsen, "Bump Mapping Unparametrized Surfaces on the GPU", 2010.
+vec3 displaced_normal(vec3 position, vec3 normal, vec2 uv)
+{
+    uv = (displacementTexTransform * vec3(uv, 1.0)).xy;
+    vec2 texel = 1.0 / vec2(textureSize(displacementTexture, 0));
+    float height = textureLod(displacementTexture, uv, 0.0).r;
+    float height_u = (textureLod(displacementTexture, uv + vec2(texel.x, 0.0), 0.0).r
+        - textureLod(displacementTexture, uv - vec2(texel.x, 0.0), 0.0).r) / (2.0 * texel.x);
+    float height_v = (textureLod(displacementTexture, uv + vec2(0.0, texel.y), 0.0).r
+        - textureLod(displacementTexture, uv - vec2(0.0, texel.y), 0.0).r) / (2.0 * texel.y);
+
+    // The gradients of the uv coordinates along the undisplaced surface
+    vec3 p = position - displacementScale * height * normal;
+    vec3 dpdx = dFdx(p);
+    vec3 dpdy = dFdy(p);
+    vec3 r1 = cross(dpdy, normal);
+    vec3 r2 = cross(normal, dpdx);
+    float det = dot(dpdx, r1);
+    if (abs(det) < 1e-20) {
+        return normal;
+    }
+    vec2 duvdx = dFdx(uv);
+    vec2 duvdy = dFdy(uv);
+    vec3 gradient_u = (r1 * duvdx.x + r2 * duvdy.x) / det;
+    vec3 gradient_v = (r1 * duvdx.y + r2 * duvdy.y) / det;
+    return normalize(normal - displacementScale * (height_u * gradient_u + height_v * gradient_v));
+}
@@ -0,0 +1,19 @@
+
+uniform sampler2D dissolveTexture;
+uniform mat3 dissolveTexTransform;
+uniform float dissolveThreshold;
+uniform float dissolveEdgeWidth;
+uniform vec3 dissolveEdgeColor;
+
+// Discards the fragment if it is dissolved, otherwise returns the emissive color of the edge band
+vec3 dissolve(vec2 uv)
+{
+    float noise = texture(dissolveTexture, (dissolveTexTransform * vec3(uv, 1.0)).xy).r;
+    if (noise < dissolveThreshold) {
+        discard;
+    }
+    if (dissolveThreshold <= 0.0) {
+        return vec3(0.0);
+    }
+    return dissolveEdgeColor * (1.0 - smoothstep(0.0, max(dissolveEdgeWidth, 0.0001), noise - dissolveThreshold));
+}
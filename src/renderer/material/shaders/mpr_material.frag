@@ -0,0 +1,38 @@
+uniform sampler3D volume;
+uniform mat4 textureTransformation;
+uniform vec2 windowRange;
+uniform vec3 crosshair;
+uniform float crosshairWidth;
+uniform int sliceAxis;
+uniform vec3 crosshairColors[3];
+
+in vec3 pos;
+
+layout (location = 0) out vec4 outColor;
+
+void main()
+{
+    vec3 uvw = (textureTransformation * vec4(pos, 1.0)).xyz;
+    if (any(lessThan(uvw, vec3(0.0))) || any(greaterThan(uvw, vec3(1.0)))) {
+        discard;
+    }
+    float value = texture(volume, uvw).r;
+    float gray = clamp((value - windowRange.x) / max(windowRange.y - windowRange.x, 0.00001), 0.0, 1.0);
+
+    // The window maps to display (sRGB) values, so convert to linear before the color mapping
+    vec3 color = vec3(gray <= 0.04045 ? gray / 12.92 : pow((gray + 0.055) / 1.055, 2.4));
+
+    if (crosshairWidth > 0.0) {
+        // The line along an axis is where the coordinates along the two other axes equal the crosshair
+        vec3 distance = abs(uvw - crosshair) / max(fwidth(uvw), vec3(0.000001));
+        for (int i = 0; i < 3; i++) {
+            if (i != sliceAxis) {
+                int j = 3 - i - sliceAxis;
+                if (distance[j] < 0.5 * crosshairWidth) {
+                    color = crosshairColors[i];
+                }
+            }
+        }
+    }
+    outColor = vec4(color_mapping(color), 1.0);
+}
@@ -0,0 +1,27 @@
+uniform vec3 eye;
+uniform vec3 buildDirection;
+uniform float criticalAngle;
+uniform vec4 supportedColor;
+uniform vec4 overhangColor;
+
+in vec3 pos;
+in vec3 nor;
+
+layout (location = 0) out vec4 outColor;
+
+void main()
+{
+    vec3 normal = normalize(gl_FrontFacing ? nor : -nor);
+
+    // The overhang angle is measured from the vertical, ie. 0 degrees for a vertical wall and 90 degrees for a surface facing straight down
+    float overhangAngle = asin(clamp(dot(normal, -buildDirection), -1.0, 1.0));
+    vec4 color = supportedColor;
+    if (overhangAngle > criticalAngle) {
+        float t = (overhangAngle - criticalAngle) / max(1.5707963 - criticalAngle, 0.0001);
+        color = mix(supportedColor, overhangColor, 0.5 + 0.5 * t);
+    }
+
+    float shade = 0.4 + 0.6 * abs(dot(normal, normalize(eye - pos)));
+    outColor = vec4(shade * color.rgb, color.a);
+    outColor.rgb = color_mapping(outColor.rgb);
+}
@@ -0,0 +1,36 @@
+uniform vec3 eye;
+uniform vec3 colorMap[9];
+uniform vec2 range;
+uniform float contourInterval;
+uniform vec4 contourColor;
+
+in vec3 pos;
+in vec3 nor;
+in vec2 uvs;
+
+layout (location = 0) out vec4 outColor;
+
+vec3 sample_color_map(float t)
+{
+    float x = clamp(t, 0.0, 1.0) * 8.0;
+    int i = min(int(x), 7);
+    return mix(colorMap[i], colorMap[i + 1], x - float(i));
+}
+
+void main()
+{
+    vec3 normal = normalize(gl_FrontFacing ? nor : -nor);
+    float value = uvs.x;
+    vec3 color = sample_color_map((value - range.x) / max(range.y - range.x, 0.000001));
+
+    if (contourInterval > 0.0) {
+        // The distance to the closest contour line measured in pixels, so the lines have the same width regardless of the gradient of the scalar field
+        float v = (value - range.x) / contourInterval;
+        float distance_to_line = abs(fract(v - 0.5) - 0.5) / max(fwidth(v), 0.000001);
+        color = mix(color, contourColor.rgb, contourColor.a * (1.0 - clamp(distance_to_line, 0.0, 1.0)));
+    }
+
+    float shade = 0.4 + 0.6 * abs(dot(normal, normalize(eye - pos)));
+    outColor = vec4(shade * color, 1.0);
+    outColor.rgb = color_mapping(outColor.rgb);
+}
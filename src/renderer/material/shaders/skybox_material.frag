@@ -1,12 +1,17 @@
 uniform samplerCube texture0;
-uniform int isHDR;
+uniform float rotation;
+uniform float intensity;
+uniform float blur;
 
 in vec3 coords;
 
 layout (location = 0) out vec4 outColor;
 
 void main() {
-    outColor = vec4(texture(texture0, coords).rgb, 1.0);
+    float c = cos(rotation);
+    float s = sin(rotation);
+    vec3 direction = vec3(c * coords.x + s * coords.z, coords.y, c * coords.z - s * coords.x);
+    outColor = vec4(intensity * texture(texture0, direction, blur).rgb, 1.0);
     outColor.rgb = tone_mapping(outColor.rgb);
     outColor.rgb = color_mapping(outColor.rgb);
 }
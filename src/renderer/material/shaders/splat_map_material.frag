@@ -0,0 +1,75 @@
+
+uniform float metallic;
+uniform float roughness;
+uniform vec3 cameraPosition;
+
+uniform sampler2D splatMap0;
+uniform mat3 splatMapTransform0;
+#ifdef USE_SECOND_SPLAT_MAP
+uniform sampler2D splatMap1;
+uniform mat3 splatMapTransform1;
+#endif
+
+uniform float tilings[LAYER_COUNT];
+uniform sampler2DArray albedoTextures;
+#ifdef USE_NORMAL_TEXTURES
+uniform sampler2DArray normalTextures;
+uniform float normalScale;
+in vec3 tang;
+in vec3 bitang;
+#endif
+
+in vec3 pos;
+in vec3 nor;
+in vec2 uvs;
+
+layout (location = 0) out vec4 outColor;
+
+void main()
+{
+    float weights[8];
+    vec4 w0 = texture(splatMap0, (splatMapTransform0 * vec3(uvs, 1.0)).xy);
+    weights[0] = w0.r;
+    weights[1] = w0.g;
+    weights[2] = w0.b;
+    weights[3] = w0.a;
+#ifdef USE_SECOND_SPLAT_MAP
+    vec4 w1 = texture(splatMap1, (splatMapTransform1 * vec3(uvs, 1.0)).xy);
+    weights[4] = w1.r;
+    weights[5] = w1.g;
+    weights[6] = w1.b;
+    weights[7] = w1.a;
+#endif
+
+    float total_weight = 0.0;
+    for (int i = 0; i < LAYER_COUNT; i++) {
+        total_weight += weights[i];
+    }
+    total_weight = max(total_weight, 0.0001);
+
+    vec3 surface_color = vec3(0.0);
+#ifdef USE_NORMAL_TEXTURES
+    vec3 tangent_normal = vec3(0.0);
+#endif
+    for (int i = 0; i < LAYER_COUNT; i++) {
+        float weight = weights[i] / total_weight;
+        vec3 layer_uvs = vec3(uvs * tilings[i], float(i));
+        surface_color += weight * texture(albedoTextures, layer_uvs).rgb;
+#ifdef USE_NORMAL_TEXTURES
+        tangent_normal += weight * (2.0 * texture(normalTextures, layer_uvs).xyz - 1.0);
+#endif
+    }
+
+    vec3 normal = normalize(gl_FrontFacing ? nor : -nor);
+#ifdef USE_NORMAL_TEXTURES
+    vec3 tangent = normalize(gl_FrontFacing ? tang : -tang);
+    vec3 bitangent = normalize(gl_FrontFacing ? bitang : -bitang);
+    mat3 tbn = mat3(tangent, bitangent, normal);
+    normal = normalize(tbn * (tangent_normal * vec3(normalScale, normalScale, 1.0)));
+#endif
+
+    outColor.rgb = calculate_lighting(cameraPosition, surface_color, pos, normal, metallic, roughness, 1.0);
+    outColor.rgb = tone_mapping(outColor.rgb);
+    outColor.rgb = color_mapping(outColor.rgb);
+    outColor.a = 1.0;
+}
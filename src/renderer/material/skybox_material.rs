@@ -4,6 +4,9 @@ use std::sync::Arc;
 
 pub struct SkyboxMaterial {
     pub texture: Arc<TextureCubeMap>,
+    pub rotation: Radians,
+    pub intensity: f32,
+    pub blur: f32,
 }
 
 impl Material for SkyboxMaterial {
@@ -26,9 +29,11 @@ impl Material for SkyboxMaterial {
     }
 
     fn use_uniforms(&self, program: &Program, camera: &Camera, _lights: &[&dyn Light]) {
-        camera.tone_mapping.use_uniforms(program);
-        camera.color_mapping.use_uniforms(program);
+        camera.use_tone_and_color_mapping_uniforms(program);
         program.use_texture_cube("texture0", &self.texture);
+        program.use_uniform_if_required("rotation", self.rotation.0);
+        program.use_uniform_if_required("intensity", self.intensity);
+        program.use_uniform_if_required("blur", self.blur);
     }
 
     fn render_states(&self) -> RenderStates {
@@ -0,0 +1,138 @@
+use crate::core::*;
+use crate::renderer::*;
+use std::sync::Arc;
+
+///
+/// A physically-based material that blends up to 8 tiled texture layers based on splat maps, also called weight maps.
+/// This is typically used together with a [Terrain] to render for example grass, rock and sand depending on the position on the terrain.
+/// This material is affected by lights.
+///
+/// Each channel (red, green, blue and alpha) of a splat map contains the weight of one layer, so one splat map is needed for up to 4 layers and two splat maps for up to 8 layers.
+/// The splat maps are sampled using the uv coordinates transformed by the [Texture2DRef::transformation], which for a [Terrain] is the world space position in the xz plane.
+/// The layers are sampled using the uv coordinates multiplied by the tiling of the layer.
+///
+#[derive(Clone)]
+pub struct SplatMapMaterial {
+    /// One or two splat maps where each channel contains the weight of one layer.
+    pub splat_maps: Vec<Texture2DRef>,
+    /// The albedo textures of the layers where each layer in the texture array is one layer of this material.
+    /// The colors are assumed to be in linear sRGB (`RgbU8`), linear sRGB with an alpha channel (`RgbaU8`) or HDR color space.
+    pub albedo_textures: Arc<Texture2DArray>,
+    /// Optional tangent space normal maps of the layers where each layer in the texture array is one layer of this material.
+    pub normal_textures: Option<Arc<Texture2DArray>>,
+    /// How many times each layer is repeated in the uv coordinate range `[0..1]`.
+    pub tilings: Vec<f32>,
+    /// A scalar multiplier applied to each normal vector of the [Self::normal_textures].
+    pub normal_scale: f32,
+    /// A value in the range `[0..1]` specifying how metallic the surface is.
+    pub metallic: f32,
+    /// A value in the range `[0..1]` specifying how rough the surface is.
+    pub roughness: f32,
+    /// Render states.
+    pub render_states: RenderStates,
+    /// The lighting model used when rendering this material
+    pub lighting_model: LightingModel,
+}
+
+impl SplatMapMaterial {
+    ///
+    /// Creates a new splat map material with the given splat maps and albedo textures where each layer is repeated the given number of times in the uv coordinate range `[0..1]`.
+    ///
+    pub fn new(
+        splat_maps: Vec<Texture2DRef>,
+        albedo_textures: Arc<Texture2DArray>,
+        tilings: Vec<f32>,
+    ) -> Self {
+        Self {
+            splat_maps,
+            albedo_textures,
+            normal_textures: None,
+            tilings,
+            normal_scale: 1.0,
+            metallic: 0.0,
+            roughness: 1.0,
+            render_states: RenderStates::default(),
+            lighting_model: LightingModel::Blinn,
+        }
+    }
+
+    // The number of layers is limited by the number of weights in the splat maps and the number of layers in the texture arrays
+    fn layer_count(&self) -> usize {
+        let mut layer_count =
+            (4 * self.splat_maps.len().clamp(1, 2)).min(self.albedo_textures.depth() as usize);
+        if let Some(ref normal_textures) = self.normal_textures {
+            layer_count = layer_count.min(normal_textures.depth() as usize);
+        }
+        layer_count.max(1)
+    }
+}
+
+impl Material for SplatMapMaterial {
+    fn id(&self) -> u16 {
+        let mut id = 0b1u16 << 15 | 0b1u16 << 8 | 0b100u16;
+        if self.normal_textures.is_some() {
+            id |= 0b1u16;
+        }
+        if self.splat_maps.len() > 1 {
+            id |= 0b1u16 << 1;
+        }
+        id |= ((self.layer_count() - 1) as u16) << 9;
+        id
+    }
+
+    fn fragment_shader_source(&self, lights: &[&dyn Light]) -> String {
+        let mut output = lights_shader_source(lights, self.lighting_model);
+        output.push_str(&format!("#define LAYER_COUNT {}\n", self.layer_count()));
+        if self.splat_maps.len() > 1 {
+            output.push_str("#define USE_SECOND_SPLAT_MAP\n");
+        }
+        if self.normal_textures.is_some() {
+            output.push_str("#define USE_NORMAL_TEXTURES\n");
+        }
+        output.push_str(ToneMapping::fragment_shader_source());
+        output.push_str(ColorMapping::fragment_shader_source());
+        output.push_str(include_str!("shaders/splat_map_material.frag"));
+        output
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            position: true,
+            normal: true,
+            uv: true,
+            tangents: self.normal_textures.is_some(),
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(&self, program: &Program, camera: &Camera, lights: &[&dyn Light]) {
+        camera.use_tone_and_color_mapping_uniforms(program);
+        program.use_uniform_if_required("cameraPosition", camera.position());
+        for (i, light) in lights.iter().enumerate() {
+            light.use_uniforms(program, i as u32);
+        }
+        program.use_uniform_if_required("metallic", self.metallic);
+        program.use_uniform_if_required("roughness", self.roughness);
+
+        for (i, splat_map) in self.splat_maps.iter().take(2).enumerate() {
+            program.use_uniform(&format!("splatMapTransform{}", i), splat_map.transformation);
+            program.use_texture(&format!("splatMap{}", i), splat_map);
+        }
+        let mut tilings = self.tilings.clone();
+        tilings.resize(self.layer_count(), 1.0);
+        program.use_uniform_array("tilings", &tilings);
+        program.use_texture_array("albedoTextures", &self.albedo_textures);
+        if let Some(ref normal_textures) = self.normal_textures {
+            program.use_uniform("normalScale", self.normal_scale);
+            program.use_texture_array("normalTextures", normal_textures);
+        }
+    }
+
+    fn render_states(&self) -> RenderStates {
+        self.render_states
+    }
+
+    fn material_type(&self) -> MaterialType {
+        MaterialType::Opaque
+    }
+}
@@ -0,0 +1,107 @@
+use crate::core::*;
+use crate::renderer::*;
+
+///
+/// A [PhysicalMaterial] which refines the geometry on the GPU using the tessellation stages, for example for high-detail terrain or curved surfaces.
+/// The edges of the triangles are subdivided depending on their length relative to the distance to the camera, so the geometry is only refined close to the camera,
+/// and the [PhysicalMaterial::displacement] is applied to the vertices of the refined geometry.
+///
+/// The tessellation stages are only available on desktop (see [Context::supports_tessellation]), otherwise the geometry is rendered without refinement,
+/// ie. the displacement is applied to the vertices of the geometry as for the [PhysicalMaterial].
+/// Requires a geometry using the triangle vertex shader of this crate, for example a [Mesh], an [InstancedMesh] or a [SkinnedMesh].
+///
+#[derive(Clone)]
+pub struct TessellatedPhysicalMaterial {
+    /// The material applied to the refined geometry.
+    pub material: PhysicalMaterial,
+    /// The number of subdivisions of an edge which has the same length as the distance from the camera to the edge.
+    pub tessellation_factor: f32,
+    /// The maximum number of subdivisions of an edge, must be at most 64.
+    pub max_tessellation_level: f32,
+}
+
+impl TessellatedPhysicalMaterial {
+    ///
+    /// Constructs a new tessellated material from the given [PhysicalMaterial] with a tessellation factor of 32 and a maximum tessellation level of 64.
+    ///
+    pub fn new(material: PhysicalMaterial) -> Self {
+        Self {
+            material,
+            tessellation_factor: 32.0,
+            max_tessellation_level: 64.0,
+        }
+    }
+}
+
+impl FromCpuMaterial for TessellatedPhysicalMaterial {
+    fn from_cpu_material(context: &Context, cpu_material: &CpuMaterial) -> Self {
+        Self::new(PhysicalMaterial::new(context, cpu_material))
+    }
+}
+
+impl Material for TessellatedPhysicalMaterial {
+    fn id(&self) -> u16 {
+        self.material.id()
+    }
+
+    fn fragment_shader_source(&self, lights: &[&dyn Light]) -> String {
+        self.material.fragment_shader_source(lights)
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        self.material.fragment_attributes()
+    }
+
+    fn use_uniforms(&self, program: &Program, camera: &Camera, lights: &[&dyn Light]) {
+        self.material.use_uniforms(program, camera, lights);
+        program.use_uniform_if_required("cameraPosition", camera.position());
+        program.use_uniform_if_required("tessellationFactor", self.tessellation_factor);
+        program.use_uniform_if_required(
+            "maxTessellationLevel",
+            self.max_tessellation_level.clamp(1.0, 64.0),
+        );
+    }
+
+    fn render_states(&self) -> RenderStates {
+        self.material.render_states()
+    }
+
+    fn material_type(&self) -> MaterialType {
+        self.material.material_type()
+    }
+
+    fn tessellation_shader_source(&self) -> Option<(String, String)> {
+        let attributes = self.fragment_attributes();
+        let mut defines = String::new();
+        if attributes.normal {
+            defines.push_str("#define USE_NORMALS\n");
+        }
+        if attributes.tangents {
+            defines.push_str("#define USE_TANGENTS\n");
+        }
+        if attributes.uv {
+            defines.push_str("#define USE_UVS\n");
+        }
+        if attributes.uv1 {
+            defines.push_str("#define USE_UVS1\n");
+        }
+        if attributes.displacement {
+            defines.push_str("#define USE_DISPLACEMENT\n");
+        }
+        if attributes.emissive_intensity {
+            defines.push_str("#define USE_EMISSIVE_INTENSITY\n");
+        }
+        Some((
+            format!(
+                "{}{}",
+                defines,
+                include_str!("shaders/tessellated_physical_material.tesc")
+            ),
+            format!(
+                "{}{}",
+                defines,
+                include_str!("shaders/tessellated_physical_material.tese")
+            ),
+        ))
+    }
+}
@@ -15,6 +15,10 @@ macro_rules! impl_object_body {
         fn material_type(&self) -> MaterialType {
             self.$inner().material_type()
         }
+
+        fn is_frustum_culled(&self) -> bool {
+            self.$inner().is_frustum_culled()
+        }
     };
 }
 
@@ -26,6 +30,14 @@ mod model;
 #[doc(inline)]
 pub use model::*;
 
+mod skinned_model;
+#[doc(inline)]
+pub use skinned_model::*;
+
+mod morph_model;
+#[doc(inline)]
+pub use morph_model::*;
+
 mod instanced_model;
 #[doc(inline)]
 pub use instanced_model::*;
@@ -54,6 +66,57 @@ mod axes;
 #[doc(inline)]
 pub use axes::*;
 
+mod glyphs;
+#[doc(inline)]
+pub use glyphs::*;
+
+mod mpr;
+#[doc(inline)]
+pub use mpr::*;
+
+#[cfg(feature = "text")]
+#[cfg_attr(docsrs, doc(feature = "text"))]
+mod text;
+#[cfg(feature = "text")]
+#[doc(inline)]
+pub use text::*;
+
+mod print_bed;
+#[doc(inline)]
+pub use print_bed::*;
+
+mod gaussian_splats;
+#[doc(inline)]
+pub use gaussian_splats::*;
+
+mod gpu_particles;
+#[doc(inline)]
+pub use gpu_particles::*;
+
+mod point_cloud_lod;
+#[doc(inline)]
+pub use point_cloud_lod::*;
+
+mod scene;
+#[doc(inline)]
+pub use scene::*;
+
+mod scene_node;
+#[doc(inline)]
+pub use scene_node::*;
+
+mod scene_command;
+#[doc(inline)]
+pub use scene_command::*;
+
+mod prefab;
+#[doc(inline)]
+pub use prefab::*;
+
+mod exploded_view;
+#[doc(inline)]
+pub use exploded_view::*;
+
 use crate::core::*;
 use crate::renderer::*;
 
@@ -72,6 +135,14 @@ pub trait Object: Geometry {
     /// Returns the type of material applied to this object.
     ///
     fn material_type(&self) -> MaterialType;
+
+    ///
+    /// Returns whether or not this object is skipped when rendering if its bounding box is outside the camera frustum, see [Frustum].
+    /// Objects which should always be rendered, for example a [Skybox], returns false. The default is true.
+    ///
+    fn is_frustum_culled(&self) -> bool {
+        true
+    }
 }
 
 use std::ops::Deref;
@@ -83,7 +154,7 @@ impl<T: Object + ?Sized> Object for &mut T {
     impl_object_body!(deref);
 }
 
-impl<T: Object> Object for Box<T> {
+impl<T: Object + ?Sized> Object for Box<T> {
     impl_object_body!(as_ref);
 }
 
@@ -107,4 +178,8 @@ impl<T: Object> Object for std::sync::RwLock<T> {
     fn material_type(&self) -> MaterialType {
         self.read().unwrap().material_type()
     }
+
+    fn is_frustum_culled(&self) -> bool {
+        self.read().unwrap().is_frustum_culled()
+    }
 }
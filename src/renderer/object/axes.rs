@@ -28,6 +28,8 @@ impl Axes {
                     ],
                     texture_transformations: None,
                     colors: Some(vec![Srgba::RED, Srgba::GREEN, Srgba::BLUE]),
+                    emissive_intensities: None,
+                    custom_attributes: Default::default(),
                 },
                 &cpu_mesh,
             ),
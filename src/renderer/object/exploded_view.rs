@@ -0,0 +1,106 @@
+use crate::renderer::*;
+
+///
+/// A helper for showing the parts of an assembly pulled apart from each other, for example in product and assembly viewers.
+/// Each part is moved away from the centroid of the assembly along the direction from the centroid to the centroid of the part,
+/// so parts far from the centroid are moved further than parts close to the centroid.
+///
+/// The explosion is controlled by a single scalar where 0 is the assembled state and 1 is the fully exploded state,
+/// so the transition can be animated by changing the scalar over time, see [ExplodedView::transformation] and [ExplodedView::apply].
+///
+#[derive(Clone, Debug)]
+pub struct ExplodedView {
+    centroid: Vec3,
+    assembled_transformations: Vec<Mat4>,
+    offsets: Vec<Vec3>,
+}
+
+impl ExplodedView {
+    ///
+    /// Creates a new exploded view from the bounding box and the local to world transformation of each part in the assembled state.
+    /// The spread specifies how far the parts are moved in the fully exploded state relative to their distance to the centroid of the assembly,
+    /// for example a spread of 1 doubles the distance from the centroid of the assembly to the centroid of each part.
+    ///
+    pub fn new(parts: &[(AxisAlignedBoundingBox, Mat4)], spread: f32) -> Self {
+        let centers = parts
+            .iter()
+            .map(|(aabb, _)| {
+                if aabb.is_empty() {
+                    None
+                } else {
+                    Some(aabb.center())
+                }
+            })
+            .collect::<Vec<_>>();
+        let count = centers.iter().flatten().count().max(1);
+        let centroid = centers
+            .iter()
+            .flatten()
+            .fold(vec3(0.0, 0.0, 0.0), |acc, c| acc + *c)
+            / count as f32;
+        Self {
+            centroid,
+            assembled_transformations: parts.iter().map(|(_, t)| *t).collect(),
+            offsets: centers
+                .iter()
+                .map(|center| {
+                    center
+                        .map(|center| spread * (center - centroid))
+                        .unwrap_or(vec3(0.0, 0.0, 0.0))
+                })
+                .collect(),
+        }
+    }
+
+    ///
+    /// Returns the centroid of the assembly, ie. the average of the centroids of the parts.
+    ///
+    pub fn centroid(&self) -> Vec3 {
+        self.centroid
+    }
+
+    ///
+    /// Returns the number of parts.
+    ///
+    pub fn part_count(&self) -> usize {
+        self.offsets.len()
+    }
+
+    ///
+    /// Returns the translation of the part with the given index in the fully exploded state.
+    ///
+    pub fn offset(&self, index: usize) -> Vec3 {
+        self.offsets[index]
+    }
+
+    ///
+    /// Sets the translation of the part with the given index in the fully exploded state, for example to move a part in another direction than away from the centroid.
+    ///
+    pub fn set_offset(&mut self, index: usize, offset: Vec3) {
+        self.offsets[index] = offset;
+    }
+
+    ///
+    /// Returns the local to world transformation of the part with the given index at the given explosion, where 0 is the assembled state and 1 is the fully exploded state.
+    /// The explosion is clamped to the range from 0 to 1 and eased, so changing the explosion linearly over time gives an animation which starts and stops smoothly.
+    ///
+    pub fn transformation(&self, index: usize, explosion: f32) -> Mat4 {
+        let t = explosion.clamp(0.0, 1.0);
+        let t = t * t * (3.0 - 2.0 * t);
+        Mat4::from_translation(t * self.offsets[index]) * self.assembled_transformations[index]
+    }
+
+    ///
+    /// Sets the transformation of each of the given parts to the transformation at the given explosion, see [ExplodedView::transformation].
+    /// The parts must be given in the same order as when this exploded view was constructed.
+    ///
+    pub fn apply<'a, T: Transformable + 'a>(
+        &self,
+        parts: impl IntoIterator<Item = &'a mut T>,
+        explosion: f32,
+    ) {
+        for (index, part) in parts.into_iter().enumerate().take(self.part_count()) {
+            part.set_transformation(self.transformation(index, explosion));
+        }
+    }
+}
@@ -0,0 +1,451 @@
+use crate::core::*;
+use crate::renderer::*;
+use std::sync::RwLock;
+
+///
+/// A set of anisotropic 3D gaussians on the CPU side, as produced by gaussian splatting captures.
+/// Use [CpuGaussianSplats::from_ply] to parse a `.ply` file and [GaussianSplats::new] to upload the splats to the GPU.
+///
+#[derive(Clone, Debug, Default)]
+pub struct CpuGaussianSplats {
+    /// The center of each gaussian.
+    pub positions: Vec<Vec3>,
+    /// The scale of each gaussian along its local axes.
+    pub scales: Vec<Vec3>,
+    /// The rotation of each gaussian.
+    pub rotations: Vec<Quat>,
+    /// The color and opacity of each gaussian.
+    pub colors: Vec<Srgba>,
+}
+
+impl CpuGaussianSplats {
+    ///
+    /// Parses the given bytes of a binary little endian `.ply` file in the standard gaussian splatting format,
+    /// ie. with the vertex properties `x`, `y`, `z`, `f_dc_0`, `f_dc_1`, `f_dc_2`, `opacity`, `scale_0`, `scale_1`, `scale_2` and `rot_0` to `rot_3`.
+    /// Any other vertex properties, for example the higher order spherical harmonics, are ignored.
+    ///
+    pub fn from_ply(bytes: &[u8]) -> Result<Self, RendererError> {
+        let error = |message: &str| RendererError::InvalidSplatFile(message.to_string());
+        let header_end = bytes
+            .windows(11)
+            .position(|w| w == b"end_header\n")
+            .ok_or_else(|| error("missing end_header"))?;
+        let header = std::str::from_utf8(&bytes[..header_end])
+            .map_err(|_| error("the header is not valid utf8"))?;
+        let mut lines = header.lines();
+        if lines.next() != Some("ply") {
+            Err(error("missing ply magic number"))?;
+        }
+
+        let mut vertex_count = None;
+        let mut properties = Vec::new();
+        let mut in_vertex_element = false;
+        for line in lines {
+            let words = line.split_whitespace().collect::<Vec<_>>();
+            match words.as_slice() {
+                ["format", format, ..] => {
+                    if *format != "binary_little_endian" {
+                        Err(error("only the binary_little_endian format is supported"))?;
+                    }
+                }
+                ["element", name, count] => {
+                    in_vertex_element = *name == "vertex";
+                    if in_vertex_element {
+                        vertex_count = Some(
+                            count
+                                .parse::<usize>()
+                                .map_err(|_| error("invalid vertex count"))?,
+                        );
+                    } else if vertex_count.is_none() {
+                        Err(error("the vertex element must be the first element"))?;
+                    }
+                }
+                ["property", data_type, name] if in_vertex_element => {
+                    let size = match *data_type {
+                        "char" | "uchar" | "int8" | "uint8" => 1,
+                        "short" | "ushort" | "int16" | "uint16" => 2,
+                        "int" | "uint" | "int32" | "uint32" => 4,
+                        "float" | "float32" => 4,
+                        "double" | "float64" => 8,
+                        _ => Err(error("unsupported property type"))?,
+                    };
+                    properties.push((
+                        name.to_string(),
+                        *data_type == "float" || *data_type == "float32",
+                        size,
+                    ));
+                }
+                _ => {}
+            }
+        }
+        let vertex_count = vertex_count.ok_or_else(|| error("missing vertex element"))?;
+
+        let mut offsets = std::collections::HashMap::new();
+        let mut stride = 0;
+        for (name, is_f32, size) in properties.iter() {
+            if *is_f32 {
+                offsets.insert(name.as_str(), stride);
+            }
+            stride += size;
+        }
+        let offset = |name: &str| {
+            offsets.get(name).copied().ok_or_else(|| {
+                RendererError::InvalidSplatFile(format!("missing float property {}", name))
+            })
+        };
+        let position_offsets = [offset("x")?, offset("y")?, offset("z")?];
+        let color_offsets = [offset("f_dc_0")?, offset("f_dc_1")?, offset("f_dc_2")?];
+        let opacity_offset = offset("opacity")?;
+        let scale_offsets = [offset("scale_0")?, offset("scale_1")?, offset("scale_2")?];
+        let rotation_offsets = [
+            offset("rot_0")?,
+            offset("rot_1")?,
+            offset("rot_2")?,
+            offset("rot_3")?,
+        ];
+
+        let data = &bytes[header_end + 11..];
+        if data.len() < vertex_count * stride {
+            Err(error("the file is truncated"))?;
+        }
+
+        // Zeroth order spherical harmonics coefficient
+        const SH_C0: f32 = 0.282_094_8;
+        let mut splats = Self::default();
+        for vertex in data.chunks_exact(stride).take(vertex_count) {
+            let read = |offset: usize| {
+                f32::from_le_bytes([
+                    vertex[offset],
+                    vertex[offset + 1],
+                    vertex[offset + 2],
+                    vertex[offset + 3],
+                ])
+            };
+            let [x, y, z] = position_offsets.map(read);
+            splats.positions.push(vec3(x, y, z));
+            let [sx, sy, sz] = scale_offsets.map(read);
+            splats.scales.push(vec3(sx.exp(), sy.exp(), sz.exp()));
+            let [w, qx, qy, qz] = rotation_offsets.map(read);
+            splats.rotations.push(Quat::new(w, qx, qy, qz).normalize());
+            let [r, g, b] = color_offsets.map(|o| (0.5 + SH_C0 * read(o)).clamp(0.0, 1.0));
+            let a = 1.0 / (1.0 + (-read(opacity_offset)).exp());
+            splats.colors.push(Srgba::new(
+                (r * 255.0) as u8,
+                (g * 255.0) as u8,
+                (b * 255.0) as u8,
+                (a * 255.0) as u8,
+            ));
+        }
+        Ok(splats)
+    }
+
+    /// Returns the number of splats.
+    pub fn count(&self) -> u32 {
+        self.positions.len() as u32
+    }
+
+    ///
+    /// Returns an error if the splats are not valid.
+    ///
+    pub fn validate(&self) -> Result<(), RendererError> {
+        let count = self.positions.len();
+        for (length, name) in [
+            (self.scales.len(), "scales"),
+            (self.rotations.len(), "rotations"),
+            (self.colors.len(), "colors"),
+        ] {
+            if length < count {
+                Err(RendererError::InvalidBufferLength(
+                    name.to_string(),
+                    count,
+                    length,
+                ))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+///
+/// Renders a set of anisotropic 3D gaussians, also known as gaussian splats, using the standard alpha-blended technique.
+/// Each gaussian is projected onto the screen as a 2D gaussian and the splats are sorted back to front relative to the camera
+/// whenever the camera moves.
+///
+pub struct GaussianSplats {
+    context: Context,
+    position_buffer: VertexBuffer,
+    instance_buffers: RwLock<(
+        InstanceBuffer,
+        InstanceBuffer,
+        InstanceBuffer,
+        InstanceBuffer,
+    )>,
+    last_view: RwLock<Option<(Vec3, Vec3)>>,
+    centers: Vec<Vec3>,
+    covariances: Vec<(Vec3, Vec3)>,
+    colors: Vec<Vec4>,
+    aabb: AxisAlignedBoundingBox,
+    transformation: Mat4,
+}
+
+impl GaussianSplats {
+    ///
+    /// Creates a new set of gaussian splats from the given [CpuGaussianSplats].
+    ///
+    pub fn new(context: &Context, cpu_splats: &CpuGaussianSplats) -> Self {
+        #[cfg(debug_assertions)]
+        cpu_splats.validate().expect("invalid gaussian splats");
+
+        let covariances = cpu_splats
+            .scales
+            .iter()
+            .zip(cpu_splats.rotations.iter())
+            .map(|(scale, rotation)| {
+                let m = Mat3::from(*rotation)
+                    * Mat3::from_cols(
+                        vec3(scale.x, 0.0, 0.0),
+                        vec3(0.0, scale.y, 0.0),
+                        vec3(0.0, 0.0, scale.z),
+                    );
+                let sigma = m * m.transpose();
+                (
+                    vec3(sigma.x.x, sigma.y.x, sigma.z.x),
+                    vec3(sigma.y.y, sigma.z.y, sigma.z.z),
+                )
+            })
+            .collect::<Vec<_>>();
+        let colors = cpu_splats
+            .colors
+            .iter()
+            .map(|c| c.to_linear_srgb())
+            .collect::<Vec<_>>();
+        let mut splats = Self {
+            context: context.clone(),
+            position_buffer: VertexBuffer::new_with_data(
+                context,
+                &[
+                    vec2(-2.0f32, -2.0),
+                    vec2(2.0, -2.0),
+                    vec2(2.0, 2.0),
+                    vec2(2.0, 2.0),
+                    vec2(-2.0, 2.0),
+                    vec2(-2.0, -2.0),
+                ],
+            ),
+            instance_buffers: RwLock::new((
+                InstanceBuffer::new(context),
+                InstanceBuffer::new(context),
+                InstanceBuffer::new(context),
+                InstanceBuffer::new(context),
+            )),
+            last_view: RwLock::new(None),
+            aabb: AxisAlignedBoundingBox::new_with_positions(&cpu_splats.positions),
+            centers: cpu_splats.positions.clone(),
+            covariances,
+            colors,
+            transformation: Mat4::identity(),
+        };
+        splats.update_instance_buffers(None);
+        splats
+    }
+
+    /// Returns the number of splats.
+    pub fn count(&self) -> u32 {
+        self.centers.len() as u32
+    }
+
+    ///
+    /// Returns the local to world transformation applied to all splats.
+    ///
+    pub fn transformation(&self) -> Mat4 {
+        self.transformation
+    }
+
+    ///
+    /// Set the local to world transformation applied to all splats.
+    /// Note that the covariance of each gaussian is only transformed by the rotation and scale part of the transformation.
+    ///
+    pub fn set_transformation(&mut self, transformation: Mat4) {
+        self.transformation = transformation;
+        *self.last_view.write().unwrap() = None;
+    }
+
+    ///
+    /// Sorts the splats back to front as seen from the given camera and uploads them to the GPU.
+    /// Without a camera, the splats are uploaded in their original order.
+    ///
+    fn update_instance_buffers(&self, camera: Option<&Camera>) {
+        let mut indices = (0..self.centers.len()).collect::<Vec<_>>();
+        if let Some(camera) = camera {
+            let view_model = camera.view() * self.transformation;
+            let depths = self
+                .centers
+                .iter()
+                .map(|c| (view_model * c.extend(1.0)).z)
+                .collect::<Vec<_>>();
+            // View space depth is negative in front of the camera, so the farthest splat has the smallest value.
+            indices.sort_by(|a, b| {
+                depths[*a]
+                    .partial_cmp(&depths[*b])
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            });
+            *self.last_view.write().unwrap() = Some((*camera.position(), camera.view_direction()));
+        }
+        let mut buffers = self.instance_buffers.write().unwrap();
+        buffers
+            .0
+            .fill(&indices.iter().map(|i| self.centers[*i]).collect::<Vec<_>>());
+        buffers.1.fill(
+            &indices
+                .iter()
+                .map(|i| self.covariances[*i].0)
+                .collect::<Vec<_>>(),
+        );
+        buffers.2.fill(
+            &indices
+                .iter()
+                .map(|i| self.covariances[*i].1)
+                .collect::<Vec<_>>(),
+        );
+        buffers
+            .3
+            .fill(&indices.iter().map(|i| self.colors[*i]).collect::<Vec<_>>());
+    }
+}
+
+impl<'a> IntoIterator for &'a GaussianSplats {
+    type Item = &'a dyn Object;
+    type IntoIter = std::iter::Once<&'a dyn Object>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        std::iter::once(self)
+    }
+}
+
+impl Geometry for GaussianSplats {
+    fn draw(
+        &self,
+        camera: &Camera,
+        program: &Program,
+        render_states: RenderStates,
+        _attributes: FragmentAttributes,
+    ) {
+        let view = Some((*camera.position(), camera.view_direction()));
+        if *self.last_view.read().unwrap() != view {
+            self.update_instance_buffers(Some(camera));
+        }
+        let viewport = camera.viewport();
+        let projection = camera.projection();
+        program.use_uniform("view", camera.view() * self.transformation);
+        program.use_uniform("projection", projection);
+        program.use_uniform(
+            "focal",
+            vec2(
+                0.5 * viewport.width as f32 * projection.x.x,
+                0.5 * viewport.height as f32 * projection.y.y,
+            ),
+        );
+        program.use_uniform(
+            "viewportSize",
+            vec2(viewport.width as f32, viewport.height as f32),
+        );
+        program.use_vertex_attribute("position", &self.position_buffer);
+        let buffers = self.instance_buffers.read().unwrap();
+        program.use_instance_attribute("center", &buffers.0);
+        program.use_instance_attribute("covariance_a", &buffers.1);
+        program.use_instance_attribute("covariance_b", &buffers.2);
+        program.use_instance_attribute("splat_color", &buffers.3);
+        program.draw_arrays_instanced(render_states, viewport, 6, self.count());
+    }
+
+    fn vertex_shader_source(&self, _required_attributes: FragmentAttributes) -> String {
+        include_str!("shaders/gaussian_splats.vert").to_owned()
+    }
+
+    fn id(&self, _required_attributes: FragmentAttributes) -> u16 {
+        0b1u16 << 15 | 0b1u16 << 8 | 0b1u16
+    }
+
+    fn render_with_material(
+        &self,
+        material: &dyn Material,
+        camera: &Camera,
+        lights: &[&dyn Light],
+    ) {
+        render_with_material(&self.context, camera, self, material, lights)
+    }
+
+    fn render_with_effect(
+        &self,
+        material: &dyn Effect,
+        camera: &Camera,
+        lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) {
+        render_with_effect(
+            &self.context,
+            camera,
+            self,
+            material,
+            lights,
+            color_texture,
+            depth_texture,
+        )
+    }
+
+    fn aabb(&self) -> AxisAlignedBoundingBox {
+        let mut aabb = self.aabb;
+        aabb.transform(&self.transformation);
+        aabb
+    }
+}
+
+impl Object for GaussianSplats {
+    fn render(&self, camera: &Camera, lights: &[&dyn Light]) {
+        render_with_material(&self.context, camera, self, GaussianSplatsMaterial, lights)
+    }
+
+    fn material_type(&self) -> MaterialType {
+        MaterialType::Transparent
+    }
+}
+
+struct GaussianSplatsMaterial;
+
+impl Material for GaussianSplatsMaterial {
+    fn id(&self) -> u16 {
+        0b1u16 << 15 | 0b1u16 << 8 | 0b1u16
+    }
+
+    fn fragment_shader_source(&self, _lights: &[&dyn Light]) -> String {
+        format!(
+            "{}{}",
+            ColorMapping::fragment_shader_source(),
+            include_str!("shaders/gaussian_splats.frag")
+        )
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            color: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(&self, program: &Program, camera: &Camera, _lights: &[&dyn Light]) {
+        camera.color_mapping.use_uniforms(program);
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates {
+            write_mask: WriteMask::COLOR,
+            blend: Blend::TRANSPARENCY,
+            ..Default::default()
+        }
+    }
+
+    fn material_type(&self) -> MaterialType {
+        MaterialType::Transparent
+    }
+}
@@ -0,0 +1,207 @@
+use crate::renderer::*;
+
+///
+/// The shape of each glyph in [Glyphs].
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum GlyphShape {
+    /// An arrow with a cylindrical tail and a conical head.
+    #[default]
+    Arrow,
+    /// A cone which is cheaper to render than an arrow and therefore suited for dense fields.
+    Cone,
+}
+
+///
+/// Oriented and scaled glyphs, for example arrows, visualising a vector field such as a flow field or the normals of a surface,
+/// with a [material] type specified by the generic parameter.
+/// Each glyph starts at a position and points in the direction of the vector at that position, with a length proportional to the length of the vector.
+/// The glyphs can be colored by a scalar value at each position using a [ColorMap], see [Glyphs::set_scalars], in which case the material should use the instance colors,
+/// for example [ColorMaterial] or [PhysicalMaterial].
+///
+/// All glyphs are rendered using instancing, so millions of glyphs can be rendered efficiently.
+///
+pub struct Glyphs<M: Material> {
+    model: Gm<InstancedMesh, M>,
+    positions: Vec<Vec3>,
+    vectors: Vec<Vec3>,
+    colors: Option<Vec<Srgba>>,
+    scale: f32,
+    thickness: f32,
+}
+
+impl<M: Material> Glyphs<M> {
+    ///
+    /// Creates new glyphs with the given shape at the given positions pointing in the direction of the vector with the same index.
+    /// The length of each glyph is the length of the vector multiplied by the scale.
+    ///
+    /// # Panics
+    /// If the number of positions and vectors are not the same.
+    ///
+    pub fn new(
+        context: &Context,
+        shape: GlyphShape,
+        positions: &[Vec3],
+        vectors: &[Vec3],
+        scale: f32,
+        material: M,
+    ) -> Self {
+        let cpu_mesh = match shape {
+            GlyphShape::Arrow => CpuMesh::arrow(0.7, 0.5, 12),
+            GlyphShape::Cone => CpuMesh::cone(12),
+        };
+        let mut glyphs = Self {
+            model: Gm::new(
+                InstancedMesh::new(context, &Instances::default(), &cpu_mesh),
+                material,
+            ),
+            positions: Vec::new(),
+            vectors: Vec::new(),
+            colors: None,
+            scale,
+            thickness: 0.1,
+        };
+        glyphs.set_vectors(positions, vectors);
+        glyphs
+    }
+
+    ///
+    /// Sets the positions and the vectors, for example when a time-dependent vector field changes.
+    /// Previously set scalar colors are removed if the number of glyphs changes.
+    ///
+    /// # Panics
+    /// If the number of positions and vectors are not the same.
+    ///
+    pub fn set_vectors(&mut self, positions: &[Vec3], vectors: &[Vec3]) {
+        assert_eq!(
+            positions.len(),
+            vectors.len(),
+            "the number of positions and vectors must be the same"
+        );
+        if positions.len() != self.positions.len() {
+            self.colors = None;
+        }
+        self.positions = positions.to_vec();
+        self.vectors = vectors.to_vec();
+        self.update();
+    }
+
+    ///
+    /// Colors each glyph by the scalar value with the same index using the given color map, where the minimum and maximum value are mapped to the ends of the color map.
+    ///
+    /// # Panics
+    /// If the number of scalars is not the same as the number of glyphs.
+    ///
+    pub fn set_scalars(&mut self, scalars: &[f32], color_map: ColorMap, min: f32, max: f32) {
+        assert_eq!(
+            scalars.len(),
+            self.positions.len(),
+            "the number of scalars must be the same as the number of glyphs"
+        );
+        let range = (max - min).max(f32::EPSILON);
+        self.colors = Some(
+            scalars
+                .iter()
+                .map(|s| color_map.color((s - min) / range))
+                .collect(),
+        );
+        self.update();
+    }
+
+    ///
+    /// Removes the scalar colors, so all glyphs are rendered with the color of the material.
+    ///
+    pub fn clear_scalars(&mut self) {
+        self.colors = None;
+        self.update();
+    }
+
+    ///
+    /// Returns the scale which is multiplied onto the length of the vectors to get the length of the glyphs.
+    ///
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    ///
+    /// Sets the scale which is multiplied onto the length of the vectors to get the length of the glyphs.
+    ///
+    pub fn set_scale(&mut self, scale: f32) {
+        self.scale = scale;
+        self.update();
+    }
+
+    ///
+    /// Returns the thickness of the glyphs relative to their length.
+    ///
+    pub fn thickness(&self) -> f32 {
+        self.thickness
+    }
+
+    ///
+    /// Sets the thickness of the glyphs relative to their length, the default is 0.1.
+    ///
+    pub fn set_thickness(&mut self, thickness: f32) {
+        self.thickness = thickness;
+        self.update();
+    }
+
+    ///
+    /// Returns the number of glyphs.
+    ///
+    pub fn glyph_count(&self) -> usize {
+        self.positions.len()
+    }
+
+    fn update(&mut self) {
+        let transformations = self
+            .positions
+            .iter()
+            .zip(self.vectors.iter())
+            .map(|(position, vector)| {
+                let length = self.scale * vector.magnitude();
+                if length.abs() < f32::EPSILON {
+                    return Mat4::from_translation(*position) * Mat4::from_scale(0.0);
+                }
+                let x = vector.normalize();
+                let up = if x.y.abs() < 0.99 {
+                    vec3(0.0, 1.0, 0.0)
+                } else {
+                    vec3(0.0, 0.0, 1.0)
+                };
+                let z = x.cross(up).normalize();
+                let y = z.cross(x);
+                let radius = self.thickness * length.abs();
+                Mat4::from_translation(*position)
+                    * Mat4::from(Mat3::from_cols(length * x, radius * y, radius * z))
+            })
+            .collect();
+        self.model.geometry.set_instances(&Instances {
+            transformations,
+            colors: self.colors.clone(),
+            ..Default::default()
+        });
+    }
+}
+
+impl<'a, M: Material> IntoIterator for &'a Glyphs<M> {
+    type Item = &'a dyn Object;
+    type IntoIter = std::iter::Once<&'a dyn Object>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        std::iter::once(&self.model)
+    }
+}
+
+impl<M: Material> std::ops::Deref for Glyphs<M> {
+    type Target = Gm<InstancedMesh, M>;
+    fn deref(&self) -> &Self::Target {
+        &self.model
+    }
+}
+
+impl<M: Material> std::ops::DerefMut for Glyphs<M> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.model
+    }
+}
@@ -0,0 +1,393 @@
+use crate::core::*;
+use crate::renderer::*;
+
+///
+/// Describes how the particles of [GpuParticles] are spawned and how they move and change over their lifetime.
+///
+#[derive(Clone, Debug)]
+pub struct ParticleEmitter {
+    /// The position where the particles are spawned.
+    pub position: Vec3,
+    /// The number of particles spawned per second.
+    pub spawn_rate: f32,
+    /// The time in seconds from a particle is spawned until it disappears.
+    pub lifetime: f32,
+    /// The initial velocity of the particles in units per second.
+    pub velocity: Vec3,
+    /// The maximum angle in radians between the initial velocity of a particle and [ParticleEmitter::velocity], so zero means that all particles move in the same direction.
+    pub spread: f32,
+    /// The acceleration applied to the particles in units per second squared.
+    pub gravity: Vec3,
+    /// The size of the particles when they are spawned.
+    pub start_size: f32,
+    /// The size of the particles when they disappear. The size is linearly interpolated over the lifetime.
+    pub end_size: f32,
+    ///
+    /// The color of the particles over their lifetime given as a list of `(t, color)` pairs sorted by `t`, where `t` is in the range `[0..1]` and is zero when a particle is spawned and one when it disappears.
+    /// The color is linearly interpolated between the pairs and the alpha value is the opacity of the particle, which can be used to fade out the particles.
+    ///
+    pub color_over_life: Vec<(f32, Srgba)>,
+}
+
+impl Default for ParticleEmitter {
+    fn default() -> Self {
+        Self {
+            position: vec3(0.0, 0.0, 0.0),
+            spawn_rate: 100.0,
+            lifetime: 2.0,
+            velocity: vec3(0.0, 5.0, 0.0),
+            spread: 0.3,
+            gravity: vec3(0.0, -9.81, 0.0),
+            start_size: 0.1,
+            end_size: 0.1,
+            color_over_life: vec![(0.0, Srgba::WHITE), (1.0, Srgba::new(255, 255, 255, 0))],
+        }
+    }
+}
+
+// The number of particle states stored in each row of the state textures
+const STATE_WIDTH: u32 = 256;
+const COLOR_OVER_LIFE_SIZE: u32 = 64;
+
+///
+/// A particle system which is simulated entirely on the GPU, so it can handle a large number of particles without any data being transferred between the CPU and GPU each frame.
+/// The particles are spawned by a [ParticleEmitter], moved by [GpuParticles::update] which must be called each frame, and rendered as round billboards facing the camera.
+///
+/// The state of each particle is stored in a pair of textures which are updated in a render pass, reading from one texture and writing to the other (ping-pong), which requires support for rendering to 32 bit float textures.
+/// When rendered as an [Object], the particles are cut off where they intersect the scene.
+/// To instead fade out the particles close to the scene (soft particles), render the particles using the [SoftParticlesEffect] with the depth texture of the scene.
+///
+pub struct GpuParticles {
+    context: Context,
+    emitter: ParticleEmitter,
+    capacity: u32,
+    states: [Texture2DArray; 2],
+    current: usize,
+    color_over_life: Texture2D,
+    corners: VertexBuffer,
+    next_index: u32,
+    spawn_remainder: f32,
+    time: f32,
+}
+
+impl GpuParticles {
+    ///
+    /// Creates a new particle system with room for the given maximum number of alive particles, which is rounded up to a multiple of 256.
+    /// When the maximum number is reached, the oldest particles are replaced by new particles.
+    ///
+    pub fn new(context: &Context, capacity: u32, emitter: ParticleEmitter) -> Self {
+        let height = ((capacity.max(1) + STATE_WIDTH - 1) / STATE_WIDTH).max(1);
+        let new_state = || {
+            let mut texture = Texture2DArray::new_empty::<[f32; 4]>(
+                context,
+                STATE_WIDTH,
+                height,
+                2,
+                Interpolation::Nearest,
+                Interpolation::Nearest,
+                None,
+                Wrapping::ClampToEdge,
+                Wrapping::ClampToEdge,
+            );
+            // All particles start out dead, ie. with an age larger than the lifetime
+            let positions = vec![[0.0f32, 0.0, 0.0, 1.0]; (STATE_WIDTH * height) as usize];
+            let velocities = vec![[0.0f32; 4]; positions.len()];
+            texture.fill(&[&positions[..], &velocities[..]]);
+            texture
+        };
+        let mut particles = Self {
+            context: context.clone(),
+            emitter: emitter.clone(),
+            capacity: STATE_WIDTH * height,
+            states: [new_state(), new_state()],
+            current: 0,
+            color_over_life: Texture2D::new_empty::<[f16; 4]>(
+                context,
+                COLOR_OVER_LIFE_SIZE,
+                1,
+                Interpolation::Linear,
+                Interpolation::Linear,
+                None,
+                Wrapping::ClampToEdge,
+                Wrapping::ClampToEdge,
+            ),
+            corners: VertexBuffer::new_with_data(
+                context,
+                &[
+                    vec2(-1.0, -1.0),
+                    vec2(1.0, -1.0),
+                    vec2(1.0, 1.0),
+                    vec2(1.0, 1.0),
+                    vec2(-1.0, 1.0),
+                    vec2(-1.0, -1.0),
+                ],
+            ),
+            next_index: 0,
+            spawn_remainder: 0.0,
+            time: 0.0,
+        };
+        particles.set_emitter(emitter);
+        particles
+    }
+
+    ///
+    /// Returns the maximum number of alive particles.
+    ///
+    pub fn capacity(&self) -> u32 {
+        self.capacity
+    }
+
+    ///
+    /// Returns the emitter which spawns the particles.
+    ///
+    pub fn emitter(&self) -> &ParticleEmitter {
+        &self.emitter
+    }
+
+    ///
+    /// Sets the emitter which spawns the particles. The particles which are already alive keep their lifetime and velocity,
+    /// but are affected by the new gravity, size and color.
+    ///
+    pub fn set_emitter(&mut self, emitter: ParticleEmitter) {
+        let data = (0..COLOR_OVER_LIFE_SIZE)
+            .map(|i| {
+                let t = i as f32 / (COLOR_OVER_LIFE_SIZE - 1) as f32;
+                let color = color_at(&emitter.color_over_life, t);
+                [
+                    f16::from_f32(color.x),
+                    f16::from_f32(color.y),
+                    f16::from_f32(color.z),
+                    f16::from_f32(color.w),
+                ]
+            })
+            .collect::<Vec<_>>();
+        self.color_over_life.fill(&data);
+        self.emitter = emitter;
+    }
+
+    ///
+    /// Spawns new particles and moves the alive particles forward in time by the given elapsed time in milliseconds, for example the elapsed time given in the frame input.
+    /// Must be called each frame.
+    ///
+    pub fn update(&mut self, elapsed_time: f64) {
+        let dt = (elapsed_time / 1000.0) as f32;
+        let spawn = self.emitter.spawn_rate.max(0.0) * dt + self.spawn_remainder;
+        let spawn_count = (spawn.floor() as u32).min(self.capacity);
+        self.spawn_remainder = if spawn_count < self.capacity {
+            spawn - spawn_count as f32
+        } else {
+            0.0
+        };
+        self.time += dt;
+
+        let (first, second) = self.states.split_at_mut(1);
+        let (source, target) = if self.current == 0 {
+            (&first[0], &mut second[0])
+        } else {
+            (&second[0], &mut first[0])
+        };
+        let camera = Camera::new_2d(Viewport::new_at_origo(target.width(), target.height()));
+        let material = SimulationMaterial {
+            state: source,
+            emitter: &self.emitter,
+            spawn_start: self.next_index,
+            spawn_count,
+            dt,
+            time: self.time,
+        };
+        let layers = [0, 1];
+        target.as_color_target(&layers, None).write(|| {
+            apply_screen_material(&self.context, material, &camera, &[]);
+        });
+        self.current = 1 - self.current;
+        self.next_index = (self.next_index + spawn_count) % self.capacity;
+    }
+}
+
+fn color_at(keys: &[(f32, Srgba)], t: f32) -> Vec4 {
+    match keys.iter().position(|(key_t, _)| *key_t > t) {
+        None => keys
+            .last()
+            .map(|(_, c)| c.to_linear_srgb())
+            .unwrap_or(vec4(1.0, 1.0, 1.0, 1.0)),
+        Some(0) => keys[0].1.to_linear_srgb(),
+        Some(i) => {
+            let (t0, c0) = keys[i - 1];
+            let (t1, c1) = keys[i];
+            let s = if t1 > t0 { (t - t0) / (t1 - t0) } else { 0.0 };
+            c0.to_linear_srgb().lerp(c1.to_linear_srgb(), s)
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a GpuParticles {
+    type Item = &'a dyn Object;
+    type IntoIter = std::iter::Once<&'a dyn Object>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        std::iter::once(self)
+    }
+}
+
+impl Geometry for GpuParticles {
+    fn draw(
+        &self,
+        camera: &Camera,
+        program: &Program,
+        render_states: RenderStates,
+        _attributes: FragmentAttributes,
+    ) {
+        let view = camera.view();
+        program.use_uniform("viewProjection", camera.projection() * view);
+        program.use_uniform("cameraRight", vec3(view.x.x, view.y.x, view.z.x));
+        program.use_uniform("cameraUp", vec3(view.x.y, view.y.y, view.z.y));
+        program.use_uniform("size", vec2(self.emitter.start_size, self.emitter.end_size));
+        program.use_uniform("stateWidth", STATE_WIDTH as i32);
+        program.use_texture_array("state", &self.states[self.current]);
+        program.use_texture("colorOverLife", &self.color_over_life);
+        program.use_vertex_attribute("corner", &self.corners);
+        program.draw_arrays_instanced(render_states, camera.viewport(), 6, self.capacity);
+    }
+
+    fn vertex_shader_source(&self, _required_attributes: FragmentAttributes) -> String {
+        include_str!("shaders/gpu_particles.vert").to_owned()
+    }
+
+    fn id(&self, _required_attributes: FragmentAttributes) -> u16 {
+        0b1u16 << 15 | 0b1000u16
+    }
+
+    fn render_with_material(
+        &self,
+        material: &dyn Material,
+        camera: &Camera,
+        lights: &[&dyn Light],
+    ) {
+        render_with_material(&self.context, camera, self, material, lights)
+    }
+
+    fn render_with_effect(
+        &self,
+        material: &dyn Effect,
+        camera: &Camera,
+        lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) {
+        render_with_effect(
+            &self.context,
+            camera,
+            self,
+            material,
+            lights,
+            color_texture,
+            depth_texture,
+        )
+    }
+
+    fn aabb(&self) -> AxisAlignedBoundingBox {
+        AxisAlignedBoundingBox::INFINITE
+    }
+}
+
+impl Object for GpuParticles {
+    fn render(&self, camera: &Camera, lights: &[&dyn Light]) {
+        render_with_material(&self.context, camera, self, ParticleMaterial, lights)
+    }
+
+    fn material_type(&self) -> MaterialType {
+        MaterialType::Transparent
+    }
+}
+
+struct ParticleMaterial;
+
+impl Material for ParticleMaterial {
+    fn id(&self) -> u16 {
+        0b1u16 << 15 | 0b1u16 << 12 | 0b0111u16
+    }
+
+    fn fragment_shader_source(&self, _lights: &[&dyn Light]) -> String {
+        let mut source = ColorMapping::fragment_shader_source().to_owned();
+        source.push_str(include_str!("shaders/gpu_particles.frag"));
+        source
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            uv: true,
+            color: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(&self, program: &Program, camera: &Camera, _lights: &[&dyn Light]) {
+        camera.color_mapping.use_uniforms(program);
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates {
+            write_mask: WriteMask::COLOR,
+            blend: Blend::TRANSPARENCY,
+            cull: Cull::None,
+            ..Default::default()
+        }
+    }
+
+    fn material_type(&self) -> MaterialType {
+        MaterialType::Transparent
+    }
+}
+
+struct SimulationMaterial<'a> {
+    state: &'a Texture2DArray,
+    emitter: &'a ParticleEmitter,
+    spawn_start: u32,
+    spawn_count: u32,
+    dt: f32,
+    time: f32,
+}
+
+impl Material for SimulationMaterial<'_> {
+    fn id(&self) -> u16 {
+        0b1u16 << 15 | 0b1u16 << 12 | 0b0110u16
+    }
+
+    fn fragment_shader_source(&self, _lights: &[&dyn Light]) -> String {
+        include_str!("shaders/gpu_particles_simulation.frag").to_owned()
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes::NONE
+    }
+
+    fn use_uniforms(&self, program: &Program, _camera: &Camera, _lights: &[&dyn Light]) {
+        program.use_texture_array("state", self.state);
+        program.use_uniform("stateWidth", STATE_WIDTH as i32);
+        program.use_uniform(
+            "capacity",
+            (self.state.width() * self.state.height()) as i32,
+        );
+        program.use_uniform("spawnStart", self.spawn_start as i32);
+        program.use_uniform("spawnCount", self.spawn_count as i32);
+        program.use_uniform("dt", self.dt);
+        program.use_uniform("time", self.time);
+        program.use_uniform("emitterPosition", self.emitter.position);
+        program.use_uniform("emitterVelocity", self.emitter.velocity);
+        program.use_uniform("spread", self.emitter.spread);
+        program.use_uniform("lifetime", self.emitter.lifetime.max(0.0001));
+        program.use_uniform("gravity", self.emitter.gravity);
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates {
+            write_mask: WriteMask::COLOR,
+            depth_test: DepthTest::Always,
+            ..Default::default()
+        }
+    }
+
+    fn material_type(&self) -> MaterialType {
+        MaterialType::Opaque
+    }
+}
@@ -161,6 +161,22 @@ impl<M: Material + FromCpuMaterial + Clone + Default> InstancedModel<M> {
     pub fn animate(&mut self, time: f32) {
         self.iter_mut().for_each(|m| m.animate(time));
     }
+
+    ///
+    /// Update the instances of all parts of this model from the given [InstanceSource].
+    ///
+    pub fn set_instances_from(&mut self, source: &(impl InstanceSource + ?Sized)) {
+        self.iter_mut()
+            .for_each(|m| m.geometry.set_instances_from(source));
+    }
+
+    ///
+    /// Overwrites the attributes of the instances of all parts of this model starting at the given index, see [InstancedMesh::update_instances].
+    ///
+    pub fn update_instances(&mut self, start: usize, instances: &Instances) {
+        self.iter_mut()
+            .for_each(|m| m.geometry.update_instances(start, instances));
+    }
 }
 
 impl<M: Material> std::ops::Deref for InstancedModel<M> {
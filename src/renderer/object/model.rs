@@ -7,9 +7,17 @@ pub use three_d_asset::Model as CpuModel;
 pub struct ModelPart<M: Material> {
     gm: Gm<Mesh, M>,
     animations: Vec<KeyFrameAnimation>,
+    name: String,
 }
 
 impl<M: Material> ModelPart<M> {
+    ///
+    /// Returns the name of this model part, which is the name of the corresponding geometry in the [CpuModel], for example the node name in a glTF file.
+    ///
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
     ///
     /// Returns a list of unique names for the animations for this model part. Use these names as input to [Self::choose_animation].
     ///
@@ -33,6 +41,14 @@ impl<M: Material> ModelPart<M> {
             self.set_animation(move |time| animation.transformation(time));
         }
     }
+
+    ///
+    /// Replaces the material of this model part without rebuilding the geometry.
+    /// To be able to switch to a material of another type, use a model with boxed materials, see [Model::into_boxed].
+    ///
+    pub fn set_material(&mut self, material: M) {
+        self.gm.material = material;
+    }
 }
 
 use std::ops::Deref;
@@ -87,6 +103,15 @@ impl<'a, M: Material> IntoIterator for &'a Model<M> {
     }
 }
 
+impl<M: Material> IntoIterator for Model<M> {
+    type Item = ModelPart<M>;
+    type IntoIter = std::vec::IntoIter<ModelPart<M>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
 impl<M: Material + FromCpuMaterial + Clone + Default> Model<M> {
     ///
     /// Constructs a [Model] from a [CpuModel], ie. constructs a list of [Gm]s with a [Mesh] as geometry (constructed from the [CpuMesh]es in the [CpuModel]) and
@@ -115,13 +140,14 @@ impl<M: Material + FromCpuMaterial + Clone + Default> Model<M> {
                     M::default()
                 };
                 let mut gm = Gm {
-                    geometry: Mesh::new(context, geometry),
+                    geometry: Mesh::new_shared(context, geometry, None),
                     material,
                 };
                 gm.set_transformation(primitive.transformation);
                 gms.push(ModelPart {
                     gm,
                     animations: primitive.animations.clone(),
+                    name: primitive.name.clone(),
                 });
             }
         }
@@ -131,7 +157,250 @@ impl<M: Material + FromCpuMaterial + Clone + Default> Model<M> {
         }
         Ok(model)
     }
+}
 
+///
+/// Loads a glTF 2.0 file, either a `.gltf` file with external buffers and textures placed relative to the file or a binary `.glb` file,
+/// and returns a [SceneNode] tree which mirrors the node hierarchy of the file.
+/// On web, the path is an url which is fetched using http.
+///
+/// The returned root node has a child node for each root node in the scenes of the file and each of these nodes has the name, the local transformation and the children of the corresponding glTF node.
+/// The primitives of the mesh of a glTF node are [ModelPart]s placed in child nodes of that node, so changing the transformation of a node moves all of the primitives below it.
+/// The materials contain the PBR metallic-roughness parameters and the albedo, metallic/roughness, normal, occlusion and emissive textures.
+/// The second set of uv coordinates (`TEXCOORD_1`) is uploaded together with the meshes and used for the occlusion and emissive textures which specify it.
+/// Use [load_gltf_model] to get the [CpuModel] instead, for example to construct a [Model] with another material type.
+///
+#[cfg(feature = "gltf")]
+#[cfg_attr(docsrs, doc(feature = "gltf"))]
+pub async fn load_gltf(
+    context: &Context,
+    path: impl AsRef<std::path::Path>,
+) -> Result<SceneNode, RendererError> {
+    let path = path.as_ref();
+    let mut raw_assets = three_d_asset::io::load_async(&[path]).await?;
+    let document = ::gltf::Gltf::from_slice(raw_assets.get(path)?)
+        .map_err(|e| RendererError::InvalidGltf(e.to_string()))?;
+    let buffers = gltf_buffers(&document, &raw_assets, path);
+    let cpu_model = prepare_gltf_model(raw_assets.deserialize(path)?);
+    let primitive_count = document
+        .scenes()
+        .flat_map(|scene| scene.nodes())
+        .map(|node| gltf_primitive_count(&node))
+        .sum::<usize>();
+    if primitive_count != cpu_model.geometries.len() {
+        return Err(RendererError::InvalidGltf(format!(
+            "the scenes contain {} primitives but {} were loaded",
+            primitive_count,
+            cpu_model.geometries.len()
+        )));
+    }
+    let mut materials = cpu_model
+        .materials
+        .iter()
+        .map(|m| PhysicalMaterial::from_cpu_material(context, m))
+        .collect::<Vec<_>>();
+    if document.materials().len() == materials.len() {
+        for (material, gltf_material) in materials.iter_mut().zip(document.materials()) {
+            if let Some(info) = gltf_material.occlusion_texture() {
+                material.occlusion_uv_channel = gltf_uv_channel(info.tex_coord());
+            }
+            if let Some(info) = gltf_material.emissive_texture() {
+                material.emissive_uv_channel = gltf_uv_channel(info.tex_coord());
+            }
+        }
+    }
+    let mut primitives = cpu_model.geometries.iter();
+    let mut root = SceneNode::new();
+    for node in document.scenes().flat_map(|scene| scene.nodes()) {
+        let child = gltf_scene_node(
+            context,
+            &node,
+            Mat4::identity(),
+            &mut primitives,
+            &materials,
+            &buffers,
+        )?;
+        root.add_child(child);
+    }
+    Ok(root)
+}
+
+#[cfg(feature = "gltf")]
+fn gltf_primitive_count(node: &::gltf::Node) -> usize {
+    node.mesh().map(|mesh| mesh.primitives().len()).unwrap_or(0)
+        + node
+            .children()
+            .map(|child| gltf_primitive_count(&child))
+            .sum::<usize>()
+}
+
+#[cfg(feature = "gltf")]
+fn gltf_scene_node<'a>(
+    context: &Context,
+    node: &::gltf::Node,
+    parent_transformation: Mat4,
+    primitives: &mut impl Iterator<Item = &'a three_d_asset::Primitive>,
+    materials: &[PhysicalMaterial],
+    buffers: &[Option<Vec<u8>>],
+) -> Result<SceneNode, RendererError> {
+    let mut scene_node = SceneNode::new();
+    scene_node.set_name(node.name().map(|name| name.to_owned()));
+    let local_transformation = Mat4::from(node.transform().matrix());
+    scene_node.set_transformation(local_transformation);
+    let world_transformation = parent_transformation * local_transformation;
+    if let Some(mesh) = node.mesh() {
+        for gltf_primitive in mesh.primitives() {
+            let primitive = primitives.next().unwrap();
+            if let CpuGeometry::Triangles(geometry) = &primitive.geometry {
+                let uvs1 = gltf_primitive
+                    .reader(|buffer| buffers.get(buffer.index())?.as_deref())
+                    .read_tex_coords(1)
+                    .map(|uvs| uvs.into_f32().map(|[u, v]| vec2(u, v)).collect::<Vec<_>>())
+                    .filter(|uvs| uvs.len() == geometry.positions.len());
+                let mut material = match primitive.material_index {
+                    Some(material_index) => materials
+                        .get(material_index)
+                        .ok_or_else(|| {
+                            RendererError::MissingMaterial(
+                                material_index.to_string(),
+                                primitive.name.clone(),
+                            )
+                        })?
+                        .clone(),
+                    None => PhysicalMaterial::default(),
+                };
+                if uvs1.is_none() {
+                    // Fall back to the first set of uv coordinates if the primitive does not have a second set
+                    material.occlusion_uv_channel = UvChannel::Uv0;
+                    material.emissive_uv_channel = UvChannel::Uv0;
+                }
+                let mut part = ModelPart {
+                    gm: Gm {
+                        geometry: Mesh::new_shared(context, geometry, uvs1.as_deref()),
+                        material,
+                    },
+                    animations: primitive.animations.clone(),
+                    name: primitive.name.clone(),
+                };
+                if let Some(animation_name) = part.animations().first().cloned() {
+                    part.choose_animation(animation_name.as_deref());
+                }
+                // Place the primitive relative to the node such that it ends up with the same transformation as in a Model
+                let mut primitive_node = SceneNode::new_with_object(part);
+                primitive_node.set_transformation(
+                    world_transformation
+                        .invert()
+                        .map(|inverse| inverse * primitive.transformation)
+                        .unwrap_or(Mat4::identity()),
+                );
+                scene_node.add_child(primitive_node);
+            }
+        }
+    }
+    for child in node.children() {
+        let child = gltf_scene_node(
+            context,
+            &child,
+            world_transformation,
+            primitives,
+            materials,
+            buffers,
+        )?;
+        scene_node.add_child(child);
+    }
+    Ok(scene_node)
+}
+
+#[cfg(feature = "gltf")]
+fn gltf_uv_channel(tex_coord: u32) -> UvChannel {
+    if tex_coord == 1 {
+        UvChannel::Uv1
+    } else {
+        UvChannel::Uv0
+    }
+}
+
+// Returns the data of the buffers in the glTF file or `None` for the buffers which are not available
+#[cfg(feature = "gltf")]
+fn gltf_buffers(
+    document: &::gltf::Gltf,
+    raw_assets: &three_d_asset::io::RawAssets,
+    path: &std::path::Path,
+) -> Vec<Option<Vec<u8>>> {
+    use base64::Engine;
+    document
+        .buffers()
+        .map(|buffer| match buffer.source() {
+            ::gltf::buffer::Source::Bin => document.blob.clone(),
+            ::gltf::buffer::Source::Uri(uri) => {
+                if let Some(data) = uri.strip_prefix("data:") {
+                    let (_, data) = data.split_once(";base64,")?;
+                    base64::engine::general_purpose::STANDARD.decode(data).ok()
+                } else {
+                    raw_assets
+                        .get(path.parent().unwrap_or(std::path::Path::new("")).join(uri))
+                        .ok()
+                        .map(|data| data.to_vec())
+                }
+            }
+        })
+        .collect()
+}
+
+///
+/// Loads a glTF 2.0 file, either a `.gltf` file with external buffers and textures placed relative to the file or a binary `.glb` file,
+/// and returns a [CpuModel] which can be used to construct a [Model], for example `Model::<PhysicalMaterial>::new(&context, &cpu_model)`.
+/// On web, the path is an url which is fetched using http.
+///
+/// The [CpuModel] contains a [CpuMesh] for each primitive in the scene with the transformation of the node hierarchy applied as the transformation of the primitive,
+/// the name of the node as the name of the primitive and the node animations, if any.
+/// The materials contain the PBR metallic-roughness parameters and the albedo, metallic/roughness, normal, occlusion and emissive textures.
+/// Tangents are computed for the meshes which use a normal texture and do not specify tangents.
+/// Use [load_gltf] to keep the node hierarchy and the second set of uv coordinates, which a [CpuMesh] cannot contain.
+///
+#[cfg(feature = "gltf")]
+#[cfg_attr(docsrs, doc(feature = "gltf"))]
+pub async fn load_gltf_model(path: impl AsRef<std::path::Path>) -> Result<CpuModel, RendererError> {
+    let path = path.as_ref();
+    let cpu_model: CpuModel = three_d_asset::io::load_async(&[path])
+        .await?
+        .deserialize(path)?;
+    Ok(prepare_gltf_model(cpu_model))
+}
+
+#[cfg(feature = "gltf")]
+fn prepare_gltf_model(mut cpu_model: CpuModel) -> CpuModel {
+    let normal_mapped = cpu_model
+        .materials
+        .iter()
+        .map(|m| m.normal_texture.is_some())
+        .collect::<Vec<_>>();
+    for primitive in cpu_model.geometries.iter_mut() {
+        let uses_normal_texture = primitive
+            .material_index
+            .map(|i| normal_mapped.get(i).copied().unwrap_or(false))
+            .unwrap_or(false);
+        if let CpuGeometry::Triangles(mesh) = &mut primitive.geometry {
+            if uses_normal_texture
+                && mesh.tangents.is_none()
+                && mesh.normals.is_some()
+                && mesh.uvs.is_some()
+            {
+                mesh.compute_tangents();
+            }
+        }
+    }
+    cpu_model
+}
+
+impl Upload for CpuModel {
+    type Output = Result<Model<PhysicalMaterial>, RendererError>;
+    fn upload(self, context: &Context) -> Self::Output {
+        Model::new(context, &self)
+    }
+}
+
+impl<M: Material> Model<M> {
     ///
     /// Returns a list of unique names for the animations in this model. Use these names as input to [Self::choose_animation].
     ///
@@ -158,6 +427,39 @@ impl<M: Material + FromCpuMaterial + Clone + Default> Model<M> {
     pub fn animate(&mut self, time: f32) {
         self.iter_mut().for_each(|m| m.animate(time));
     }
+
+    ///
+    /// Replaces the material of all parts of this model without rebuilding the geometry.
+    ///
+    pub fn set_material(&mut self, material: M)
+    where
+        M: Clone,
+    {
+        self.iter_mut()
+            .for_each(|part| part.set_material(material.clone()));
+    }
+}
+
+impl<M: Material + 'static> Model<M> {
+    ///
+    /// Converts this model into a model where the material of each part is stored as a `Box<dyn Material>`.
+    /// This makes it possible to switch the material of a model part to a material of another type at runtime using [ModelPart::set_material].
+    ///
+    pub fn into_boxed(self) -> Model<Box<dyn Material>> {
+        Model(
+            self.0
+                .into_iter()
+                .map(|part| ModelPart {
+                    gm: Gm {
+                        geometry: part.gm.geometry,
+                        material: Box::new(part.gm.material) as Box<dyn Material>,
+                    },
+                    animations: part.animations,
+                    name: part.name,
+                })
+                .collect(),
+        )
+    }
 }
 
 impl<M: Material> std::ops::Deref for Model<M> {
@@ -0,0 +1,68 @@
+use crate::renderer::*;
+
+///
+/// A 3D model which is a blend between two meshes with the same topology, see [MorphMesh], with a [material] type specified by the generic parameter.
+/// Both forward and deferred materials can be used.
+/// The blend is controlled by a single weight between 0 and 1, see [MorphModel::set_weight], which for example can be connected to a slider in a before/after comparison.
+///
+pub struct MorphModel<M: Material> {
+    gm: Gm<MorphMesh, M>,
+}
+
+impl<M: Material> MorphModel<M> {
+    ///
+    /// Constructs a new morph model which blends from the first mesh to the second mesh using the given material.
+    /// The blend weight is initially 0, ie. the model looks like the first mesh.
+    ///
+    /// # Panics
+    /// If the two meshes do not have the same number of vertices.
+    ///
+    pub fn new(context: &Context, from: &CpuMesh, to: &CpuMesh, material: M) -> Self {
+        Self {
+            gm: Gm::new(MorphMesh::new(context, from, to), material),
+        }
+    }
+
+    ///
+    /// Returns the blend weight, where 0 is the first mesh and 1 is the second mesh.
+    ///
+    pub fn weight(&self) -> f32 {
+        self.gm.geometry.weight()
+    }
+
+    ///
+    /// Sets the blend weight, where 0 is the first mesh and 1 is the second mesh. The weight is clamped to the range `[0, 1]`.
+    ///
+    pub fn set_weight(&mut self, weight: f32) {
+        self.gm.geometry.set_weight(weight);
+    }
+
+    ///
+    /// Set the local to world transformation applied to the model.
+    ///
+    pub fn set_transformation(&mut self, transformation: Mat4) {
+        self.gm.geometry.set_transformation(transformation);
+    }
+}
+
+impl<'a, M: Material> IntoIterator for &'a MorphModel<M> {
+    type Item = &'a dyn Object;
+    type IntoIter = std::iter::Once<&'a dyn Object>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        std::iter::once(&self.gm)
+    }
+}
+
+impl<M: Material> std::ops::Deref for MorphModel<M> {
+    type Target = Gm<MorphMesh, M>;
+    fn deref(&self) -> &Self::Target {
+        &self.gm
+    }
+}
+
+impl<M: Material> std::ops::DerefMut for MorphModel<M> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.gm
+    }
+}
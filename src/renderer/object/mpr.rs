@@ -0,0 +1,279 @@
+use crate::core::*;
+use crate::renderer::*;
+use std::sync::Arc;
+
+///
+/// The orientation of a slice in [MprViews], where the volume is assumed to be in the patient coordinate system used by DICOM, see [CpuVolume],
+/// ie. the x axis points to the left of the patient, the y axis to the back and the z axis to the head.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SliceOrientation {
+    /// A slice orthogonal to the z axis, ie. dividing the body into an upper and a lower part.
+    Axial,
+    /// A slice orthogonal to the y axis, ie. dividing the body into a front and a back part.
+    Coronal,
+    /// A slice orthogonal to the x axis, ie. dividing the body into a left and a right part.
+    Sagittal,
+}
+
+impl SliceOrientation {
+    fn axis(&self) -> usize {
+        match self {
+            Self::Sagittal => 0,
+            Self::Coronal => 1,
+            Self::Axial => 2,
+        }
+    }
+
+    // The axes of the volume along the horizontal and vertical direction of the slice
+    fn slice_axes(&self) -> (usize, usize) {
+        match self {
+            Self::Sagittal => (1, 2),
+            Self::Coronal => (0, 2),
+            Self::Axial => (0, 1),
+        }
+    }
+}
+
+///
+/// Multi-planar reconstruction (MPR) of a volume, ie. an axial, a coronal and a sagittal slice through a [Texture3D] which all pass through a shared crosshair position.
+/// Each slice is usually rendered in its own viewport using the camera returned by [MprViews::camera], while all three slices can be rendered together in a 3D view next to for example a [VoxelGrid].
+/// When the crosshair is moved, for example by clicking in one of the views using [MprViews::position_at_pixel], all three slices and crosshairs are updated.
+///
+/// The volume is scaled to fill a box of the given size with center in origo which is then transformed by the transformation, see [CpuVolume::transformation].
+///
+pub struct MprViews {
+    slices: [Gm<Mesh, MprMaterial>; 3],
+    size: Vec3,
+    transformation: Mat4,
+    crosshair: Vec3,
+}
+
+impl MprViews {
+    ///
+    /// Creates axial, coronal and sagittal slices through the center of the given volume which is scaled to fill a box with the given size.
+    /// The window covers all values in the range `[0..1]`.
+    ///
+    pub fn new(context: &Context, volume: Arc<Texture3D>, size: Vec3) -> Self {
+        let new_slice = |axis: usize| {
+            let mut material = MprMaterial::new(volume.clone(), Mat4::identity());
+            material.slice_axis = axis;
+            material.crosshair_width = 1.0;
+            Gm::new(Mesh::new(context, &CpuMesh::square()), material)
+        };
+        let mut mpr = Self {
+            slices: [new_slice(0), new_slice(1), new_slice(2)],
+            size,
+            transformation: Mat4::identity(),
+            crosshair: vec3(0.5, 0.5, 0.5),
+        };
+        mpr.update();
+        mpr
+    }
+
+    ///
+    /// Returns the slice with the given orientation.
+    ///
+    pub fn slice(&self, orientation: SliceOrientation) -> &Gm<Mesh, MprMaterial> {
+        &self.slices[orientation.axis()]
+    }
+
+    ///
+    /// Returns the slice with the given orientation, for example to change the colors of the crosshair.
+    ///
+    pub fn slice_mut(&mut self, orientation: SliceOrientation) -> &mut Gm<Mesh, MprMaterial> {
+        &mut self.slices[orientation.axis()]
+    }
+
+    ///
+    /// Returns the local to world transformation of the volume.
+    ///
+    pub fn transformation(&self) -> Mat4 {
+        self.transformation
+    }
+
+    ///
+    /// Set the local to world transformation of the volume.
+    ///
+    pub fn set_transformation(&mut self, transformation: Mat4) {
+        self.transformation = transformation;
+        self.update();
+    }
+
+    ///
+    /// Returns the window, ie. the width of the range of values shown from black to white.
+    ///
+    pub fn window(&self) -> f32 {
+        self.slices[0].material.window
+    }
+
+    ///
+    /// Returns the level, ie. the center of the range of values shown from black to white.
+    ///
+    pub fn level(&self) -> f32 {
+        self.slices[0].material.level
+    }
+
+    ///
+    /// Sets the window and level of all slices, so the values from `level - 0.5 * window` to `level + 0.5 * window` are shown from black to white.
+    /// For a volume loaded using [CpuVolume], the window and level can be given in the original units, for example Hounsfield units, using [CpuVolume::normalize].
+    ///
+    pub fn set_window_level(&mut self, window: f32, level: f32) {
+        for slice in self.slices.iter_mut() {
+            slice.material.window = window;
+            slice.material.level = level;
+        }
+    }
+
+    ///
+    /// Returns the position of the crosshair in world space.
+    ///
+    pub fn crosshair(&self) -> Vec3 {
+        (self.texture_transformation().invert().unwrap() * self.crosshair.extend(1.0)).truncate()
+    }
+
+    ///
+    /// Moves the crosshair to the given position in world space, which is clamped to the volume, and moves each slice to pass through the crosshair.
+    ///
+    pub fn set_crosshair(&mut self, position: Vec3) {
+        let uvw = (self.texture_transformation() * position.extend(1.0)).truncate();
+        self.crosshair = vec3(
+            uvw.x.clamp(0.0, 1.0),
+            uvw.y.clamp(0.0, 1.0),
+            uvw.z.clamp(0.0, 1.0),
+        );
+        self.update();
+    }
+
+    ///
+    /// Shows or hides the crosshair in all slices.
+    ///
+    pub fn set_crosshair_visible(&mut self, visible: bool) {
+        for slice in self.slices.iter_mut() {
+            slice.material.crosshair_width = if visible { 1.0 } else { 0.0 };
+        }
+    }
+
+    ///
+    /// Returns an orthographic camera in the given viewport looking at the slice with the given orientation, so the whole volume fits in the viewport.
+    /// Axial slices are seen from the feet, coronal slices from the front and sagittal slices from the left of the patient.
+    ///
+    pub fn camera(&self, orientation: SliceOrientation, viewport: Viewport) -> Camera {
+        let (u, v) = orientation.slice_axes();
+        let axis = orientation.axis();
+        let center = self.slice_center(orientation);
+        let world_vector = |axis: usize, length: f32| {
+            let mut vector = vec3(0.0, 0.0, 0.0);
+            vector[axis] = length;
+            (self.transformation * vector.extend(0.0)).truncate()
+        };
+        let width = world_vector(u, self.size[u]).magnitude();
+        let height = world_vector(v, self.size[v]).magnitude();
+        // Radiological convention, ie. the left of the patient is shown to the right
+        let (view_sign, up_sign) = match orientation {
+            SliceOrientation::Axial => (1.0, -1.0),
+            SliceOrientation::Coronal => (1.0, 1.0),
+            SliceOrientation::Sagittal => (-1.0, 1.0),
+        };
+        let up = up_sign * world_vector(v, 1.0).normalize();
+        let direction = view_sign * world_vector(axis, 1.0).normalize();
+        let distance = world_vector(0, self.size.x).magnitude()
+            + world_vector(1, self.size.y).magnitude()
+            + world_vector(2, self.size.z).magnitude();
+        Camera::new_orthographic(
+            viewport,
+            center - distance * direction,
+            center,
+            up,
+            height.max(width * viewport.height as f32 / viewport.width.max(1) as f32),
+            0.0,
+            2.0 * distance,
+        )
+    }
+
+    ///
+    /// Returns the position in world space on the slice with the given orientation seen in the given pixel using the given camera,
+    /// for example the camera returned by [MprViews::camera], or `None` if the volume is not visible in the pixel.
+    /// Use [MprViews::set_crosshair] to move the crosshair to the returned position.
+    ///
+    pub fn position_at_pixel(
+        &self,
+        orientation: SliceOrientation,
+        camera: &Camera,
+        pixel: impl Into<PhysicalPoint> + Copy,
+    ) -> Option<Vec3> {
+        let ray = camera.ray_at_pixel(pixel);
+        let mut normal = vec3(0.0, 0.0, 0.0);
+        normal[orientation.axis()] = 1.0;
+        let normal = (self.transformation * normal.extend(0.0)).truncate();
+        let denominator = ray.direction.dot(normal);
+        if denominator.abs() < f32::EPSILON {
+            return None;
+        }
+        let t = (self.slice_center(orientation) - ray.origin).dot(normal) / denominator;
+        let position = ray.at(t);
+        let uvw = (self.texture_transformation() * position.extend(1.0)).truncate();
+        let epsilon = 0.0001;
+        if (0..3).all(|i| uvw[i] >= -epsilon && uvw[i] <= 1.0 + epsilon) {
+            Some(position)
+        } else {
+            None
+        }
+    }
+
+    fn texture_transformation(&self) -> Mat4 {
+        Mat4::from_translation(vec3(0.5, 0.5, 0.5))
+            * Mat4::from_nonuniform_scale(1.0 / self.size.x, 1.0 / self.size.y, 1.0 / self.size.z)
+            * self.transformation.invert().unwrap()
+    }
+
+    fn slice_center(&self, orientation: SliceOrientation) -> Vec3 {
+        let axis = orientation.axis();
+        let mut center = vec3(0.0, 0.0, 0.0);
+        center[axis] = (self.crosshair[axis] - 0.5) * self.size[axis];
+        (self.transformation * center.extend(1.0)).truncate()
+    }
+
+    fn update(&mut self) {
+        let texture_transformation = self.texture_transformation();
+        for orientation in [
+            SliceOrientation::Sagittal,
+            SliceOrientation::Coronal,
+            SliceOrientation::Axial,
+        ] {
+            let (u, v) = orientation.slice_axes();
+            let axis = orientation.axis();
+            let column = |axis: usize, length: f32| {
+                let mut column = vec4(0.0, 0.0, 0.0, 0.0);
+                column[axis] = length;
+                column
+            };
+            let mut translation = vec4(0.0, 0.0, 0.0, 1.0);
+            translation[axis] = (self.crosshair[axis] - 0.5) * self.size[axis];
+            // Maps the square in the xy-plane to the slice through the volume
+            let slice_transformation = Mat4::from_cols(
+                column(u, 0.5 * self.size[u]),
+                column(v, 0.5 * self.size[v]),
+                column(axis, 1.0),
+                translation,
+            );
+            let slice = &mut self.slices[axis];
+            slice.set_transformation(self.transformation * slice_transformation);
+            slice.material.texture_transformation = texture_transformation;
+            slice.material.crosshair = self.crosshair;
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a MprViews {
+    type Item = &'a dyn Object;
+    type IntoIter = std::vec::IntoIter<&'a dyn Object>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.slices
+            .iter()
+            .map(|slice| slice as &dyn Object)
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+}
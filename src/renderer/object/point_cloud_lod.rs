@@ -0,0 +1,227 @@
+use crate::core::*;
+use crate::renderer::*;
+use std::collections::{BinaryHeap, HashMap};
+
+///
+/// A node in the octree hierarchy of a [PointCloudLod].
+/// The hierarchy only describes the structure of the point cloud, the actual points of a node are loaded on demand.
+///
+#[derive(Clone, Debug)]
+pub struct PointCloudLodNode {
+    /// The name of the node which can be used to locate the points of the node, for example `"r"`, `"r0"`, `"r03"` in the potree naming scheme.
+    pub name: String,
+    /// The bounding box of the points in this node.
+    pub aabb: AxisAlignedBoundingBox,
+    /// The number of points in this node.
+    pub point_count: usize,
+    /// The indices of the children of this node in the list of nodes given to [PointCloudLod::new].
+    pub children: Vec<usize>,
+}
+
+///
+/// Hierarchical, out-of-core rendering of very large point clouds.
+///
+/// The point cloud is divided into an octree of [PointCloudLodNode]s where each node contains a subsample of the points inside its bounding box
+/// and the children refine the parent. Each time [PointCloudLod::update] is called, the nodes that are visible and largest on the screen are selected
+/// until the point budget is reached and the points of nodes that are not yet loaded are requested from the loader function.
+/// Nodes that have not been used for a while are unloaded when the number of loaded points exceeds twice the point budget.
+///
+pub struct PointCloudLod<M: Material> {
+    context: Context,
+    nodes: Vec<PointCloudLodNode>,
+    loaded: HashMap<usize, (Gm<InstancedMesh, M>, u64)>,
+    visible: Vec<usize>,
+    point_mesh: CpuMesh,
+    material: M,
+    loader: Box<dyn FnMut(&PointCloudLodNode) -> Option<PointCloud>>,
+    frame: u64,
+    /// The maximum number of points that are rendered.
+    pub point_budget: usize,
+    /// The maximum number of nodes that are loaded in one call to [PointCloudLod::update], used to avoid stalls when many nodes become visible at once.
+    pub max_loads_per_update: usize,
+    /// Nodes that are smaller than this number of pixels on the screen are not rendered.
+    pub min_node_pixel_size: f32,
+}
+
+impl<M: Material + Clone> PointCloudLod<M> {
+    ///
+    /// Creates a new hierarchical point cloud from the given list of nodes where the first node is the root.
+    /// Each point is rendered as an instance of the given point mesh with the given material.
+    ///
+    /// The loader function is called with a node whenever the points of that node are needed and should return the points inside that node.
+    /// If the points are not available yet, for example if they are loaded asynchronously, the loader should return `None` and it will be called again in a later update.
+    ///
+    pub fn new(
+        context: &Context,
+        nodes: Vec<PointCloudLodNode>,
+        point_mesh: CpuMesh,
+        material: M,
+        point_budget: usize,
+        loader: impl FnMut(&PointCloudLodNode) -> Option<PointCloud> + 'static,
+    ) -> Self {
+        Self {
+            context: context.clone(),
+            nodes,
+            loaded: HashMap::new(),
+            visible: Vec::new(),
+            point_mesh,
+            material,
+            loader: Box::new(loader),
+            frame: 0,
+            point_budget,
+            max_loads_per_update: 4,
+            min_node_pixel_size: 50.0,
+        }
+    }
+
+    ///
+    /// Returns the nodes in the hierarchy.
+    ///
+    pub fn nodes(&self) -> &[PointCloudLodNode] {
+        &self.nodes
+    }
+
+    ///
+    /// Returns the number of points that are currently rendered.
+    ///
+    pub fn visible_point_count(&self) -> usize {
+        self.visible
+            .iter()
+            .map(|i| self.nodes[*i].point_count)
+            .sum()
+    }
+
+    ///
+    /// Selects the nodes to render based on the given camera and the point budget and loads the points of the selected nodes if needed.
+    /// Should be called each frame before rendering.
+    ///
+    pub fn update(&mut self, camera: &Camera) {
+        self.frame += 1;
+        self.visible.clear();
+        if self.nodes.is_empty() {
+            return;
+        }
+
+        let mut queue = BinaryHeap::new();
+        queue.push(Candidate {
+            priority: f32::MAX,
+            index: 0,
+        });
+        let mut point_count = 0;
+        let mut loads = 0;
+        while let Some(Candidate { priority, index }) = queue.pop() {
+            let node = &self.nodes[index];
+            if priority < self.min_node_pixel_size || !camera.in_frustum(&node.aabb) {
+                continue;
+            }
+            if point_count + node.point_count > self.point_budget {
+                break;
+            }
+            if !self.loaded.contains_key(&index) {
+                if loads >= self.max_loads_per_update {
+                    continue;
+                }
+                loads += 1;
+                if let Some(points) = (self.loader)(node) {
+                    let gm = Gm::new(
+                        InstancedMesh::new(&self.context, &points.into(), &self.point_mesh),
+                        self.material.clone(),
+                    );
+                    self.loaded.insert(index, (gm, self.frame));
+                } else {
+                    continue;
+                }
+            }
+            if let Some((_, last_used)) = self.loaded.get_mut(&index) {
+                *last_used = self.frame;
+            }
+            point_count += node.point_count;
+            self.visible.push(index);
+            for child in node.children.iter() {
+                queue.push(Candidate {
+                    priority: self.pixel_size(camera, &self.nodes[*child].aabb),
+                    index: *child,
+                });
+            }
+        }
+        self.unload();
+    }
+
+    fn pixel_size(&self, camera: &Camera, aabb: &AxisAlignedBoundingBox) -> f32 {
+        let radius = 0.5 * aabb.size().magnitude();
+        let distance = aabb.center().distance(*camera.position());
+        if distance <= radius {
+            f32::MAX
+        } else {
+            let viewport_height = camera.viewport().height as f32;
+            match camera.projection_type() {
+                three_d_asset::ProjectionType::Perspective { field_of_view_y } => {
+                    viewport_height * radius / (distance * (0.5 * field_of_view_y.0).tan())
+                }
+                three_d_asset::ProjectionType::Orthographic { height } => {
+                    viewport_height * radius / *height
+                }
+            }
+        }
+    }
+
+    fn unload(&mut self) {
+        let mut loaded_points: usize = self.loaded.keys().map(|i| self.nodes[*i].point_count).sum();
+        if loaded_points <= 2 * self.point_budget {
+            return;
+        }
+        let mut candidates = self
+            .loaded
+            .iter()
+            .filter(|(_, (_, last_used))| *last_used != self.frame)
+            .map(|(index, (_, last_used))| (*last_used, *index))
+            .collect::<Vec<_>>();
+        candidates.sort();
+        for (_, index) in candidates {
+            if loaded_points <= 2 * self.point_budget {
+                break;
+            }
+            self.loaded.remove(&index);
+            loaded_points -= self.nodes[index].point_count;
+        }
+    }
+}
+
+impl<'a, M: Material> IntoIterator for &'a PointCloudLod<M> {
+    type Item = &'a dyn Object;
+    type IntoIter = std::vec::IntoIter<&'a dyn Object>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.visible
+            .iter()
+            .filter_map(|i| self.loaded.get(i))
+            .map(|(gm, _)| gm as &dyn Object)
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+}
+
+struct Candidate {
+    priority: f32,
+    index: usize,
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority.total_cmp(&other.priority).is_eq()
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.priority.total_cmp(&other.priority)
+    }
+}
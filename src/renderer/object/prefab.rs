@@ -0,0 +1,244 @@
+use crate::core::*;
+use crate::renderer::*;
+
+///
+/// A material which can be tinted by a color, for example to distinguish instances of a [Prefab], see [Prefab::instantiate_tinted].
+///
+pub trait Tint {
+    ///
+    /// Multiplies the base color of the material by the given color.
+    ///
+    fn tint(&mut self, color: Srgba);
+}
+
+fn multiply(a: Srgba, b: Srgba) -> Srgba {
+    let m = |a: u8, b: u8| ((a as u16 * b as u16 + 127) / 255) as u8;
+    Srgba::new(m(a.r, b.r), m(a.g, b.g), m(a.b, b.b), m(a.a, b.a))
+}
+
+impl Tint for ColorMaterial {
+    fn tint(&mut self, color: Srgba) {
+        self.color = multiply(self.color, color);
+    }
+}
+
+impl Tint for PhysicalMaterial {
+    fn tint(&mut self, color: Srgba) {
+        self.albedo = multiply(self.albedo, color);
+    }
+}
+
+impl Tint for DeferredPhysicalMaterial {
+    fn tint(&mut self, color: Srgba) {
+        self.albedo = multiply(self.albedo, color);
+    }
+}
+
+///
+/// A node in the hierarchy of a [Prefab] which has a transformation relative to the parent node, an optional mesh with a material and a list of child nodes.
+///
+pub struct PrefabNode<M: Material> {
+    name: Option<String>,
+    transformation: Mat4,
+    part: Option<(Mesh, M)>,
+    children: Vec<PrefabNode<M>>,
+}
+
+impl<M: Material> PrefabNode<M> {
+    ///
+    /// Creates a new empty node with an identity transformation.
+    ///
+    pub fn new() -> Self {
+        Self {
+            name: None,
+            transformation: Mat4::identity(),
+            part: None,
+            children: Vec::new(),
+        }
+    }
+
+    ///
+    /// Creates a new node with the given mesh and material and an identity transformation.
+    /// In each instance, the transformation of the mesh is replaced by the world transformation of the corresponding [SceneNode].
+    ///
+    pub fn new_with_part(mesh: Mesh, material: M) -> Self {
+        Self {
+            part: Some((mesh, material)),
+            ..Self::new()
+        }
+    }
+
+    ///
+    /// Returns the name of this node, if any.
+    ///
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    ///
+    /// Sets the name of this node, which is also the name of the corresponding [SceneNode] in each instance.
+    ///
+    pub fn set_name(&mut self, name: Option<String>) {
+        self.name = name;
+    }
+
+    ///
+    /// Returns the transformation of this node relative to the parent node.
+    ///
+    pub fn transformation(&self) -> Mat4 {
+        self.transformation
+    }
+
+    ///
+    /// Sets the transformation of this node relative to the parent node.
+    ///
+    pub fn set_transformation(&mut self, transformation: Mat4) {
+        self.transformation = transformation;
+    }
+
+    ///
+    /// Returns the material of this node, if any.
+    ///
+    pub fn material(&self) -> Option<&M> {
+        self.part.as_ref().map(|(_, material)| material)
+    }
+
+    ///
+    /// Returns the material of this node mutably, if any. Changes only affect instances created afterwards.
+    ///
+    pub fn material_mut(&mut self) -> Option<&mut M> {
+        self.part.as_mut().map(|(_, material)| material)
+    }
+
+    ///
+    /// Adds the given node as a child of this node and returns a mutable reference to the child.
+    ///
+    pub fn add_child(&mut self, child: PrefabNode<M>) -> &mut PrefabNode<M> {
+        self.children.push(child);
+        self.children.last_mut().unwrap()
+    }
+
+    ///
+    /// Returns the children of this node.
+    ///
+    pub fn children(&self) -> &[PrefabNode<M>] {
+        &self.children
+    }
+}
+
+impl<M: Material> Default for PrefabNode<M> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<M: Material + Clone + 'static> PrefabNode<M> {
+    fn instantiate(&self, transformation: Mat4, modify: &dyn Fn(&mut M)) -> SceneNode {
+        let mut node = if let Some((mesh, material)) = &self.part {
+            let mut material = material.clone();
+            modify(&mut material);
+            SceneNode::new_with_object(Gm::new(mesh.share(), material))
+        } else {
+            SceneNode::new()
+        };
+        node.set_name(self.name.clone());
+        node.set_transformation(transformation);
+        for child in self.children.iter() {
+            node.add_child(child.instantiate(child.transformation, modify));
+        }
+        node
+    }
+}
+
+///
+/// A template of a hierarchy of nodes with meshes and materials, see [PrefabNode], which can be instantiated many times into a [SceneNode] hierarchy, for example a tree in a forest or a chair in a room.
+/// The instances share the vertex data on the GPU with the prefab, see [Mesh::share], and the textures of the materials, so only the transformations and material parameters are stored per instance.
+/// Each instance can be given its own transformation and tint, see [Prefab::instantiate] and [Prefab::instantiate_tinted].
+///
+pub struct Prefab<M: Material> {
+    root: PrefabNode<M>,
+}
+
+impl<M: Material + Clone + 'static> Prefab<M> {
+    ///
+    /// Creates a new prefab with the given root node.
+    ///
+    pub fn new(root: PrefabNode<M>) -> Self {
+        Self { root }
+    }
+
+    ///
+    /// Returns the root node.
+    ///
+    pub fn root(&self) -> &PrefabNode<M> {
+        &self.root
+    }
+
+    ///
+    /// Returns the root node mutably. Changes only affect instances created afterwards.
+    ///
+    pub fn root_mut(&mut self) -> &mut PrefabNode<M> {
+        &mut self.root
+    }
+
+    ///
+    /// Creates a new instance of the prefab with the given transformation as the transformation of the root node,
+    /// which can be added to a scene graph using [SceneNode::add_child].
+    ///
+    pub fn instantiate(&self, transformation: Mat4) -> SceneNode {
+        self.root
+            .instantiate(transformation * self.root.transformation, &|_| {})
+    }
+
+    ///
+    /// Creates a new instance of the prefab as in [Prefab::instantiate] where the materials are tinted by the given color.
+    ///
+    pub fn instantiate_tinted(&self, transformation: Mat4, tint: Srgba) -> SceneNode
+    where
+        M: Tint,
+    {
+        self.root
+            .instantiate(transformation * self.root.transformation, &|material| {
+                material.tint(tint)
+            })
+    }
+}
+
+impl<M: Material + FromCpuMaterial + Clone + Default + 'static> Prefab<M> {
+    ///
+    /// Constructs a prefab from a [CpuModel] with a child node of the root for each triangle mesh in the model, named by the name of the mesh.
+    /// The materials are constructed as in [Model::new].
+    ///
+    pub fn from_cpu_model(context: &Context, cpu_model: &CpuModel) -> Result<Self, RendererError> {
+        let materials = cpu_model
+            .materials
+            .iter()
+            .map(|m| M::from_cpu_material(context, m))
+            .collect::<Vec<_>>();
+        let mut root = PrefabNode::new();
+        root.set_name(Some(cpu_model.name.clone()));
+        for primitive in cpu_model.geometries.iter() {
+            if let CpuGeometry::Triangles(geometry) = &primitive.geometry {
+                let material = if let Some(material_index) = primitive.material_index {
+                    materials
+                        .get(material_index)
+                        .ok_or_else(|| {
+                            RendererError::MissingMaterial(
+                                material_index.to_string(),
+                                primitive.name.clone(),
+                            )
+                        })?
+                        .clone()
+                } else {
+                    M::default()
+                };
+                let mut node =
+                    PrefabNode::new_with_part(Mesh::new_shared(context, geometry, None), material);
+                node.set_name(Some(primitive.name.clone()));
+                node.set_transformation(primitive.transformation);
+                root.add_child(node);
+            }
+        }
+        Ok(Self::new(root))
+    }
+}
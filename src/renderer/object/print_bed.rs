@@ -0,0 +1,211 @@
+use crate::renderer::*;
+
+///
+/// The predefined camera positions of a [PrintBedScene], see [PrintBedScene::home_camera].
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PrintBedView {
+    /// Looking at the bed from the front and slightly above.
+    Isometric,
+    /// Looking at the front of the bed (along the negative z-axis).
+    Front,
+    /// Looking straight down on the bed.
+    Top,
+    /// Looking at the left side of the bed (along the positive x-axis).
+    Left,
+    /// Looking at the right side of the bed (along the negative x-axis).
+    Right,
+}
+
+///
+/// A ready-made scene for 3D printing and slicer applications consisting of a print bed with a grid, a wireframe of the build volume,
+/// axes indicating the orientation and lights which casts the shadows of the printed parts onto the bed.
+///
+/// The bed is placed in the xz-plane with the corner at the origin and the build direction along the y-axis,
+/// so the build volume is the box from the origin to `(width, height, depth)`.
+/// Use [PrintBedScene::update_shadows] when the parts change to update the shadows and [PrintBedScene::home_camera] to get the standard camera positions.
+///
+pub struct PrintBedScene {
+    bed: Gm<Mesh, PhysicalMaterial>,
+    grid: Gm<InstancedMesh, ColorMaterial>,
+    build_volume: Gm<BoundingBox, ColorMaterial>,
+    axes: Axes,
+    ambient_light: AmbientLight,
+    directional_light: DirectionalLight,
+    size: Vec3,
+    /// The size of the shadow map used by [PrintBedScene::update_shadows].
+    pub shadow_map_size: u32,
+}
+
+impl PrintBedScene {
+    ///
+    /// Creates a new print bed scene with a build volume of the given size (width along the x-axis, height along the y-axis and depth along the z-axis)
+    /// and grid lines with the given spacing, all in the same unit, for example millimeters.
+    ///
+    pub fn new(context: &Context, size: Vec3, grid_spacing: f32) -> Self {
+        let max_size = size.x.max(size.y).max(size.z);
+        let thickness = 0.01 * max_size;
+
+        let mut bed_mesh = CpuMesh::cube();
+        bed_mesh
+            .transform(
+                &(Mat4::from_translation(vec3(0.5 * size.x, -0.5 * thickness, 0.5 * size.z))
+                    * Mat4::from_nonuniform_scale(0.5 * size.x, 0.5 * thickness, 0.5 * size.z)),
+            )
+            .unwrap();
+        let bed = Gm::new(
+            Mesh::new(context, &bed_mesh),
+            PhysicalMaterial::new_opaque(
+                context,
+                &CpuMaterial {
+                    albedo: Srgba::new_opaque(50, 50, 55),
+                    roughness: 0.8,
+                    metallic: 0.0,
+                    ..Default::default()
+                },
+            ),
+        );
+
+        let line_width = 0.1 * thickness;
+        let mut transformations = Vec::new();
+        let spacing = grid_spacing.max(f32::EPSILON);
+        let mut x = 0.0;
+        while x <= size.x + 0.01 * spacing {
+            transformations.push(
+                Mat4::from_translation(vec3(x, 0.5 * line_width, 0.0))
+                    * Mat4::from_angle_y(degrees(-90.0))
+                    * Mat4::from_nonuniform_scale(size.z, line_width, line_width),
+            );
+            x += spacing;
+        }
+        let mut z = 0.0;
+        while z <= size.z + 0.01 * spacing {
+            transformations.push(
+                Mat4::from_translation(vec3(0.0, 0.5 * line_width, z))
+                    * Mat4::from_nonuniform_scale(size.x, line_width, line_width),
+            );
+            z += spacing;
+        }
+        let grid = Gm::new(
+            InstancedMesh::new(
+                context,
+                &Instances {
+                    transformations,
+                    ..Default::default()
+                },
+                &CpuMesh::cylinder(8),
+            ),
+            ColorMaterial {
+                color: Srgba::new_opaque(110, 110, 120),
+                ..Default::default()
+            },
+        );
+
+        let build_volume = Gm::new(
+            BoundingBox::new_with_thickness(
+                context,
+                AxisAlignedBoundingBox::new_with_positions(&[vec3(0.0, 0.0, 0.0), size]),
+                0.5 * line_width,
+            ),
+            ColorMaterial {
+                color: Srgba::new_opaque(180, 180, 190),
+                ..Default::default()
+            },
+        );
+
+        Self {
+            bed,
+            grid,
+            build_volume,
+            axes: Axes::new(context, 0.5 * thickness, 0.1 * max_size),
+            ambient_light: AmbientLight::new(context, 0.4, Srgba::WHITE),
+            directional_light: DirectionalLight::new(
+                context,
+                2.0,
+                Srgba::WHITE,
+                &vec3(0.2, -1.0, -0.3),
+            ),
+            size,
+            shadow_map_size: 2048,
+        }
+    }
+
+    ///
+    /// Returns the size of the build volume.
+    ///
+    pub fn size(&self) -> Vec3 {
+        self.size
+    }
+
+    ///
+    /// Returns the build volume, ie. the box from the origin to the size of the build volume.
+    ///
+    pub fn build_volume(&self) -> AxisAlignedBoundingBox {
+        AxisAlignedBoundingBox::new_with_positions(&[vec3(0.0, 0.0, 0.0), self.size])
+    }
+
+    ///
+    /// Updates the shadows of the given parts onto the bed. Should be called when the parts are added, removed or moved.
+    ///
+    pub fn update_shadows(&mut self, parts: impl IntoIterator<Item = impl Geometry> + Clone) {
+        self.directional_light
+            .generate_shadow_map(self.shadow_map_size, parts);
+    }
+
+    ///
+    /// Returns the lights of the scene, which should be used when rendering both the scene and the parts.
+    ///
+    pub fn lights(&self) -> [&dyn Light; 2] {
+        [&self.ambient_light, &self.directional_light]
+    }
+
+    ///
+    /// Returns a camera with the given viewport placed at the given predefined position, so the whole build volume is visible.
+    ///
+    pub fn home_camera(&self, viewport: Viewport, view: PrintBedView) -> Camera {
+        let mut camera = Camera::new_perspective(
+            viewport,
+            vec3(0.0, 0.0, 1.0),
+            vec3(0.0, 0.0, 0.0),
+            vec3(0.0, 1.0, 0.0),
+            degrees(45.0),
+            0.01,
+            1.0,
+        );
+        self.set_home_camera(&mut camera, view);
+        camera
+    }
+
+    ///
+    /// Moves the given camera to the given predefined position, so the whole build volume is visible.
+    ///
+    pub fn set_home_camera(&self, camera: &mut Camera, view: PrintBedView) {
+        let center = 0.5 * self.size;
+        let radius = 0.5 * self.size.magnitude();
+        let distance = 2.5 * radius;
+        let (direction, up) = match view {
+            PrintBedView::Isometric => (vec3(0.6, 0.6, 1.0).normalize(), vec3(0.0, 1.0, 0.0)),
+            PrintBedView::Front => (vec3(0.0, 0.0, 1.0), vec3(0.0, 1.0, 0.0)),
+            PrintBedView::Top => (vec3(0.0, 1.0, 0.0), vec3(0.0, 0.0, -1.0)),
+            PrintBedView::Left => (vec3(-1.0, 0.0, 0.0), vec3(0.0, 1.0, 0.0)),
+            PrintBedView::Right => (vec3(1.0, 0.0, 0.0), vec3(0.0, 1.0, 0.0)),
+        };
+        camera.set_view(center + distance * direction, center, up);
+        camera.set_perspective_projection(degrees(45.0), 0.01 * radius, 10.0 * radius);
+    }
+}
+
+impl<'a> IntoIterator for &'a PrintBedScene {
+    type Item = &'a dyn Object;
+    type IntoIter = std::array::IntoIter<&'a dyn Object, 4>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        [
+            &self.bed as &dyn Object,
+            &self.grid,
+            &self.build_volume,
+            &self.axes,
+        ]
+        .into_iter()
+    }
+}
@@ -0,0 +1,776 @@
+use crate::renderer::*;
+use std::cell::RefCell;
+use std::collections::HashSet;
+
+///
+/// Identifies an object in a [Scene]. The id is returned when adding the object to the scene and is valid until the object is removed.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SceneObjectId {
+    index: usize,
+    generation: u32,
+}
+
+pub(super) struct SceneEntry {
+    generation: u32,
+    object: Option<Box<dyn Object>>,
+    name: Option<String>,
+    tags: HashSet<String>,
+    visible: bool,
+    casts_shadows: bool,
+    receives_shadows: bool,
+    scissor_box: Option<ScissorBox>,
+    lighting_mode: LightingMode,
+}
+
+///
+/// A collection of objects which supports finding the objects in a region or along a ray without iterating all of the objects.
+/// The objects can also be given a name and a set of tags, which makes it possible to find objects symbolically, see [Scene::find_by_name] and [Scene::find_by_tag].
+///
+/// Each object can be hidden without removing it from the scene, see [Scene::set_visible], and it can be specified whether or not the object casts and receives shadows,
+/// see [Scene::set_casts_shadows] and [Scene::set_receives_shadows], and which lights affect the object, see [Scene::set_lighting_mode]. These settings are respected when rendering the scene using [Scene::render] and when generating shadow maps from [Scene::shadow_casters].
+///
+/// The queries use a spatial index based on the bounding boxes of the objects, which is built when needed and rebuilt after objects are added, removed or changed.
+/// A scene can be used directly in a render call, for example [RenderTarget::render], since a reference to a scene can be iterated as a list of the visible objects.
+///
+pub struct Scene {
+    entries: Vec<SceneEntry>,
+    free: Vec<usize>,
+    index: RefCell<Option<SpatialIndex>>,
+}
+
+impl Scene {
+    ///
+    /// Creates a new empty scene.
+    ///
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            free: Vec::new(),
+            index: RefCell::new(None),
+        }
+    }
+
+    ///
+    /// Adds the given object to the scene and returns the id which can be used to access or remove the object.
+    ///
+    pub fn add(&mut self, object: impl Object + 'static) -> SceneObjectId {
+        self.index.replace(None);
+        let object: Box<dyn Object> = Box::new(object);
+        if let Some(index) = self.free.pop() {
+            let entry = &mut self.entries[index];
+            entry.object = Some(object);
+            SceneObjectId {
+                index,
+                generation: entry.generation,
+            }
+        } else {
+            self.entries.push(SceneEntry {
+                generation: 0,
+                object: Some(object),
+                name: None,
+                tags: HashSet::new(),
+                visible: true,
+                casts_shadows: true,
+                receives_shadows: true,
+                scissor_box: None,
+                lighting_mode: LightingMode::Dynamic,
+            });
+            SceneObjectId {
+                index: self.entries.len() - 1,
+                generation: 0,
+            }
+        }
+    }
+
+    ///
+    /// Adds the given object to the scene with the given name and returns the id which can be used to access or remove the object.
+    ///
+    pub fn add_named(
+        &mut self,
+        name: impl Into<String>,
+        object: impl Object + 'static,
+    ) -> SceneObjectId {
+        let id = self.add(object);
+        self.set_name(id, Some(name.into()));
+        id
+    }
+
+    ///
+    /// Adds the parts of the given model to the scene, each named by the name of the model part, for example the node name in a glTF file.
+    /// Returns the ids of the parts in the same order as in the model.
+    ///
+    pub fn add_model<M: Material + 'static>(&mut self, model: Model<M>) -> Vec<SceneObjectId> {
+        model
+            .into_iter()
+            .map(|part| {
+                let name = part.name().to_string();
+                self.add_named(name, part)
+            })
+            .collect()
+    }
+
+    ///
+    /// Removes the object with the given id from the scene and returns it, if it is in the scene.
+    ///
+    pub fn remove(&mut self, id: SceneObjectId) -> Option<Box<dyn Object>> {
+        let entry = self.entries.get_mut(id.index)?;
+        if entry.generation != id.generation || entry.object.is_none() {
+            return None;
+        }
+        self.index.replace(None);
+        entry.generation = entry.generation.wrapping_add(1);
+        entry.name = None;
+        entry.tags.clear();
+        entry.visible = true;
+        entry.casts_shadows = true;
+        entry.receives_shadows = true;
+        entry.scissor_box = None;
+        entry.lighting_mode = LightingMode::Dynamic;
+        self.free.push(id.index);
+        entry.object.take()
+    }
+
+    // Removes the object with the given id together with its name, tags and settings, which can be put back with the same id using [Scene::restore_entry].
+    pub(super) fn take_entry(&mut self, id: SceneObjectId) -> Option<SceneEntry> {
+        let entry = self.entries.get(id.index)?;
+        if entry.generation != id.generation || entry.object.is_none() {
+            return None;
+        }
+        let removed = SceneEntry {
+            generation: id.generation,
+            object: None,
+            name: entry.name.clone(),
+            tags: entry.tags.clone(),
+            visible: entry.visible,
+            casts_shadows: entry.casts_shadows,
+            receives_shadows: entry.receives_shadows,
+            scissor_box: entry.scissor_box,
+            lighting_mode: entry.lighting_mode,
+        };
+        let object = self.remove(id)?;
+        Some(SceneEntry {
+            object: Some(object),
+            ..removed
+        })
+    }
+
+    // Puts an entry removed by [Scene::take_entry] back with the same id.
+    // Returns the entry again if the slot has been used by another object since the entry was removed.
+    pub(super) fn restore_entry(
+        &mut self,
+        id: SceneObjectId,
+        entry: SceneEntry,
+    ) -> Result<(), SceneEntry> {
+        let free_index = self.free.iter().position(|i| *i == id.index);
+        match (self.entries.get(id.index), free_index) {
+            (Some(slot), Some(free_index)) if slot.generation == id.generation.wrapping_add(1) => {
+                self.free.swap_remove(free_index);
+                self.index.replace(None);
+                self.entries[id.index] = entry;
+                Ok(())
+            }
+            _ => Err(entry),
+        }
+    }
+
+    ///
+    /// Returns the name of the object with the given id, if it is in the scene and has a name.
+    ///
+    pub fn name(&self, id: SceneObjectId) -> Option<&str> {
+        self.entry(id).and_then(|entry| entry.name.as_deref())
+    }
+
+    ///
+    /// Sets the name of the object with the given id, if it is in the scene. Use `None` to remove the name.
+    /// Names are not required to be unique, but [Scene::find_by_name] only returns one of the objects with a given name.
+    ///
+    pub fn set_name(&mut self, id: SceneObjectId, name: Option<String>) {
+        if let Some(entry) = self.entry_mut(id) {
+            entry.name = name;
+        }
+    }
+
+    ///
+    /// Returns the tags of the object with the given id, or an empty iterator if the object is not in the scene.
+    ///
+    pub fn tags(&self, id: SceneObjectId) -> impl Iterator<Item = &str> {
+        self.entry(id)
+            .into_iter()
+            .flat_map(|entry| entry.tags.iter().map(|tag| tag.as_str()))
+    }
+
+    ///
+    /// Returns whether or not the object with the given id has the given tag.
+    ///
+    pub fn has_tag(&self, id: SceneObjectId, tag: &str) -> bool {
+        self.entry(id)
+            .map(|entry| entry.tags.contains(tag))
+            .unwrap_or(false)
+    }
+
+    ///
+    /// Adds the given tag to the object with the given id, if it is in the scene.
+    ///
+    pub fn add_tag(&mut self, id: SceneObjectId, tag: impl Into<String>) {
+        if let Some(entry) = self.entry_mut(id) {
+            entry.tags.insert(tag.into());
+        }
+    }
+
+    ///
+    /// Removes the given tag from the object with the given id, if it is in the scene.
+    ///
+    pub fn remove_tag(&mut self, id: SceneObjectId, tag: &str) {
+        if let Some(entry) = self.entry_mut(id) {
+            entry.tags.remove(tag);
+        }
+    }
+
+    ///
+    /// Returns whether or not the object with the given id is visible.
+    ///
+    pub fn is_visible(&self, id: SceneObjectId) -> bool {
+        self.entry(id).map(|entry| entry.visible).unwrap_or(false)
+    }
+
+    ///
+    /// Sets whether or not the object with the given id is visible. Objects that are not visible are not rendered and do not cast shadows,
+    /// but they are kept in the scene together with their GPU resources, so they can be shown again without any cost.
+    ///
+    pub fn set_visible(&mut self, id: SceneObjectId, visible: bool) {
+        if let Some(entry) = self.entry_mut(id) {
+            entry.visible = visible;
+        }
+    }
+
+    ///
+    /// Returns whether or not the object with the given id casts shadows.
+    ///
+    pub fn casts_shadows(&self, id: SceneObjectId) -> bool {
+        self.entry(id)
+            .map(|entry| entry.casts_shadows)
+            .unwrap_or(false)
+    }
+
+    ///
+    /// Sets whether or not the object with the given id casts shadows, ie. whether it is included in [Scene::shadow_casters].
+    ///
+    pub fn set_casts_shadows(&mut self, id: SceneObjectId, casts_shadows: bool) {
+        if let Some(entry) = self.entry_mut(id) {
+            entry.casts_shadows = casts_shadows;
+        }
+    }
+
+    ///
+    /// Returns whether or not the object with the given id receives shadows.
+    ///
+    pub fn receives_shadows(&self, id: SceneObjectId) -> bool {
+        self.entry(id)
+            .map(|entry| entry.receives_shadows)
+            .unwrap_or(false)
+    }
+
+    ///
+    /// Sets whether or not the object with the given id receives shadows.
+    /// Objects that do not receive shadows are rendered by [Scene::render] with the lights returned by [Light::without_shadows].
+    ///
+    pub fn set_receives_shadows(&mut self, id: SceneObjectId, receives_shadows: bool) {
+        if let Some(entry) = self.entry_mut(id) {
+            entry.receives_shadows = receives_shadows;
+        }
+    }
+
+    ///
+    /// Returns the visible objects that cast shadows, which should be used when generating shadow maps,
+    /// for example using [DirectionalLight::generate_shadow_map].
+    ///
+    pub fn shadow_casters(&self) -> impl Iterator<Item = &dyn Object> {
+        self.iter_entries()
+            .filter(|(_, entry)| entry.visible && entry.casts_shadows)
+            .filter_map(|(_, entry)| entry.object.as_deref())
+    }
+
+    ///
+    /// Returns the scissor box that the object with the given id is clipped to, if any.
+    ///
+    pub fn scissor_box(&self, id: SceneObjectId) -> Option<ScissorBox> {
+        self.entry(id).and_then(|entry| entry.scissor_box)
+    }
+
+    ///
+    /// Clips the object with the given id to the given scissor box in physical pixels when rendered using [Scene::render].
+    /// This is useful when rendering into a viewport embedded in a GUI panel, where objects should not be rendered outside the panel.
+    /// Use [Scene::find_by_tag] to clip a group of objects. Use `None` to remove the clipping.
+    ///
+    pub fn set_scissor_box(&mut self, id: SceneObjectId, scissor_box: Option<ScissorBox>) {
+        if let Some(entry) = self.entry_mut(id) {
+            entry.scissor_box = scissor_box;
+        }
+    }
+
+    ///
+    /// Returns the lighting mode of the object with the given id.
+    ///
+    pub fn lighting_mode(&self, id: SceneObjectId) -> LightingMode {
+        self.entry(id)
+            .map(|entry| entry.lighting_mode)
+            .unwrap_or_default()
+    }
+
+    ///
+    /// Sets the lighting mode of the object with the given id, which specifies which lights are used when rendering the object using [Scene::render].
+    /// For example, use [LightingMode::BakedIndirect] for static architecture with light maps and [LightingMode::Dynamic], which is the default, for moving objects.
+    ///
+    pub fn set_lighting_mode(&mut self, id: SceneObjectId, lighting_mode: LightingMode) {
+        if let Some(entry) = self.entry_mut(id) {
+            entry.lighting_mode = lighting_mode;
+        }
+    }
+
+    ///
+    /// Renders the visible objects in the scene into the given render target using the given camera and lights.
+    /// Objects that do not receive shadows are rendered with the lights returned by [Light::without_shadows],
+    /// objects are only rendered with the lights selected by their lighting mode, see [Scene::set_lighting_mode],
+    /// and objects with a scissor box are only rendered inside the scissor box, see [Scene::set_scissor_box].
+    /// The objects with a forward material, including all transparent objects, are rendered in one call, so they are sorted together regardless of their settings,
+    /// while the objects with a deferred material are rendered in groups with the same settings.
+    ///
+    pub fn render(&self, target: &RenderTarget, camera: &Camera, lights: &[&dyn Light]) {
+        let lights_without_shadows = lights
+            .iter()
+            .map(|light| light.without_shadows())
+            .collect::<Vec<_>>();
+        let unshadowed_lights = lights
+            .iter()
+            .zip(lights_without_shadows.iter())
+            .map(|(light, light_without_shadows)| {
+                light_without_shadows.as_deref().unwrap_or(*light)
+            })
+            .collect::<Vec<_>>();
+
+        let target_scissor_box = target.scissor_box();
+        let mut deferred_groups: Vec<(ScissorBox, bool, LightingMode, Vec<&dyn Object>)> =
+            Vec::new();
+        let mut forward_objects = Vec::new();
+        for (_, entry) in self.iter_entries().filter(|(_, entry)| entry.visible) {
+            if let Some(object) = entry.object.as_deref() {
+                let scissor_box = entry
+                    .scissor_box
+                    .map(|s| s.intersection(target_scissor_box))
+                    .unwrap_or(target_scissor_box);
+                if scissor_box.width == 0 || scissor_box.height == 0 {
+                    continue;
+                }
+                if object.material_type() == MaterialType::Deferred {
+                    let key = (scissor_box, entry.receives_shadows, entry.lighting_mode);
+                    if let Some(group) = deferred_groups.iter_mut().find(|g| (g.0, g.1, g.2) == key)
+                    {
+                        group.3.push(object);
+                    } else {
+                        deferred_groups.push((key.0, key.1, key.2, vec![object]));
+                    }
+                } else {
+                    forward_objects.push(SceneRenderObject {
+                        object,
+                        lights: entry.lighting_mode.lights(if entry.receives_shadows {
+                            lights
+                        } else {
+                            &unshadowed_lights
+                        }),
+                        scissor_box,
+                        target_scissor_box,
+                        context: &target.context,
+                    });
+                }
+            }
+        }
+
+        for (scissor_box, receives_shadows, lighting_mode, objects) in deferred_groups {
+            let lights = lighting_mode.lights(if receives_shadows {
+                lights
+            } else {
+                &unshadowed_lights
+            });
+            target.render_partially(scissor_box, camera, objects, &lights);
+        }
+        if !forward_objects.is_empty() {
+            // Each object uses its own lights and scissor box, so no lights are given to the render call
+            target.render_partially(target_scissor_box, camera, &forward_objects, &[]);
+        }
+    }
+
+    ///
+    /// Returns the id of an object with the given name, if any.
+    ///
+    pub fn find_by_name(&self, name: &str) -> Option<SceneObjectId> {
+        self.iter_entries()
+            .find(|(_, entry)| entry.name.as_deref() == Some(name))
+            .map(|(id, _)| id)
+    }
+
+    ///
+    /// Returns the ids of all objects with the given tag.
+    ///
+    pub fn find_by_tag(&self, tag: &str) -> Vec<SceneObjectId> {
+        self.iter_entries()
+            .filter(|(_, entry)| entry.tags.contains(tag))
+            .map(|(id, _)| id)
+            .collect()
+    }
+
+    ///
+    /// Returns the object with the given id, if it is in the scene.
+    ///
+    pub fn get(&self, id: SceneObjectId) -> Option<&dyn Object> {
+        self.entry(id).and_then(|entry| entry.object.as_deref())
+    }
+
+    ///
+    /// Returns a mutable reference to the object with the given id, if it is in the scene.
+    /// The spatial index is rebuilt before the next query, since the object might be moved.
+    ///
+    pub fn get_mut(&mut self, id: SceneObjectId) -> Option<&mut dyn Object> {
+        self.index.replace(None);
+        self.entry_mut(id)
+            .and_then(|entry| entry.object.as_deref_mut())
+    }
+
+    ///
+    /// Returns the number of objects in the scene.
+    ///
+    pub fn len(&self) -> usize {
+        self.entries.len() - self.free.len()
+    }
+
+    ///
+    /// Returns whether or not the scene is empty.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    ///
+    /// Returns an iterator over the ids and objects in the scene.
+    ///
+    pub fn iter(&self) -> impl Iterator<Item = (SceneObjectId, &dyn Object)> {
+        self.iter_entries()
+            .filter_map(|(id, entry)| entry.object.as_deref().map(|object| (id, object)))
+    }
+
+    fn iter_entries(&self) -> impl Iterator<Item = (SceneObjectId, &SceneEntry)> {
+        self.entries
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| entry.object.is_some())
+            .map(|(index, entry)| {
+                (
+                    SceneObjectId {
+                        index,
+                        generation: entry.generation,
+                    },
+                    entry,
+                )
+            })
+    }
+
+    fn entry(&self, id: SceneObjectId) -> Option<&SceneEntry> {
+        self.entries
+            .get(id.index)
+            .filter(|entry| entry.generation == id.generation && entry.object.is_some())
+    }
+
+    fn entry_mut(&mut self, id: SceneObjectId) -> Option<&mut SceneEntry> {
+        self.entries
+            .get_mut(id.index)
+            .filter(|entry| entry.generation == id.generation && entry.object.is_some())
+    }
+
+    ///
+    /// Returns the objects whose bounding box intersects the given region.
+    /// Objects with an infinite bounding box, for example a [Skybox], are always returned and objects with an empty bounding box are never returned.
+    ///
+    pub fn query_aabb(&self, region: AxisAlignedBoundingBox) -> Vec<&dyn Object> {
+        if region.is_empty() {
+            return Vec::new();
+        }
+        self.with_index(|index| index.query_aabb(region.min(), region.max()))
+            .into_iter()
+            .filter_map(|i| self.entries[i].object.as_deref())
+            .collect()
+    }
+
+    ///
+    /// Returns the objects whose bounding box is hit by the ray starting at the given position in the given direction,
+    /// sorted by the distance from the start of the ray to the bounding box.
+    /// Note that the bounding box is tested and not the geometry itself, use [ray_intersect] to find the exact intersection with the geometry.
+    /// Objects with an infinite bounding box, for example a [Skybox], are always returned and objects with an empty bounding box are never returned.
+    ///
+    pub fn query_ray(&self, position: Vec3, direction: Vec3) -> Vec<&dyn Object> {
+        let mut hits = self.with_index(|index| index.query_ray(position, direction));
+        hits.sort_by(|a, b| a.0.total_cmp(&b.0));
+        hits.into_iter()
+            .filter_map(|(_, i)| self.entries[i].object.as_deref())
+            .collect()
+    }
+
+    fn with_index<T>(&self, f: impl FnOnce(&SpatialIndex) -> T) -> T {
+        let mut index = self.index.borrow_mut();
+        let index = index.get_or_insert_with(|| {
+            SpatialIndex::new(
+                self.entries
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(i, entry)| entry.object.as_ref().map(|o| (i, o.aabb())))
+                    .collect(),
+            )
+        });
+        f(index)
+    }
+}
+
+impl Default for Scene {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> IntoIterator for &'a Scene {
+    type Item = &'a dyn Object;
+    type IntoIter = std::vec::IntoIter<&'a dyn Object>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries
+            .iter()
+            .filter(|entry| entry.visible)
+            .filter_map(|entry| entry.object.as_deref())
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+}
+
+const LEAF_SIZE: usize = 4;
+
+enum NodeContent {
+    Leaf(Vec<(usize, Vec3, Vec3)>),
+    Branch(usize, usize),
+}
+
+struct Node {
+    min: Vec3,
+    max: Vec3,
+    content: NodeContent,
+}
+
+///
+/// A bounding volume hierarchy of the bounding boxes of the objects in a scene.
+///
+struct SpatialIndex {
+    nodes: Vec<Node>,
+    unbounded: Vec<usize>,
+}
+
+impl SpatialIndex {
+    fn new(objects: Vec<(usize, AxisAlignedBoundingBox)>) -> Self {
+        let mut bounded = Vec::new();
+        let mut unbounded = Vec::new();
+        for (i, aabb) in objects {
+            if aabb.is_empty() {
+                continue;
+            }
+            let (min, max) = (aabb.min(), aabb.max());
+            if is_finite(min) && is_finite(max) {
+                bounded.push((i, min, max));
+            } else {
+                unbounded.push(i);
+            }
+        }
+        let mut nodes = Vec::new();
+        if !bounded.is_empty() {
+            build(&mut nodes, bounded);
+        }
+        Self { nodes, unbounded }
+    }
+
+    fn query_aabb(&self, min: Vec3, max: Vec3) -> Vec<usize> {
+        let mut result = self.unbounded.clone();
+        self.traverse(
+            |node_min, node_max| overlaps(node_min, node_max, min, max).then_some(0.0),
+            |_, i| result.push(i),
+        );
+        result
+    }
+
+    fn query_ray(&self, position: Vec3, direction: Vec3) -> Vec<(f32, usize)> {
+        let mut result = self
+            .unbounded
+            .iter()
+            .map(|i| (f32::INFINITY, *i))
+            .collect::<Vec<_>>();
+        self.traverse(
+            |min, max| ray_hit(position, direction, min, max),
+            |t, i| result.push((t, i)),
+        );
+        result
+    }
+
+    ///
+    /// Visits all objects where the test succeeds for the object and all of its ancestor nodes.
+    ///
+    fn traverse(
+        &self,
+        test: impl Fn(Vec3, Vec3) -> Option<f32>,
+        mut visit: impl FnMut(f32, usize),
+    ) {
+        if self.nodes.is_empty() {
+            return;
+        }
+        let mut stack = vec![0];
+        while let Some(n) = stack.pop() {
+            let node = &self.nodes[n];
+            if test(node.min, node.max).is_none() {
+                continue;
+            }
+            match &node.content {
+                NodeContent::Leaf(objects) => {
+                    for (i, min, max) in objects.iter() {
+                        if let Some(t) = test(*min, *max) {
+                            visit(t, *i);
+                        }
+                    }
+                }
+                NodeContent::Branch(left, right) => {
+                    stack.push(*left);
+                    stack.push(*right);
+                }
+            }
+        }
+    }
+}
+
+fn build(nodes: &mut Vec<Node>, mut objects: Vec<(usize, Vec3, Vec3)>) -> usize {
+    let mut min = objects[0].1;
+    let mut max = objects[0].2;
+    for (_, object_min, object_max) in objects.iter() {
+        min = vec3(
+            min.x.min(object_min.x),
+            min.y.min(object_min.y),
+            min.z.min(object_min.z),
+        );
+        max = vec3(
+            max.x.max(object_max.x),
+            max.y.max(object_max.y),
+            max.z.max(object_max.z),
+        );
+    }
+    let index = nodes.len();
+    if objects.len() <= LEAF_SIZE {
+        nodes.push(Node {
+            min,
+            max,
+            content: NodeContent::Leaf(objects),
+        });
+        return index;
+    }
+    nodes.push(Node {
+        min,
+        max,
+        content: NodeContent::Branch(0, 0),
+    });
+
+    // Split at the median of the centers along the longest axis
+    let size = max - min;
+    let axis = if size.x >= size.y && size.x >= size.z {
+        0
+    } else if size.y >= size.z {
+        1
+    } else {
+        2
+    };
+    objects.sort_by(|a, b| (a.1[axis] + a.2[axis]).total_cmp(&(b.1[axis] + b.2[axis])));
+    let right_objects = objects.split_off(objects.len() / 2);
+    let left = build(nodes, objects);
+    let right = build(nodes, right_objects);
+    nodes[index].content = NodeContent::Branch(left, right);
+    index
+}
+
+fn is_finite(v: Vec3) -> bool {
+    v.x.is_finite() && v.y.is_finite() && v.z.is_finite()
+}
+
+fn overlaps(min0: Vec3, max0: Vec3, min1: Vec3, max1: Vec3) -> bool {
+    min0.x <= max1.x
+        && min1.x <= max0.x
+        && min0.y <= max1.y
+        && min1.y <= max0.y
+        && min0.z <= max1.z
+        && min1.z <= max0.z
+}
+
+///
+/// Returns the distance along the ray to the box if the ray hits the box, using the slab method.
+///
+fn ray_hit(position: Vec3, direction: Vec3, min: Vec3, max: Vec3) -> Option<f32> {
+    let mut t_min = 0.0f32;
+    let mut t_max = f32::INFINITY;
+    for axis in 0..3 {
+        if direction[axis].abs() < f32::EPSILON {
+            if position[axis] < min[axis] || position[axis] > max[axis] {
+                return None;
+            }
+        } else {
+            let t0 = (min[axis] - position[axis]) / direction[axis];
+            let t1 = (max[axis] - position[axis]) / direction[axis];
+            t_min = t_min.max(t0.min(t1));
+            t_max = t_max.min(t0.max(t1));
+            if t_min > t_max {
+                return None;
+            }
+        }
+    }
+    Some(t_min)
+}
+
+///
+/// An object in a [Scene] which is rendered with its own lights and scissor box,
+/// so objects with different settings can be sorted together in one render call.
+///
+struct SceneRenderObject<'a> {
+    object: &'a dyn Object,
+    lights: Vec<&'a dyn Light>,
+    scissor_box: ScissorBox,
+    target_scissor_box: ScissorBox,
+    context: &'a Context,
+}
+
+impl SceneRenderObject<'_> {
+    fn object(&self) -> &dyn Object {
+        self.object
+    }
+}
+
+impl Geometry for SceneRenderObject<'_> {
+    impl_geometry_body!(object);
+}
+
+impl Object for SceneRenderObject<'_> {
+    fn render(&self, camera: &Camera, _lights: &[&dyn Light]) {
+        let own_scissor_box = self.scissor_box != self.target_scissor_box;
+        if own_scissor_box {
+            self.context.set_scissor(self.scissor_box);
+        }
+        self.object.render(camera, &self.lights);
+        if own_scissor_box {
+            self.context.set_scissor(self.target_scissor_box);
+        }
+    }
+
+    fn material_type(&self) -> MaterialType {
+        self.object.material_type()
+    }
+
+    fn is_frustum_culled(&self) -> bool {
+        self.object.is_frustum_culled()
+    }
+}
@@ -0,0 +1,281 @@
+use super::scene::SceneEntry;
+use crate::renderer::*;
+
+///
+/// An edit of a scene of type `S`, for example a [Scene] or a [SceneNode], which can be undone.
+/// Commands are usually executed using a [CommandHistory], which keeps track of the executed commands, so they can be undone and redone in the right order.
+///
+pub trait SceneCommand<S> {
+    ///
+    /// Applies the edit to the scene. This is called when the command is executed and again each time it is redone after being undone.
+    ///
+    fn apply(&mut self, scene: &mut S);
+
+    ///
+    /// Reverts the edit, so the scene is in the same state as before [SceneCommand::apply] was called.
+    ///
+    fn undo(&mut self, scene: &mut S);
+}
+
+///
+/// A list of executed [SceneCommand]s which can be undone and redone, as is used for the undo and redo functionality in an editor.
+/// Executing a new command removes the commands which have been undone, so they can no longer be redone.
+///
+pub struct CommandHistory<S> {
+    done: Vec<Box<dyn SceneCommand<S>>>,
+    undone: Vec<Box<dyn SceneCommand<S>>>,
+    limit: Option<usize>,
+}
+
+impl<S> CommandHistory<S> {
+    ///
+    /// Creates a new empty history which keeps all executed commands.
+    ///
+    pub fn new() -> Self {
+        Self {
+            done: Vec::new(),
+            undone: Vec::new(),
+            limit: None,
+        }
+    }
+
+    ///
+    /// Creates a new empty history which only keeps the given number of the most recently executed commands, so older commands can no longer be undone.
+    ///
+    pub fn new_with_limit(limit: usize) -> Self {
+        Self {
+            limit: Some(limit),
+            ..Self::new()
+        }
+    }
+
+    ///
+    /// Applies the given command to the scene and adds it to the history.
+    ///
+    pub fn execute(&mut self, scene: &mut S, mut command: impl SceneCommand<S> + 'static) {
+        command.apply(scene);
+        self.undone.clear();
+        self.done.push(Box::new(command));
+        if let Some(limit) = self.limit {
+            if self.done.len() > limit {
+                self.done.drain(..self.done.len() - limit);
+            }
+        }
+    }
+
+    ///
+    /// Undoes the most recently executed command which has not been undone. Returns whether or not there was a command to undo.
+    ///
+    pub fn undo(&mut self, scene: &mut S) -> bool {
+        if let Some(mut command) = self.done.pop() {
+            command.undo(scene);
+            self.undone.push(command);
+            true
+        } else {
+            false
+        }
+    }
+
+    ///
+    /// Applies the most recently undone command again. Returns whether or not there was a command to redo.
+    ///
+    pub fn redo(&mut self, scene: &mut S) -> bool {
+        if let Some(mut command) = self.undone.pop() {
+            command.apply(scene);
+            self.done.push(command);
+            true
+        } else {
+            false
+        }
+    }
+
+    ///
+    /// Returns whether or not there is a command to undo.
+    ///
+    pub fn can_undo(&self) -> bool {
+        !self.done.is_empty()
+    }
+
+    ///
+    /// Returns whether or not there is a command to redo.
+    ///
+    pub fn can_redo(&self) -> bool {
+        !self.undone.is_empty()
+    }
+
+    ///
+    /// Removes all commands from the history without changing the scene.
+    ///
+    pub fn clear(&mut self) {
+        self.done.clear();
+        self.undone.clear();
+    }
+}
+
+impl<S> Default for CommandHistory<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+///
+/// A [SceneCommand] which adds an object to a [Scene].
+/// Undoing the command removes the object again, and redoing it puts the object back with the same id, so later commands using the id are still valid.
+///
+pub struct AddObject {
+    object: Option<Box<dyn Object>>,
+    name: Option<String>,
+    id: Option<SceneObjectId>,
+    removed: Option<SceneEntry>,
+}
+
+impl AddObject {
+    ///
+    /// Creates a command which adds the given object.
+    ///
+    pub fn new(object: impl Object + 'static) -> Self {
+        Self {
+            object: Some(Box::new(object)),
+            name: None,
+            id: None,
+            removed: None,
+        }
+    }
+
+    ///
+    /// Creates a command which adds the given object with the given name.
+    ///
+    pub fn new_named(name: impl Into<String>, object: impl Object + 'static) -> Self {
+        Self {
+            name: Some(name.into()),
+            ..Self::new(object)
+        }
+    }
+
+    ///
+    /// Returns the id of the added object after the command has been applied.
+    ///
+    pub fn id(&self) -> Option<SceneObjectId> {
+        self.id
+    }
+}
+
+impl SceneCommand<Scene> for AddObject {
+    fn apply(&mut self, scene: &mut Scene) {
+        if let Some(object) = self.object.take() {
+            let id = scene.add(object);
+            scene.set_name(id, self.name.take());
+            self.id = Some(id);
+        } else if let (Some(id), Some(entry)) = (self.id, self.removed.take()) {
+            self.removed = scene.restore_entry(id, entry).err();
+        }
+    }
+
+    fn undo(&mut self, scene: &mut Scene) {
+        if let Some(id) = self.id {
+            self.removed = self.removed.take().or_else(|| scene.take_entry(id));
+        }
+    }
+}
+
+///
+/// A [SceneCommand] which removes an object from a [Scene].
+/// Undoing the command puts the object back with the same id together with its name, tags and settings.
+///
+pub struct RemoveObject {
+    id: SceneObjectId,
+    removed: Option<SceneEntry>,
+}
+
+impl RemoveObject {
+    ///
+    /// Creates a command which removes the object with the given id.
+    ///
+    pub fn new(id: SceneObjectId) -> Self {
+        Self { id, removed: None }
+    }
+}
+
+impl SceneCommand<Scene> for RemoveObject {
+    fn apply(&mut self, scene: &mut Scene) {
+        self.removed = self.removed.take().or_else(|| scene.take_entry(self.id));
+    }
+
+    fn undo(&mut self, scene: &mut Scene) {
+        if let Some(entry) = self.removed.take() {
+            self.removed = scene.restore_entry(self.id, entry).err();
+        }
+    }
+}
+
+///
+/// A [SceneCommand] which sets the transformation relative to the parent node of the node with the given name in a [SceneNode] hierarchy, see [SceneNode::find].
+///
+pub struct SetTransformation {
+    name: String,
+    transformation: Mat4,
+    previous: Option<Mat4>,
+}
+
+impl SetTransformation {
+    ///
+    /// Creates a command which sets the transformation of the node with the given name.
+    ///
+    pub fn new(name: impl Into<String>, transformation: Mat4) -> Self {
+        Self {
+            name: name.into(),
+            transformation,
+            previous: None,
+        }
+    }
+}
+
+impl SceneCommand<SceneNode> for SetTransformation {
+    fn apply(&mut self, scene: &mut SceneNode) {
+        if let Some(node) = scene.find(&self.name) {
+            self.previous = Some(node.transformation());
+            node.set_transformation(self.transformation);
+        }
+    }
+
+    fn undo(&mut self, scene: &mut SceneNode) {
+        if let Some(previous) = self.previous.take() {
+            if let Some(node) = scene.find(&self.name) {
+                node.set_transformation(previous);
+            }
+        }
+    }
+}
+
+///
+/// A [SceneCommand] which sets a value, for example a material parameter, reached through the given accessor function.
+/// The scene can be any type which owns the value, for example a [Gm] or an application specific struct containing the editable objects,
+/// so for example `SetParameter::new(|model: &mut Gm<Mesh, PhysicalMaterial>| &mut model.material.roughness, 0.2)` sets the roughness of the material of a model.
+///
+pub struct SetParameter<S, V> {
+    accessor: Box<dyn for<'a> Fn(&'a mut S) -> &'a mut V>,
+    value: V,
+}
+
+impl<S, V> SetParameter<S, V> {
+    ///
+    /// Creates a command which sets the value returned by the accessor to the given value.
+    ///
+    pub fn new(accessor: impl for<'a> Fn(&'a mut S) -> &'a mut V + 'static, value: V) -> Self {
+        Self {
+            accessor: Box::new(accessor),
+            value,
+        }
+    }
+}
+
+impl<S, V> SceneCommand<S> for SetParameter<S, V> {
+    fn apply(&mut self, scene: &mut S) {
+        // The value is swapped in and out, so the previous value is stored in the command until the command is undone
+        std::mem::swap((self.accessor)(scene), &mut self.value);
+    }
+
+    fn undo(&mut self, scene: &mut S) {
+        std::mem::swap((self.accessor)(scene), &mut self.value);
+    }
+}
@@ -0,0 +1,269 @@
+use crate::renderer::*;
+
+///
+/// An [Object] which can be placed in a [SceneNode], ie. an object where the local to world transformation can be set.
+///
+pub trait Transformable: Object {
+    ///
+    /// Set the local to world transformation applied to this object.
+    ///
+    fn set_transformation(&mut self, transformation: Mat4);
+}
+
+macro_rules! impl_transformable_for_gm {
+    ($geometry:ty) => {
+        impl<M: Material> Transformable for Gm<$geometry, M> {
+            fn set_transformation(&mut self, transformation: Mat4) {
+                self.geometry.set_transformation(transformation);
+            }
+        }
+    };
+}
+
+impl_transformable_for_gm!(Mesh);
+impl_transformable_for_gm!(InstancedMesh);
+impl_transformable_for_gm!(SkinnedMesh);
+impl_transformable_for_gm!(MorphMesh);
+impl_transformable_for_gm!(Sprites);
+impl_transformable_for_gm!(ParticleSystem);
+
+impl<M: Material> Transformable for ModelPart<M> {
+    fn set_transformation(&mut self, transformation: Mat4) {
+        self.geometry.set_transformation(transformation);
+    }
+}
+
+///
+/// A node in a hierarchical scene graph which has a transformation relative to the parent node, an optional [Transformable] object and a list of child nodes.
+/// The world transformation of a node is the world transformation of the parent node multiplied by the local transformation of the node,
+/// and it is applied to the object of the node whenever the transformation of the node or one of its ancestors changes,
+/// so for example the parts of a robot arm can be moved by only changing the local transformation of each joint.
+///
+/// A node which is not visible is not rendered and neither are its descendants.
+/// A reference to a node can be used directly in a render call, for example [RenderTarget::render], since it can be iterated as the list of visible objects in the subtree.
+///
+pub struct SceneNode {
+    name: Option<String>,
+    local_transformation: Mat4,
+    parent_transformation: Mat4,
+    visible: bool,
+    object: Option<Box<dyn Transformable>>,
+    children: Vec<SceneNode>,
+}
+
+impl SceneNode {
+    ///
+    /// Creates a new empty node with an identity transformation.
+    ///
+    pub fn new() -> Self {
+        Self {
+            name: None,
+            local_transformation: Mat4::identity(),
+            parent_transformation: Mat4::identity(),
+            visible: true,
+            object: None,
+            children: Vec::new(),
+        }
+    }
+
+    ///
+    /// Creates a new node with the given object and an identity transformation.
+    ///
+    pub fn new_with_object(object: impl Transformable + 'static) -> Self {
+        let mut node = Self::new();
+        node.set_object(Some(Box::new(object)));
+        node
+    }
+
+    ///
+    /// Returns the name of this node, if any.
+    ///
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    ///
+    /// Sets the name of this node which can be used to find the node, see [SceneNode::find].
+    ///
+    pub fn set_name(&mut self, name: Option<String>) {
+        self.name = name;
+    }
+
+    ///
+    /// Returns the transformation of this node relative to the parent node.
+    ///
+    pub fn transformation(&self) -> Mat4 {
+        self.local_transformation
+    }
+
+    ///
+    /// Sets the transformation of this node relative to the parent node and updates the world transformation of this node and all of its descendants.
+    ///
+    pub fn set_transformation(&mut self, transformation: Mat4) {
+        self.local_transformation = transformation;
+        self.update_transformations(self.parent_transformation);
+    }
+
+    ///
+    /// Returns the local to world transformation of this node, ie. the transformation applied to the object of this node.
+    ///
+    pub fn world_transformation(&self) -> Mat4 {
+        self.parent_transformation * self.local_transformation
+    }
+
+    ///
+    /// Returns whether or not this node is visible. Note that the node is not rendered if one of its ancestors is not visible.
+    ///
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    ///
+    /// Sets whether or not this node and its descendants are visible.
+    ///
+    pub fn set_visible(&mut self, visible: bool) {
+        self.visible = visible;
+    }
+
+    ///
+    /// Returns the object of this node, if any.
+    ///
+    pub fn object(&self) -> Option<&dyn Transformable> {
+        self.object.as_deref()
+    }
+
+    ///
+    /// Returns the object of this node mutably, if any.
+    /// **Note:** The transformation of the object is overwritten when the transformation of this node or one of its ancestors changes.
+    ///
+    pub fn object_mut(&mut self) -> Option<&mut (dyn Transformable + 'static)> {
+        self.object.as_deref_mut()
+    }
+
+    ///
+    /// Sets the object of this node and returns the previous object, if any. The world transformation of this node is applied to the object.
+    ///
+    pub fn set_object(
+        &mut self,
+        object: Option<Box<dyn Transformable>>,
+    ) -> Option<Box<dyn Transformable>> {
+        let previous = std::mem::replace(&mut self.object, object);
+        let world_transformation = self.world_transformation();
+        if let Some(object) = self.object.as_mut() {
+            object.set_transformation(world_transformation);
+        }
+        previous
+    }
+
+    ///
+    /// Adds the given node as a child of this node and returns a mutable reference to the child.
+    ///
+    pub fn add_child(&mut self, mut child: SceneNode) -> &mut SceneNode {
+        child.update_transformations(self.world_transformation());
+        self.children.push(child);
+        self.children.last_mut().unwrap()
+    }
+
+    ///
+    /// Removes the child with the given index and returns it. The world transformation of the removed node is kept until it is added to another node.
+    ///
+    /// # Panics
+    /// If the index is out of bounds.
+    ///
+    pub fn remove_child(&mut self, index: usize) -> SceneNode {
+        self.children.remove(index)
+    }
+
+    ///
+    /// Returns the children of this node.
+    ///
+    pub fn children(&self) -> &[SceneNode] {
+        &self.children
+    }
+
+    ///
+    /// Returns the child with the given index mutably, if it exists.
+    ///
+    pub fn child_mut(&mut self, index: usize) -> Option<&mut SceneNode> {
+        self.children.get_mut(index)
+    }
+
+    ///
+    /// Returns the first node in the subtree starting at this node (including this node) with the given name, searching depth first.
+    ///
+    pub fn find(&mut self, name: &str) -> Option<&mut SceneNode> {
+        if self.name.as_deref() == Some(name) {
+            return Some(self);
+        }
+        self.children.iter_mut().find_map(|child| child.find(name))
+    }
+
+    ///
+    /// Returns the visible objects in the subtree starting at this node.
+    ///
+    pub fn objects(&self) -> Vec<&dyn Object> {
+        let mut objects = Vec::new();
+        self.collect_objects(&mut objects);
+        objects
+    }
+
+    ///
+    /// Render the visible objects in the subtree starting at this node.
+    /// Must be called in the callback given as input to a [RenderTarget], [ColorTarget] or [DepthTarget] write method.
+    ///
+    pub fn render(&self, camera: &Camera, lights: &[&dyn Light]) {
+        for object in self.objects() {
+            object.render(camera, lights);
+        }
+    }
+
+    ///
+    /// For updating the animation of the objects in the subtree starting at this node. The time parameter should be some continious time, for example the time since start.
+    ///
+    pub fn animate(&mut self, time: f32) {
+        if let Some(object) = self.object.as_mut() {
+            object.animate(time);
+        }
+        self.children
+            .iter_mut()
+            .for_each(|child| child.animate(time));
+    }
+
+    fn update_transformations(&mut self, parent_transformation: Mat4) {
+        self.parent_transformation = parent_transformation;
+        let world_transformation = self.world_transformation();
+        if let Some(object) = self.object.as_mut() {
+            object.set_transformation(world_transformation);
+        }
+        for child in self.children.iter_mut() {
+            child.update_transformations(world_transformation);
+        }
+    }
+
+    fn collect_objects<'a>(&'a self, objects: &mut Vec<&'a dyn Object>) {
+        if !self.visible {
+            return;
+        }
+        if let Some(object) = self.object.as_ref() {
+            objects.push(object);
+        }
+        for child in self.children.iter() {
+            child.collect_objects(objects);
+        }
+    }
+}
+
+impl Default for SceneNode {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> IntoIterator for &'a SceneNode {
+    type Item = &'a dyn Object;
+    type IntoIter = std::vec::IntoIter<&'a dyn Object>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.objects().into_iter()
+    }
+}
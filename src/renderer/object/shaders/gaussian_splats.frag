@@ -0,0 +1,17 @@
+in vec4 col;
+in vec2 offset;
+
+layout (location = 0) out vec4 outColor;
+
+void main()
+{
+    float power = -dot(offset, offset);
+    if (power < -4.0) {
+        discard;
+    }
+    float alpha = exp(power) * col.a;
+    if (alpha < 1.0 / 255.0) {
+        discard;
+    }
+    outColor = vec4(color_mapping(col.rgb), alpha);
+}
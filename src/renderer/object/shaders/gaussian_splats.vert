@@ -0,0 +1,63 @@
+uniform mat4 view;
+uniform mat4 projection;
+uniform vec2 focal;
+uniform vec2 viewportSize;
+
+in vec2 position;
+in vec3 center;
+in vec3 covariance_a;
+in vec3 covariance_b;
+in vec4 splat_color;
+
+out vec4 col;
+out vec2 offset;
+
+void main()
+{
+    col = splat_color;
+    offset = position;
+
+    vec4 cam = view * vec4(center, 1.0);
+    vec4 clip = projection * cam;
+    float bound = 1.2 * clip.w;
+    if (clip.z < -clip.w || clip.x < -bound || clip.x > bound || clip.y < -bound || clip.y > bound) {
+        // Outside the view frustum
+        gl_Position = vec4(0.0, 0.0, 2.0, 1.0);
+        return;
+    }
+
+    // Project the 3D covariance onto the screen using the jacobian of the perspective projection
+    float z = -cam.z;
+    mat3 J = transpose(mat3(
+        focal.x / z, 0.0, focal.x * cam.x / (z * z),
+        0.0, focal.y / z, focal.y * cam.y / (z * z),
+        0.0, 0.0, 0.0
+    ));
+    mat3 T = J * mat3(view);
+    mat3 sigma = mat3(
+        covariance_a.x, covariance_a.y, covariance_a.z,
+        covariance_a.y, covariance_b.x, covariance_b.y,
+        covariance_a.z, covariance_b.y, covariance_b.z
+    );
+    mat3 cov2d = T * sigma * transpose(T);
+
+    // Low pass filter to ensure that each splat covers at least one pixel
+    float a = cov2d[0][0] + 0.3;
+    float b = cov2d[0][1];
+    float d = cov2d[1][1] + 0.3;
+
+    float mid = 0.5 * (a + d);
+    float radius = length(vec2(0.5 * (a - d), b));
+    float lambda1 = mid + radius;
+    float lambda2 = max(mid - radius, 0.1);
+    vec2 diagonal = normalize(vec2(b, lambda1 - a));
+    vec2 major_axis = min(sqrt(2.0 * lambda1), 1024.0) * diagonal;
+    vec2 minor_axis = min(sqrt(2.0 * lambda2), 1024.0) * vec2(diagonal.y, -diagonal.x);
+
+    vec2 ndc_center = clip.xy / clip.w;
+    gl_Position = vec4(
+        ndc_center + (position.x * major_axis + position.y * minor_axis) / viewportSize,
+        clip.z / clip.w,
+        1.0
+    );
+}
@@ -0,0 +1,16 @@
+
+in vec3 pos;
+in vec2 uvs;
+in vec4 col;
+
+layout (location = 0) out vec4 outColor;
+
+void main()
+{
+    float d = length(2.0 * uvs - 1.0);
+    float alpha = col.a * (1.0 - smoothstep(0.5, 1.0, d));
+    if (alpha < 0.004) {
+        discard;
+    }
+    outColor = vec4(color_mapping(col.rgb), alpha);
+}
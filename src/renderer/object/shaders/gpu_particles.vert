@@ -0,0 +1,31 @@
+uniform mat4 viewProjection;
+uniform vec3 cameraRight;
+uniform vec3 cameraUp;
+uniform vec2 size;
+uniform int stateWidth;
+uniform sampler2DArray state;
+uniform sampler2D colorOverLife;
+
+in vec2 corner;
+
+out vec3 pos;
+out vec2 uvs;
+out vec4 col;
+
+void main()
+{
+    ivec2 index = ivec2(gl_InstanceID % stateWidth, gl_InstanceID / stateWidth);
+    vec4 positionAndAge = texelFetch(state, ivec3(index, 0), 0);
+    float lifetime = texelFetch(state, ivec3(index, 1), 0).w;
+    if (positionAndAge.w >= lifetime) {
+        // Dead particles are moved outside the view volume
+        gl_Position = vec4(0.0, 0.0, 2.0, 1.0);
+        return;
+    }
+    float t = positionAndAge.w / lifetime;
+    float s = mix(size.x, size.y, t);
+    pos = positionAndAge.xyz + s * (cameraRight * corner.x + cameraUp * corner.y);
+    uvs = 0.5 * corner + 0.5;
+    col = texture(colorOverLife, vec2(t, 0.5));
+    gl_Position = viewProjection * vec4(pos, 1.0);
+}
@@ -0,0 +1,53 @@
+uniform sampler2DArray state;
+uniform int stateWidth;
+uniform int capacity;
+uniform int spawnStart;
+uniform int spawnCount;
+uniform float dt;
+uniform float time;
+uniform vec3 emitterPosition;
+uniform vec3 emitterVelocity;
+uniform float spread;
+uniform float lifetime;
+uniform vec3 gravity;
+
+layout (location = 0) out vec4 outPositionAndAge;
+layout (location = 1) out vec4 outVelocityAndLifetime;
+
+float hash(float n)
+{
+    return fract(sin(n) * 43758.5453123);
+}
+
+void main()
+{
+    ivec2 texel = ivec2(gl_FragCoord.xy);
+    int index = texel.y * stateWidth + texel.x;
+    vec4 positionAndAge = texelFetch(state, ivec3(texel, 0), 0);
+    vec4 velocityAndLifetime = texelFetch(state, ivec3(texel, 1), 0);
+
+    if ((index - spawnStart + capacity) % capacity < spawnCount) {
+        // Spawn a new particle with a random direction inside the cone around the emitter velocity
+        float seed = float(index) + time * 1000.0;
+        float phi = 6.2831853 * hash(seed);
+        float cosTheta = mix(1.0, cos(spread), hash(seed + 17.0));
+        float sinTheta = sqrt(max(0.0, 1.0 - cosTheta * cosTheta));
+        float speed = length(emitterVelocity);
+        vec3 w = speed > 0.0 ? emitterVelocity / speed : vec3(0.0, 1.0, 0.0);
+        vec3 u = normalize(cross(abs(w.y) < 0.99 ? vec3(0.0, 1.0, 0.0) : vec3(1.0, 0.0, 0.0), w));
+        vec3 v = cross(w, u);
+        vec3 direction = sinTheta * cos(phi) * u + sinTheta * sin(phi) * v + cosTheta * w;
+        // Spread the spawn time of the particles spawned this frame over the time step
+        float age = dt * hash(seed + 31.0);
+        vec3 velocity = speed * direction + gravity * age;
+        outPositionAndAge = vec4(emitterPosition + velocity * age, age);
+        outVelocityAndLifetime = vec4(velocity, lifetime);
+    } else if (positionAndAge.w < velocityAndLifetime.w) {
+        vec3 velocity = velocityAndLifetime.xyz + gravity * dt;
+        outPositionAndAge = vec4(positionAndAge.xyz + velocity * dt, positionAndAge.w + dt);
+        outVelocityAndLifetime = vec4(velocity, velocityAndLifetime.w);
+    } else {
+        outPositionAndAge = positionAndAge;
+        outVelocityAndLifetime = velocityAndLifetime;
+    }
+}
@@ -0,0 +1,15 @@
+uniform sampler2D atlas;
+uniform vec4 textColor;
+
+in vec2 uvs;
+
+layout (location = 0) out vec4 outColor;
+
+void main()
+{
+    float coverage = texture(atlas, uvs).r;
+    if (coverage < 0.004) {
+        discard;
+    }
+    outColor = vec4(color_mapping(textColor.rgb), textColor.a * coverage);
+}
@@ -0,0 +1,16 @@
+uniform mat4 viewProjection;
+uniform vec3 anchor;
+uniform vec3 right;
+uniform vec3 up;
+
+in vec3 position;
+in vec2 uv_coordinate;
+
+out vec2 uvs;
+
+void main()
+{
+    uvs = uv_coordinate;
+    vec3 worldPosition = anchor + right * position.x + up * position.y;
+    gl_Position = viewProjection * vec4(worldPosition, 1.0);
+}
@@ -0,0 +1,130 @@
+use crate::renderer::*;
+
+///
+/// A 3D model consisting of a set of [Gm]s with [SkinnedMesh]es as the geometries which are deformed by the same [Skeleton],
+/// and a [material] type specified by the generic parameter. Both forward and deferred materials can be used.
+///
+/// The skeleton is animated by one of the [AnimationClip]s given at construction, see [SkinnedModel::choose_animation], which is evaluated each time [SkinnedModel::animate] is called.
+///
+pub struct SkinnedModel<M: Material> {
+    parts: Vec<Gm<SkinnedMesh, M>>,
+    skeleton: Skeleton,
+    animation_clips: Vec<AnimationClip>,
+    current_animation: Option<usize>,
+}
+
+impl<M: Material> SkinnedModel<M> {
+    ///
+    /// Constructs a new skinned model where each part is constructed from a [CpuMesh] with the corresponding [CpuSkin] and a material.
+    /// The model is initially animated using the first animation clip, if any.
+    ///
+    pub fn new(
+        context: &Context,
+        skeleton: Skeleton,
+        parts: Vec<(&CpuMesh, &CpuSkin, M)>,
+        animation_clips: Vec<AnimationClip>,
+    ) -> Self {
+        let parts = parts
+            .into_iter()
+            .map(|(cpu_mesh, skin, material)| Gm {
+                geometry: SkinnedMesh::new(context, cpu_mesh, skin, skeleton.clone()),
+                material,
+            })
+            .collect();
+        Self {
+            parts,
+            skeleton,
+            current_animation: if animation_clips.is_empty() {
+                None
+            } else {
+                Some(0)
+            },
+            animation_clips,
+        }
+    }
+
+    ///
+    /// Returns the skeleton which deforms this model.
+    ///
+    pub fn skeleton(&self) -> &Skeleton {
+        &self.skeleton
+    }
+
+    ///
+    /// Returns the names of the animation clips of this model. Use these names as input to [Self::choose_animation].
+    ///
+    pub fn animations(&self) -> Vec<String> {
+        self.animation_clips
+            .iter()
+            .map(|clip| clip.name.clone())
+            .collect()
+    }
+
+    ///
+    /// Specifies the animation clip to use when [Self::animate] is called or `None` to stop the animation. Use the [Self::animations] method to get a list of possible animations.
+    ///
+    pub fn choose_animation(&mut self, animation_name: Option<&str>) {
+        self.current_animation = animation_name.and_then(|name| {
+            self.animation_clips
+                .iter()
+                .position(|clip| clip.name == name)
+        });
+    }
+
+    ///
+    /// For updating the animation. The time parameter should be some continious time in seconds, for example the time since start.
+    ///
+    pub fn animate(&mut self, time: f32) {
+        if let Some(clip) = self
+            .current_animation
+            .and_then(|index| self.animation_clips.get(index))
+        {
+            let pose = clip.pose(&self.skeleton, time);
+            self.set_pose(&pose);
+        }
+    }
+
+    ///
+    /// Sets the pose of the skeleton for all parts of this model, ie. the local transformation of each joint, see [Skeleton::joint_matrices].
+    ///
+    pub fn set_pose(&mut self, pose: &[Mat4]) {
+        self.parts
+            .iter_mut()
+            .for_each(|part| part.geometry.set_pose(pose));
+    }
+
+    ///
+    /// Set the local to world transformation applied to all parts of this model.
+    ///
+    pub fn set_transformation(&mut self, transformation: Mat4) {
+        self.parts
+            .iter_mut()
+            .for_each(|part| part.geometry.set_transformation(transformation));
+    }
+}
+
+impl<'a, M: Material> IntoIterator for &'a SkinnedModel<M> {
+    type Item = &'a dyn Object;
+    type IntoIter = std::vec::IntoIter<&'a dyn Object>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.parts
+            .iter()
+            .map(|m| m as &dyn Object)
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+}
+
+impl<M: Material> std::ops::Deref for SkinnedModel<M> {
+    type Target = Vec<Gm<SkinnedMesh, M>>;
+    fn deref(&self) -> &Self::Target {
+        &self.parts
+    }
+}
+
+impl<M: Material> std::ops::DerefMut for SkinnedModel<M> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.parts
+    }
+}
@@ -51,30 +51,10 @@ impl Skybox {
     /// Creates a new skybox with a cube texture generated from the equirectangular texture given as input.
     ///
     pub fn new_from_equirectangular(context: &Context, cpu_texture: &CpuTexture) -> Self {
-        let texture = match cpu_texture.data {
-            TextureData::RgbaU8(_) | TextureData::RgbU8(_) => {
-                let mut cpu_texture = cpu_texture.clone();
-                cpu_texture.data.to_linear_srgb();
-                TextureCubeMap::new_from_equirectangular::<u8>(context, &cpu_texture)
-            }
-            TextureData::RgU8(_) | TextureData::RU8(_) => {
-                TextureCubeMap::new_from_equirectangular::<u8>(context, cpu_texture)
-            }
-            TextureData::RgbaF16(_)
-            | TextureData::RgbF16(_)
-            | TextureData::RgF16(_)
-            | TextureData::RF16(_) => {
-                TextureCubeMap::new_from_equirectangular::<f16>(context, cpu_texture)
-            }
-            TextureData::RgbaF32(_)
-            | TextureData::RgbF32(_)
-            | TextureData::RgF32(_)
-            | TextureData::RF32(_) => {
-                TextureCubeMap::new_from_equirectangular::<f32>(context, cpu_texture)
-            }
-        };
-
-        Self::new_with_texture(context, Arc::new(texture))
+        Self::new_with_texture(
+            context,
+            Arc::new(environment_map_from_equirectangular(context, cpu_texture)),
+        )
     }
 
     ///
@@ -127,7 +107,12 @@ impl Skybox {
         Skybox {
             context: context.clone(),
             vertex_buffer,
-            material: SkyboxMaterial { texture },
+            material: SkyboxMaterial {
+                texture,
+                rotation: Radians(0.0),
+                intensity: 1.0,
+                blur: 0.0,
+            },
         }
     }
 
@@ -137,6 +122,51 @@ impl Skybox {
     pub fn texture(&self) -> &Arc<TextureCubeMap> {
         &self.material.texture
     }
+
+    ///
+    /// Returns the rotation of the skybox around the vertical (y) axis.
+    ///
+    pub fn rotation(&self) -> Radians {
+        self.material.rotation
+    }
+
+    ///
+    /// Rotates the skybox around the vertical (y) axis, which for example can be used to change the direction of the sun in an HDR environment.
+    /// Note that this does not rotate lighting calculated from the same environment map, for example an [AmbientLight] with an [Environment], but an [EnvironmentLight] can be rotated using [EnvironmentLight::rotation].
+    ///
+    pub fn set_rotation(&mut self, rotation: impl Into<Radians>) {
+        self.material.rotation = rotation.into();
+    }
+
+    ///
+    /// Returns the intensity which the colors of the skybox are multiplied with.
+    ///
+    pub fn intensity(&self) -> f32 {
+        self.material.intensity
+    }
+
+    ///
+    /// Sets the intensity, or exposure, which the colors of the skybox are multiplied with. The default is 1.
+    ///
+    pub fn set_intensity(&mut self, intensity: f32) {
+        self.material.intensity = intensity;
+    }
+
+    ///
+    /// Returns the blur level of the skybox.
+    ///
+    pub fn blur(&self) -> f32 {
+        self.material.blur
+    }
+
+    ///
+    /// Sets the blur level of the skybox, which is used as a bias when choosing the mip level of the cube map texture,
+    /// so a value of 1 samples from a mip level with half the resolution and so on. The default is 0, ie. no blur.
+    /// Only has an effect if the cube map texture has mip maps.
+    ///
+    pub fn set_blur(&mut self, blur: f32) {
+        self.material.blur = blur;
+    }
 }
 
 impl<'a> IntoIterator for &'a Skybox {
@@ -211,4 +241,39 @@ impl Object for Skybox {
     fn material_type(&self) -> MaterialType {
         MaterialType::Opaque
     }
+
+    fn is_frustum_culled(&self) -> bool {
+        false
+    }
+}
+
+///
+/// Converts the given equirectangular texture into a cube map, where 8 bit sRGB colors are converted to linear colors.
+///
+pub(crate) fn environment_map_from_equirectangular(
+    context: &Context,
+    cpu_texture: &CpuTexture,
+) -> TextureCubeMap {
+    match cpu_texture.data {
+        TextureData::RgbaU8(_) | TextureData::RgbU8(_) => {
+            let mut cpu_texture = cpu_texture.clone();
+            cpu_texture.data.to_linear_srgb();
+            TextureCubeMap::new_from_equirectangular::<u8>(context, &cpu_texture)
+        }
+        TextureData::RgU8(_) | TextureData::RU8(_) => {
+            TextureCubeMap::new_from_equirectangular::<u8>(context, cpu_texture)
+        }
+        TextureData::RgbaF16(_)
+        | TextureData::RgbF16(_)
+        | TextureData::RgF16(_)
+        | TextureData::RF16(_) => {
+            TextureCubeMap::new_from_equirectangular::<f16>(context, cpu_texture)
+        }
+        TextureData::RgbaF32(_)
+        | TextureData::RgbF32(_)
+        | TextureData::RgF32(_)
+        | TextureData::RF32(_) => {
+            TextureCubeMap::new_from_equirectangular::<f32>(context, cpu_texture)
+        }
+    }
 }
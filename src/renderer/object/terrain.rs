@@ -16,6 +16,9 @@ const VERTICES_PER_SIDE: usize = 33;
 
 ///
 /// A terrain geometry based on a height map and with an applied material.
+/// The terrain is split into square patches where the level of detail of each patch depends on the distance to the center, see [Terrain::set_lod].
+/// Each patch has a skirt along the edges hanging down below the surface, which hides the cracks between neighbouring patches with different level of detail.
+/// Use a [SplatMapMaterial] to blend between different textures on the terrain.
 ///
 pub struct Terrain<M: Material> {
     context: Context,
@@ -74,6 +77,48 @@ impl<M: Material + Clone> Terrain<M> {
         }
     }
 
+    ///
+    /// Creates a new [Terrain] from a height map texture, where the first channel of each texel is the height of the terrain.
+    /// The texture covers the square area with the given side length around the given center and the values in the texture are scaled by the given height scale,
+    /// so for example a texture with `u8` values results in heights in the range `[0..height_scale]`.
+    /// Outside the area covered by the texture, the height at the closest edge of the texture is used.
+    ///
+    pub fn new_from_height_map_texture(
+        context: &Context,
+        material: M,
+        height_map: &CpuTexture,
+        height_scale: f32,
+        side_length: f32,
+        vertex_distance: f32,
+        center: Vec2,
+    ) -> Self {
+        let width = height_map.width as usize;
+        let height = height_map.height as usize;
+        let heights = height_values(&height_map.data);
+        let origin = center - vec2(0.5 * side_length, 0.5 * side_length);
+        Self::new(
+            context,
+            material,
+            Arc::new(move |x, z| {
+                // Bilinear interpolation between the texel centers
+                let u = ((x - origin.x) / side_length * width as f32 - 0.5)
+                    .clamp(0.0, (width - 1) as f32);
+                let v = ((z - origin.y) / side_length * height as f32 - 0.5)
+                    .clamp(0.0, (height - 1) as f32);
+                let (u0, v0) = (u.floor() as usize, v.floor() as usize);
+                let (u1, v1) = ((u0 + 1).min(width - 1), (v0 + 1).min(height - 1));
+                let (fu, fv) = (u - u0 as f32, v - v0 as f32);
+                let h = |u: usize, v: usize| heights[v * width + u];
+                let h0 = h(u0, v0) * (1.0 - fu) + h(u1, v0) * fu;
+                let h1 = h(u0, v1) * (1.0 - fu) + h(u1, v1) * fu;
+                height_scale * (h0 * (1.0 - fv) + h1 * fv)
+            }),
+            side_length,
+            vertex_distance,
+            center,
+        )
+    }
+
     ///
     /// Returns the height at the given position.
     ///
@@ -197,6 +242,18 @@ impl<M: Material + Clone> Terrain<M> {
                 indices.push(r * resolution + resolution + (c * resolution + resolution) * stride);
             }
         }
+
+        // The skirts hanging down from the edges of the patch, which hide the cracks between patches with different level of detail.
+        // Both sides of the skirts are added, since the cracks can be seen from both sides.
+        for edge in 0..4 {
+            for i in 0..max {
+                let k0 = i * resolution;
+                let k1 = k0 + resolution;
+                let (a, b) = (edge_vertex(edge, k0), edge_vertex(edge, k1));
+                let (sa, sb) = (skirt_vertex(edge, k0), skirt_vertex(edge, k1));
+                indices.extend([a, b, sb, a, sb, sa, a, sb, b, a, sa, sb]);
+            }
+        }
         Arc::new(ElementBuffer::new_with_data(context, &indices))
     }
 }
@@ -214,6 +271,40 @@ impl<'a, M: Material> IntoIterator for &'a Terrain<M> {
     }
 }
 
+// The index of the k'th vertex on the given edge of the grid of a patch
+fn edge_vertex(edge: u32, k: u32) -> u32 {
+    let n = VERTICES_PER_SIDE as u32;
+    match edge {
+        0 => k,
+        1 => (n - 1) * n + k,
+        2 => k * n,
+        _ => k * n + n - 1,
+    }
+}
+
+// The index of the skirt vertex below the k'th vertex on the given edge of a patch
+fn skirt_vertex(edge: u32, k: u32) -> u32 {
+    let n = VERTICES_PER_SIDE as u32;
+    n * n + edge * n + k
+}
+
+fn height_values(data: &TextureData) -> Vec<f32> {
+    match data {
+        TextureData::RU8(d) => d.iter().map(|v| *v as f32 / 255.0).collect(),
+        TextureData::RgU8(d) => d.iter().map(|v| v[0] as f32 / 255.0).collect(),
+        TextureData::RgbU8(d) => d.iter().map(|v| v[0] as f32 / 255.0).collect(),
+        TextureData::RgbaU8(d) => d.iter().map(|v| v[0] as f32 / 255.0).collect(),
+        TextureData::RF16(d) => d.iter().map(|v| v.to_f32()).collect(),
+        TextureData::RgF16(d) => d.iter().map(|v| v[0].to_f32()).collect(),
+        TextureData::RgbF16(d) => d.iter().map(|v| v[0].to_f32()).collect(),
+        TextureData::RgbaF16(d) => d.iter().map(|v| v[0].to_f32()).collect(),
+        TextureData::RF32(d) => d.clone(),
+        TextureData::RgF32(d) => d.iter().map(|v| v[0]).collect(),
+        TextureData::RgbF32(d) => d.iter().map(|v| v[0]).collect(),
+        TextureData::RgbaF32(d) => d.iter().map(|v| v[0]).collect(),
+    }
+}
+
 fn patch_size(vertex_distance: f32) -> f32 {
     vertex_distance * (VERTICES_PER_SIDE - 1) as f32
 }
@@ -252,9 +343,10 @@ impl TerrainPatch {
     ) -> Self {
         let patch_size = patch_size(vertex_distance);
         let offset = vec2(index.0 as f32 * patch_size, index.1 as f32 * patch_size);
-        let positions = Self::positions(height_map.clone(), offset, vertex_distance);
+        let mut positions = Self::positions(height_map.clone(), offset, vertex_distance);
+        let mut normals = Self::normals(height_map, offset, &positions, vertex_distance);
+        Self::add_skirts(&mut positions, &mut normals, vertex_distance);
         let aabb = AxisAlignedBoundingBox::new_with_positions(&positions);
-        let normals = Self::normals(height_map, offset, &positions, vertex_distance);
 
         let positions_buffer = VertexBuffer::new_with_data(context, &positions);
         let normals_buffer = VertexBuffer::new_with_data(context, &normals);
@@ -294,6 +386,23 @@ impl TerrainPatch {
         data
     }
 
+    fn add_skirts(positions: &mut Vec<Vec3>, normals: &mut Vec<Vec3>, vertex_distance: f32) {
+        // The skirts must be deep enough to cover the largest possible crack, which is bounded by the height variation of the patch
+        let (min, max) = positions
+            .iter()
+            .fold((f32::MAX, f32::MIN), |(min, max), p| {
+                (min.min(p.y), max.max(p.y))
+            });
+        let depth = max - min + vertex_distance;
+        for edge in 0..4 {
+            for k in 0..VERTICES_PER_SIDE as u32 {
+                let vertex_id = edge_vertex(edge, k) as usize;
+                positions.push(positions[vertex_id] - vec3(0.0, depth, 0.0));
+                normals.push(normals[vertex_id]);
+            }
+        }
+    }
+
     fn normals(
         height_map: impl Fn(f32, f32) -> f32,
         offset: Vec2,
@@ -0,0 +1,528 @@
+use crate::core::*;
+use crate::renderer::*;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+#[derive(Clone, Copy, Debug)]
+struct GlyphInfo {
+    // The glyph rectangle relative to the pen position on the baseline in pixels
+    min: Vec2,
+    max: Vec2,
+    // The glyph rectangle in the atlas in texture coordinates
+    uv_min: Vec2,
+    uv_max: Vec2,
+    advance: f32,
+}
+
+///
+/// A texture containing the rasterized glyphs of a font at a given pixel size, which is used to render [Text].
+/// The atlas is built at runtime from the bytes of a TrueType (`.ttf`) or OpenType (`.otf`) font and can be shared between many texts.
+///
+pub struct FontAtlas {
+    font: fontdue::Font,
+    pixel_size: f32,
+    line_height: f32,
+    glyphs: HashMap<char, GlyphInfo>,
+    texture: Texture2D,
+}
+
+impl FontAtlas {
+    ///
+    /// Builds an atlas with the printable ASCII and Latin-1 characters rasterized from the given font bytes at the given pixel size.
+    /// A larger pixel size gives sharper text when the text is rendered large, at the cost of a larger texture.
+    ///
+    pub fn new(
+        context: &Context,
+        font_bytes: &[u8],
+        pixel_size: f32,
+    ) -> Result<Self, RendererError> {
+        Self::new_with_characters(
+            context,
+            font_bytes,
+            pixel_size,
+            (' '..='~').chain('\u{a0}'..='\u{ff}'),
+        )
+    }
+
+    ///
+    /// Builds an atlas with the given characters rasterized from the given font bytes at the given pixel size.
+    /// Characters which are not in the atlas are not rendered.
+    ///
+    pub fn new_with_characters(
+        context: &Context,
+        font_bytes: &[u8],
+        pixel_size: f32,
+        characters: impl IntoIterator<Item = char>,
+    ) -> Result<Self, RendererError> {
+        let font = fontdue::Font::from_bytes(font_bytes, fontdue::FontSettings::default())
+            .map_err(|e| RendererError::InvalidFont(e.to_string()))?;
+        let line_height = font
+            .horizontal_line_metrics(pixel_size)
+            .map(|m| m.new_line_size)
+            .unwrap_or(pixel_size);
+
+        // Pack the glyphs in rows of a fixed width
+        const WIDTH: usize = 512;
+        const PADDING: usize = 1;
+        let mut rasterized = Vec::new();
+        let (mut x, mut y, mut row_height) = (PADDING, PADDING, 0);
+        for c in characters {
+            let (metrics, bitmap) = font.rasterize(c, pixel_size);
+            if metrics.width + 2 * PADDING > WIDTH {
+                continue;
+            }
+            if x + metrics.width + PADDING > WIDTH {
+                x = PADDING;
+                y += row_height + PADDING;
+                row_height = 0;
+            }
+            rasterized.push((c, metrics, bitmap, x, y));
+            x += metrics.width + PADDING;
+            row_height = row_height.max(metrics.height);
+        }
+        let height = (y + row_height + PADDING).next_power_of_two();
+
+        let mut data = vec![0u8; WIDTH * height];
+        let mut glyphs = HashMap::new();
+        for (c, metrics, bitmap, x, y) in rasterized {
+            for row in 0..metrics.height {
+                let start = (y + row) * WIDTH + x;
+                data[start..start + metrics.width]
+                    .copy_from_slice(&bitmap[row * metrics.width..(row + 1) * metrics.width]);
+            }
+            // The first row of the bitmap is the top of the glyph
+            glyphs.insert(
+                c,
+                GlyphInfo {
+                    min: vec2(metrics.xmin as f32, metrics.ymin as f32),
+                    max: vec2(
+                        (metrics.xmin + metrics.width as i32) as f32,
+                        (metrics.ymin + metrics.height as i32) as f32,
+                    ),
+                    uv_min: vec2(
+                        x as f32 / WIDTH as f32,
+                        (y + metrics.height) as f32 / height as f32,
+                    ),
+                    uv_max: vec2(
+                        (x + metrics.width) as f32 / WIDTH as f32,
+                        y as f32 / height as f32,
+                    ),
+                    advance: metrics.advance_width,
+                },
+            );
+        }
+        let mut texture = Texture2D::new_empty::<u8>(
+            context,
+            WIDTH as u32,
+            height as u32,
+            Interpolation::Linear,
+            Interpolation::Linear,
+            None,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+        texture.fill(&data);
+        Ok(Self {
+            font,
+            pixel_size,
+            line_height,
+            glyphs,
+            texture,
+        })
+    }
+
+    ///
+    /// Returns the pixel size the glyphs are rasterized at.
+    ///
+    pub fn pixel_size(&self) -> f32 {
+        self.pixel_size
+    }
+
+    ///
+    /// Returns the texture containing the rasterized glyphs in the red channel.
+    ///
+    pub fn texture(&self) -> &Texture2D {
+        &self.texture
+    }
+
+    // Returns the positions and uv coordinates of two triangles for each glyph of the text, where the size of a line is 1,
+    // together with the bounding rectangle of the glyphs.
+    fn layout(&self, text: &str, alignment: TextAlignment) -> (Vec<Vec3>, Vec<Vec2>, Vec2, Vec2) {
+        let scale = 1.0 / self.line_height;
+        let mut positions = Vec::new();
+        let mut uvs = Vec::new();
+        let mut min = vec2(f32::MAX, f32::MAX);
+        let mut max = vec2(f32::MIN, f32::MIN);
+        for (line_index, line) in text.lines().enumerate() {
+            let mut pen = 0.0;
+            let mut previous = None;
+            let line_start = positions.len();
+            for c in line.chars() {
+                let glyph = match self.glyphs.get(&c) {
+                    Some(glyph) => glyph,
+                    None => continue,
+                };
+                if let Some(previous) = previous {
+                    pen += self
+                        .font
+                        .horizontal_kern(previous, c, self.pixel_size)
+                        .unwrap_or(0.0);
+                }
+                if glyph.max.x > glyph.min.x && glyph.max.y > glyph.min.y {
+                    let p0 = glyph.min + vec2(pen, 0.0);
+                    let p1 = glyph.max + vec2(pen, 0.0);
+                    for (x, y, u, v) in [
+                        (p0.x, p0.y, glyph.uv_min.x, glyph.uv_min.y),
+                        (p1.x, p0.y, glyph.uv_max.x, glyph.uv_min.y),
+                        (p1.x, p1.y, glyph.uv_max.x, glyph.uv_max.y),
+                        (p1.x, p1.y, glyph.uv_max.x, glyph.uv_max.y),
+                        (p0.x, p1.y, glyph.uv_min.x, glyph.uv_max.y),
+                        (p0.x, p0.y, glyph.uv_min.x, glyph.uv_min.y),
+                    ] {
+                        positions.push(vec3(x * scale, y * scale - line_index as f32, 0.0));
+                        uvs.push(vec2(u, v));
+                    }
+                }
+                pen += glyph.advance;
+                previous = Some(c);
+            }
+            let offset = match alignment {
+                TextAlignment::Left => 0.0,
+                TextAlignment::Center => -0.5 * pen * scale,
+                TextAlignment::Right => -pen * scale,
+            };
+            for p in positions[line_start..].iter_mut() {
+                p.x += offset;
+                min = vec2(min.x.min(p.x), min.y.min(p.y));
+                max = vec2(max.x.max(p.x), max.y.max(p.y));
+            }
+        }
+        (positions, uvs, min, max)
+    }
+}
+
+///
+/// The horizontal alignment of each line of a [Text] relative to its position.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum TextAlignment {
+    /// The lines start at the position.
+    #[default]
+    Left,
+    /// The lines are centered around the position.
+    Center,
+    /// The lines end at the position.
+    Right,
+}
+
+///
+/// The appearance of a [Text].
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TextStyle {
+    /// The height of a line, in pixels for an overlay text and in world units for a billboard text.
+    pub size: f32,
+    /// The color of the text.
+    pub color: Srgba,
+    /// The horizontal alignment of each line.
+    pub alignment: TextAlignment,
+}
+
+impl Default for TextStyle {
+    fn default() -> Self {
+        Self {
+            size: 16.0,
+            color: Srgba::WHITE,
+            alignment: TextAlignment::Left,
+        }
+    }
+}
+
+///
+/// Where a [Text] is placed.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TextPlacement {
+    /// A 2D overlay text, for example for a HUD, with the baseline of the first line at the given position in viewport coordinates,
+    /// ie. (0, 0) is the bottom left corner of the viewport. Should be rendered using [RenderTarget::render_ui].
+    Overlay(Vec2),
+    /// A label in the 3D scene, which always faces the camera, with the baseline of the first line at the given position in world space.
+    Billboard(Vec3),
+}
+
+///
+/// A text rendered using the glyphs in a [FontAtlas], either as a 2D overlay in viewport coordinates or as a billboard in the 3D scene which always faces the camera, see [TextPlacement].
+/// Lines are separated by `\n` and the first line is placed at the position, with the following lines below.
+///
+pub struct Text {
+    context: Context,
+    atlas: Arc<FontAtlas>,
+    text: String,
+    placement: TextPlacement,
+    style: TextStyle,
+    position_buffer: VertexBuffer,
+    uv_buffer: VertexBuffer,
+    vertex_count: u32,
+    extent: (Vec2, Vec2),
+}
+
+impl Text {
+    ///
+    /// Creates a new text with the given placement and style.
+    ///
+    pub fn new(
+        context: &Context,
+        atlas: Arc<FontAtlas>,
+        text: &str,
+        placement: TextPlacement,
+        style: TextStyle,
+    ) -> Self {
+        let mut t = Self {
+            context: context.clone(),
+            atlas,
+            text: String::new(),
+            placement,
+            style,
+            position_buffer: VertexBuffer::new(context),
+            uv_buffer: VertexBuffer::new(context),
+            vertex_count: 0,
+            extent: (vec2(0.0, 0.0), vec2(0.0, 0.0)),
+        };
+        t.set_text(text);
+        t
+    }
+
+    ///
+    /// Returns the text.
+    ///
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    ///
+    /// Sets the text.
+    ///
+    pub fn set_text(&mut self, text: &str) {
+        self.text = text.to_string();
+        self.update();
+    }
+
+    ///
+    /// Returns the placement of the text.
+    ///
+    pub fn placement(&self) -> TextPlacement {
+        self.placement
+    }
+
+    ///
+    /// Sets the placement of the text, for example to move a label together with an object.
+    ///
+    pub fn set_placement(&mut self, placement: TextPlacement) {
+        self.placement = placement;
+    }
+
+    ///
+    /// Returns the style of the text.
+    ///
+    pub fn style(&self) -> TextStyle {
+        self.style
+    }
+
+    ///
+    /// Sets the style of the text.
+    ///
+    pub fn set_style(&mut self, style: TextStyle) {
+        let alignment_changed = style.alignment != self.style.alignment;
+        self.style = style;
+        if alignment_changed {
+            self.update();
+        }
+    }
+
+    ///
+    /// Returns the width and height of the text in pixels for an overlay text and in world units for a billboard text.
+    ///
+    pub fn size(&self) -> Vec2 {
+        (self.extent.1 - self.extent.0) * self.style.size
+    }
+
+    fn update(&mut self) {
+        let (positions, uvs, min, max) = self.atlas.layout(&self.text, self.style.alignment);
+        self.vertex_count = positions.len() as u32;
+        self.extent = if positions.is_empty() {
+            (vec2(0.0, 0.0), vec2(0.0, 0.0))
+        } else {
+            (min, max)
+        };
+        if !positions.is_empty() {
+            self.position_buffer.fill(&positions);
+            self.uv_buffer.fill(&uvs);
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a Text {
+    type Item = &'a dyn Object;
+    type IntoIter = std::iter::Once<&'a dyn Object>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        std::iter::once(self)
+    }
+}
+
+impl Geometry for Text {
+    fn draw(
+        &self,
+        camera: &Camera,
+        program: &Program,
+        render_states: RenderStates,
+        _attributes: FragmentAttributes,
+    ) {
+        if self.vertex_count == 0 {
+            return;
+        }
+        let (anchor, right, up) = match self.placement {
+            TextPlacement::Overlay(position) => (
+                position.extend(0.0),
+                vec3(1.0, 0.0, 0.0),
+                vec3(0.0, 1.0, 0.0),
+            ),
+            TextPlacement::Billboard(position) => {
+                let view = camera.view();
+                (
+                    position,
+                    vec3(view.x.x, view.y.x, view.z.x),
+                    vec3(view.x.y, view.y.y, view.z.y),
+                )
+            }
+        };
+        program.use_uniform("viewProjection", camera.projection() * camera.view());
+        program.use_uniform("anchor", anchor);
+        program.use_uniform("right", right * self.style.size);
+        program.use_uniform("up", up * self.style.size);
+        program.use_vertex_attribute("position", &self.position_buffer);
+        program.use_vertex_attribute("uv_coordinate", &self.uv_buffer);
+        program.draw_arrays(render_states, camera.viewport(), self.vertex_count);
+    }
+
+    fn vertex_shader_source(&self, _required_attributes: FragmentAttributes) -> String {
+        include_str!("shaders/text.vert").to_owned()
+    }
+
+    fn id(&self, _required_attributes: FragmentAttributes) -> u16 {
+        0b1u16 << 15 | 0b111u16
+    }
+
+    fn render_with_material(
+        &self,
+        material: &dyn Material,
+        camera: &Camera,
+        lights: &[&dyn Light],
+    ) {
+        render_with_material(&self.context, camera, self, material, lights)
+    }
+
+    fn render_with_effect(
+        &self,
+        material: &dyn Effect,
+        camera: &Camera,
+        lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) {
+        render_with_effect(
+            &self.context,
+            camera,
+            self,
+            material,
+            lights,
+            color_texture,
+            depth_texture,
+        )
+    }
+
+    fn aabb(&self) -> AxisAlignedBoundingBox {
+        let (min, max) = self.extent;
+        match self.placement {
+            TextPlacement::Overlay(position) => AxisAlignedBoundingBox::new_with_positions(&[
+                (position + min * self.style.size).extend(0.0),
+                (position + max * self.style.size).extend(0.0),
+            ]),
+            TextPlacement::Billboard(position) => {
+                // The text can face any direction, so use a box containing all orientations
+                let radius = self.style.size
+                    * min
+                        .magnitude()
+                        .max(max.magnitude())
+                        .max(vec2(min.x, max.y).magnitude())
+                        .max(vec2(max.x, min.y).magnitude());
+                AxisAlignedBoundingBox::new_with_positions(&[
+                    position - vec3(radius, radius, radius),
+                    position + vec3(radius, radius, radius),
+                ])
+            }
+        }
+    }
+}
+
+impl Object for Text {
+    fn render(&self, camera: &Camera, lights: &[&dyn Light]) {
+        render_with_material(
+            &self.context,
+            camera,
+            self,
+            TextMaterial {
+                texture: self.atlas.texture(),
+                color: self.style.color,
+            },
+            lights,
+        )
+    }
+
+    fn material_type(&self) -> MaterialType {
+        MaterialType::Transparent
+    }
+}
+
+struct TextMaterial<'a> {
+    texture: &'a Texture2D,
+    color: Srgba,
+}
+
+impl Material for TextMaterial<'_> {
+    fn id(&self) -> u16 {
+        0b1u16 << 15 | 0b1u16 << 12 | 0b0101u16
+    }
+
+    fn fragment_shader_source(&self, _lights: &[&dyn Light]) -> String {
+        let mut source = ColorMapping::fragment_shader_source().to_owned();
+        source.push_str(include_str!("shaders/text.frag"));
+        source
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            uv: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(&self, program: &Program, camera: &Camera, _lights: &[&dyn Light]) {
+        camera.color_mapping.use_uniforms(program);
+        program.use_uniform("textColor", self.color.to_linear_srgb());
+        program.use_texture("atlas", self.texture);
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates {
+            write_mask: WriteMask::COLOR,
+            blend: Blend::TRANSPARENCY,
+            cull: Cull::None,
+            ..Default::default()
+        }
+    }
+
+    fn material_type(&self) -> MaterialType {
+        MaterialType::Transparent
+    }
+}
@@ -0,0 +1,91 @@
+use crate::renderer::*;
+
+// Weighted blended order-independent transparency as described in
+// McGuire and Bavoil, "Weighted Blended Order-Independent Transparency", Journal of Computer Graphics Techniques, 2013.
+
+// Turns the fragment shader of a transparent material into a shader which writes the color weighted by opacity and depth to the accumulation targets
+pub(crate) fn fragment_shader_source(material_source: &str) -> String {
+    let mut source = material_source
+        .replacen(
+            "layout (location = 0) out vec4 outColor;",
+            "vec4 outColor;",
+            1,
+        )
+        .replacen("void main()", "void material_main()", 1);
+    source.push_str(include_str!("shaders/oit_accumulation.frag"));
+    source
+}
+
+// The color is added to the first target while the alpha channel of the first target is multiplied by one minus the opacity,
+// and the weight is added to the second target
+pub(crate) fn render_states(material_render_states: RenderStates) -> RenderStates {
+    RenderStates {
+        write_mask: WriteMask::COLOR,
+        blend: Blend::Enabled {
+            source_rgb_multiplier: BlendMultiplierType::One,
+            source_alpha_multiplier: BlendMultiplierType::Zero,
+            destination_rgb_multiplier: BlendMultiplierType::One,
+            destination_alpha_multiplier: BlendMultiplierType::OneMinusSrcAlpha,
+            rgb_equation: BlendEquationType::Add,
+            alpha_equation: BlendEquationType::Add,
+        },
+        ..material_render_states
+    }
+}
+
+pub(crate) struct OitResolveEffect;
+
+impl Effect for OitResolveEffect {
+    fn fragment_shader_source(
+        &self,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        _depth_texture: Option<DepthTexture>,
+    ) -> String {
+        format!(
+            "{}{}",
+            color_texture
+                .expect("Must supply the accumulation textures to resolve the transparency")
+                .fragment_shader_source(),
+            include_str!("shaders/oit_resolve.frag")
+        )
+    }
+
+    fn id(&self, color_texture: Option<ColorTexture>, _depth_texture: Option<DepthTexture>) -> u16 {
+        0b1u16 << 14
+            | 0b1u16 << 7
+            | 0b1u16 << 13
+            | color_texture
+                .expect("Must supply the accumulation textures to resolve the transparency")
+                .id()
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            uv: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(
+        &self,
+        program: &Program,
+        _camera: &Camera,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        _depth_texture: Option<DepthTexture>,
+    ) {
+        color_texture
+            .expect("Must supply the accumulation textures to resolve the transparency")
+            .use_uniforms(program);
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates {
+            write_mask: WriteMask::COLOR,
+            depth_test: DepthTest::Always,
+            blend: Blend::TRANSPARENCY,
+            cull: Cull::Back,
+        }
+    }
+}
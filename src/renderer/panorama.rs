@@ -0,0 +1,206 @@
+use crate::renderer::*;
+
+///
+/// Renders the given objects with the given lights in all directions from the given position and returns the result as an equirectangular panorama image,
+/// which can be shared as a 360° view of the scene. The image has the given width and half the width as height and is tone and color mapped to 8-bit sRGB colors
+/// using the default mapping, see [Camera::set_default_tone_and_color_mapping].
+///
+/// The scene is first rendered into the six sides of a cube map with a size of a quarter of the width, which is then stitched into the panorama.
+/// For a high dynamic range panorama, for example to use as an environment map, see [render_panorama_hdr].
+///
+pub fn render_panorama(
+    context: &Context,
+    position: Vec3,
+    width: u32,
+    objects: &[&dyn Object],
+    lights: &[&dyn Light],
+    z_near: f32,
+    z_far: f32,
+) -> CpuTexture {
+    let cube_map = render_cube_map(context, position, width / 4, objects, lights, z_near, z_far);
+    let mut camera = Camera::new_2d(Viewport::new_at_origo(width, width / 2));
+    camera.set_default_tone_and_color_mapping();
+    let mut texture = Texture2D::new_empty::<[u8; 4]>(
+        context,
+        width,
+        width / 2,
+        Interpolation::Nearest,
+        Interpolation::Nearest,
+        None,
+        Wrapping::ClampToEdge,
+        Wrapping::ClampToEdge,
+    );
+    let data = texture
+        .as_color_target(None)
+        .clear(ClearState::default())
+        .apply_screen_material(
+            &EquirectangularMaterial {
+                cube_map: &cube_map,
+            },
+            &camera,
+            &[],
+        )
+        .read::<[u8; 4]>();
+    CpuTexture {
+        name: "panorama".to_string(),
+        data: TextureData::RgbaU8(data),
+        width,
+        height: width / 2,
+        ..Default::default()
+    }
+}
+
+///
+/// Renders the given objects with the given lights in all directions from the given position and returns the result as a high dynamic range equirectangular panorama image
+/// with the given width and half the width as height. The colors are neither tone nor color mapped,
+/// so the image can be used as an environment map, for example using [TextureCubeMap::new_from_equirectangular] and [Skybox::new_from_equirectangular].
+///
+/// Also see [render_panorama].
+///
+pub fn render_panorama_hdr(
+    context: &Context,
+    position: Vec3,
+    width: u32,
+    objects: &[&dyn Object],
+    lights: &[&dyn Light],
+    z_near: f32,
+    z_far: f32,
+) -> CpuTexture {
+    let cube_map = render_cube_map(context, position, width / 4, objects, lights, z_near, z_far);
+    let mut camera = Camera::new_2d(Viewport::new_at_origo(width, width / 2));
+    camera.disable_tone_and_color_mapping();
+    let mut texture = Texture2D::new_empty::<[f16; 4]>(
+        context,
+        width,
+        width / 2,
+        Interpolation::Nearest,
+        Interpolation::Nearest,
+        None,
+        Wrapping::ClampToEdge,
+        Wrapping::ClampToEdge,
+    );
+    let data = texture
+        .as_color_target(None)
+        .clear(ClearState::default())
+        .apply_screen_material(
+            &EquirectangularMaterial {
+                cube_map: &cube_map,
+            },
+            &camera,
+            &[],
+        )
+        .read::<[f16; 4]>();
+    CpuTexture {
+        name: "panorama".to_string(),
+        data: TextureData::RgbaF16(data),
+        width,
+        height: width / 2,
+        ..Default::default()
+    }
+}
+
+fn render_cube_map(
+    context: &Context,
+    position: Vec3,
+    size: u32,
+    objects: &[&dyn Object],
+    lights: &[&dyn Light],
+    z_near: f32,
+    z_far: f32,
+) -> TextureCubeMap {
+    let size = size.max(1);
+    let mut cube_map = TextureCubeMap::new_empty::<[f16; 4]>(
+        context,
+        size,
+        size,
+        Interpolation::Linear,
+        Interpolation::Linear,
+        None,
+        Wrapping::ClampToEdge,
+        Wrapping::ClampToEdge,
+        Wrapping::ClampToEdge,
+    );
+    let mut depth_texture = DepthTexture2D::new::<f32>(
+        context,
+        size,
+        size,
+        Wrapping::ClampToEdge,
+        Wrapping::ClampToEdge,
+    );
+    let viewport = Viewport::new_at_origo(size, size);
+    for side in CubeMapSide::iter() {
+        let mut camera = Camera::new_perspective(
+            viewport,
+            position,
+            position + side.direction(),
+            side.up(),
+            degrees(90.0),
+            z_near,
+            z_far,
+        );
+        camera.disable_tone_and_color_mapping();
+        RenderTarget::new(
+            cube_map.as_color_target(&[side], None),
+            depth_texture.as_depth_target(),
+        )
+        .clear(ClearState::color_and_depth(0.0, 0.0, 0.0, 1.0, 1.0))
+        .render(&camera, objects, lights);
+    }
+    cube_map
+}
+
+struct EquirectangularMaterial<'a> {
+    cube_map: &'a TextureCubeMap,
+}
+
+impl Material for EquirectangularMaterial<'_> {
+    fn fragment_shader_source(&self, _lights: &[&dyn Light]) -> String {
+        format!(
+            "{}{}
+            uniform samplerCube cubeMap;
+
+            in vec2 uvs;
+
+            layout (location = 0) out vec4 outColor;
+
+            void main()
+            {{
+                float phi = (uvs.x - 0.5) * 6.2831853;
+                float theta = (uvs.y - 0.5) * 3.1415927;
+                vec3 direction = vec3(cos(theta) * cos(phi), sin(theta), cos(theta) * sin(phi));
+                outColor = texture(cubeMap, direction);
+                outColor.rgb = tone_mapping(outColor.rgb);
+                outColor.rgb = color_mapping(outColor.rgb);
+            }}",
+            ToneMapping::fragment_shader_source(),
+            ColorMapping::fragment_shader_source()
+        )
+    }
+
+    fn id(&self) -> u16 {
+        0b1u16 << 15 | 0b1000u16
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            uv: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(&self, program: &Program, camera: &Camera, _lights: &[&dyn Light]) {
+        camera.use_tone_and_color_mapping_uniforms(program);
+        program.use_texture_cube("cubeMap", self.cube_map);
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates {
+            depth_test: DepthTest::Always,
+            ..Default::default()
+        }
+    }
+
+    fn material_type(&self) -> MaterialType {
+        MaterialType::Opaque
+    }
+}
@@ -0,0 +1,164 @@
+use crate::renderer::*;
+
+///
+/// The result of a successful pick, see [Picker::pick].
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PickResult {
+    /// The id of the picked object given when it was added to the [Picker].
+    pub id: u32,
+    /// The index of the picked instance if the object was added using [Picker::add_instanced], otherwise `None`.
+    pub instance: Option<u32>,
+    /// The position in world space of the picked surface.
+    pub position: Vec3,
+    /// The distance from the camera to the picked surface along the view direction.
+    pub depth: f32,
+}
+
+///
+/// Finds the object under a pixel, for example under the cursor, by rendering the added objects with a [PickMaterial] which writes the id of each object
+/// into an offscreen target and reading back the id in the given pixel.
+/// This works for any geometry, for example the parts of a [Model] or a sphere created with [CpuMesh::sphere],
+/// and returns the index of the picked instance for objects with an [InstancedMesh], for example an [InstancedModel], see [Picker::add_instanced].
+///
+/// Only the picked pixel is rendered, so picking is cheap, but it requires a round trip to the GPU.
+/// Also see [pick] which finds the picked position without rendering the objects to a render target.
+///
+pub struct Picker<'a> {
+    context: Context,
+    objects: Vec<(u32, bool, &'a dyn Object)>,
+    textures: Option<(Texture2D, DepthTexture2D)>,
+}
+
+impl<'a> Picker<'a> {
+    ///
+    /// Creates a new picker without any objects.
+    ///
+    pub fn new(context: &Context) -> Self {
+        Self {
+            context: context.clone(),
+            objects: Vec::new(),
+            textures: None,
+        }
+    }
+
+    ///
+    /// Adds the given objects, for example a [Model], with the given id, which is returned by [Picker::pick] if one of the objects is picked.
+    /// The id must be smaller than `2^24`.
+    ///
+    pub fn add(&mut self, id: u32, objects: impl IntoIterator<Item = &'a dyn Object>) {
+        self.objects
+            .extend(objects.into_iter().map(|object| (id, false, object)));
+    }
+
+    ///
+    /// Adds the given objects, for example an [InstancedModel], with the given id, which is returned by [Picker::pick] together with the index of the picked instance
+    /// if one of the objects is picked. The geometry of the objects must be an [InstancedMesh].
+    /// The id must be smaller than `2^24`.
+    ///
+    pub fn add_instanced(&mut self, id: u32, objects: impl IntoIterator<Item = &'a dyn Object>) {
+        self.objects
+            .extend(objects.into_iter().map(|object| (id, true, object)));
+    }
+
+    ///
+    /// Removes all objects with the given id.
+    ///
+    pub fn remove(&mut self, id: u32) {
+        self.objects.retain(|(object_id, _, _)| *object_id != id);
+    }
+
+    ///
+    /// Removes all objects.
+    ///
+    pub fn clear(&mut self) {
+        self.objects.clear();
+    }
+
+    ///
+    /// Returns the closest of the added objects in the given pixel as seen from the given camera, or `None` if no object is in the pixel.
+    /// The pixel coordinate must be in physical pixels, where (viewport.x, viewport.y) indicate the bottom left corner of the viewport
+    /// and (viewport.x + viewport.width, viewport.y + viewport.height) indicate the top right corner.
+    ///
+    pub fn pick(
+        &mut self,
+        camera: &Camera,
+        pixel: impl Into<PhysicalPoint> + Copy,
+    ) -> Option<PickResult> {
+        let viewport = camera.viewport();
+        let point: PhysicalPoint = pixel.into();
+        let x = (point.x - viewport.x as f32).floor();
+        let y = (point.y - viewport.y as f32).floor();
+        if x < 0.0 || y < 0.0 || x >= viewport.width as f32 || y >= viewport.height as f32 {
+            return None;
+        }
+        let scissor_box = ScissorBox {
+            x: x as i32,
+            y: y as i32,
+            width: 1,
+            height: 1,
+        };
+
+        if self
+            .textures
+            .as_ref()
+            .map(|(t, _)| t.width() != viewport.width || t.height() != viewport.height)
+            .unwrap_or(true)
+        {
+            self.textures = Some((
+                Texture2D::new_empty::<[f32; 4]>(
+                    &self.context,
+                    viewport.width,
+                    viewport.height,
+                    Interpolation::Nearest,
+                    Interpolation::Nearest,
+                    None,
+                    Wrapping::ClampToEdge,
+                    Wrapping::ClampToEdge,
+                ),
+                DepthTexture2D::new::<f32>(
+                    &self.context,
+                    viewport.width,
+                    viewport.height,
+                    Wrapping::ClampToEdge,
+                    Wrapping::ClampToEdge,
+                ),
+            ));
+        }
+        let (texture, depth_texture) = self.textures.as_mut().unwrap();
+        let mut pick_camera = camera.clone();
+        pick_camera.set_viewport(Viewport::new_at_origo(viewport.width, viewport.height));
+        let objects = &self.objects;
+        let value = RenderTarget::new(
+            texture.as_color_target(None),
+            depth_texture.as_depth_target(),
+        )
+        .clear_partially(
+            scissor_box,
+            ClearState::color_and_depth(0.0, 0.0, 0.0, 0.0, 1.0),
+        )
+        .write_partially(scissor_box, || {
+            for (id, instanced, object) in objects.iter() {
+                object.render_with_material(&PickMaterial::new(*id, *instanced), &pick_camera, &[]);
+            }
+        })
+        .read_color_partially::<[f32; 4]>(scissor_box)[0];
+
+        if value[3] == 0.0 {
+            return None;
+        }
+        let depth = value[2];
+        let direction = camera.view_direction_at_pixel(pixel);
+        Some(PickResult {
+            id: value[0] as u32,
+            instance: if value[1] >= 0.0 {
+                Some(value[1] as u32)
+            } else {
+                None
+            },
+            position: camera.position_at_pixel(pixel)
+                + direction * depth / direction.dot(camera.view_direction()),
+            depth,
+        })
+    }
+}
@@ -0,0 +1,195 @@
+use crate::renderer::*;
+
+///
+/// A set of quality settings used by the [QualityManager].
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct QualityTier {
+    /// The name of the tier, for example shown in a settings menu.
+    pub name: String,
+    /// The size of the shadow maps, for example used in [DirectionalLight::generate_shadow_map].
+    pub shadow_map_size: u32,
+    /// The number of samples used by screen space ambient occlusion, see [Ssao::sample_count].
+    pub ssao_samples: u32,
+    /// The render scale, for example used in [RenderScale::set_scale].
+    pub render_scale: f32,
+}
+
+impl QualityTier {
+    ///
+    /// Returns a default set of tiers ordered from the lowest to the highest quality.
+    ///
+    pub fn default_tiers() -> Vec<Self> {
+        vec![
+            Self {
+                name: "Low".to_string(),
+                shadow_map_size: 512,
+                ssao_samples: 4,
+                render_scale: 0.5,
+            },
+            Self {
+                name: "Medium".to_string(),
+                shadow_map_size: 1024,
+                ssao_samples: 8,
+                render_scale: 0.75,
+            },
+            Self {
+                name: "High".to_string(),
+                shadow_map_size: 2048,
+                ssao_samples: 16,
+                render_scale: 1.0,
+            },
+            Self {
+                name: "Ultra".to_string(),
+                shadow_map_size: 4096,
+                ssao_samples: 32,
+                render_scale: 1.0,
+            },
+        ]
+    }
+}
+
+///
+/// Monitors the frame time and automatically steps the quality up or down between a list of user-defined [QualityTier]s to reach a target frame rate.
+///
+/// Each frame, wrap the rendering in [QualityManager::measure] to measure the time spent on the GPU, if supported (see [GpuTimer]),
+/// and call [QualityManager::update] afterwards. Then use the settings of the current tier, see [QualityManager::tier], for example
+/// as the shadow map size when generating shadow maps and as the scale of a [RenderScale].
+///
+/// To avoid oscillating between two tiers, the quality is only changed when the average frame time has been above or below the target for a while,
+/// the thresholds for stepping up and down are different and stepping up again to a tier that was just left is delayed even more.
+///
+pub struct QualityManager {
+    tiers: Vec<QualityTier>,
+    current: usize,
+    timer: Option<GpuTimer>,
+    average_frame_time: Option<f64>,
+    frames_since_change: u32,
+    last_downgrade_from: Option<usize>,
+    /// The desired frame time in milliseconds.
+    pub target_frame_time: f64,
+    /// The quality is stepped down when the average frame time is larger than the target frame time multiplied by this factor.
+    pub downgrade_threshold: f64,
+    /// The quality is stepped up when the average frame time is smaller than the target frame time multiplied by this factor.
+    pub upgrade_threshold: f64,
+    /// The minimum number of frames between two changes of the quality.
+    pub min_frames_between_changes: u32,
+}
+
+impl QualityManager {
+    ///
+    /// Constructs a new quality manager with the given tiers ordered from the lowest to the highest quality, which starts at the highest quality
+    /// and targets 60 frames per second.
+    ///
+    /// # Panics
+    /// If the list of tiers is empty.
+    ///
+    pub fn new(context: &Context, tiers: Vec<QualityTier>) -> Self {
+        assert!(
+            !tiers.is_empty(),
+            "a quality manager needs at least one quality tier"
+        );
+        Self {
+            current: tiers.len() - 1,
+            tiers,
+            timer: GpuTimer::new(context),
+            average_frame_time: None,
+            frames_since_change: 0,
+            last_downgrade_from: None,
+            target_frame_time: 1000.0 / 60.0,
+            downgrade_threshold: 1.1,
+            upgrade_threshold: 0.7,
+            min_frames_between_changes: 60,
+        }
+    }
+
+    ///
+    /// Measures the time the GPU spends rendering in the given closure, if supported, which is then used by [QualityManager::update] instead of the frame time.
+    ///
+    pub fn measure<T>(&mut self, render: impl FnOnce() -> T) -> T {
+        match self.timer {
+            Some(ref mut timer) => timer.measure(render),
+            None => render(),
+        }
+    }
+
+    ///
+    /// Updates the average frame time and steps the quality up or down if needed. Should be called once each frame.
+    /// The given frame time in milliseconds, for example [FrameInput::elapsed_time](crate::FrameInput::elapsed_time), is only used if the GPU time is not measured, see [QualityManager::measure].
+    /// Returns true if the tier changed.
+    ///
+    pub fn update(&mut self, frame_time: f64) -> bool {
+        let frame_time = self
+            .timer
+            .as_mut()
+            .and_then(|timer| timer.elapsed_time())
+            .unwrap_or(frame_time);
+        let average_frame_time = match self.average_frame_time {
+            Some(average) => average + 0.05 * (frame_time - average),
+            None => frame_time,
+        };
+        self.average_frame_time = Some(average_frame_time);
+        self.frames_since_change = self.frames_since_change.saturating_add(1);
+        if self.frames_since_change < self.min_frames_between_changes {
+            return false;
+        }
+
+        if average_frame_time > self.downgrade_threshold * self.target_frame_time
+            && self.current > 0
+        {
+            self.last_downgrade_from = Some(self.current);
+            self.set_tier_index(self.current - 1);
+            true
+        } else if average_frame_time < self.upgrade_threshold * self.target_frame_time
+            && self.current + 1 < self.tiers.len()
+        {
+            // Wait longer before stepping up to the tier which was just too slow
+            if self.last_downgrade_from == Some(self.current + 1)
+                && self.frames_since_change < 4 * self.min_frames_between_changes
+            {
+                return false;
+            }
+            self.set_tier_index(self.current + 1);
+            true
+        } else {
+            false
+        }
+    }
+
+    ///
+    /// Returns the current tier.
+    ///
+    pub fn tier(&self) -> &QualityTier {
+        &self.tiers[self.current]
+    }
+
+    ///
+    /// Returns the index of the current tier in the list of tiers.
+    ///
+    pub fn tier_index(&self) -> usize {
+        self.current
+    }
+
+    ///
+    /// Sets the current tier, for example when the user chooses a tier manually. The index is clamped to the number of tiers.
+    ///
+    pub fn set_tier_index(&mut self, index: usize) {
+        self.current = index.min(self.tiers.len() - 1);
+        self.frames_since_change = 0;
+        self.average_frame_time = None;
+    }
+
+    ///
+    /// Returns the tiers ordered from the lowest to the highest quality.
+    ///
+    pub fn tiers(&self) -> &[QualityTier] {
+        &self.tiers
+    }
+
+    ///
+    /// Returns the average frame time in milliseconds since the last change of the tier or `None` if no frames have been measured.
+    ///
+    pub fn average_frame_time(&self) -> Option<f64> {
+        self.average_frame_time
+    }
+}
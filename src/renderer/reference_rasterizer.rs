@@ -0,0 +1,427 @@
+use crate::renderer::*;
+
+///
+/// The shading used by the [ReferenceRasterizer], which corresponds to a subset of the materials used when rendering on the GPU.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReferenceMaterial {
+    ///
+    /// The same as a [ColorMaterial] with the given color and without a texture, rendered with a camera where tone and color mapping is disabled.
+    /// If the mesh has per vertex colors, they are interpolated and multiplied with the color.
+    /// As on the GPU, the colors are converted to linear sRGB before they are interpolated and the result is the linear color.
+    ///
+    Color(Srgba),
+    ///
+    /// The same as a [DepthMaterial] with the given minimum and maximum distance.
+    /// If a distance is `None`, the near or far plane of the camera is used.
+    ///
+    Depth {
+        /// The minimum distance from the camera to any object.
+        min_distance: Option<f32>,
+        /// The maximum distance from the camera to any object.
+        max_distance: Option<f32>,
+    },
+}
+
+///
+/// A simple rasterizer which renders a [CpuMesh] on the CPU with a [ReferenceMaterial].
+/// The result is deterministic and does not depend on a GPU or graphics driver,
+/// which makes it useful for producing reference images for tests, for example golden-image tests of the GPU rendering.
+///
+/// The rasterizer follows the same conventions as the GPU rendering:
+/// - Pixel centers are at half-integer coordinates and the top-left fill rule is used, so pixels on an edge shared by two triangles are only covered once.
+/// - The triangles are clipped against the near plane and the depth test passes if the depth is less than the current depth (see [DepthTest::Less]).
+/// - Triangles are not culled and are not blended.
+///
+pub struct ReferenceRasterizer {
+    width: u32,
+    height: u32,
+    color: Vec<[u8; 4]>,
+    depth: Vec<f32>,
+}
+
+impl ReferenceRasterizer {
+    ///
+    /// Constructs a new rasterizer with the given size in pixels which is cleared to transparent black and maximum depth.
+    ///
+    pub fn new(width: u32, height: u32) -> Self {
+        let size = (width * height) as usize;
+        Self {
+            width,
+            height,
+            color: vec![[0, 0, 0, 0]; size],
+            depth: vec![1.0; size],
+        }
+    }
+
+    ///
+    /// Returns the width in pixels.
+    ///
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    ///
+    /// Returns the height in pixels.
+    ///
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    ///
+    /// Clears the color to the given color and the depth to the maximum depth.
+    ///
+    pub fn clear(&mut self, color: Srgba) -> &mut Self {
+        self.color.fill([color.r, color.g, color.b, color.a]);
+        self.depth.fill(1.0);
+        self
+    }
+
+    ///
+    /// Renders the mesh transformed by the given transformation with the given camera and material.
+    /// The viewport of the camera is ignored, the mesh is always rendered into the full size of the rasterizer.
+    ///
+    pub fn render(
+        &mut self,
+        camera: &Camera,
+        mesh: &CpuMesh,
+        transformation: Mat4,
+        material: ReferenceMaterial,
+    ) -> &mut Self {
+        let positions = mesh.positions.to_f32();
+        let indices = mesh
+            .indices
+            .to_u32()
+            .unwrap_or_else(|| (0..positions.len() as u32).collect());
+        let view_projection = camera.projection() * camera.view();
+        let eye = *camera.position();
+        let (min_distance, max_distance) = match material {
+            ReferenceMaterial::Depth {
+                min_distance,
+                max_distance,
+            } => (
+                min_distance.unwrap_or_else(|| camera.z_near()),
+                max_distance.unwrap_or_else(|| camera.z_far()),
+            ),
+            ReferenceMaterial::Color(_) => (0.0, 1.0),
+        };
+
+        let vertex = |index: u32| {
+            let index = index as usize;
+            let world_position = (transformation * positions[index].extend(1.0)).truncate();
+            ClipVertex {
+                clip_position: view_projection * world_position.extend(1.0),
+                world_position,
+                color: mesh
+                    .colors
+                    .as_ref()
+                    .map(|colors| colors[index].to_linear_srgb())
+                    .unwrap_or(vec4(1.0, 1.0, 1.0, 1.0)),
+            }
+        };
+        for triangle in indices.chunks_exact(3) {
+            let polygon = clip_against_near_plane([
+                vertex(triangle[0]),
+                vertex(triangle[1]),
+                vertex(triangle[2]),
+            ]);
+            for i in 2..polygon.len() {
+                self.rasterize_triangle(
+                    [&polygon[0], &polygon[i - 1], &polygon[i]],
+                    |v: &ClipVertex| match material {
+                        ReferenceMaterial::Color(color) => {
+                            let c = color.to_linear_srgb();
+                            vec4(
+                                c.x * v.color.x,
+                                c.y * v.color.y,
+                                c.z * v.color.z,
+                                c.w * v.color.w,
+                            )
+                        }
+                        ReferenceMaterial::Depth { .. } => {
+                            let distance = (v.world_position.distance(eye) - min_distance)
+                                / (max_distance - min_distance);
+                            vec4(distance, distance, distance, 1.0)
+                        }
+                    },
+                );
+            }
+        }
+        self
+    }
+
+    ///
+    /// Returns the color of each pixel in the same order as [RenderTarget::read_color], ie. starting with the top left pixel and then row by row.
+    ///
+    pub fn color(&self) -> &[[u8; 4]] {
+        &self.color
+    }
+
+    ///
+    /// Returns the depth in the range `[0, 1]` of each pixel in the same order as [RenderTarget::read_depth], ie. starting with the bottom left pixel and then row by row.
+    ///
+    pub fn depth(&self) -> &[f32] {
+        &self.depth
+    }
+
+    ///
+    /// Returns the color as an image.
+    ///
+    pub fn color_image(&self) -> CpuTexture {
+        CpuTexture {
+            data: TextureData::RgbaU8(self.color.clone()),
+            width: self.width,
+            height: self.height,
+            ..Default::default()
+        }
+    }
+
+    fn rasterize_triangle(
+        &mut self,
+        vertices: [&ClipVertex; 3],
+        shade: impl Fn(&ClipVertex) -> Vec4,
+    ) {
+        let (width, height) = (self.width as f32, self.height as f32);
+        let screen = vertices.map(|v| {
+            let ndc = v.clip_position.truncate() / v.clip_position.w;
+            vec3(
+                (0.5 * ndc.x + 0.5) * width,
+                (0.5 * ndc.y + 0.5) * height,
+                0.5 * ndc.z + 0.5,
+            )
+        });
+        let area = edge(screen[0], screen[1], screen[2]);
+        if area == 0.0 || !area.is_finite() {
+            return;
+        }
+        // Make the triangle counter clockwise, so the edge functions are positive inside the triangle
+        let (vertices, screen, area) = if area < 0.0 {
+            (
+                [vertices[0], vertices[2], vertices[1]],
+                [screen[0], screen[2], screen[1]],
+                -area,
+            )
+        } else {
+            (vertices, screen, area)
+        };
+
+        let min_x = screen.iter().map(|p| p.x).fold(f32::MAX, f32::min);
+        let max_x = screen.iter().map(|p| p.x).fold(f32::MIN, f32::max);
+        let min_y = screen.iter().map(|p| p.y).fold(f32::MAX, f32::min);
+        let max_y = screen.iter().map(|p| p.y).fold(f32::MIN, f32::max);
+        let x0 = (min_x - 0.5).ceil().max(0.0) as u32;
+        let x1 = ((max_x - 0.5).floor() + 1.0).clamp(0.0, width) as u32;
+        let y0 = (min_y - 0.5).ceil().max(0.0) as u32;
+        let y1 = ((max_y - 0.5).floor() + 1.0).clamp(0.0, height) as u32;
+        let inv_w = vertices.map(|v| 1.0 / v.clip_position.w);
+
+        for y in y0..y1 {
+            for x in x0..x1 {
+                let p = vec3(x as f32 + 0.5, y as f32 + 0.5, 0.0);
+                let w0 = edge(screen[1], screen[2], p);
+                let w1 = edge(screen[2], screen[0], p);
+                let w2 = edge(screen[0], screen[1], p);
+                if !is_inside(w0, screen[1], screen[2])
+                    || !is_inside(w1, screen[2], screen[0])
+                    || !is_inside(w2, screen[0], screen[1])
+                {
+                    continue;
+                }
+                let (b0, b1, b2) = (w0 / area, w1 / area, w2 / area);
+                let depth = b0 * screen[0].z + b1 * screen[1].z + b2 * screen[2].z;
+                let index = (y * self.width + x) as usize;
+                let color_index = ((self.height - 1 - y) * self.width + x) as usize;
+                if !(0.0..=1.0).contains(&depth) || depth >= self.depth[index] {
+                    continue;
+                }
+
+                // Perspective correct interpolation
+                let (p0, p1, p2) = (b0 * inv_w[0], b1 * inv_w[1], b2 * inv_w[2]);
+                let sum = p0 + p1 + p2;
+                let (p0, p1, p2) = (p0 / sum, p1 / sum, p2 / sum);
+                let fragment = ClipVertex {
+                    clip_position: vec4(0.0, 0.0, 0.0, 1.0),
+                    world_position: p0 * vertices[0].world_position
+                        + p1 * vertices[1].world_position
+                        + p2 * vertices[2].world_position,
+                    color: p0 * vertices[0].color + p1 * vertices[1].color + p2 * vertices[2].color,
+                };
+                let color = shade(&fragment);
+                self.depth[index] = depth;
+                self.color[color_index] = [
+                    to_u8(color.x),
+                    to_u8(color.y),
+                    to_u8(color.z),
+                    to_u8(color.w),
+                ];
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct ClipVertex {
+    clip_position: Vec4,
+    world_position: Vec3,
+    color: Vec4,
+}
+
+impl ClipVertex {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        Self {
+            clip_position: self.clip_position + (other.clip_position - self.clip_position) * t,
+            world_position: self.world_position + (other.world_position - self.world_position) * t,
+            color: self.color + (other.color - self.color) * t,
+        }
+    }
+}
+
+fn clip_against_near_plane(triangle: [ClipVertex; 3]) -> Vec<ClipVertex> {
+    // The distance to the near plane, which is positive on the visible side
+    let distance = |v: &ClipVertex| v.clip_position.z + v.clip_position.w;
+    let mut polygon = Vec::with_capacity(4);
+    for i in 0..3 {
+        let current = &triangle[i];
+        let next = &triangle[(i + 1) % 3];
+        let (d0, d1) = (distance(current), distance(next));
+        if d0 >= 0.0 {
+            polygon.push(*current);
+        }
+        if (d0 >= 0.0) != (d1 >= 0.0) {
+            polygon.push(current.lerp(next, d0 / (d0 - d1)));
+        }
+    }
+    polygon
+}
+
+fn edge(a: Vec3, b: Vec3, p: Vec3) -> f32 {
+    (b.x - a.x) * (p.y - a.y) - (b.y - a.y) * (p.x - a.x)
+}
+
+///
+/// Implements the top-left fill rule for a counter clockwise triangle with the y-axis pointing up.
+///
+fn is_inside(weight: f32, a: Vec3, b: Vec3) -> bool {
+    if weight != 0.0 {
+        weight > 0.0
+    } else {
+        let top = a.y == b.y && b.x < a.x;
+        let left = b.y < a.y;
+        top || left
+    }
+}
+
+fn to_u8(value: f32) -> u8 {
+    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WIDTH: u32 = 16;
+    const HEIGHT: u32 = 16;
+
+    fn gradient_quad(bottom: Srgba, top: Srgba) -> CpuMesh {
+        let (width, height) = (WIDTH as f32, HEIGHT as f32);
+        CpuMesh {
+            positions: Positions::F32(vec![
+                vec3(0.0, 0.0, 0.0),
+                vec3(width, 0.0, 0.0),
+                vec3(width, height, 0.0),
+                vec3(0.0, height, 0.0),
+            ]),
+            indices: Indices::U32(vec![0, 1, 2, 0, 2, 3]),
+            colors: Some(vec![bottom, bottom, top, top]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn interpolates_vertex_colors_in_linear_space() {
+        let camera = Camera::new_2d(Viewport::new_at_origo(WIDTH, HEIGHT));
+        let top = Srgba::new(128, 128, 128, 255);
+        let mut rasterizer = ReferenceRasterizer::new(WIDTH, HEIGHT);
+        rasterizer.render(
+            &camera,
+            &gradient_quad(Srgba::new(0, 0, 0, 255), top),
+            Mat4::identity(),
+            ReferenceMaterial::Color(Srgba::WHITE),
+        );
+
+        let linear_top = top.to_linear_srgb().x;
+        for (i, pixel) in rasterizer.color().iter().enumerate() {
+            // Both triangles share the diagonal, so every pixel should be covered
+            assert_eq!(pixel[3], 255, "pixel {} is not covered", i);
+            let y = HEIGHT - 1 - i as u32 / WIDTH;
+            let t = (y as f32 + 0.5) / HEIGHT as f32;
+            let expected = to_u8(t * linear_top);
+            assert!(
+                (pixel[0] as i32 - expected as i32).abs() <= 1,
+                "pixel {} is {} but expected {}",
+                i,
+                pixel[0],
+                expected
+            );
+        }
+    }
+
+    #[cfg(all(
+        feature = "headless",
+        feature = "test-utils",
+        not(target_arch = "wasm32")
+    ))]
+    #[test]
+    #[ignore = "requires a GPU"]
+    fn matches_gpu_rendering() {
+        use crate::test::*;
+        let context = crate::HeadlessContext::new().unwrap();
+        let mut camera = Camera::new_perspective(
+            Viewport::new_at_origo(64, 64),
+            vec3(2.0, 1.5, 3.0),
+            vec3(0.0, 0.0, 0.0),
+            vec3(0.0, 1.0, 0.0),
+            degrees(45.0),
+            0.1,
+            10.0,
+        );
+        camera.disable_tone_and_color_mapping();
+        let mut mesh = CpuMesh::cube();
+        let vertex_count = mesh.vertex_count();
+        mesh.colors = Some(
+            (0..vertex_count)
+                .map(|i| Srgba::new((i * 37 % 256) as u8, (i * 91 % 256) as u8, 200, 255))
+                .collect(),
+        );
+        let color = Srgba::new(230, 180, 120, 255);
+
+        let mut rasterizer = ReferenceRasterizer::new(64, 64);
+        rasterizer.render(
+            &camera,
+            &mesh,
+            Mat4::identity(),
+            ReferenceMaterial::Color(color),
+        );
+        let expected = TestImage::from(&rasterizer);
+
+        let actual = render_offscreen(&context, 64, 64, |target| {
+            target.render(
+                &camera,
+                &Gm::new(
+                    Mesh::new(&context, &mesh),
+                    ColorMaterial {
+                        color,
+                        ..Default::default()
+                    },
+                ),
+                &[],
+            );
+        });
+        let comparison = compare(&actual, &expected, Tolerance::default()).unwrap();
+        assert!(
+            comparison.passed,
+            "{} pixels differ from the reference rasterizer",
+            comparison.differing_pixels
+        );
+    }
+}
@@ -0,0 +1,263 @@
+use crate::renderer::*;
+
+///
+/// A render of a scene together with auxiliary layers, ie. the linear depth, the world space normals and the object IDs of each pixel,
+/// which can be used by offline compositing tools to relight the render and apply post effects.
+///
+/// The pixels of all layers are stored row by row starting with the top left pixel, which is the same order as returned by [RenderTarget::read_color].
+/// Use [RenderLayers::save] to save the layers as PNG and EXR files (requires the `render-export` feature).
+///
+#[derive(Clone, Debug)]
+pub struct RenderLayers {
+    /// The width in pixels.
+    pub width: u32,
+    /// The height in pixels.
+    pub height: u32,
+    /// The rendered colors, ie. the beauty layer.
+    pub beauty: Vec<[u8; 4]>,
+    /// The distance in world units from the camera position to the surface of each pixel. Pixels without any object have an infinite distance.
+    pub depth: Vec<f32>,
+    /// The normalized world space normal of the surface of each pixel. Pixels without any object have a zero normal.
+    pub normals: Vec<Vec3>,
+    /// The ID of the object in each pixel, which is the index of the object in the list of objects given to [RenderLayers::render] plus one.
+    /// Pixels without any object have the ID 0.
+    pub object_ids: Vec<u32>,
+}
+
+impl RenderLayers {
+    ///
+    /// Renders the given objects with the given camera and lights into the beauty layer and the auxiliary layers.
+    /// The size of the layers is given by the viewport of the camera.
+    ///
+    pub fn render(
+        context: &Context,
+        camera: &Camera,
+        objects: &[&dyn Object],
+        lights: &[&dyn Light],
+    ) -> Self {
+        let viewport = camera.viewport();
+        let (width, height) = (viewport.width, viewport.height);
+        let mut camera = camera.clone();
+        camera.set_viewport(Viewport::new_at_origo(width, height));
+        let mut depth_texture = DepthTexture2D::new::<f32>(
+            context,
+            width,
+            height,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+
+        // Beauty
+        let mut beauty_texture = Texture2D::new_empty::<[u8; 4]>(
+            context,
+            width,
+            height,
+            Interpolation::Nearest,
+            Interpolation::Nearest,
+            None,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+        let beauty = RenderTarget::new(
+            beauty_texture.as_color_target(None),
+            depth_texture.as_depth_target(),
+        )
+        .clear(ClearState::color_and_depth(0.0, 0.0, 0.0, 0.0, 1.0))
+        .render(&camera, objects, lights)
+        .read_color::<[u8; 4]>();
+
+        // Normals, depth and object IDs
+        let mut layers_texture = Texture2DArray::new_empty::<[f32; 4]>(
+            context,
+            width,
+            height,
+            2,
+            Interpolation::Nearest,
+            Interpolation::Nearest,
+            None,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+        RenderTarget::new(
+            layers_texture.as_color_target(&[0, 1], None),
+            depth_texture.as_depth_target(),
+        )
+        .clear(ClearState::color_and_depth(0.0, 0.0, 0.0, 0.0, 1.0))
+        .write(|| {
+            for (index, object) in objects
+                .iter()
+                .enumerate()
+                .filter(|(_, o)| camera.in_frustum(&o.aabb()))
+            {
+                object.render_with_material(
+                    &LayersMaterial {
+                        object_id: index as u32 + 1,
+                    },
+                    &camera,
+                    lights,
+                );
+            }
+        });
+        let object_ids: Vec<u32> = layers_texture
+            .as_color_target(&[1], None)
+            .read::<[f32; 4]>()
+            .into_iter()
+            .map(|p| p[0].round() as u32)
+            .collect();
+        let normal_and_depth = layers_texture
+            .as_color_target(&[0], None)
+            .read::<[f32; 4]>();
+        let (normals, depth) = normal_and_depth
+            .into_iter()
+            .zip(object_ids.iter())
+            .map(|(p, id)| {
+                if *id == 0 {
+                    (vec3(0.0, 0.0, 0.0), f32::INFINITY)
+                } else {
+                    (vec3(p[0], p[1], p[2]), p[3])
+                }
+            })
+            .unzip();
+
+        Self {
+            width,
+            height,
+            beauty,
+            depth,
+            normals,
+            object_ids,
+        }
+    }
+
+    ///
+    /// Returns the beauty layer as an 8-bit RGBA image.
+    ///
+    pub fn beauty_image(&self) -> CpuTexture {
+        self.image("beauty", TextureData::RgbaU8(self.beauty.clone()))
+    }
+
+    ///
+    /// Returns the depth layer as a single channel 32-bit floating point image.
+    ///
+    pub fn depth_image(&self) -> CpuTexture {
+        self.image("depth", TextureData::RF32(self.depth.clone()))
+    }
+
+    ///
+    /// Returns the normal layer as a three channel 32-bit floating point image where each channel is in the range `[-1, 1]`.
+    ///
+    pub fn normal_image(&self) -> CpuTexture {
+        self.image(
+            "normal",
+            TextureData::RgbF32(self.normals.iter().map(|n| [n.x, n.y, n.z]).collect()),
+        )
+    }
+
+    fn image(&self, name: &str, data: TextureData) -> CpuTexture {
+        CpuTexture {
+            name: name.to_string(),
+            data,
+            width: self.width,
+            height: self.height,
+            ..Default::default()
+        }
+    }
+
+    ///
+    /// Saves the layers in the given directory as `{name}.png` (the beauty layer), `{name}.depth.exr`, `{name}.normal.exr` and `{name}.id.png` (the object IDs as 16-bit grayscale).
+    /// The directory is created if it does not exist.
+    ///
+    #[cfg(all(feature = "render-export", not(target_arch = "wasm32")))]
+    #[cfg_attr(docsrs, doc(feature = "render-export"))]
+    pub fn save(
+        &self,
+        directory: impl AsRef<std::path::Path>,
+        name: &str,
+    ) -> Result<(), RendererError> {
+        let directory = directory.as_ref();
+        std::fs::create_dir_all(directory)?;
+        image::save_buffer(
+            directory.join(format!("{}.png", name)),
+            &self.beauty.iter().flatten().copied().collect::<Vec<_>>(),
+            self.width,
+            self.height,
+            image::ColorType::Rgba8,
+        )?;
+        image::Rgb32FImage::from_raw(
+            self.width,
+            self.height,
+            self.depth.iter().flat_map(|d| [*d, *d, *d]).collect(),
+        )
+        .expect("the depth layer has the size of the image")
+        .save(directory.join(format!("{}.depth.exr", name)))?;
+        image::Rgb32FImage::from_raw(
+            self.width,
+            self.height,
+            self.normals.iter().flat_map(|n| [n.x, n.y, n.z]).collect(),
+        )
+        .expect("the normal layer has the size of the image")
+        .save(directory.join(format!("{}.normal.exr", name)))?;
+        image::ImageBuffer::<image::Luma<u16>, _>::from_raw(
+            self.width,
+            self.height,
+            self.object_ids
+                .iter()
+                .map(|id| (*id).min(u16::MAX as u32) as u16)
+                .collect::<Vec<_>>(),
+        )
+        .expect("the object ID layer has the size of the image")
+        .save(directory.join(format!("{}.id.png", name)))?;
+        Ok(())
+    }
+}
+
+struct LayersMaterial {
+    object_id: u32,
+}
+
+impl Material for LayersMaterial {
+    fn fragment_shader_source(&self, _lights: &[&dyn Light]) -> String {
+        "
+        uniform vec3 eye;
+        uniform float objectId;
+
+        in vec3 pos;
+        in vec3 nor;
+
+        layout (location = 0) out vec4 outNormalAndDepth;
+        layout (location = 1) out vec4 outObjectId;
+
+        void main()
+        {
+            vec3 normal = normalize(gl_FrontFacing ? nor : -nor);
+            outNormalAndDepth = vec4(normal, distance(pos, eye));
+            outObjectId = vec4(objectId, 0.0, 0.0, 1.0);
+        }"
+        .to_string()
+    }
+
+    fn id(&self) -> u16 {
+        0b1u16 << 15 | 0b1001u16
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            position: true,
+            normal: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(&self, program: &Program, camera: &Camera, _lights: &[&dyn Light]) {
+        program.use_uniform("eye", camera.position());
+        program.use_uniform("objectId", self.object_id as f32);
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates::default()
+    }
+
+    fn material_type(&self) -> MaterialType {
+        MaterialType::Opaque
+    }
+}
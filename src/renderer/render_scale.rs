@@ -0,0 +1,153 @@
+use crate::renderer::*;
+
+///
+/// Renders the scene into an offscreen render target with a resolution which is scaled down compared to the viewport of the camera
+/// and then scales the result up to the render target, typically the screen, using an [UpsampleEffect].
+/// Since the cost of rendering is roughly proportional to the number of pixels, this is an effective way to keep a stable frame rate on weak GPUs,
+/// for example integrated GPUs and mobile devices on web.
+///
+/// The scene is rendered into a texture with the [IntermediateFormat] of the context without tone and color mapping,
+/// and the tone and color mapping of the camera is applied when scaling up, so the lighting is kept in high dynamic range until the final write.
+///
+/// The scale can be set manually using [RenderScale::set_scale] or adjusted automatically based on the frame time using [RenderScale::update].
+///
+pub struct RenderScale {
+    context: Context,
+    color_texture: Texture2D,
+    depth_texture: DepthTexture2D,
+    scale: f32,
+    average_frame_time: Option<f64>,
+    frames_since_change: u32,
+    /// The minimum scale used when adjusting the scale automatically, see [RenderScale::update].
+    pub min_scale: f32,
+    /// The maximum scale used when adjusting the scale automatically, see [RenderScale::update].
+    pub max_scale: f32,
+    /// The desired frame time in milliseconds used when adjusting the scale automatically, see [RenderScale::update].
+    pub target_frame_time: f64,
+    /// The filter used when scaling the low resolution image up to the resolution of the render target.
+    pub filter: UpsampleFilter,
+}
+
+impl RenderScale {
+    ///
+    /// Constructs a new render scale with a scale of 1, ie. full resolution, which is adjusted between 0.5 and 1 to reach 60 frames per second when calling [RenderScale::update].
+    ///
+    pub fn new(context: &Context) -> Self {
+        Self {
+            context: context.clone(),
+            color_texture: new_color_texture(context, 1, 1),
+            depth_texture: new_depth_texture(context, 1, 1),
+            scale: 1.0,
+            average_frame_time: None,
+            frames_since_change: 0,
+            min_scale: 0.5,
+            max_scale: 1.0,
+            target_frame_time: 1000.0 / 60.0,
+            filter: UpsampleFilter::default(),
+        }
+    }
+
+    ///
+    /// Returns the current scale, ie. the resolution of the offscreen render target relative to the viewport of the camera.
+    ///
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    ///
+    /// Sets the scale, ie. the resolution of the offscreen render target relative to the viewport of the camera.
+    /// The scale is clamped to the range `(0, 1]`.
+    ///
+    pub fn set_scale(&mut self, scale: f32) {
+        self.scale = scale.clamp(0.01, 1.0);
+    }
+
+    ///
+    /// Adjusts the scale based on the given frame time in milliseconds, for example [FrameInput::elapsed_time](crate::FrameInput::elapsed_time),
+    /// so the average frame time approaches the target frame time. Returns the new scale.
+    /// Should be called once each frame.
+    ///
+    pub fn update(&mut self, frame_time: f64) -> f32 {
+        let average_frame_time = match self.average_frame_time {
+            Some(average) => average + 0.1 * (frame_time - average),
+            None => frame_time,
+        };
+        self.average_frame_time = Some(average_frame_time);
+        self.frames_since_change += 1;
+
+        // Wait for the average frame time to reflect the last change before changing again
+        if self.frames_since_change >= 10 {
+            let scale = if average_frame_time > 1.1 * self.target_frame_time {
+                // The cost is roughly proportional to the number of pixels, ie. the square of the scale
+                self.scale * ((self.target_frame_time / average_frame_time).sqrt() as f32).max(0.8)
+            } else if average_frame_time < 0.8 * self.target_frame_time {
+                self.scale + 0.05
+            } else {
+                self.scale
+            };
+            let scale = scale.clamp(self.min_scale, self.max_scale);
+            if scale != self.scale {
+                self.set_scale(scale);
+                self.frames_since_change = 0;
+            }
+        }
+        self.scale
+    }
+
+    ///
+    /// Clears an offscreen render target with a resolution given by the viewport of the camera and the current scale using the given clear state.
+    /// Then calls the `render` closure with the offscreen render target and a copy of the camera which has a viewport that covers the offscreen render target
+    /// and where the tone and color mapping is disabled.
+    /// Finally, the result is scaled up into the viewport of the camera in the given render target with the tone and color mapping of the camera.
+    /// Only the color is written to the render target, not the depth.
+    ///
+    pub fn render(
+        &mut self,
+        target: &RenderTarget,
+        camera: &Camera,
+        clear_state: ClearState,
+        render: impl FnOnce(&RenderTarget, &Camera),
+    ) {
+        let viewport = camera.viewport();
+        let width = ((viewport.width as f32 * self.scale).round() as u32).max(1);
+        let height = ((viewport.height as f32 * self.scale).round() as u32).max(1);
+        if self.color_texture.width() != width || self.color_texture.height() != height {
+            self.color_texture = new_color_texture(&self.context, width, height);
+            self.depth_texture = new_depth_texture(&self.context, width, height);
+        }
+        let mut scaled_camera = camera.clone();
+        scaled_camera.set_viewport(Viewport::new_at_origo(width, height));
+        scaled_camera.disable_tone_and_color_mapping();
+
+        let offscreen_target = RenderTarget::new(
+            self.color_texture.as_color_target(None),
+            self.depth_texture.as_depth_target(),
+        );
+        offscreen_target.clear(clear_state);
+        render(&offscreen_target, &scaled_camera);
+
+        target.apply_screen_effect(
+            &UpsampleEffect {
+                filter: self.filter,
+            },
+            camera,
+            &[],
+            Some(ColorTexture::Single(&self.color_texture)),
+            None,
+        );
+    }
+}
+
+fn new_color_texture(context: &Context, width: u32, height: u32) -> Texture2D {
+    new_intermediate_texture(context, width, height, Interpolation::Linear)
+}
+
+fn new_depth_texture(context: &Context, width: u32, height: u32) -> DepthTexture2D {
+    DepthTexture2D::new::<f32>(
+        context,
+        width,
+        height,
+        Wrapping::ClampToEdge,
+        Wrapping::ClampToEdge,
+    )
+}
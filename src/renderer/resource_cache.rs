@@ -0,0 +1,234 @@
+use crate::core::*;
+use crate::renderer::geometry::BaseMesh;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Weak};
+
+// A cache of the textures and meshes created when loading assets, keyed by a hash of the content,
+// so assets which are referenced multiple times share the resources on the GPU.
+// The content is stored together with a weak reference to the resource, so a hash collision never returns a resource with different content
+// and the resources are dropped when they are no longer used.
+pub(crate) struct ResourceCache {
+    enabled: bool,
+    textures: HashMap<u64, Vec<CacheEntry<Texture2D>>>,
+    meshes: HashMap<u64, Vec<CacheEntry<BaseMesh>>>,
+}
+
+struct CacheEntry<T> {
+    content: Vec<u8>,
+    resource: Weak<T>,
+}
+
+impl Default for ResourceCache {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            textures: HashMap::new(),
+            meshes: HashMap::new(),
+        }
+    }
+}
+
+impl ResourceCache {
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.textures.clear();
+            self.meshes.clear();
+        }
+    }
+}
+
+// Returns a texture with the given content, which is shared with other textures with the same content if resource sharing is enabled
+pub(crate) fn shared_texture(context: &Context, cpu_texture: &CpuTexture) -> Arc<Texture2D> {
+    let mut cache = context.resource_cache.write().unwrap();
+    if !cache.enabled {
+        return Arc::new(Texture2D::new(context, cpu_texture));
+    }
+    find_or_insert(&mut cache.textures, texture_content(cpu_texture), || {
+        Texture2D::new(context, cpu_texture)
+    })
+}
+
+// Returns mesh data with the given content, which is shared with other meshes with the same content if resource sharing is enabled
+pub(crate) fn shared_mesh(
+    context: &Context,
+    cpu_mesh: &CpuMesh,
+    uvs1: Option<&[Vec2]>,
+) -> Arc<BaseMesh> {
+    let mut cache = context.resource_cache.write().unwrap();
+    if !cache.enabled {
+        return Arc::new(BaseMesh::new_with_uvs1(context, cpu_mesh, uvs1));
+    }
+    find_or_insert(&mut cache.meshes, mesh_content(cpu_mesh, uvs1), || {
+        BaseMesh::new_with_uvs1(context, cpu_mesh, uvs1)
+    })
+}
+
+// Removes the given mesh data from the cache, so it is no longer shared with meshes created afterwards, for example because the data is about to change
+pub(crate) fn unshare_mesh(context: &Context, mesh: &Arc<BaseMesh>) {
+    context
+        .resource_cache
+        .write()
+        .unwrap()
+        .meshes
+        .values_mut()
+        .for_each(|entries| entries.retain(|e| e.resource.as_ptr() != Arc::as_ptr(mesh)));
+}
+
+fn find_or_insert<T>(
+    entries: &mut HashMap<u64, Vec<CacheEntry<T>>>,
+    content: Vec<u8>,
+    create: impl FnOnce() -> T,
+) -> Arc<T> {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    content.hash(&mut hasher);
+    let key = hasher.finish();
+    if let Some(resource) = entries.get(&key).and_then(|entries| {
+        entries
+            .iter()
+            .filter(|e| e.content == content)
+            .find_map(|e| e.resource.upgrade())
+    }) {
+        return resource;
+    }
+    let resource = Arc::new(create());
+    entries.retain(|_, entries| {
+        entries.retain(|e| e.resource.strong_count() > 0);
+        !entries.is_empty()
+    });
+    entries.entry(key).or_default().push(CacheEntry {
+        content,
+        resource: Arc::downgrade(&resource),
+    });
+    resource
+}
+
+// Returns the bytes of everything in the texture which affects the texture on the GPU
+fn texture_content(cpu_texture: &CpuTexture) -> Vec<u8> {
+    let mut content = Vec::new();
+    content.extend(cpu_texture.width.to_le_bytes());
+    content.extend(cpu_texture.height.to_le_bytes());
+    content.extend(
+        format!(
+            "{:?}{:?}{:?}{:?}{:?}",
+            cpu_texture.min_filter,
+            cpu_texture.mag_filter,
+            cpu_texture.mipmap,
+            cpu_texture.wrap_s,
+            cpu_texture.wrap_t
+        )
+        .bytes(),
+    );
+    match &cpu_texture.data {
+        TextureData::RU8(data) => {
+            content.push(0);
+            content.extend(data.iter());
+        }
+        TextureData::RgU8(data) => {
+            content.push(1);
+            content.extend(data.iter().flatten());
+        }
+        TextureData::RgbU8(data) => {
+            content.push(2);
+            content.extend(data.iter().flatten());
+        }
+        TextureData::RgbaU8(data) => {
+            content.push(3);
+            content.extend(data.iter().flatten());
+        }
+        TextureData::RF16(data) => {
+            content.push(4);
+            data.iter()
+                .for_each(|v| content.extend(v.to_bits().to_le_bytes()));
+        }
+        TextureData::RgF16(data) => {
+            content.push(5);
+            data.iter()
+                .flatten()
+                .for_each(|v| content.extend(v.to_bits().to_le_bytes()));
+        }
+        TextureData::RgbF16(data) => {
+            content.push(6);
+            data.iter()
+                .flatten()
+                .for_each(|v| content.extend(v.to_bits().to_le_bytes()));
+        }
+        TextureData::RgbaF16(data) => {
+            content.push(7);
+            data.iter()
+                .flatten()
+                .for_each(|v| content.extend(v.to_bits().to_le_bytes()));
+        }
+        TextureData::RF32(data) => {
+            content.push(8);
+            extend_floats(&mut content, data.iter().copied());
+        }
+        TextureData::RgF32(data) => {
+            content.push(9);
+            extend_floats(&mut content, data.iter().flatten().copied());
+        }
+        TextureData::RgbF32(data) => {
+            content.push(10);
+            extend_floats(&mut content, data.iter().flatten().copied());
+        }
+        TextureData::RgbaF32(data) => {
+            content.push(11);
+            extend_floats(&mut content, data.iter().flatten().copied());
+        }
+    }
+    content
+}
+
+// Returns the bytes of everything in the mesh which is transferred to the GPU
+fn mesh_content(cpu_mesh: &CpuMesh, uvs1: Option<&[Vec2]>) -> Vec<u8> {
+    let mut content = Vec::new();
+    let positions = cpu_mesh.positions.to_f32();
+    content.extend((positions.len() as u64).to_le_bytes());
+    extend_floats(&mut content, positions.iter().flat_map(|p| [p.x, p.y, p.z]));
+    match &cpu_mesh.indices {
+        Indices::U8(indices) => {
+            content.push(0);
+            content.extend(indices.iter());
+        }
+        Indices::U16(indices) => {
+            content.push(1);
+            indices.iter().for_each(|i| content.extend(i.to_le_bytes()));
+        }
+        Indices::U32(indices) => {
+            content.push(2);
+            indices.iter().for_each(|i| content.extend(i.to_le_bytes()));
+        }
+        Indices::None => content.push(3),
+    }
+    content.push(cpu_mesh.normals.is_some() as u8);
+    if let Some(normals) = &cpu_mesh.normals {
+        extend_floats(&mut content, normals.iter().flat_map(|n| [n.x, n.y, n.z]));
+    }
+    content.push(cpu_mesh.tangents.is_some() as u8);
+    if let Some(tangents) = &cpu_mesh.tangents {
+        extend_floats(
+            &mut content,
+            tangents.iter().flat_map(|t| [t.x, t.y, t.z, t.w]),
+        );
+    }
+    content.push(cpu_mesh.uvs.is_some() as u8);
+    if let Some(uvs) = &cpu_mesh.uvs {
+        extend_floats(&mut content, uvs.iter().flat_map(|uv| [uv.x, uv.y]));
+    }
+    content.push(cpu_mesh.colors.is_some() as u8);
+    if let Some(colors) = &cpu_mesh.colors {
+        colors
+            .iter()
+            .for_each(|c| content.extend([c.r, c.g, c.b, c.a]));
+    }
+    content.push(uvs1.is_some() as u8);
+    if let Some(uvs1) = uvs1 {
+        extend_floats(&mut content, uvs1.iter().flat_map(|uv| [uv.x, uv.y]));
+    }
+    content
+}
+
+fn extend_floats(content: &mut Vec<u8>, values: impl Iterator<Item = f32>) {
+    values.for_each(|v| content.extend(v.to_bits().to_le_bytes()));
+}
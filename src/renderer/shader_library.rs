@@ -0,0 +1,135 @@
+use crate::renderer::*;
+use std::collections::{HashMap, HashSet};
+
+///
+/// A shader preprocessor which resolves `#include <name>` and `#include "name"` directives, so shader source can be shared between shaders
+/// instead of being copied into each shader.
+///
+/// An include is resolved by first looking for a module with the given name which has been added using [ShaderLibrary::add_module]
+/// and then, if an include directory is set using [ShaderLibrary::set_include_directory], by reading the file with the given name relative to that directory.
+/// Each module is only included once per shader, even if it is included multiple times, and the included modules can include other modules.
+///
+/// The default library contains the following modules from this crate:
+/// - `three-d/shared` - common constants and functions, for example `PI` and `saturate`.
+/// - `three-d/noise` - hash, value noise and fractal brownian motion functions.
+/// - `three-d/lighting` - the functions used for lighting calculations, requires that either `PHONG`, `BLINN` or `COOK` is defined before the include (see also [lights_shader_source]).
+/// - `three-d/tone_mapping` - the `tone_mapping` function (see [ToneMapping]), use [Camera::use_tone_and_color_mapping_uniforms] to set the uniforms including the exposure.
+/// - `three-d/color_mapping` - the `color_mapping` function (see [ColorMapping]).
+///
+#[derive(Clone, Debug)]
+pub struct ShaderLibrary {
+    modules: HashMap<String, String>,
+    #[cfg(not(target_arch = "wasm32"))]
+    include_directory: Option<std::path::PathBuf>,
+}
+
+impl ShaderLibrary {
+    ///
+    /// Creates a new shader library without any modules.
+    /// Use [ShaderLibrary::default] to create a library which contains the modules from this crate.
+    ///
+    pub fn new() -> Self {
+        Self {
+            modules: HashMap::new(),
+            #[cfg(not(target_arch = "wasm32"))]
+            include_directory: None,
+        }
+    }
+
+    ///
+    /// Adds a module with the given name and shader source which can then be included using `#include <name>`.
+    /// If a module with the same name already exists, it is replaced.
+    ///
+    pub fn add_module(&mut self, name: impl Into<String>, source: impl Into<String>) {
+        self.modules.insert(name.into(), source.into());
+    }
+
+    ///
+    /// Returns true if this library contains a module with the given name.
+    ///
+    pub fn has_module(&self, name: &str) -> bool {
+        self.modules.contains_key(name)
+    }
+
+    ///
+    /// Sets the directory used for resolving includes that does not match any of the modules in this library.
+    ///
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn set_include_directory(&mut self, path: impl AsRef<std::path::Path>) {
+        self.include_directory = Some(path.as_ref().to_path_buf());
+    }
+
+    ///
+    /// Returns the given shader source where all include directives are replaced by the source of the included modules.
+    ///
+    pub fn preprocess(&self, source: &str) -> Result<String, RendererError> {
+        let mut output = String::with_capacity(source.len());
+        self.preprocess_internal(source, &mut HashSet::new(), &mut output)?;
+        Ok(output)
+    }
+
+    fn preprocess_internal(
+        &self,
+        source: &str,
+        included: &mut HashSet<String>,
+        output: &mut String,
+    ) -> Result<(), RendererError> {
+        for line in source.lines() {
+            if let Some(name) = include_name(line) {
+                if included.insert(name.to_string()) {
+                    let module = self.resolve(name)?;
+                    self.preprocess_internal(&module, included, output)?;
+                }
+            } else {
+                output.push_str(line);
+                output.push('\n');
+            }
+        }
+        Ok(())
+    }
+
+    fn resolve(&self, name: &str) -> Result<String, RendererError> {
+        if let Some(source) = self.modules.get(name) {
+            return Ok(source.clone());
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(ref directory) = self.include_directory {
+            if let Ok(source) = std::fs::read_to_string(directory.join(name)) {
+                return Ok(source);
+            }
+        }
+        Err(RendererError::UnresolvedShaderInclude(name.to_string()))
+    }
+}
+
+impl Default for ShaderLibrary {
+    fn default() -> Self {
+        let mut library = Self::new();
+        library.add_module("three-d/shared", include_str!("../core/shared.frag"));
+        library.add_module("three-d/noise", include_str!("shaders/noise.frag"));
+        library.add_module(
+            "three-d/lighting",
+            format!(
+                "#include <three-d/shared>\n{}",
+                include_str!("light/shaders/light_shared.frag")
+            ),
+        );
+        library.add_module(
+            "three-d/tone_mapping",
+            ToneMapping::fragment_shader_source(),
+        );
+        library.add_module(
+            "three-d/color_mapping",
+            ColorMapping::fragment_shader_source(),
+        );
+        library
+    }
+}
+
+fn include_name(line: &str) -> Option<&str> {
+    let rest = line.trim().strip_prefix("#include")?.trim();
+    rest.strip_prefix('<')
+        .and_then(|r| r.strip_suffix('>'))
+        .or_else(|| rest.strip_prefix('"').and_then(|r| r.strip_suffix('"')))
+        .map(|name| name.trim())
+}
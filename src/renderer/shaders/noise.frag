@@ -0,0 +1,74 @@
+// Hash and noise functions which are useful for procedural textures and effects
+
+float hash12(vec2 p)
+{
+    vec3 p3 = fract(vec3(p.xyx) * 0.1031);
+    p3 += dot(p3, p3.yzx + 33.33);
+    return fract((p3.x + p3.y) * p3.z);
+}
+
+vec2 hash22(vec2 p)
+{
+    vec3 p3 = fract(vec3(p.xyx) * vec3(0.1031, 0.1030, 0.0973));
+    p3 += dot(p3, p3.yzx + 33.33);
+    return fract((p3.xx + p3.yz) * p3.zy);
+}
+
+float hash13(vec3 p)
+{
+    p = fract(p * 0.1031);
+    p += dot(p, p.zyx + 31.32);
+    return fract((p.x + p.y) * p.z);
+}
+
+// Value noise in the range [0..1]
+float value_noise(vec2 p)
+{
+    vec2 i = floor(p);
+    vec2 f = fract(p);
+    vec2 u = f * f * (3.0 - 2.0 * f);
+    return mix(mix(hash12(i), hash12(i + vec2(1.0, 0.0)), u.x),
+               mix(hash12(i + vec2(0.0, 1.0)), hash12(i + vec2(1.0, 1.0)), u.x), u.y);
+}
+
+// Value noise in the range [0..1]
+float value_noise(vec3 p)
+{
+    vec3 i = floor(p);
+    vec3 f = fract(p);
+    vec3 u = f * f * (3.0 - 2.0 * f);
+    return mix(mix(mix(hash13(i), hash13(i + vec3(1.0, 0.0, 0.0)), u.x),
+                   mix(hash13(i + vec3(0.0, 1.0, 0.0)), hash13(i + vec3(1.0, 1.0, 0.0)), u.x), u.y),
+               mix(mix(hash13(i + vec3(0.0, 0.0, 1.0)), hash13(i + vec3(1.0, 0.0, 1.0)), u.x),
+                   mix(hash13(i + vec3(0.0, 1.0, 1.0)), hash13(i + vec3(1.0, 1.0, 1.0)), u.x), u.y), u.z);
+}
+
+// Fractal brownian motion of value noise in the range [0..1]
+float fbm(vec2 p, int octaves)
+{
+    float value = 0.0;
+    float amplitude = 0.5;
+    float total = 0.0;
+    for (int i = 0; i < octaves; i++) {
+        value += amplitude * value_noise(p);
+        total += amplitude;
+        p *= 2.0;
+        amplitude *= 0.5;
+    }
+    return value / total;
+}
+
+// Fractal brownian motion of value noise in the range [0..1]
+float fbm(vec3 p, int octaves)
+{
+    float value = 0.0;
+    float amplitude = 0.5;
+    float total = 0.0;
+    for (int i = 0; i < octaves; i++) {
+        value += amplitude * value_noise(p);
+        total += amplitude;
+        p *= 2.0;
+        amplitude *= 0.5;
+    }
+    return value / total;
+}
@@ -0,0 +1,13 @@
+
+layout (location = 0) out vec4 accumulation;
+layout (location = 1) out vec4 weight;
+
+void main()
+{
+    material_main();
+    float alpha = clamp(outColor.a, 0.0, 1.0);
+    // Weight function (eq. 10) from the paper which favors surfaces close to the camera
+    float w = clamp(pow(min(1.0, alpha * 10.0) + 0.01, 3.0) * 1e8 * pow(1.0 - gl_FragCoord.z * 0.9, 3.0), 1e-2, 3e3);
+    accumulation = vec4(outColor.rgb * alpha * w, alpha);
+    weight = vec4(alpha * w, 0.0, 0.0, alpha);
+}
@@ -0,0 +1,56 @@
+
+uniform sampler2D historyMap;
+uniform sampler2D velocityMap;
+uniform mat4 viewProjectionInverse;
+uniform mat4 previousViewProjection;
+uniform vec2 resolution;
+uniform vec2 sourceResolution;
+uniform vec2 jitterIndex;
+uniform float historyWeight;
+
+in vec2 uvs;
+
+layout (location = 0) out vec4 outColor;
+
+void main()
+{
+    vec4 current = sample_color(uvs);
+
+    // The pixel in the 2x2 block of full resolution pixels which was sampled in this frame
+    vec2 blockIndex = mod(floor(uvs * resolution), 2.0);
+    if (all(equal(blockIndex, jitterIndex)) || historyWeight == 0.0) {
+        outColor = current;
+        return;
+    }
+
+    // Reproject into the previous frame using the velocity buffer if a moving object was rendered into it,
+    // otherwise using the depth, which only accounts for the movement of the camera
+    vec4 velocity = texture(velocityMap, uvs);
+    vec2 previousUv;
+    if (velocity.a > 0.5) {
+        previousUv = velocity.xy;
+    } else {
+        float depth = sample_depth(uvs);
+        vec4 worldPosition = viewProjectionInverse * vec4(2.0 * vec3(uvs, depth) - 1.0, 1.0);
+        vec4 previousClipPosition = previousViewProjection * vec4(worldPosition.xyz / worldPosition.w, 1.0);
+        previousUv = 0.5 * previousClipPosition.xy / previousClipPosition.w + 0.5;
+    }
+    if (any(lessThan(previousUv, vec2(0.0))) || any(greaterThan(previousUv, vec2(1.0)))) {
+        outColor = current;
+        return;
+    }
+
+    // Clamp the history to the colors in the neighbourhood to reject history which is no longer visible
+    vec2 texel = 1.0 / sourceResolution;
+    vec4 minColor = current;
+    vec4 maxColor = current;
+    for (int x = -1; x <= 1; x++) {
+        for (int y = -1; y <= 1; y++) {
+            vec4 neighbour = sample_color(uvs + vec2(float(x), float(y)) * texel);
+            minColor = min(minColor, neighbour);
+            maxColor = max(maxColor, neighbour);
+        }
+    }
+    vec4 history = clamp(texture(historyMap, previousUv), minColor, maxColor);
+    outColor = mix(current, history, historyWeight);
+}
@@ -0,0 +1,13 @@
+
+uniform mat4 motion;
+uniform mat4 previousViewProjection;
+
+in vec3 pos;
+
+layout (location = 0) out vec4 outColor;
+
+void main()
+{
+    vec4 previousClipPosition = previousViewProjection * motion * vec4(pos, 1.0);
+    outColor = vec4(0.5 * previousClipPosition.xy / previousClipPosition.w + 0.5, 0.0, 1.0);
+}
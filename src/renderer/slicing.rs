@@ -0,0 +1,178 @@
+use crate::renderer::*;
+
+///
+/// A stack of binary images, each describing which parts of a plane parallel to the xz-plane is inside the sliced geometries, see [render_slices].
+///
+#[derive(Clone, Debug)]
+pub struct Slices {
+    /// The width of each slice in pixels, ie. along the x-axis.
+    pub width: u32,
+    /// The height of each slice in pixels, ie. along the z-axis.
+    pub height: u32,
+    /// The size of a pixel in world units.
+    pub pixel_size: f32,
+    /// The distance in world units between two slices.
+    pub layer_height: f32,
+    /// The world position of the corner of the slices with the minimum coordinates, ie. the left edge of the top left pixel of the first slice.
+    pub origin: Vec3,
+    /// The slices ordered from the bottom to the top. Each slice contains `width * height` values, which are true if the pixel is inside the geometries.
+    /// The pixels are stored row by row starting with the top left pixel which is at the minimum x and z coordinates.
+    pub layers: Vec<Vec<bool>>,
+}
+
+impl Slices {
+    ///
+    /// Returns the height (y-coordinate) in world space of the slice with the given index.
+    ///
+    pub fn layer_position(&self, index: usize) -> f32 {
+        self.origin.y + (index as f32 + 0.5) * self.layer_height
+    }
+
+    ///
+    /// Returns the slice with the given index as a single channel 8-bit image where pixels inside the geometries are white and all other pixels are black.
+    ///
+    pub fn layer_image(&self, index: usize) -> CpuTexture {
+        CpuTexture {
+            name: format!("slice{}", index),
+            data: TextureData::RU8(
+                self.layers[index]
+                    .iter()
+                    .map(|inside| if *inside { 255 } else { 0 })
+                    .collect(),
+            ),
+            width: self.width,
+            height: self.height,
+            ..Default::default()
+        }
+    }
+}
+
+///
+/// Slices the given geometries with planes parallel to the xz-plane, ie. the y-axis is the build direction, and returns a stack of binary images with one image for each layer,
+/// for example to preview the layers of a 3D print or a CNC job.
+/// The slices are placed in the middle of each layer of the given height starting from the bottom of the bounding box of the geometries,
+/// and the size of a pixel in each slice is given by the pixel size in world units.
+///
+/// Each slice is rendered using an orthographic camera looking down with the near plane at the slice, so a pixel is inside the geometries if the closest surface below the slice faces away from the camera.
+/// **Note:** This requires that the geometries are closed and that the triangles are ordered counter clockwise when seen from outside.
+///
+pub fn render_slices(
+    context: &Context,
+    geometries: &[&dyn Geometry],
+    layer_height: f32,
+    pixel_size: f32,
+) -> Slices {
+    let mut aabb = AxisAlignedBoundingBox::EMPTY;
+    geometries
+        .iter()
+        .for_each(|g| aabb.expand_with_aabb(&g.aabb()));
+    if aabb.is_empty() {
+        return Slices {
+            width: 0,
+            height: 0,
+            pixel_size,
+            layer_height,
+            origin: vec3(0.0, 0.0, 0.0),
+            layers: Vec::new(),
+        };
+    }
+    let size = aabb.size();
+    let width = ((size.x / pixel_size).ceil() as u32).max(1);
+    let height = ((size.z / pixel_size).ceil() as u32).max(1);
+    let layer_count = ((size.y / layer_height).ceil() as usize).max(1);
+    let origin = aabb.min();
+    let center = vec3(
+        origin.x + 0.5 * width as f32 * pixel_size,
+        0.0,
+        origin.z + 0.5 * height as f32 * pixel_size,
+    );
+
+    let mut texture = Texture2D::new_empty::<[u8; 4]>(
+        context,
+        width,
+        height,
+        Interpolation::Nearest,
+        Interpolation::Nearest,
+        None,
+        Wrapping::ClampToEdge,
+        Wrapping::ClampToEdge,
+    );
+    let mut depth_texture = DepthTexture2D::new::<f32>(
+        context,
+        width,
+        height,
+        Wrapping::ClampToEdge,
+        Wrapping::ClampToEdge,
+    );
+    let mut layers = Vec::with_capacity(layer_count);
+    for index in 0..layer_count {
+        let y = origin.y + (index as f32 + 0.5) * layer_height;
+        let position = vec3(center.x, y, center.z);
+        let camera = Camera::new_orthographic(
+            Viewport::new_at_origo(width, height),
+            position,
+            position - vec3(0.0, 1.0, 0.0),
+            vec3(0.0, 0.0, -1.0),
+            height as f32 * pixel_size,
+            0.0,
+            y - origin.y + pixel_size,
+        );
+        let data = RenderTarget::new(
+            texture.as_color_target(None),
+            depth_texture.as_depth_target(),
+        )
+        .clear(ClearState::color_and_depth(0.0, 0.0, 0.0, 1.0, 1.0))
+        .write(|| {
+            for geometry in geometries {
+                geometry.render_with_material(&SliceMaterial, &camera, &[]);
+            }
+        })
+        .read_color::<[u8; 4]>();
+        layers.push(data.into_iter().map(|p| p[0] > 127).collect());
+    }
+
+    Slices {
+        width,
+        height,
+        pixel_size,
+        layer_height,
+        origin,
+        layers,
+    }
+}
+
+struct SliceMaterial;
+
+impl Material for SliceMaterial {
+    fn fragment_shader_source(&self, _lights: &[&dyn Light]) -> String {
+        "
+        layout (location = 0) out vec4 outColor;
+
+        void main()
+        {
+            outColor = gl_FrontFacing ? vec4(0.0, 0.0, 0.0, 1.0) : vec4(1.0, 1.0, 1.0, 1.0);
+        }"
+        .to_string()
+    }
+
+    fn id(&self) -> u16 {
+        0b1u16 << 15 | 0b1010u16
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes::NONE
+    }
+
+    fn use_uniforms(&self, _program: &Program, _camera: &Camera, _lights: &[&dyn Light]) {}
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates {
+            cull: Cull::None,
+            ..Default::default()
+        }
+    }
+
+    fn material_type(&self) -> MaterialType {
+        MaterialType::Opaque
+    }
+}
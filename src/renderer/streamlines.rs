@@ -0,0 +1,378 @@
+use crate::renderer::*;
+
+///
+/// A 3D vector field, for example the velocity field of a fluid simulation, which can be traced using [trace_streamlines] and [trace_pathlines].
+///
+pub trait VectorField {
+    ///
+    /// Returns the vector at the given position and time or `None` if the position is outside the field.
+    /// Fields which do not change over time can ignore the time.
+    ///
+    fn sample(&self, position: Vec3, time: f32) -> Option<Vec3>;
+}
+
+impl<F: Fn(Vec3, f32) -> Option<Vec3>> VectorField for F {
+    fn sample(&self, position: Vec3, time: f32) -> Option<Vec3> {
+        self(position, time)
+    }
+}
+
+///
+/// A vector field sampled on a regular 3D grid, for example loaded from the output of a CFD simulation.
+/// The field is trilinearly interpolated between the grid points and is undefined outside the grid.
+///
+#[derive(Clone, Debug)]
+pub struct SampledVectorField {
+    origin: Vec3,
+    spacing: Vec3,
+    size: [usize; 3],
+    vectors: Vec<Vec3>,
+}
+
+impl SampledVectorField {
+    ///
+    /// Creates a new sampled vector field where the grid point with index `(x, y, z)` is placed at `origin + (x * spacing.x, y * spacing.y, z * spacing.z)`
+    /// and the vector at that grid point is `vectors[x + size[0] * (y + size[1] * z)]`.
+    ///
+    /// # Panics
+    /// If the number of vectors is not equal to the number of grid points.
+    ///
+    pub fn new(origin: Vec3, spacing: Vec3, size: [usize; 3], vectors: Vec<Vec3>) -> Self {
+        assert_eq!(
+            vectors.len(),
+            size[0] * size[1] * size[2],
+            "the number of vectors must be equal to the number of grid points"
+        );
+        Self {
+            origin,
+            spacing,
+            size,
+            vectors,
+        }
+    }
+
+    ///
+    /// Returns the bounding box of the grid.
+    ///
+    pub fn aabb(&self) -> AxisAlignedBoundingBox {
+        let extent = vec3(
+            self.spacing.x * (self.size[0].max(1) - 1) as f32,
+            self.spacing.y * (self.size[1].max(1) - 1) as f32,
+            self.spacing.z * (self.size[2].max(1) - 1) as f32,
+        );
+        AxisAlignedBoundingBox::new_with_positions(&[self.origin, self.origin + extent])
+    }
+
+    fn vector(&self, x: usize, y: usize, z: usize) -> Vec3 {
+        self.vectors[x + self.size[0] * (y + self.size[1] * z)]
+    }
+}
+
+impl VectorField for SampledVectorField {
+    fn sample(&self, position: Vec3, _time: f32) -> Option<Vec3> {
+        let mut index = [0usize; 3];
+        let mut fraction = [0.0f32; 3];
+        for axis in 0..3 {
+            let p = (position[axis] - self.origin[axis]) / self.spacing[axis];
+            let max = (self.size[axis] as f32 - 1.0).max(0.0);
+            if !(0.0..=max).contains(&p) {
+                return None;
+            }
+            let i = (p.floor() as usize).min(self.size[axis].saturating_sub(2));
+            index[axis] = i;
+            fraction[axis] = (p - i as f32).clamp(0.0, 1.0);
+        }
+        let next = |axis: usize| (index[axis] + 1).min(self.size[axis] - 1);
+        let [x0, y0, z0] = index;
+        let (x1, y1, z1) = (next(0), next(1), next(2));
+        let [fx, fy, fz] = fraction;
+        let lerp = |a: Vec3, b: Vec3, t: f32| a * (1.0 - t) + b * t;
+        let c00 = lerp(self.vector(x0, y0, z0), self.vector(x1, y0, z0), fx);
+        let c10 = lerp(self.vector(x0, y1, z0), self.vector(x1, y1, z0), fx);
+        let c01 = lerp(self.vector(x0, y0, z1), self.vector(x1, y0, z1), fx);
+        let c11 = lerp(self.vector(x0, y1, z1), self.vector(x1, y1, z1), fx);
+        Some(lerp(lerp(c00, c10, fy), lerp(c01, c11, fy), fz))
+    }
+}
+
+///
+/// Strategies for placing the seed points where the tracing of streamlines and pathlines starts.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StreamlineSeeding {
+    /// Seed points on a regular grid with the given number of points along each axis inside the bounding box.
+    Grid {
+        /// The box in which the seed points are placed.
+        aabb: AxisAlignedBoundingBox,
+        /// The number of seed points along the x, y and z axis.
+        resolution: [u32; 3],
+    },
+    /// The given number of seed points evenly distributed on the surface of a sphere, for example around an obstacle.
+    Sphere {
+        /// The center of the sphere.
+        center: Vec3,
+        /// The radius of the sphere.
+        radius: f32,
+        /// The number of seed points.
+        count: u32,
+    },
+    /// Seed points on a regular grid in a square in the plane with the given normal, for example at the inlet of a flow.
+    Plane {
+        /// The center of the square.
+        center: Vec3,
+        /// The normal of the plane.
+        normal: Vec3,
+        /// The side length of the square.
+        size: f32,
+        /// The number of seed points along each side of the square.
+        resolution: u32,
+    },
+}
+
+impl StreamlineSeeding {
+    ///
+    /// Returns the seed points.
+    ///
+    pub fn seeds(&self) -> Vec<Vec3> {
+        let t = |i: u32, n: u32| {
+            if n > 1 {
+                i as f32 / (n - 1) as f32
+            } else {
+                0.5
+            }
+        };
+        match *self {
+            Self::Grid { aabb, resolution } => {
+                let min = aabb.min();
+                let size = aabb.size();
+                let mut seeds = Vec::new();
+                for z in 0..resolution[2] {
+                    for y in 0..resolution[1] {
+                        for x in 0..resolution[0] {
+                            seeds.push(
+                                min + vec3(
+                                    size.x * t(x, resolution[0]),
+                                    size.y * t(y, resolution[1]),
+                                    size.z * t(z, resolution[2]),
+                                ),
+                            );
+                        }
+                    }
+                }
+                seeds
+            }
+            Self::Sphere {
+                center,
+                radius,
+                count,
+            } => {
+                // Fibonacci sphere
+                let golden_angle = std::f32::consts::PI * (3.0 - 5.0f32.sqrt());
+                (0..count)
+                    .map(|i| {
+                        let y = 1.0 - 2.0 * (i as f32 + 0.5) / count as f32;
+                        let r = (1.0 - y * y).max(0.0).sqrt();
+                        let phi = golden_angle * i as f32;
+                        center + radius * vec3(r * phi.cos(), y, r * phi.sin())
+                    })
+                    .collect()
+            }
+            Self::Plane {
+                center,
+                normal,
+                size,
+                resolution,
+            } => {
+                let normal = normal.normalize();
+                let up = if normal.y.abs() < 0.99 {
+                    vec3(0.0, 1.0, 0.0)
+                } else {
+                    vec3(0.0, 0.0, 1.0)
+                };
+                let u = normal.cross(up).normalize();
+                let v = u.cross(normal);
+                let mut seeds = Vec::new();
+                for j in 0..resolution {
+                    for i in 0..resolution {
+                        seeds.push(
+                            center
+                                + size * (t(i, resolution) - 0.5) * u
+                                + size * (t(j, resolution) - 0.5) * v,
+                        );
+                    }
+                }
+                seeds
+            }
+        }
+    }
+}
+
+///
+/// The direction in which streamlines and pathlines are traced from the seed points.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum TraceDirection {
+    /// Along the vector field.
+    #[default]
+    Forward,
+    /// Against the vector field.
+    Backward,
+    /// Both along and against the vector field, so the seed point is in the middle of the line.
+    Both,
+}
+
+///
+/// Options for tracing streamlines and pathlines, see [trace_streamlines] and [trace_pathlines].
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StreamlineOptions {
+    /// The time step used by the integration. Smaller steps gives more accurate and smoother lines.
+    pub step_size: f32,
+    /// The maximum number of integration steps in each direction.
+    pub max_steps: u32,
+    /// The tracing stops when the length of the vector is below this speed, for example at a stagnation point.
+    pub min_speed: f32,
+    /// The direction in which the lines are traced.
+    pub direction: TraceDirection,
+    /// The time at the seed points. Streamlines are traced in the field at this time, while pathlines start at this time.
+    pub start_time: f32,
+}
+
+impl Default for StreamlineOptions {
+    fn default() -> Self {
+        Self {
+            step_size: 0.01,
+            max_steps: 1000,
+            min_speed: 1e-6,
+            direction: TraceDirection::Forward,
+            start_time: 0.0,
+        }
+    }
+}
+
+///
+/// A traced streamline or pathline.
+///
+#[derive(Clone, Debug, Default)]
+pub struct Streamline {
+    /// The points along the line.
+    pub points: Vec<Vec3>,
+    /// The speed, ie. the length of the vector, at each point, which for example can be mapped to colors using a [ColorMap].
+    pub speeds: Vec<f32>,
+}
+
+impl Streamline {
+    ///
+    /// Returns the color of each point by mapping the speed to the given color map, where the minimum and maximum speed are mapped to the ends of the color map.
+    ///
+    pub fn colors(&self, color_map: ColorMap, min_speed: f32, max_speed: f32) -> Vec<Srgba> {
+        let range = (max_speed - min_speed).max(f32::EPSILON);
+        self.speeds
+            .iter()
+            .map(|s| color_map.color((s - min_speed) / range))
+            .collect()
+    }
+}
+
+///
+/// Traces streamlines, ie. lines which are tangent to the vector field at a fixed time, from each of the seed points using fourth order Runge-Kutta integration.
+/// The lines can be rendered using [Polylines].
+///
+pub fn trace_streamlines(
+    field: &impl VectorField,
+    seeds: &[Vec3],
+    options: &StreamlineOptions,
+) -> Vec<Streamline> {
+    seeds
+        .iter()
+        .map(|seed| trace(field, *seed, options, false))
+        .collect()
+}
+
+///
+/// Traces pathlines, ie. the paths of massless particles released at the seed points at the start time, through a vector field which changes over time
+/// using fourth order Runge-Kutta integration. For a vector field which does not change over time, pathlines are the same as streamlines.
+/// The lines can be rendered using [Polylines].
+///
+pub fn trace_pathlines(
+    field: &impl VectorField,
+    seeds: &[Vec3],
+    options: &StreamlineOptions,
+) -> Vec<Streamline> {
+    seeds
+        .iter()
+        .map(|seed| trace(field, *seed, options, true))
+        .collect()
+}
+
+fn trace(
+    field: &impl VectorField,
+    seed: Vec3,
+    options: &StreamlineOptions,
+    unsteady: bool,
+) -> Streamline {
+    let integrate = |sign: f32| {
+        let h = sign * options.step_size;
+        let mut points = Vec::new();
+        let mut speeds = Vec::new();
+        let mut position = seed;
+        let mut time = options.start_time;
+        let sample = |p: Vec3, t: f32| {
+            field
+                .sample(p, if unsteady { t } else { options.start_time })
+                .map(|v| sign * v)
+        };
+        for _ in 0..options.max_steps {
+            let k1 = match sample(position, time) {
+                Some(v) if v.magnitude() >= options.min_speed => v,
+                _ => break,
+            };
+            points.push(position);
+            speeds.push(k1.magnitude());
+            let step = (|| {
+                let k2 = sample(position + 0.5 * h.abs() * k1, time + 0.5 * h)?;
+                let k3 = sample(position + 0.5 * h.abs() * k2, time + 0.5 * h)?;
+                let k4 = sample(position + h.abs() * k3, time + h)?;
+                Some(h.abs() / 6.0 * (k1 + 2.0 * k2 + 2.0 * k3 + k4))
+            })();
+            match step {
+                Some(step) => {
+                    position += step;
+                    time += h;
+                }
+                None => break,
+            }
+        }
+        if let Some(speed) = field
+            .sample(position, if unsteady { time } else { options.start_time })
+            .map(|v| v.magnitude())
+        {
+            if points.last() != Some(&position) {
+                points.push(position);
+                speeds.push(speed);
+            }
+        }
+        (points, speeds)
+    };
+
+    match options.direction {
+        TraceDirection::Forward => {
+            let (points, speeds) = integrate(1.0);
+            Streamline { points, speeds }
+        }
+        TraceDirection::Backward => {
+            let (points, speeds) = integrate(-1.0);
+            Streamline { points, speeds }
+        }
+        TraceDirection::Both => {
+            let (mut points, mut speeds) = integrate(-1.0);
+            points.reverse();
+            speeds.reverse();
+            let (forward_points, forward_speeds) = integrate(1.0);
+            let skip = if points.is_empty() { 0 } else { 1 };
+            points.extend(forward_points.into_iter().skip(skip));
+            speeds.extend(forward_speeds.into_iter().skip(skip));
+            Streamline { points, speeds }
+        }
+    }
+}
@@ -0,0 +1,366 @@
+use crate::renderer::*;
+
+///
+/// Renders the scene at half the resolution in each direction and reconstructs the full resolution image by combining the current frame with the previous frames,
+/// which reduces the number of shaded pixels to a quarter. This is a companion to [RenderScale] which is useful for heavy scenes, for example with deferred materials, on weak GPUs.
+///
+/// Each frame, the camera is jittered so the low resolution image samples one of the four pixels in each 2x2 block of full resolution pixels, cycling through the four pixels over four frames.
+/// The remaining pixels are reconstructed from the history of the previous frames, which is reprojected into the current frame using a velocity buffer
+/// and clamped to the colors of the current frame to avoid ghosting.
+/// The velocity buffer contains the motion of the moving objects given to [TemporalUpsampling::render_with_motion],
+/// all other pixels are reprojected using the depth, ie. the velocity is calculated from the movement of the camera.
+///
+pub struct TemporalUpsampling {
+    context: Context,
+    color_texture: Texture2D,
+    depth_texture: DepthTexture2D,
+    velocity_texture: Texture2D,
+    history: [Texture2D; 2],
+    frame: usize,
+    previous_view_projection: Option<Mat4>,
+    /// The weight of the history in the range `[0, 1]` for the pixels which are not sampled in the current frame.
+    /// A higher weight gives a sharper image when the camera is still but more ghosting when the camera moves.
+    pub history_weight: f32,
+}
+
+impl TemporalUpsampling {
+    ///
+    /// Constructs a new temporal upsampling.
+    ///
+    pub fn new(context: &Context) -> Self {
+        Self {
+            context: context.clone(),
+            color_texture: new_intermediate_texture(context, 1, 1, Interpolation::Nearest),
+            depth_texture: new_depth_texture(context, 1, 1),
+            velocity_texture: new_velocity_texture(context, 1, 1),
+            history: [
+                new_intermediate_texture(context, 1, 1, Interpolation::Linear),
+                new_intermediate_texture(context, 1, 1, Interpolation::Linear),
+            ],
+            frame: 0,
+            previous_view_projection: None,
+            history_weight: 0.9,
+        }
+    }
+
+    ///
+    /// Discards the history of the previous frames. Should be called when the content changes completely, for example on a camera cut.
+    ///
+    pub fn reset(&mut self) {
+        self.previous_view_projection = None;
+    }
+
+    ///
+    /// Clears an offscreen render target with half the resolution of the viewport of the camera using the given clear state.
+    /// Then calls the `render` closure with the offscreen render target and a jittered copy of the camera which has a viewport that covers the offscreen render target.
+    /// Finally, the full resolution image is reconstructed into the viewport of the camera in the given render target. Only the color is written to the render target, not the depth.
+    ///
+    /// Moving objects are reconstructed from the low resolution image only and therefore appear blurry, use [TemporalUpsampling::render_with_motion] to avoid that.
+    ///
+    pub fn render(
+        &mut self,
+        target: &RenderTarget,
+        camera: &Camera,
+        clear_state: ClearState,
+        render: impl FnOnce(&RenderTarget, &Camera),
+    ) {
+        self.render_with_motion(target, camera, clear_state, render, std::iter::empty());
+    }
+
+    ///
+    /// Same as [TemporalUpsampling::render], except that the given moving objects are written to the velocity buffer which is used for reprojecting the history into the current frame.
+    /// Each object is given by its geometry and its motion since the previous frame, ie. the transformation from its current world position to its world position in the previous frame.
+    /// For an object which is moved by changing its transformation, the motion is `previous_transformation * transformation.invert().unwrap()`.
+    ///
+    /// The geometries must be rendered in the `render` closure as well, since the velocity buffer is depth tested against the rendered depth.
+    ///
+    pub fn render_with_motion<'a>(
+        &mut self,
+        target: &RenderTarget,
+        camera: &Camera,
+        clear_state: ClearState,
+        render: impl FnOnce(&RenderTarget, &Camera),
+        moving_objects: impl IntoIterator<Item = (&'a dyn Geometry, Mat4)>,
+    ) {
+        let viewport = camera.viewport();
+        let width = (viewport.width + 1) / 2;
+        let height = (viewport.height + 1) / 2;
+        if self.color_texture.width() != width || self.color_texture.height() != height {
+            self.color_texture =
+                new_intermediate_texture(&self.context, width, height, Interpolation::Nearest);
+            self.depth_texture = new_depth_texture(&self.context, width, height);
+            self.velocity_texture = new_velocity_texture(&self.context, width, height);
+        }
+        if self.history[0].width() != viewport.width || self.history[0].height() != viewport.height
+        {
+            self.history = [
+                new_intermediate_texture(
+                    &self.context,
+                    viewport.width,
+                    viewport.height,
+                    Interpolation::Linear,
+                ),
+                new_intermediate_texture(
+                    &self.context,
+                    viewport.width,
+                    viewport.height,
+                    Interpolation::Linear,
+                ),
+            ];
+            self.previous_view_projection = None;
+        }
+
+        // Sample the pixels in each 2x2 block in the order bottom left, top right, bottom right, top left
+        let jitter_index = [
+            vec2(0.0, 0.0),
+            vec2(1.0, 1.0),
+            vec2(1.0, 0.0),
+            vec2(0.0, 1.0),
+        ][self.frame % 4];
+        let jittered_camera = jittered_camera(
+            camera,
+            Viewport::new_at_origo(width, height),
+            jitter_index - vec2(0.5, 0.5),
+        );
+        let offscreen_target = RenderTarget::new(
+            self.color_texture.as_color_target(None),
+            self.depth_texture.as_depth_target(),
+        );
+        offscreen_target.clear(clear_state);
+        render(&offscreen_target, &jittered_camera);
+
+        // Write the previous position of the moving objects into the velocity buffer
+        let view_projection = camera.projection() * camera.view();
+        let previous_view_projection = self.previous_view_projection.unwrap_or(view_projection);
+        RenderTarget::new(
+            self.velocity_texture.as_color_target(None),
+            self.depth_texture.as_depth_target(),
+        )
+        .clear(ClearState::color(0.0, 0.0, 0.0, 0.0))
+        .write(|| {
+            for (geometry, motion) in moving_objects {
+                geometry.render_with_material(
+                    &VelocityMaterial {
+                        motion,
+                        previous_view_projection,
+                    },
+                    &jittered_camera,
+                    &[],
+                );
+            }
+        });
+
+        // Reconstruct the full resolution image into the next history texture
+        let (previous_history, next_history) = {
+            let (first, second) = self.history.split_at_mut(1);
+            if self.frame % 2 == 0 {
+                (&first[0], &mut second[0])
+            } else {
+                (&second[0], &mut first[0])
+            }
+        };
+        let history_camera = {
+            let mut c = camera.clone();
+            c.set_viewport(Viewport::new_at_origo(viewport.width, viewport.height));
+            c
+        };
+        next_history.as_color_target(None).apply_screen_effect(
+            &TemporalUpsamplingEffect {
+                history: previous_history,
+                velocity: &self.velocity_texture,
+                view_projection_inverse: view_projection.invert().unwrap_or(Mat4::identity()),
+                previous_view_projection,
+                jitter_index,
+                history_weight: if self.previous_view_projection.is_some() {
+                    self.history_weight.clamp(0.0, 1.0)
+                } else {
+                    0.0
+                },
+            },
+            &history_camera,
+            &[],
+            Some(ColorTexture::Single(&self.color_texture)),
+            Some(DepthTexture::Single(&self.depth_texture)),
+        );
+        target.apply_screen_effect(
+            &CopyEffect {
+                write_mask: WriteMask::COLOR,
+            },
+            camera,
+            &[],
+            Some(ColorTexture::Single(next_history)),
+            None,
+        );
+        self.previous_view_projection = Some(view_projection);
+        self.frame += 1;
+    }
+}
+
+///
+/// Returns a copy of the camera with the given viewport where the image is shifted by the given offset in pixels of the full resolution image, ie. twice the size of the given viewport.
+/// The shift is done by rotating a perspective camera or translating an orthographic camera, which for the small offsets used is close to a shift of the projection.
+///
+fn jittered_camera(camera: &Camera, viewport: Viewport, offset: Vec2) -> Camera {
+    let mut jittered_camera = camera.clone();
+    jittered_camera.set_viewport(viewport);
+    let full_width = 2.0 * viewport.width as f32;
+    let full_height = 2.0 * viewport.height as f32;
+    let aspect = full_width / full_height;
+    let ndc_offset = vec2(2.0 * offset.x / full_width, 2.0 * offset.y / full_height);
+    let right = camera.right_direction().normalize();
+    let up = right.cross(camera.view_direction()).normalize();
+    let position = *camera.position();
+    let target = *camera.target();
+    match camera.projection_type() {
+        three_d_asset::ProjectionType::Perspective { field_of_view_y } => {
+            let t = (0.5 * field_of_view_y.0).tan() * position.distance(target);
+            let shift = right * ndc_offset.x * t * aspect + up * ndc_offset.y * t;
+            jittered_camera.set_view(position, target + shift, *camera.up());
+        }
+        three_d_asset::ProjectionType::Orthographic { height } => {
+            let t = 0.5 * *height;
+            let shift = right * ndc_offset.x * t * aspect + up * ndc_offset.y * t;
+            jittered_camera.set_view(position + shift, target + shift, *camera.up());
+        }
+    }
+    jittered_camera
+}
+
+struct TemporalUpsamplingEffect<'a> {
+    history: &'a Texture2D,
+    velocity: &'a Texture2D,
+    view_projection_inverse: Mat4,
+    previous_view_projection: Mat4,
+    jitter_index: Vec2,
+    history_weight: f32,
+}
+
+impl Effect for TemporalUpsamplingEffect<'_> {
+    fn fragment_shader_source(
+        &self,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) -> String {
+        format!(
+            "{}\n{}\n{}",
+            color_texture.unwrap().fragment_shader_source(),
+            depth_texture.unwrap().fragment_shader_source(),
+            include_str!("shaders/temporal_upsampling.frag")
+        )
+    }
+
+    fn id(&self, color_texture: Option<ColorTexture>, depth_texture: Option<DepthTexture>) -> u16 {
+        0b1u16 << 14 | 0b1u16 << 8 | color_texture.unwrap().id() | depth_texture.unwrap().id()
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            uv: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(
+        &self,
+        program: &Program,
+        camera: &Camera,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) {
+        let color_texture = color_texture.unwrap();
+        color_texture.use_uniforms(program);
+        depth_texture.unwrap().use_uniforms(program);
+        program.use_texture("historyMap", self.history);
+        program.use_texture("velocityMap", self.velocity);
+        program.use_uniform("viewProjectionInverse", self.view_projection_inverse);
+        program.use_uniform("previousViewProjection", self.previous_view_projection);
+        let viewport = camera.viewport();
+        program.use_uniform(
+            "resolution",
+            vec2(viewport.width as f32, viewport.height as f32),
+        );
+        program.use_uniform(
+            "sourceResolution",
+            vec2(color_texture.width() as f32, color_texture.height() as f32),
+        );
+        program.use_uniform("jitterIndex", self.jitter_index);
+        program.use_uniform("historyWeight", self.history_weight);
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates {
+            write_mask: WriteMask::COLOR,
+            depth_test: DepthTest::Always,
+            cull: Cull::Back,
+            ..Default::default()
+        }
+    }
+}
+
+///
+/// Writes the position of each fragment in the previous frame, in texture coordinates of the full resolution image, into the red and green channels
+/// and one into the alpha channel to mark that the fragment belongs to a moving object.
+///
+struct VelocityMaterial {
+    motion: Mat4,
+    previous_view_projection: Mat4,
+}
+
+impl Material for VelocityMaterial {
+    fn id(&self) -> u16 {
+        0b1u16 << 15 | 0b1u16 << 12 | 0b1000u16
+    }
+
+    fn fragment_shader_source(&self, _lights: &[&dyn Light]) -> String {
+        include_str!("shaders/temporal_upsampling_velocity.frag").to_string()
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            position: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(&self, program: &Program, _camera: &Camera, _lights: &[&dyn Light]) {
+        program.use_uniform("motion", self.motion);
+        program.use_uniform("previousViewProjection", self.previous_view_projection);
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates {
+            write_mask: WriteMask::COLOR,
+            depth_test: DepthTest::LessOrEqual,
+            ..Default::default()
+        }
+    }
+
+    fn material_type(&self) -> MaterialType {
+        MaterialType::Opaque
+    }
+}
+
+fn new_velocity_texture(context: &Context, width: u32, height: u32) -> Texture2D {
+    Texture2D::new_empty::<[f16; 4]>(
+        context,
+        width,
+        height,
+        Interpolation::Nearest,
+        Interpolation::Nearest,
+        None,
+        Wrapping::ClampToEdge,
+        Wrapping::ClampToEdge,
+    )
+}
+
+fn new_depth_texture(context: &Context, width: u32, height: u32) -> DepthTexture2D {
+    DepthTexture2D::new::<f32>(
+        context,
+        width,
+        height,
+        Wrapping::ClampToEdge,
+        Wrapping::ClampToEdge,
+    )
+}
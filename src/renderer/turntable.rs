@@ -0,0 +1,174 @@
+use crate::renderer::*;
+
+///
+/// Specifies how the lights used by [capture_turntable] are placed.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum TurntableLighting {
+    /// The lights follow the camera, so the object is lit from the same side in all frames, like a photo studio where the camera and lights move around the object.
+    #[default]
+    FixedToCamera,
+    /// The lights are fixed relative to the object, so the lighting changes as the camera orbits the object, like an object on a rotating turntable in a photo studio.
+    FixedToObject,
+}
+
+///
+/// The image sequence captured by [capture_turntable].
+///
+/// The pixels of each frame are stored row by row starting with the top left pixel, which is the same order as returned by [RenderTarget::read_color].
+///
+#[derive(Clone, Debug)]
+pub struct TurntableCapture {
+    /// The width in pixels of each frame.
+    pub width: u32,
+    /// The height in pixels of each frame.
+    pub height: u32,
+    /// The rendered colors of each frame. Pixels without any object are transparent.
+    pub frames: Vec<Vec<[u8; 4]>>,
+}
+
+impl TurntableCapture {
+    ///
+    /// Returns the frame with the given index as an 8-bit RGBA image.
+    ///
+    pub fn frame_image(&self, index: usize) -> CpuTexture {
+        CpuTexture {
+            name: format!("frame{}", index),
+            data: TextureData::RgbaU8(self.frames[index].clone()),
+            width: self.width,
+            height: self.height,
+            ..Default::default()
+        }
+    }
+
+    ///
+    /// Saves the frames in the given directory as `{name}_0000.png`, `{name}_0001.png` and so on.
+    /// The directory is created if it does not exist.
+    ///
+    #[cfg(all(feature = "render-export", not(target_arch = "wasm32")))]
+    #[cfg_attr(docsrs, doc(feature = "render-export"))]
+    pub fn save(
+        &self,
+        directory: impl AsRef<std::path::Path>,
+        name: &str,
+    ) -> Result<(), RendererError> {
+        let directory = directory.as_ref();
+        std::fs::create_dir_all(directory)?;
+        for (index, frame) in self.frames.iter().enumerate() {
+            image::save_buffer(
+                directory.join(format!("{}_{:04}.png", name, index)),
+                &frame.iter().flatten().copied().collect::<Vec<_>>(),
+                self.width,
+                self.height,
+                image::ColorType::Rgba8,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+///
+/// Captures an image sequence of the given objects seen from a camera which orbits the objects one full turn around the y-axis,
+/// for example to create a product video or a 360 degree product view for a web shop.
+/// The camera looks slightly down on the objects and is placed so the objects are fully visible in all frames.
+///
+/// The objects are lit by a key light, a fill light and an ambient light which are either fixed to the camera or to the objects, see [TurntableLighting].
+/// The objects should therefore use a material which is affected by lights, for example [PhysicalMaterial].
+///
+pub fn capture_turntable(
+    context: &Context,
+    objects: &[&dyn Object],
+    frame_count: u32,
+    width: u32,
+    height: u32,
+    lighting: TurntableLighting,
+) -> TurntableCapture {
+    let mut aabb = AxisAlignedBoundingBox::EMPTY;
+    for object in objects {
+        aabb.expand_with_aabb(&object.aabb());
+    }
+    let (target, radius) = if aabb.is_empty() {
+        (vec3(0.0, 0.0, 0.0), 1.0)
+    } else {
+        (
+            aabb.center(),
+            (0.5 * aabb.size().magnitude()).max(f32::EPSILON),
+        )
+    };
+    let field_of_view = degrees(35.0);
+    let aspect = width as f32 / height.max(1) as f32;
+    // The distance where the bounding sphere fits inside the narrowest of the horizontal and vertical field of view
+    let tan_half_angle = (0.5 * Radians::from(field_of_view).0).tan();
+    let half_angle = tan_half_angle.min(aspect * tan_half_angle).atan();
+    let distance = 1.1 * radius / half_angle.sin();
+    let elevation = Radians::from(degrees(20.0)).0;
+
+    let mut camera = Camera::new_perspective(
+        Viewport::new_at_origo(width, height),
+        target + distance * vec3(0.0, elevation.sin(), elevation.cos()),
+        target,
+        vec3(0.0, 1.0, 0.0),
+        field_of_view,
+        0.01 * distance,
+        distance + 2.0 * radius,
+    );
+    let ambient_light = AmbientLight::new(context, 0.3, Srgba::WHITE);
+    let mut key_light = DirectionalLight::new(context, 2.0, Srgba::WHITE, &vec3(0.0, -1.0, 0.0));
+    let mut fill_light = DirectionalLight::new(context, 0.6, Srgba::WHITE, &vec3(0.0, -1.0, 0.0));
+
+    let mut color_texture = Texture2D::new_empty::<[u8; 4]>(
+        context,
+        width,
+        height,
+        Interpolation::Nearest,
+        Interpolation::Nearest,
+        None,
+        Wrapping::ClampToEdge,
+        Wrapping::ClampToEdge,
+    );
+    let mut depth_texture = DepthTexture2D::new::<f32>(
+        context,
+        width,
+        height,
+        Wrapping::ClampToEdge,
+        Wrapping::ClampToEdge,
+    );
+
+    let frame_count = frame_count.max(1);
+    let mut frames = Vec::with_capacity(frame_count as usize);
+    for frame in 0..frame_count {
+        let angle = 2.0 * std::f32::consts::PI * frame as f32 / frame_count as f32;
+        let offset = distance
+            * vec3(
+                angle.sin() * elevation.cos(),
+                elevation.sin(),
+                angle.cos() * elevation.cos(),
+            );
+        camera.set_view(target + offset, target, vec3(0.0, 1.0, 0.0));
+
+        if frame == 0 || lighting == TurntableLighting::FixedToCamera {
+            // The key light shines from the upper left of the camera and the fill light from the right
+            let view_direction = camera.view_direction();
+            let right = camera.right_direction();
+            let up = right.cross(view_direction);
+            key_light.direction = (view_direction + 0.7 * right - 0.8 * up).normalize();
+            fill_light.direction = (view_direction - 0.9 * right - 0.2 * up).normalize();
+        }
+
+        frames.push(
+            RenderTarget::new(
+                color_texture.as_color_target(None),
+                depth_texture.as_depth_target(),
+            )
+            .clear(ClearState::color_and_depth(0.0, 0.0, 0.0, 0.0, 1.0))
+            .render(&camera, objects, &[&ambient_light, &key_light, &fill_light])
+            .read_color::<[u8; 4]>(),
+        );
+    }
+
+    TurntableCapture {
+        width,
+        height,
+        frames,
+    }
+}
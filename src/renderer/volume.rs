@@ -0,0 +1,652 @@
+use crate::renderer::*;
+
+///
+/// A scalar volume on the CPU side, for example a CT or MRI scan, with the voxel data and the placement of the voxels in world (patient) space.
+/// Use [CpuVolume::from_nrrd] to parse a `.nrrd` file or [CpuVolume::from_dicom_series] to combine the slices of a DICOM series.
+///
+/// The voxel data is normalized to the range `[0..1]`, so it can be uploaded directly to a [Texture3D] and rendered using for example [IsosurfaceMaterial] or [MarchingCubes],
+/// while the range of the original values is kept so values, for example in Hounsfield units, can be converted using [CpuVolume::normalize].
+///
+#[derive(Clone, Debug)]
+pub struct CpuVolume {
+    /// The normalized voxel data in a single red channel.
+    pub voxels: CpuTexture3D,
+    /// The minimum and maximum of the original values, which are mapped to 0 and 1 respectively in the normalized voxel data.
+    pub value_range: [f32; 2],
+    /// The distance in world units, typically millimeters, between the centers of neighbouring voxels along the x, y and z axis of the voxel data.
+    pub spacing: Vec3,
+    /// The position in world space of the center of the first voxel.
+    pub origin: Vec3,
+    /// The directions in world space of the x, y and z axis of the voxel data as the columns of an orthonormal matrix.
+    pub orientation: Mat3,
+}
+
+impl CpuVolume {
+    ///
+    /// Returns the given original value, for example an iso value in Hounsfield units, mapped to the range of the normalized voxel data.
+    ///
+    pub fn normalize(&self, value: f32) -> f32 {
+        (value - self.value_range[0])
+            / (self.value_range[1] - self.value_range[0]).max(f32::EPSILON)
+    }
+
+    ///
+    /// Returns the size of the box covered by the voxels, ie. the spacing multiplied by the number of voxels along each axis.
+    ///
+    pub fn size(&self) -> Vec3 {
+        vec3(
+            self.spacing.x * self.voxels.width as f32,
+            self.spacing.y * self.voxels.height as f32,
+            self.spacing.z * self.voxels.depth as f32,
+        )
+    }
+
+    ///
+    /// Returns the transformation from a box of size [CpuVolume::size] with center in origo, which is used by for example [VoxelGrid] and [MarchingCubes],
+    /// to the placement of the volume in world space.
+    ///
+    pub fn transformation(&self) -> Mat4 {
+        let half_extent = 0.5 * (self.size() - self.spacing);
+        Mat4::from_translation(self.origin + self.orientation * half_extent)
+            * Mat4::from(self.orientation)
+    }
+
+    ///
+    /// Returns a voxel grid with the normalized voxel data and the size of the volume, which can be used to construct a [VoxelGrid].
+    /// Apply [CpuVolume::transformation] to place the voxel grid in world space.
+    ///
+    pub fn to_voxel_grid(&self) -> CpuVoxelGrid {
+        CpuVoxelGrid {
+            name: self.voxels.name.clone(),
+            voxels: self.voxels.clone(),
+            size: self.size(),
+        }
+    }
+
+    ///
+    /// Parses the given bytes of a `.nrrd` file containing a 3D scalar volume with the data attached after the header.
+    /// Raw and ascii encodings of all integer types up to 32 bits, `float` and `double` are supported, while compressed data and detached data files are not.
+    /// The spacing, origin and orientation is read from the `space directions` and `space origin` fields if present, otherwise from the `spacings` field.
+    ///
+    pub fn from_nrrd(bytes: &[u8]) -> Result<Self, RendererError> {
+        let error = |message: &str| RendererError::InvalidVolumeFile(message.to_string());
+        if !bytes.starts_with(b"NRRD") {
+            Err(error("missing NRRD magic number"))?;
+        }
+        let header_end = bytes
+            .windows(2)
+            .position(|w| w == b"\n\n")
+            .map(|p| (p, p + 2))
+            .or_else(|| {
+                bytes
+                    .windows(4)
+                    .position(|w| w == b"\r\n\r\n")
+                    .map(|p| (p, p + 4))
+            });
+        let (header_end, data_start) = header_end.ok_or_else(|| error("missing end of header"))?;
+        let header = std::str::from_utf8(&bytes[..header_end])
+            .map_err(|_| error("the header is not valid utf8"))?;
+
+        let mut scalar_type = None;
+        let mut sizes = None;
+        let mut encoding = "raw".to_string();
+        let mut big_endian = false;
+        let mut spacings = None;
+        let mut directions = None;
+        let mut origin = vec3(0.0, 0.0, 0.0);
+        let mut byte_skip = 0i64;
+        for line in header.lines().skip(1) {
+            let line = line.trim();
+            if line.starts_with('#') || line.contains(":=") {
+                continue;
+            }
+            let (field, value) = match line.split_once(':') {
+                Some(field_and_value) => field_and_value,
+                None => continue,
+            };
+            let value = value.trim();
+            match field.trim().to_lowercase().as_str() {
+                "type" => {
+                    scalar_type =
+                        Some(NrrdType::parse(value).ok_or_else(|| error("unsupported type"))?)
+                }
+                "dimension" => {
+                    if value != "3" {
+                        Err(error("only 3 dimensional volumes are supported"))?;
+                    }
+                }
+                "sizes" => {
+                    let s = parse_numbers::<usize>(value).ok_or_else(|| error("invalid sizes"))?;
+                    if s.len() != 3 {
+                        Err(error("invalid sizes"))?;
+                    }
+                    sizes = Some([s[0], s[1], s[2]]);
+                }
+                "encoding" => encoding = value.to_lowercase(),
+                "endian" => big_endian = value == "big",
+                "spacings" => {
+                    let s = parse_numbers::<f32>(value).ok_or_else(|| error("invalid spacings"))?;
+                    if s.len() != 3 {
+                        Err(error("invalid spacings"))?;
+                    }
+                    spacings = Some(vec3(s[0], s[1], s[2]));
+                }
+                "space directions" => {
+                    let vectors = value
+                        .split(')')
+                        .map(str::trim)
+                        .filter(|t| !t.is_empty())
+                        .map(parse_vector)
+                        .collect::<Option<Vec<_>>>()
+                        .ok_or_else(|| error("invalid space directions"))?;
+                    if vectors.len() != 3 {
+                        Err(error("invalid space directions"))?;
+                    }
+                    directions = Some(Mat3::from_cols(vectors[0], vectors[1], vectors[2]));
+                }
+                "space origin" => {
+                    origin = parse_vector(value).ok_or_else(|| error("invalid space origin"))?;
+                }
+                "byte skip" => {
+                    byte_skip = value.parse().map_err(|_| error("invalid byte skip"))?;
+                }
+                "data file" | "datafile" => Err(error("detached data files are not supported"))?,
+                _ => {}
+            }
+        }
+        let scalar_type = scalar_type.ok_or_else(|| error("missing type"))?;
+        let sizes = sizes.ok_or_else(|| error("missing sizes"))?;
+        let count = sizes[0] * sizes[1] * sizes[2];
+
+        let data = &bytes[data_start..];
+        let values = match encoding.as_str() {
+            "raw" => {
+                let byte_count = count * scalar_type.size();
+                let data = if byte_skip < 0 {
+                    data.len()
+                        .checked_sub(byte_count)
+                        .map(|start| &data[start..])
+                } else {
+                    data.get(byte_skip as usize..)
+                }
+                .filter(|data| data.len() >= byte_count)
+                .ok_or_else(|| error("the data is too short"))?;
+                data.chunks_exact(scalar_type.size())
+                    .take(count)
+                    .map(|bytes| scalar_type.read(bytes, big_endian))
+                    .collect::<Vec<_>>()
+            }
+            "ascii" | "text" | "txt" => {
+                let values = std::str::from_utf8(data)
+                    .ok()
+                    .and_then(|text| parse_numbers::<f32>(text))
+                    .ok_or_else(|| error("invalid ascii data"))?;
+                if values.len() < count {
+                    Err(error("the data is too short"))?;
+                }
+                values
+            }
+            _ => Err(error("only raw and ascii encodings are supported"))?,
+        };
+
+        let (spacing, orientation) = match directions {
+            Some(directions) => {
+                let spacing = vec3(
+                    directions.x.magnitude(),
+                    directions.y.magnitude(),
+                    directions.z.magnitude(),
+                );
+                (
+                    spacing,
+                    Mat3::from_cols(
+                        directions.x / spacing.x,
+                        directions.y / spacing.y,
+                        directions.z / spacing.z,
+                    ),
+                )
+            }
+            None => {
+                let spacing = spacings
+                    .map(|s| vec3(valid_spacing(s.x), valid_spacing(s.y), valid_spacing(s.z)))
+                    .unwrap_or(vec3(1.0, 1.0, 1.0));
+                (spacing, Mat3::identity())
+            }
+        };
+        Ok(Self::new(
+            "nrrd",
+            sizes,
+            values,
+            spacing,
+            origin,
+            orientation,
+        ))
+    }
+
+    ///
+    /// Combines the given DICOM files, each containing a single slice of a series, into a volume.
+    /// The slices are sorted by their position along the slice normal, or by the instance number if the position is missing,
+    /// and the rescale slope and intercept are applied so the values are for example in Hounsfield units for CT scans.
+    /// Only uncompressed little endian transfer syntaxes with 8 or 16 bit single channel pixel data are supported.
+    ///
+    #[cfg(feature = "dicom")]
+    #[cfg_attr(docsrs, doc(feature = "dicom"))]
+    pub fn from_dicom_series(files: &[&[u8]]) -> Result<Self, RendererError> {
+        let error = |message: &str| RendererError::InvalidVolumeFile(message.to_string());
+        let mut slices = files
+            .iter()
+            .map(|bytes| dicom::DicomSlice::parse(bytes))
+            .collect::<Result<Vec<_>, _>>()?;
+        let first = slices.first().ok_or_else(|| error("the series is empty"))?;
+        let (rows, columns) = (first.rows, first.columns);
+        if slices
+            .iter()
+            .any(|slice| slice.rows != rows || slice.columns != columns)
+        {
+            Err(error("the slices do not have the same size"))?;
+        }
+        let [row_direction, column_direction] = first
+            .orientation
+            .unwrap_or([vec3(1.0, 0.0, 0.0), vec3(0.0, 1.0, 0.0)]);
+        let normal = row_direction.cross(column_direction).normalize();
+        if slices.iter().all(|slice| slice.position.is_some()) {
+            slices.sort_by(|a, b| {
+                normal
+                    .dot(a.position.unwrap())
+                    .partial_cmp(&normal.dot(b.position.unwrap()))
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            });
+        } else {
+            slices.sort_by_key(|slice| slice.instance_number);
+        }
+
+        let first = &slices[0];
+        let slice_spacing = match (slices.get(1).and_then(|s| s.position), first.position) {
+            (Some(second), Some(first)) => normal.dot(second - first).abs(),
+            _ => first.slice_thickness,
+        };
+        let spacing = vec3(
+            first.pixel_spacing[1],
+            first.pixel_spacing[0],
+            valid_spacing(slice_spacing),
+        );
+        let origin = first.position.unwrap_or(vec3(0.0, 0.0, 0.0));
+        let values = slices
+            .iter()
+            .flat_map(|slice| slice.values())
+            .collect::<Vec<_>>();
+        Ok(Self::new(
+            "dicom",
+            [columns, rows, slices.len()],
+            values,
+            spacing,
+            origin,
+            Mat3::from_cols(row_direction, column_direction, normal),
+        ))
+    }
+
+    fn new(
+        name: &str,
+        sizes: [usize; 3],
+        values: Vec<f32>,
+        spacing: Vec3,
+        origin: Vec3,
+        orientation: Mat3,
+    ) -> Self {
+        let count = sizes[0] * sizes[1] * sizes[2];
+        let (min, max) = values
+            .iter()
+            .take(count)
+            .fold((f32::MAX, f32::MIN), |(min, max), v| {
+                (min.min(*v), max.max(*v))
+            });
+        let range = (max - min).max(f32::EPSILON);
+        Self {
+            voxels: CpuTexture3D {
+                name: name.to_string(),
+                data: TextureData::RF32(
+                    values
+                        .into_iter()
+                        .take(count)
+                        .map(|v| (v - min) / range)
+                        .collect(),
+                ),
+                width: sizes[0] as u32,
+                height: sizes[1] as u32,
+                depth: sizes[2] as u32,
+                ..Default::default()
+            },
+            value_range: [min, max],
+            spacing,
+            origin,
+            orientation,
+        }
+    }
+}
+
+fn valid_spacing(spacing: f32) -> f32 {
+    if spacing.is_finite() && spacing > 0.0 {
+        spacing
+    } else {
+        1.0
+    }
+}
+
+fn parse_numbers<T: std::str::FromStr>(text: &str) -> Option<Vec<T>> {
+    text.split_whitespace().map(|t| t.parse().ok()).collect()
+}
+
+fn parse_vector(text: &str) -> Option<Vec3> {
+    let numbers = text
+        .trim()
+        .strip_prefix('(')?
+        .trim_end_matches(')')
+        .split(',')
+        .map(|t| t.trim().parse::<f32>().ok())
+        .collect::<Option<Vec<_>>>()?;
+    if numbers.len() == 3 {
+        Some(vec3(numbers[0], numbers[1], numbers[2]))
+    } else {
+        None
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum NrrdType {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    F32,
+    F64,
+}
+
+impl NrrdType {
+    fn parse(name: &str) -> Option<Self> {
+        Some(match name {
+            "signed char" | "int8" | "int8_t" => Self::I8,
+            "uchar" | "unsigned char" | "uint8" | "uint8_t" => Self::U8,
+            "short" | "short int" | "signed short" | "signed short int" | "int16" | "int16_t" => {
+                Self::I16
+            }
+            "ushort" | "unsigned short" | "unsigned short int" | "uint16" | "uint16_t" => Self::U16,
+            "int" | "signed int" | "int32" | "int32_t" => Self::I32,
+            "uint" | "unsigned int" | "uint32" | "uint32_t" => Self::U32,
+            "float" => Self::F32,
+            "double" => Self::F64,
+            _ => None?,
+        })
+    }
+
+    fn size(&self) -> usize {
+        match self {
+            Self::I8 | Self::U8 => 1,
+            Self::I16 | Self::U16 => 2,
+            Self::I32 | Self::U32 | Self::F32 => 4,
+            Self::F64 => 8,
+        }
+    }
+
+    fn read(&self, bytes: &[u8], big_endian: bool) -> f32 {
+        macro_rules! read {
+            ($t:ty) => {{
+                let bytes = bytes.try_into().unwrap();
+                (if big_endian {
+                    <$t>::from_be_bytes(bytes)
+                } else {
+                    <$t>::from_le_bytes(bytes)
+                }) as f32
+            }};
+        }
+        match self {
+            Self::I8 => bytes[0] as i8 as f32,
+            Self::U8 => bytes[0] as f32,
+            Self::I16 => read!(i16),
+            Self::U16 => read!(u16),
+            Self::I32 => read!(i32),
+            Self::U32 => read!(u32),
+            Self::F32 => read!(f32),
+            Self::F64 => read!(f64),
+        }
+    }
+}
+
+#[cfg(feature = "dicom")]
+mod dicom {
+    use crate::renderer::*;
+
+    const UNDEFINED_LENGTH: u32 = 0xFFFFFFFF;
+
+    pub struct DicomSlice<'a> {
+        pub rows: usize,
+        pub columns: usize,
+        pub pixel_spacing: [f32; 2],
+        pub slice_thickness: f32,
+        pub position: Option<Vec3>,
+        pub orientation: Option<[Vec3; 2]>,
+        pub instance_number: i32,
+        bits_allocated: u16,
+        signed: bool,
+        slope: f32,
+        intercept: f32,
+        pixel_data: &'a [u8],
+    }
+
+    impl<'a> DicomSlice<'a> {
+        pub fn parse(bytes: &'a [u8]) -> Result<Self, RendererError> {
+            let error = |message: &str| RendererError::InvalidVolumeFile(message.to_string());
+            let mut offset = if bytes.len() >= 132 && &bytes[128..132] == b"DICM" {
+                132
+            } else {
+                0
+            };
+            let mut slice = Self {
+                rows: 0,
+                columns: 0,
+                pixel_spacing: [1.0, 1.0],
+                slice_thickness: 1.0,
+                position: None,
+                orientation: None,
+                instance_number: 0,
+                bits_allocated: 16,
+                signed: false,
+                slope: 1.0,
+                intercept: 0.0,
+                pixel_data: &[],
+            };
+            let mut explicit = offset > 0;
+            let mut samples_per_pixel = 1;
+            while offset < bytes.len() {
+                // The file meta information is always explicit VR little endian
+                let group = read_u16(bytes, offset)?;
+                let element = read_element(bytes, offset, explicit || group == 0x0002)?;
+                offset = element.next_offset;
+                let value = element.value;
+                match element.tag {
+                    (0x0002, 0x0010) => match text(value).as_str() {
+                        "1.2.840.10008.1.2" => explicit = false,
+                        "1.2.840.10008.1.2.1" => explicit = true,
+                        _ => Err(error(
+                            "only uncompressed little endian transfer syntaxes are supported",
+                        ))?,
+                    },
+                    (0x0018, 0x0050) => {
+                        slice.slice_thickness = decimals(value).first().copied().unwrap_or(1.0)
+                    }
+                    (0x0020, 0x0013) => {
+                        slice.instance_number = text(value).parse().unwrap_or(0);
+                    }
+                    (0x0020, 0x0032) => {
+                        let p = decimals(value);
+                        if p.len() == 3 {
+                            slice.position = Some(vec3(p[0], p[1], p[2]));
+                        }
+                    }
+                    (0x0020, 0x0037) => {
+                        let o = decimals(value);
+                        if o.len() == 6 {
+                            slice.orientation =
+                                Some([vec3(o[0], o[1], o[2]), vec3(o[3], o[4], o[5])]);
+                        }
+                    }
+                    (0x0028, 0x0002) => samples_per_pixel = read_u16(value, 0)?,
+                    (0x0028, 0x0010) => slice.rows = read_u16(value, 0)? as usize,
+                    (0x0028, 0x0011) => slice.columns = read_u16(value, 0)? as usize,
+                    (0x0028, 0x0030) => {
+                        let s = decimals(value);
+                        if s.len() == 2 {
+                            slice.pixel_spacing = [s[0], s[1]];
+                        }
+                    }
+                    (0x0028, 0x0100) => slice.bits_allocated = read_u16(value, 0)?,
+                    (0x0028, 0x0103) => slice.signed = read_u16(value, 0)? == 1,
+                    (0x0028, 0x1052) => {
+                        slice.intercept = decimals(value).first().copied().unwrap_or(0.0)
+                    }
+                    (0x0028, 0x1053) => {
+                        slice.slope = decimals(value).first().copied().unwrap_or(1.0)
+                    }
+                    (0x7FE0, 0x0010) => {
+                        if element.undefined_length {
+                            Err(error("encapsulated pixel data is not supported"))?;
+                        }
+                        slice.pixel_data = value;
+                        break;
+                    }
+                    _ => {}
+                }
+            }
+            if samples_per_pixel != 1 {
+                Err(error("only single channel pixel data is supported"))?;
+            }
+            if slice.bits_allocated != 8 && slice.bits_allocated != 16 {
+                Err(error("only 8 and 16 bit pixel data is supported"))?;
+            }
+            if slice.pixel_data.len()
+                < slice.rows * slice.columns * slice.bits_allocated as usize / 8
+            {
+                Err(error("missing pixel data"))?;
+            }
+            Ok(slice)
+        }
+
+        pub fn values(&self) -> impl Iterator<Item = f32> + '_ {
+            let count = self.rows * self.columns;
+            let bytes_per_pixel = self.bits_allocated as usize / 8;
+            self.pixel_data
+                .chunks_exact(bytes_per_pixel)
+                .take(count)
+                .map(move |bytes| {
+                    let value = match (bytes_per_pixel, self.signed) {
+                        (1, false) => bytes[0] as f32,
+                        (1, true) => bytes[0] as i8 as f32,
+                        (_, false) => u16::from_le_bytes([bytes[0], bytes[1]]) as f32,
+                        (_, true) => i16::from_le_bytes([bytes[0], bytes[1]]) as f32,
+                    };
+                    self.slope * value + self.intercept
+                })
+        }
+    }
+
+    struct Element<'a> {
+        tag: (u16, u16),
+        value: &'a [u8],
+        undefined_length: bool,
+        next_offset: usize,
+    }
+
+    fn read_element(bytes: &[u8], offset: usize, explicit: bool) -> Result<Element, RendererError> {
+        let tag = (read_u16(bytes, offset)?, read_u16(bytes, offset + 2)?);
+        let (length, value_offset) = if explicit && tag.0 != 0xFFFE {
+            let vr = bytes
+                .get(offset + 4..offset + 6)
+                .ok_or_else(unexpected_end)?;
+            match vr {
+                b"OB" | b"OD" | b"OF" | b"OL" | b"OV" | b"OW" | b"SQ" | b"SV" | b"UC" | b"UN"
+                | b"UR" | b"UT" | b"UV" => (read_u32(bytes, offset + 8)?, offset + 12),
+                _ => (read_u16(bytes, offset + 6)? as u32, offset + 8),
+            }
+        } else {
+            (read_u32(bytes, offset + 4)?, offset + 8)
+        };
+        if length == UNDEFINED_LENGTH {
+            let next_offset = skip_sequence(bytes, value_offset, explicit)?;
+            Ok(Element {
+                tag,
+                value: &bytes[value_offset..next_offset],
+                undefined_length: true,
+                next_offset,
+            })
+        } else {
+            let next_offset = value_offset + length as usize;
+            Ok(Element {
+                tag,
+                value: bytes
+                    .get(value_offset..next_offset)
+                    .ok_or_else(unexpected_end)?,
+                undefined_length: false,
+                next_offset,
+            })
+        }
+    }
+
+    // Skips the items of a sequence (or encapsulated pixel data) with undefined length and returns the offset after the sequence delimitation item.
+    fn skip_sequence(
+        bytes: &[u8],
+        mut offset: usize,
+        explicit: bool,
+    ) -> Result<usize, RendererError> {
+        loop {
+            let tag = (read_u16(bytes, offset)?, read_u16(bytes, offset + 2)?);
+            let length = read_u32(bytes, offset + 4)?;
+            offset += 8;
+            match tag {
+                (0xFFFE, 0xE0DD) => return Ok(offset),
+                (0xFFFE, 0xE000) if length == UNDEFINED_LENGTH => loop {
+                    if (read_u16(bytes, offset)?, read_u16(bytes, offset + 2)?) == (0xFFFE, 0xE00D)
+                    {
+                        offset += 8;
+                        break;
+                    }
+                    offset = read_element(bytes, offset, explicit)?.next_offset;
+                },
+                (0xFFFE, 0xE000) => offset += length as usize,
+                _ => Err(RendererError::InvalidVolumeFile(
+                    "invalid sequence item".to_string(),
+                ))?,
+            }
+        }
+    }
+
+    fn unexpected_end() -> RendererError {
+        RendererError::InvalidVolumeFile("unexpected end of file".to_string())
+    }
+
+    fn read_u16(bytes: &[u8], offset: usize) -> Result<u16, RendererError> {
+        bytes
+            .get(offset..offset + 2)
+            .map(|b| u16::from_le_bytes([b[0], b[1]]))
+            .ok_or_else(unexpected_end)
+    }
+
+    fn read_u32(bytes: &[u8], offset: usize) -> Result<u32, RendererError> {
+        bytes
+            .get(offset..offset + 4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .ok_or_else(unexpected_end)
+    }
+
+    fn text(value: &[u8]) -> String {
+        String::from_utf8_lossy(value)
+            .trim_matches(|c: char| c == '\0' || c.is_whitespace())
+            .to_string()
+    }
+
+    fn decimals(value: &[u8]) -> Vec<f32> {
+        text(value)
+            .split('\\')
+            .filter_map(|t| t.trim().parse().ok())
+            .collect()
+    }
+}
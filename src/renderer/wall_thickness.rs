@@ -0,0 +1,55 @@
+use crate::renderer::*;
+
+///
+/// Estimates the wall thickness at each vertex of the given closed mesh by casting a ray from the vertex into the mesh, ie. in the opposite direction of the normal,
+/// and measuring the distance to the closest triangle hit by the ray. Vertices where the ray does not hit the mesh are given an infinite thickness.
+/// The normals are computed if the mesh does not contain normals.
+///
+/// The result can be visualised using [wall_thickness_colors], for example to find walls which are too thin to be manufactured.
+///
+pub fn wall_thickness(cpu_mesh: &CpuMesh) -> Vec<f32> {
+    let positions = cpu_mesh.positions.to_f32();
+    let normals = match cpu_mesh.normals {
+        Some(ref normals) => normals.clone(),
+        None => {
+            let mut mesh = cpu_mesh.clone();
+            mesh.compute_normals();
+            mesh.normals.unwrap_or_default()
+        }
+    };
+    let bvh = Bvh::new(cpu_mesh);
+    let size = cpu_mesh.compute_aabb().size();
+    let offset = 1e-5 * size.x.max(size.y).max(size.z);
+
+    positions
+        .iter()
+        .zip(normals.iter())
+        .map(|(position, normal)| {
+            let direction = -normal.normalize();
+            bvh.intersect(&Ray::new(*position + offset * direction, direction))
+                .map(|hit| hit.t + offset)
+                .unwrap_or(f32::INFINITY)
+        })
+        .collect()
+}
+
+///
+/// Returns a color for each of the given wall thicknesses, for example computed by [wall_thickness], which can be used as the vertex colors of a [CpuMesh]
+/// and rendered using a [ColorMaterial] or [PhysicalMaterial].
+/// Thicknesses below the minimum thickness are red, and thicknesses between the minimum thickness and twice the minimum thickness
+/// are colored with a gradient from yellow to green, while thicker walls are green.
+///
+pub fn wall_thickness_colors(thickness: &[f32], min_thickness: f32) -> Vec<Srgba> {
+    let min_thickness = min_thickness.max(f32::EPSILON);
+    thickness
+        .iter()
+        .map(|t| {
+            if *t < min_thickness {
+                Srgba::RED
+            } else {
+                let a = ((t - min_thickness) / min_thickness).min(1.0);
+                Srgba::new_opaque((255.0 * (1.0 - a)) as u8, 220, 0)
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,274 @@
+//!
+//! Utilities for regression testing of rendering, ie. rendering to an offscreen render target, reading back the result
+//! and comparing it against a reference image with a perceptual tolerance.
+//!
+//! A typical test renders an image using [render_offscreen] with a [HeadlessContext](crate::HeadlessContext)
+//! and then compares the image against a reference PNG file using [assert_matches_reference].
+//! If the reference file does not exist or the environment variable `THREE_D_UPDATE_REFERENCES` is set, the reference file is written instead.
+//!
+
+use crate::core::*;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+///
+/// Error in the [test](crate::test) module.
+///
+#[derive(Error, Debug)]
+#[allow(missing_docs)]
+pub enum TestError {
+    #[error("failed to load or save image")]
+    Image(#[from] image::ImageError),
+    #[error("the size of the images does not match, {0}x{1} and {2}x{3}")]
+    SizeMismatch(u32, u32, u32, u32),
+}
+
+///
+/// An 8-bit RGBA image where the first pixel is the top left pixel, which is the same order as returned by [RenderTarget::read_color].
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TestImage {
+    /// The width in pixels.
+    pub width: u32,
+    /// The height in pixels.
+    pub height: u32,
+    /// The pixels, row by row starting from the top.
+    pub pixels: Vec<[u8; 4]>,
+}
+
+impl TestImage {
+    ///
+    /// Loads an image from a PNG file.
+    ///
+    pub fn from_png_file(path: impl AsRef<Path>) -> Result<Self, TestError> {
+        let image = image::open(path)?.to_rgba8();
+        Ok(Self {
+            width: image.width(),
+            height: image.height(),
+            pixels: image.pixels().map(|p| p.0).collect(),
+        })
+    }
+
+    ///
+    /// Saves the image as a PNG file.
+    ///
+    pub fn save_png_file(&self, path: impl AsRef<Path>) -> Result<(), TestError> {
+        image::save_buffer(
+            path,
+            &self.pixels.iter().flatten().copied().collect::<Vec<_>>(),
+            self.width,
+            self.height,
+            image::ColorType::Rgba8,
+        )?;
+        Ok(())
+    }
+}
+
+impl From<&crate::renderer::ReferenceRasterizer> for TestImage {
+    fn from(rasterizer: &crate::renderer::ReferenceRasterizer) -> Self {
+        Self {
+            width: rasterizer.width(),
+            height: rasterizer.height(),
+            pixels: rasterizer.color().to_vec(),
+        }
+    }
+}
+
+///
+/// Renders into an offscreen render target with an 8-bit RGBA color texture and a depth texture of the given size and reads back the result.
+/// The render target is cleared to transparent black and maximum depth before the `render` closure is called.
+///
+pub fn render_offscreen(
+    context: &Context,
+    width: u32,
+    height: u32,
+    render: impl FnOnce(&RenderTarget),
+) -> TestImage {
+    let mut color_texture = Texture2D::new_empty::<[u8; 4]>(
+        context,
+        width,
+        height,
+        Interpolation::Nearest,
+        Interpolation::Nearest,
+        None,
+        Wrapping::ClampToEdge,
+        Wrapping::ClampToEdge,
+    );
+    let mut depth_texture = DepthTexture2D::new::<f32>(
+        context,
+        width,
+        height,
+        Wrapping::ClampToEdge,
+        Wrapping::ClampToEdge,
+    );
+    let target = RenderTarget::new(
+        color_texture.as_color_target(None),
+        depth_texture.as_depth_target(),
+    );
+    target.clear(ClearState::color_and_depth(0.0, 0.0, 0.0, 0.0, 1.0));
+    render(&target);
+    TestImage {
+        width,
+        height,
+        pixels: target.read_color(),
+    }
+}
+
+///
+/// The tolerance used when comparing two images.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tolerance {
+    /// The perceptual color difference in the range `[0, 1]` between two pixels where they are considered different.
+    pub pixel_threshold: f32,
+    /// The fraction of the pixels in the range `[0, 1]` that are allowed to be different.
+    pub max_differing_pixels: f32,
+}
+
+impl Tolerance {
+    ///
+    /// A tolerance where the images has to be exactly equal.
+    ///
+    pub const EXACT: Self = Self {
+        pixel_threshold: 0.0,
+        max_differing_pixels: 0.0,
+    };
+}
+
+impl Default for Tolerance {
+    fn default() -> Self {
+        Self {
+            pixel_threshold: 0.1,
+            max_differing_pixels: 0.001,
+        }
+    }
+}
+
+///
+/// The result of comparing two images, see [compare].
+///
+#[derive(Clone, Debug)]
+pub struct Comparison {
+    /// The number of pixels where the perceptual difference is above the pixel threshold of the tolerance.
+    pub differing_pixels: usize,
+    /// The largest perceptual difference in the range `[0, 1]` of any pixel.
+    pub max_difference: f32,
+    /// An image where the differing pixels are red and the rest of the pixels are a faded version of the expected image.
+    pub difference_image: TestImage,
+    /// Whether or not the images are equal within the tolerance.
+    pub passed: bool,
+}
+
+///
+/// Compares two images of the same size using a perceptual color difference (the YIQ color space difference used by for example pixelmatch),
+/// so small differences which are hard to see, for example caused by different GPU drivers, can be tolerated.
+///
+pub fn compare(
+    actual: &TestImage,
+    expected: &TestImage,
+    tolerance: Tolerance,
+) -> Result<Comparison, TestError> {
+    if actual.width != expected.width || actual.height != expected.height {
+        Err(TestError::SizeMismatch(
+            actual.width,
+            actual.height,
+            expected.width,
+            expected.height,
+        ))?;
+    }
+    let mut differing_pixels = 0;
+    let mut max_difference = 0.0f32;
+    let mut difference_pixels = Vec::with_capacity(expected.pixels.len());
+    for (a, e) in actual.pixels.iter().zip(expected.pixels.iter()) {
+        let difference = perceptual_difference(*a, *e);
+        max_difference = max_difference.max(difference);
+        if difference > tolerance.pixel_threshold {
+            differing_pixels += 1;
+            difference_pixels.push([255, 0, 0, 255]);
+        } else {
+            let gray = (255.0 - 0.1 * (255.0 - luminance(*e))) as u8;
+            difference_pixels.push([gray, gray, gray, 255]);
+        }
+    }
+    let pixel_count = expected.pixels.len().max(1);
+    Ok(Comparison {
+        differing_pixels,
+        max_difference,
+        difference_image: TestImage {
+            width: expected.width,
+            height: expected.height,
+            pixels: difference_pixels,
+        },
+        passed: differing_pixels as f32 <= tolerance.max_differing_pixels * pixel_count as f32,
+    })
+}
+
+///
+/// Compares the image against the reference PNG file at the given path and panics if they are not equal within the tolerance.
+/// On failure, the actual image and the difference image are saved next to the reference file with the extensions `.actual.png` and `.diff.png`.
+///
+/// If the reference file does not exist or the environment variable `THREE_D_UPDATE_REFERENCES` is set, the image is saved as the reference instead.
+///
+pub fn assert_matches_reference(actual: &TestImage, path: impl AsRef<Path>, tolerance: Tolerance) {
+    let path = path.as_ref();
+    if !path.exists() || std::env::var_os("THREE_D_UPDATE_REFERENCES").is_some() {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).ok();
+        }
+        actual
+            .save_png_file(path)
+            .unwrap_or_else(|e| panic!("failed to save reference image {:?}: {}", path, e));
+        return;
+    }
+    let expected = TestImage::from_png_file(path)
+        .unwrap_or_else(|e| panic!("failed to load reference image {:?}: {}", path, e));
+    let comparison = compare(actual, &expected, tolerance)
+        .unwrap_or_else(|e| panic!("failed to compare with reference image {:?}: {}", path, e));
+    if !comparison.passed {
+        actual.save_png_file(with_suffix(path, "actual")).ok();
+        comparison
+            .difference_image
+            .save_png_file(with_suffix(path, "diff"))
+            .ok();
+        panic!(
+            "the image does not match the reference image {:?}, {} of {} pixels differ (maximum difference {})",
+            path,
+            comparison.differing_pixels,
+            expected.pixels.len(),
+            comparison.max_difference
+        );
+    }
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    path.with_extension(format!("{}.png", suffix))
+}
+
+fn luminance(color: [u8; 4]) -> f32 {
+    let [r, g, b] = blend_with_white(color);
+    0.29889531 * r + 0.58662247 * g + 0.11448223 * b
+}
+
+fn blend_with_white(color: [u8; 4]) -> [f32; 3] {
+    let a = color[3] as f32 / 255.0;
+    [0, 1, 2].map(|i| 255.0 + (color[i] as f32 - 255.0) * a)
+}
+
+fn perceptual_difference(a: [u8; 4], b: [u8; 4]) -> f32 {
+    if a == b {
+        return 0.0;
+    }
+    let yiq = |color: [u8; 4]| {
+        let [r, g, b] = blend_with_white(color);
+        (
+            0.29889531 * r + 0.58662247 * g + 0.11448223 * b,
+            0.59597799 * r - 0.27417610 * g - 0.32180189 * b,
+            0.21147017 * r - 0.52261711 * g + 0.31114694 * b,
+        )
+    };
+    let (y0, i0, q0) = yiq(a);
+    let (y1, i1, q1) = yiq(b);
+    let delta = 0.5053 * (y0 - y1).powi(2) + 0.299 * (i0 - i1).powi(2) + 0.1957 * (q0 - q1).powi(2);
+    // The maximum possible delta is 35215
+    (delta / 35215.0).sqrt().min(1.0)
+}
@@ -18,3 +18,15 @@ pub use winit_window::*;
 mod headless;
 #[cfg(all(feature = "headless", not(target_arch = "wasm32")))]
 pub use headless::*;
+
+#[cfg(all(feature = "renderdoc", not(target_arch = "wasm32")))]
+#[cfg_attr(docsrs, doc(feature = "renderdoc"))]
+mod render_doc;
+#[cfg(all(feature = "renderdoc", not(target_arch = "wasm32")))]
+pub use render_doc::*;
+
+#[cfg(all(feature = "asset-cache", target_arch = "wasm32"))]
+#[cfg_attr(docsrs, doc(feature = "asset-cache"))]
+mod asset_cache;
+#[cfg(all(feature = "asset-cache", target_arch = "wasm32"))]
+pub use asset_cache::*;
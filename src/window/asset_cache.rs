@@ -0,0 +1,245 @@
+use js_sys::{Object, Promise, Reflect, Uint8Array};
+use thiserror::Error;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{IdbDatabase, IdbObjectStore, IdbRequest, IdbTransactionMode, Request, Response};
+
+///
+/// Error associated with an asset cache.
+///
+#[derive(Error, Debug)]
+#[allow(missing_docs)]
+pub enum AssetCacheError {
+    #[error("IndexedDB is not available")]
+    Unavailable,
+    #[error("IndexedDB request failed: {0}")]
+    Database(String),
+    #[error("failed to fetch {0}: {1}")]
+    Fetch(String, String),
+}
+
+const STORE_NAME: &str = "assets";
+
+///
+/// A cache of downloaded assets stored in the IndexedDB of the browser, so large assets are not downloaded again each time the page is loaded.
+/// Can only be used on web, not on native.
+///
+/// Each time an asset is requested, the server is asked whether the cached asset is still valid using the `ETag` and `Last-Modified` headers of the cached response.
+/// If the server responds that the asset is not modified, the cached asset is used, otherwise the asset is downloaded and the cache is updated.
+/// If the server cannot be reached, the cached asset is used if available.
+/// Note that for assets on another origin, the server must expose the `ETag` and `Last-Modified` headers and allow the `If-None-Match` and `If-Modified-Since` headers using CORS.
+///
+pub struct AssetCache {
+    database: IdbDatabase,
+}
+
+impl AssetCache {
+    ///
+    /// Opens the cache with the given name, which is created if it does not exist.
+    ///
+    pub async fn open(name: &str) -> Result<Self, AssetCacheError> {
+        let factory = web_sys::window()
+            .ok_or(AssetCacheError::Unavailable)?
+            .indexed_db()
+            .map_err(|_| AssetCacheError::Unavailable)?
+            .ok_or(AssetCacheError::Unavailable)?;
+        let request = factory.open_with_u32(name, 1).map_err(database_error)?;
+        let upgrade_request = request.clone();
+        request.set_onupgradeneeded(Some(
+            Closure::once_into_js(move || {
+                if let Ok(database) = upgrade_request.result() {
+                    let _ = database
+                        .unchecked_into::<IdbDatabase>()
+                        .create_object_store(STORE_NAME);
+                }
+            })
+            .unchecked_ref(),
+        ));
+        let database = request_result(&request).await?.unchecked_into();
+        Ok(Self { database })
+    }
+
+    ///
+    /// Loads the assets at the given urls, either from the cache or by downloading them, and returns them as raw assets which can be deserialized, for example
+    /// `cache.load(&["scene.gltf", "scene.bin"]).await?.deserialize("scene.gltf")`.
+    /// Note that all files needed to deserialize an asset, for example the binary buffers and textures of a glTF file, must be given.
+    ///
+    pub async fn load(
+        &self,
+        urls: &[impl AsRef<str>],
+    ) -> Result<three_d_asset::io::RawAssets, AssetCacheError> {
+        let mut raw_assets = three_d_asset::io::RawAssets::new();
+        for url in urls {
+            let bytes = self.fetch(url.as_ref()).await?;
+            raw_assets.insert(url.as_ref(), bytes);
+        }
+        Ok(raw_assets)
+    }
+
+    ///
+    /// Returns the content of the asset at the given url, either from the cache or by downloading it.
+    ///
+    pub async fn fetch(&self, url: &str) -> Result<Vec<u8>, AssetCacheError> {
+        let cached = self.get(url).await?;
+        let request = Request::new_with_str(url).map_err(|e| fetch_error(url, e))?;
+        if let Some(entry) = &cached {
+            if let Some(etag) = string_property(entry, "etag") {
+                let _ = request.headers().set("If-None-Match", &etag);
+            }
+            if let Some(last_modified) = string_property(entry, "lastModified") {
+                let _ = request.headers().set("If-Modified-Since", &last_modified);
+            }
+        }
+        let window = web_sys::window().ok_or(AssetCacheError::Unavailable)?;
+        let response: Response = match JsFuture::from(window.fetch_with_request(&request)).await {
+            Ok(response) => response.unchecked_into(),
+            Err(e) => {
+                // Offline, so use the cached asset if there is one
+                return cached
+                    .map(|entry| bytes_property(&entry))
+                    .ok_or_else(|| fetch_error(url, e));
+            }
+        };
+        if response.status() == 304 {
+            if let Some(entry) = cached {
+                return Ok(bytes_property(&entry));
+            }
+        }
+        if !response.ok() {
+            return Err(AssetCacheError::Fetch(
+                url.to_owned(),
+                format!("{} {}", response.status(), response.status_text()),
+            ));
+        }
+        let buffer = JsFuture::from(response.array_buffer().map_err(|e| fetch_error(url, e))?)
+            .await
+            .map_err(|e| fetch_error(url, e))?;
+        let bytes = Uint8Array::new(&buffer);
+
+        let headers = response.headers();
+        let etag = headers.get("ETag").ok().flatten();
+        let last_modified = headers.get("Last-Modified").ok().flatten();
+        if etag.is_some() || last_modified.is_some() {
+            let entry = Object::new();
+            let _ = Reflect::set(&entry, &"bytes".into(), &bytes);
+            let _ = Reflect::set(&entry, &"etag".into(), &etag.into());
+            let _ = Reflect::set(&entry, &"lastModified".into(), &last_modified.into());
+            request_result(
+                &self
+                    .store(IdbTransactionMode::Readwrite)?
+                    .put_with_key(&entry, &url.into())
+                    .map_err(database_error)?,
+            )
+            .await?;
+        }
+        Ok(bytes.to_vec())
+    }
+
+    ///
+    /// Removes the asset at the given url from the cache.
+    ///
+    pub async fn remove(&self, url: &str) -> Result<(), AssetCacheError> {
+        request_result(
+            &self
+                .store(IdbTransactionMode::Readwrite)?
+                .delete(&url.into())
+                .map_err(database_error)?,
+        )
+        .await?;
+        Ok(())
+    }
+
+    ///
+    /// Removes all assets from the cache.
+    ///
+    pub async fn clear(&self) -> Result<(), AssetCacheError> {
+        request_result(
+            &self
+                .store(IdbTransactionMode::Readwrite)?
+                .clear()
+                .map_err(database_error)?,
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn get(&self, url: &str) -> Result<Option<JsValue>, AssetCacheError> {
+        let entry = request_result(
+            &self
+                .store(IdbTransactionMode::Readonly)?
+                .get(&url.into())
+                .map_err(database_error)?,
+        )
+        .await?;
+        Ok(if entry.is_undefined() {
+            None
+        } else {
+            Some(entry)
+        })
+    }
+
+    fn store(&self, mode: IdbTransactionMode) -> Result<IdbObjectStore, AssetCacheError> {
+        self.database
+            .transaction_with_str_and_mode(STORE_NAME, mode)
+            .and_then(|transaction| transaction.object_store(STORE_NAME))
+            .map_err(database_error)
+    }
+}
+
+// Waits for the request to finish and returns the result
+async fn request_result(request: &IdbRequest) -> Result<JsValue, AssetCacheError> {
+    let promise = Promise::new(&mut |resolve, reject| {
+        request.set_onsuccess(Some(
+            Closure::once_into_js(move || {
+                let _ = resolve.call0(&JsValue::NULL);
+            })
+            .unchecked_ref(),
+        ));
+        request.set_onerror(Some(
+            Closure::once_into_js(move || {
+                let _ = reject.call0(&JsValue::NULL);
+            })
+            .unchecked_ref(),
+        ));
+    });
+    JsFuture::from(promise).await.map_err(|_| {
+        AssetCacheError::Database(
+            request
+                .error()
+                .ok()
+                .flatten()
+                .map(|e| e.message())
+                .unwrap_or_default(),
+        )
+    })?;
+    request.result().map_err(database_error)
+}
+
+fn string_property(entry: &JsValue, name: &str) -> Option<String> {
+    Reflect::get(entry, &name.into())
+        .ok()
+        .and_then(|value| value.as_string())
+}
+
+fn bytes_property(entry: &JsValue) -> Vec<u8> {
+    Reflect::get(entry, &"bytes".into())
+        .map(|value| Uint8Array::new(&value).to_vec())
+        .unwrap_or_default()
+}
+
+fn message(error: JsValue) -> String {
+    error
+        .dyn_ref::<js_sys::Error>()
+        .map(|e| String::from(e.message()))
+        .or_else(|| error.as_string())
+        .unwrap_or_else(|| format!("{:?}", error))
+}
+
+fn database_error(error: JsValue) -> AssetCacheError {
+    AssetCacheError::Database(message(error))
+}
+
+fn fetch_error(url: &str, error: JsValue) -> AssetCacheError {
+    AssetCacheError::Fetch(url.to_owned(), message(error))
+}
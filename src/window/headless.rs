@@ -1,7 +1,7 @@
 use crate::{Context, CoreError};
 use glutin_029::{
-    dpi::PhysicalSize, event_loop::EventLoop, ContextBuilder, ContextCurrentState, CreationError,
-    NotCurrent, PossiblyCurrent,
+    dpi::PhysicalSize, event_loop::EventLoop, window::WindowBuilder, ContextBuilder,
+    ContextCurrentState, ContextError, CreationError, NotCurrent, PossiblyCurrent, WindowedContext,
 };
 use std::rc::Rc;
 use thiserror::Error;
@@ -25,10 +25,15 @@ pub enum HeadlessError {
 /// For a graphics context associated with a window, see [WindowedContext](crate::WindowedContext).
 /// Can only be created on native, not on web.
 ///
+/// The context derefs to the same [Context] as is created by a [Window](crate::Window), so everything which can be rendered in a window can be rendered offscreen,
+/// for example for rendering screenshots on a server or in continuous integration, see [RenderTarget::read_color_to_image](crate::RenderTarget::read_color_to_image).
+/// On Linux, a surfaceless EGL context is created directly on an EGL device if possible, which requires neither a display nor an event loop, otherwise an OSMesa context, a pbuffer context or finally a context for a hidden window.
+/// On other operating systems, a pbuffer context is created if possible, otherwise a context for a hidden window.
+///
 #[derive(Clone)]
 pub struct HeadlessContext {
     context: Context,
-    _glutin_context: Rc<glutin_029::Context<PossiblyCurrent>>,
+    glutin_context: Rc<GlutinContext>,
 }
 
 impl HeadlessContext {
@@ -37,9 +42,21 @@ impl HeadlessContext {
     ///
     #[allow(unsafe_code)]
     pub fn new() -> Result<Self, HeadlessError> {
-        let cb = ContextBuilder::new();
-        let glutin_context = build_context(cb)?;
-        let glutin_context = unsafe { glutin_context.make_current().map_err(|(_, e)| e)? };
+        #[cfg(target_os = "linux")]
+        if let Some(surfaceless_context) = SurfacelessContext::new() {
+            let context = Context::from_gl_context(std::sync::Arc::new(unsafe {
+                crate::context::Context::from_loader_function(|s| {
+                    surfaceless_context.get_proc_address(s) as *const _
+                })
+            }))?;
+            return Ok(Self {
+                context,
+                glutin_context: Rc::new(GlutinContext::Surfaceless(surfaceless_context)),
+            });
+        }
+
+        let glutin_context = build_context(ContextBuilder::new())?;
+        let glutin_context = unsafe { glutin_context.make_current()? };
         let context = Context::from_gl_context(std::sync::Arc::new(unsafe {
             crate::context::Context::from_loader_function(|s| {
                 glutin_context.get_proc_address(s) as *const _
@@ -47,11 +64,20 @@ impl HeadlessContext {
         }))?;
         Ok(Self {
             context,
-            _glutin_context: Rc::new(glutin_context),
+            glutin_context: Rc::new(glutin_context),
         })
     }
 }
 
+impl Drop for HeadlessContext {
+    fn drop(&mut self) {
+        // The graphics context is destroyed when the last clone is dropped
+        if Rc::strong_count(&self.glutin_context) == 1 {
+            self.context.report_leaks();
+        }
+    }
+}
+
 impl std::ops::Deref for HeadlessContext {
     type Target = Context;
     fn deref(&self) -> &Self::Target {
@@ -59,26 +85,111 @@ impl std::ops::Deref for HeadlessContext {
     }
 }
 
-/*#[cfg(target_os = "linux")]
-fn build_context_surfaceless<T1: ContextCurrentState>(
-    cb: ContextBuilder<T1>,
-    el: &EventLoop<()>,
-) -> Result<glutin_029::Context<NotCurrent>, CreationError> {
-    use glutin_029::platform::unix::HeadlessContextExt;
-    cb.build_surfaceless(&el)
-}*/
+// The different kinds of contexts which can be used as a headless context.
+// The event loop is kept alive as long as the context, since the context might depend on it.
+enum GlutinContext<T: ContextCurrentState = PossiblyCurrent> {
+    #[cfg(target_os = "linux")]
+    Surfaceless(SurfacelessContext),
+    Headless(glutin_029::Context<T>, Option<EventLoop<()>>),
+    HiddenWindow(WindowedContext<T>, EventLoop<()>),
+}
+
+impl GlutinContext<NotCurrent> {
+    #[allow(unsafe_code)]
+    unsafe fn make_current(self) -> Result<GlutinContext, ContextError> {
+        Ok(match self {
+            #[cfg(target_os = "linux")]
+            Self::Surfaceless(context) => GlutinContext::Surfaceless(context),
+            Self::Headless(context, event_loop) => {
+                GlutinContext::Headless(context.make_current().map_err(|(_, e)| e)?, event_loop)
+            }
+            Self::HiddenWindow(context, event_loop) => {
+                GlutinContext::HiddenWindow(context.make_current().map_err(|(_, e)| e)?, event_loop)
+            }
+        })
+    }
+}
+
+impl GlutinContext {
+    fn get_proc_address(&self, address: &str) -> *const core::ffi::c_void {
+        match self {
+            #[cfg(target_os = "linux")]
+            Self::Surfaceless(context) => context.get_proc_address(address),
+            Self::Headless(context, _) => context.get_proc_address(address),
+            Self::HiddenWindow(context, _) => context.get_proc_address(address),
+        }
+    }
+}
+
+// A surfaceless EGL context which is created directly on an EGL device, so it requires neither a display nor an event loop
+#[cfg(target_os = "linux")]
+struct SurfacelessContext {
+    _context: glutin::api::egl::context::PossiblyCurrentContext,
+    display: glutin::api::egl::display::Display,
+}
 
-fn build_context_headless<T1: ContextCurrentState>(
-    cb: ContextBuilder<T1>,
+#[cfg(target_os = "linux")]
+impl SurfacelessContext {
+    #[allow(unsafe_code)]
+    fn new() -> Option<Self> {
+        use glutin::api::egl::{device::Device, display::Display};
+        use glutin::config::{ConfigSurfaceTypes, ConfigTemplateBuilder};
+        use glutin::context::ContextAttributesBuilder;
+        use glutin::prelude::*;
+        Device::query_devices().ok()?.find_map(|device| unsafe {
+            let display = Display::with_device(&device, None).ok()?;
+            let template = ConfigTemplateBuilder::new()
+                .with_surface_type(ConfigSurfaceTypes::empty())
+                .build();
+            let config = display.find_configs(template).ok()?.next()?;
+            let context = display
+                .create_context(&config, &ContextAttributesBuilder::new().build(None))
+                .ok()?
+                .make_current_surfaceless()
+                .ok()?;
+            Some(Self {
+                _context: context,
+                display,
+            })
+        })
+    }
+
+    fn get_proc_address(&self, address: &str) -> *const core::ffi::c_void {
+        use glutin::prelude::*;
+        std::ffi::CString::new(address)
+            .map(|address| self.display.get_proc_address(&address))
+            .unwrap_or(std::ptr::null())
+    }
+}
+
+// Creating an event loop panics if there is no display available, for example on a server, so it is only created if a display is specified
+#[cfg(target_os = "linux")]
+fn new_event_loop() -> Option<EventLoop<()>> {
+    (std::env::var_os("DISPLAY").is_some() || std::env::var_os("WAYLAND_DISPLAY").is_some())
+        .then(EventLoop::new)
+}
+
+fn build_context_headless(
+    cb: ContextBuilder<NotCurrent>,
     el: &EventLoop<()>,
 ) -> Result<glutin_029::Context<NotCurrent>, CreationError> {
     let size_one = PhysicalSize::new(1, 1);
-    cb.build_headless(&el, size_one)
+    cb.build_headless(el, size_one)
+}
+
+fn build_context_hidden_window(
+    cb: ContextBuilder<NotCurrent>,
+    el: &EventLoop<()>,
+) -> Result<WindowedContext<NotCurrent>, CreationError> {
+    let window_builder = WindowBuilder::new()
+        .with_visible(false)
+        .with_inner_size(PhysicalSize::new(1, 1));
+    cb.build_windowed(window_builder, el)
 }
 
 #[cfg(target_os = "linux")]
-fn build_context_osmesa<T1: ContextCurrentState>(
-    cb: ContextBuilder<T1>,
+fn build_context_osmesa(
+    cb: ContextBuilder<NotCurrent>,
 ) -> Result<glutin_029::Context<NotCurrent>, CreationError> {
     use glutin_029::platform::unix::HeadlessContextExt;
     let size_one = PhysicalSize::new(1, 1);
@@ -86,42 +197,39 @@ fn build_context_osmesa<T1: ContextCurrentState>(
 }
 
 #[cfg(target_os = "linux")]
-fn build_context<T1: ContextCurrentState>(
-    cb: ContextBuilder<T1>,
-) -> Result<glutin_029::Context<NotCurrent>, CreationError> {
-    // On unix operating systems, you should always try for surfaceless first,
-    // and if that does not work, headless (pbuffers), and if that too fails,
-    // finally osmesa.
-    //
-    // If willing, you could attempt to use hidden windows instead of os mesa,
-    // but note that you must handle events for the window that come on the
-    // events loop.
-
-    /*
-    let err1 = match build_context_surfaceless(cb.clone(), &el) {
-        Ok(ctx) => return Ok((ctx, el)),
-        Err(err) => err,
-    };*/
-
-    let _err3 = match build_context_osmesa(cb.clone()) {
-        Ok(ctx) => return Ok(ctx),
-        Err(err) => err,
-    };
-
-    let el = EventLoop::new();
+fn build_context(
+    cb: ContextBuilder<NotCurrent>,
+) -> Result<GlutinContext<NotCurrent>, CreationError> {
+    // The surfaceless context is tried before this, see HeadlessContext::new,
+    // so try osmesa first, since it does not require a display, then headless (pbuffers) and finally a hidden window.
+    if let Ok(ctx) = build_context_osmesa(cb.clone()) {
+        return Ok(GlutinContext::Headless(ctx, None));
+    }
 
-    let err2 = match build_context_headless(cb, &el) {
-        Ok(ctx) => return Ok(ctx),
+    let el = new_event_loop().ok_or(CreationError::NotSupported(
+        "no display available for creating a headless context".to_owned(),
+    ))?;
+    let err = match build_context_headless(cb.clone(), &el) {
+        Ok(ctx) => return Ok(GlutinContext::Headless(ctx, Some(el))),
         Err(err) => err,
     };
 
-    Err(err2)
+    build_context_hidden_window(cb, &el)
+        .map(|ctx| GlutinContext::HiddenWindow(ctx, el))
+        .map_err(|_| err)
 }
 
 #[cfg(not(target_os = "linux"))]
-fn build_context<T1: ContextCurrentState>(
-    cb: ContextBuilder<T1>,
-) -> Result<glutin_029::Context<NotCurrent>, CreationError> {
+fn build_context(
+    cb: ContextBuilder<NotCurrent>,
+) -> Result<GlutinContext<NotCurrent>, CreationError> {
     let el = EventLoop::new();
-    build_context_headless(cb.clone(), &el)
+    let err = match build_context_headless(cb.clone(), &el) {
+        Ok(ctx) => return Ok(GlutinContext::Headless(ctx, Some(el))),
+        Err(err) => err,
+    };
+
+    build_context_hidden_window(cb, &el)
+        .map(|ctx| GlutinContext::HiddenWindow(ctx, el))
+        .map_err(|_| err)
 }
@@ -0,0 +1,98 @@
+use renderdoc::{RenderDoc, V141};
+use std::cell::RefCell;
+use std::rc::Rc;
+use thiserror::Error;
+
+///
+/// Error associated with the RenderDoc in-application API.
+///
+#[derive(Error, Debug)]
+#[allow(missing_docs)]
+pub enum RenderDocError {
+    #[error("the RenderDoc in-application API is not available, is the application launched from RenderDoc?")]
+    NotAvailable(#[from] renderdoc::Error),
+}
+
+///
+/// Triggers frame captures in [RenderDoc](https://renderdoc.org/) from within the application using the RenderDoc in-application API.
+/// This makes it possible to capture exactly the frame of interest, for example when a specific key is pressed, instead of relying on the capture hotkey.
+/// Combined with the debug markers which are automatically added around each render pass (see [Context::push_debug_group](crate::Context::push_debug_group)),
+/// the captured frames are easy to navigate in the RenderDoc event browser.
+///
+/// The application has to be launched from RenderDoc, otherwise [RenderDocCapture::new] returns an error.
+/// A clone refers to the same RenderDoc instance as the original.
+/// Can only be used on native, not on web.
+///
+#[derive(Clone)]
+pub struct RenderDocCapture {
+    render_doc: Rc<RefCell<RenderDoc<V141>>>,
+}
+
+impl RenderDocCapture {
+    ///
+    /// Connects to RenderDoc. Returns an error if the application is not launched from RenderDoc.
+    ///
+    pub fn new() -> Result<Self, RenderDocError> {
+        Ok(Self {
+            render_doc: Rc::new(RefCell::new(RenderDoc::new()?)),
+        })
+    }
+
+    ///
+    /// Captures the next frame, ie. the commands between the next two swaps of the buffers of the window.
+    ///
+    pub fn capture_next_frame(&self) {
+        self.render_doc.borrow_mut().trigger_capture();
+    }
+
+    ///
+    /// Captures the given number of frames starting with the next frame.
+    ///
+    pub fn capture_next_frames(&self, frame_count: u32) {
+        self.render_doc
+            .borrow_mut()
+            .trigger_multi_frame_capture(frame_count);
+    }
+
+    ///
+    /// Starts capturing the commands sent to the current graphics context until [RenderDocCapture::end_capture] is called.
+    /// Can be used to capture only a part of a frame or to capture the commands when rendering without a window, for example with a [HeadlessContext](crate::HeadlessContext).
+    ///
+    pub fn start_capture(&self) {
+        self.render_doc
+            .borrow_mut()
+            .start_frame_capture(std::ptr::null::<std::ffi::c_void>(), std::ptr::null());
+    }
+
+    ///
+    /// Ends the capture started by [RenderDocCapture::start_capture].
+    ///
+    pub fn end_capture(&self) {
+        self.render_doc
+            .borrow_mut()
+            .end_frame_capture(std::ptr::null::<std::ffi::c_void>(), std::ptr::null());
+    }
+
+    ///
+    /// Returns whether or not a capture is currently in progress.
+    ///
+    pub fn is_capturing(&self) -> bool {
+        self.render_doc.borrow_mut().is_frame_capturing()
+    }
+
+    ///
+    /// Returns the number of captures made so far.
+    ///
+    pub fn capture_count(&self) -> u32 {
+        self.render_doc.borrow().get_num_captures()
+    }
+
+    ///
+    /// Sets the template for the path of the capture files, for example `captures/my_app` which results in files named `captures/my_app_frame123.rdc`.
+    ///
+    pub fn set_capture_path_template(&self, path_template: impl AsRef<std::path::Path>) {
+        self.render_doc
+            .borrow_mut()
+            .set_capture_file_path_template(path_template.as_ref());
+    }
+}
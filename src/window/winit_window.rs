@@ -17,6 +17,9 @@ pub use frame_input_generator::*;
 mod windowed_context;
 pub use windowed_context::*;
 
+mod render_loop_plugin;
+pub use render_loop_plugin::*;
+
 use thiserror::Error;
 ///
 /// Error associated with a window.
@@ -77,6 +80,8 @@ pub struct Window {
     gl: WindowedContext,
     #[allow(dead_code)]
     maximized: bool,
+    plugins: Vec<Box<dyn RenderLoopPlugin>>,
+    panic_handler: Option<Box<dyn FnMut(&str)>>,
 }
 
 impl Window {
@@ -206,17 +211,83 @@ impl Window {
             #[cfg(target_arch = "wasm32")]
             closure,
             maximized,
+            plugins: Vec::new(),
+            panic_handler: None,
         })
     }
 
+    ///
+    /// Attaches a plugin to the render loop, see [RenderLoopPlugin].
+    /// The plugin is called each frame before and after the `callback` closure given to [Window::render_loop] and when the window is resized.
+    ///
+    pub fn add_plugin(&mut self, plugin: impl RenderLoopPlugin + 'static) {
+        self.plugins.push(Box::new(plugin));
+    }
+
+    ///
+    /// Catches panics in the `callback` closure given to [Window::render_loop] and in the plugins, instead of the application being killed,
+    /// which on web leaves a canvas that silently stopped updating.
+    /// When a panic occurs, the error message is given to the handler, the message is shown on top of the canvas on web and the render loop is stopped gracefully.
+    /// The previously installed panic hook is still called and is restored when the render loop stops.
+    ///
+    /// **Note:** On web, panics abort the program unless the standard library is built with `panic = "unwind"`.
+    /// The handler is still called and the message is still shown in that case, but the program stops afterwards.
+    ///
+    pub fn catch_panics(&mut self, handler: impl FnMut(&str) + 'static) {
+        self.panic_handler = Some(Box::new(handler));
+    }
+
     ///
     /// Start the main render loop which calls the `callback` closure each frame.
     ///
-    pub fn render_loop<F: 'static + FnMut(FrameInput) -> FrameOutput>(self, mut callback: F) {
+    pub fn render_loop<F: 'static + FnMut(FrameInput) -> FrameOutput>(mut self, callback: F) {
         let mut frame_input_generator = FrameInputGenerator::from_winit_window(&self.window);
+        let mut callback = Some(callback);
+        let mut plugins = std::mem::take(&mut self.plugins);
+        let catch_panics = self.panic_handler.is_some();
+        let mut previous_hook = None;
+        if let Some(panic_handler) = self.panic_handler.take() {
+            PANIC_HANDLER.with(|handler| *handler.borrow_mut() = Some(panic_handler));
+            #[cfg(target_arch = "wasm32")]
+            {
+                use winit::platform::web::WindowExtWebSys;
+                PANIC_CANVAS.with(|canvas| *canvas.borrow_mut() = Some(self.window.canvas()));
+            }
+            // The handler is called from the hook, since on web the panic aborts the program before it can be caught
+            let hook: std::sync::Arc<PanicHook> = std::sync::Arc::new(std::panic::take_hook());
+            let chained_hook = hook.clone();
+            std::panic::set_hook(Box::new(move |info| {
+                chained_hook(info);
+                if RENDERING_FRAME.with(|rendering| rendering.replace(false)) {
+                    let message = info.to_string();
+                    PANIC_HANDLER.with(|handler| {
+                        if let Ok(mut handler) = handler.try_borrow_mut() {
+                            if let Some(handler) = handler.as_mut() {
+                                handler(&message);
+                            }
+                        }
+                    });
+                    #[cfg(target_arch = "wasm32")]
+                    PANIC_CANVAS.with(|canvas| {
+                        if let Some(canvas) = canvas.borrow().as_ref() {
+                            show_error_overlay(canvas, &message);
+                        }
+                    });
+                }
+            }));
+            previous_hook = Some(hook);
+        }
         self.event_loop
             .run(move |event, _, control_flow| match event {
                 Event::LoopDestroyed => {
+                    // Drop everything owned by the callback, so any GPU resources that are still alive after this are leaked
+                    drop(callback.take());
+                    plugins.clear();
+                    if let Some(hook) = previous_hook.take() {
+                        restore_panic_hook(hook);
+                    }
+                    // The window is not necessarily dropped when the loop ends, so the leaks are reported here
+                    self.gl.report_leaks();
                     #[cfg(target_arch = "wasm32")]
                     {
                         use wasm_bindgen::JsCast;
@@ -251,8 +322,45 @@ impl Window {
                         });
                     }
 
-                    let frame_input = frame_input_generator.generate(&self.gl);
-                    let frame_output = callback(frame_input);
+                    let frame_callback = match callback.as_mut() {
+                        Some(callback) => callback,
+                        None => return,
+                    };
+                    let mut run_frame = || {
+                        let mut frame_input = frame_input_generator.generate(&self.gl);
+                        for plugin in plugins.iter_mut() {
+                            plugin.pre_frame(&mut frame_input);
+                        }
+                        if plugins.is_empty() {
+                            frame_callback(frame_input)
+                        } else {
+                            let mut frame_output = frame_callback(frame_input.clone());
+                            for plugin in plugins.iter_mut() {
+                                plugin.post_frame(&frame_input, &mut frame_output);
+                            }
+                            frame_output
+                        }
+                    };
+                    let frame_output = if catch_panics {
+                        RENDERING_FRAME.with(|rendering| rendering.set(true));
+                        let result =
+                            std::panic::catch_unwind(std::panic::AssertUnwindSafe(run_frame));
+                        RENDERING_FRAME.with(|rendering| rendering.set(false));
+                        match result {
+                            Ok(frame_output) => frame_output,
+                            Err(_) => {
+                                // The panic handler has already been called from the panic hook
+                                drop(callback.take());
+                                if let Some(hook) = previous_hook.take() {
+                                    restore_panic_hook(hook);
+                                }
+                                *control_flow = ControlFlow::Exit;
+                                return;
+                            }
+                        }
+                    } else {
+                        run_frame()
+                    };
                     if frame_output.exit {
                         *control_flow = ControlFlow::Exit;
                     } else {
@@ -273,9 +381,19 @@ impl Window {
                     match event {
                         WindowEvent::Resized(physical_size) => {
                             self.gl.resize(*physical_size);
+                            let viewport =
+                                Viewport::new_at_origo(physical_size.width, physical_size.height);
+                            for plugin in plugins.iter_mut() {
+                                plugin.on_resize(&self.gl, viewport);
+                            }
                         }
                         WindowEvent::ScaleFactorChanged { new_inner_size, .. } => {
                             self.gl.resize(**new_inner_size);
+                            let viewport =
+                                Viewport::new_at_origo(new_inner_size.width, new_inner_size.height);
+                            for plugin in plugins.iter_mut() {
+                                plugin.on_resize(&self.gl, viewport);
+                            }
                         }
                         WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
                         _ => (),
@@ -317,3 +435,50 @@ impl Window {
         (*self.gl).clone()
     }
 }
+
+type PanicHook = Box<dyn Fn(&std::panic::PanicInfo<'_>) + Sync + Send + 'static>;
+
+thread_local! {
+    static PANIC_HANDLER: std::cell::RefCell<Option<Box<dyn FnMut(&str)>>> = std::cell::RefCell::new(None);
+    static RENDERING_FRAME: std::cell::Cell<bool> = std::cell::Cell::new(false);
+}
+
+#[cfg(target_arch = "wasm32")]
+thread_local! {
+    static PANIC_CANVAS: std::cell::RefCell<Option<web_sys::HtmlCanvasElement>> = std::cell::RefCell::new(None);
+}
+
+fn restore_panic_hook(hook: std::sync::Arc<PanicHook>) {
+    PANIC_HANDLER.with(|handler| handler.borrow_mut().take());
+    #[cfg(target_arch = "wasm32")]
+    PANIC_CANVAS.with(|canvas| canvas.borrow_mut().take());
+    let _ = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| hook(info)));
+}
+
+#[cfg(target_arch = "wasm32")]
+fn show_error_overlay(canvas: &web_sys::HtmlCanvasElement, message: &str) {
+    let (document, parent) = match (canvas.owner_document(), canvas.parent_node()) {
+        (Some(document), Some(parent)) => (document, parent),
+        _ => return,
+    };
+    if let Ok(overlay) = document.create_element("div") {
+        overlay.set_text_content(Some(message));
+        overlay
+            .set_attribute(
+                "style",
+                &format!(
+                    "position: absolute; left: {}px; top: {}px; width: {}px; height: {}px; box-sizing: border-box; overflow: auto; \
+                    padding: 16px; background: rgba(0, 0, 0, 0.8); color: #ff5555; font-family: monospace; white-space: pre-wrap;",
+                    canvas.offset_left(),
+                    canvas.offset_top(),
+                    canvas.offset_width(),
+                    canvas.offset_height()
+                ),
+            )
+            .ok();
+        parent
+            .insert_before(&overlay, canvas.next_sibling().as_ref())
+            .ok();
+    }
+}
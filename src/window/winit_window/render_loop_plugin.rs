@@ -0,0 +1,29 @@
+use super::{FrameInput, FrameOutput};
+use crate::core::{Context, Viewport};
+
+///
+/// A reusable subsystem, for example a profiler, a recorder or a hot-reloader, which is attached to the render loop of a [Window](super::Window) using [Window::add_plugin](super::Window::add_plugin),
+/// instead of having to be called manually from the render loop closure.
+/// All of the hooks have an empty default implementation, so only the needed hooks have to be implemented.
+///
+/// The plugins are called in the order they are added.
+///
+pub trait RenderLoopPlugin {
+    ///
+    /// Called each frame before the render loop closure with the frame input that is afterwards given to the closure.
+    /// The frame input can be modified, for example to add or remove events.
+    ///
+    fn pre_frame(&mut self, _frame_input: &mut FrameInput) {}
+
+    ///
+    /// Called each frame after the render loop closure with the same frame input as the closure and the frame output returned by the closure.
+    /// This is called before the buffers are swapped, so the screen contains what has been rendered this frame.
+    /// The frame output can be modified, for example to request another frame.
+    ///
+    fn post_frame(&mut self, _frame_input: &FrameInput, _frame_output: &mut FrameOutput) {}
+
+    ///
+    /// Called when the window is resized with the new viewport of the window in physical pixels.
+    ///
+    fn on_resize(&mut self, _context: &Context, _viewport: Viewport) {}
+}
@@ -234,3 +234,9 @@ impl std::ops::Deref for WindowedContext {
         &self.context
     }
 }
+
+impl Drop for WindowedContext {
+    fn drop(&mut self) {
+        self.context.report_leaks();
+    }
+}